@@ -0,0 +1,654 @@
+//! Std-blocking abook addressbook client.
+//!
+//! [`AbookClient`] exposes the `addressbook` file of [abook] through
+//! the shared API, as one addressbook named after the file name.
+//! Entries convert to and from vCard (see [`convert`]); writes rebuild
+//! the file the way abook does and replace it atomically (temporary
+//! file then rename), so readers never see a half-written file.
+//!
+//! Card ids derive from the `uid` field, which abook keeps as an
+//! unknown field, or from the tag of the vCard rendering when there is
+//! none. Card and addressbook tags are synthesized from the contents;
+//! the addressbook carries no description nor color.
+//!
+//! [abook]: https://abook.sourceforge.io
+//! [`convert`]: crate::abook::convert
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    abook::convert::{
+        AbookFile, addressbook_from_file, card_from_entry, card_ref_from_entry, entry_card, field,
+        fields_from_contact, paginate,
+    },
+    addressbook::{Addressbook, AddressbookDiff},
+    backend::AddressbookBackend,
+    capabilities::Capabilities,
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef, Contact,
+        ParseContactError,
+        file::{UpdateUidError, update_uid, write_atomic},
+        split_vcf,
+        tag::{content_tag, tag_matches},
+        uid::fresh_card_id,
+    },
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+};
+
+/// Errors surfaced by [`AbookClient`].
+#[derive(Debug, Error)]
+pub enum AbookClientError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Failed to gather randomness for new card id: {0}")]
+    Random(getrandom::Error),
+    #[error("Cannot create nor delete addressbooks of an abook file")]
+    SingleFile,
+    #[error("Addressbook metadata of abook files cannot be changed")]
+    ReadOnlyMetadata,
+    #[error("Addressbook `{0}` not found")]
+    AddressbookNotFound(String),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card body is empty")]
+    EmptyBody,
+    #[error("Card body is not a single vCard")]
+    InvalidCard,
+    #[error("Invalid card: {0}")]
+    ParseCard(ParseContactError),
+    #[error("Card `{card_id}` contents carry the UID of card `{uid_id}`")]
+    UidMismatch { card_id: String, uid_id: String },
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{0}` already exists")]
+    CardAlreadyExists(String),
+    #[error("Card `{card_id}` changed (current tag: {actual})")]
+    PreconditionFailed { card_id: String, actual: String },
+}
+
+impl AbookClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::AddressbookNotFound(_) | Self::CardNotFound(_) => ErrorKind::NotFound,
+            Self::CardAlreadyExists(_) | Self::PreconditionFailed { .. } => ErrorKind::Conflict,
+            Self::SingleFile
+            | Self::ReadOnlyMetadata
+            | Self::InvalidCardId(_)
+            | Self::EmptyBody
+            | Self::InvalidCard
+            | Self::ParseCard(_)
+            | Self::UidMismatch { .. } => ErrorKind::InvalidData,
+            Self::Random(_) => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current tag of the card when the error is a failed
+    /// `if_match` precondition.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::PreconditionFailed { actual, .. } => Some(actual),
+            _ => None,
+        }
+    }
+}
+
+impl From<UpdateUidError> for AbookClientError {
+    fn from(err: UpdateUidError) -> Self {
+        match err {
+            UpdateUidError::Mismatch { card_id, uid_id } => Self::UidMismatch { card_id, uid_id },
+            UpdateUidError::InvalidCardId(card_id) => Self::InvalidCardId(card_id),
+        }
+    }
+}
+
+/// Std-blocking abook addressbook client.
+#[derive(Clone, Debug)]
+pub struct AbookClient {
+    path: PathBuf,
+}
+
+impl AbookClient {
+    /// Builds a client exposing the abook file at `path` (usually
+    /// `~/.abook/addressbook`) as one addressbook, whose id is the file
+    /// name. A missing file reads as an empty addressbook and is
+    /// created on first write.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the file path the client was built with.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the static capabilities of the abook backend.
+    ///
+    /// Tags are synthesized from the contents; there is no sync token
+    /// nor query engine, and no addressbook metadata to write.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            etag: true,
+            ctag: true,
+            sync_token: false,
+            query: false,
+            multiget: true,
+            vcard_versions: Vec::from(["4.0".to_string()]),
+            max_resource_size: None,
+            writable_metadata: Vec::new(),
+        }
+    }
+
+    /// Lists the single addressbook of the file, with the tag of the
+    /// file as `ctag`.
+    pub fn list_addressbooks(&self) -> Result<Vec<Addressbook>, AbookClientError> {
+        let data = read_or_empty(&self.path)?;
+        Ok(Vec::from([addressbook_from_file(&self.id(), &data)]))
+    }
+
+    /// Always fails with [`AbookClientError::SingleFile`]: an abook
+    /// file holds exactly one addressbook.
+    pub fn create_addressbook(
+        &self,
+        _name: &str,
+        _description: Option<&str>,
+        _color: Option<&str>,
+    ) -> Result<String, AbookClientError> {
+        Err(AbookClientError::SingleFile)
+    }
+
+    /// Checks that the addressbook `id` exists. Any metadata change
+    /// fails with [`AbookClientError::ReadOnlyMetadata`]: the name is
+    /// the file name, and there is no description nor color.
+    pub fn update_addressbook(
+        &self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AbookClientError> {
+        self.check_addressbook(id)?;
+
+        if patch.name.is_some() || patch.description.is_some() || patch.color.is_some() {
+            return Err(AbookClientError::ReadOnlyMetadata);
+        }
+
+        Ok(())
+    }
+
+    /// Always fails with [`AbookClientError::SingleFile`]: an abook
+    /// file holds exactly one addressbook.
+    pub fn delete_addressbook(&self, _id: &str) -> Result<(), AbookClientError> {
+        Err(AbookClientError::SingleFile)
+    }
+
+    /// Lists cards inside `addressbook_id`, in file order, applying
+    /// 1-indexed pagination.
+    pub fn list_cards(
+        &self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AbookClientError> {
+        let file = self.load(addressbook_id)?;
+
+        let cards = file
+            .entries
+            .iter()
+            .map(|fields| card_from_entry(addressbook_id, fields))
+            .collect();
+
+        Ok(paginate(cards, page, page_size))
+    }
+
+    /// Lists the id and synthesized tag of every card in
+    /// `addressbook_id`, in file order.
+    pub fn list_card_refs(&self, addressbook_id: &str) -> Result<Vec<CardRef>, AbookClientError> {
+        let file = self.load(addressbook_id)?;
+        Ok(file.entries.iter().map(card_ref_from_entry).collect())
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`,
+    /// evaluated locally over every card.
+    pub fn search_cards(
+        &self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AbookClientError> {
+        let mut cards = self.list_cards(addressbook_id, None, None)?;
+        cards.retain(|card| filter.matches(card));
+        Ok(cards)
+    }
+
+    /// Returns every card of `addressbook_id` as a full
+    /// [`CardChanges`] listing: there is no sync token, so
+    /// `sync_token` is ignored.
+    pub fn sync_cards(
+        &self,
+        addressbook_id: &str,
+        _sync_token: Option<&str>,
+    ) -> Result<CardChanges, AbookClientError> {
+        Ok(CardChanges {
+            cards: self.list_cards(addressbook_id, None, None)?,
+            deleted: Vec::new(),
+            sync_token: None,
+            full: true,
+        })
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub fn get_card(&self, addressbook_id: &str, card_id: &str) -> Result<Card, AbookClientError> {
+        self.list_cards(addressbook_id, None, None)?
+            .into_iter()
+            .find(|card| card.id == card_id)
+            .ok_or_else(|| AbookClientError::CardNotFound(card_id.to_string()))
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id`, reading
+    /// the file once. Ids with no card end up in
+    /// [`CardBatch::missing`].
+    pub fn get_cards(
+        &self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AbookClientError> {
+        let cards = self.list_cards(addressbook_id, None, None)?;
+        let mut batch = CardBatch::default();
+
+        for card_id in card_ids {
+            match cards.iter().find(|card| card.id == *card_id) {
+                Some(card) => batch.cards.push(card.clone()),
+                None => batch.missing.push(card_id.to_string()),
+            }
+        }
+
+        batch.cards.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(batch)
+    }
+
+    /// Appends a new entry to `addressbook_id`. Returns its assigned id
+    /// and synthesized tag.
+    pub fn create_card(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AbookClientError> {
+        self.create_card_with_options(addressbook_id, contents, CardCreateOptions::default())
+    }
+
+    /// Appends a new entry to `addressbook_id`, failing when a card
+    /// with the same id exists. Returns its assigned id and
+    /// synthesized tag.
+    ///
+    /// The id always derives from the `UID`, the only identity an
+    /// abook entry keeps: a fresh `UID` is set when the card has none,
+    /// whatever `options` say.
+    pub fn create_card_with_options(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        _options: CardCreateOptions,
+    ) -> Result<CardRef, AbookClientError> {
+        let mut contact = parse_contents(&contents)?;
+
+        if contact
+            .uid
+            .as_deref()
+            .is_none_or(|uid| uid.trim().is_empty())
+        {
+            let uuid = fresh_card_id().map_err(AbookClientError::Random)?;
+            contact.uid = Some(format!("urn:uuid:{uuid}"));
+        }
+
+        let fields = fields_from_contact(&contact);
+        let (id, contents) = entry_card(&fields);
+        let mut file = self.load(addressbook_id)?;
+
+        if file.entries.iter().any(|entry| entry_card(entry).0 == id) {
+            return Err(AbookClientError::CardAlreadyExists(id));
+        }
+
+        file.entries.push(fields);
+        store(&self.path, &file)?;
+
+        Ok(CardRef {
+            id,
+            href: None,
+            etag: Some(content_tag(&contents)),
+        })
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id` in place, gating on
+    /// `if_match` when present. Without `if_match`, a missing card is
+    /// appended. Returns the card id and the tag of the new contents,
+    /// as read back from the entry.
+    ///
+    /// Contents without `UID` get the current `UID` of the card, or
+    /// `card_id` itself, so the card keeps its id; contents whose `UID`
+    /// maps to another id, and is not the current `UID` of the card
+    /// either, fail with [`AbookClientError::UidMismatch`].
+    pub fn update_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AbookClientError> {
+        if card_id.is_empty() {
+            return Err(AbookClientError::InvalidCardId(String::new()));
+        }
+
+        let mut contact = parse_contents(&contents)?;
+        let mut file = self.load(addressbook_id)?;
+
+        let current: Vec<(String, Vec<u8>)> = file.entries.iter().map(entry_card).collect();
+        let position = current.iter().position(|(id, _)| id == card_id);
+
+        // NOTE: the current UID maps back to the card id, whereas the
+        // id itself may not (escaped bytes).
+        let current_uid = position.and_then(|i| field(&file.entries[i], "uid"));
+
+        if let Some(uid) = update_uid(card_id, contact.uid.as_deref(), current_uid)? {
+            contact.uid = Some(uid);
+        }
+
+        if let Some(if_match) = if_match {
+            let Some(position) = position else {
+                return Err(AbookClientError::CardNotFound(card_id.to_string()));
+            };
+
+            check_precondition(card_id, &current[position].1, if_match)?;
+        }
+
+        let fields = fields_from_contact(&contact);
+        let (_, contents) = entry_card(&fields);
+
+        match position {
+            Some(position) => file.entries[position] = fields,
+            None => file.entries.push(fields),
+        }
+
+        store(&self.path, &file)?;
+
+        Ok(CardRef {
+            id: card_id.to_string(),
+            href: None,
+            etag: Some(content_tag(&contents)),
+        })
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating the card or overwriting it unconditionally.
+    pub fn upsert_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AbookClientError> {
+        self.update_card(addressbook_id, card_id, contents, None)
+    }
+
+    /// Removes `card_id` from `addressbook_id`, gating on `if_match`
+    /// when present.
+    pub fn delete_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AbookClientError> {
+        let mut file = self.load(addressbook_id)?;
+
+        let current = file
+            .entries
+            .iter()
+            .map(entry_card)
+            .enumerate()
+            .find(|(_, (id, _))| id == card_id);
+
+        let Some((position, (_, contents))) = current else {
+            return Err(AbookClientError::CardNotFound(card_id.to_string()));
+        };
+
+        if let Some(if_match) = if_match {
+            check_precondition(card_id, &contents, if_match)?;
+        }
+
+        file.entries.remove(position);
+        store(&self.path, &file)
+    }
+
+    /// Returns the id of the single addressbook, the file name.
+    fn id(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Checks that `id` is the addressbook of the file.
+    fn check_addressbook(&self, id: &str) -> Result<(), AbookClientError> {
+        if id != self.id() {
+            return Err(AbookClientError::AddressbookNotFound(id.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Reads and parses the file of the addressbook `id`.
+    fn load(&self, id: &str) -> Result<AbookFile, AbookClientError> {
+        self.check_addressbook(id)?;
+        let data = read_or_empty(&self.path)?;
+        Ok(AbookFile::parse(&String::from_utf8_lossy(&data)))
+    }
+}
+
+/// Reads the file at `path`, a missing file reading as empty.
+fn read_or_empty(path: &Path) -> Result<Vec<u8>, AbookClientError> {
+    match fs::read(path) {
+        Ok(data) => Ok(data),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Parses `contents` holding exactly one vCard.
+fn parse_contents(contents: &[u8]) -> Result<Contact, AbookClientError> {
+    if contents.is_empty() {
+        return Err(AbookClientError::EmptyBody);
+    }
+
+    if split_vcf(contents).len() != 1 {
+        return Err(AbookClientError::InvalidCard);
+    }
+
+    Contact::from_bytes(contents).map_err(AbookClientError::ParseCard)
+}
+
+/// Checks `if_match` against the tag of the current `contents` of
+/// `card_id`.
+fn check_precondition(
+    card_id: &str,
+    contents: &[u8],
+    if_match: &str,
+) -> Result<(), AbookClientError> {
+    let actual = content_tag(contents);
+
+    if tag_matches(if_match, &actual) {
+        return Ok(());
+    }
+
+    Err(AbookClientError::PreconditionFailed {
+        card_id: card_id.to_string(),
+        actual,
+    })
+}
+
+/// Rewrites the file at `path` with `file`, atomically.
+fn store(path: &Path, file: &AbookFile) -> Result<(), AbookClientError> {
+    Ok(write_atomic(path, file.render().as_bytes())?)
+}
+
+impl AddressbookBackend for AbookClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(AbookClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(AbookClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(AbookClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(AbookClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(AbookClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(AbookClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(AbookClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(AbookClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(AbookClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(AbookClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(AbookClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(AbookClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(AbookClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(AbookClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(AbookClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(AbookClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
@@ -0,0 +1,589 @@
+//! Conversions between abook `addressbook` files and the shared types
+//! used by [`AbookClient`], plus the `From` impl that wraps it into the
+//! unified client's abook variant.
+//!
+//! abook entries are flat `key=value` sections. The standard fields
+//! map to vCard properties: `name` to `FN`, `email` to one `EMAIL` per
+//! address, the address fields to one `ADR`, `phone`, `workphone`,
+//! `fax` and `mobile` to typed `TEL`s, `nick` to `NICKNAME`, `url` to
+//! `URL`, `notes` to `NOTE`, `anniversary` to `BDAY` and `groups` to
+//! `CATEGORIES`. The `uid` field carries the vCard `UID`, and any other
+//! field (e.g. `custom1`) travels as an `X-ABOOK-<FIELD>` property.
+//! Every other vCard property has no abook counterpart and is dropped,
+//! as are the types and parameters of the mapped ones.
+//!
+//! [`AbookClient`]: crate::abook::client::AbookClient
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    addressbook::Addressbook,
+    card::{
+        Address, Card, CardRef, Contact, Email, Phone, Property, StructuredName, escape,
+        tag::content_tag, uid::card_id_from_uid,
+    },
+};
+
+impl From<crate::abook::client::AbookClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::abook::client::AbookClient) -> Self {
+        Self::Abook(client)
+    }
+}
+
+/// Name of the section describing the file format.
+const FORMAT_SECTION: &str = "format";
+
+/// Prefix of the vCard properties carrying unmapped abook fields.
+const EXTRA_PREFIX: &str = "X-ABOOK-";
+
+/// Fields of an abook entry, in file order.
+pub(crate) type Fields = Vec<(String, String)>;
+
+/// Parsed abook `addressbook` file.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AbookFile {
+    /// Fields of the `[format]` section.
+    pub format: Fields,
+    /// Entries, in file order.
+    pub entries: Vec<Fields>,
+}
+
+impl AbookFile {
+    /// Parses the contents of an abook file.
+    ///
+    /// Numbered sections are entries; comments, blank lines, malformed
+    /// lines and sections other than `[format]` are skipped.
+    pub(crate) fn parse(data: &str) -> Self {
+        let mut file = Self::default();
+        let mut current: Option<&mut Fields> = None;
+
+        for line in data.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let section = section.trim();
+
+                current = if section == FORMAT_SECTION {
+                    file.format.clear();
+                    Some(&mut file.format)
+                } else if !section.is_empty() && section.bytes().all(|b| b.is_ascii_digit()) {
+                    file.entries.push(Fields::new());
+                    file.entries.last_mut()
+                } else {
+                    None
+                };
+
+                continue;
+            }
+
+            let (Some(fields), Some((key, value))) = (current.as_deref_mut(), line.split_once('='))
+            else {
+                continue;
+            };
+
+            fields.push((key.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+
+        file
+    }
+
+    /// Renders the file the way abook writes it, entries numbered from
+    /// `0` in order.
+    pub(crate) fn render(&self) -> String {
+        let mut out = String::from("# abook addressbook file\n\n");
+
+        out.push_str("[format]\n");
+
+        if self.format.is_empty() {
+            out.push_str("program=abook\nversion=0.6.1\n");
+        } else {
+            push_fields(&mut out, &self.format);
+        }
+
+        for (i, fields) in self.entries.iter().enumerate() {
+            out.push_str(&format!("\n[{i}]\n"));
+            push_fields(&mut out, fields);
+        }
+
+        out
+    }
+}
+
+fn push_fields(out: &mut String, fields: &Fields) {
+    for (key, value) in fields {
+        out.push_str(key);
+        out.push('=');
+        out.push_str(value);
+        out.push('\n');
+    }
+}
+
+/// Maps the abook file of `id` to a shared [`Addressbook`].
+///
+/// The id doubles as the name; the `ctag` is the tag of the whole
+/// file, so that any entry change bumps it.
+pub(crate) fn addressbook_from_file(id: &str, data: &[u8]) -> Addressbook {
+    Addressbook {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: None,
+        color: None,
+        ctag: Some(content_tag(data)),
+        sync_token: None,
+    }
+}
+
+/// Returns the value of the field `key`, when set and not empty.
+pub(crate) fn field<'a>(fields: &'a Fields, key: &str) -> Option<&'a str> {
+    fields
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+        .filter(|v| !v.is_empty())
+}
+
+/// Returns the id of the entry, with its vCard rendering.
+///
+/// Ids derive from the `uid` field, or from the tag of the vCard
+/// rendering when there is none.
+pub(crate) fn entry_card(fields: &Fields) -> (String, Vec<u8>) {
+    let contents = contact_from_fields(fields).to_bytes();
+
+    let id = match field(fields, "uid") {
+        Some(uid) => card_id_from_uid(uid),
+        None => content_tag(&contents),
+    };
+
+    (id, contents)
+}
+
+/// Maps an entry to a shared [`Card`].
+pub(crate) fn card_from_entry(addressbook_id: &str, fields: &Fields) -> Card {
+    let (id, contents) = entry_card(fields);
+
+    Card {
+        id,
+        addressbook_id: addressbook_id.to_string(),
+        etag: Some(content_tag(&contents)),
+        contents,
+    }
+}
+
+/// Maps an entry to a [`CardRef`].
+pub(crate) fn card_ref_from_entry(fields: &Fields) -> CardRef {
+    let (id, contents) = entry_card(fields);
+
+    CardRef {
+        id,
+        href: None,
+        etag: Some(content_tag(&contents)),
+    }
+}
+
+/// Maps the fields of an abook entry to a typed [`Contact`].
+pub(crate) fn contact_from_fields(fields: &Fields) -> Contact {
+    let mut contact = Contact::new();
+    let mut address = Address::default();
+
+    for (key, value) in fields.iter().filter(|(_, v)| !v.is_empty()) {
+        match key.as_str() {
+            "uid" => contact.uid = Some(value.clone()),
+            "name" => contact.formatted_name = Some(value.clone()),
+            "email" => {
+                let emails = value.split(',').map(str::trim).filter(|e| !e.is_empty());
+
+                contact.emails.extend(emails.map(|address| Email {
+                    address: address.to_string(),
+                    ..Default::default()
+                }));
+            }
+            "address" => address.street = value.clone(),
+            "address2" => address.extended = value.clone(),
+            "city" => address.locality = value.clone(),
+            "state" => address.region = value.clone(),
+            "zip" => address.postal_code = value.clone(),
+            "country" => address.country = value.clone(),
+            "phone" => contact.phones.push(phone(value, "home")),
+            "workphone" => contact.phones.push(phone(value, "work")),
+            "fax" => contact.phones.push(phone(value, "fax")),
+            "mobile" => contact.phones.push(phone(value, "cell")),
+            "nick" => contact.properties.push(text_prop("NICKNAME", value)),
+            "url" => contact.properties.push(text_prop("URL", value)),
+            "notes" => contact.notes.push(value.clone()),
+            "anniversary" => contact.birthday = Some(vcard_date(value)),
+            "groups" => {
+                let groups = value.split(',').map(str::trim).filter(|g| !g.is_empty());
+                contact.categories.extend(groups.map(ToString::to_string));
+            }
+            key => {
+                let name = format!("{EXTRA_PREFIX}{}", key.to_ascii_uppercase());
+                contact.properties.push(text_prop(&name, value));
+            }
+        }
+    }
+
+    if address != Address::default() {
+        contact.addresses.push(address);
+    }
+
+    contact
+}
+
+/// Maps a typed [`Contact`] to the fields of an abook entry.
+///
+/// Entries need a name: without `FN`, it is composed from `N`, then
+/// falls back to the first email.
+pub(crate) fn fields_from_contact(contact: &Contact) -> Fields {
+    let mut fields = Fields::new();
+    let mut push = |key: &str, value: &str| {
+        let value = single_line(value);
+
+        if !value.is_empty() {
+            fields.push((key.to_string(), value));
+        }
+    };
+
+    let name = contact
+        .formatted_name
+        .clone()
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| contact.name.as_ref().map(composed_name))
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| contact.emails.first().map(|email| email.address.clone()));
+
+    push("name", name.as_deref().unwrap_or_default());
+
+    let emails: Vec<&str> = contact
+        .emails
+        .iter()
+        .map(|email| email.address.as_str())
+        .collect();
+    push("email", &emails.join(","));
+
+    if let Some(adr) = contact.addresses.first() {
+        push("address", &adr.street);
+        push("address2", &adr.extended);
+        push("city", &adr.locality);
+        push("state", &adr.region);
+        push("zip", &adr.postal_code);
+        push("country", &adr.country);
+    }
+
+    let mut phones = [
+        ("phone", None::<&str>),
+        ("workphone", None),
+        ("fax", None),
+        ("mobile", None),
+    ];
+
+    for tel in &contact.phones {
+        let slot = match phone_key(tel) {
+            "workphone" => 1,
+            "fax" => 2,
+            "mobile" => 3,
+            _ => 0,
+        };
+
+        phones[slot].1.get_or_insert(tel.number.as_str());
+    }
+
+    for (key, number) in phones {
+        if let Some(number) = number {
+            push(key, number);
+        }
+    }
+
+    let nickname = first_text(contact, "NICKNAME").map(|nick| {
+        let nick = nick.split(',').next().unwrap_or_default();
+        nick.trim().to_string()
+    });
+
+    push("nick", nickname.as_deref().unwrap_or_default());
+    push(
+        "url",
+        first_text(contact, "URL").as_deref().unwrap_or_default(),
+    );
+    push("notes", &contact.notes.join(" "));
+
+    if let Some(bday) = &contact.birthday {
+        push("anniversary", &abook_date(bday));
+    }
+
+    push("groups", &contact.categories.join(","));
+
+    for prop in &contact.properties {
+        let Some(key) = prop.name.strip_prefix(EXTRA_PREFIX) else {
+            continue;
+        };
+
+        let key = key.to_ascii_lowercase();
+
+        if !key.is_empty() && !key.contains(['=', '[', ']']) {
+            push(&key, &prop.text());
+        }
+    }
+
+    if let Some(uid) = &contact.uid {
+        push("uid", uid);
+    }
+
+    fields
+}
+
+/// 1-indexed pagination on an in-memory list. `page_size = None`
+/// returns the full slice; `page_size = 0` or a page past the end
+/// returns an empty vector.
+pub(crate) fn paginate<T>(items: Vec<T>, page: Option<u32>, page_size: Option<u32>) -> Vec<T> {
+    let Some(size) = page_size else {
+        return items;
+    };
+
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let page = page.unwrap_or(1).max(1);
+    let skip = ((page - 1) as usize).saturating_mul(size as usize);
+
+    if skip >= items.len() {
+        return Vec::new();
+    }
+
+    items.into_iter().skip(skip).take(size as usize).collect()
+}
+
+/// Returns the abook field of a phone number from its types.
+fn phone_key(phone: &Phone) -> &'static str {
+    let has = |kind: &str| phone.types.iter().any(|t| t.eq_ignore_ascii_case(kind));
+
+    if has("fax") {
+        "fax"
+    } else if has("cell") {
+        "mobile"
+    } else if has("work") {
+        "workphone"
+    } else {
+        "phone"
+    }
+}
+
+fn phone(number: &str, kind: &str) -> Phone {
+    Phone {
+        number: number.to_string(),
+        types: Vec::from([kind.to_string()]),
+        ..Default::default()
+    }
+}
+
+/// Builds a vCard property holding `text`, escaped.
+fn text_prop(name: &str, text: &str) -> Property {
+    Property {
+        name: name.to_string(),
+        value: escape(text),
+        ..Default::default()
+    }
+}
+
+/// Returns the text of the first property named `name`.
+fn first_text(contact: &Contact, name: &str) -> Option<String> {
+    contact
+        .properties
+        .iter()
+        .find(|prop| prop.name == name)
+        .map(Property::text)
+}
+
+/// Composes a name from the structured name components.
+fn composed_name(name: &StructuredName) -> String {
+    [
+        &name.prefixes,
+        &name.given,
+        &name.additional,
+        &name.family,
+        &name.suffixes,
+    ]
+    .into_iter()
+    .filter(|p| !p.is_empty())
+    .map(String::as_str)
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Flattens line breaks, which abook values cannot hold.
+fn single_line(value: &str) -> String {
+    value
+        .split(['\r', '\n'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Renders a vCard date (`19900102`, `--0102`) the way abook stores
+/// anniversaries (`1990-01-02`, `--01-02`). Other values are kept as
+/// is.
+fn abook_date(value: &str) -> String {
+    let value = value.trim();
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+
+    if value.len() == 8 && is_digits(value) {
+        return format!("{}-{}-{}", &value[..4], &value[4..6], &value[6..]);
+    }
+
+    match value.strip_prefix("--") {
+        Some(md) if md.len() == 4 && is_digits(md) => format!("--{}-{}", &md[..2], &md[2..]),
+        _ => value.to_string(),
+    }
+}
+
+/// Renders an abook anniversary as a vCard date, see [`abook_date`].
+fn vcard_date(value: &str) -> String {
+    let digits = value.replace('-', "");
+    let is_digits = !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+
+    match digits.len() {
+        8 if is_digits => digits,
+        4 if is_digits && value.starts_with("--") => format!("--{digits}"),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> Fields {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    const ALICE: &[(&str, &str)] = &[
+        ("name", "Alice Liddell"),
+        ("email", "alice@example.org,alice@work.example"),
+        ("address", "1 Rabbit Hole"),
+        ("city", "Oxford"),
+        ("zip", "OX1"),
+        ("country", "UK"),
+        ("phone", "+44 1"),
+        ("workphone", "+44 2"),
+        ("mobile", "+44 3"),
+        ("nick", "alice"),
+        ("url", "https://example.org/alice"),
+        ("notes", "Met at a tea party"),
+        ("anniversary", "1852-05-04"),
+        ("groups", "friends,family"),
+        ("custom1", "Blue"),
+        ("uid", "alice-uid"),
+    ];
+
+    #[test]
+    fn entry_to_contact() {
+        let contact = contact_from_fields(&fields(ALICE));
+
+        assert_eq!(contact.uid.as_deref(), Some("alice-uid"));
+        assert_eq!(contact.formatted_name.as_deref(), Some("Alice Liddell"));
+
+        let emails: Vec<&str> = contact.emails.iter().map(|e| e.address.as_str()).collect();
+        assert_eq!(emails, ["alice@example.org", "alice@work.example"]);
+
+        let adr = &contact.addresses[0];
+        assert_eq!(adr.street, "1 Rabbit Hole");
+        assert_eq!(adr.locality, "Oxford");
+        assert_eq!(adr.region, "");
+        assert_eq!(adr.country, "UK");
+
+        let phones: Vec<(&str, &str)> = contact
+            .phones
+            .iter()
+            .map(|p| (p.number.as_str(), p.types[0].as_str()))
+            .collect();
+        assert_eq!(
+            phones,
+            [("+44 1", "home"), ("+44 2", "work"), ("+44 3", "cell")]
+        );
+
+        assert_eq!(contact.birthday.as_deref(), Some("18520504"));
+        assert_eq!(contact.notes, ["Met at a tea party"]);
+        assert_eq!(contact.categories, ["friends", "family"]);
+        assert_eq!(first_text(&contact, "NICKNAME").as_deref(), Some("alice"));
+        assert_eq!(
+            first_text(&contact, "X-ABOOK-CUSTOM1").as_deref(),
+            Some("Blue")
+        );
+    }
+
+    #[test]
+    fn round_trip_entry_through_vcard() {
+        let contact = contact_from_fields(&fields(ALICE));
+        let contact = Contact::parse(&contact.to_string()).unwrap();
+
+        assert_eq!(fields_from_contact(&contact), fields(ALICE));
+    }
+
+    #[test]
+    fn contact_to_entry() {
+        let vcard = "BEGIN:VCARD\r\n\
+            VERSION:4.0\r\n\
+            N:Hatter;Mad;;;\r\n\
+            EMAIL:hatter@example.org\r\n\
+            TEL;TYPE=cell:+44 4\r\n\
+            TEL;TYPE=cell:+44 5\r\n\
+            TEL;TYPE=work,fax:+44 6\r\n\
+            NICKNAME:hatter,tea\r\n\
+            NOTE:Always\\nsix o'clock\r\n\
+            BDAY:--0504\r\n\
+            CATEGORIES:tea\r\n\
+            END:VCARD\r\n";
+
+        let contact = Contact::parse(vcard).unwrap();
+
+        assert_eq!(
+            fields_from_contact(&contact),
+            fields(&[
+                ("name", "Mad Hatter"),
+                ("email", "hatter@example.org"),
+                ("fax", "+44 6"),
+                ("mobile", "+44 4"),
+                ("nick", "hatter"),
+                ("notes", "Always six o'clock"),
+                ("anniversary", "--05-04"),
+                ("groups", "tea"),
+            ])
+        );
+    }
+
+    #[test]
+    fn round_trip_file() {
+        let data = "# abook addressbook file\n\
+            \n\
+            [format]\n\
+            program=abook\n\
+            version=0.6.1\n\
+            \n\
+            [0]\n\
+            name=Alice Liddell\n\
+            email=alice@example.org\n\
+            \n\
+            [1]\n\
+            name=Mad Hatter\n\
+            custom1=Tea\n";
+
+        let file = AbookFile::parse(data);
+
+        assert_eq!(file.entries.len(), 2);
+        assert_eq!(field(&file.entries[1], "custom1"), Some("Tea"));
+        assert_eq!(file.render(), data);
+    }
+}
@@ -0,0 +1,4 @@
+//! abook backend: the std client plus its conversions.
+
+pub mod client;
+pub mod convert;
@@ -0,0 +1,85 @@
+//! Google People addressbook create coroutine, a
+//! `POST contactGroups`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let id = client.create_addressbook("personal", None, None)?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    google::{
+        convert::{ContactGroup, GROUP_FIELDS, endpoint, from_value},
+        person::group_id,
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleAddressbookCreate`].
+#[derive(Debug, Error)]
+pub enum GoogleAddressbookCreateError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+    #[error("Invalid addressbook `{0}`")]
+    InvalidAddressbook(String),
+}
+
+/// I/O-free coroutine creating a Google contact group.
+///
+/// Contact groups have neither description nor color: both are
+/// ignored. On completion returns the id assigned by the server.
+pub struct GoogleAddressbookCreate {
+    inner: GoogleSend,
+}
+
+impl GoogleAddressbookCreate {
+    /// Builds the coroutine creating the contact group `name`,
+    /// rejecting an empty name.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        name: &str,
+    ) -> Result<Self, GoogleAddressbookCreateError> {
+        trace!("prepare google addressbook create");
+
+        if name.trim().is_empty() {
+            return Err(GoogleAddressbookCreateError::InvalidAddressbook(
+                name.to_string(),
+            ));
+        }
+
+        let url = endpoint(base_url, "contactGroups", &[]);
+        let body = json!({ "contactGroup": { "name": name }, "readGroupFields": GROUP_FIELDS });
+
+        Ok(Self {
+            inner: GoogleSend::post(&url, token, user_agent, &body),
+        })
+    }
+}
+
+impl HttpCoroutine for GoogleAddressbookCreate {
+    type Yield = HttpYield;
+    type Return = Result<String, GoogleAddressbookCreateError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => {
+                let result = from_value::<ContactGroup>(body)
+                    .map(|group| group_id(&group.resource_name).to_string())
+                    .map_err(Into::into);
+                HttpCoroutineState::Complete(result)
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Google People addressbook delete coroutine, a `DELETE` on one
+//! contact group.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! client.delete_addressbook("personal")?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    google::{
+        convert::{endpoint, is_valid_id},
+        person::group_resource_name,
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleAddressbookDelete`].
+#[derive(Debug, Error)]
+pub enum GoogleAddressbookDeleteError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+    #[error("Invalid addressbook `{0}`")]
+    InvalidAddressbook(String),
+}
+
+/// I/O-free coroutine deleting a Google contact group along with its
+/// contacts, including the ones also belonging to other groups.
+pub struct GoogleAddressbookDelete {
+    inner: GoogleSend,
+}
+
+impl GoogleAddressbookDelete {
+    /// Builds the coroutine deleting the contact group `id`, rejecting
+    /// an invalid id.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        id: &str,
+    ) -> Result<Self, GoogleAddressbookDeleteError> {
+        trace!("prepare google addressbook delete");
+
+        if !is_valid_id(id) {
+            return Err(GoogleAddressbookDeleteError::InvalidAddressbook(
+                id.to_string(),
+            ));
+        }
+
+        let path = group_resource_name(id);
+        let url = endpoint(base_url, &path, &[("deleteContacts", "true")]);
+
+        Ok(Self {
+            inner: GoogleSend::delete(&url, token, user_agent),
+        })
+    }
+}
+
+impl HttpCoroutine for GoogleAddressbookDelete {
+    type Yield = HttpYield;
+    type Return = Result<(), GoogleAddressbookDeleteError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(_)) => HttpCoroutineState::Complete(Ok(())),
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Google People addressbook list coroutine, a paged
+//! `GET contactGroups`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let addressbooks = client.list_addressbooks()?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    addressbook::Addressbook,
+    google::{
+        convert::{
+            ContactGroup, GROUP_FIELDS, PAGE_SIZE, addressbook_from_group, endpoint, from_value,
+        },
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleAddressbookList`].
+#[derive(Debug, Error)]
+pub enum GoogleAddressbookListError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ListResponse {
+    contact_groups: Vec<ContactGroup>,
+    next_page_token: Option<String>,
+}
+
+/// I/O-free coroutine listing Google contact groups.
+///
+/// Only user groups and the `myContacts` system group show up. On
+/// completion returns them as shared [`Addressbook`]s, sorted by
+/// name.
+pub struct GoogleAddressbookList {
+    base_url: Url,
+    token: String,
+    user_agent: String,
+    addressbooks: Vec<Addressbook>,
+    inner: GoogleSend,
+}
+
+impl GoogleAddressbookList {
+    /// Builds the coroutine listing the contact groups of the account.
+    pub fn new(base_url: &Url, token: &str, user_agent: &str) -> Self {
+        trace!("prepare google addressbook list");
+
+        Self {
+            base_url: base_url.clone(),
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            addressbooks: Vec::new(),
+            inner: Self::request(base_url, token, user_agent, None),
+        }
+    }
+
+    fn request(base_url: &Url, token: &str, user_agent: &str, page: Option<&str>) -> GoogleSend {
+        let mut query = Vec::from([("pageSize", PAGE_SIZE), ("groupFields", GROUP_FIELDS)]);
+
+        if let Some(page) = page {
+            query.push(("pageToken", page));
+        }
+
+        let url = endpoint(base_url, "contactGroups", &query);
+        GoogleSend::get(&url, token, user_agent)
+    }
+}
+
+impl HttpCoroutine for GoogleAddressbookList {
+    type Yield = HttpYield;
+    type Return = Result<Vec<Addressbook>, GoogleAddressbookListError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            let body = match self.inner.resume(arg.take()) {
+                HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                HttpCoroutineState::Complete(Ok(body)) => body,
+                HttpCoroutineState::Complete(Err(err)) => {
+                    return HttpCoroutineState::Complete(Err(err.into()));
+                }
+            };
+
+            let list: ListResponse = match from_value(body) {
+                Ok(list) => list,
+                Err(err) => return HttpCoroutineState::Complete(Err(err.into())),
+            };
+
+            let groups = list.contact_groups.into_iter();
+            let addressbooks = groups
+                .filter(ContactGroup::is_addressbook)
+                .map(addressbook_from_group);
+            self.addressbooks.extend(addressbooks);
+
+            match list.next_page_token.filter(|page| !page.is_empty()) {
+                Some(page) => {
+                    self.inner =
+                        Self::request(&self.base_url, &self.token, &self.user_agent, Some(&page));
+                }
+                None => {
+                    let mut addressbooks = core::mem::take(&mut self.addressbooks);
+                    addressbooks.sort_by(|a, b| a.name.cmp(&b.name));
+                    return HttpCoroutineState::Complete(Ok(addressbooks));
+                }
+            }
+        }
+    }
+}
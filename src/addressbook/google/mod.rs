@@ -0,0 +1,8 @@
+//! Google People addressbook operations: create, delete, list, update.
+//!
+//! Addressbooks map to contact groups.
+
+pub mod create;
+pub mod delete;
+pub mod list;
+pub mod update;
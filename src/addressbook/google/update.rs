@@ -0,0 +1,141 @@
+//! Google People addressbook update coroutine, a `GET` then a `PUT` on
+//! one contact group.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! client.update_addressbook("personal", patch)?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    addressbook::AddressbookDiff,
+    google::{
+        convert::{ContactGroup, GROUP_FIELDS, endpoint, from_value, is_valid_id},
+        person::group_resource_name,
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleAddressbookUpdate`].
+#[derive(Debug, Error)]
+pub enum GoogleAddressbookUpdateError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+    #[error("Invalid addressbook `{0}`")]
+    InvalidAddressbook(String),
+}
+
+enum State {
+    /// Nothing to update.
+    Done,
+    /// Fetching the group `etag`, required by the update.
+    Get(GoogleSend),
+    /// Renaming the group.
+    Put(GoogleSend),
+}
+
+/// I/O-free coroutine renaming a Google contact group.
+///
+/// Contact groups only have a name: description and color changes are
+/// ignored.
+pub struct GoogleAddressbookUpdate {
+    url: Url,
+    token: String,
+    user_agent: String,
+    name: String,
+    state: State,
+}
+
+impl GoogleAddressbookUpdate {
+    /// Builds the coroutine applying `patch` to the contact group `id`,
+    /// rejecting an invalid id or an empty name.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<Self, GoogleAddressbookUpdateError> {
+        trace!("prepare google addressbook update");
+
+        if !is_valid_id(id) {
+            return Err(GoogleAddressbookUpdateError::InvalidAddressbook(
+                id.to_string(),
+            ));
+        }
+
+        let url = endpoint(base_url, &group_resource_name(id), &[]);
+
+        let state = match &patch.name {
+            Some(name) if name.trim().is_empty() => {
+                return Err(GoogleAddressbookUpdateError::InvalidAddressbook(
+                    id.to_string(),
+                ));
+            }
+            Some(_) => {
+                let url = endpoint(
+                    base_url,
+                    &group_resource_name(id),
+                    &[("groupFields", GROUP_FIELDS)],
+                );
+                State::Get(GoogleSend::get(&url, token, user_agent))
+            }
+            None => State::Done,
+        };
+
+        Ok(Self {
+            url,
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            name: patch.name.unwrap_or_default(),
+            state,
+        })
+    }
+}
+
+impl HttpCoroutine for GoogleAddressbookUpdate {
+    type Yield = HttpYield;
+    type Return = Result<(), GoogleAddressbookUpdateError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            let result = match &mut self.state {
+                State::Done => return HttpCoroutineState::Complete(Ok(())),
+                State::Get(send) | State::Put(send) => match send.resume(arg.take()) {
+                    HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                    HttpCoroutineState::Complete(result) => result,
+                },
+            };
+
+            let body = match result {
+                Ok(body) => body,
+                Err(err) => return HttpCoroutineState::Complete(Err(err.into())),
+            };
+
+            if let State::Put(_) = self.state {
+                return HttpCoroutineState::Complete(Ok(()));
+            }
+
+            let group: ContactGroup = match from_value(body) {
+                Ok(group) => group,
+                Err(err) => return HttpCoroutineState::Complete(Err(err.into())),
+            };
+
+            let body = json!({
+                "contactGroup": { "name": self.name, "etag": group.etag },
+                "updateGroupFields": "name",
+            });
+
+            let send = GoogleSend::put(&self.url, &self.token, &self.user_agent, &body);
+            self.state = State::Put(send);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! JMAP addressbook create coroutine, an `AddressBook/set` creating
+//! one addressbook.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let id = client.create_addressbook("personal", None, None)?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{SetResponse, from_value},
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapAddressbookCreate`].
+#[derive(Debug, Error)]
+pub enum JmapAddressbookCreateError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+    #[error("Invalid addressbook `{0}`")]
+    InvalidAddressbook(String),
+}
+
+/// I/O-free coroutine creating a JMAP addressbook.
+///
+/// JMAP addressbooks have no color: it is ignored. On completion
+/// returns the id assigned by the server.
+pub struct JmapAddressbookCreate {
+    inner: JmapApi,
+}
+
+impl JmapAddressbookCreate {
+    /// Builds the coroutine creating addressbook `name`, rejecting an
+    /// empty name.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<Self, JmapAddressbookCreateError> {
+        trace!("prepare jmap addressbook create");
+
+        if name.trim().is_empty() {
+            return Err(JmapAddressbookCreateError::InvalidAddressbook(
+                name.to_string(),
+            ));
+        }
+
+        let book = json!({ "name": name, "description": description });
+        let calls = Vec::from([invocation(
+            "AddressBook/set",
+            json!({ "accountId": session.account_id, "create": { "new": book } }),
+            "0",
+        )]);
+
+        Ok(Self {
+            inner: JmapApi::new(session, auth, user_agent, calls),
+        })
+    }
+
+    fn finish(&self, mut responses: JmapResponses) -> Result<String, JmapAddressbookCreateError> {
+        let set: SetResponse = from_value(responses.take("0")?)?;
+
+        if let Some(err) = set.create_error("new") {
+            return Err(JmapApiError::from(err).into());
+        }
+
+        let id = set
+            .created
+            .as_ref()
+            .and_then(|created| created.get("new"))
+            .and_then(|book| book.get("id"))
+            .and_then(|id| id.as_str())
+            .ok_or_else(|| JmapApiError::MissingResponse("0".to_string()))?;
+
+        Ok(id.to_string())
+    }
+}
+
+impl HttpCoroutine for JmapAddressbookCreate {
+    type Yield = HttpYield;
+    type Return = Result<String, JmapAddressbookCreateError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(responses)) => {
+                HttpCoroutineState::Complete(self.finish(responses))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
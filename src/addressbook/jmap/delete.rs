@@ -0,0 +1,104 @@
+//! JMAP addressbook delete coroutine, an `AddressBook/set` destroying
+//! one addressbook along with its cards.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! client.delete_addressbook("personal")?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{SetResponse, from_value},
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapAddressbookDelete`].
+#[derive(Debug, Error)]
+pub enum JmapAddressbookDeleteError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+    #[error("Invalid addressbook `{0}`")]
+    InvalidAddressbook(String),
+}
+
+/// I/O-free coroutine deleting a JMAP addressbook.
+///
+/// Sets `onDestroyRemoveContents`, so that cards belonging to no other
+/// addressbook are destroyed too, like deleting a CardDAV collection
+/// or a vdir directory does.
+pub struct JmapAddressbookDelete {
+    id: String,
+    inner: JmapApi,
+}
+
+impl JmapAddressbookDelete {
+    /// Builds the coroutine deleting addressbook `id`, rejecting an
+    /// empty id.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        id: &str,
+    ) -> Result<Self, JmapAddressbookDeleteError> {
+        trace!("prepare jmap addressbook delete");
+
+        if id.is_empty() {
+            return Err(JmapAddressbookDeleteError::InvalidAddressbook(
+                id.to_string(),
+            ));
+        }
+
+        let calls = Vec::from([invocation(
+            "AddressBook/set",
+            json!({
+                "accountId": session.account_id,
+                "destroy": [id],
+                "onDestroyRemoveContents": true,
+            }),
+            "0",
+        )]);
+
+        Ok(Self {
+            id: id.to_string(),
+            inner: JmapApi::new(session, auth, user_agent, calls),
+        })
+    }
+
+    fn finish(&self, mut responses: JmapResponses) -> Result<(), JmapAddressbookDeleteError> {
+        let set: SetResponse = from_value(responses.take("0")?)?;
+
+        match set.destroy_error(&self.id) {
+            Some(err) => Err(JmapApiError::from(err).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl HttpCoroutine for JmapAddressbookDelete {
+    type Yield = HttpYield;
+    type Return = Result<(), JmapAddressbookDeleteError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(responses)) => {
+                HttpCoroutineState::Complete(self.finish(responses))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! JMAP addressbook list coroutine, an `AddressBook/get` batched with
+//! a `ContactCard/get` fetching the state of the cards.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let addressbooks = client.list_addressbooks()?;
+//! ```
+
+use alloc::vec::Vec;
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{
+    addressbook::Addressbook,
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{AddressBookWire, GetResponse, addressbook_from_wire, from_value},
+        jscontact::JsCard,
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapAddressbookList`].
+#[derive(Debug, Error)]
+pub enum JmapAddressbookListError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+}
+
+/// I/O-free coroutine listing every addressbook of the contacts
+/// account.
+///
+/// The card state fetched alongside (an empty `ContactCard/get`) feeds
+/// the `ctag` and sync token of every addressbook, so callers can skip
+/// unchanged addressbooks without listing their cards. On completion
+/// sorts the result by name.
+pub struct JmapAddressbookList {
+    inner: JmapApi,
+}
+
+impl JmapAddressbookList {
+    /// Builds the coroutine listing the addressbooks of `session`.
+    pub fn new(session: &JmapSession, auth: &JmapAuth, user_agent: &str) -> Self {
+        trace!("prepare jmap addressbook list");
+
+        let account_id = &session.account_id;
+        let calls = Vec::from([
+            invocation(
+                "AddressBook/get",
+                json!({ "accountId": account_id, "ids": null }),
+                "0",
+            ),
+            invocation(
+                "ContactCard/get",
+                json!({ "accountId": account_id, "ids": [], "properties": ["id"] }),
+                "1",
+            ),
+        ]);
+
+        Self {
+            inner: JmapApi::new(session, auth, user_agent, calls),
+        }
+    }
+
+    fn finish(
+        &self,
+        mut responses: JmapResponses,
+    ) -> Result<Vec<Addressbook>, JmapAddressbookListError> {
+        let books: GetResponse<AddressBookWire> = from_value(responses.take("0")?)?;
+        let cards: GetResponse<JsCard> = from_value(responses.take("1")?)?;
+
+        let mut addressbooks: Vec<Addressbook> = books
+            .list
+            .into_iter()
+            .map(|book| addressbook_from_wire(book, &books.state, &cards.state))
+            .collect();
+        addressbooks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(addressbooks)
+    }
+}
+
+impl HttpCoroutine for JmapAddressbookList {
+    type Yield = HttpYield;
+    type Return = Result<Vec<Addressbook>, JmapAddressbookListError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(responses)) => {
+                HttpCoroutineState::Complete(self.finish(responses))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! JMAP addressbook operations: create, delete, list, update.
+
+pub mod create;
+pub mod delete;
+pub mod list;
+pub mod update;
@@ -0,0 +1,118 @@
+//! JMAP addressbook update coroutine, an `AddressBook/set` patching
+//! one addressbook.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! client.update_addressbook("personal", AddressbookDiff {
+//!     name: Some("Personal".into()),
+//!     ..Default::default()
+//! })?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde_json::{Map, Value, json};
+use thiserror::Error;
+
+use crate::{
+    addressbook::AddressbookDiff,
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{SetResponse, from_value},
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapAddressbookUpdate`].
+#[derive(Debug, Error)]
+pub enum JmapAddressbookUpdateError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+    #[error("Invalid addressbook `{0}`")]
+    InvalidAddressbook(String),
+}
+
+/// I/O-free coroutine applying an [`AddressbookDiff`] to a JMAP
+/// addressbook.
+///
+/// JMAP addressbooks have no color: a color change is ignored.
+pub struct JmapAddressbookUpdate {
+    id: String,
+    inner: JmapApi,
+}
+
+impl JmapAddressbookUpdate {
+    /// Builds the coroutine patching addressbook `id`, rejecting an
+    /// empty id or name.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<Self, JmapAddressbookUpdateError> {
+        trace!("prepare jmap addressbook update");
+
+        if id.is_empty() {
+            return Err(JmapAddressbookUpdateError::InvalidAddressbook(
+                id.to_string(),
+            ));
+        }
+
+        let mut update = Map::new();
+
+        if let Some(name) = patch.name {
+            if name.trim().is_empty() {
+                return Err(JmapAddressbookUpdateError::InvalidAddressbook(name));
+            }
+
+            update.insert("name".to_string(), Value::from(name));
+        }
+
+        if let Some(description) = patch.description {
+            update.insert("description".to_string(), Value::from(description));
+        }
+
+        let calls = Vec::from([invocation(
+            "AddressBook/set",
+            json!({ "accountId": session.account_id, "update": { id: update } }),
+            "0",
+        )]);
+
+        Ok(Self {
+            id: id.to_string(),
+            inner: JmapApi::new(session, auth, user_agent, calls),
+        })
+    }
+
+    fn finish(&self, mut responses: JmapResponses) -> Result<(), JmapAddressbookUpdateError> {
+        let set: SetResponse = from_value(responses.take("0")?)?;
+
+        match set.update_error(&self.id) {
+            Some(err) => Err(JmapApiError::from(err).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl HttpCoroutine for JmapAddressbookUpdate {
+    type Yield = HttpYield;
+    type Return = Result<(), JmapAddressbookUpdateError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(responses)) => {
+                HttpCoroutineState::Complete(self.finish(responses))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
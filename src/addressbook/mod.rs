@@ -1,6 +1,12 @@
 //! Addressbook domain: the shared [`Addressbook`] type and the
 //! per-backend addressbook operations.
 
+#[cfg(feature = "google")]
+pub mod google;
+#[cfg(feature = "jmap")]
+pub mod jmap;
+#[cfg(feature = "msgraph")]
+pub mod msgraph;
 mod types;
 #[cfg(feature = "vdir")]
 pub mod vdir;
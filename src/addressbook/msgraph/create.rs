@@ -0,0 +1,85 @@
+//! Microsoft Graph addressbook create coroutine, a
+//! `POST me/contactFolders`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let id = client.create_addressbook("personal", None, None)?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    http::coroutine::*,
+    msgraph::{
+        convert::{ContactFolder, endpoint, from_value},
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+/// Errors produced by [`MsGraphAddressbookCreate`].
+#[derive(Debug, Error)]
+pub enum MsGraphAddressbookCreateError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+    #[error("Invalid addressbook `{0}`")]
+    InvalidAddressbook(String),
+}
+
+/// I/O-free coroutine creating a Microsoft Graph contact folder under
+/// the default one.
+///
+/// Contact folders have neither description nor color: both are
+/// ignored. On completion returns the id assigned by the server.
+pub struct MsGraphAddressbookCreate {
+    inner: MsGraphSend,
+}
+
+impl MsGraphAddressbookCreate {
+    /// Builds the coroutine creating the contact folder `name`,
+    /// rejecting an empty name.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        name: &str,
+    ) -> Result<Self, MsGraphAddressbookCreateError> {
+        trace!("prepare msgraph addressbook create");
+
+        if name.trim().is_empty() {
+            return Err(MsGraphAddressbookCreateError::InvalidAddressbook(
+                name.to_string(),
+            ));
+        }
+
+        let url = endpoint(base_url, "me/contactFolders", &[]);
+        let body = json!({ "displayName": name });
+
+        Ok(Self {
+            inner: MsGraphSend::post(&url, token, user_agent, &body),
+        })
+    }
+}
+
+impl HttpCoroutine for MsGraphAddressbookCreate {
+    type Yield = HttpYield;
+    type Return = Result<String, MsGraphAddressbookCreateError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => {
+                let result = from_value::<ContactFolder>(body)
+                    .map(|folder| folder.id)
+                    .map_err(Into::into);
+                HttpCoroutineState::Complete(result)
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
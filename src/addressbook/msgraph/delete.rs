@@ -0,0 +1,78 @@
+//! Microsoft Graph addressbook delete coroutine, a `DELETE` on one
+//! contact folder.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! client.delete_addressbook(&folder_id)?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    http::coroutine::*,
+    msgraph::{
+        convert::{DEFAULT_FOLDER, endpoint, folder_path, is_valid_id},
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+/// Errors produced by [`MsGraphAddressbookDelete`].
+#[derive(Debug, Error)]
+pub enum MsGraphAddressbookDeleteError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+    #[error("Invalid addressbook `{0}`")]
+    InvalidAddressbook(String),
+}
+
+/// I/O-free coroutine deleting a Microsoft Graph contact folder along
+/// with its contacts.
+///
+/// Graph moves the folder to the deleted items of the mailbox. The
+/// default folder cannot be deleted.
+pub struct MsGraphAddressbookDelete {
+    inner: MsGraphSend,
+}
+
+impl MsGraphAddressbookDelete {
+    /// Builds the coroutine deleting the contact folder `id`,
+    /// rejecting an invalid id or the default folder.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        id: &str,
+    ) -> Result<Self, MsGraphAddressbookDeleteError> {
+        trace!("prepare msgraph addressbook delete");
+
+        if !is_valid_id(id) || id == DEFAULT_FOLDER {
+            return Err(MsGraphAddressbookDeleteError::InvalidAddressbook(
+                id.to_string(),
+            ));
+        }
+
+        let url = endpoint(base_url, &folder_path(id), &[]);
+
+        Ok(Self {
+            inner: MsGraphSend::delete(&url, token, user_agent),
+        })
+    }
+}
+
+impl HttpCoroutine for MsGraphAddressbookDelete {
+    type Yield = HttpYield;
+    type Return = Result<(), MsGraphAddressbookDeleteError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(_)) => HttpCoroutineState::Complete(Ok(())),
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
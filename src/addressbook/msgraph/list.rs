@@ -0,0 +1,108 @@
+//! Microsoft Graph addressbook list coroutine, a paged
+//! `GET me/contactFolders`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let addressbooks = client.list_addressbooks()?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    addressbook::Addressbook,
+    http::coroutine::*,
+    msgraph::{
+        convert::{
+            CollectionPage, ContactFolder, PAGE_SIZE, addressbook_from_folder, default_addressbook,
+            endpoint, from_value, next_link,
+        },
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+/// Errors produced by [`MsGraphAddressbookList`].
+#[derive(Debug, Error)]
+pub enum MsGraphAddressbookListError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+}
+
+/// I/O-free coroutine listing Microsoft Graph contact folders.
+///
+/// Graph lists the folders created under the default contact folder,
+/// which shows up as the [`DEFAULT_FOLDER`] addressbook. Folders
+/// nested deeper are left out. On completion returns them as shared
+/// [`Addressbook`]s, sorted by name.
+///
+/// [`DEFAULT_FOLDER`]: crate::msgraph::convert::DEFAULT_FOLDER
+pub struct MsGraphAddressbookList {
+    token: String,
+    user_agent: String,
+    addressbooks: Vec<Addressbook>,
+    inner: MsGraphSend,
+}
+
+impl MsGraphAddressbookList {
+    /// Builds the coroutine listing the contact folders of the user.
+    pub fn new(base_url: &Url, token: &str, user_agent: &str) -> Self {
+        trace!("prepare msgraph addressbook list");
+
+        let url = endpoint(base_url, "me/contactFolders", &[("$top", PAGE_SIZE)]);
+
+        Self {
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            addressbooks: Vec::from([default_addressbook()]),
+            inner: MsGraphSend::get(&url, token, user_agent),
+        }
+    }
+}
+
+impl HttpCoroutine for MsGraphAddressbookList {
+    type Yield = HttpYield;
+    type Return = Result<Vec<Addressbook>, MsGraphAddressbookListError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            let body = match self.inner.resume(arg.take()) {
+                HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                HttpCoroutineState::Complete(Ok(body)) => body,
+                HttpCoroutineState::Complete(Err(err)) => {
+                    return HttpCoroutineState::Complete(Err(err.into()));
+                }
+            };
+
+            let page: CollectionPage<ContactFolder> = match from_value(body) {
+                Ok(page) => page,
+                Err(err) => return HttpCoroutineState::Complete(Err(err.into())),
+            };
+
+            let addressbooks = page.value.into_iter().map(addressbook_from_folder);
+            self.addressbooks.extend(addressbooks);
+
+            match page.next_link {
+                Some(link) => {
+                    let url = match next_link(&link) {
+                        Ok(url) => url,
+                        Err(err) => return HttpCoroutineState::Complete(Err(err.into())),
+                    };
+
+                    self.inner = MsGraphSend::get(&url, &self.token, &self.user_agent);
+                }
+                None => {
+                    let mut addressbooks = core::mem::take(&mut self.addressbooks);
+                    addressbooks.sort_by(|a, b| a.name.cmp(&b.name));
+                    return HttpCoroutineState::Complete(Ok(addressbooks));
+                }
+            }
+        }
+    }
+}
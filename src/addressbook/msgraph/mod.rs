@@ -0,0 +1,9 @@
+//! Microsoft Graph addressbook operations: create, delete, list,
+//! update.
+//!
+//! Addressbooks map to the contact folders of the user.
+
+pub mod create;
+pub mod delete;
+pub mod list;
+pub mod update;
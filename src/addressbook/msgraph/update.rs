@@ -0,0 +1,91 @@
+//! Microsoft Graph addressbook update coroutine, a `PATCH` on one
+//! contact folder.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! client.update_addressbook(&folder_id, patch)?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    addressbook::AddressbookDiff,
+    http::coroutine::*,
+    msgraph::{
+        convert::{DEFAULT_FOLDER, endpoint, folder_path, is_valid_id},
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+/// Errors produced by [`MsGraphAddressbookUpdate`].
+#[derive(Debug, Error)]
+pub enum MsGraphAddressbookUpdateError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+    #[error("Invalid addressbook `{0}`")]
+    InvalidAddressbook(String),
+}
+
+/// I/O-free coroutine renaming a Microsoft Graph contact folder.
+///
+/// Contact folders only have a name: description and color changes
+/// are ignored. The default folder cannot be renamed.
+pub struct MsGraphAddressbookUpdate {
+    inner: Option<MsGraphSend>,
+}
+
+impl MsGraphAddressbookUpdate {
+    /// Builds the coroutine applying `patch` to the contact folder
+    /// `id`, rejecting an invalid id, the default folder or an empty
+    /// name.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<Self, MsGraphAddressbookUpdateError> {
+        trace!("prepare msgraph addressbook update");
+
+        let invalid = || MsGraphAddressbookUpdateError::InvalidAddressbook(id.to_string());
+
+        if !is_valid_id(id) {
+            return Err(invalid());
+        }
+
+        let inner = match patch.name {
+            Some(name) if name.trim().is_empty() || id == DEFAULT_FOLDER => return Err(invalid()),
+            Some(name) => {
+                let url = endpoint(base_url, &folder_path(id), &[]);
+                let body = json!({ "displayName": name });
+                Some(MsGraphSend::patch(&url, token, user_agent, &body))
+            }
+            None => None,
+        };
+
+        Ok(Self { inner })
+    }
+}
+
+impl HttpCoroutine for MsGraphAddressbookUpdate {
+    type Yield = HttpYield;
+    type Return = Result<(), MsGraphAddressbookUpdateError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        let Some(inner) = &mut self.inner else {
+            return HttpCoroutineState::Complete(Ok(()));
+        };
+
+        match inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(_)) => HttpCoroutineState::Complete(Ok(())),
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -6,8 +6,9 @@ use alloc::string::String;
 ///
 /// Strict least-common-denominator shape: only fields that are
 /// first-class in every protocol the crate targets (vdir, CardDAV).
-/// Partial-coverage fields (description, color, ctag) remain
-/// `Option<String>` and are populated by the backends that know them.
+/// Partial-coverage fields (description, color, ctag, sync token)
+/// remain `Option<String>` and are populated by the backends that
+/// know them.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
@@ -35,6 +36,12 @@ pub struct Addressbook {
     /// changed without listing every card.
     #[cfg_attr(feature = "serde", serde(default))]
     pub ctag: Option<String>,
+
+    /// Collection synchronization token (RFC 6578 `DAV:sync-token`),
+    /// when the backend exposes it. Feed it to `sync_cards` to fetch
+    /// only the cards changed since this listing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sync_token: Option<String>,
 }
 
 /// Partial update applied to an [`Addressbook`].
@@ -0,0 +1,161 @@
+//! CardDAV home-set discovery coroutine wrapping
+//! [`io_webdav::rfc5397::current_user_principal::CurrentUserPrincipal`]
+//! and [`io_webdav::rfc6352::addressbook::home_set::AddressbookHomeSet`].
+//!
+//! Sends a Depth: 0 PROPFIND on the base URL for the
+//! `DAV:current-user-principal` (RFC 5397), then another one on the
+//! principal for the `CR:addressbook-home-set` (RFC 6352 §7.1.1). A
+//! server without principal support is asked for the home-set on the
+//! base URL directly. A redirect surfaces as
+//! [`WebdavAddressbookDiscoverError::Redirect`]: following it may take a
+//! new connection, which only the caller can open.
+//!
+//! The async client runs it on first use and caches the result; the
+//! std client relies on the discovery of its inner
+//! [`io_webdav::client::WebdavClientStd`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let home = client.addressbook_home_set()?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use io_webdav::{
+    coroutine::*,
+    rfc4918::{WebdavAuth, coroutine::WebdavRedirectYield, follow_redirects::FollowRedirectsError},
+    rfc5397::current_user_principal::CurrentUserPrincipal,
+    rfc6352::addressbook::home_set::AddressbookHomeSet,
+};
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+/// Errors produced by [`WebdavAddressbookDiscover`].
+#[derive(Debug, Error)]
+pub enum WebdavAddressbookDiscoverError {
+    #[error(transparent)]
+    Send(#[from] FollowRedirectsError),
+    #[error("Server at `{0}` advertises no addressbook home-set")]
+    MissingHomeSet(Url),
+    #[error("Server redirected addressbook discovery to `{0}`")]
+    Redirect(Url),
+    #[error("Addressbook discover resumed after completion")]
+    Done,
+}
+
+/// Progress of [`WebdavAddressbookDiscover`].
+enum State {
+    Principal(CurrentUserPrincipal),
+    HomeSet(Url, AddressbookHomeSet),
+    Done,
+}
+
+/// I/O-free coroutine discovering the CardDAV addressbook home-set.
+///
+/// On completion returns the absolute URL of the home-set.
+pub struct WebdavAddressbookDiscover {
+    base_url: Url,
+    auth: WebdavAuth,
+    user_agent: String,
+    state: State,
+}
+
+impl WebdavAddressbookDiscover {
+    /// Builds the coroutine discovering the home-set of the account
+    /// served at `base_url`.
+    pub fn new(base_url: &Url, auth: &WebdavAuth, user_agent: &str) -> Self {
+        trace!("prepare webdav addressbook discover");
+
+        let principal = CurrentUserPrincipal::new(base_url, auth, user_agent);
+
+        Self {
+            base_url: base_url.clone(),
+            auth: auth.clone(),
+            user_agent: user_agent.to_string(),
+            state: State::Principal(principal),
+        }
+    }
+}
+
+/// Maps a redirect-aware yield to a standard one, or to the error
+/// ending the discovery on redirect.
+fn standard_yield(y: WebdavRedirectYield) -> Result<WebdavYield, WebdavAddressbookDiscoverError> {
+    match y {
+        WebdavRedirectYield::WantsRead => Ok(WebdavYield::WantsRead),
+        WebdavRedirectYield::WantsWrite(bytes) => Ok(WebdavYield::WantsWrite(bytes)),
+        WebdavRedirectYield::WantsRedirect { url, .. } => {
+            Err(WebdavAddressbookDiscoverError::Redirect(url))
+        }
+    }
+}
+
+impl WebdavCoroutine for WebdavAddressbookDiscover {
+    type Yield = WebdavYield;
+    type Return = Result<Url, WebdavAddressbookDiscoverError>;
+
+    fn resume(
+        &mut self,
+        mut arg: Option<&[u8]>,
+    ) -> WebdavCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match core::mem::replace(&mut self.state, State::Done) {
+                State::Principal(mut inner) => match inner.resume(arg.take()) {
+                    WebdavCoroutineState::Yielded(y) => {
+                        return match standard_yield(y) {
+                            Ok(y) => {
+                                self.state = State::Principal(inner);
+                                WebdavCoroutineState::Yielded(y)
+                            }
+                            Err(err) => WebdavCoroutineState::Complete(Err(err)),
+                        };
+                    }
+                    // NOTE: the home-set PROPFIND starts right away,
+                    // on the same resume call.
+                    WebdavCoroutineState::Complete(Ok(principal)) => {
+                        let principal = principal.unwrap_or_else(|| self.base_url.clone());
+
+                        trace!("discovered principal {principal}");
+
+                        let home = AddressbookHomeSet::new(
+                            &self.base_url,
+                            &self.auth,
+                            &self.user_agent,
+                            principal.path(),
+                        );
+                        self.state = State::HomeSet(principal, home);
+                    }
+                    WebdavCoroutineState::Complete(Err(err)) => {
+                        return WebdavCoroutineState::Complete(Err(err.into()));
+                    }
+                },
+                State::HomeSet(principal, mut inner) => {
+                    return match inner.resume(arg.take()) {
+                        WebdavCoroutineState::Yielded(y) => match standard_yield(y) {
+                            Ok(y) => {
+                                self.state = State::HomeSet(principal, inner);
+                                WebdavCoroutineState::Yielded(y)
+                            }
+                            Err(err) => WebdavCoroutineState::Complete(Err(err)),
+                        },
+                        WebdavCoroutineState::Complete(Ok(Some(home))) => {
+                            WebdavCoroutineState::Complete(Ok(home))
+                        }
+                        WebdavCoroutineState::Complete(Ok(None)) => WebdavCoroutineState::Complete(
+                            Err(WebdavAddressbookDiscoverError::MissingHomeSet(principal)),
+                        ),
+                        WebdavCoroutineState::Complete(Err(err)) => {
+                            WebdavCoroutineState::Complete(Err(err.into()))
+                        }
+                    };
+                }
+                State::Done => {
+                    return WebdavCoroutineState::Complete(Err(
+                        WebdavAddressbookDiscoverError::Done,
+                    ));
+                }
+            }
+        }
+    }
+}
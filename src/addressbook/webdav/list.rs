@@ -30,8 +30,11 @@ pub enum WebdavAddressbookListError {
 /// I/O-free coroutine listing every WebDAV addressbook under the
 /// home-set.
 ///
-/// On completion maps each wire addressbook to an [`Addressbook`] and
-/// sorts the result by name.
+/// Asks the server for the `CS:getctag` and `DAV:sync-token`
+/// properties alongside the display ones, so callers can skip
+/// unchanged addressbooks without listing their cards. On completion
+/// maps each wire addressbook to an [`Addressbook`] and sorts the
+/// result by name.
 pub struct WebdavAddressbookList {
     inner: ListAddressbooks,
 }
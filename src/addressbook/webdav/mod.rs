@@ -1,6 +1,8 @@
-//! CardDAV addressbook operations: create, delete, list, update.
+//! CardDAV addressbook operations: create, delete, discover, list,
+//! update.
 
 pub mod create;
 pub mod delete;
+pub mod discover;
 pub mod list;
 pub mod update;
@@ -55,6 +55,8 @@ impl WebdavAddressbookUpdate {
             display_name: patch.name,
             description: patch.description.unwrap_or(None),
             color: patch.color.unwrap_or(None),
+            ctag: None,
+            sync_token: None,
         };
 
         Ok(Self {
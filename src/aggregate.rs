@@ -0,0 +1,660 @@
+//! Aggregated view over several addressbook accounts.
+//!
+//! [`AddressbookClientStd`] speaks one protocol per account on purpose.
+//! [`AddressbookAggregate`] owns several of them, each under an account
+//! name, and exposes them as one: addressbook ids are namespaced as
+//! `<account>/<addressbook id>`, listing and searching fan out to every
+//! account in one call, and card operations are routed to the account
+//! owning the addressbook.
+//!
+//! Fanned-out operations never fail as a whole: each account failing
+//! is reported in [`FanOut::errors`] next to the results of the others.
+//! Routed operations report the account they failed on, see
+//! [`AggregateError::Account`].
+//!
+//! [`AddressbookClientStd`]: crate::client::AddressbookClientStd
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::debug;
+use thiserror::Error;
+
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    capabilities::Capabilities,
+    card::{Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef},
+    client::{AddressbookClientStd, AddressbookClientStdError},
+    error::ErrorKind,
+};
+
+/// Separator between the account name and the addressbook id of an
+/// aggregated addressbook id.
+pub const ACCOUNT_SEPARATOR: char = '/';
+
+/// Errors surfaced by [`AddressbookAggregate`].
+#[derive(Debug, Error)]
+pub enum AggregateError {
+    #[error("Invalid account name `{0}`")]
+    InvalidAccount(String),
+    #[error("Account `{0}` already exists")]
+    DuplicateAccount(String),
+    #[error("Account `{0}` not found")]
+    AccountNotFound(String),
+    #[error("Addressbook id `{0}` is not prefixed with an account name")]
+    InvalidAddressbookId(String),
+    #[error("Account `{account}`: {source}")]
+    Account {
+        account: String,
+        #[source]
+        source: AddressbookClientStdError,
+    },
+}
+
+impl AggregateError {
+    /// Classifies the error, see [`ErrorKind`]. Errors of an account
+    /// keep the classification of its backend.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Account { source, .. } => source.kind(),
+            Self::AccountNotFound(_) => ErrorKind::NotFound,
+            Self::DuplicateAccount(_) => ErrorKind::Conflict,
+            Self::InvalidAccount(_) | Self::InvalidAddressbookId(_) => ErrorKind::InvalidData,
+        }
+    }
+
+    /// Returns the current ETag of the card when the error is a failed
+    /// `if_match` precondition.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::Account { source, .. } => source.current_etag(),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the account the error comes from, if any.
+    pub fn account(&self) -> Option<&str> {
+        match self {
+            Self::Account { account, .. } => Some(account),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of an operation fanned out over every account: the results
+/// of the accounts that succeeded, and the errors of the others.
+#[derive(Debug)]
+pub struct FanOut<T> {
+    pub items: Vec<T>,
+    /// One [`AggregateError::Account`] per failure.
+    pub errors: Vec<AggregateError>,
+}
+
+impl<T> Default for FanOut<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl<T> FanOut<T> {
+    /// Tells whether every account succeeded.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Unified view over several named [`AddressbookClientStd`] accounts.
+///
+/// Accounts are iterated by name. Every addressbook id taken or
+/// returned is namespaced, see [`aggregate_id`], and so is the
+/// [`Card::addressbook_id`] of every returned card.
+#[derive(Debug, Default)]
+pub struct AddressbookAggregate {
+    accounts: BTreeMap<String, AddressbookClientStd>,
+}
+
+impl AddressbookAggregate {
+    /// Builds an aggregate without account.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `client` under the account `name`, which must be
+    /// non-empty, free of [`ACCOUNT_SEPARATOR`] and not taken yet.
+    pub fn add_account(
+        &mut self,
+        name: impl ToString,
+        client: impl Into<AddressbookClientStd>,
+    ) -> Result<(), AggregateError> {
+        let name = name.to_string();
+
+        if name.is_empty() || name.contains(ACCOUNT_SEPARATOR) {
+            return Err(AggregateError::InvalidAccount(name));
+        }
+
+        if self.accounts.contains_key(&name) {
+            return Err(AggregateError::DuplicateAccount(name));
+        }
+
+        self.accounts.insert(name, client.into());
+        Ok(())
+    }
+
+    /// Unregisters the account `name`, returning its client.
+    pub fn remove_account(&mut self, name: &str) -> Option<AddressbookClientStd> {
+        self.accounts.remove(name)
+    }
+
+    /// Returns the names of the accounts, sorted.
+    pub fn accounts(&self) -> impl Iterator<Item = &str> {
+        self.accounts.keys().map(String::as_str)
+    }
+
+    /// Returns the client of the account `name`, to reach
+    /// backend-specific operations.
+    pub fn account_mut(&mut self, name: &str) -> Option<&mut AddressbookClientStd> {
+        self.accounts.get_mut(name)
+    }
+
+    /// Returns the capabilities of the account `name`.
+    pub fn capabilities(&mut self, account: &str) -> Result<Capabilities, AggregateError> {
+        let client = self
+            .accounts
+            .get_mut(account)
+            .ok_or_else(|| AggregateError::AccountNotFound(account.to_string()))?;
+
+        in_account(account, client.capabilities())
+    }
+
+    /// Lists the addressbooks of every account, with namespaced ids.
+    pub fn list_addressbooks(&mut self) -> FanOut<Addressbook> {
+        let mut out = FanOut::default();
+
+        for (account, client) in &mut self.accounts {
+            match client.list_addressbooks() {
+                Ok(addressbooks) => {
+                    out.items
+                        .extend(addressbooks.into_iter().map(|mut addressbook| {
+                            addressbook.id = aggregate_id(account, &addressbook.id);
+                            addressbook
+                        }));
+                }
+                Err(source) => {
+                    debug!("cannot list addressbooks of account {account}: {source}");
+                    out.errors.push(AggregateError::Account {
+                        account: account.clone(),
+                        source,
+                    });
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Creates an addressbook in the account `account`. Returns the
+    /// namespaced id of the new addressbook.
+    pub fn create_addressbook(
+        &mut self,
+        account: &str,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AggregateError> {
+        let client = self
+            .accounts
+            .get_mut(account)
+            .ok_or_else(|| AggregateError::AccountNotFound(account.to_string()))?;
+
+        let id = in_account(account, client.create_addressbook(name, description, color))?;
+
+        Ok(aggregate_id(account, &id))
+    }
+
+    /// Applies `patch` to the addressbook `id` (namespaced).
+    pub fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AggregateError> {
+        let (account, client, id) = self.route(id)?;
+        in_account(account, client.update_addressbook(id, patch))
+    }
+
+    /// Deletes the addressbook `id` (namespaced) and its cards.
+    pub fn delete_addressbook(&mut self, id: &str) -> Result<(), AggregateError> {
+        let (account, client, id) = self.route(id)?;
+        in_account(account, client.delete_addressbook(id))
+    }
+
+    /// Lists cards inside `addressbook_id` (namespaced), with 1-indexed
+    /// pagination.
+    pub fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        let cards = in_account(account, client.list_cards(id, page, page_size))?;
+        Ok(namespace_cards(addressbook_id, cards))
+    }
+
+    /// Lists the id and ETag of every card in `addressbook_id`
+    /// (namespaced).
+    pub fn list_card_refs(&mut self, addressbook_id: &str) -> Result<Vec<CardRef>, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        in_account(account, client.list_card_refs(id))
+    }
+
+    /// Searches the cards matching `filter` in every addressbook of
+    /// every account.
+    ///
+    /// An account whose addressbooks cannot be listed, or an
+    /// addressbook that cannot be searched, ends up in
+    /// [`FanOut::errors`]; the search goes on with the others.
+    pub fn search_cards(&mut self, filter: &CardFilter) -> FanOut<Card> {
+        let mut out = FanOut::default();
+
+        for (account, client) in &mut self.accounts {
+            let addressbooks = match client.list_addressbooks() {
+                Ok(addressbooks) => addressbooks,
+                Err(source) => {
+                    debug!("cannot list addressbooks of account {account}: {source}");
+                    out.errors.push(AggregateError::Account {
+                        account: account.clone(),
+                        source,
+                    });
+                    continue;
+                }
+            };
+
+            for addressbook in addressbooks {
+                match client.search_cards(&addressbook.id, filter) {
+                    Ok(cards) => {
+                        let id = aggregate_id(account, &addressbook.id);
+                        out.items.extend(namespace_cards(&id, cards));
+                    }
+                    Err(source) => {
+                        debug!(
+                            "cannot search addressbook {} of {account}: {source}",
+                            addressbook.id
+                        );
+                        out.errors.push(AggregateError::Account {
+                            account: account.clone(),
+                            source,
+                        });
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Searches the cards of `addressbook_id` (namespaced) matching
+    /// `filter`.
+    pub fn search_addressbook(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        let cards = in_account(account, client.search_cards(id, filter))?;
+        Ok(namespace_cards(addressbook_id, cards))
+    }
+
+    /// Returns the changes of `addressbook_id` (namespaced) since
+    /// `sync_token`, see [`AddressbookClientStd::sync_cards`].
+    pub fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        let mut changes = in_account(account, client.sync_cards(id, sync_token))?;
+        changes.cards = namespace_cards(addressbook_id, changes.cards);
+        Ok(changes)
+    }
+
+    /// Fetches the card `card_id` from `addressbook_id` (namespaced).
+    pub fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        let mut card = in_account(account, client.get_card(id, card_id))?;
+        card.addressbook_id = addressbook_id.to_string();
+        Ok(card)
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id`
+    /// (namespaced) at once.
+    pub fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        let mut batch = in_account(account, client.get_cards(id, card_ids))?;
+        batch.cards = namespace_cards(addressbook_id, batch.cards);
+        Ok(batch)
+    }
+
+    /// Appends a raw vCard to `addressbook_id` (namespaced). Returns
+    /// the new card id and ETag.
+    pub fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        in_account(account, client.create_card(id, contents))
+    }
+
+    /// Appends a raw vCard to `addressbook_id` (namespaced) with the
+    /// given `options`. Returns the new card id and ETag.
+    pub fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        in_account(
+            account,
+            client.create_card_with_options(id, contents, options),
+        )
+    }
+
+    /// Replaces the bytes of `card_id` inside `addressbook_id`
+    /// (namespaced), gating on `if_match` when present.
+    pub fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        in_account(account, client.update_card(id, card_id, contents, if_match))
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`
+    /// (namespaced), creating or overwriting it unconditionally.
+    pub fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        in_account(account, client.upsert_card(id, card_id, contents))
+    }
+
+    /// Deletes `card_id` from `addressbook_id` (namespaced), gating on
+    /// `if_match` when present.
+    pub fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AggregateError> {
+        let (account, client, id) = self.route(addressbook_id)?;
+        in_account(account, client.delete_card(id, card_id, if_match))
+    }
+
+    /// Splits the namespaced `addressbook_id`, returning the owning
+    /// account, its client and the addressbook id within the account.
+    fn route<'a>(
+        &mut self,
+        addressbook_id: &'a str,
+    ) -> Result<(&'a str, &mut AddressbookClientStd, &'a str), AggregateError> {
+        let (account, id) = split_id(addressbook_id)
+            .ok_or_else(|| AggregateError::InvalidAddressbookId(addressbook_id.to_string()))?;
+
+        let client = self
+            .accounts
+            .get_mut(account)
+            .ok_or_else(|| AggregateError::AccountNotFound(account.to_string()))?;
+
+        Ok((account, client, id))
+    }
+}
+
+/// Namespaces the addressbook `id` of the account `account`.
+pub fn aggregate_id(account: &str, id: &str) -> String {
+    format!("{account}{ACCOUNT_SEPARATOR}{id}")
+}
+
+/// Splits a namespaced addressbook id into its account name and the
+/// addressbook id within the account. Returns `None` when either part
+/// is empty.
+pub fn split_id(id: &str) -> Option<(&str, &str)> {
+    id.split_once(ACCOUNT_SEPARATOR)
+        .filter(|(account, id)| !account.is_empty() && !id.is_empty())
+}
+
+/// Attaches the name of `account` to the error of `result`.
+fn in_account<T>(
+    account: &str,
+    result: Result<T, AddressbookClientStdError>,
+) -> Result<T, AggregateError> {
+    result.map_err(|source| AggregateError::Account {
+        account: account.to_string(),
+        source,
+    })
+}
+
+/// Sets the namespaced `addressbook_id` on every card of `cards`.
+fn namespace_cards(addressbook_id: &str, mut cards: Vec<Card>) -> Vec<Card> {
+    for card in &mut cards {
+        card.addressbook_id = addressbook_id.to_string();
+    }
+
+    cards
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use alloc::boxed::Box;
+
+    use super::*;
+    use crate::{
+        backend::{AddressbookBackend, BackendError},
+        card::PropFilter,
+        memory::client::InMemoryClient,
+    };
+
+    const ALICE: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alice\r\nEND:VCARD\r\n";
+    const BOB: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:bob\r\nFN:Bob\r\nEND:VCARD\r\n";
+
+    /// Backend of an unreachable account, failing every operation.
+    #[derive(Debug)]
+    struct Offline;
+
+    fn offline<T>() -> Result<T, AddressbookClientStdError> {
+        Err(BackendError::new(ErrorKind::Transport, "account offline").into())
+    }
+
+    impl AddressbookBackend for Offline {
+        fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+            offline()
+        }
+
+        fn create_addressbook(
+            &mut self,
+            _name: &str,
+            _description: Option<&str>,
+            _color: Option<&str>,
+        ) -> Result<String, AddressbookClientStdError> {
+            offline()
+        }
+
+        fn update_addressbook(
+            &mut self,
+            _id: &str,
+            _patch: AddressbookDiff,
+        ) -> Result<(), AddressbookClientStdError> {
+            offline()
+        }
+
+        fn delete_addressbook(&mut self, _id: &str) -> Result<(), AddressbookClientStdError> {
+            offline()
+        }
+
+        fn list_cards(
+            &mut self,
+            _addressbook_id: &str,
+            _page: Option<u32>,
+            _page_size: Option<u32>,
+        ) -> Result<Vec<Card>, AddressbookClientStdError> {
+            offline()
+        }
+
+        fn get_card(
+            &mut self,
+            _addressbook_id: &str,
+            _card_id: &str,
+        ) -> Result<Card, AddressbookClientStdError> {
+            offline()
+        }
+
+        fn create_card(
+            &mut self,
+            _addressbook_id: &str,
+            _contents: Vec<u8>,
+        ) -> Result<CardRef, AddressbookClientStdError> {
+            offline()
+        }
+
+        fn update_card(
+            &mut self,
+            _addressbook_id: &str,
+            _card_id: &str,
+            _contents: Vec<u8>,
+            _if_match: Option<&str>,
+        ) -> Result<CardRef, AddressbookClientStdError> {
+            offline()
+        }
+
+        fn delete_card(
+            &mut self,
+            _addressbook_id: &str,
+            _card_id: &str,
+            _if_match: Option<&str>,
+        ) -> Result<(), AddressbookClientStdError> {
+            offline()
+        }
+    }
+
+    /// Builds an aggregate of the accounts `home` and `work`, each with
+    /// one addressbook holding one card. Returns the namespaced ids of
+    /// both addressbooks.
+    fn aggregate() -> (AddressbookAggregate, String, String) {
+        let mut aggregate = AddressbookAggregate::new();
+        aggregate
+            .add_account("home", InMemoryClient::new())
+            .unwrap();
+        aggregate
+            .add_account("work", InMemoryClient::new())
+            .unwrap();
+
+        let home = aggregate
+            .create_addressbook("home", "Friends", None, None)
+            .unwrap();
+        let work = aggregate
+            .create_addressbook("work", "Team", None, None)
+            .unwrap();
+
+        aggregate.create_card(&home, ALICE.to_vec()).unwrap();
+        aggregate.create_card(&work, BOB.to_vec()).unwrap();
+
+        (aggregate, home, work)
+    }
+
+    #[test]
+    fn namespace_addressbook_ids() {
+        let (mut aggregate, home, work) = aggregate();
+
+        assert!(home.starts_with("home/"));
+        assert!(work.starts_with("work/"));
+
+        let addressbooks = aggregate.list_addressbooks();
+        assert!(addressbooks.is_complete());
+
+        let ids: Vec<&str> = addressbooks.items.iter().map(|ab| ab.id.as_str()).collect();
+        assert_eq!(ids, [home.as_str(), work.as_str()]);
+
+        let cards = aggregate.list_cards(&home, None, None).unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].addressbook_id, home);
+
+        let card = aggregate.get_card(&home, &cards[0].id).unwrap();
+        assert_eq!(card.addressbook_id, home);
+        assert_eq!(card.contents, ALICE);
+
+        let filter = CardFilter::any([PropFilter::contains("FN", "bob")]);
+        let found = aggregate.search_cards(&filter);
+        assert!(found.is_complete());
+        assert_eq!(found.items.len(), 1);
+        assert_eq!(found.items[0].addressbook_id, work);
+    }
+
+    #[test]
+    fn reject_invalid_ids() {
+        let (mut aggregate, _, _) = aggregate();
+
+        let err = aggregate
+            .add_account("a/b", InMemoryClient::new())
+            .unwrap_err();
+        assert!(matches!(err, AggregateError::InvalidAccount(_)));
+
+        let err = aggregate
+            .add_account("home", InMemoryClient::new())
+            .unwrap_err();
+        assert!(matches!(err, AggregateError::DuplicateAccount(_)));
+
+        let err = aggregate.list_cards("home", None, None).unwrap_err();
+        assert!(matches!(err, AggregateError::InvalidAddressbookId(_)));
+
+        let err = aggregate.list_cards("other/ab", None, None).unwrap_err();
+        assert!(matches!(err, AggregateError::AccountNotFound(_)));
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn report_errors_per_account() {
+        let (mut aggregate, home, work) = aggregate();
+        let offline = AddressbookClientStd::Custom(Box::new(Offline));
+        aggregate.add_account("remote", offline).unwrap();
+
+        let addressbooks = aggregate.list_addressbooks();
+        let ids: Vec<&str> = addressbooks.items.iter().map(|ab| ab.id.as_str()).collect();
+        assert_eq!(ids, [home.as_str(), work.as_str()]);
+
+        assert!(!addressbooks.is_complete());
+        assert_eq!(addressbooks.errors.len(), 1);
+        assert_eq!(addressbooks.errors[0].account(), Some("remote"));
+        assert_eq!(addressbooks.errors[0].kind(), ErrorKind::Transport);
+
+        let found = aggregate.search_cards(&CardFilter::default());
+        assert_eq!(found.items.len(), 2);
+        assert_eq!(found.errors.len(), 1);
+        assert_eq!(found.errors[0].account(), Some("remote"));
+
+        let err = aggregate
+            .create_card("remote/ab", ALICE.to_vec())
+            .unwrap_err();
+        assert_eq!(err.account(), Some("remote"));
+        assert_eq!(err.kind(), ErrorKind::Transport);
+    }
+}
@@ -0,0 +1,271 @@
+//! Pluggable addressbook backends.
+//!
+//! [`AddressbookBackend`] is the shared API as a trait, implemented by
+//! the per-backend std clients ([`VdirClient`], [`WebdavClient`]).
+//! Third-party backends implement it too and plug into the unified
+//! client through [`AddressbookClientStd::Custom`], so code written
+//! against [`AddressbookClientStd`] runs unchanged on top of them.
+//!
+//! Only the nine core operations are required. The others come with
+//! default implementations built on top of them (e.g. `search_cards`
+//! lists every card and filters locally); [`Capabilities`] tells which
+//! ones a backend implements natively.
+//!
+//! [`VdirClient`]: crate::vdir::client::VdirClient
+//! [`WebdavClient`]: crate::webdav::client::WebdavClient
+//! [`AddressbookClientStd`]: crate::client::AddressbookClientStd
+//! [`AddressbookClientStd::Custom`]: crate::client::AddressbookClientStd::Custom
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{error::Error as StdError, fmt::Debug};
+
+use thiserror::Error;
+
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    capabilities::Capabilities,
+    card::{Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef},
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+};
+
+/// Error reported by a third-party [`AddressbookBackend`].
+///
+/// Wraps the backend's own error along with its [`ErrorKind`], so
+/// callers can classify it like any built-in backend error.
+#[derive(Debug, Error)]
+#[error("Custom backend error ({kind:?})")]
+pub struct BackendError {
+    pub kind: ErrorKind,
+    pub current_etag: Option<String>,
+    #[source]
+    pub source: Box<dyn StdError + Send + Sync>,
+}
+
+impl BackendError {
+    /// Wraps `source`, classified as `kind`.
+    pub fn new(kind: ErrorKind, source: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self {
+            kind,
+            current_etag: None,
+            source: source.into(),
+        }
+    }
+
+    /// Attaches the current ETag of the card, for failed `if_match`
+    /// preconditions.
+    pub fn with_current_etag(mut self, etag: impl ToString) -> Self {
+        self.current_etag = Some(etag.to_string());
+        self
+    }
+}
+
+/// The shared addressbook API, implemented once per backend.
+///
+/// Methods mirror [`AddressbookClientStd`] and carry the same
+/// semantics; see there for the details of each operation.
+///
+/// [`AddressbookClientStd`]: crate::client::AddressbookClientStd
+pub trait AddressbookBackend: Debug {
+    /// Lists every addressbook available to the account.
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError>;
+
+    /// Creates an addressbook. Returns the backend-assigned id.
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError>;
+
+    /// Applies a partial update to the addressbook identified by `id`.
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError>;
+
+    /// Deletes the addressbook identified by `id` and its cards.
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError>;
+
+    /// Lists cards inside `addressbook_id`, with 1-indexed pagination.
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError>;
+
+    /// Fetches the card `card_id` from `addressbook_id`.
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError>;
+
+    /// Appends a raw vCard to `addressbook_id`, failing when a card
+    /// with the same id exists. Returns the new card id and ETag.
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError>;
+
+    /// Replaces the bytes of `card_id`, gating on `if_match` when
+    /// present. Returns the new card ETag.
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError>;
+
+    /// Permanently deletes `card_id`, gating on `if_match` when
+    /// present.
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError>;
+
+    /// Tells which operations the backend implements natively.
+    ///
+    /// Defaults to [`Capabilities::default`].
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(Capabilities::default())
+    }
+
+    /// Lists the id and ETag of every card inside `addressbook_id`.
+    ///
+    /// Defaults to a full [`list_cards`](Self::list_cards) with the
+    /// contents dropped.
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        let cards = self.list_cards(addressbook_id, None, None)?;
+
+        let refs = cards
+            .into_iter()
+            .map(|card| CardRef {
+                id: card.id,
+                href: None,
+                etag: card.etag,
+            })
+            .collect();
+
+        Ok(refs)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`.
+    ///
+    /// Defaults to listing every card and evaluating `filter` locally
+    /// via [`CardFilter::matches`].
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        let mut cards = self.list_cards(addressbook_id, None, None)?;
+        cards.retain(|card| filter.matches(card));
+        Ok(cards)
+    }
+
+    /// Fetches the cards of `addressbook_id` changed since
+    /// `sync_token`.
+    ///
+    /// Defaults to a full listing flagged by [`CardChanges::full`].
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        _sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        let cards = self.list_cards(addressbook_id, None, None)?;
+
+        Ok(CardChanges {
+            cards,
+            deleted: Vec::new(),
+            sync_token: None,
+            full: true,
+        })
+    }
+
+    /// Fetches several cards from `addressbook_id` at once.
+    ///
+    /// Defaults to one [`get_card`](Self::get_card) per id, reporting
+    /// the [`ErrorKind::NotFound`] ones in [`CardBatch::missing`].
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        let mut batch = CardBatch::default();
+
+        for card_id in card_ids {
+            match self.get_card(addressbook_id, card_id) {
+                Ok(card) => batch.cards.push(card),
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    batch.missing.push(card_id.to_string());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(batch)
+    }
+
+    /// Appends a raw vCard to `addressbook_id` with the given
+    /// `options`.
+    ///
+    /// Defaults to [`create_card`](Self::create_card), ignoring
+    /// `options`.
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        _options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        self.create_card(addressbook_id, contents)
+    }
+
+    /// Writes `contents` as card `card_id`, creating or overwriting
+    /// it.
+    ///
+    /// Defaults to an unconditional [`update_card`](Self::update_card),
+    /// which only creates the card when the backend's update does.
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        self.update_card(addressbook_id, card_id, contents, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use core::error::Error as _;
+
+    use crate::error::ErrorKind;
+
+    use super::BackendError;
+
+    #[test]
+    fn backend_error_reports_source_once() {
+        let err = BackendError::new(ErrorKind::NotFound, "card `alice` not found");
+
+        assert_eq!(err.to_string(), "Custom backend error (NotFound)");
+        assert_eq!(
+            err.source().map(|source| source.to_string()).as_deref(),
+            Some("card `alice` not found"),
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Capability domain: the shared [`Capabilities`] type and the CardDAV
+//! capability discovery.
+//!
+//! A [`Capabilities`] tells which parts of the shared API a backend
+//! implements natively, as opposed to emulating them (e.g. a search
+//! evaluated by listing every card and filtering locally), along with
+//! the limits callers must respect.
+
+mod types;
+#[cfg(feature = "webdav")]
+pub mod webdav;
+
+#[doc(inline)]
+pub use types::*;
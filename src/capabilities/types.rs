@@ -0,0 +1,65 @@
+use alloc::{string::String, vec::Vec};
+
+/// What a backend supports natively, and its limits.
+///
+/// The default is the conservative answer: nothing beyond the core
+/// operations, no known limit.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct Capabilities {
+    /// Cards carry an ETag and `if_match` preconditions are enforced
+    /// on update and delete.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub etag: bool,
+
+    /// Addressbooks carry a [`ctag`](crate::addressbook::Addressbook::ctag)
+    /// changing whenever their content does.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ctag: bool,
+
+    /// `sync_cards` returns deltas against a sync token rather than
+    /// full listings.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sync_token: bool,
+
+    /// `search_cards` is evaluated by the backend rather than by
+    /// listing and filtering every card.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub query: bool,
+
+    /// `get_cards` fetches a batch in one round trip rather than one
+    /// card at a time.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub multiget: bool,
+
+    /// vCard versions accepted for card contents (e.g. `3.0`, `4.0`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub vcard_versions: Vec<String>,
+
+    /// Largest card body accepted, in bytes, when the backend
+    /// advertises one.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_resource_size: Option<u64>,
+
+    /// Addressbook metadata fields `update_addressbook` can change.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub writable_metadata: Vec<AddressbookField>,
+}
+
+/// Writable addressbook metadata field, see
+/// [`Capabilities::writable_metadata`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum AddressbookField {
+    Name,
+    Description,
+    Color,
+}
+
+impl AddressbookField {
+    /// Every metadata field of the shared
+    /// [`Addressbook`](crate::addressbook::Addressbook).
+    pub const ALL: [Self; 3] = [Self::Name, Self::Description, Self::Color];
+}
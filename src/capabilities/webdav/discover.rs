@@ -0,0 +1,120 @@
+//! WebDAV capability discovery coroutine wrapping
+//! [`io_webdav::rfc4918::options::Options`] and
+//! [`io_webdav::rfc6352::addressbook::list::ListAddressbooks`].
+//!
+//! Sends an `OPTIONS` on the home-set (DAV compliance classes and
+//! allowed methods), then lists the addressbooks under it along with
+//! their `CS:getctag` and `DAV:sync-token`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let capabilities = client.capabilities()?;
+//! ```
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use io_webdav::{
+    coroutine::*,
+    rfc4918::{WebdavAuth, options::Options, send::SendError},
+    rfc6352::addressbook::list::ListAddressbooks,
+};
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{capabilities::Capabilities, webdav::convert::capabilities_from_wire};
+
+/// Errors produced by [`WebdavCapabilitiesDiscover`].
+#[derive(Debug, Error)]
+pub enum WebdavCapabilitiesDiscoverError {
+    #[error(transparent)]
+    Send(#[from] SendError),
+    #[error("Capabilities discover resumed after completion")]
+    Done,
+}
+
+/// Progress of [`WebdavCapabilitiesDiscover`].
+enum State {
+    Options(Box<Options>, ListAddressbooks),
+    /// The `DAV` and `Allow` headers of the `OPTIONS` response.
+    Addressbooks(String, String, ListAddressbooks),
+    Done,
+}
+
+/// I/O-free coroutine discovering the capabilities of a CardDAV
+/// server.
+///
+/// On completion returns the capabilities shared by every addressbook
+/// of the home-set, see [`capabilities_from_wire`].
+pub struct WebdavCapabilitiesDiscover {
+    state: State,
+}
+
+impl WebdavCapabilitiesDiscover {
+    /// Builds the coroutine discovering the capabilities of the
+    /// addressbooks under the home-set at `home_path`.
+    pub fn new(base_url: &Url, auth: &WebdavAuth, user_agent: &str, home_path: &str) -> Self {
+        trace!("prepare webdav capabilities discover");
+
+        let options = Options::new(base_url, auth, user_agent, home_path);
+        let addressbooks = ListAddressbooks::new(base_url, auth, user_agent, home_path);
+
+        Self {
+            state: State::Options(Box::new(options), addressbooks),
+        }
+    }
+}
+
+impl WebdavCoroutine for WebdavCapabilitiesDiscover {
+    type Yield = WebdavYield;
+    type Return = Result<Capabilities, WebdavCapabilitiesDiscoverError>;
+
+    fn resume(
+        &mut self,
+        mut arg: Option<&[u8]>,
+    ) -> WebdavCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match core::mem::replace(&mut self.state, State::Done) {
+                State::Options(mut inner, addressbooks) => match inner.resume(arg.take()) {
+                    WebdavCoroutineState::Yielded(y) => {
+                        self.state = State::Options(inner, addressbooks);
+                        return WebdavCoroutineState::Yielded(y);
+                    }
+                    // NOTE: the PROPFIND starts right away, on the
+                    // same resume call.
+                    WebdavCoroutineState::Complete(Ok(ok)) => {
+                        let header =
+                            |name| String::from(ok.response.header(name).unwrap_or_default());
+                        self.state =
+                            State::Addressbooks(header("dav"), header("allow"), addressbooks);
+                    }
+                    WebdavCoroutineState::Complete(Err(err)) => {
+                        return WebdavCoroutineState::Complete(Err(err.into()));
+                    }
+                },
+                State::Addressbooks(dav, allow, mut inner) => {
+                    return match inner.resume(arg.take()) {
+                        WebdavCoroutineState::Yielded(y) => {
+                            self.state = State::Addressbooks(dav, allow, inner);
+                            WebdavCoroutineState::Yielded(y)
+                        }
+                        WebdavCoroutineState::Complete(Ok(addressbooks)) => {
+                            let addressbooks: Vec<_> = addressbooks.into_iter().collect();
+                            let capabilities = capabilities_from_wire(&dav, &allow, &addressbooks);
+                            WebdavCoroutineState::Complete(Ok(capabilities))
+                        }
+                        WebdavCoroutineState::Complete(Err(err)) => {
+                            WebdavCoroutineState::Complete(Err(err.into()))
+                        }
+                    };
+                }
+                State::Done => {
+                    return WebdavCoroutineState::Complete(Err(
+                        WebdavCapabilitiesDiscoverError::Done,
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+//! CardDAV capability operations: discover.
+
+pub mod discover;
@@ -0,0 +1,1289 @@
+//! Typed contact model on top of raw vCard bytes.
+//!
+//! [`Contact`] exposes the commonly edited vCard properties (RFC 6350,
+//! also accepting vCard 3.0) as plain Rust fields, so applications can
+//! read and edit contacts without learning a vCard entry model.
+//!
+//! The model sits on its own content-line layer rather than on
+//! calcard, which normalizes what it writes: parsing then serializing
+//! a contact writes every line the caller did not edit back byte for
+//! byte (folding, parameter order and case, line terminators
+//! included) and at its original position. Only edited lines are
+//! re-serialized, keeping their original group and parameters when
+//! those did not change.
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, str::from_utf8};
+
+use thiserror::Error;
+
+/// Errors surfaced when parsing raw card bytes as a [`Contact`].
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum ParseContactError {
+    #[error("Card contents are not valid UTF-8")]
+    InvalidUtf8,
+    #[error("Missing vCard `BEGIN:VCARD` line")]
+    MissingBegin,
+    #[error("Missing vCard `END:VCARD` line")]
+    MissingEnd,
+    #[error("Invalid vCard line: {0}")]
+    InvalidLine(String),
+}
+
+/// A single property parameter, e.g. `TYPE=work,voice`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct Parameter {
+    /// Upper-cased parameter name.
+    pub name: String,
+
+    /// Parameter values, unquoted.
+    pub values: Vec<String>,
+}
+
+/// A single vCard content line, kept as on the wire.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct Property {
+    /// Property group, e.g. `item1` in `item1.EMAIL`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub group: Option<String>,
+
+    /// Upper-cased property name.
+    pub name: String,
+
+    /// Property parameters, in order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub params: Vec<Parameter>,
+
+    /// Raw (still escaped) property value.
+    pub value: String,
+}
+
+impl Property {
+    /// Parses one unfolded content line.
+    pub fn parse(line: &str) -> Result<Self, ParseContactError> {
+        let invalid = || ParseContactError::InvalidLine(line.to_string());
+
+        let (head, value) = split_unquoted(line, ':').ok_or_else(invalid)?;
+
+        let mut parts = split_all_unquoted(head, ';').into_iter();
+        let name = parts.next().filter(|n| !n.is_empty()).ok_or_else(invalid)?;
+
+        let (group, name) = match name.split_once('.') {
+            Some((group, name)) => (Some(group.to_string()), name),
+            None => (None, name),
+        };
+
+        if name.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut params = Vec::new();
+
+        for param in parts {
+            let (name, values) = match param.split_once('=') {
+                Some((name, values)) => (name, values),
+                // NOTE: vCard 3.0 allows bare `TYPE` values such as
+                // `TEL;HOME:…`.
+                None => ("TYPE", param),
+            };
+
+            params.push(Parameter {
+                name: name.to_ascii_uppercase(),
+                values: split_all_unquoted(values, ',')
+                    .into_iter()
+                    .map(|v| v.trim_matches('"').to_string())
+                    .collect(),
+            });
+        }
+
+        Ok(Self {
+            group,
+            name: name.to_ascii_uppercase(),
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    /// Returns the values of every parameter named `name`
+    /// (case-insensitive), flattened.
+    pub fn param_values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.params
+            .iter()
+            .filter(move |p| p.name.eq_ignore_ascii_case(name))
+            .flat_map(|p| p.values.iter().map(String::as_str))
+    }
+
+    /// Returns the value unescaped as a single vCard text.
+    pub fn text(&self) -> String {
+        unescape(&self.value)
+    }
+
+    /// Renders the property as one unfolded content line, without
+    /// line terminator.
+    pub(crate) fn to_line(&self) -> String {
+        let mut line = String::new();
+
+        if let Some(group) = &self.group {
+            line.push_str(group);
+            line.push('.');
+        }
+
+        line.push_str(&self.name);
+
+        for param in &self.params {
+            line.push(';');
+            line.push_str(&param.name);
+            line.push('=');
+
+            for (i, value) in param.values.iter().enumerate() {
+                if i > 0 {
+                    line.push(',');
+                }
+
+                if value.contains([':', ';', ',']) {
+                    line.push('"');
+                    line.push_str(value);
+                    line.push('"');
+                } else {
+                    line.push_str(value);
+                }
+            }
+        }
+
+        line.push(':');
+        line.push_str(&self.value);
+        line
+    }
+}
+
+/// Structured name (`N`), one string per component.
+///
+/// List-valued components (several given names, for instance) are
+/// kept comma-separated inside their component.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct StructuredName {
+    pub family: String,
+    pub given: String,
+    pub additional: String,
+    pub prefixes: String,
+    pub suffixes: String,
+}
+
+/// Email address (`EMAIL`).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct Email {
+    pub address: String,
+    /// Lower-cased `TYPE` values, e.g. `work`, `home`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub types: Vec<String>,
+    /// `PREF` value, 1 being the most preferred.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pref: Option<u8>,
+    /// Remaining parameters and group, kept for round-trip.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: PropertyExtra,
+}
+
+/// Phone number (`TEL`), either a `tel:` URI or free text.
+///
+/// With a `VALUE=uri` parameter (kept in `extra`), `number` holds the
+/// URI as written; otherwise it holds the unescaped text.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct Phone {
+    pub number: String,
+    /// Lower-cased `TYPE` values, e.g. `cell`, `voice`, `work`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub types: Vec<String>,
+    /// `PREF` value, 1 being the most preferred.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pref: Option<u8>,
+    /// Remaining parameters and group, kept for round-trip.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: PropertyExtra,
+}
+
+/// Delivery address (`ADR`), one string per component.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct Address {
+    pub po_box: String,
+    pub extended: String,
+    pub street: String,
+    pub locality: String,
+    pub region: String,
+    pub postal_code: String,
+    pub country: String,
+    /// Lower-cased `TYPE` values, e.g. `home`, `work`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub types: Vec<String>,
+    /// `PREF` value, 1 being the most preferred.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pref: Option<u8>,
+    /// Remaining parameters and group, kept for round-trip.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: PropertyExtra,
+}
+
+/// Organization (`ORG`): name followed by organizational units.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct Organization {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub units: Vec<String>,
+}
+
+/// Group and parameters of a typed property that have no typed
+/// field, kept so that serializing does not drop them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct PropertyExtra {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub group: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub params: Vec<Parameter>,
+}
+
+/// Typed view of a vCard.
+///
+/// Every field maps one vCard property; [`Contact::properties`] keeps
+/// the ones without a typed field. Build one with [`Contact::parse`]
+/// or [`Card::as_contact`](crate::card::Card::as_contact), edit the
+/// fields, then serialize it back with [`Contact::to_bytes`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct Contact {
+    /// vCard version (`VERSION`), `4.0` when built from scratch.
+    pub version: String,
+
+    /// Unique identifier (`UID`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub uid: Option<String>,
+
+    /// Formatted name (`FN`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub formatted_name: Option<String>,
+
+    /// Structured name (`N`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: Option<StructuredName>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub emails: Vec<Email>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub phones: Vec<Phone>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub addresses: Vec<Address>,
+
+    /// Organization (`ORG`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub org: Option<Organization>,
+
+    /// Job title (`TITLE`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub title: Option<String>,
+
+    /// Birthday (`BDAY`), as written (e.g. `19850412`, `--0412`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub birthday: Option<String>,
+
+    /// Free-form notes (`NOTE`), one per property.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub notes: Vec<String>,
+
+    /// Categories (`CATEGORIES`), merged across properties.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub categories: Vec<String>,
+
+    /// Photo (`PHOTO`): a URI (possibly a `data:` one), or the base64
+    /// payload of a vCard 3.0 inline photo.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub photo: Option<String>,
+
+    /// Revision timestamp (`REV`), as written.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rev: Option<String>,
+
+    /// Properties without a typed field, and repeats of the
+    /// single-valued typed ones, in original order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub properties: Vec<Property>,
+
+    /// Group and parameters of the single-valued typed properties,
+    /// keyed by property name.
+    #[cfg_attr(feature = "serde", serde(default))]
+    extras: BTreeMap<String, PropertyExtra>,
+
+    /// Group and parameters of each `NOTE` line, aligned with
+    /// [`Contact::notes`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    note_extras: Vec<PropertyExtra>,
+
+    /// Number of categories and group and parameters of each
+    /// `CATEGORIES` line, in order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    category_lines: Vec<(usize, PropertyExtra)>,
+
+    /// Original order of the content lines.
+    #[cfg_attr(feature = "serde", serde(default))]
+    layout: Vec<Slot>,
+
+    /// Original text of the parsed content lines, aligned with
+    /// `layout`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    raw_lines: Vec<RawLine>,
+
+    /// Original `BEGIN:VCARD` and `END:VCARD` lines.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    raw_delimiters: Option<(String, String)>,
+}
+
+/// Parsed content line as found in the original vCard.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct RawLine {
+    /// Line as rendered right after parsing: as long as the contact
+    /// still renders it the same, the line is left untouched.
+    rendered: Property,
+
+    /// Original text, folding, line terminator and following blank
+    /// lines included.
+    text: String,
+}
+
+/// Position of a content line in a [`Contact`], recorded at parse
+/// time to write lines back in their original order.
+///
+/// The variant order is the serialization order of lines added since.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+enum Slot {
+    Version,
+    Uid,
+    FormattedName,
+    Name,
+    Email(usize),
+    Phone(usize),
+    Address(usize),
+    Org,
+    Title,
+    Birthday,
+    Note(usize),
+    Categories(usize),
+    Photo,
+    Rev,
+    Property(usize),
+}
+
+impl Contact {
+    /// Builds an empty vCard 4.0 contact.
+    pub fn new() -> Self {
+        Self {
+            version: "4.0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Parses raw vCard bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseContactError> {
+        let s = from_utf8(bytes).map_err(|_| ParseContactError::InvalidUtf8)?;
+        Self::parse(s)
+    }
+
+    /// Parses a single vCard.
+    pub fn parse(s: &str) -> Result<Self, ParseContactError> {
+        let (begin, props, end) = parse_lines(s)?;
+
+        let mut contact = Self::default();
+        let mut texts = Vec::with_capacity(props.len());
+
+        for (prop, text) in props {
+            contact.push(prop);
+            texts.push(text);
+        }
+
+        if contact.version.is_empty() {
+            contact.version = "4.0".to_string();
+        }
+
+        let mut rendered = contact.lines();
+
+        contact.raw_lines = contact
+            .layout
+            .iter()
+            .zip(texts)
+            .map(|(slot, text)| RawLine {
+                rendered: rendered.remove(slot).unwrap_or_default(),
+                text: text.to_string(),
+            })
+            .collect();
+
+        contact.raw_delimiters = Some((begin.to_string(), end.to_string()));
+
+        Ok(contact)
+    }
+
+    /// Serializes the contact as vCard bytes.
+    ///
+    /// Lines left untouched since parsing are written back as found;
+    /// the others use the line terminator of the parsed vCard (CRLF
+    /// by default) and are folded at 75 octets.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+
+    /// Dispatches one parsed property to its typed field, or keeps it
+    /// in [`Contact::properties`], recording its position.
+    fn push(&mut self, prop: Property) {
+        let slot = match prop.name.as_str() {
+            "VERSION" if !self.layout.contains(&Slot::Version) => {
+                self.version = prop.value;
+                Slot::Version
+            }
+            "UID" if self.uid.is_none() => {
+                self.uid = Some(self.single(prop, false));
+                Slot::Uid
+            }
+            "FN" if self.formatted_name.is_none() => {
+                self.formatted_name = Some(self.single(prop, true));
+                Slot::FormattedName
+            }
+            "N" if self.name.is_none() => {
+                let mut c = split_components(&prop.value).into_iter();
+                self.name = Some(StructuredName {
+                    family: c.next().unwrap_or_default(),
+                    given: c.next().unwrap_or_default(),
+                    additional: c.next().unwrap_or_default(),
+                    prefixes: c.next().unwrap_or_default(),
+                    suffixes: c.next().unwrap_or_default(),
+                });
+                self.single(prop, false);
+                Slot::Name
+            }
+            "EMAIL" => {
+                let (types, pref, extra) = typed_params(&prop);
+                self.emails.push(Email {
+                    address: prop.text(),
+                    types,
+                    pref,
+                    extra,
+                });
+                Slot::Email(self.emails.len() - 1)
+            }
+            "TEL" => {
+                let (types, pref, extra) = typed_params(&prop);
+                let number = if is_uri(&extra) {
+                    prop.value
+                } else {
+                    prop.text()
+                };
+                self.phones.push(Phone {
+                    number,
+                    types,
+                    pref,
+                    extra,
+                });
+                Slot::Phone(self.phones.len() - 1)
+            }
+            "ADR" => {
+                let (types, pref, extra) = typed_params(&prop);
+                let mut c = split_components(&prop.value).into_iter();
+                self.addresses.push(Address {
+                    po_box: c.next().unwrap_or_default(),
+                    extended: c.next().unwrap_or_default(),
+                    street: c.next().unwrap_or_default(),
+                    locality: c.next().unwrap_or_default(),
+                    region: c.next().unwrap_or_default(),
+                    postal_code: c.next().unwrap_or_default(),
+                    country: c.next().unwrap_or_default(),
+                    types,
+                    pref,
+                    extra,
+                });
+                Slot::Address(self.addresses.len() - 1)
+            }
+            "ORG" if self.org.is_none() => {
+                let mut c = split_components(&prop.value).into_iter();
+                self.org = Some(Organization {
+                    name: c.next().unwrap_or_default(),
+                    units: c.collect(),
+                });
+                self.single(prop, false);
+                Slot::Org
+            }
+            "TITLE" if self.title.is_none() => {
+                self.title = Some(self.single(prop, true));
+                Slot::Title
+            }
+            "BDAY" if self.birthday.is_none() => {
+                self.birthday = Some(self.single(prop, false));
+                Slot::Birthday
+            }
+            "NOTE" => {
+                self.notes.push(prop.text());
+                self.note_extras.push(PropertyExtra {
+                    group: prop.group,
+                    params: prop.params,
+                });
+                Slot::Note(self.notes.len() - 1)
+            }
+            "CATEGORIES" => {
+                let categories = split_all_unescaped(&prop.value, ',');
+                self.categories
+                    .extend(categories.iter().map(|c| unescape(c)));
+                let extra = PropertyExtra {
+                    group: prop.group,
+                    params: prop.params,
+                };
+                self.category_lines.push((categories.len(), extra));
+                Slot::Categories(self.category_lines.len() - 1)
+            }
+            "PHOTO" if self.photo.is_none() => {
+                self.photo = Some(self.single(prop, false));
+                Slot::Photo
+            }
+            "REV" if self.rev.is_none() => {
+                self.rev = Some(self.single(prop, false));
+                Slot::Rev
+            }
+            _ => {
+                self.properties.push(prop);
+                Slot::Property(self.properties.len() - 1)
+            }
+        };
+
+        self.layout.push(slot);
+    }
+
+    /// Records the group and parameters of a single-valued typed
+    /// property, then returns its value (unescaped when `text`).
+    fn single(&mut self, prop: Property, text: bool) -> String {
+        let value = if text { prop.text() } else { prop.value };
+
+        let extra = PropertyExtra {
+            group: prop.group,
+            params: prop.params,
+        };
+
+        if extra != PropertyExtra::default() {
+            self.extras.insert(prop.name, extra);
+        }
+
+        value
+    }
+
+    /// Builds the content line of a single-valued typed property,
+    /// reattaching its original group and parameters.
+    fn single_line(&self, name: &str, value: String) -> Property {
+        let extra = self.extras.get(name).cloned().unwrap_or_default();
+
+        Property {
+            group: extra.group,
+            name: name.to_string(),
+            params: extra.params,
+            value,
+        }
+    }
+
+    /// Lists every property of the contact in serialization order:
+    /// parsed lines keep their original position, lines added since
+    /// follow, typed ones first.
+    pub fn to_properties(&self) -> Vec<Property> {
+        self.ordered_lines()
+            .into_iter()
+            .map(|(_, prop)| prop)
+            .collect()
+    }
+
+    /// Renders every property of the contact along with its slot, in
+    /// serialization order.
+    fn ordered_lines(&self) -> Vec<(Slot, Property)> {
+        let mut lines = self.lines();
+
+        let mut ordered: Vec<_> = self
+            .layout
+            .iter()
+            .filter_map(|slot| lines.remove_entry(slot))
+            .collect();
+
+        ordered.extend(lines);
+        ordered
+    }
+
+    /// Renders every property of the contact, keyed by slot.
+    fn lines(&self) -> BTreeMap<Slot, Property> {
+        let mut lines = BTreeMap::new();
+
+        let version = if self.version.is_empty() {
+            "4.0"
+        } else {
+            &self.version
+        };
+        lines.insert(
+            Slot::Version,
+            Property {
+                name: "VERSION".to_string(),
+                value: version.to_string(),
+                ..Default::default()
+            },
+        );
+
+        if let Some(uid) = &self.uid {
+            lines.insert(Slot::Uid, self.single_line("UID", uid.clone()));
+        }
+
+        if let Some(fname) = &self.formatted_name {
+            lines.insert(Slot::FormattedName, self.single_line("FN", escape(fname)));
+        }
+
+        if let Some(n) = &self.name {
+            let value =
+                join_components(&[&n.family, &n.given, &n.additional, &n.prefixes, &n.suffixes]);
+            lines.insert(Slot::Name, self.single_line("N", value));
+        }
+
+        for (i, email) in self.emails.iter().enumerate() {
+            let line = typed_line(
+                "EMAIL",
+                escape(&email.address),
+                &email.types,
+                email.pref,
+                &email.extra,
+            );
+            lines.insert(Slot::Email(i), line);
+        }
+
+        for (i, phone) in self.phones.iter().enumerate() {
+            let number = if is_uri(&phone.extra) {
+                phone.number.clone()
+            } else {
+                escape(&phone.number)
+            };
+            let line = typed_line("TEL", number, &phone.types, phone.pref, &phone.extra);
+            lines.insert(Slot::Phone(i), line);
+        }
+
+        for (i, adr) in self.addresses.iter().enumerate() {
+            let value = join_components(&[
+                &adr.po_box,
+                &adr.extended,
+                &adr.street,
+                &adr.locality,
+                &adr.region,
+                &adr.postal_code,
+                &adr.country,
+            ]);
+            let line = typed_line("ADR", value, &adr.types, adr.pref, &adr.extra);
+            lines.insert(Slot::Address(i), line);
+        }
+
+        if let Some(org) = &self.org {
+            let mut components = Vec::with_capacity(org.units.len() + 1);
+            components.push(org.name.as_str());
+            components.extend(org.units.iter().map(String::as_str));
+            let line = self.single_line("ORG", join_components(&components));
+            lines.insert(Slot::Org, line);
+        }
+
+        if let Some(title) = &self.title {
+            lines.insert(Slot::Title, self.single_line("TITLE", escape(title)));
+        }
+
+        if let Some(bday) = &self.birthday {
+            lines.insert(Slot::Birthday, self.single_line("BDAY", bday.clone()));
+        }
+
+        for (i, note) in self.notes.iter().enumerate() {
+            let extra = self.note_extras.get(i).cloned().unwrap_or_default();
+            let line = Property {
+                group: extra.group,
+                name: "NOTE".to_string(),
+                params: extra.params,
+                value: escape(note),
+            };
+            lines.insert(Slot::Note(i), line);
+        }
+
+        for (i, (categories, extra)) in self.category_lines().into_iter().enumerate() {
+            let value = categories
+                .iter()
+                .map(|c| escape(c))
+                .collect::<Vec<_>>()
+                .join(",");
+            let line = Property {
+                group: extra.group,
+                name: "CATEGORIES".to_string(),
+                params: extra.params,
+                value,
+            };
+            lines.insert(Slot::Categories(i), line);
+        }
+
+        if let Some(photo) = &self.photo {
+            lines.insert(Slot::Photo, self.single_line("PHOTO", photo.clone()));
+        }
+
+        if let Some(rev) = &self.rev {
+            lines.insert(Slot::Rev, self.single_line("REV", rev.clone()));
+        }
+
+        for (i, prop) in self.properties.iter().enumerate() {
+            lines.insert(Slot::Property(i), prop.clone());
+        }
+
+        lines
+    }
+
+    /// Splits [`Contact::categories`] back into the parsed
+    /// `CATEGORIES` lines, along with their group and parameters.
+    ///
+    /// Categories added since go to the last line; lines left without
+    /// category are dropped.
+    fn category_lines(&self) -> Vec<(&[String], PropertyExtra)> {
+        let mut lines = Vec::new();
+        let mut rest = self.categories.as_slice();
+
+        for (i, (count, extra)) in self.category_lines.iter().enumerate() {
+            let count = if i + 1 == self.category_lines.len() {
+                rest.len()
+            } else {
+                (*count).min(rest.len())
+            };
+
+            let (categories, tail) = rest.split_at(count);
+            rest = tail;
+
+            if !categories.is_empty() {
+                lines.push((categories, extra.clone()));
+            }
+        }
+
+        if !rest.is_empty() {
+            lines.push((rest, PropertyExtra::default()));
+        }
+
+        lines
+    }
+}
+
+impl fmt::Display for Contact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (begin, end) = match &self.raw_delimiters {
+            Some((begin, end)) => (begin.as_str(), end.as_str()),
+            None => ("BEGIN:VCARD\r\n", "END:VCARD\r\n"),
+        };
+
+        let eol = if begin.ends_with("\r\n") || !begin.ends_with('\n') {
+            "\r\n"
+        } else {
+            "\n"
+        };
+
+        let raw_lines: BTreeMap<_, _> = self.layout.iter().zip(&self.raw_lines).collect();
+
+        f.write_str(begin)?;
+
+        for (slot, prop) in self.ordered_lines() {
+            let Some(raw) = raw_lines.get(&slot) else {
+                write_folded(f, &prop.to_line(), eol)?;
+                continue;
+            };
+
+            if raw.rendered == prop {
+                f.write_str(&raw.text)?;
+                continue;
+            }
+
+            // NOTE: the value changed but the group and parameters did
+            // not, so the original ones are written back as found.
+            let original = unfold(&raw.text)
+                .first()
+                .and_then(|(line, _)| Property::parse(line).ok())
+                .filter(|_| raw.rendered.group == prop.group)
+                .filter(|_| raw.rendered.params == prop.params);
+
+            let line = match original {
+                Some(original) => Property {
+                    value: prop.value,
+                    ..original
+                },
+                None => prop,
+            };
+
+            write_folded(f, &line.to_line(), eol)?;
+        }
+
+        f.write_str(end)
+    }
+}
+
+/// Parses the content lines of a single vCard, without the
+/// `BEGIN:VCARD` / `END:VCARD` delimiters.
+pub(crate) fn parse_properties(s: &str) -> Result<Vec<Property>, ParseContactError> {
+    let (_, props, _) = parse_lines(s)?;
+    Ok(props.into_iter().map(|(prop, _)| prop).collect())
+}
+
+/// Parses the content lines of a single vCard, each along with its
+/// original text. Also returns the original text of the
+/// `BEGIN:VCARD` and `END:VCARD` lines.
+#[allow(clippy::type_complexity)]
+fn parse_lines(s: &str) -> Result<(&str, Vec<(Property, &str)>, &str), ParseContactError> {
+    let mut lines = unfold(s).into_iter();
+
+    let begin = match lines.next() {
+        Some((line, text)) if line.trim().eq_ignore_ascii_case("BEGIN:VCARD") => text,
+        _ => return Err(ParseContactError::MissingBegin),
+    };
+
+    let mut props = Vec::new();
+
+    for (line, text) in lines {
+        if line.trim().eq_ignore_ascii_case("END:VCARD") {
+            return Ok((begin, props, text));
+        }
+
+        props.push((Property::parse(&line)?, text));
+    }
+
+    Err(ParseContactError::MissingEnd)
+}
+
+/// Joins folded lines (RFC 6350 §3.2): a line break followed by a
+/// space or a tab continues the previous line. Blank lines are
+/// skipped.
+///
+/// Each line comes with its original text: folding and line
+/// terminator included, along with the blank lines following it (or
+/// preceding it, for the first line).
+fn unfold(s: &str) -> Vec<(String, &str)> {
+    let mut lines: Vec<(String, usize, usize)> = Vec::new();
+    let mut end = 0;
+
+    for raw in s.split_inclusive('\n') {
+        let start = end;
+        end += raw.len();
+
+        let line = raw.strip_suffix('\n').unwrap_or(raw);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        match lines.last_mut() {
+            Some((unfolded, _, last_end)) if line.starts_with([' ', '\t']) => {
+                unfolded.push_str(&line[1..]);
+                *last_end = end;
+            }
+            Some((_, _, last_end)) if line.trim().is_empty() => *last_end = end,
+            None if line.trim().is_empty() => (),
+            None => lines.push((line.to_string(), 0, end)),
+            Some(_) => lines.push((line.to_string(), start, end)),
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|(line, start, end)| (line, &s[start..end]))
+        .collect()
+}
+
+/// Writes `line` folded at 75 octets, without splitting UTF-8
+/// characters, followed by `eol`.
+fn write_folded(f: &mut fmt::Formatter<'_>, line: &str, eol: &str) -> fmt::Result {
+    const MAX: usize = 75;
+
+    let mut width = 0;
+
+    for c in line.chars() {
+        // NOTE: continuation lines start with a space, which counts
+        // toward the limit.
+        if width + c.len_utf8() > MAX {
+            f.write_str(eol)?;
+            fmt::Write::write_char(f, ' ')?;
+            width = 1;
+        }
+
+        fmt::Write::write_char(f, c)?;
+        width += c.len_utf8();
+    }
+
+    f.write_str(eol)
+}
+
+/// Splits `s` at the first `sep` found outside double quotes.
+fn split_unquoted(s: &str, sep: char) -> Option<(&str, &str)> {
+    let mut quoted = false;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c == sep && !quoted => return Some((&s[..i], &s[i + 1..])),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Splits `s` at every `sep` found outside double quotes.
+fn split_all_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = s;
+
+    while let Some((head, tail)) = split_unquoted(rest, sep) {
+        parts.push(head);
+        rest = tail;
+    }
+
+    parts.push(rest);
+    parts
+}
+
+/// Splits `s` at every `sep` not preceded by a backslash escape.
+fn split_all_unescaped(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == sep => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Splits a structured value (`N`, `ADR`, `ORG`) into its unescaped
+/// components.
+fn split_components(value: &str) -> Vec<String> {
+    split_all_unescaped(value, ';')
+        .into_iter()
+        .map(unescape)
+        .collect()
+}
+
+/// Joins components into an escaped structured value.
+///
+/// Commas are left unescaped: inside a component they separate list
+/// values.
+fn join_components(components: &[&str]) -> String {
+    components
+        .iter()
+        .map(|c| escape(c).replace("\\,", ","))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Unescapes a vCard text value (RFC 6350 §3.4).
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+
+    out
+}
+
+/// Escapes a vCard text value (RFC 6350 §3.4).
+pub(crate) fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            ',' => out.push_str("\\,"),
+            ';' => out.push_str("\\;"),
+            '\r' => (),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Tells whether the value of a typed property is a URI (`VALUE=uri`),
+/// written verbatim rather than as an escaped text.
+fn is_uri(extra: &PropertyExtra) -> bool {
+    extra
+        .params
+        .iter()
+        .any(|p| p.name == "VALUE" && p.values.iter().any(|v| v.eq_ignore_ascii_case("uri")))
+}
+
+/// Extracts the `TYPE` and `PREF` parameters of a multi-valued typed
+/// property, keeping the others aside.
+fn typed_params(prop: &Property) -> (Vec<String>, Option<u8>, PropertyExtra) {
+    let mut types = Vec::new();
+    let mut pref = None;
+    let mut params = Vec::new();
+
+    for param in &prop.params {
+        match param.name.as_str() {
+            "TYPE" => types.extend(param.values.iter().map(|v| v.to_ascii_lowercase())),
+            "PREF" => pref = param.values.first().and_then(|v| v.parse().ok()),
+            _ => params.push(param.clone()),
+        }
+    }
+
+    let extra = PropertyExtra {
+        group: prop.group.clone(),
+        params,
+    };
+
+    (types, pref, extra)
+}
+
+/// Builds the content line of a multi-valued typed property.
+fn typed_line(
+    name: &str,
+    value: String,
+    types: &[String],
+    pref: Option<u8>,
+    extra: &PropertyExtra,
+) -> Property {
+    let mut params = Vec::new();
+
+    if !types.is_empty() {
+        params.push(Parameter {
+            name: "TYPE".to_string(),
+            values: types.to_vec(),
+        });
+    }
+
+    if let Some(pref) = pref {
+        params.push(Parameter {
+            name: "PREF".to_string(),
+            values: alloc::vec![pref.to_string()],
+        });
+    }
+
+    params.extend(extra.params.iter().cloned());
+
+    Property {
+        group: extra.group.clone(),
+        name: name.to_string(),
+        params,
+        value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    /// Joins `lines` as a CRLF-terminated vCard.
+    fn vcard(lines: &[&str]) -> String {
+        let mut s = String::from("BEGIN:VCARD\r\n");
+
+        for line in lines {
+            s.push_str(line);
+            s.push_str("\r\n");
+        }
+
+        s.push_str("END:VCARD\r\n");
+        s
+    }
+
+    #[test]
+    fn round_trip_apple_vcard() {
+        let s = vcard(&[
+            "VERSION:3.0",
+            "PRODID:-//Apple Inc.//iPhone OS 17.0//EN",
+            "N:Appleseed;John;;;",
+            "FN:John Appleseed",
+            "ORG:Apple Inc.;",
+            "item1.EMAIL;TYPE=internet,work;PREF=1:john@example.com",
+            "item1.X-ABLABEL:_$!<Work>!$_",
+            "TEL;TYPE=cell,voice:+1 555 0100",
+            "item2.ADR;TYPE=home:;;1 Infinite Loop;Cupertino;CA;95014;United States",
+            "item2.X-ABADR:us",
+            "NOTE:Met at WWDC\\, 2023",
+            "CATEGORIES:friends,work",
+            "X-SOCIALPROFILE;TYPE=twitter:https://twitter.com/jappleseed",
+            "REV:2023-06-05T10:00:00Z",
+            "UID:7B2F3C7A-0000-4000-8000-000000000001",
+        ]);
+
+        let contact = Contact::parse(&s).unwrap();
+
+        assert_eq!(contact.formatted_name.as_deref(), Some("John Appleseed"));
+        assert_eq!(contact.emails[0].extra.group.as_deref(), Some("item1"));
+        assert_eq!(contact.notes, vec!["Met at WWDC, 2023".to_string()]);
+        assert_eq!(contact.to_string(), s);
+    }
+
+    #[test]
+    fn round_trip_vcard4_with_repeats() {
+        let s = vcard(&[
+            "VERSION:4.0",
+            "UID:urn:uuid:4fbe8971-0bc3-424c-9c26-36c3e1eff6b1",
+            "FN;LANGUAGE=en:Jane Doe",
+            "FN;LANGUAGE=fr:Jeanne Doe",
+            "N:Doe;Jane;;Dr.;",
+            "X-PHONETIC-FIRST-NAME:jein",
+            "TEL;TYPE=work;VALUE=uri:tel:+1-555-555-5555;ext=5555",
+            "EMAIL;TYPE=home:jane@example.org",
+            "NOTE;LANGUAGE=en:first line\\nsecond line",
+            "g1.NOTE:grouped note",
+            "CATEGORIES;PID=1:colleagues,climbing",
+            "CATEGORIES:book club",
+            "BDAY:--0412",
+        ]);
+
+        let contact = Contact::parse(&s).unwrap();
+
+        assert_eq!(contact.formatted_name.as_deref(), Some("Jane Doe"));
+        assert_eq!(contact.properties[0].value, "Jeanne Doe");
+        assert_eq!(contact.phones[0].number, "tel:+1-555-555-5555;ext=5555");
+        assert_eq!(contact.notes[0], "first line\nsecond line");
+        assert_eq!(contact.categories.len(), 3);
+        assert_eq!(contact.to_string(), s);
+    }
+
+    #[test]
+    fn escape_text_phone() {
+        let s = vcard(&["VERSION:4.0", "TEL:+1 555\\; ext 2"]);
+        let contact = Contact::parse(&s).unwrap();
+
+        assert_eq!(contact.phones[0].number, "+1 555; ext 2");
+        assert_eq!(contact.to_string(), s);
+    }
+
+    #[test]
+    fn keep_repeated_single_properties() {
+        let s = vcard(&[
+            "VERSION:3.0",
+            "UID:first",
+            "UID:second",
+            "TITLE;LANGUAGE=en:Engineer",
+            "TITLE;LANGUAGE=de:Ingenieur",
+            "REV:1",
+            "REV:2",
+        ]);
+
+        let contact = Contact::parse(&s).unwrap();
+
+        assert_eq!(contact.uid.as_deref(), Some("first"));
+        assert_eq!(contact.title.as_deref(), Some("Engineer"));
+        assert_eq!(contact.properties.len(), 3);
+        assert_eq!(contact.to_string(), s);
+    }
+
+    #[test]
+    fn append_edits_after_parsed_lines() {
+        let s = vcard(&[
+            "VERSION:4.0",
+            "X-FIRST:1",
+            "FN:Jane",
+            "CATEGORIES;PID=1:a,b",
+            "CATEGORIES:c",
+            "X-LAST:2",
+        ]);
+
+        let mut contact = Contact::parse(&s).unwrap();
+        contact.formatted_name = Some("Jane Doe".to_string());
+        contact.emails.push(Email {
+            address: "jane@example.org".to_string(),
+            ..Default::default()
+        });
+        contact.categories.push("d".to_string());
+
+        let expected = vcard(&[
+            "VERSION:4.0",
+            "X-FIRST:1",
+            "FN:Jane Doe",
+            "CATEGORIES;PID=1:a,b",
+            "CATEGORIES:c,d",
+            "X-LAST:2",
+            "EMAIL:jane@example.org",
+        ]);
+
+        assert_eq!(contact.to_string(), expected);
+    }
+
+    #[test]
+    fn round_trip_google_vcard_byte_for_byte() {
+        let s = "BEGIN:VCARD\nVERSION:3.0\nFN:Alex Smith\nN:Smith;Alex;;;\n\
+                 EMAIL;TYPE=INTERNET;TYPE=HOME:alex@example.net\n\
+                 TEL;HOME:+44 20 7946 0000\nitem1.URL:https://example.net\n\
+                 item1.X-ABLabel:blog\nNOTE:a note folded\n  over two lines\n\
+                 \nEND:VCARD\n";
+
+        let contact = Contact::parse(s).unwrap();
+
+        assert_eq!(contact.emails[0].types, ["internet", "home"]);
+        assert_eq!(contact.phones[0].types, ["home"]);
+        assert_eq!(contact.notes[0], "a note folded over two lines");
+        assert_eq!(contact.to_bytes(), s.as_bytes());
+    }
+
+    #[test]
+    fn reserialize_edited_lines_only() {
+        let s = "BEGIN:VCARD\nVERSION:3.0\nFN:Alex Smith\n\
+                 EMAIL;TYPE=INTERNET;TYPE=HOME:alex@example.net\n\
+                 TEL;HOME:+44 20 7946 0000\nitem1.X-ABLabel:blog\nEND:VCARD\n";
+
+        let mut contact = Contact::parse(s).unwrap();
+        contact.emails[0].address = "alex@example.org".to_string();
+        contact.phones[0].types = vec!["work".to_string()];
+
+        let expected = "BEGIN:VCARD\nVERSION:3.0\nFN:Alex Smith\n\
+                        EMAIL;TYPE=INTERNET;TYPE=HOME:alex@example.org\n\
+                        TEL;TYPE=work:+44 20 7946 0000\nitem1.X-ABLabel:blog\n\
+                        END:VCARD\n";
+
+        assert_eq!(contact.to_string(), expected);
+    }
+
+    #[test]
+    fn fold_long_lines() {
+        let note = "x".repeat(200);
+        let mut contact = Contact::new();
+        contact.notes.push(note.clone());
+
+        let s = contact.to_string();
+
+        assert!(s.lines().all(|line| line.len() <= 75));
+        assert_eq!(Contact::parse(&s).unwrap().notes, vec![note]);
+    }
+}
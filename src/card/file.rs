@@ -0,0 +1,132 @@
+//! Helpers shared by the single-file backends (`.vcf` file, abook,
+//! mutt alias): the atomic file rewrite and the `UID` checks of an
+//! in-place card update.
+
+use alloc::format;
+#[cfg(any(feature = "vcf-file", feature = "abook"))]
+use alloc::string::{String, ToString};
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+#[cfg(any(feature = "vcf-file", feature = "abook"))]
+use crate::card::uid::card_id_from_uid;
+
+/// Rewrites the file at `path` with `data`, atomically: the data goes
+/// to a temporary sibling file, synced, then renamed over `path`. The
+/// temporary file is removed when the rename fails.
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(format!(".{}", tmp_name.to_string_lossy()));
+
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+
+    if let Err(err) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Errors produced by [`update_uid`], converted into the matching
+/// variants of each backend error.
+#[cfg(any(feature = "vcf-file", feature = "abook"))]
+#[derive(Debug)]
+pub(crate) enum UpdateUidError {
+    /// The `UID` of the contents maps to another card id.
+    Mismatch { card_id: String, uid_id: String },
+    /// The card has no `UID` and its id cannot serve as one.
+    InvalidCardId(String),
+}
+
+/// Checks the `UID` of the contents overwriting `card_id`, so that the
+/// card keeps its id.
+///
+/// A `uid` mapping to `card_id`, or equal to the `current_uid` of the
+/// card (escaped bytes, disambiguated duplicate), is kept: returns
+/// `None`. Contents without `UID` get the current `UID` of the card,
+/// or `card_id` itself when it maps back to itself: returns the `UID`
+/// to inject.
+#[cfg(any(feature = "vcf-file", feature = "abook"))]
+pub(crate) fn update_uid(
+    card_id: &str,
+    uid: Option<&str>,
+    current_uid: Option<&str>,
+) -> Result<Option<String>, UpdateUidError> {
+    match uid.filter(|uid| !uid.trim().is_empty()) {
+        Some(uid) if card_id_from_uid(uid) != card_id && current_uid != Some(uid) => {
+            Err(UpdateUidError::Mismatch {
+                card_id: card_id.to_string(),
+                uid_id: card_id_from_uid(uid),
+            })
+        }
+        Some(_) => Ok(None),
+        // NOTE: the current UID maps back to the card id, whereas the
+        // id itself may not (escaped bytes, disambiguated duplicate).
+        None => match current_uid {
+            Some(uid) => Ok(Some(uid.to_string())),
+            None if card_id_from_uid(card_id) == card_id => Ok(Some(card_id.to_string())),
+            None => Err(UpdateUidError::InvalidCardId(card_id.to_string())),
+        },
+    }
+}
+
+#[cfg(all(test, any(feature = "vcf-file", feature = "abook")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_uid_keeps_matching_uid() {
+        assert!(matches!(update_uid("abc", Some("abc"), None), Ok(None)));
+        assert!(matches!(
+            update_uid("abc-2", Some("abc"), Some("abc")),
+            Ok(None)
+        ));
+    }
+
+    #[test]
+    fn update_uid_rejects_foreign_uid() {
+        let err = update_uid("abc", Some("xyz"), Some("abc")).unwrap_err();
+        assert!(matches!(
+            err,
+            UpdateUidError::Mismatch { card_id, uid_id } if card_id == "abc" && uid_id == "xyz"
+        ));
+    }
+
+    #[test]
+    fn update_uid_injects_current_uid_or_id() {
+        assert_eq!(
+            update_uid("a=20b", None, Some("a b")).unwrap().as_deref(),
+            Some("a b")
+        );
+        assert_eq!(
+            update_uid("abc", Some(" "), None).unwrap().as_deref(),
+            Some("abc")
+        );
+        assert!(matches!(
+            update_uid("a=20b", None, None),
+            Err(UpdateUidError::InvalidCardId(id)) if id == "a=20b"
+        ));
+    }
+
+    #[test]
+    fn write_atomic_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("io-addressbook-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cards.vcf");
+
+        write_atomic(&path, b"old").unwrap();
+        write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
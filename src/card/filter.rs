@@ -0,0 +1,202 @@
+//! Card filter shared across all protocols.
+//!
+//! [`CardFilter`] mirrors the RFC 6352 §10.5 `CARDDAV:filter`
+//! element: CardDAV sends it as an addressbook-query REPORT, other
+//! backends evaluate it locally via [`CardFilter::matches`].
+
+use alloc::{string::String, vec::Vec};
+
+use crate::card::{Card, Property, contact::parse_properties};
+
+/// How a list of tests combines.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum FilterTest {
+    /// At least one test must match (`anyof`).
+    #[default]
+    AnyOf,
+    /// Every test must match (`allof`).
+    AllOf,
+}
+
+/// Where the searched text must appear in the property value.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum MatchType {
+    Equals,
+    #[default]
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+/// How characters compare (RFC 4790 collations).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Collation {
+    /// `i;unicode-casemap`: case-insensitive over Unicode.
+    #[default]
+    UnicodeCasemap,
+    /// `i;ascii-casemap`: case-insensitive over ASCII letters only.
+    AsciiCasemap,
+    /// `i;octet`: exact byte comparison.
+    Octet,
+}
+
+/// Text comparison applied to a property value.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct TextMatch {
+    pub text: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub match_type: MatchType,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub collation: Collation,
+    /// Inverts the result (`negate-condition="yes"`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub negate: bool,
+}
+
+/// Test applied to every property named `name`.
+///
+/// With no text match, only checks that the property exists (or, with
+/// `is_not_defined`, that it does not). `is_not_defined` takes
+/// precedence: text matches are then ignored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct PropFilter {
+    /// Property name, e.g. `FN`, `EMAIL`.
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub test: FilterTest,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub text_matches: Vec<TextMatch>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub is_not_defined: bool,
+}
+
+/// Card search filter.
+///
+/// An empty filter matches every card.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct CardFilter {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub test: FilterTest,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub props: Vec<PropFilter>,
+}
+
+impl TextMatch {
+    /// Builds a case-insensitive substring match on `text`.
+    pub fn contains(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Tests `value` (an unescaped property value) against the match.
+    pub fn matches(&self, value: &str) -> bool {
+        let (value, text) = match self.collation {
+            Collation::UnicodeCasemap => (value.to_lowercase(), self.text.to_lowercase()),
+            Collation::AsciiCasemap => (value.to_ascii_lowercase(), self.text.to_ascii_lowercase()),
+            Collation::Octet => (value.into(), self.text.clone()),
+        };
+
+        let found = match self.match_type {
+            MatchType::Equals => value == text,
+            MatchType::Contains => value.contains(&text),
+            MatchType::StartsWith => value.starts_with(&text),
+            MatchType::EndsWith => value.ends_with(&text),
+        };
+
+        found != self.negate
+    }
+}
+
+impl PropFilter {
+    /// Builds a filter matching cards whose `name` property contains
+    /// `text`, case-insensitively.
+    pub fn contains(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            text_matches: alloc::vec![TextMatch::contains(text)],
+            ..Default::default()
+        }
+    }
+
+    /// Tests the properties of one card against the filter.
+    pub fn matches(&self, props: &[Property]) -> bool {
+        let mut props = props
+            .iter()
+            .filter(|p| p.name.eq_ignore_ascii_case(&self.name))
+            .peekable();
+
+        if self.is_not_defined {
+            return props.peek().is_none();
+        }
+
+        props.any(|prop| {
+            let value = prop.text();
+            let mut results = self.text_matches.iter().map(|m| m.matches(&value));
+
+            match self.test {
+                _ if self.text_matches.is_empty() => true,
+                FilterTest::AnyOf => results.any(|r| r),
+                FilterTest::AllOf => results.all(|r| r),
+            }
+        })
+    }
+}
+
+impl CardFilter {
+    /// Builds a filter matching cards that pass any of `props`.
+    pub fn any(props: impl IntoIterator<Item = PropFilter>) -> Self {
+        Self {
+            test: FilterTest::AnyOf,
+            props: props.into_iter().collect(),
+        }
+    }
+
+    /// Builds a filter matching cards that pass all of `props`.
+    pub fn all(props: impl IntoIterator<Item = PropFilter>) -> Self {
+        Self {
+            test: FilterTest::AllOf,
+            props: props.into_iter().collect(),
+        }
+    }
+
+    /// Evaluates the filter locally against `card`. Cards that do not
+    /// parse as vCard never match.
+    pub fn matches(&self, card: &Card) -> bool {
+        let Ok(contents) = core::str::from_utf8(&card.contents) else {
+            return false;
+        };
+
+        match parse_properties(contents) {
+            Ok(props) => self.matches_properties(&props),
+            Err(_) => false,
+        }
+    }
+
+    /// Evaluates the filter against already parsed properties.
+    pub fn matches_properties(&self, props: &[Property]) -> bool {
+        if self.props.is_empty() {
+            return true;
+        }
+
+        let mut results = self.props.iter().map(|f| f.matches(props));
+
+        match self.test {
+            FilterTest::AnyOf => results.any(|r| r),
+            FilterTest::AllOf => results.all(|r| r),
+        }
+    }
+}
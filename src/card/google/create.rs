@@ -0,0 +1,98 @@
+//! Google People card create coroutine, a `people:createContact`
+//! with the People rendering of a vCard.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.create_card("myContacts", contents)?;
+//! ```
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::{CardRef, Contact, ParseContactError},
+    google::{
+        convert::{MY_CONTACTS, card_ref_from_person, endpoint, from_value},
+        person::{PERSON_FIELDS, Person},
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleCardCreate`].
+#[derive(Debug, Error)]
+pub enum GoogleCardCreateError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+    #[error("Card body is empty")]
+    EmptyCardBody,
+    #[error("Invalid card: {0}")]
+    InvalidCard(ParseContactError),
+}
+
+/// I/O-free coroutine creating a Google person.
+///
+/// The server assigns the person id, so the create options do not
+/// apply. On completion returns the new id and `etag`.
+pub struct GoogleCardCreate {
+    inner: GoogleSend,
+}
+
+impl GoogleCardCreate {
+    /// Builds the coroutine creating a person from the vCard
+    /// `contents` inside `addressbook_id`, rejecting an empty or
+    /// invalid body.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        contents: &[u8],
+    ) -> Result<Self, GoogleCardCreateError> {
+        trace!("prepare google card create");
+
+        if contents.is_empty() {
+            return Err(GoogleCardCreateError::EmptyCardBody);
+        }
+
+        let contact = Contact::from_bytes(contents).map_err(GoogleCardCreateError::InvalidCard)?;
+        let mut person = Person::from_contact(&contact);
+
+        // NOTE: every created contact lands in `myContacts`, which
+        // cannot be named as a membership.
+        if addressbook_id != MY_CONTACTS {
+            person.add_to_group(addressbook_id);
+        }
+
+        let url = endpoint(
+            base_url,
+            "people:createContact",
+            &[("personFields", PERSON_FIELDS)],
+        );
+        let body = serde_json::to_value(&person).unwrap_or_default();
+
+        Ok(Self {
+            inner: GoogleSend::post(&url, token, user_agent, &body),
+        })
+    }
+}
+
+impl HttpCoroutine for GoogleCardCreate {
+    type Yield = HttpYield;
+    type Return = Result<CardRef, GoogleCardCreateError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => {
+                let result = from_value::<Person>(body)
+                    .map(|person| card_ref_from_person(&person))
+                    .map_err(Into::into);
+                HttpCoroutineState::Complete(result)
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
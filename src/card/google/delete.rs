@@ -0,0 +1,145 @@
+//! Google People card delete coroutine, a `GET` on the person then a
+//! `people/{id}:deleteContact`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! client.delete_card("myContacts", "c1234", Some(&etag))?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::google::get::GooglePersonGet,
+    google::{
+        convert::{endpoint, etag_matches, is_valid_id},
+        person::person_resource_name,
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleCardDelete`].
+#[derive(Debug, Error)]
+pub enum GoogleCardDeleteError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{card_id}` changed on the server (current ETag: {etag:?})")]
+    PreconditionFailed {
+        card_id: String,
+        etag: Option<String>,
+    },
+}
+
+enum State {
+    Get(GooglePersonGet),
+    Delete(GoogleSend),
+}
+
+/// I/O-free coroutine permanently deleting a Google person, from
+/// every contact group.
+///
+/// `people/{id}:deleteContact` takes no `etag`: the person is fetched
+/// first to check its membership and `if_match`.
+pub struct GoogleCardDelete {
+    url: Url,
+    token: String,
+    user_agent: String,
+    id: String,
+    if_match: Option<String>,
+    state: State,
+}
+
+impl GoogleCardDelete {
+    /// Builds the coroutine deleting `card_id` from `addressbook_id`,
+    /// rejecting an invalid id.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<Self, GoogleCardDeleteError> {
+        trace!("prepare google card delete");
+
+        if !is_valid_id(card_id) {
+            return Err(GoogleCardDeleteError::InvalidCardId(card_id.to_string()));
+        }
+
+        let path = [&person_resource_name(card_id), ":deleteContact"].concat();
+        let get = GooglePersonGet::new(base_url, token, user_agent, addressbook_id, card_id);
+
+        Ok(Self {
+            url: endpoint(base_url, &path, &[]),
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            id: card_id.to_string(),
+            if_match: if_match.map(ToString::to_string),
+            state: State::Get(get),
+        })
+    }
+}
+
+impl HttpCoroutine for GoogleCardDelete {
+    type Yield = HttpYield;
+    type Return = Result<(), GoogleCardDeleteError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match &mut self.state {
+                State::Get(get) => {
+                    let current = match get.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => {
+                            return HttpCoroutineState::Yielded(y);
+                        }
+                        HttpCoroutineState::Complete(Ok(Some(current))) => current,
+                        HttpCoroutineState::Complete(Ok(None)) => {
+                            let err = GoogleCardDeleteError::CardNotFound(self.id.clone());
+                            return HttpCoroutineState::Complete(Err(err));
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            return HttpCoroutineState::Complete(Err(err.into()));
+                        }
+                    };
+
+                    let etag = current.etag.unwrap_or_default();
+
+                    if let Some(if_match) = &self.if_match {
+                        if !etag_matches(if_match, &etag) {
+                            let err = GoogleCardDeleteError::PreconditionFailed {
+                                card_id: self.id.clone(),
+                                etag: Some(etag),
+                            };
+                            return HttpCoroutineState::Complete(Err(err));
+                        }
+                    }
+
+                    let send = GoogleSend::delete(&self.url, &self.token, &self.user_agent);
+                    self.state = State::Delete(send);
+                }
+                State::Delete(send) => {
+                    return match send.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+                        HttpCoroutineState::Complete(Ok(_)) => HttpCoroutineState::Complete(Ok(())),
+                        HttpCoroutineState::Complete(Err(err)) if err.is_status(404) => {
+                            let err = GoogleCardDeleteError::CardNotFound(self.id.clone());
+                            HttpCoroutineState::Complete(Err(err))
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            HttpCoroutineState::Complete(Err(err.into()))
+                        }
+                    };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Google People card get coroutine, a `GET` on one person.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.get_card("myContacts", "c1234")?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::Card,
+    google::{
+        convert::{card_from_person, endpoint, from_value, is_valid_id},
+        person::{PERSON_FIELDS, Person, person_resource_name},
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleCardGet`].
+#[derive(Debug, Error)]
+pub enum GoogleCardGetError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+}
+
+/// I/O-free coroutine fetching a single Google person.
+///
+/// A person outside the contact group counts as missing. On
+/// completion maps the person to a [`Card`].
+pub struct GoogleCardGet {
+    addressbook_id: String,
+    id: String,
+    inner: GooglePersonGet,
+}
+
+impl GoogleCardGet {
+    /// Builds the coroutine fetching `card_id` from `addressbook_id`,
+    /// rejecting an invalid id.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Self, GoogleCardGetError> {
+        trace!("prepare google card get");
+
+        if !is_valid_id(card_id) {
+            return Err(GoogleCardGetError::InvalidCardId(card_id.to_string()));
+        }
+
+        Ok(Self {
+            addressbook_id: addressbook_id.to_string(),
+            id: card_id.to_string(),
+            inner: GooglePersonGet::new(base_url, token, user_agent, addressbook_id, card_id),
+        })
+    }
+}
+
+impl HttpCoroutine for GoogleCardGet {
+    type Yield = HttpYield;
+    type Return = Result<Card, GoogleCardGetError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(Some(person))) => {
+                let card = card_from_person(&self.addressbook_id, &person);
+                HttpCoroutineState::Complete(Ok(card))
+            }
+            HttpCoroutineState::Complete(Ok(None)) => {
+                let err = GoogleCardGetError::CardNotFound(self.id.clone());
+                HttpCoroutineState::Complete(Err(err))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
+
+/// I/O-free coroutine fetching a person of a contact group.
+///
+/// On completion returns `None` when the person does not exist or is
+/// outside the group.
+pub(crate) struct GooglePersonGet {
+    addressbook_id: String,
+    inner: GoogleSend,
+}
+
+impl GooglePersonGet {
+    pub(crate) fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Self {
+        let path = person_resource_name(card_id);
+        let url = endpoint(base_url, &path, &[("personFields", PERSON_FIELDS)]);
+
+        Self {
+            addressbook_id: addressbook_id.to_string(),
+            inner: GoogleSend::get(&url, token, user_agent),
+        }
+    }
+}
+
+impl HttpCoroutine for GooglePersonGet {
+    type Yield = HttpYield;
+    type Return = Result<Option<Person>, GoogleSendError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        let result = match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => from_value::<Person>(body)
+                .map(|person| Some(person).filter(|p| p.in_group(&self.addressbook_id))),
+            HttpCoroutineState::Complete(Err(err)) if err.is_status(404) => Ok(None),
+            HttpCoroutineState::Complete(Err(err)) => Err(err),
+        };
+
+        HttpCoroutineState::Complete(result)
+    }
+}
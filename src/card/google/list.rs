@@ -0,0 +1,200 @@
+//! Google People card list coroutine, a paged
+//! `GET people/me/connections` filtered on the contact group.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let cards = client.list_cards("myContacts", None, None)?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::Card,
+    google::{
+        convert::{PAGE_SIZE, card_from_person, endpoint, from_value, paginate},
+        person::{PERSON_FIELDS, Person},
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleCardList`].
+#[derive(Debug, Error)]
+pub enum GoogleCardListError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+}
+
+/// I/O-free coroutine listing the cards of a Google contact group.
+///
+/// The People API cannot list the people of a group along with their
+/// fields, so every connection is fetched and filtered on its
+/// memberships; pagination is then applied in memory, in the server
+/// order. On completion maps each person to a [`Card`].
+pub struct GoogleCardList {
+    addressbook_id: String,
+    page: Option<u32>,
+    page_size: Option<u32>,
+    inner: GooglePeoplePager,
+}
+
+impl GoogleCardList {
+    /// Builds the coroutine listing the cards of `addressbook_id`,
+    /// applying 1-indexed pagination.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Self {
+        trace!("prepare google card list");
+
+        Self {
+            addressbook_id: addressbook_id.to_string(),
+            page,
+            page_size,
+            inner: GooglePeoplePager::new(base_url, token, user_agent, None),
+        }
+    }
+}
+
+impl HttpCoroutine for GoogleCardList {
+    type Yield = HttpYield;
+    type Return = Result<Vec<Card>, GoogleCardListError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok((people, _))) => {
+                let cards = people
+                    .iter()
+                    .filter(|person| person.in_group(&self.addressbook_id))
+                    .map(|person| card_from_person(&self.addressbook_id, person))
+                    .collect();
+                let cards = paginate(cards, self.page, self.page_size);
+                HttpCoroutineState::Complete(Ok(cards))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ConnectionsResponse {
+    connections: Vec<Person>,
+    next_page_token: Option<String>,
+    next_sync_token: Option<String>,
+}
+
+/// I/O-free coroutine fetching every connection of the account, one
+/// page per request.
+///
+/// With a sync token, only the people changed since it are returned,
+/// deleted ones flagged by their metadata. On completion returns the
+/// people along with the sync token of the last page.
+pub(crate) struct GooglePeoplePager {
+    base_url: Url,
+    token: String,
+    user_agent: String,
+    sync_token: Option<String>,
+    people: Vec<Person>,
+    inner: GoogleSend,
+}
+
+impl GooglePeoplePager {
+    /// Builds the coroutine fetching the connections, changed since
+    /// `sync_token` when given.
+    pub(crate) fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        sync_token: Option<&str>,
+    ) -> Self {
+        Self {
+            base_url: base_url.clone(),
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            sync_token: sync_token.map(ToString::to_string),
+            people: Vec::new(),
+            inner: Self::request(base_url, token, user_agent, sync_token, None),
+        }
+    }
+
+    fn request(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        sync_token: Option<&str>,
+        page: Option<&str>,
+    ) -> GoogleSend {
+        let mut query = Vec::from([
+            ("personFields", PERSON_FIELDS),
+            ("pageSize", PAGE_SIZE),
+            ("requestSyncToken", "true"),
+        ]);
+
+        if let Some(sync_token) = sync_token {
+            query.push(("syncToken", sync_token));
+        }
+
+        if let Some(page) = page {
+            query.push(("pageToken", page));
+        }
+
+        let url = endpoint(base_url, "people/me/connections", &query);
+        GoogleSend::get(&url, token, user_agent)
+    }
+}
+
+impl HttpCoroutine for GooglePeoplePager {
+    type Yield = HttpYield;
+    type Return = Result<(Vec<Person>, Option<String>), GoogleSendError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            let body = match self.inner.resume(arg.take()) {
+                HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                HttpCoroutineState::Complete(Ok(body)) => body,
+                HttpCoroutineState::Complete(Err(err)) => {
+                    return HttpCoroutineState::Complete(Err(err));
+                }
+            };
+
+            let page: ConnectionsResponse = match from_value(body) {
+                Ok(page) => page,
+                Err(err) => return HttpCoroutineState::Complete(Err(err)),
+            };
+
+            self.people.extend(page.connections);
+
+            match page.next_page_token.filter(|page| !page.is_empty()) {
+                Some(next) => {
+                    self.inner = Self::request(
+                        &self.base_url,
+                        &self.token,
+                        &self.user_agent,
+                        self.sync_token.as_deref(),
+                        Some(&next),
+                    );
+                }
+                None => {
+                    let people = core::mem::take(&mut self.people);
+                    let sync_token = page.next_sync_token;
+                    return HttpCoroutineState::Complete(Ok((people, sync_token)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Google People card operations: create, delete, get, list,
+//! multiget, sync, update.
+//!
+//! Cards map to people belonging to the contact group of the
+//! addressbook.
+
+pub mod create;
+pub mod delete;
+pub mod get;
+pub mod list;
+pub mod multiget;
+pub mod sync;
+pub mod update;
@@ -0,0 +1,164 @@
+//! Google People card multiget coroutine, `people:batchGet` requests
+//! of up to 200 people each.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let batch = client.get_cards("myContacts", &["c1234", "c5678"])?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::CardBatch,
+    google::{
+        convert::{BATCH_SIZE, card_from_person, endpoint, from_value, is_valid_id},
+        person::{PERSON_FIELDS, Person, person_resource_name},
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleCardMultiget`].
+#[derive(Debug, Error)]
+pub enum GoogleCardMultigetError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct BatchResponse {
+    responses: Vec<PersonResponse>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PersonResponse {
+    person: Option<Person>,
+}
+
+/// I/O-free coroutine fetching several Google people.
+///
+/// People missing or outside the contact group end up in
+/// [`CardBatch::missing`], in request order.
+pub struct GoogleCardMultiget {
+    base_url: Url,
+    token: String,
+    user_agent: String,
+    addressbook_id: String,
+    ids: Vec<String>,
+    position: usize,
+    people: Vec<Person>,
+    inner: Option<GoogleSend>,
+}
+
+impl GoogleCardMultiget {
+    /// Builds the coroutine fetching `card_ids` from `addressbook_id`,
+    /// rejecting any invalid id.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<Self, GoogleCardMultigetError> {
+        trace!("prepare google card multiget");
+
+        if let Some(id) = card_ids.iter().find(|id| !is_valid_id(id)) {
+            return Err(GoogleCardMultigetError::InvalidCardId(id.to_string()));
+        }
+
+        let mut coroutine = Self {
+            base_url: base_url.clone(),
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            addressbook_id: addressbook_id.to_string(),
+            ids: card_ids.iter().map(ToString::to_string).collect(),
+            position: 0,
+            people: Vec::new(),
+            inner: None,
+        };
+
+        coroutine.inner = coroutine.next_request();
+        Ok(coroutine)
+    }
+
+    /// Builds the request fetching the next batch of ids, if any.
+    fn next_request(&mut self) -> Option<GoogleSend> {
+        let end = self.ids.len().min(self.position + BATCH_SIZE);
+        let names: Vec<String> = self.ids[self.position..end]
+            .iter()
+            .map(|id| person_resource_name(id))
+            .collect();
+
+        if names.is_empty() {
+            return None;
+        }
+
+        self.position = end;
+
+        let mut query = Vec::from([("personFields", PERSON_FIELDS)]);
+        query.extend(names.iter().map(|name| ("resourceNames", name.as_str())));
+
+        let url = endpoint(&self.base_url, "people:batchGet", &query);
+        Some(GoogleSend::get(&url, &self.token, &self.user_agent))
+    }
+
+    fn finish(&mut self) -> CardBatch {
+        let mut batch = CardBatch::default();
+
+        for id in &self.ids {
+            let person = self.people.iter().find(|person| person.id() == id);
+
+            match person.filter(|person| person.in_group(&self.addressbook_id)) {
+                Some(person) => batch
+                    .cards
+                    .push(card_from_person(&self.addressbook_id, person)),
+                None => batch.missing.push(id.clone()),
+            }
+        }
+
+        batch
+    }
+}
+
+impl HttpCoroutine for GoogleCardMultiget {
+    type Yield = HttpYield;
+    type Return = Result<CardBatch, GoogleCardMultigetError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            let Some(inner) = &mut self.inner else {
+                return HttpCoroutineState::Complete(Ok(self.finish()));
+            };
+
+            let body = match inner.resume(arg.take()) {
+                HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                HttpCoroutineState::Complete(Ok(body)) => body,
+                HttpCoroutineState::Complete(Err(err)) => {
+                    return HttpCoroutineState::Complete(Err(err.into()));
+                }
+            };
+
+            let batch: BatchResponse = match from_value(body) {
+                Ok(batch) => batch,
+                Err(err) => return HttpCoroutineState::Complete(Err(err.into())),
+            };
+
+            let people = batch.responses.into_iter().filter_map(|r| r.person);
+            self.people.extend(people);
+            self.inner = self.next_request();
+        }
+    }
+}
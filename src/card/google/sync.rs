@@ -0,0 +1,115 @@
+//! Google People card sync coroutine, `people/me/connections` pages
+//! requested with the sync token of the previous run.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let changes = client.sync_cards("myContacts", Some(&token))?;
+//! ```
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::{CardChanges, google::list::GooglePeoplePager},
+    google::{convert::card_from_person, send::GoogleSendError},
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleCardSync`].
+#[derive(Debug, Error)]
+pub enum GoogleCardSyncError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+    #[error("Sync token `{0}` rejected by the server")]
+    InvalidSyncToken(String),
+}
+
+/// I/O-free coroutine fetching the cards of a Google contact group
+/// changed since a sync token.
+///
+/// Sync tokens cover every contact of the account: people changed
+/// outside the group are reported as deleted, which also covers the
+/// ones removed from it. On completion returns the changed cards
+/// (sorted by id), the deleted ids and the next token. An absent
+/// token yields the full group content, flagged as such. Google lets
+/// tokens expire after a week, surfacing as
+/// [`GoogleCardSyncError::InvalidSyncToken`].
+pub struct GoogleCardSync {
+    addressbook_id: String,
+    sync_token: Option<String>,
+    inner: GooglePeoplePager,
+}
+
+impl GoogleCardSync {
+    /// Builds the coroutine syncing `addressbook_id` from
+    /// `sync_token`, or from scratch when `None`.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Self {
+        trace!("prepare google card sync");
+
+        let sync_token = sync_token.filter(|t| !t.is_empty());
+
+        Self {
+            addressbook_id: addressbook_id.to_string(),
+            sync_token: sync_token.map(ToString::to_string),
+            inner: GooglePeoplePager::new(base_url, token, user_agent, sync_token),
+        }
+    }
+}
+
+impl HttpCoroutine for GoogleCardSync {
+    type Yield = HttpYield;
+    type Return = Result<CardChanges, GoogleCardSyncError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        let (people, next_token) = match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(page)) => page,
+            HttpCoroutineState::Complete(Err(err)) => {
+                let err = match &self.sync_token {
+                    Some(token) if err.is_failed_precondition() || err.is_status(410) => {
+                        GoogleCardSyncError::InvalidSyncToken(token.clone())
+                    }
+                    _ => err.into(),
+                };
+                return HttpCoroutineState::Complete(Err(err));
+            }
+        };
+
+        let mut changed = BTreeMap::new();
+        let mut deleted = BTreeSet::new();
+
+        for person in people {
+            let id = person.id().to_string();
+
+            if !person.metadata.deleted && person.in_group(&self.addressbook_id) {
+                deleted.remove(&id);
+                changed.insert(id, card_from_person(&self.addressbook_id, &person));
+            } else if self.sync_token.is_some() {
+                changed.remove(&id);
+                deleted.insert(id);
+            }
+        }
+
+        let changes = CardChanges {
+            cards: changed.into_values().collect(),
+            deleted: deleted.into_iter().collect(),
+            sync_token: next_token,
+            full: self.sync_token.is_none(),
+        };
+
+        HttpCoroutineState::Complete(Ok(changes))
+    }
+}
@@ -0,0 +1,184 @@
+//! Google People card update coroutine, a `GET` on the person then a
+//! `people/{id}:updateContact` with the People rendering of a vCard.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.update_card("myContacts", "c1234", contents, Some(&etag))?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::{CardRef, Contact, ParseContactError, google::get::GooglePersonGet},
+    google::{
+        convert::{card_ref_from_person, endpoint, etag_matches, from_value, is_valid_id},
+        person::{PERSON_FIELDS, Person, UPDATE_FIELDS, person_resource_name},
+        send::{GoogleSend, GoogleSendError},
+    },
+    http::coroutine::*,
+};
+
+/// Errors produced by [`GoogleCardUpdate`].
+#[derive(Debug, Error)]
+pub enum GoogleCardUpdateError {
+    #[error(transparent)]
+    Send(#[from] GoogleSendError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card body is empty")]
+    EmptyCardBody,
+    #[error("Invalid card: {0}")]
+    InvalidCard(ParseContactError),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{card_id}` changed on the server (current ETag: {etag:?})")]
+    PreconditionFailed {
+        card_id: String,
+        etag: Option<String>,
+    },
+}
+
+enum State {
+    Get(GooglePersonGet),
+    Update(GoogleSend),
+}
+
+/// I/O-free coroutine overwriting an existing Google person.
+///
+/// The update must carry the current `etag` of the person, so the
+/// person is always fetched first: this checks its membership and
+/// `if_match`, and makes a concurrent change fail the update. Other
+/// memberships of the person are kept. On completion returns the
+/// card id and new `etag`.
+pub struct GoogleCardUpdate {
+    url: Url,
+    token: String,
+    user_agent: String,
+    id: String,
+    if_match: Option<String>,
+    person: Person,
+    state: State,
+}
+
+impl GoogleCardUpdate {
+    /// Builds the coroutine overwriting `card_id` inside
+    /// `addressbook_id` with the vCard `contents`, rejecting an invalid
+    /// id or an empty or invalid body.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: &[u8],
+        if_match: Option<&str>,
+    ) -> Result<Self, GoogleCardUpdateError> {
+        trace!("prepare google card update");
+
+        if !is_valid_id(card_id) {
+            return Err(GoogleCardUpdateError::InvalidCardId(card_id.to_string()));
+        }
+
+        if contents.is_empty() {
+            return Err(GoogleCardUpdateError::EmptyCardBody);
+        }
+
+        let contact = Contact::from_bytes(contents).map_err(GoogleCardUpdateError::InvalidCard)?;
+        let mut person = Person::from_contact(&contact);
+        person.resource_name = Some(person_resource_name(card_id));
+
+        let path = [&person_resource_name(card_id), ":updateContact"].concat();
+        let url = endpoint(
+            base_url,
+            &path,
+            &[
+                ("updatePersonFields", UPDATE_FIELDS),
+                ("personFields", PERSON_FIELDS),
+            ],
+        );
+
+        let get = GooglePersonGet::new(base_url, token, user_agent, addressbook_id, card_id);
+
+        Ok(Self {
+            url,
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            id: card_id.to_string(),
+            if_match: if_match.map(ToString::to_string),
+            person,
+            state: State::Get(get),
+        })
+    }
+
+    fn precondition_failed(&self, etag: Option<String>) -> GoogleCardUpdateError {
+        GoogleCardUpdateError::PreconditionFailed {
+            card_id: self.id.clone(),
+            etag,
+        }
+    }
+}
+
+impl HttpCoroutine for GoogleCardUpdate {
+    type Yield = HttpYield;
+    type Return = Result<CardRef, GoogleCardUpdateError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match &mut self.state {
+                State::Get(get) => {
+                    let current = match get.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => {
+                            return HttpCoroutineState::Yielded(y);
+                        }
+                        HttpCoroutineState::Complete(Ok(Some(current))) => current,
+                        HttpCoroutineState::Complete(Ok(None)) => {
+                            let err = GoogleCardUpdateError::CardNotFound(self.id.clone());
+                            return HttpCoroutineState::Complete(Err(err));
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            return HttpCoroutineState::Complete(Err(err.into()));
+                        }
+                    };
+
+                    let etag = current.etag.unwrap_or_default();
+
+                    if let Some(if_match) = &self.if_match {
+                        if !etag_matches(if_match, &etag) {
+                            let err = self.precondition_failed(Some(etag));
+                            return HttpCoroutineState::Complete(Err(err));
+                        }
+                    }
+
+                    self.person.etag = Some(etag);
+
+                    let body = serde_json::to_value(&self.person).unwrap_or_default();
+                    let send = GoogleSend::patch(&self.url, &self.token, &self.user_agent, &body);
+                    self.state = State::Update(send);
+                }
+                State::Update(send) => {
+                    let result = match send.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => {
+                            return HttpCoroutineState::Yielded(y);
+                        }
+                        HttpCoroutineState::Complete(Ok(body)) => from_value::<Person>(body)
+                            .map(|person| card_ref_from_person(&person))
+                            .map_err(Into::into),
+                        HttpCoroutineState::Complete(Err(err))
+                            if err.is_failed_precondition() && self.if_match.is_some() =>
+                        {
+                            Err(self.precondition_failed(None))
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => Err(err.into()),
+                    };
+
+                    return HttpCoroutineState::Complete(result);
+                }
+            }
+        }
+    }
+}
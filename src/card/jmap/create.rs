@@ -0,0 +1,115 @@
+//! JMAP card create coroutine, a `ContactCard/set` creating the
+//! JSContact rendering of a vCard.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.create_card("personal", contents)?;
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use log::trace;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::{
+    card::{CardRef, Contact, ParseContactError},
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{SetResponse, card_ref_from_wire, from_value},
+        jscontact::JsCard,
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapCardCreate`].
+#[derive(Debug, Error)]
+pub enum JmapCardCreateError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+    #[error("Card body is empty")]
+    EmptyCardBody,
+    #[error("Invalid card: {0}")]
+    InvalidCard(ParseContactError),
+}
+
+/// I/O-free coroutine creating a JMAP card.
+///
+/// The server assigns the card id, so the create options do not
+/// apply. On completion returns the new id and the tag of the card as
+/// stored, the properties the server set being merged into the sent
+/// ones.
+pub struct JmapCardCreate {
+    card: JsCard,
+    inner: JmapApi,
+}
+
+impl JmapCardCreate {
+    /// Builds the coroutine creating a card from the vCard `contents`
+    /// inside `addressbook_id`, rejecting an empty or invalid body.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        addressbook_id: &str,
+        contents: &[u8],
+    ) -> Result<Self, JmapCardCreateError> {
+        trace!("prepare jmap card create");
+
+        if contents.is_empty() {
+            return Err(JmapCardCreateError::EmptyCardBody);
+        }
+
+        let contact = Contact::from_bytes(contents).map_err(JmapCardCreateError::InvalidCard)?;
+        let card = JsCard::from_contact(&contact);
+
+        let calls = Vec::from([invocation(
+            "ContactCard/set",
+            json!({
+                "accountId": session.account_id,
+                "create": { "new": card.to_create(addressbook_id) },
+            }),
+            "0",
+        )]);
+
+        Ok(Self {
+            card,
+            inner: JmapApi::new(session, auth, user_agent, calls),
+        })
+    }
+
+    fn finish(&self, mut responses: JmapResponses) -> Result<CardRef, JmapCardCreateError> {
+        let set: SetResponse = from_value(responses.take("0")?)?;
+
+        if let Some(err) = set.create_error("new") {
+            return Err(JmapApiError::from(err).into());
+        }
+
+        let created = set
+            .created
+            .as_ref()
+            .and_then(|created| created.get("new"))
+            .filter(|created| created.get("id").is_some_and(Value::is_string))
+            .ok_or_else(|| JmapApiError::MissingResponse(String::from("0")))?;
+
+        Ok(card_ref_from_wire(&self.card.merge(created)))
+    }
+}
+
+impl HttpCoroutine for JmapCardCreate {
+    type Yield = HttpYield;
+    type Return = Result<CardRef, JmapCardCreateError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(responses)) => {
+                HttpCoroutineState::Complete(self.finish(responses))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! JMAP card delete coroutine, a `ContactCard/set` destroying one
+//! card.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! client.delete_card("personal", "card-id", None)?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::{
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{Precondition, SetResponse, check_precondition, from_value},
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapCardDelete`].
+#[derive(Debug, Error)]
+pub enum JmapCardDeleteError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{card_id}` changed on the server (current ETag: {etag:?})")]
+    PreconditionFailed {
+        card_id: String,
+        etag: Option<String>,
+    },
+}
+
+enum State {
+    Check(JmapApi),
+    Write(JmapApi),
+}
+
+/// I/O-free coroutine deleting a JMAP card.
+///
+/// With `if_match`, the card is fetched first and its synthesized tag
+/// compared, then the destruction is gated on the card state, like
+/// [`JmapCardUpdate`](crate::card::jmap::update::JmapCardUpdate).
+pub struct JmapCardDelete {
+    session: JmapSession,
+    auth: JmapAuth,
+    user_agent: String,
+    addressbook_id: String,
+    id: String,
+    if_match: Option<String>,
+    state: State,
+}
+
+impl JmapCardDelete {
+    /// Builds the coroutine deleting `card_id` from `addressbook_id`,
+    /// gating the deletion on `if_match` when present. Rejects an
+    /// empty id.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<Self, JmapCardDeleteError> {
+        trace!("prepare jmap card delete");
+
+        if card_id.is_empty() {
+            return Err(JmapCardDeleteError::InvalidCardId(card_id.to_string()));
+        }
+
+        let state = match if_match {
+            Some(_) => {
+                let calls = Vec::from([invocation(
+                    "ContactCard/get",
+                    json!({ "accountId": session.account_id, "ids": [card_id] }),
+                    "0",
+                )]);
+                State::Check(JmapApi::new(session, auth, user_agent, calls))
+            }
+            None => State::Write(destroy(session, auth, user_agent, card_id, None)),
+        };
+
+        Ok(Self {
+            session: session.clone(),
+            auth: auth.clone(),
+            user_agent: user_agent.to_string(),
+            addressbook_id: addressbook_id.to_string(),
+            id: card_id.to_string(),
+            if_match: if_match.map(str::to_string),
+            state,
+        })
+    }
+
+    fn check(&self, mut responses: JmapResponses) -> Result<String, JmapCardDeleteError> {
+        let if_match = self.if_match.as_deref().unwrap_or_default();
+        let get = from_value(responses.take("0")?)?;
+
+        match check_precondition(get, &self.addressbook_id, &self.id, if_match) {
+            Precondition::Met(state) => Ok(state),
+            Precondition::NotFound => Err(JmapCardDeleteError::CardNotFound(self.id.clone())),
+            Precondition::Failed(etag) => Err(JmapCardDeleteError::PreconditionFailed {
+                card_id: self.id.clone(),
+                etag: Some(etag),
+            }),
+        }
+    }
+
+    fn finish(&self, mut responses: JmapResponses) -> Result<(), JmapCardDeleteError> {
+        let set: SetResponse = match responses.take("0") {
+            Ok(set) => from_value(set)?,
+            Err(err) if self.if_match.is_some() && err.method_kind() == Some("stateMismatch") => {
+                return Err(JmapCardDeleteError::PreconditionFailed {
+                    card_id: self.id.clone(),
+                    etag: None,
+                });
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        match set.destroy_error(&self.id) {
+            Some(err) if err.kind == "notFound" => {
+                Err(JmapCardDeleteError::CardNotFound(self.id.clone()))
+            }
+            Some(err) => Err(JmapApiError::from(err).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Builds the `ContactCard/set` request destroying card `id`, gated
+/// on the card state `if_in_state` when present.
+fn destroy(
+    session: &JmapSession,
+    auth: &JmapAuth,
+    user_agent: &str,
+    id: &str,
+    if_in_state: Option<String>,
+) -> JmapApi {
+    let mut arguments = json!({ "accountId": session.account_id, "destroy": [id] });
+
+    if let Some(state) = if_in_state {
+        arguments["ifInState"] = Value::from(state);
+    }
+
+    let calls = Vec::from([invocation("ContactCard/set", arguments, "0")]);
+    JmapApi::new(session, auth, user_agent, calls)
+}
+
+impl HttpCoroutine for JmapCardDelete {
+    type Yield = HttpYield;
+    type Return = Result<(), JmapCardDeleteError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match &mut self.state {
+                State::Check(api) => match api.resume(arg.take()) {
+                    HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                    HttpCoroutineState::Complete(Ok(responses)) => match self.check(responses) {
+                        Ok(state) => {
+                            self.state = State::Write(destroy(
+                                &self.session,
+                                &self.auth,
+                                &self.user_agent,
+                                &self.id,
+                                Some(state),
+                            ))
+                        }
+                        Err(err) => return HttpCoroutineState::Complete(Err(err)),
+                    },
+                    HttpCoroutineState::Complete(Err(err)) => {
+                        return HttpCoroutineState::Complete(Err(err.into()));
+                    }
+                },
+                State::Write(api) => {
+                    return match api.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+                        HttpCoroutineState::Complete(Ok(responses)) => {
+                            HttpCoroutineState::Complete(self.finish(responses))
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            HttpCoroutineState::Complete(Err(err.into()))
+                        }
+                    };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! JMAP card get coroutine, a `ContactCard/get` on one card.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.get_card("personal", "card-id")?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{
+    card::Card,
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{GetResponse, card_from_wire, from_value},
+        jscontact::JsCard,
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapCardGet`].
+#[derive(Debug, Error)]
+pub enum JmapCardGetError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+}
+
+/// I/O-free coroutine fetching a single JMAP card.
+///
+/// A card outside the addressbook counts as missing. On completion
+/// maps the JSContact card to a [`Card`].
+pub struct JmapCardGet {
+    addressbook_id: String,
+    id: String,
+    inner: JmapApi,
+}
+
+impl JmapCardGet {
+    /// Builds the coroutine fetching `card_id` from `addressbook_id`,
+    /// rejecting an empty id.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Self, JmapCardGetError> {
+        trace!("prepare jmap card get");
+
+        if card_id.is_empty() {
+            return Err(JmapCardGetError::InvalidCardId(card_id.to_string()));
+        }
+
+        let calls = Vec::from([invocation(
+            "ContactCard/get",
+            json!({ "accountId": session.account_id, "ids": [card_id] }),
+            "0",
+        )]);
+
+        Ok(Self {
+            addressbook_id: addressbook_id.to_string(),
+            id: card_id.to_string(),
+            inner: JmapApi::new(session, auth, user_agent, calls),
+        })
+    }
+
+    fn finish(&self, mut responses: JmapResponses) -> Result<Card, JmapCardGetError> {
+        let get: GetResponse<JsCard> = from_value(responses.take("0")?)?;
+
+        get.list
+            .iter()
+            .find(|card| {
+                card.id.as_deref() == Some(&self.id) && card.in_addressbook(&self.addressbook_id)
+            })
+            .map(|card| card_from_wire(&self.addressbook_id, card))
+            .ok_or_else(|| JmapCardGetError::CardNotFound(self.id.clone()))
+    }
+}
+
+impl HttpCoroutine for JmapCardGet {
+    type Yield = HttpYield;
+    type Return = Result<Card, JmapCardGetError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(responses)) => {
+                HttpCoroutineState::Complete(self.finish(responses))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
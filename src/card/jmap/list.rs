@@ -0,0 +1,245 @@
+//! JMAP card list coroutine, `ContactCard/query` pages each fetched
+//! with a back-referenced `ContactCard/get`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let cards = client.list_cards("personal", None, None)?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::{
+    card::Card,
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{GetResponse, card_from_wire, from_value, result_of},
+        jscontact::JsCard,
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Number of cards fetched per request when listing a whole
+/// addressbook, below the `maxObjectsInGet` minimum servers are
+/// expected to accept.
+const PAGE_SIZE: u64 = 256;
+
+/// Errors produced by [`JmapCardList`].
+#[derive(Debug, Error)]
+pub enum JmapCardListError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+}
+
+/// I/O-free coroutine listing the cards of a JMAP addressbook.
+///
+/// Pagination is native: the requested page maps to the `position`
+/// and `limit` of the query, in the server order. Without page size,
+/// every card is fetched page after page. On completion maps each
+/// JSContact card to a [`Card`].
+pub struct JmapCardList {
+    addressbook_id: String,
+    inner: JmapCardPager,
+}
+
+impl JmapCardList {
+    /// Builds the coroutine listing the cards of `addressbook_id`,
+    /// applying 1-indexed pagination.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Self {
+        trace!("prepare jmap card list");
+
+        let filter = json!({ "inAddressBook": addressbook_id });
+
+        Self {
+            addressbook_id: addressbook_id.to_string(),
+            inner: JmapCardPager::new(session, auth, user_agent, filter, page, page_size),
+        }
+    }
+}
+
+impl HttpCoroutine for JmapCardList {
+    type Yield = HttpYield;
+    type Return = Result<Vec<Card>, JmapCardListError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok((cards, _))) => {
+                let cards = cards
+                    .iter()
+                    .filter(|card| card.in_addressbook(&self.addressbook_id))
+                    .map(|card| card_from_wire(&self.addressbook_id, card))
+                    .collect();
+                HttpCoroutineState::Complete(Ok(cards))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryResponse {
+    ids: Vec<String>,
+    #[serde(default)]
+    total: Option<u64>,
+}
+
+/// I/O-free coroutine fetching the cards matching a `ContactCard/query`
+/// filter, one page per request.
+///
+/// On completion returns the cards along with the card state of the
+/// first page, so that changes made while paging are replayed by the
+/// next sync rather than missed.
+pub(crate) struct JmapCardPager {
+    session: JmapSession,
+    auth: JmapAuth,
+    user_agent: String,
+    filter: Value,
+    position: u64,
+    limit: u64,
+    paged: bool,
+    cards: Vec<JsCard>,
+    state: Option<String>,
+    inner: JmapApi,
+}
+
+impl JmapCardPager {
+    /// Builds the coroutine fetching the cards matching `filter`: the
+    /// given 1-indexed page, or every page without `page_size`.
+    pub(crate) fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        filter: Value,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Self {
+        let (position, limit, paged) = match page_size {
+            Some(size) => {
+                let page = page.unwrap_or(1).max(1) as u64;
+                ((page - 1) * size as u64, size as u64, true)
+            }
+            None => (0, PAGE_SIZE, false),
+        };
+
+        let inner = Self::request(session, auth, user_agent, &filter, position, limit);
+
+        Self {
+            session: session.clone(),
+            auth: auth.clone(),
+            user_agent: user_agent.to_string(),
+            filter,
+            position,
+            limit,
+            paged,
+            cards: Vec::new(),
+            state: None,
+            inner,
+        }
+    }
+
+    fn request(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        filter: &Value,
+        position: u64,
+        limit: u64,
+    ) -> JmapApi {
+        let account_id = &session.account_id;
+        let calls = Vec::from([
+            invocation(
+                "ContactCard/query",
+                json!({
+                    "accountId": account_id,
+                    "filter": filter,
+                    "position": position,
+                    "limit": limit,
+                    "calculateTotal": true,
+                }),
+                "0",
+            ),
+            invocation(
+                "ContactCard/get",
+                json!({
+                    "accountId": account_id,
+                    "#ids": result_of("0", "ContactCard/query", "/ids"),
+                }),
+                "1",
+            ),
+        ]);
+
+        JmapApi::new(session, auth, user_agent, calls)
+    }
+
+    /// Collects one page, returning whether another one follows.
+    fn collect(&mut self, mut responses: JmapResponses) -> Result<bool, JmapApiError> {
+        let query: QueryResponse = from_value(responses.take("0")?)?;
+        let get: GetResponse<JsCard> = from_value(responses.take("1")?)?;
+
+        self.state.get_or_insert(get.state);
+        self.cards.extend(get.list);
+        self.position += query.ids.len() as u64;
+
+        let more = match query.total {
+            Some(total) => self.position < total,
+            None => query.ids.len() as u64 == self.limit,
+        };
+
+        Ok(!self.paged && !query.ids.is_empty() && more)
+    }
+}
+
+impl HttpCoroutine for JmapCardPager {
+    type Yield = HttpYield;
+    type Return = Result<(Vec<JsCard>, String), JmapApiError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            let responses = match self.inner.resume(arg.take()) {
+                HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                HttpCoroutineState::Complete(Ok(responses)) => responses,
+                HttpCoroutineState::Complete(Err(err)) => {
+                    return HttpCoroutineState::Complete(Err(err));
+                }
+            };
+
+            match self.collect(responses) {
+                Ok(true) => {
+                    self.inner = Self::request(
+                        &self.session,
+                        &self.auth,
+                        &self.user_agent,
+                        &self.filter,
+                        self.position,
+                        self.limit,
+                    );
+                }
+                Ok(false) => {
+                    let cards = core::mem::take(&mut self.cards);
+                    let state = self.state.take().unwrap_or_default();
+                    return HttpCoroutineState::Complete(Ok((cards, state)));
+                }
+                Err(err) => return HttpCoroutineState::Complete(Err(err)),
+            }
+        }
+    }
+}
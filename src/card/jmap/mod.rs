@@ -0,0 +1,11 @@
+//! JMAP card operations: create, delete, get, list, multiget, query,
+//! sync, update.
+
+pub mod create;
+pub mod delete;
+pub mod get;
+pub mod list;
+pub mod multiget;
+pub mod query;
+pub mod sync;
+pub mod update;
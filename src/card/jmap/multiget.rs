@@ -0,0 +1,114 @@
+//! JMAP card multiget coroutine, a `ContactCard/get` on a batch of
+//! cards.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let batch = client.get_cards("personal", &["a", "b"])?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{
+    card::{Card, CardBatch},
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{GetResponse, card_from_wire, from_value},
+        jscontact::JsCard,
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapCardMultiget`].
+#[derive(Debug, Error)]
+pub enum JmapCardMultigetError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+}
+
+/// I/O-free coroutine fetching a batch of JMAP cards in one request.
+///
+/// On completion returns the cards found in the addressbook, sorted by
+/// id, and the requested ids that are not, in request order.
+pub struct JmapCardMultiget {
+    addressbook_id: String,
+    ids: Vec<String>,
+    inner: JmapApi,
+}
+
+impl JmapCardMultiget {
+    /// Builds the coroutine fetching `card_ids` from
+    /// `addressbook_id`, rejecting any empty id.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<Self, JmapCardMultigetError> {
+        trace!("prepare jmap card multiget");
+
+        if card_ids.iter().any(|id| id.is_empty()) {
+            return Err(JmapCardMultigetError::InvalidCardId(String::new()));
+        }
+
+        let calls = Vec::from([invocation(
+            "ContactCard/get",
+            json!({ "accountId": session.account_id, "ids": card_ids }),
+            "0",
+        )]);
+
+        Ok(Self {
+            addressbook_id: addressbook_id.to_string(),
+            ids: card_ids.iter().map(|id| id.to_string()).collect(),
+            inner: JmapApi::new(session, auth, user_agent, calls),
+        })
+    }
+
+    fn finish(&self, mut responses: JmapResponses) -> Result<CardBatch, JmapCardMultigetError> {
+        let get: GetResponse<JsCard> = from_value(responses.take("0")?)?;
+
+        let mut cards: Vec<Card> = get
+            .list
+            .iter()
+            .filter(|card| card.in_addressbook(&self.addressbook_id))
+            .map(|card| card_from_wire(&self.addressbook_id, card))
+            .collect();
+        cards.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let missing = self
+            .ids
+            .iter()
+            .filter(|id| !cards.iter().any(|card| &card.id == *id))
+            .cloned()
+            .collect();
+
+        Ok(CardBatch { cards, missing })
+    }
+}
+
+impl HttpCoroutine for JmapCardMultiget {
+    type Yield = HttpYield;
+    type Return = Result<CardBatch, JmapCardMultigetError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(responses)) => {
+                HttpCoroutineState::Complete(self.finish(responses))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
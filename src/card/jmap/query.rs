@@ -0,0 +1,93 @@
+//! JMAP card query coroutine, a `ContactCard/query` translating the
+//! [`CardFilter`] into filter conditions.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let cards = client.search_cards("personal", &CardFilter::any([
+//!     PropFilter::contains("FN", "doe"),
+//! ]))?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use thiserror::Error;
+
+use crate::{
+    card::{Card, CardFilter, jmap::list::JmapCardPager},
+    http::coroutine::*,
+    jmap::{
+        api::JmapApiError,
+        convert::{card_from_wire, filter_condition},
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapCardQuery`].
+#[derive(Debug, Error)]
+pub enum JmapCardQueryError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+}
+
+/// I/O-free coroutine searching the cards of a JMAP addressbook.
+///
+/// The server narrows the search down to a superset of the matching
+/// cards (the whole addressbook when the filter has no JMAP
+/// counterpart), then each card is confirmed locally with
+/// [`CardFilter::matches`]. On completion returns the matching cards
+/// sorted by id.
+pub struct JmapCardQuery {
+    addressbook_id: String,
+    filter: CardFilter,
+    inner: JmapCardPager,
+}
+
+impl JmapCardQuery {
+    /// Builds the coroutine searching the cards of `addressbook_id`
+    /// matching `filter`.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Self {
+        trace!("prepare jmap card query");
+
+        let condition = filter_condition(addressbook_id, filter);
+
+        Self {
+            addressbook_id: addressbook_id.to_string(),
+            filter: filter.clone(),
+            inner: JmapCardPager::new(session, auth, user_agent, condition, None, None),
+        }
+    }
+}
+
+impl HttpCoroutine for JmapCardQuery {
+    type Yield = HttpYield;
+    type Return = Result<Vec<Card>, JmapCardQueryError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok((cards, _))) => {
+                let mut cards: Vec<Card> = cards
+                    .iter()
+                    .filter(|card| card.in_addressbook(&self.addressbook_id))
+                    .map(|card| card_from_wire(&self.addressbook_id, card))
+                    .filter(|card| self.filter.matches(card))
+                    .collect();
+                cards.sort_by(|a, b| a.id.cmp(&b.id));
+                HttpCoroutineState::Complete(Ok(cards))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
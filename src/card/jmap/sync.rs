@@ -0,0 +1,234 @@
+//! JMAP card sync coroutine, `ContactCard/changes` rounds each
+//! fetching the created and updated cards with back-referenced
+//! `ContactCard/get`s.
+//!
+//! A state the server cannot compute changes from surfaces as
+//! [`JmapCardSyncError::InvalidSyncToken`]; the client falls back to a
+//! tokenless (full) sync in that case.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let changes = client.sync_cards("personal", Some(&token))?;
+//! ```
+
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{
+    card::{Card, CardChanges, jmap::list::JmapCardPager},
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{ChangesResponse, GetResponse, card_from_wire, from_value, result_of},
+        jscontact::JsCard,
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapCardSync`].
+#[derive(Debug, Error)]
+pub enum JmapCardSyncError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+    #[error("Sync token `{0}` rejected by the server")]
+    InvalidSyncToken(String),
+}
+
+enum State {
+    Full(Box<JmapCardPager>),
+    Changes(JmapApi),
+}
+
+/// I/O-free coroutine fetching the cards of a JMAP addressbook changed
+/// since a sync token, the token being the card state.
+///
+/// Card states cover the whole account: cards changed in other
+/// addressbooks are skipped, and cards moved out of this one are
+/// reported as deleted. On completion returns the changed cards
+/// (sorted by id), the deleted ids and the new state. An absent token
+/// yields the full addressbook content, flagged as such.
+pub struct JmapCardSync {
+    session: JmapSession,
+    auth: JmapAuth,
+    user_agent: String,
+    addressbook_id: String,
+    sync_token: String,
+    changed: BTreeMap<String, Card>,
+    deleted: BTreeSet<String>,
+    state: State,
+}
+
+impl JmapCardSync {
+    /// Builds the coroutine syncing `addressbook_id` from
+    /// `sync_token`, or from scratch when `None`.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Self {
+        trace!("prepare jmap card sync");
+
+        let sync_token = sync_token.filter(|t| !t.is_empty()).unwrap_or_default();
+
+        let state = if sync_token.is_empty() {
+            let filter = json!({ "inAddressBook": addressbook_id });
+            State::Full(Box::new(JmapCardPager::new(
+                session, auth, user_agent, filter, None, None,
+            )))
+        } else {
+            State::Changes(changes(session, auth, user_agent, sync_token))
+        };
+
+        Self {
+            session: session.clone(),
+            auth: auth.clone(),
+            user_agent: user_agent.to_string(),
+            addressbook_id: addressbook_id.to_string(),
+            sync_token: sync_token.to_string(),
+            changed: BTreeMap::new(),
+            deleted: BTreeSet::new(),
+            state,
+        }
+    }
+
+    /// Collects one round of changes, returning the state to resume
+    /// from when the server has more.
+    fn collect(
+        &mut self,
+        mut responses: JmapResponses,
+    ) -> Result<Option<String>, JmapCardSyncError> {
+        let changes: ChangesResponse = match responses.take("0") {
+            Ok(changes) => from_value(changes)?,
+            Err(err) if err.method_kind() == Some("cannotCalculateChanges") => {
+                return Err(JmapCardSyncError::InvalidSyncToken(self.sync_token.clone()));
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        for call_id in ["1", "2"] {
+            let get: GetResponse<JsCard> = from_value(responses.take(call_id)?)?;
+
+            for card in get.list {
+                let id = card.id.clone().unwrap_or_default();
+
+                if card.in_addressbook(&self.addressbook_id) {
+                    self.deleted.remove(&id);
+                    let card = card_from_wire(&self.addressbook_id, &card);
+                    self.changed.insert(id, card);
+                } else {
+                    self.changed.remove(&id);
+                    self.deleted.insert(id);
+                }
+            }
+        }
+
+        for id in changes.destroyed {
+            self.changed.remove(&id);
+            self.deleted.insert(id);
+        }
+
+        self.sync_token = changes.new_state;
+
+        Ok(changes.has_more_changes.then(|| self.sync_token.clone()))
+    }
+
+    fn finish(&mut self) -> CardChanges {
+        CardChanges {
+            cards: core::mem::take(&mut self.changed).into_values().collect(),
+            deleted: core::mem::take(&mut self.deleted).into_iter().collect(),
+            sync_token: Some(self.sync_token.clone()),
+            full: false,
+        }
+    }
+}
+
+/// Builds the request fetching the card changes since `state`.
+fn changes(session: &JmapSession, auth: &JmapAuth, user_agent: &str, state: &str) -> JmapApi {
+    let account_id = &session.account_id;
+    let calls = Vec::from([
+        invocation(
+            "ContactCard/changes",
+            json!({ "accountId": account_id, "sinceState": state }),
+            "0",
+        ),
+        invocation(
+            "ContactCard/get",
+            json!({
+                "accountId": account_id,
+                "#ids": result_of("0", "ContactCard/changes", "/created"),
+            }),
+            "1",
+        ),
+        invocation(
+            "ContactCard/get",
+            json!({
+                "accountId": account_id,
+                "#ids": result_of("0", "ContactCard/changes", "/updated"),
+            }),
+            "2",
+        ),
+    ]);
+
+    JmapApi::new(session, auth, user_agent, calls)
+}
+
+impl HttpCoroutine for JmapCardSync {
+    type Yield = HttpYield;
+    type Return = Result<CardChanges, JmapCardSyncError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match &mut self.state {
+                State::Full(pager) => {
+                    return match pager.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+                        HttpCoroutineState::Complete(Ok((cards, state))) => {
+                            let mut cards: Vec<Card> = cards
+                                .iter()
+                                .filter(|card| card.in_addressbook(&self.addressbook_id))
+                                .map(|card| card_from_wire(&self.addressbook_id, card))
+                                .collect();
+                            cards.sort_by(|a, b| a.id.cmp(&b.id));
+
+                            HttpCoroutineState::Complete(Ok(CardChanges {
+                                cards,
+                                deleted: Vec::new(),
+                                sync_token: Some(state),
+                                full: true,
+                            }))
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            HttpCoroutineState::Complete(Err(err.into()))
+                        }
+                    };
+                }
+                State::Changes(api) => match api.resume(arg.take()) {
+                    HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                    HttpCoroutineState::Complete(Ok(responses)) => match self.collect(responses) {
+                        Ok(Some(state)) => {
+                            let api = changes(&self.session, &self.auth, &self.user_agent, &state);
+                            self.state = State::Changes(api);
+                        }
+                        Ok(None) => return HttpCoroutineState::Complete(Ok(self.finish())),
+                        Err(err) => return HttpCoroutineState::Complete(Err(err)),
+                    },
+                    HttpCoroutineState::Complete(Err(err)) => {
+                        return HttpCoroutineState::Complete(Err(err.into()));
+                    }
+                },
+            }
+        }
+    }
+}
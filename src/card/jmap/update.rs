@@ -0,0 +1,242 @@
+//! JMAP card update coroutine, a `ContactCard/set` replacing the
+//! properties of a card with the JSContact rendering of a vCard.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.update_card("personal", "card-id", contents, None)?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::{
+    card::{CardRef, Contact, ParseContactError},
+    http::coroutine::*,
+    jmap::{
+        api::{JmapApi, JmapApiError, JmapResponses, invocation},
+        convert::{Precondition, SetResponse, card_ref_from_wire, check_precondition, from_value},
+        jscontact::JsCard,
+        send::JmapAuth,
+        session::JmapSession,
+    },
+};
+
+/// Errors produced by [`JmapCardUpdate`].
+#[derive(Debug, Error)]
+pub enum JmapCardUpdateError {
+    #[error(transparent)]
+    Api(#[from] JmapApiError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card body is empty")]
+    EmptyCardBody,
+    #[error("Invalid card: {0}")]
+    InvalidCard(ParseContactError),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{card_id}` changed on the server (current ETag: {etag:?})")]
+    PreconditionFailed {
+        card_id: String,
+        etag: Option<String>,
+    },
+}
+
+enum State {
+    Check(JmapApi),
+    Write(JmapApi),
+}
+
+/// I/O-free coroutine overwriting an existing JMAP card.
+///
+/// JMAP has no ETag: with `if_match`, the card is fetched first and
+/// its synthesized tag compared, then the write is gated on the card
+/// state so that a concurrent change makes it fail too. On completion
+/// returns the card id and the tag of the card as stored.
+pub struct JmapCardUpdate {
+    session: JmapSession,
+    auth: JmapAuth,
+    user_agent: String,
+    addressbook_id: String,
+    if_match: Option<String>,
+    card: JsCard,
+    state: State,
+}
+
+impl JmapCardUpdate {
+    /// Builds the coroutine overwriting `card_id` inside
+    /// `addressbook_id` with the vCard `contents`, gating the write on
+    /// `if_match` when present. Rejects an empty id or body, or an
+    /// invalid body.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: &[u8],
+        if_match: Option<&str>,
+    ) -> Result<Self, JmapCardUpdateError> {
+        trace!("prepare jmap card update");
+
+        if card_id.is_empty() {
+            return Err(JmapCardUpdateError::InvalidCardId(card_id.to_string()));
+        }
+
+        if contents.is_empty() {
+            return Err(JmapCardUpdateError::EmptyCardBody);
+        }
+
+        let contact = Contact::from_bytes(contents).map_err(JmapCardUpdateError::InvalidCard)?;
+        let mut card = JsCard::from_contact(&contact);
+        card.id = Some(card_id.to_string());
+
+        let state = match if_match {
+            Some(_) => {
+                let calls = Vec::from([invocation(
+                    "ContactCard/get",
+                    json!({ "accountId": session.account_id, "ids": [card_id] }),
+                    "0",
+                )]);
+                State::Check(JmapApi::new(session, auth, user_agent, calls))
+            }
+            None => State::Write(write(
+                session,
+                auth,
+                user_agent,
+                addressbook_id,
+                &card,
+                None,
+            )),
+        };
+
+        Ok(Self {
+            session: session.clone(),
+            auth: auth.clone(),
+            user_agent: user_agent.to_string(),
+            addressbook_id: addressbook_id.to_string(),
+            if_match: if_match.map(str::to_string),
+            card,
+            state,
+        })
+    }
+
+    fn id(&self) -> String {
+        self.card.id.clone().unwrap_or_default()
+    }
+
+    fn check(&self, mut responses: JmapResponses) -> Result<String, JmapCardUpdateError> {
+        let if_match = self.if_match.as_deref().unwrap_or_default();
+        let get = from_value(responses.take("0")?)?;
+
+        match check_precondition(get, &self.addressbook_id, &self.id(), if_match) {
+            Precondition::Met(state) => Ok(state),
+            Precondition::NotFound => Err(JmapCardUpdateError::CardNotFound(self.id())),
+            Precondition::Failed(etag) => Err(JmapCardUpdateError::PreconditionFailed {
+                card_id: self.id(),
+                etag: Some(etag),
+            }),
+        }
+    }
+
+    fn finish(&self, mut responses: JmapResponses) -> Result<CardRef, JmapCardUpdateError> {
+        let set: SetResponse = match responses.take("0") {
+            Ok(set) => from_value(set)?,
+            Err(err) if self.if_match.is_some() && err.method_kind() == Some("stateMismatch") => {
+                return Err(JmapCardUpdateError::PreconditionFailed {
+                    card_id: self.id(),
+                    etag: None,
+                });
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        match set.update_error(&self.id()) {
+            Some(err) if err.kind == "notFound" => {
+                return Err(JmapCardUpdateError::CardNotFound(self.id()));
+            }
+            Some(err) => return Err(JmapApiError::from(err).into()),
+            None => (),
+        }
+
+        let changes = set
+            .updated
+            .as_ref()
+            .and_then(|updated| updated.get(&self.id()).cloned().flatten())
+            .unwrap_or(Value::Null);
+
+        Ok(card_ref_from_wire(&self.card.merge(&changes)))
+    }
+}
+
+/// Builds the `ContactCard/set` request replacing the properties of
+/// `card`, gated on the card state `if_in_state` when present.
+fn write(
+    session: &JmapSession,
+    auth: &JmapAuth,
+    user_agent: &str,
+    addressbook_id: &str,
+    card: &JsCard,
+    if_in_state: Option<String>,
+) -> JmapApi {
+    let id = card.id.clone().unwrap_or_default();
+    let mut arguments = json!({
+        "accountId": session.account_id,
+        "update": { id: card.to_patch(addressbook_id) },
+    });
+
+    if let Some(state) = if_in_state {
+        arguments["ifInState"] = Value::from(state);
+    }
+
+    let calls = Vec::from([invocation("ContactCard/set", arguments, "0")]);
+    JmapApi::new(session, auth, user_agent, calls)
+}
+
+impl HttpCoroutine for JmapCardUpdate {
+    type Yield = HttpYield;
+    type Return = Result<CardRef, JmapCardUpdateError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match &mut self.state {
+                State::Check(api) => match api.resume(arg.take()) {
+                    HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                    HttpCoroutineState::Complete(Ok(responses)) => match self.check(responses) {
+                        Ok(state) => {
+                            self.state = State::Write(write(
+                                &self.session,
+                                &self.auth,
+                                &self.user_agent,
+                                &self.addressbook_id,
+                                &self.card,
+                                Some(state),
+                            ))
+                        }
+                        Err(err) => return HttpCoroutineState::Complete(Err(err)),
+                    },
+                    HttpCoroutineState::Complete(Err(err)) => {
+                        return HttpCoroutineState::Complete(Err(err.into()));
+                    }
+                },
+                State::Write(api) => {
+                    return match api.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+                        HttpCoroutineState::Complete(Ok(responses)) => {
+                            HttpCoroutineState::Complete(self.finish(responses))
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            HttpCoroutineState::Complete(Err(err.into()))
+                        }
+                    };
+                }
+            }
+        }
+    }
+}
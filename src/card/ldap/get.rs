@@ -0,0 +1,110 @@
+//! LDAP card get coroutine, a base-scope search on one entry.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.get_card(
+//!     "ou=people,dc=example,dc=com",
+//!     "uid=jdoe,ou=people,dc=example,dc=com",
+//! )?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use thiserror::Error;
+
+use crate::{
+    card::Card,
+    ldap::{
+        convert::{ATTRIBUTES, card_from_entry},
+        coroutine::*,
+        directory::LdapDirectory,
+        send::{LdapScope, LdapSend, LdapSendError, NO_SUCH_OBJECT},
+    },
+};
+
+/// Errors produced by [`LdapCardGet`].
+#[derive(Debug, Error)]
+pub enum LdapCardGetError {
+    #[error(transparent)]
+    Send(#[from] LdapSendError),
+    #[error("Addressbook `{0}` not found")]
+    AddressbookNotFound(String),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+}
+
+/// I/O-free coroutine fetching a single directory entry.
+///
+/// An entry not matching the directory filter counts as missing. On
+/// completion maps the entry to a [`Card`].
+pub struct LdapCardGet {
+    addressbook_id: String,
+    id: String,
+    inner: LdapSend,
+}
+
+impl LdapCardGet {
+    /// Builds the coroutine fetching the entry `card_id`, its DN,
+    /// rejecting any DN outside of the directory.
+    pub fn new(
+        directory: &LdapDirectory,
+        message_id: i32,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Self, LdapCardGetError> {
+        trace!("prepare ldap card get");
+
+        if !directory.is_addressbook(addressbook_id) {
+            let id = addressbook_id.to_string();
+            return Err(LdapCardGetError::AddressbookNotFound(id));
+        }
+
+        if !directory.contains(card_id) {
+            return Err(LdapCardGetError::InvalidCardId(card_id.to_string()));
+        }
+
+        let inner = LdapSend::search(
+            message_id,
+            card_id,
+            LdapScope::Base,
+            &directory.filter,
+            &ATTRIBUTES,
+        );
+
+        Ok(Self {
+            addressbook_id: addressbook_id.to_string(),
+            id: card_id.to_string(),
+            inner,
+        })
+    }
+}
+
+impl LdapCoroutine for LdapCardGet {
+    type Yield = LdapYield;
+    type Return = Result<Card, LdapCardGetError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> LdapCoroutineState<Self::Yield, Self::Return> {
+        let entries = match self.inner.resume(arg) {
+            LdapCoroutineState::Yielded(y) => return LdapCoroutineState::Yielded(y),
+            LdapCoroutineState::Complete(Ok(entries)) => entries,
+            LdapCoroutineState::Complete(Err(err)) if err.is_result(NO_SUCH_OBJECT) => {
+                let err = LdapCardGetError::CardNotFound(self.id.clone());
+                return LdapCoroutineState::Complete(Err(err));
+            }
+            LdapCoroutineState::Complete(Err(err)) => {
+                return LdapCoroutineState::Complete(Err(err.into()));
+            }
+        };
+
+        let result = match entries.first() {
+            Some(entry) => Ok(card_from_entry(&self.addressbook_id, entry)),
+            None => Err(LdapCardGetError::CardNotFound(self.id.clone())),
+        };
+
+        LdapCoroutineState::Complete(result)
+    }
+}
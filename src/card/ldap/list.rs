@@ -0,0 +1,99 @@
+//! LDAP card list coroutine, a search of the directory entries.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let cards = client.list_cards("ou=people,dc=example,dc=com", None, None)?;
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use log::trace;
+use thiserror::Error;
+
+use crate::{
+    card::Card,
+    ldap::{
+        convert::{ATTRIBUTES, card_from_entry, paginate},
+        coroutine::*,
+        directory::LdapDirectory,
+        send::{LdapSend, LdapSendError},
+    },
+};
+
+/// Errors produced by [`LdapCardList`].
+#[derive(Debug, Error)]
+pub enum LdapCardListError {
+    #[error(transparent)]
+    Send(#[from] LdapSendError),
+    #[error("Addressbook `{0}` not found")]
+    AddressbookNotFound(String),
+}
+
+/// I/O-free coroutine listing the cards of the directory.
+///
+/// On completion maps every entry to a [`Card`], sorted by id, then
+/// applies the pagination locally.
+pub struct LdapCardList {
+    addressbook_id: String,
+    page: Option<u32>,
+    page_size: Option<u32>,
+    inner: LdapSend,
+}
+
+impl LdapCardList {
+    /// Builds the coroutine listing the cards of `addressbook_id`,
+    /// which must be the directory base DN.
+    pub fn new(
+        directory: &LdapDirectory,
+        message_id: i32,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Self, LdapCardListError> {
+        trace!("prepare ldap card list");
+
+        if !directory.is_addressbook(addressbook_id) {
+            let id = addressbook_id.into();
+            return Err(LdapCardListError::AddressbookNotFound(id));
+        }
+
+        let inner = LdapSend::search(
+            message_id,
+            &directory.base_dn,
+            directory.scope,
+            &directory.filter,
+            &ATTRIBUTES,
+        );
+
+        Ok(Self {
+            addressbook_id: addressbook_id.into(),
+            page,
+            page_size,
+            inner,
+        })
+    }
+}
+
+impl LdapCoroutine for LdapCardList {
+    type Yield = LdapYield;
+    type Return = Result<Vec<Card>, LdapCardListError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> LdapCoroutineState<Self::Yield, Self::Return> {
+        let entries = match self.inner.resume(arg) {
+            LdapCoroutineState::Yielded(y) => return LdapCoroutineState::Yielded(y),
+            LdapCoroutineState::Complete(Ok(entries)) => entries,
+            LdapCoroutineState::Complete(Err(err)) => {
+                return LdapCoroutineState::Complete(Err(err.into()));
+            }
+        };
+
+        let cards = entries
+            .iter()
+            .map(|entry| card_from_entry(&self.addressbook_id, entry))
+            .collect();
+
+        let cards = paginate(cards, self.page, self.page_size);
+        LdapCoroutineState::Complete(Ok(cards))
+    }
+}
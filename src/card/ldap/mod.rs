@@ -0,0 +1,5 @@
+//! LDAP card operations: get, list, search.
+
+pub mod get;
+pub mod list;
+pub mod search;
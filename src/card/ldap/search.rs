@@ -0,0 +1,103 @@
+//! LDAP card search coroutine, a search of the directory entries
+//! narrowed down by the card filter.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let filter = CardFilter {
+//!     props: vec![PropFilter::contains("EMAIL", "@example.com")],
+//!     ..Default::default()
+//! };
+//! let cards = client.search_cards("ou=people,dc=example,dc=com", &filter)?;
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use log::trace;
+use thiserror::Error;
+
+use crate::{
+    card::{Card, CardFilter},
+    ldap::{
+        convert::{ATTRIBUTES, card_from_entry, search_filter},
+        coroutine::*,
+        directory::LdapDirectory,
+        send::{LdapSend, LdapSendError},
+    },
+};
+
+/// Errors produced by [`LdapCardSearch`].
+#[derive(Debug, Error)]
+pub enum LdapCardSearchError {
+    #[error(transparent)]
+    Send(#[from] LdapSendError),
+    #[error("Addressbook `{0}` not found")]
+    AddressbookNotFound(String),
+}
+
+/// I/O-free coroutine searching the cards of the directory.
+///
+/// The server selects the entries that may match, see
+/// [`search_filter`]; on completion the coroutine keeps the cards
+/// matching the filter, sorted by id.
+pub struct LdapCardSearch {
+    addressbook_id: String,
+    filter: CardFilter,
+    inner: LdapSend,
+}
+
+impl LdapCardSearch {
+    /// Builds the coroutine searching the cards of `addressbook_id`
+    /// matching `filter`.
+    pub fn new(
+        directory: &LdapDirectory,
+        message_id: i32,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Self, LdapCardSearchError> {
+        trace!("prepare ldap card search");
+
+        if !directory.is_addressbook(addressbook_id) {
+            let id = addressbook_id.into();
+            return Err(LdapCardSearchError::AddressbookNotFound(id));
+        }
+
+        let inner = LdapSend::search(
+            message_id,
+            &directory.base_dn,
+            directory.scope,
+            &search_filter(directory, filter),
+            &ATTRIBUTES,
+        );
+
+        Ok(Self {
+            addressbook_id: addressbook_id.into(),
+            filter: filter.clone(),
+            inner,
+        })
+    }
+}
+
+impl LdapCoroutine for LdapCardSearch {
+    type Yield = LdapYield;
+    type Return = Result<Vec<Card>, LdapCardSearchError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> LdapCoroutineState<Self::Yield, Self::Return> {
+        let entries = match self.inner.resume(arg) {
+            LdapCoroutineState::Yielded(y) => return LdapCoroutineState::Yielded(y),
+            LdapCoroutineState::Complete(Ok(entries)) => entries,
+            LdapCoroutineState::Complete(Err(err)) => {
+                return LdapCoroutineState::Complete(Err(err.into()));
+            }
+        };
+
+        let mut cards: Vec<Card> = entries
+            .iter()
+            .map(|entry| card_from_entry(&self.addressbook_id, entry))
+            .filter(|card| self.filter.matches(card))
+            .collect();
+
+        cards.sort_by(|a, b| a.id.cmp(&b.id));
+        LdapCoroutineState::Complete(Ok(cards))
+    }
+}
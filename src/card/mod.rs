@@ -1,10 +1,64 @@
-//! Card domain: the shared [`Card`] type and the per-backend card operations.
+//! Card domain: the shared [`Card`] type, its typed [`Contact`] view,
+//! the [`CardFilter`] search type, multi-card `.vcf` helpers and the
+//! per-backend card operations.
 
+mod contact;
+#[cfg(any(feature = "vcf-file", feature = "abook", feature = "mutt-alias"))]
+pub(crate) mod file;
+mod filter;
+#[cfg(feature = "google")]
+pub mod google;
+#[cfg(feature = "jmap")]
+pub mod jmap;
+#[cfg(feature = "ldap")]
+pub mod ldap;
+#[cfg(feature = "msgraph")]
+pub mod msgraph;
+#[cfg(any(
+    feature = "vdir",
+    feature = "memory",
+    feature = "sqlite",
+    feature = "vcf-file",
+    feature = "jmap",
+    feature = "ldap",
+    feature = "abook",
+    feature = "mutt-alias"
+))]
+pub(crate) mod tag;
 mod types;
+#[cfg(any(
+    feature = "vdir",
+    feature = "webdav",
+    feature = "memory",
+    feature = "sqlite",
+    feature = "vcf-file",
+    feature = "abook"
+))]
+// NOTE: each backend only picks the helpers it needs, so some of them
+// are unused depending on the enabled features.
+#[allow(dead_code)]
+pub(crate) mod uid;
+mod vcf;
 #[cfg(feature = "vdir")]
 pub mod vdir;
 #[cfg(feature = "webdav")]
 pub mod webdav;
 
+#[cfg(any(
+    feature = "jmap",
+    feature = "google",
+    feature = "msgraph",
+    feature = "abook",
+    feature = "mutt-alias"
+))]
+pub(crate) use contact::escape;
+#[cfg(feature = "sqlite")]
+pub(crate) use contact::parse_properties;
+#[doc(inline)]
+pub use contact::*;
+#[doc(inline)]
+pub use filter::*;
 #[doc(inline)]
 pub use types::*;
+#[doc(inline)]
+pub use vcf::*;
@@ -0,0 +1,88 @@
+//! Microsoft Graph card create coroutine, a `POST` of the Graph
+//! rendering of a vCard on the contacts of a contact folder.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.create_card("contacts", contents)?;
+//! ```
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::{CardRef, Contact, ParseContactError},
+    http::coroutine::*,
+    msgraph::{
+        contact::MsGraphContact,
+        convert::{card_ref_from_contact, contacts_path, endpoint, from_value},
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+/// Errors produced by [`MsGraphCardCreate`].
+#[derive(Debug, Error)]
+pub enum MsGraphCardCreateError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+    #[error("Card body is empty")]
+    EmptyCardBody,
+    #[error("Invalid card: {0}")]
+    InvalidCard(ParseContactError),
+}
+
+/// I/O-free coroutine creating a Microsoft Graph contact.
+///
+/// The server assigns the contact id, so the create options do not
+/// apply. On completion returns the new id and `@odata.etag`.
+pub struct MsGraphCardCreate {
+    inner: MsGraphSend,
+}
+
+impl MsGraphCardCreate {
+    /// Builds the coroutine creating a contact from the vCard
+    /// `contents` inside `addressbook_id`, rejecting an empty or
+    /// invalid body.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        contents: &[u8],
+    ) -> Result<Self, MsGraphCardCreateError> {
+        trace!("prepare msgraph card create");
+
+        if contents.is_empty() {
+            return Err(MsGraphCardCreateError::EmptyCardBody);
+        }
+
+        let contact = Contact::from_bytes(contents).map_err(MsGraphCardCreateError::InvalidCard)?;
+        let contact = MsGraphContact::from_contact(&contact);
+
+        let url = endpoint(base_url, &contacts_path(addressbook_id), &[]);
+        let body = serde_json::to_value(&contact).unwrap_or_default();
+
+        Ok(Self {
+            inner: MsGraphSend::post(&url, token, user_agent, &body),
+        })
+    }
+}
+
+impl HttpCoroutine for MsGraphCardCreate {
+    type Yield = HttpYield;
+    type Return = Result<CardRef, MsGraphCardCreateError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => {
+                let result = from_value::<MsGraphContact>(body)
+                    .map(|contact| card_ref_from_contact(&contact))
+                    .map_err(Into::into);
+                HttpCoroutineState::Complete(result)
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Microsoft Graph card delete coroutine, a `GET` on the contact then
+//! a `DELETE`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! client.delete_card("contacts", &contact_id, Some(&etag))?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::msgraph::get::MsGraphContactGet,
+    http::coroutine::*,
+    msgraph::{
+        convert::{contact_path, endpoint, etag_matches, is_valid_id},
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+/// Errors produced by [`MsGraphCardDelete`].
+#[derive(Debug, Error)]
+pub enum MsGraphCardDeleteError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{card_id}` changed on the server (current ETag: {etag:?})")]
+    PreconditionFailed {
+        card_id: String,
+        etag: Option<String>,
+    },
+}
+
+enum State {
+    /// Fetching the contact, to check `if_match`.
+    Get(MsGraphContactGet),
+    Delete(MsGraphSend),
+}
+
+/// I/O-free coroutine deleting a Microsoft Graph contact.
+///
+/// Graph moves the contact to the deleted items of the mailbox. With
+/// `if_match`, the contact is fetched first to check its
+/// `@odata.etag`.
+pub struct MsGraphCardDelete {
+    url: Url,
+    token: String,
+    user_agent: String,
+    id: String,
+    if_match: Option<String>,
+    state: State,
+}
+
+impl MsGraphCardDelete {
+    /// Builds the coroutine deleting `card_id` from `addressbook_id`,
+    /// rejecting an invalid id.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<Self, MsGraphCardDeleteError> {
+        trace!("prepare msgraph card delete");
+
+        if !is_valid_id(card_id) {
+            return Err(MsGraphCardDeleteError::InvalidCardId(card_id.to_string()));
+        }
+
+        let url = endpoint(base_url, &contact_path(addressbook_id, card_id), &[]);
+
+        let state = match if_match {
+            Some(_) => State::Get(MsGraphContactGet::new(
+                base_url,
+                token,
+                user_agent,
+                addressbook_id,
+                card_id,
+            )),
+            None => State::Delete(MsGraphSend::delete(&url, token, user_agent)),
+        };
+
+        Ok(Self {
+            url,
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            id: card_id.to_string(),
+            if_match: if_match.map(ToString::to_string),
+            state,
+        })
+    }
+}
+
+impl HttpCoroutine for MsGraphCardDelete {
+    type Yield = HttpYield;
+    type Return = Result<(), MsGraphCardDeleteError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match &mut self.state {
+                State::Get(get) => {
+                    let current = match get.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => {
+                            return HttpCoroutineState::Yielded(y);
+                        }
+                        HttpCoroutineState::Complete(Ok(Some(current))) => current,
+                        HttpCoroutineState::Complete(Ok(None)) => {
+                            let err = MsGraphCardDeleteError::CardNotFound(self.id.clone());
+                            return HttpCoroutineState::Complete(Err(err));
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            return HttpCoroutineState::Complete(Err(err.into()));
+                        }
+                    };
+
+                    let etag = current.etag.unwrap_or_default();
+
+                    if let Some(if_match) = &self.if_match {
+                        if !etag_matches(if_match, &etag) {
+                            let err = MsGraphCardDeleteError::PreconditionFailed {
+                                card_id: self.id.clone(),
+                                etag: Some(etag),
+                            };
+                            return HttpCoroutineState::Complete(Err(err));
+                        }
+                    }
+
+                    let send = MsGraphSend::delete(&self.url, &self.token, &self.user_agent);
+                    self.state = State::Delete(send);
+                }
+                State::Delete(send) => {
+                    return match send.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+                        HttpCoroutineState::Complete(Ok(_)) => HttpCoroutineState::Complete(Ok(())),
+                        HttpCoroutineState::Complete(Err(err)) if err.is_status(404) => {
+                            let err = MsGraphCardDeleteError::CardNotFound(self.id.clone());
+                            HttpCoroutineState::Complete(Err(err))
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            HttpCoroutineState::Complete(Err(err.into()))
+                        }
+                    };
+                }
+            }
+        }
+    }
+}
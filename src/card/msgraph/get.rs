@@ -0,0 +1,130 @@
+//! Microsoft Graph card get coroutine, a `GET` on one contact.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.get_card("contacts", &contact_id)?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::Card,
+    http::coroutine::*,
+    msgraph::{
+        contact::{MsGraphContact, expand_raw_property},
+        convert::{card_from_contact, contact_path, endpoint, from_value, is_valid_id},
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+/// Errors produced by [`MsGraphCardGet`].
+#[derive(Debug, Error)]
+pub enum MsGraphCardGetError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+}
+
+/// I/O-free coroutine fetching a single Microsoft Graph contact.
+///
+/// On completion maps the contact to a [`Card`].
+pub struct MsGraphCardGet {
+    addressbook_id: String,
+    id: String,
+    inner: MsGraphContactGet,
+}
+
+impl MsGraphCardGet {
+    /// Builds the coroutine fetching `card_id` from `addressbook_id`,
+    /// rejecting an invalid id.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Self, MsGraphCardGetError> {
+        trace!("prepare msgraph card get");
+
+        if !is_valid_id(card_id) {
+            return Err(MsGraphCardGetError::InvalidCardId(card_id.to_string()));
+        }
+
+        Ok(Self {
+            addressbook_id: addressbook_id.to_string(),
+            id: card_id.to_string(),
+            inner: MsGraphContactGet::new(base_url, token, user_agent, addressbook_id, card_id),
+        })
+    }
+}
+
+impl HttpCoroutine for MsGraphCardGet {
+    type Yield = HttpYield;
+    type Return = Result<Card, MsGraphCardGetError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(Some(contact))) => {
+                let card = card_from_contact(&self.addressbook_id, &contact);
+                HttpCoroutineState::Complete(Ok(card))
+            }
+            HttpCoroutineState::Complete(Ok(None)) => {
+                let err = MsGraphCardGetError::CardNotFound(self.id.clone());
+                HttpCoroutineState::Complete(Err(err))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
+
+/// I/O-free coroutine fetching a contact of a contact folder, along
+/// with its verbatim vCard properties.
+///
+/// On completion returns `None` when the contact does not exist in
+/// the folder.
+pub(crate) struct MsGraphContactGet {
+    inner: MsGraphSend,
+}
+
+impl MsGraphContactGet {
+    pub(crate) fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Self {
+        let path = contact_path(addressbook_id, card_id);
+        let expand = expand_raw_property();
+        let url = endpoint(base_url, &path, &[("$expand", &expand)]);
+
+        Self {
+            inner: MsGraphSend::get(&url, token, user_agent),
+        }
+    }
+}
+
+impl HttpCoroutine for MsGraphContactGet {
+    type Yield = HttpYield;
+    type Return = Result<Option<MsGraphContact>, MsGraphSendError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        let result = match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => from_value::<MsGraphContact>(body).map(Some),
+            HttpCoroutineState::Complete(Err(err)) if err.is_status(404) => Ok(None),
+            HttpCoroutineState::Complete(Err(err)) => Err(err),
+        };
+
+        HttpCoroutineState::Complete(result)
+    }
+}
@@ -0,0 +1,153 @@
+//! Microsoft Graph card list coroutine, a paged `GET` on the contacts
+//! of a contact folder.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let cards = client.list_cards("contacts", None, None)?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::Card,
+    http::coroutine::*,
+    msgraph::{
+        contact::{MsGraphContact, expand_raw_property},
+        convert::{
+            CollectionPage, PAGE_SIZE, card_from_contact, contacts_path, endpoint, from_value,
+            next_link, paginate,
+        },
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+/// Errors produced by [`MsGraphCardList`].
+#[derive(Debug, Error)]
+pub enum MsGraphCardListError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+}
+
+/// I/O-free coroutine listing the cards of a Microsoft Graph contact
+/// folder.
+///
+/// Every contact of the folder is fetched, then pagination is applied
+/// in memory, in the server order. On completion maps each contact to
+/// a [`Card`].
+pub struct MsGraphCardList {
+    addressbook_id: String,
+    page: Option<u32>,
+    page_size: Option<u32>,
+    inner: MsGraphContactPager,
+}
+
+impl MsGraphCardList {
+    /// Builds the coroutine listing the cards of `addressbook_id`,
+    /// applying 1-indexed pagination.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Self {
+        trace!("prepare msgraph card list");
+
+        Self {
+            addressbook_id: addressbook_id.to_string(),
+            page,
+            page_size,
+            inner: MsGraphContactPager::new(base_url, token, user_agent, addressbook_id),
+        }
+    }
+}
+
+impl HttpCoroutine for MsGraphCardList {
+    type Yield = HttpYield;
+    type Return = Result<Vec<Card>, MsGraphCardListError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(contacts)) => {
+                let cards = contacts
+                    .iter()
+                    .map(|contact| card_from_contact(&self.addressbook_id, contact))
+                    .collect();
+                let cards = paginate(cards, self.page, self.page_size);
+                HttpCoroutineState::Complete(Ok(cards))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
+
+/// I/O-free coroutine fetching every contact of a contact folder,
+/// one page per request, along with their verbatim vCard properties.
+pub(crate) struct MsGraphContactPager {
+    token: String,
+    user_agent: String,
+    contacts: Vec<MsGraphContact>,
+    inner: MsGraphSend,
+}
+
+impl MsGraphContactPager {
+    pub(crate) fn new(base_url: &Url, token: &str, user_agent: &str, addressbook_id: &str) -> Self {
+        let expand = expand_raw_property();
+        let url = endpoint(
+            base_url,
+            &contacts_path(addressbook_id),
+            &[("$top", PAGE_SIZE), ("$expand", &expand)],
+        );
+
+        Self {
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            contacts: Vec::new(),
+            inner: MsGraphSend::get(&url, token, user_agent),
+        }
+    }
+}
+
+impl HttpCoroutine for MsGraphContactPager {
+    type Yield = HttpYield;
+    type Return = Result<Vec<MsGraphContact>, MsGraphSendError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            let body = match self.inner.resume(arg.take()) {
+                HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                HttpCoroutineState::Complete(Ok(body)) => body,
+                HttpCoroutineState::Complete(Err(err)) => {
+                    return HttpCoroutineState::Complete(Err(err));
+                }
+            };
+
+            let page: CollectionPage<MsGraphContact> = match from_value(body) {
+                Ok(page) => page,
+                Err(err) => return HttpCoroutineState::Complete(Err(err)),
+            };
+
+            self.contacts.extend(page.value);
+
+            let Some(link) = page.next_link else {
+                let contacts = core::mem::take(&mut self.contacts);
+                return HttpCoroutineState::Complete(Ok(contacts));
+            };
+
+            match next_link(&link) {
+                Ok(url) => self.inner = MsGraphSend::get(&url, &self.token, &self.user_agent),
+                Err(err) => return HttpCoroutineState::Complete(Err(err)),
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Microsoft Graph card operations: create, delete, get, list,
+//! multiget, sync, update.
+//!
+//! Cards map to the contacts of the contact folder of the
+//! addressbook.
+
+pub mod create;
+pub mod delete;
+pub mod get;
+pub mod list;
+pub mod multiget;
+pub mod sync;
+pub mod update;
@@ -0,0 +1,210 @@
+//! Microsoft Graph card multiget coroutine, `$batch` requests of up
+//! to 20 contact `GET`s each.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let batch = client.get_cards("contacts", &[&first_id, &second_id])?;
+//! ```
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::CardBatch,
+    http::coroutine::*,
+    msgraph::{
+        contact::{MsGraphContact, expand_raw_property},
+        convert::{
+            BATCH_SIZE, batch_url, card_from_contact, contact_path, endpoint, from_value,
+            is_valid_id,
+        },
+        send::{MsGraphSend, MsGraphSendError, status_error},
+    },
+};
+
+/// Errors produced by [`MsGraphCardMultiget`].
+#[derive(Debug, Error)]
+pub enum MsGraphCardMultigetError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct BatchResponse {
+    responses: Vec<BatchItem>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct BatchItem {
+    id: String,
+    status: u16,
+    body: Value,
+}
+
+/// I/O-free coroutine fetching several Microsoft Graph contacts.
+///
+/// Each `$batch` request carries one `GET` per contact, answered
+/// independently: contacts missing from the folder end up in
+/// [`CardBatch::missing`], in request order, while any other failure
+/// fails the whole coroutine.
+pub struct MsGraphCardMultiget {
+    base_url: Url,
+    token: String,
+    user_agent: String,
+    addressbook_id: String,
+    ids: Vec<String>,
+    position: usize,
+    contacts: BTreeMap<usize, MsGraphContact>,
+    inner: Option<MsGraphSend>,
+}
+
+impl MsGraphCardMultiget {
+    /// Builds the coroutine fetching `card_ids` from `addressbook_id`,
+    /// rejecting any invalid id.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<Self, MsGraphCardMultigetError> {
+        trace!("prepare msgraph card multiget");
+
+        if let Some(id) = card_ids.iter().find(|id| !is_valid_id(id)) {
+            return Err(MsGraphCardMultigetError::InvalidCardId(id.to_string()));
+        }
+
+        let mut coroutine = Self {
+            base_url: base_url.clone(),
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            addressbook_id: addressbook_id.to_string(),
+            ids: card_ids.iter().map(ToString::to_string).collect(),
+            position: 0,
+            contacts: BTreeMap::new(),
+            inner: None,
+        };
+
+        coroutine.inner = coroutine.next_request();
+        Ok(coroutine)
+    }
+
+    /// Builds the request fetching the next batch of ids, if any.
+    ///
+    /// Requests are identified by the position of their id.
+    fn next_request(&mut self) -> Option<MsGraphSend> {
+        let end = self.ids.len().min(self.position + BATCH_SIZE);
+
+        if self.position == end {
+            return None;
+        }
+
+        let expand = expand_raw_property();
+
+        let requests: Vec<Value> = (self.position..end)
+            .map(|i| {
+                let path = contact_path(&self.addressbook_id, &self.ids[i]);
+                let url = endpoint(&self.base_url, &path, &[("$expand", &expand)]);
+
+                json!({
+                    "id": i.to_string(),
+                    "method": "GET",
+                    "url": batch_url(&self.base_url, &url),
+                })
+            })
+            .collect();
+
+        self.position = end;
+
+        let url = endpoint(&self.base_url, "$batch", &[]);
+        let body = json!({ "requests": requests });
+        Some(MsGraphSend::post(
+            &url,
+            &self.token,
+            &self.user_agent,
+            &body,
+        ))
+    }
+
+    /// Collects the contacts of a `$batch` response.
+    fn collect(&mut self, body: Value) -> Result<(), MsGraphSendError> {
+        let batch: BatchResponse = from_value(body)?;
+
+        for item in batch.responses {
+            let Ok(position) = item.id.parse::<usize>() else {
+                continue;
+            };
+
+            match item.status {
+                200..300 => {
+                    let contact: MsGraphContact = from_value(item.body)?;
+                    self.contacts.insert(position, contact);
+                }
+                // NOTE: malformed ids get a 400 rather than a 404
+                400 | 404 => (),
+                status => {
+                    let body = serde_json::to_vec(&item.body).unwrap_or_default();
+                    return Err(status_error(status, &body));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> CardBatch {
+        let mut batch = CardBatch::default();
+
+        for (position, id) in self.ids.iter().enumerate() {
+            match self.contacts.get(&position) {
+                Some(contact) => batch
+                    .cards
+                    .push(card_from_contact(&self.addressbook_id, contact)),
+                None => batch.missing.push(id.clone()),
+            }
+        }
+
+        batch
+    }
+}
+
+impl HttpCoroutine for MsGraphCardMultiget {
+    type Yield = HttpYield;
+    type Return = Result<CardBatch, MsGraphCardMultigetError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            let Some(inner) = &mut self.inner else {
+                return HttpCoroutineState::Complete(Ok(self.finish()));
+            };
+
+            let body = match inner.resume(arg.take()) {
+                HttpCoroutineState::Yielded(y) => return HttpCoroutineState::Yielded(y),
+                HttpCoroutineState::Complete(Ok(body)) => body,
+                HttpCoroutineState::Complete(Err(err)) => {
+                    return HttpCoroutineState::Complete(Err(err.into()));
+                }
+            };
+
+            if let Err(err) = self.collect(body) {
+                return HttpCoroutineState::Complete(Err(err.into()));
+            }
+
+            self.inner = self.next_request();
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Microsoft Graph card sync coroutine, a delta query on the contacts
+//! of a contact folder followed by a `$batch` fetch of the changed
+//! ones.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let changes = client.sync_cards("contacts", Some(&token))?;
+//! ```
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::{
+        CardChanges,
+        msgraph::multiget::{MsGraphCardMultiget, MsGraphCardMultigetError},
+    },
+    http::{coroutine::*, send::HttpSendError},
+    msgraph::{
+        contact::MsGraphContact,
+        convert::{CollectionPage, delta_token, endpoint, folder_path, from_value, next_link},
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+const INVALID_ID: &str = "invalid contact id in delta";
+
+/// Errors produced by [`MsGraphCardSync`].
+#[derive(Debug, Error)]
+pub enum MsGraphCardSyncError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+    #[error("Sync token `{0}` rejected by the server")]
+    InvalidSyncToken(String),
+}
+
+enum State {
+    /// Following the delta pages up to the delta link.
+    Delta(MsGraphSend),
+    /// Fetching the changed contacts, along with their verbatim vCard
+    /// properties, which delta queries cannot expand.
+    Fetch(Box<MsGraphCardMultiget>),
+}
+
+/// I/O-free coroutine fetching the cards of a Microsoft Graph contact
+/// folder changed since a sync token.
+///
+/// Sync tokens are the `$deltatoken` of the delta link ending the
+/// previous run. On completion returns the changed cards (sorted by
+/// id), the deleted ids and the next token. An absent token yields
+/// the full folder content, flagged as such. Expired tokens surface
+/// as [`MsGraphCardSyncError::InvalidSyncToken`].
+pub struct MsGraphCardSync {
+    base_url: Url,
+    token: String,
+    user_agent: String,
+    addressbook_id: String,
+    sync_token: Option<String>,
+    next_token: Option<String>,
+    changed: BTreeSet<String>,
+    deleted: BTreeSet<String>,
+    state: State,
+}
+
+impl MsGraphCardSync {
+    /// Builds the coroutine syncing `addressbook_id` from
+    /// `sync_token`, or from scratch when `None`.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Self {
+        trace!("prepare msgraph card sync");
+
+        let sync_token = sync_token.filter(|t| !t.is_empty());
+        let path = format!("{}/contacts/delta", folder_path(addressbook_id));
+
+        let url = match sync_token {
+            Some(sync_token) => endpoint(base_url, &path, &[("$deltatoken", sync_token)]),
+            None => endpoint(base_url, &path, &[("$select", "id")]),
+        };
+
+        Self {
+            base_url: base_url.clone(),
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            addressbook_id: addressbook_id.to_string(),
+            sync_token: sync_token.map(ToString::to_string),
+            next_token: None,
+            changed: BTreeSet::new(),
+            deleted: BTreeSet::new(),
+            state: State::Delta(MsGraphSend::get(&url, token, user_agent)),
+        }
+    }
+
+    /// Collects the changes of a delta page, returning the link to the
+    /// next page if any.
+    fn collect(&mut self, page: CollectionPage<MsGraphContact>) -> Option<String> {
+        for contact in page.value {
+            if contact.removed.is_none() {
+                self.deleted.remove(&contact.id);
+                self.changed.insert(contact.id);
+            } else if self.sync_token.is_some() {
+                self.changed.remove(&contact.id);
+                self.deleted.insert(contact.id);
+            }
+        }
+
+        if let Some(link) = &page.delta_link {
+            self.next_token = delta_token(link);
+        }
+
+        page.next_link
+    }
+
+    fn fetch(&self) -> Result<MsGraphCardMultiget, MsGraphSendError> {
+        let ids: Vec<&str> = self.changed.iter().map(String::as_str).collect();
+
+        MsGraphCardMultiget::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            &self.addressbook_id,
+            &ids,
+        )
+        .map_err(|_| MsGraphSendError::Http(HttpSendError::InvalidResponse(INVALID_ID)))
+    }
+}
+
+impl HttpCoroutine for MsGraphCardSync {
+    type Yield = HttpYield;
+    type Return = Result<CardChanges, MsGraphCardSyncError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match &mut self.state {
+                State::Delta(send) => {
+                    let body = match send.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => {
+                            return HttpCoroutineState::Yielded(y);
+                        }
+                        HttpCoroutineState::Complete(Ok(body)) => body,
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            let err = match &self.sync_token {
+                                Some(token) if err.is_invalid_sync_state() => {
+                                    MsGraphCardSyncError::InvalidSyncToken(token.clone())
+                                }
+                                _ => err.into(),
+                            };
+                            return HttpCoroutineState::Complete(Err(err));
+                        }
+                    };
+
+                    let page = match from_value(body) {
+                        Ok(page) => page,
+                        Err(err) => return HttpCoroutineState::Complete(Err(err.into())),
+                    };
+
+                    let next = match self.collect(page) {
+                        Some(link) => next_link(&link).map(|url| {
+                            State::Delta(MsGraphSend::get(&url, &self.token, &self.user_agent))
+                        }),
+                        None => self
+                            .fetch()
+                            .map(|multiget| State::Fetch(Box::new(multiget))),
+                    };
+
+                    match next {
+                        Ok(state) => self.state = state,
+                        Err(err) => return HttpCoroutineState::Complete(Err(err.into())),
+                    }
+                }
+                State::Fetch(multiget) => {
+                    let batch = match multiget.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => {
+                            return HttpCoroutineState::Yielded(y);
+                        }
+                        HttpCoroutineState::Complete(Ok(batch)) => batch,
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            // NOTE: ids are checked when building the
+                            // multiget, so only send errors remain
+                            let MsGraphCardMultigetError::Send(err) = err else {
+                                let err = MsGraphSendError::Http(HttpSendError::InvalidResponse(
+                                    INVALID_ID,
+                                ));
+                                return HttpCoroutineState::Complete(Err(err.into()));
+                            };
+                            return HttpCoroutineState::Complete(Err(err.into()));
+                        }
+                    };
+
+                    // NOTE: contacts deleted between the delta query
+                    // and the fetch are reported as such
+                    let mut deleted = core::mem::take(&mut self.deleted);
+                    if self.sync_token.is_some() {
+                        deleted.extend(batch.missing);
+                    }
+
+                    let mut cards = batch.cards;
+                    cards.sort_by(|a, b| a.id.cmp(&b.id));
+
+                    let changes = CardChanges {
+                        cards,
+                        deleted: deleted.into_iter().collect(),
+                        sync_token: self.next_token.take(),
+                        full: self.sync_token.is_none(),
+                    };
+
+                    return HttpCoroutineState::Complete(Ok(changes));
+                }
+            }
+        }
+    }
+}
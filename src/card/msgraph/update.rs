@@ -0,0 +1,171 @@
+//! Microsoft Graph card update coroutine, a `GET` on the contact then
+//! a `PATCH` with the Graph rendering of a vCard.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.update_card("contacts", &contact_id, contents, Some(&etag))?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::{CardRef, Contact, ParseContactError, msgraph::get::MsGraphContactGet},
+    http::coroutine::*,
+    msgraph::{
+        contact::MsGraphContact,
+        convert::{
+            card_ref_from_contact, contact_path, endpoint, etag_matches, from_value, is_valid_id,
+        },
+        send::{MsGraphSend, MsGraphSendError},
+    },
+};
+
+/// Errors produced by [`MsGraphCardUpdate`].
+#[derive(Debug, Error)]
+pub enum MsGraphCardUpdateError {
+    #[error(transparent)]
+    Send(#[from] MsGraphSendError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card body is empty")]
+    EmptyCardBody,
+    #[error("Invalid card: {0}")]
+    InvalidCard(ParseContactError),
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{card_id}` changed on the server (current ETag: {etag:?})")]
+    PreconditionFailed {
+        card_id: String,
+        etag: Option<String>,
+    },
+}
+
+enum State {
+    Get(MsGraphContactGet),
+    Update(MsGraphSend),
+}
+
+/// I/O-free coroutine overwriting an existing Microsoft Graph contact.
+///
+/// The contact is fetched first, which checks that it belongs to the
+/// folder and matches `if_match`. Every mapped field is sent, so that
+/// the ones the card lacks are cleared. On completion returns the
+/// card id and new `@odata.etag`.
+pub struct MsGraphCardUpdate {
+    url: Url,
+    token: String,
+    user_agent: String,
+    id: String,
+    if_match: Option<String>,
+    contact: MsGraphContact,
+    state: State,
+}
+
+impl MsGraphCardUpdate {
+    /// Builds the coroutine overwriting `card_id` inside
+    /// `addressbook_id` with the vCard `contents`, rejecting an invalid
+    /// id or an empty or invalid body.
+    pub fn new(
+        base_url: &Url,
+        token: &str,
+        user_agent: &str,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: &[u8],
+        if_match: Option<&str>,
+    ) -> Result<Self, MsGraphCardUpdateError> {
+        trace!("prepare msgraph card update");
+
+        if !is_valid_id(card_id) {
+            return Err(MsGraphCardUpdateError::InvalidCardId(card_id.to_string()));
+        }
+
+        if contents.is_empty() {
+            return Err(MsGraphCardUpdateError::EmptyCardBody);
+        }
+
+        let contact = Contact::from_bytes(contents).map_err(MsGraphCardUpdateError::InvalidCard)?;
+        let url = endpoint(base_url, &contact_path(addressbook_id, card_id), &[]);
+        let get = MsGraphContactGet::new(base_url, token, user_agent, addressbook_id, card_id);
+
+        Ok(Self {
+            url,
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            id: card_id.to_string(),
+            if_match: if_match.map(ToString::to_string),
+            contact: MsGraphContact::from_contact(&contact),
+            state: State::Get(get),
+        })
+    }
+}
+
+impl HttpCoroutine for MsGraphCardUpdate {
+    type Yield = HttpYield;
+    type Return = Result<CardRef, MsGraphCardUpdateError>;
+
+    fn resume(&mut self, mut arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match &mut self.state {
+                State::Get(get) => {
+                    let current = match get.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => {
+                            return HttpCoroutineState::Yielded(y);
+                        }
+                        HttpCoroutineState::Complete(Ok(Some(current))) => current,
+                        HttpCoroutineState::Complete(Ok(None)) => {
+                            let err = MsGraphCardUpdateError::CardNotFound(self.id.clone());
+                            return HttpCoroutineState::Complete(Err(err));
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => {
+                            return HttpCoroutineState::Complete(Err(err.into()));
+                        }
+                    };
+
+                    let etag = current.etag.clone().unwrap_or_default();
+
+                    if let Some(if_match) = &self.if_match {
+                        if !etag_matches(if_match, &etag) {
+                            let err = MsGraphCardUpdateError::PreconditionFailed {
+                                card_id: self.id.clone(),
+                                etag: Some(etag),
+                            };
+                            return HttpCoroutineState::Complete(Err(err));
+                        }
+                    }
+
+                    if current.has_raw_properties() {
+                        self.contact.clear_raw_properties();
+                    }
+
+                    let body = serde_json::to_value(&self.contact).unwrap_or_default();
+                    let send = MsGraphSend::patch(&self.url, &self.token, &self.user_agent, &body);
+                    self.state = State::Update(send);
+                }
+                State::Update(send) => {
+                    let result = match send.resume(arg.take()) {
+                        HttpCoroutineState::Yielded(y) => {
+                            return HttpCoroutineState::Yielded(y);
+                        }
+                        HttpCoroutineState::Complete(Ok(body)) => {
+                            from_value::<MsGraphContact>(body)
+                                .map(|contact| card_ref_from_contact(&contact))
+                                .map_err(Into::into)
+                        }
+                        HttpCoroutineState::Complete(Err(err)) if err.is_status(404) => {
+                            Err(MsGraphCardUpdateError::CardNotFound(self.id.clone()))
+                        }
+                        HttpCoroutineState::Complete(Err(err)) => Err(err.into()),
+                    };
+
+                    return HttpCoroutineState::Complete(result);
+                }
+            }
+        }
+    }
+}
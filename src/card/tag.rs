@@ -0,0 +1,60 @@
+//! Entity tags synthesized from card contents.
+//!
+//! Backends without native ETags (vdir, in-memory, SQLite, `.vcf`,
+//! abook and mutt alias files, LDAP) hash the raw card bytes into a tag that gates
+//! conditional writes the way a CardDAV ETag does.
+
+use alloc::{format, string::String};
+
+/// Synthesizes a stable entity tag from raw card contents.
+///
+/// Same bytes always give the same tag, so it can gate conditional
+/// writes the way a CardDAV ETag does.
+pub(crate) fn content_tag(contents: &[u8]) -> String {
+    let mut hasher = TagHasher::default();
+    hasher.write(contents);
+    hasher.finish()
+}
+
+/// Compares an `If-Match`-style tag against a synthesized one,
+/// tolerating the surrounding quotes of the HTTP form.
+#[cfg(any(
+    feature = "vdir",
+    feature = "memory",
+    feature = "sqlite",
+    feature = "vcf-file",
+    feature = "jmap",
+    feature = "abook",
+    feature = "mutt-alias"
+))]
+pub(crate) fn tag_matches(if_match: &str, tag: &str) -> bool {
+    if_match.trim_matches('"') == tag
+}
+
+/// Incremental FNV-1a (64-bit) hasher rendering lowercase hex tags.
+///
+/// Not cryptographic: tags only need to change when the hashed data
+/// does, and the algorithm must stay stable across releases since
+/// callers persist them.
+pub(crate) struct TagHasher(u64);
+
+impl Default for TagHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl TagHasher {
+    /// Feeds `bytes` into the hash state.
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    /// Renders the hash state as a 16-digit hex tag.
+    pub(crate) fn finish(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
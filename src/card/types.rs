@@ -10,6 +10,8 @@ use calcard::{Entry, icalendar::ICalendarComponentType, vcard::VCard};
 #[cfg(feature = "parser")]
 use thiserror::Error;
 
+use crate::card::{Contact, ParseContactError};
+
 /// Errors surfaced when parsing raw card bytes as a vCard.
 #[cfg(feature = "parser")]
 #[derive(Clone, Debug, Error)]
@@ -34,8 +36,9 @@ pub enum ParseCardError {
 
 /// A single card inside an addressbook.
 ///
-/// Strict least-common-denominator shape: contents stay raw bytes; the
-/// optional `parser` feature exposes calcard-backed helpers on top.
+/// Strict least-common-denominator shape: contents stay raw bytes;
+/// [`Card::as_contact`] gives a typed view on top, and the optional
+/// `parser` feature exposes calcard-backed helpers.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
@@ -48,7 +51,7 @@ pub struct Card {
     pub addressbook_id: String,
 
     /// Entity tag (RFC 9110 §8.8.3, without surrounding quotes) when
-    /// the backend exposes it; vdir surfaces `None`.
+    /// the backend exposes it; vdir synthesizes one from the contents.
     #[cfg_attr(feature = "serde", serde(default))]
     pub etag: Option<String>,
 
@@ -56,12 +59,120 @@ pub struct Card {
     pub contents: Vec<u8>,
 }
 
+/// Options of a card creation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct CardCreateOptions {
+    /// Derives the card id from the vCard `UID` instead of minting a
+    /// random one, injecting a fresh `UID` when the card lacks one.
+    /// Creation then fails when a card with that id already exists,
+    /// which makes re-importing the same file idempotent. Contents
+    /// that do not parse as vCard fall back to a random id.
+    ///
+    /// The duplicate check compares ids, not `UID`s: a card sharing
+    /// the `UID` but stored under another id (created with this option
+    /// off, or by another tool) is not detected, and a second card
+    /// with that `UID` gets created next to it.
+    ///
+    /// Defaults to `true` with the `parser` feature, `false` without.
+    #[cfg_attr(feature = "serde", serde(default = "id_from_uid_default"))]
+    pub id_from_uid: bool,
+}
+
+impl Default for CardCreateOptions {
+    fn default() -> Self {
+        Self {
+            id_from_uid: id_from_uid_default(),
+        }
+    }
+}
+
+fn id_from_uid_default() -> bool {
+    cfg!(feature = "parser")
+}
+
+/// Lightweight reference to a card: identity and version, no
+/// contents.
+///
+/// Meant for cheap change detection: compare `etag` against a local
+/// cache, then fetch only what changed via `get_cards`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct CardRef {
+    /// Card identifier, same as [`Card::id`].
+    pub id: String,
+
+    /// Resource path of the card on the server (CardDAV only).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub href: Option<String>,
+
+    /// Entity tag, same as [`Card::etag`] for the same card version.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub etag: Option<String>,
+}
+
+/// Changes reported by an incremental card synchronization.
+///
+/// Produced from a sync token (RFC 6578 sync-collection) when the
+/// backend supports one, or from a full listing otherwise; `full`
+/// tells the two apart.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct CardChanges {
+    /// Cards added or changed since the given sync token, or every
+    /// card of the addressbook when `full` is `true`.
+    pub cards: Vec<Card>,
+
+    /// Identifiers of the cards removed since the given sync token.
+    /// Always empty when `full` is `true`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub deleted: Vec<String>,
+
+    /// Token to pass to the next synchronization, when the backend
+    /// exposes one.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sync_token: Option<String>,
+
+    /// Whether `cards` is a full listing rather than a delta: the
+    /// token was absent, rejected by the server, or unsupported by
+    /// the backend. Callers should treat every locally known card
+    /// missing from `cards` as removed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub full: bool,
+}
+
+/// Result of a batch card fetch.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct CardBatch {
+    /// Cards found, sorted by id.
+    pub cards: Vec<Card>,
+
+    /// Requested identifiers with no matching card, in request order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub missing: Vec<String>,
+}
+
 impl Card {
     /// Returns the raw card bytes.
     pub fn contents(&self) -> &[u8] {
         &self.contents
     }
 
+    /// Parses the bytes as a typed [`Contact`].
+    pub fn as_contact(&self) -> Result<Contact, ParseContactError> {
+        Contact::from_bytes(&self.contents)
+    }
+
+    /// Replaces the bytes with the serialized `contact`.
+    pub fn set_contact(&mut self, contact: &Contact) {
+        self.contents = contact.to_bytes();
+    }
+
     /// Parses the bytes as a vCard.
     #[cfg(feature = "parser")]
     pub fn as_vcard(&self) -> Result<VCard, ParseCardError> {
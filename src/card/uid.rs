@@ -0,0 +1,123 @@
+//! Card identifiers derived from the vCard `UID` property.
+//!
+//! Helpers shared by the backend create coroutines when
+//! [`CardCreateOptions::id_from_uid`] is enabled: find the `UID` of a
+//! raw vCard, inject a fresh one when missing, and map it to a resource
+//! name safe for both file names and URL path segments.
+//!
+//! [`CardCreateOptions::id_from_uid`]: crate::card::CardCreateOptions::id_from_uid
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::str::from_utf8;
+
+use crate::card::contact::parse_properties;
+
+/// Returns the `UID` of the raw vCard `contents`.
+///
+/// The outer `None` means the contents do not parse as a vCard; the
+/// inner one means the vCard has no (or an empty) `UID`.
+pub(crate) fn find_uid(contents: &[u8]) -> Option<Option<String>> {
+    let contents = from_utf8(contents).ok()?;
+    let props = parse_properties(contents).ok()?;
+
+    let uid = props
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case("UID"))
+        .map(|p| p.text())
+        .filter(|uid| !uid.trim().is_empty());
+
+    Some(uid)
+}
+
+/// Inserts a `UID:<uid>` line right before the last `END:VCARD` of
+/// `contents`, keeping every other byte untouched. Uses CRLF unless
+/// the contents only use bare LF line endings.
+pub(crate) fn inject_uid(contents: &[u8], uid: &str) -> Vec<u8> {
+    let eol: &[u8] = if contents.windows(2).any(|w| w == b"\r\n") {
+        b"\r\n"
+    } else {
+        b"\n"
+    };
+
+    let end = contents
+        .windows(9)
+        .rposition(|w| w.eq_ignore_ascii_case(b"END:VCARD"))
+        .unwrap_or(contents.len());
+
+    let mut out = Vec::with_capacity(contents.len() + uid.len() + 6);
+    out.extend_from_slice(&contents[..end]);
+
+    if !out.is_empty() && !out.ends_with(b"\n") {
+        out.extend_from_slice(eol);
+    }
+
+    out.extend_from_slice(b"UID:");
+    out.extend_from_slice(uid.as_bytes());
+    out.extend_from_slice(eol);
+    out.extend_from_slice(&contents[end..]);
+    out
+}
+
+/// Maps `uid` to a card id usable as a file stem and as a URL path
+/// segment.
+///
+/// The `urn:uuid:` prefix is dropped (unless nothing follows it).
+/// ASCII alphanumerics and `-`, `_`, `@` are kept as is, as is `.`
+/// except in first position; every other byte is written `=XX`
+/// (uppercase hex). Apart from that prefix, distinct UIDs never share
+/// an id.
+pub(crate) fn card_id_from_uid(uid: &str) -> String {
+    let uid = uid.trim();
+    let uid = match uid.get(..9) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("urn:uuid:") && uid.len() > 9 => &uid[9..],
+        _ => uid,
+    };
+
+    let mut id = String::with_capacity(uid.len());
+
+    for (i, byte) in uid.bytes().enumerate() {
+        match byte {
+            b'.' if i > 0 => id.push('.'),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'@' => id.push(byte as char),
+            _ => id.push_str(&format!("={byte:02X}")),
+        }
+    }
+
+    id
+}
+
+/// Generates a fresh card id from the system entropy source, used when
+/// the id is not derived from the vCard `UID`.
+pub(crate) fn fresh_card_id() -> Result<String, getrandom::Error> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes)?;
+    Ok(uuid_from_bytes(bytes))
+}
+
+/// Formats 16 random bytes as an RFC 9562 version 4 UUID.
+pub(crate) fn uuid_from_bytes(mut bytes: [u8; 16]) -> String {
+    // NOTE: RFC 4122 4.4 stamps version 4 and variant 10xx.
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut out = [0u8; 36];
+    let mut cursor = 0;
+    for (i, byte) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            out[cursor] = b'-';
+            cursor += 1;
+        }
+        out[cursor] = HEX[(byte >> 4) as usize];
+        out[cursor + 1] = HEX[(byte & 0x0f) as usize];
+        cursor += 2;
+    }
+
+    from_utf8(&out)
+        .expect("ASCII hex is always valid UTF-8")
+        .to_string()
+}
@@ -0,0 +1,55 @@
+//! Multi-card `.vcf` data: splitting an export into single vCards and
+//! joining vCards back into one file.
+
+use alloc::vec::Vec;
+
+/// Splits multi-card `.vcf` data into the raw bytes of each vCard, in
+/// order.
+///
+/// A vCard spans from a `BEGIN:VCARD` line to the matching
+/// `END:VCARD` line, both included along with their line endings.
+/// Nested vCards (vCard 2.1 `AGENT`) stay inside their parent; bytes
+/// outside any vCard are dropped, as is a trailing vCard missing its
+/// `END:VCARD`.
+pub fn split_vcf(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut cards = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut offset = 0;
+
+    for line in data.split_inclusive(|b| *b == b'\n') {
+        let content = line.trim_ascii_end();
+
+        if content.eq_ignore_ascii_case(b"BEGIN:VCARD") {
+            if depth == 0 {
+                start = offset;
+            }
+            depth += 1;
+        } else if content.eq_ignore_ascii_case(b"END:VCARD") && depth > 0 {
+            depth -= 1;
+            if depth == 0 {
+                cards.push(data[start..offset + line.len()].to_vec());
+            }
+        }
+
+        offset += line.len();
+    }
+
+    cards
+}
+
+/// Joins raw vCards into multi-card `.vcf` data, adding a CRLF after
+/// any vCard not ending with a line break.
+pub fn join_vcf<'a>(cards: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+    let mut data = Vec::new();
+
+    for card in cards {
+        data.extend_from_slice(card);
+
+        if !card.is_empty() && !card.ends_with(b"\n") {
+            data.extend_from_slice(b"\r\n");
+        }
+    }
+
+    data
+}
@@ -1,13 +1,22 @@
 //! Vdir card create coroutine wrapping
-//! [`io_vdir::item::store::VdirItemStore`] with a generated id.
+//! [`io_vdir::item::store::VdirItemStore`].
+//!
+//! The card id is known upfront: with
+//! [`CardCreateOptions::id_from_uid`] it derives from the vCard `UID`
+//! (a fresh one is injected when missing), otherwise it is a random
+//! UUID from a `WantsRandom` yield. A `WantsFileExists` yield then
+//! guards against overwriting an existing card, mirroring the
+//! `If-None-Match: *` of the CardDAV create. Only the derived file is
+//! probed: other cards are not scanned for the same `UID`.
 //!
 //! # Example
 //!
 //! ```rust,ignore
-//! let id = client.create_card("personal", contents)?;
+//! let card = client.create_card("personal", contents)?;
 //! ```
 
-use alloc::{string::String, vec::Vec};
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
+use core::mem;
 
 use io_vdir::{
     coroutine::*,
@@ -20,6 +29,14 @@ use io_vdir::{
 use log::trace;
 use thiserror::Error;
 
+use crate::{
+    card::tag::content_tag,
+    card::{
+        CardCreateOptions, CardRef,
+        uid::{card_id_from_uid, find_uid, inject_uid, uuid_from_bytes},
+    },
+};
+
 /// Errors produced by [`VdirCardCreate`].
 #[derive(Debug, Error)]
 pub enum VdirCardCreateError {
@@ -27,47 +44,147 @@ pub enum VdirCardCreateError {
     Store(#[from] VdirItemStoreError),
     #[error("Empty card body")]
     EmptyBody,
+    #[error("Card `{0}` already exists")]
+    AlreadyExists(String),
+    #[error("Invalid reply {0:?} to vdir card create")]
+    InvalidReply(Option<VdirReply>),
+}
+
+/// Progress of [`VdirCardCreate`].
+enum State {
+    /// Waiting for random bytes to mint the card id, and the `UID`
+    /// to inject when `inject_uid` is set.
+    Random { inject_uid: bool },
+    /// Waiting for the existence check of the `UID`-derived file.
+    Exists,
+    /// Running the inner store.
+    Store(VdirItemStore),
 }
 
 /// I/O-free coroutine writing a new Vcard item under a collection.
 ///
-/// The id is minted by the inner store coroutine; on completion the
-/// generated card id is returned.
+/// On completion returns the card id along with the synthesized tag
+/// of the stored contents.
 pub struct VdirCardCreate {
-    inner: VdirItemStore,
+    path: VdirPath,
+    id: Option<String>,
+    tag: String,
+    contents: Vec<u8>,
+    state: State,
 }
 
 impl VdirCardCreate {
-    /// Builds the coroutine storing `contents` as a fresh Vcard item
+    /// Builds the coroutine storing `contents` as a new Vcard item
     /// under the addressbook at `path`, rejecting empty contents.
-    pub fn new(path: impl Into<VdirPath>, contents: Vec<u8>) -> Result<Self, VdirCardCreateError> {
+    pub fn new(
+        path: impl Into<VdirPath>,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<Self, VdirCardCreateError> {
         trace!("prepare vdir card create");
 
         if contents.is_empty() {
             return Err(VdirCardCreateError::EmptyBody);
         }
 
+        let (id, state) = match options.id_from_uid.then(|| find_uid(&contents)) {
+            Some(Some(Some(uid))) => (Some(card_id_from_uid(&uid)), State::Exists),
+            Some(Some(None)) => (None, State::Random { inject_uid: true }),
+            Some(None) | None => (None, State::Random { inject_uid: false }),
+        };
+
         Ok(Self {
-            inner: VdirItemStore::new(
-                path,
-                None,
-                ItemKind::Vcard,
-                contents,
-                VdirItemStoreOptions::default(),
-            ),
+            path: path.into(),
+            id,
+            tag: String::new(),
+            contents,
+            state,
         })
     }
+
+    /// Returns the path of the card file, once its id is known.
+    fn card_path(&self, id: &str) -> VdirPath {
+        self.path.join(format!("{id}.vcf"))
+    }
+
+    /// Builds the inner store for the pending contents.
+    fn store(&mut self) -> State {
+        let contents = mem::take(&mut self.contents);
+        self.tag = content_tag(&contents);
+
+        State::Store(VdirItemStore::new(
+            self.path.clone(),
+            self.id.clone(),
+            ItemKind::Vcard,
+            contents,
+            VdirItemStoreOptions::default(),
+        ))
+    }
 }
 
 impl VdirCoroutine for VdirCardCreate {
     type Yield = VdirYield;
-    type Return = Result<String, VdirCardCreateError>;
+    type Return = Result<CardRef, VdirCardCreateError>;
+
+    fn resume(
+        &mut self,
+        mut arg: Option<VdirReply>,
+    ) -> VdirCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match (&mut self.state, arg.take()) {
+                (State::Random { .. }, None) => {
+                    return VdirCoroutineState::Yielded(VdirYield::WantsRandom { len: 16 });
+                }
+                (State::Random { inject_uid: inject }, Some(VdirReply::Random(bytes))) => {
+                    let Ok(bytes) = <[u8; 16]>::try_from(bytes.as_slice()) else {
+                        let err = VdirCardCreateError::InvalidReply(Some(VdirReply::Random(bytes)));
+                        return VdirCoroutineState::Complete(Err(err));
+                    };
+
+                    let uuid = uuid_from_bytes(bytes);
+
+                    if *inject {
+                        self.contents = inject_uid(&self.contents, &format!("urn:uuid:{uuid}"));
+                    }
+
+                    self.id = Some(uuid);
+                    self.state = State::Exists;
+                }
+                (State::Exists, None) => {
+                    let id = self.id.as_deref().unwrap_or_default();
+                    let paths = BTreeSet::from([self.card_path(id)]);
+                    return VdirCoroutineState::Yielded(VdirYield::WantsFileExists(paths));
+                }
+                (State::Exists, Some(VdirReply::FileExists(exists))) => {
+                    if exists.values().any(|exists| *exists) {
+                        let id = self.id.take().unwrap_or_default();
+                        let err = VdirCardCreateError::AlreadyExists(id);
+                        return VdirCoroutineState::Complete(Err(err));
+                    }
 
-    fn resume(&mut self, arg: Option<VdirReply>) -> VdirCoroutineState<Self::Yield, Self::Return> {
-        match self.inner.resume(arg) {
-            VdirCoroutineState::Yielded(y) => VdirCoroutineState::Yielded(y),
-            VdirCoroutineState::Complete(Ok(out)) => VdirCoroutineState::Complete(Ok(out.id)),
-            VdirCoroutineState::Complete(Err(err)) => VdirCoroutineState::Complete(Err(err.into())),
+                    self.state = self.store();
+                }
+                (State::Store(inner), arg) => {
+                    return match inner.resume(arg) {
+                        VdirCoroutineState::Yielded(y) => VdirCoroutineState::Yielded(y),
+                        VdirCoroutineState::Complete(Ok(out)) => {
+                            let card = CardRef {
+                                id: out.id,
+                                href: None,
+                                etag: Some(self.tag.clone()),
+                            };
+                            VdirCoroutineState::Complete(Ok(card))
+                        }
+                        VdirCoroutineState::Complete(Err(err)) => {
+                            VdirCoroutineState::Complete(Err(err.into()))
+                        }
+                    };
+                }
+                (_, arg) => {
+                    let err = VdirCardCreateError::InvalidReply(arg);
+                    return VdirCoroutineState::Complete(Err(err));
+                }
+            }
         }
     }
 }
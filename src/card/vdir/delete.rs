@@ -1,41 +1,74 @@
 //! Vdir card delete coroutine wrapping
 //! [`io_vdir::item::delete::VdirItemDelete`].
 //!
+//! When an `if_match` tag is given, the current item is first read
+//! through [`io_vdir::item::get::VdirItemGet`] and its synthesized
+//! tag compared, mirroring a CardDAV conditional `DELETE`.
+//!
 //! # Example
 //!
 //! ```rust,ignore
-//! client.delete_card("personal", "card-id")?;
+//! client.delete_card("personal", "card-id", None)?;
 //! ```
 
-use alloc::string::ToString;
+use alloc::string::{String, ToString};
 
 use io_vdir::{
     coroutine::*,
-    item::delete::{VdirItemDelete, VdirItemDeleteError, VdirItemDeleteOptions},
+    item::{
+        delete::{VdirItemDelete, VdirItemDeleteError, VdirItemDeleteOptions},
+        get::{VdirItemGet, VdirItemGetError, VdirItemGetOptions},
+    },
     path::VdirPath,
 };
 use log::trace;
 use thiserror::Error;
 
+use crate::card::{
+    tag::{content_tag, tag_matches},
+    vdir::CardExists,
+};
+
 /// Errors produced by [`VdirCardDelete`].
 #[derive(Debug, Error)]
 pub enum VdirCardDeleteError {
+    #[error(transparent)]
+    Get(#[from] VdirItemGetError),
     #[error(transparent)]
     Delete(#[from] VdirItemDeleteError),
+    #[error("Card `{0}` not found")]
+    NotFound(String),
+    #[error("Card precondition failed: expected tag `{expected}`, found `{actual}`")]
+    PreconditionFailed { expected: String, actual: String },
 }
 
 /// I/O-free coroutine locating then removing a Vdir card by its id.
 pub struct VdirCardDelete {
+    card_id: String,
+    exists: Option<CardExists>,
+    check: Option<(VdirItemGet, String)>,
     inner: VdirItemDelete,
 }
 
 impl VdirCardDelete {
     /// Builds the coroutine deleting card `card_id` from the
-    /// addressbook at `path`.
-    pub fn new(path: impl Into<VdirPath>, card_id: impl ToString) -> Self {
+    /// addressbook at `path`, gating the removal on `if_match` when
+    /// present.
+    pub fn new(path: impl Into<VdirPath>, card_id: impl ToString, if_match: Option<&str>) -> Self {
         trace!("prepare vdir card delete");
+
+        let path = path.into();
+        let card_id = card_id.to_string();
+        let check = if_match.map(|tag| {
+            let get = VdirItemGet::new(path.clone(), &card_id, VdirItemGetOptions::default());
+            (get, tag.to_string())
+        });
+
         Self {
-            inner: VdirItemDelete::new(path, card_id, VdirItemDeleteOptions::default()),
+            exists: Some(CardExists::new(&path, &card_id)),
+            check,
+            inner: VdirItemDelete::new(path, &card_id, VdirItemDeleteOptions::default()),
+            card_id,
         }
     }
 }
@@ -44,7 +77,41 @@ impl VdirCoroutine for VdirCardDelete {
     type Yield = VdirYield;
     type Return = Result<(), VdirCardDeleteError>;
 
-    fn resume(&mut self, arg: Option<VdirReply>) -> VdirCoroutineState<Self::Yield, Self::Return> {
+    fn resume(
+        &mut self,
+        mut arg: Option<VdirReply>,
+    ) -> VdirCoroutineState<Self::Yield, Self::Return> {
+        if let Some(exists) = &mut self.exists {
+            match exists.resume(arg.take()) {
+                Err(y) => return VdirCoroutineState::Yielded(y),
+                Ok(false) => {
+                    let err = VdirCardDeleteError::NotFound(self.card_id.clone());
+                    return VdirCoroutineState::Complete(Err(err));
+                }
+                Ok(true) => self.exists = None,
+            }
+        }
+
+        if let Some((get, expected)) = &mut self.check {
+            match get.resume(arg.take()) {
+                VdirCoroutineState::Yielded(y) => return VdirCoroutineState::Yielded(y),
+                VdirCoroutineState::Complete(Err(err)) => {
+                    return VdirCoroutineState::Complete(Err(err.into()));
+                }
+                VdirCoroutineState::Complete(Ok(item)) => {
+                    let actual = content_tag(&item.contents);
+
+                    if !tag_matches(expected, &actual) {
+                        let expected = expected.clone();
+                        let err = VdirCardDeleteError::PreconditionFailed { expected, actual };
+                        return VdirCoroutineState::Complete(Err(err));
+                    }
+
+                    self.check = None;
+                }
+            }
+        }
+
         match self.inner.resume(arg) {
             VdirCoroutineState::Yielded(y) => VdirCoroutineState::Yielded(y),
             VdirCoroutineState::Complete(r) => VdirCoroutineState::Complete(r.map_err(Into::into)),
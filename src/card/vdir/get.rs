@@ -20,7 +20,10 @@ use io_vdir::{
 use log::trace;
 use thiserror::Error;
 
-use crate::{card::Card, vdir::convert::card_from_item};
+use crate::{
+    card::{Card, vdir::CardExists},
+    vdir::convert::card_from_item,
+};
 
 /// Errors produced by [`VdirCardGet`].
 #[derive(Debug, Error)]
@@ -29,6 +32,8 @@ pub enum VdirCardGetError {
     Get(#[from] VdirItemGetError),
     #[error("Invalid card id `{0}`")]
     InvalidCardId(String),
+    #[error("Card `{0}` not found")]
+    NotFound(String),
 }
 
 /// I/O-free coroutine fetching a Vdir card by its id.
@@ -38,6 +43,7 @@ pub enum VdirCardGetError {
 pub struct VdirCardGet {
     addressbook_id: String,
     card_id: String,
+    exists: Option<CardExists>,
     inner: VdirItemGet,
 }
 
@@ -46,9 +52,12 @@ impl VdirCardGet {
     /// addressbook `addressbook_id` located at `path`.
     pub fn new(path: impl Into<VdirPath>, addressbook_id: &str, card_id: &str) -> Self {
         trace!("prepare vdir card get");
+        let path = path.into();
+
         Self {
             addressbook_id: addressbook_id.to_string(),
             card_id: card_id.to_string(),
+            exists: Some(CardExists::new(&path, card_id)),
             inner: VdirItemGet::new(path, card_id, VdirItemGetOptions::default()),
         }
     }
@@ -58,7 +67,21 @@ impl VdirCoroutine for VdirCardGet {
     type Yield = VdirYield;
     type Return = Result<Card, VdirCardGetError>;
 
-    fn resume(&mut self, arg: Option<VdirReply>) -> VdirCoroutineState<Self::Yield, Self::Return> {
+    fn resume(
+        &mut self,
+        mut arg: Option<VdirReply>,
+    ) -> VdirCoroutineState<Self::Yield, Self::Return> {
+        if let Some(exists) = &mut self.exists {
+            match exists.resume(arg.take()) {
+                Err(y) => return VdirCoroutineState::Yielded(y),
+                Ok(false) => {
+                    let err = VdirCardGetError::NotFound(self.card_id.clone());
+                    return VdirCoroutineState::Complete(Err(err));
+                }
+                Ok(true) => self.exists = None,
+            }
+        }
+
         match self.inner.resume(arg) {
             VdirCoroutineState::Yielded(y) => VdirCoroutineState::Yielded(y),
             VdirCoroutineState::Complete(Ok(item)) => {
@@ -1,7 +1,54 @@
-//! Vdir card operations: create, delete, get, list, update.
+//! Vdir card operations: create, delete, get, list, multiget, query,
+//! refs, update.
 
 pub mod create;
 pub mod delete;
 pub mod get;
 pub mod list;
+pub mod multiget;
+pub mod query;
+pub mod refs;
 pub mod update;
+
+use alloc::collections::BTreeSet;
+
+use io_vdir::{coroutine::*, path::VdirPath};
+
+/// Step checking that a card file exists before running an io-vdir
+/// coroutine on it, so that a missing card is reported as such rather
+/// than as an opaque io-vdir failure.
+///
+/// Card `id` is expected at `<addressbook>/<id>.vcf`.
+pub(crate) struct CardExists {
+    path: VdirPath,
+    sent: bool,
+}
+
+impl CardExists {
+    /// Builds the step probing card `card_id` of the addressbook at
+    /// `path`.
+    pub(crate) fn new(path: &VdirPath, card_id: &str) -> Self {
+        Self {
+            path: path.join(alloc::format!("{card_id}.vcf")),
+            sent: false,
+        }
+    }
+
+    /// Yields the probe on first call, then returns whether the card
+    /// file exists once `arg` holds the reply.
+    pub(crate) fn resume(&mut self, arg: Option<VdirReply>) -> Result<bool, VdirYield> {
+        if !self.sent {
+            self.sent = true;
+            return Err(VdirYield::WantsFileExists(BTreeSet::from([self
+                .path
+                .clone()])));
+        }
+
+        match arg {
+            Some(VdirReply::FileExists(exists)) => {
+                Ok(exists.get(&self.path).copied().unwrap_or(false))
+            }
+            _ => Ok(false),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Vdir card multiget coroutine.
+//!
+//! Fetches several cards at once: one multi-path `WantsFileExists`
+//! yield to sort out the missing ids, then one multi-path
+//! `WantsFileRead` yield for the others, instead of a directory scan
+//! per card.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let batch = client.get_cards("personal", &["card-a", "card-b"])?;
+//! ```
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use io_vdir::{coroutine::*, path::VdirPath};
+use log::trace;
+use thiserror::Error;
+
+use crate::{
+    card::tag::content_tag,
+    card::{Card, CardBatch},
+};
+
+/// Errors produced by [`VdirCardMultiget`].
+#[derive(Debug, Error)]
+pub enum VdirCardMultigetError {
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Invalid reply {0:?} to vdir card multiget")]
+    InvalidReply(Option<VdirReply>),
+}
+
+/// Progress of [`VdirCardMultiget`].
+enum State {
+    Exists,
+    Read,
+    Done,
+}
+
+/// I/O-free coroutine fetching a batch of Vdir cards by id.
+///
+/// Card `id` is expected at `<addressbook>/<id>.vcf`. On completion
+/// returns the cards found (sorted by id) and the ids with no file.
+pub struct VdirCardMultiget {
+    addressbook_id: String,
+    paths: BTreeMap<VdirPath, String>,
+    missing: Vec<String>,
+    state: State,
+}
+
+impl VdirCardMultiget {
+    /// Builds the coroutine fetching `card_ids` from the addressbook
+    /// `addressbook_id` located at `path`, rejecting empty ids and ids
+    /// containing a path separator.
+    pub fn new(
+        path: impl Into<VdirPath>,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<Self, VdirCardMultigetError> {
+        trace!("prepare vdir card multiget");
+
+        let path = path.into();
+        let mut paths = BTreeMap::new();
+
+        for id in card_ids {
+            if id.is_empty() || id.contains(['/', '\\']) {
+                return Err(VdirCardMultigetError::InvalidCardId(id.to_string()));
+            }
+
+            paths.insert(path.join(format!("{id}.vcf")), id.to_string());
+        }
+
+        Ok(Self {
+            addressbook_id: addressbook_id.to_string(),
+            paths,
+            missing: Vec::new(),
+            state: State::Exists,
+        })
+    }
+}
+
+impl VdirCoroutine for VdirCardMultiget {
+    type Yield = VdirYield;
+    type Return = Result<CardBatch, VdirCardMultigetError>;
+
+    fn resume(&mut self, arg: Option<VdirReply>) -> VdirCoroutineState<Self::Yield, Self::Return> {
+        match (&self.state, arg) {
+            (State::Exists, None) => {
+                if self.paths.is_empty() {
+                    self.state = State::Done;
+                    return VdirCoroutineState::Complete(Ok(CardBatch::default()));
+                }
+
+                let paths = self.paths.keys().cloned().collect();
+                VdirCoroutineState::Yielded(VdirYield::WantsFileExists(paths))
+            }
+            (State::Exists, Some(VdirReply::FileExists(exists))) => {
+                for (path, exists) in exists {
+                    if !exists && let Some(id) = self.paths.remove(&path) {
+                        self.missing.push(id);
+                    }
+                }
+
+                if self.paths.is_empty() {
+                    self.state = State::Done;
+                    let batch = CardBatch {
+                        cards: Vec::new(),
+                        missing: core::mem::take(&mut self.missing),
+                    };
+                    return VdirCoroutineState::Complete(Ok(batch));
+                }
+
+                self.state = State::Read;
+                let paths = self.paths.keys().cloned().collect();
+                VdirCoroutineState::Yielded(VdirYield::WantsFileRead(paths))
+            }
+            (State::Read, Some(VdirReply::FileRead(contents))) => {
+                self.state = State::Done;
+
+                let mut cards: Vec<Card> = contents
+                    .into_iter()
+                    .filter_map(|(path, contents)| {
+                        let id = self.paths.remove(&path)?;
+                        Some(Card {
+                            id,
+                            addressbook_id: self.addressbook_id.clone(),
+                            etag: Some(content_tag(&contents)),
+                            contents,
+                        })
+                    })
+                    .collect();
+                cards.sort_by(|a, b| a.id.cmp(&b.id));
+
+                // NOTE: files reported as existing but absent from the
+                // read reply vanished in between.
+                let mut missing = core::mem::take(&mut self.missing);
+                missing.extend(core::mem::take(&mut self.paths).into_values());
+
+                VdirCoroutineState::Complete(Ok(CardBatch { cards, missing }))
+            }
+            (_, arg) => VdirCoroutineState::Complete(Err(VdirCardMultigetError::InvalidReply(arg))),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Vdir card query coroutine wrapping
+//! [`io_vdir::item::list::VdirItemList`].
+//!
+//! vdir has no server to delegate to: every Vcard item is read and
+//! the [`CardFilter`] is evaluated locally via
+//! [`CardFilter::matches`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let filter = CardFilter::any([PropFilter::contains("EMAIL", "@example.org")]);
+//! let cards = client.search_cards("personal", &filter)?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use io_vdir::{
+    coroutine::*,
+    item::{
+        ItemKind,
+        list::{VdirItemList, VdirItemListError, VdirItemListOptions},
+    },
+    path::VdirPath,
+};
+use log::trace;
+use thiserror::Error;
+
+use crate::{
+    card::{Card, CardFilter},
+    vdir::convert::card_from_item,
+};
+
+/// Errors produced by [`VdirCardQuery`].
+#[derive(Debug, Error)]
+pub enum VdirCardQueryError {
+    #[error(transparent)]
+    List(#[from] VdirItemListError),
+}
+
+/// I/O-free coroutine searching the cards of a Vdir addressbook.
+///
+/// On completion keeps only the Vcard items matching the filter, maps
+/// each to a [`Card`], and sorts the result by id.
+pub struct VdirCardQuery {
+    addressbook_id: String,
+    filter: CardFilter,
+    inner: VdirItemList,
+}
+
+impl VdirCardQuery {
+    /// Builds the coroutine searching the cards of addressbook
+    /// `addressbook_id` located at `path` with `filter`.
+    pub fn new(path: impl Into<VdirPath>, addressbook_id: &str, filter: &CardFilter) -> Self {
+        trace!("prepare vdir card query");
+        Self {
+            addressbook_id: addressbook_id.to_string(),
+            filter: filter.clone(),
+            inner: VdirItemList::new(path, VdirItemListOptions::default()),
+        }
+    }
+}
+
+impl VdirCoroutine for VdirCardQuery {
+    type Yield = VdirYield;
+    type Return = Result<Vec<Card>, VdirCardQueryError>;
+
+    fn resume(&mut self, arg: Option<VdirReply>) -> VdirCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            VdirCoroutineState::Yielded(y) => VdirCoroutineState::Yielded(y),
+            VdirCoroutineState::Complete(Ok(items)) => {
+                let mut cards: Vec<Card> = items
+                    .into_iter()
+                    .filter(|item| matches!(item.kind, ItemKind::Vcard))
+                    .filter_map(|item| card_from_item(&self.addressbook_id, item))
+                    .filter(|card| self.filter.matches(card))
+                    .collect();
+                cards.sort_by(|a, b| a.id.cmp(&b.id));
+                VdirCoroutineState::Complete(Ok(cards))
+            }
+            VdirCoroutineState::Complete(Err(err)) => VdirCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Vdir card reference list coroutine wrapping
+//! [`io_vdir::item::list::VdirItemList`].
+//!
+//! vdir has no stored entity tag: the synthesized tag is a hash of
+//! the contents, so every file is still read, but only ids and tags
+//! are kept.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let refs = client.list_card_refs("personal")?;
+//! ```
+
+use alloc::{string::ToString, vec::Vec};
+
+use io_vdir::{
+    coroutine::*,
+    item::{
+        ItemKind,
+        list::{VdirItemList, VdirItemListError, VdirItemListOptions},
+    },
+    path::VdirPath,
+};
+use log::trace;
+use thiserror::Error;
+
+use crate::{card::CardRef, card::tag::content_tag};
+
+/// Errors produced by [`VdirCardRefList`].
+#[derive(Debug, Error)]
+pub enum VdirCardRefListError {
+    #[error(transparent)]
+    List(#[from] VdirItemListError),
+}
+
+/// I/O-free coroutine listing the id and synthesized tag of every
+/// Vcard in a Vdir addressbook.
+///
+/// On completion keeps only Vcard items, maps each to a [`CardRef`]
+/// (without href), and sorts the result by id.
+pub struct VdirCardRefList {
+    inner: VdirItemList,
+}
+
+impl VdirCardRefList {
+    /// Builds the coroutine listing card references of the addressbook
+    /// located at `path`.
+    pub fn new(path: impl Into<VdirPath>) -> Self {
+        trace!("prepare vdir card ref list");
+        Self {
+            inner: VdirItemList::new(path, VdirItemListOptions::default()),
+        }
+    }
+}
+
+impl VdirCoroutine for VdirCardRefList {
+    type Yield = VdirYield;
+    type Return = Result<Vec<CardRef>, VdirCardRefListError>;
+
+    fn resume(&mut self, arg: Option<VdirReply>) -> VdirCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            VdirCoroutineState::Yielded(y) => VdirCoroutineState::Yielded(y),
+            VdirCoroutineState::Complete(Ok(items)) => {
+                let mut refs: Vec<CardRef> = items
+                    .into_iter()
+                    .filter(|item| matches!(item.kind, ItemKind::Vcard))
+                    .filter_map(|item| {
+                        Some(CardRef {
+                            id: item.id()?.to_string(),
+                            href: None,
+                            etag: Some(content_tag(&item.contents)),
+                        })
+                    })
+                    .collect();
+                refs.sort_by(|a, b| a.id.cmp(&b.id));
+                VdirCoroutineState::Complete(Ok(refs))
+            }
+            VdirCoroutineState::Complete(Err(err)) => VdirCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -1,18 +1,26 @@
 //! Vdir card update coroutine wrapping
 //! [`io_vdir::item::store::VdirItemStore`] in overwrite mode.
 //!
+//! When an `if_match` tag is given, the current item is first read
+//! through [`io_vdir::item::get::VdirItemGet`] and its synthesized
+//! tag compared, mirroring a CardDAV conditional `PUT`.
+//!
 //! # Example
 //!
 //! ```rust,ignore
 //! client.update_card("personal", "card-id", contents, None)?;
 //! ```
 
-use alloc::{string::String, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 
 use io_vdir::{
     coroutine::*,
     item::{
         ItemKind,
+        get::{VdirItemGet, VdirItemGetError, VdirItemGetOptions},
         store::{VdirItemStore, VdirItemStoreError, VdirItemStoreOptions},
     },
     path::VdirPath,
@@ -20,27 +28,48 @@ use io_vdir::{
 use log::trace;
 use thiserror::Error;
 
+use crate::{
+    card::CardRef,
+    card::tag::{content_tag, tag_matches},
+    card::vdir::CardExists,
+};
+
 /// Errors produced by [`VdirCardUpdate`].
 #[derive(Debug, Error)]
 pub enum VdirCardUpdateError {
+    #[error(transparent)]
+    Get(#[from] VdirItemGetError),
     #[error(transparent)]
     Store(#[from] VdirItemStoreError),
     #[error("Empty card body")]
     EmptyBody,
+    #[error("Card `{0}` not found")]
+    NotFound(String),
+    #[error("Card precondition failed: expected tag `{expected}`, found `{actual}`")]
+    PreconditionFailed { expected: String, actual: String },
 }
 
 /// I/O-free coroutine overwriting an existing Vdir card's contents.
+///
+/// On completion returns the card id along with the synthesized tag
+/// of the new contents.
 pub struct VdirCardUpdate {
+    id: String,
+    tag: String,
+    exists: Option<CardExists>,
+    check: Option<(VdirItemGet, String)>,
     inner: VdirItemStore,
 }
 
 impl VdirCardUpdate {
     /// Builds the coroutine overwriting card `card_id` under the
-    /// addressbook at `path`, rejecting empty contents.
+    /// addressbook at `path`, gating the write on `if_match` when
+    /// present. Rejects empty contents.
     pub fn new(
         path: impl Into<VdirPath>,
         card_id: &str,
         contents: Vec<u8>,
+        if_match: Option<&str>,
     ) -> Result<Self, VdirCardUpdateError> {
         trace!("prepare vdir card update");
 
@@ -48,11 +77,20 @@ impl VdirCardUpdate {
             return Err(VdirCardUpdateError::EmptyBody);
         }
 
-        let id: String = card_id.into();
+        let path = path.into();
+        let check = if_match.map(|tag| {
+            let get = VdirItemGet::new(path.clone(), card_id, VdirItemGetOptions::default());
+            (get, tag.to_string())
+        });
+
         Ok(Self {
+            id: card_id.to_string(),
+            tag: content_tag(&contents),
+            exists: if_match.map(|_| CardExists::new(&path, card_id)),
+            check,
             inner: VdirItemStore::new(
                 path,
-                Some(id),
+                Some(card_id.to_string()),
                 ItemKind::Vcard,
                 contents,
                 VdirItemStoreOptions::default(),
@@ -63,12 +101,53 @@ impl VdirCardUpdate {
 
 impl VdirCoroutine for VdirCardUpdate {
     type Yield = VdirYield;
-    type Return = Result<(), VdirCardUpdateError>;
+    type Return = Result<CardRef, VdirCardUpdateError>;
+
+    fn resume(
+        &mut self,
+        mut arg: Option<VdirReply>,
+    ) -> VdirCoroutineState<Self::Yield, Self::Return> {
+        if let Some(exists) = &mut self.exists {
+            match exists.resume(arg.take()) {
+                Err(y) => return VdirCoroutineState::Yielded(y),
+                Ok(false) => {
+                    let err = VdirCardUpdateError::NotFound(self.id.clone());
+                    return VdirCoroutineState::Complete(Err(err));
+                }
+                Ok(true) => self.exists = None,
+            }
+        }
+
+        if let Some((get, expected)) = &mut self.check {
+            match get.resume(arg.take()) {
+                VdirCoroutineState::Yielded(y) => return VdirCoroutineState::Yielded(y),
+                VdirCoroutineState::Complete(Err(err)) => {
+                    return VdirCoroutineState::Complete(Err(err.into()));
+                }
+                VdirCoroutineState::Complete(Ok(item)) => {
+                    let actual = content_tag(&item.contents);
+
+                    if !tag_matches(expected, &actual) {
+                        let expected = expected.clone();
+                        let err = VdirCardUpdateError::PreconditionFailed { expected, actual };
+                        return VdirCoroutineState::Complete(Err(err));
+                    }
+
+                    self.check = None;
+                }
+            }
+        }
 
-    fn resume(&mut self, arg: Option<VdirReply>) -> VdirCoroutineState<Self::Yield, Self::Return> {
         match self.inner.resume(arg) {
             VdirCoroutineState::Yielded(y) => VdirCoroutineState::Yielded(y),
-            VdirCoroutineState::Complete(Ok(_)) => VdirCoroutineState::Complete(Ok(())),
+            VdirCoroutineState::Complete(Ok(_)) => {
+                let card = CardRef {
+                    id: self.id.clone(),
+                    href: None,
+                    etag: Some(self.tag.clone()),
+                };
+                VdirCoroutineState::Complete(Ok(card))
+            }
             VdirCoroutineState::Complete(Err(err)) => VdirCoroutineState::Complete(Err(err.into())),
         }
     }
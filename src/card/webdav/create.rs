@@ -1,24 +1,41 @@
 //! WebDAV card create coroutine wrapping
 //! [`io_webdav::rfc6352::card::create::CreateCard`].
 //!
+//! The `PUT` carries `If-None-Match: *`, so the server refuses to
+//! overwrite an existing resource (412, surfaced as
+//! [`WebdavCardCreateError::AlreadyExists`]). With
+//! [`CardCreateOptions::id_from_uid`], the resource name derives from
+//! the vCard `UID` (a fresh one is injected when missing); otherwise a
+//! random name is minted.
+//!
 //! # Example
 //!
 //! ```rust,ignore
-//! let id = client.create_card("personal", contents)?;
+//! let card = client.create_card("personal", contents)?;
 //! ```
 
-use alloc::{string::String, vec::Vec};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 use io_webdav::{
     coroutine::*,
     rfc4918::{WebdavAuth, send::SendError},
-    rfc6352::card::create::CreateCard,
+    rfc6352::card::{create::CreateCard, join_path},
 };
 use log::trace;
 use thiserror::Error;
 use url::Url;
 
-use crate::webdav::convert::fresh_card_id;
+use crate::{
+    card::{
+        CardCreateOptions, CardRef,
+        uid::{card_id_from_uid, find_uid, fresh_card_id, inject_uid},
+    },
+    webdav::convert::{card_uri, send_error_status},
+};
 
 /// Errors produced by [`WebdavCardCreate`].
 #[derive(Debug, Error)]
@@ -29,13 +46,19 @@ pub enum WebdavCardCreateError {
     EmptyCardBody,
     #[error("Failed to gather randomness for new card id: {0}")]
     Random(getrandom::Error),
+    #[error("Card `{0}` already exists")]
+    AlreadyExists(String),
 }
 
 /// I/O-free coroutine creating a WebDAV card.
 ///
-/// Synthesizes a fresh resource id; on completion returns the id the
-/// server confirmed.
+/// On completion returns the id the server confirmed, the resource
+/// href and the `ETag` response header (`None` when the server omitted
+/// it, as RFC 9110 allows when the stored body differs from the sent
+/// one).
 pub struct WebdavCardCreate {
+    card_id: String,
+    addressbook_path: String,
     inner: CreateCard,
 }
 
@@ -47,7 +70,8 @@ impl WebdavCardCreate {
         auth: &WebdavAuth,
         user_agent: &str,
         addressbook_path: &str,
-        contents: Vec<u8>,
+        mut contents: Vec<u8>,
+        options: CardCreateOptions,
     ) -> Result<Self, WebdavCardCreateError> {
         trace!("prepare webdav card create");
 
@@ -55,29 +79,56 @@ impl WebdavCardCreate {
             return Err(WebdavCardCreateError::EmptyCardBody);
         }
 
-        let card_id = fresh_card_id().map_err(WebdavCardCreateError::Random)?;
+        let fresh_card_id = || fresh_card_id().map_err(WebdavCardCreateError::Random);
+
+        let card_id = match options.id_from_uid.then(|| find_uid(&contents)) {
+            Some(Some(Some(uid))) => card_id_from_uid(&uid),
+            Some(Some(None)) => {
+                let uuid = fresh_card_id()?;
+                contents = inject_uid(&contents, &format!("urn:uuid:{uuid}"));
+                uuid
+            }
+            Some(None) | None => fresh_card_id()?,
+        };
+
+        let inner = CreateCard::new(
+            base_url,
+            auth,
+            user_agent,
+            addressbook_path,
+            &card_id,
+            contents,
+        );
 
         Ok(Self {
-            inner: CreateCard::new(
-                base_url,
-                auth,
-                user_agent,
-                addressbook_path,
-                &card_id,
-                contents,
-            ),
+            card_id,
+            addressbook_path: addressbook_path.to_string(),
+            inner,
         })
     }
 }
 
 impl WebdavCoroutine for WebdavCardCreate {
     type Yield = WebdavYield;
-    type Return = Result<String, WebdavCardCreateError>;
+    type Return = Result<CardRef, WebdavCardCreateError>;
 
     fn resume(&mut self, arg: Option<&[u8]>) -> WebdavCoroutineState<Self::Yield, Self::Return> {
         match self.inner.resume(arg) {
             WebdavCoroutineState::Yielded(y) => WebdavCoroutineState::Yielded(y),
-            WebdavCoroutineState::Complete(Ok(ok)) => WebdavCoroutineState::Complete(Ok(ok.id)),
+            WebdavCoroutineState::Complete(Ok(ok)) => {
+                let card = CardRef {
+                    href: Some(join_path(&self.addressbook_path, &card_uri(&ok.id))),
+                    id: ok.id,
+                    etag: ok.etag,
+                };
+                WebdavCoroutineState::Complete(Ok(card))
+            }
+            // NOTE: RFC 9110 §13.1.2: a failed `If-None-Match: *`
+            // means a resource already lives at that name.
+            WebdavCoroutineState::Complete(Err(err)) if send_error_status(&err) == Some(412) => {
+                let err = WebdavCardCreateError::AlreadyExists(self.card_id.clone());
+                WebdavCoroutineState::Complete(Err(err))
+            }
             WebdavCoroutineState::Complete(Err(err)) => {
                 WebdavCoroutineState::Complete(Err(err.into()))
             }
@@ -4,7 +4,7 @@
 //! # Example
 //!
 //! ```rust,ignore
-//! client.delete_card("personal", "card-id")?;
+//! client.delete_card("personal", "card-id", None)?;
 //! ```
 
 use alloc::string::String;
@@ -18,6 +18,8 @@ use log::trace;
 use thiserror::Error;
 use url::Url;
 
+use crate::webdav::convert::card_uri;
+
 /// Errors produced by [`WebdavCardDelete`].
 #[derive(Debug, Error)]
 pub enum WebdavCardDeleteError {
@@ -34,13 +36,15 @@ pub struct WebdavCardDelete {
 
 impl WebdavCardDelete {
     /// Builds the coroutine deleting card `card_id` from the collection
-    /// at `addressbook_path`, rejecting an empty card id.
+    /// at `addressbook_path`, gating the removal on `if_match` when
+    /// present. Rejects an empty card id.
     pub fn new(
         base_url: &Url,
         auth: &WebdavAuth,
         user_agent: &str,
         addressbook_path: &str,
         card_id: &str,
+        if_match: Option<&str>,
     ) -> Result<Self, WebdavCardDeleteError> {
         trace!("prepare webdav card delete");
 
@@ -49,7 +53,14 @@ impl WebdavCardDelete {
         }
 
         Ok(Self {
-            inner: DeleteCard::new(base_url, auth, user_agent, addressbook_path, card_id, None),
+            inner: DeleteCard::new(
+                base_url,
+                auth,
+                user_agent,
+                addressbook_path,
+                &card_uri(card_id),
+                if_match,
+            ),
         })
     }
 }
@@ -0,0 +1,97 @@
+//! WebDAV card ETag coroutine wrapping
+//! [`io_webdav::rfc4918::propfind::Propfind`].
+//!
+//! Issues a Depth: 0 PROPFIND for `DAV:getetag` on a single card. Used
+//! as a follow-up after a write whose response carried no `ETag`
+//! header.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let card = client.create_card("personal", contents)?;
+//! ```
+
+use alloc::string::{String, ToString};
+
+use io_webdav::{
+    coroutine::*,
+    rfc4918::{GETETAG, WebdavAuth, propfind::Propfind, send::SendError},
+    rfc6352::card::join_path,
+};
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{card::CardRef, webdav::convert::card_uri};
+
+/// Errors produced by [`WebdavCardEtag`].
+#[derive(Debug, Error)]
+pub enum WebdavCardEtagError {
+    #[error(transparent)]
+    Send(#[from] SendError),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+}
+
+/// I/O-free coroutine fetching the href and ETag of a WebDAV card,
+/// without its contents.
+pub struct WebdavCardEtag {
+    card_id: String,
+    card_path: String,
+    inner: Propfind,
+}
+
+impl WebdavCardEtag {
+    /// Builds the coroutine fetching the ETag of card `card_id` in the
+    /// collection at `addressbook_path`, rejecting an empty id.
+    pub fn new(
+        base_url: &Url,
+        auth: &WebdavAuth,
+        user_agent: &str,
+        addressbook_path: &str,
+        card_id: &str,
+    ) -> Result<Self, WebdavCardEtagError> {
+        trace!("prepare webdav card etag");
+
+        if card_id.is_empty() {
+            return Err(WebdavCardEtagError::InvalidCardId(String::new()));
+        }
+
+        let card_path = join_path(addressbook_path, &card_uri(card_id));
+        let inner = Propfind::new(base_url, auth, user_agent, &card_path, 0, &[GETETAG]);
+
+        Ok(Self {
+            card_id: card_id.to_string(),
+            card_path,
+            inner,
+        })
+    }
+}
+
+impl WebdavCoroutine for WebdavCardEtag {
+    type Yield = WebdavYield;
+    type Return = Result<CardRef, WebdavCardEtagError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> WebdavCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            WebdavCoroutineState::Yielded(y) => WebdavCoroutineState::Yielded(y),
+            WebdavCoroutineState::Complete(Ok(multistatus)) => {
+                let etag = multistatus
+                    .responses
+                    .iter()
+                    .find_map(|entry| entry.text(GETETAG))
+                    .map(|etag| etag.trim_matches('"').to_string());
+
+                let card = CardRef {
+                    id: self.card_id.clone(),
+                    href: Some(self.card_path.clone()),
+                    etag,
+                };
+                WebdavCoroutineState::Complete(Ok(card))
+            }
+            WebdavCoroutineState::Complete(Err(err)) => {
+                WebdavCoroutineState::Complete(Err(err.into()))
+            }
+        }
+    }
+}
@@ -18,7 +18,7 @@ use log::trace;
 use thiserror::Error;
 use url::Url;
 
-use crate::card::Card;
+use crate::{card::Card, webdav::convert::card_uri};
 
 /// Errors produced by [`WebdavCardGet`].
 #[derive(Debug, Error)]
@@ -59,7 +59,13 @@ impl WebdavCardGet {
         Ok(Self {
             addressbook_id: addressbook_id.to_string(),
             card_id: card_id.to_string(),
-            inner: ReadCard::new(base_url, auth, user_agent, addressbook_path, card_id),
+            inner: ReadCard::new(
+                base_url,
+                auth,
+                user_agent,
+                addressbook_path,
+                &card_uri(card_id),
+            ),
         })
     }
 }
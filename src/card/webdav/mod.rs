@@ -1,7 +1,13 @@
-//! CardDAV card operations: create, delete, get, list, update.
+//! CardDAV card operations: create, delete, etag, get, list,
+//! multiget, query, refs, sync, update.
 
 pub mod create;
 pub mod delete;
+pub mod etag;
 pub mod get;
 pub mod list;
+pub mod multiget;
+pub mod query;
+pub mod refs;
+pub mod sync;
 pub mod update;
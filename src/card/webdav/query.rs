@@ -0,0 +1,97 @@
+//! WebDAV card query coroutine wrapping
+//! [`io_webdav::rfc4918::report::Report`].
+//!
+//! Translates a [`CardFilter`] into an RFC 6352 §8.6
+//! addressbook-query REPORT, so that only matching cards travel over
+//! the wire.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let filter = CardFilter::any([PropFilter::contains("EMAIL", "@example.org")]);
+//! let cards = client.search_cards("personal", &filter)?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use io_webdav::{
+    coroutine::*,
+    rfc4918::{GETETAG, WebdavAuth, report::Report, report_query_body, send::SendError},
+    rfc6352::addressbook::{ADDRESS_DATA, ADDRESSBOOK_QUERY, CARDDAV},
+};
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::{Card, CardFilter},
+    webdav::convert::{card_from_response, filter_to_xml},
+};
+
+/// Errors produced by [`WebdavCardQuery`].
+#[derive(Debug, Error)]
+pub enum WebdavCardQueryError {
+    #[error(transparent)]
+    Send(#[from] SendError),
+}
+
+/// I/O-free coroutine searching the cards of a WebDAV addressbook
+/// collection server-side.
+///
+/// On completion maps each matching entry to a [`Card`] and sorts the
+/// result by id.
+pub struct WebdavCardQuery {
+    addressbook_id: String,
+    inner: Report,
+}
+
+impl WebdavCardQuery {
+    /// Builds the coroutine querying the collection at
+    /// `addressbook_path` (the addressbook `addressbook_id`) with
+    /// `filter`.
+    pub fn new(
+        base_url: &Url,
+        auth: &WebdavAuth,
+        user_agent: &str,
+        addressbook_path: &str,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Self {
+        trace!("prepare webdav card query");
+
+        let filter = filter_to_xml(filter);
+        let props = [GETETAG, ADDRESS_DATA];
+        let body = report_query_body(ADDRESSBOOK_QUERY, &[CARDDAV], &props, &filter);
+
+        Self {
+            addressbook_id: addressbook_id.to_string(),
+            inner: Report::new(base_url, auth, user_agent, addressbook_path, 1, body),
+        }
+    }
+}
+
+impl WebdavCoroutine for WebdavCardQuery {
+    type Yield = WebdavYield;
+    type Return = Result<Vec<Card>, WebdavCardQueryError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> WebdavCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            WebdavCoroutineState::Yielded(y) => WebdavCoroutineState::Yielded(y),
+            WebdavCoroutineState::Complete(Ok(multistatus)) => {
+                let mut cards: Vec<Card> = multistatus
+                    .responses
+                    .iter()
+                    .filter_map(|entry| card_from_response(&self.addressbook_id, entry))
+                    .collect();
+                cards.sort_by(|a, b| a.id.cmp(&b.id));
+                WebdavCoroutineState::Complete(Ok(cards))
+            }
+            WebdavCoroutineState::Complete(Err(err)) => {
+                WebdavCoroutineState::Complete(Err(err.into()))
+            }
+        }
+    }
+}
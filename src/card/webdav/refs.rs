@@ -0,0 +1,83 @@
+//! WebDAV card reference list coroutine wrapping
+//! [`io_webdav::rfc6352::card::enumerate::EnumCards`].
+//!
+//! Issues a Depth: 1 PROPFIND for `DAV:getetag` only, so no
+//! `address-data` crosses the wire.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let refs = client.list_card_refs("personal")?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use io_webdav::{
+    coroutine::*,
+    rfc4918::{WebdavAuth, send::SendError},
+    rfc6352::card::enumerate::EnumCards,
+};
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{card::CardRef, webdav::convert::card_ref_from_wire};
+
+/// Errors produced by [`WebdavCardRefList`].
+#[derive(Debug, Error)]
+pub enum WebdavCardRefListError {
+    #[error(transparent)]
+    Send(#[from] SendError),
+}
+
+/// I/O-free coroutine listing the id, href and ETag of every card
+/// inside a WebDAV addressbook collection.
+///
+/// On completion maps each wire entry to a [`CardRef`] and sorts the
+/// result by id.
+pub struct WebdavCardRefList {
+    addressbook_path: String,
+    inner: EnumCards,
+}
+
+impl WebdavCardRefList {
+    /// Builds the coroutine listing card references in the collection
+    /// at `addressbook_path`.
+    pub fn new(
+        base_url: &Url,
+        auth: &WebdavAuth,
+        user_agent: &str,
+        addressbook_path: &str,
+    ) -> Self {
+        trace!("prepare webdav card ref list");
+        Self {
+            addressbook_path: addressbook_path.to_string(),
+            inner: EnumCards::new(base_url, auth, user_agent, addressbook_path),
+        }
+    }
+}
+
+impl WebdavCoroutine for WebdavCardRefList {
+    type Yield = WebdavYield;
+    type Return = Result<Vec<CardRef>, WebdavCardRefListError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> WebdavCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            WebdavCoroutineState::Yielded(y) => WebdavCoroutineState::Yielded(y),
+            WebdavCoroutineState::Complete(Ok(wires)) => {
+                let mut refs: Vec<CardRef> = wires
+                    .into_iter()
+                    .map(|wire| card_ref_from_wire(&self.addressbook_path, wire))
+                    .collect();
+                refs.sort_by(|a, b| a.id.cmp(&b.id));
+                WebdavCoroutineState::Complete(Ok(refs))
+            }
+            WebdavCoroutineState::Complete(Err(err)) => {
+                WebdavCoroutineState::Complete(Err(err.into()))
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! WebDAV card sync coroutine wrapping
+//! [`io_webdav::rfc6578::sync_collection::SyncCollection`] and
+//! [`io_webdav::rfc6352::card::multiget::MultigetCards`].
+//!
+//! Issues an RFC 6578 sync-collection REPORT against the addressbook
+//! collection, running it again from the returned token as long as the
+//! server truncates the result, then fetches the changed cards in a
+//! single addressbook-multiget REPORT. A rejected sync token surfaces
+//! as [`WebdavCardSyncError::InvalidSyncToken`]; the client falls back
+//! to a tokenless (full) sync in that case.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let changes = client.sync_cards("personal", Some(&token))?;
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use io_webdav::{
+    coroutine::*,
+    rfc4918::{GETETAG, WebdavAuth, send::SendError},
+    rfc6352::card::multiget::MultigetCards,
+    rfc6578::sync_collection::{SyncCollection, SyncCollectionError},
+};
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    card::{Card, CardChanges},
+    webdav::convert::{card_from_entry, card_id_from_href, send_error_status},
+};
+
+/// Errors produced by [`WebdavCardSync`].
+#[derive(Debug, Error)]
+pub enum WebdavCardSyncError {
+    #[error(transparent)]
+    Send(#[from] SendError),
+    #[error("Sync token `{0}` rejected by the server")]
+    InvalidSyncToken(String),
+    #[error("Card sync resumed after completion")]
+    Done,
+}
+
+/// Progress of [`WebdavCardSync`].
+enum State {
+    Sync(SyncCollection),
+    Multiget(MultigetCards),
+    Done,
+}
+
+/// I/O-free coroutine fetching the cards changed in a WebDAV
+/// addressbook collection since a sync token.
+///
+/// On completion maps each changed entry to a [`Card`] (sorted by id)
+/// and returns them along with the removed ids and the new token. An
+/// absent token yields the full collection content, flagged as such.
+pub struct WebdavCardSync {
+    base_url: Url,
+    auth: WebdavAuth,
+    user_agent: String,
+    addressbook_path: String,
+    addressbook_id: String,
+    sync_token: Option<String>,
+    full: bool,
+    changed: Vec<String>,
+    deleted: Vec<String>,
+    state: State,
+}
+
+impl WebdavCardSync {
+    /// Builds the coroutine syncing the collection at
+    /// `addressbook_path` (the addressbook `addressbook_id`) from
+    /// `sync_token`, or from scratch when `None`.
+    pub fn new(
+        base_url: &Url,
+        auth: &WebdavAuth,
+        user_agent: &str,
+        addressbook_path: &str,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Self {
+        trace!("prepare webdav card sync");
+
+        // NOTE: an empty token is the RFC 6578 initial sync, same as
+        // no token at all.
+        let sync_token = sync_token.filter(|t| !t.is_empty());
+        let sync = SyncCollection::new(
+            base_url,
+            auth,
+            user_agent,
+            addressbook_path,
+            sync_token,
+            &[GETETAG],
+        );
+
+        Self {
+            base_url: base_url.clone(),
+            auth: auth.clone(),
+            user_agent: user_agent.to_string(),
+            addressbook_path: addressbook_path.to_string(),
+            addressbook_id: addressbook_id.to_string(),
+            sync_token: sync_token.map(str::to_string),
+            full: sync_token.is_none(),
+            changed: Vec::new(),
+            deleted: Vec::new(),
+            state: State::Sync(sync),
+        }
+    }
+
+    /// Builds the final [`CardChanges`] out of the fetched `cards`.
+    fn changes(&mut self, mut cards: Vec<Card>) -> CardChanges {
+        cards.sort_by(|a, b| a.id.cmp(&b.id));
+
+        CardChanges {
+            cards,
+            deleted: core::mem::take(&mut self.deleted),
+            sync_token: self.sync_token.take(),
+            full: self.full,
+        }
+    }
+
+    /// Maps a failed sync-collection REPORT to a
+    /// [`WebdavCardSyncError`].
+    fn sync_error(&mut self, err: SyncCollectionError) -> WebdavCardSyncError {
+        let err = match err {
+            SyncCollectionError::InvalidSyncToken => {
+                let token = self.sync_token.take().unwrap_or_default();
+                return WebdavCardSyncError::InvalidSyncToken(token);
+            }
+            SyncCollectionError::Send(err) => err,
+        };
+
+        // NOTE: RFC 6578 §3.2 reports an invalid or expired token
+        // through the DAV:valid-sync-token precondition, which servers
+        // send as 403 (some as 409, and not all of them name the
+        // precondition in the body).
+        let rejected = matches!(send_error_status(&err), Some(403 | 409));
+
+        match self.sync_token.take() {
+            Some(token) if rejected => WebdavCardSyncError::InvalidSyncToken(token),
+            _ => err.into(),
+        }
+    }
+}
+
+impl WebdavCoroutine for WebdavCardSync {
+    type Yield = WebdavYield;
+    type Return = Result<CardChanges, WebdavCardSyncError>;
+
+    fn resume(
+        &mut self,
+        mut arg: Option<&[u8]>,
+    ) -> WebdavCoroutineState<Self::Yield, Self::Return> {
+        loop {
+            match core::mem::replace(&mut self.state, State::Done) {
+                State::Sync(mut inner) => {
+                    let delta = match inner.resume(arg.take()) {
+                        WebdavCoroutineState::Yielded(y) => {
+                            self.state = State::Sync(inner);
+                            return WebdavCoroutineState::Yielded(y);
+                        }
+                        WebdavCoroutineState::Complete(Ok(delta)) => delta,
+                        WebdavCoroutineState::Complete(Err(err)) => {
+                            return WebdavCoroutineState::Complete(Err(self.sync_error(err)));
+                        }
+                    };
+
+                    self.changed.extend(delta.changed.into_iter().map(|change| {
+                        let uri = change.href.trim_end_matches('/').rsplit('/').next();
+                        uri.unwrap_or_default().to_string()
+                    }));
+                    self.deleted.extend(
+                        delta
+                            .vanished
+                            .iter()
+                            .map(|href| card_id_from_href(href).to_string()),
+                    );
+                    self.sync_token = delta.sync_token;
+
+                    // NOTE: a truncated result (RFC 6578 §3.6) is
+                    // drained by running the report again from the
+                    // token it returned, on the same resume call.
+                    if let Some(token) = self.sync_token.as_deref().filter(|_| delta.truncated) {
+                        trace!("sync-collection truncated, resuming from {token}");
+                        self.state = State::Sync(SyncCollection::new(
+                            &self.base_url,
+                            &self.auth,
+                            &self.user_agent,
+                            &self.addressbook_path,
+                            Some(token),
+                            &[GETETAG],
+                        ));
+                        continue;
+                    }
+
+                    // NOTE: an empty multiget is pointless, and some
+                    // servers reject it.
+                    if self.changed.is_empty() {
+                        let changes = self.changes(Vec::new());
+                        return WebdavCoroutineState::Complete(Ok(changes));
+                    }
+
+                    let uris: Vec<&str> = self.changed.iter().map(String::as_str).collect();
+                    self.state = State::Multiget(MultigetCards::new(
+                        &self.base_url,
+                        &self.auth,
+                        &self.user_agent,
+                        &self.addressbook_path,
+                        &uris,
+                    ));
+                }
+                State::Multiget(mut inner) => {
+                    return match inner.resume(arg.take()) {
+                        WebdavCoroutineState::Yielded(y) => {
+                            self.state = State::Multiget(inner);
+                            WebdavCoroutineState::Yielded(y)
+                        }
+                        WebdavCoroutineState::Complete(Ok(entries)) => {
+                            let cards = entries
+                                .into_iter()
+                                .map(|entry| card_from_entry(&self.addressbook_id, entry))
+                                .collect();
+                            WebdavCoroutineState::Complete(Ok(self.changes(cards)))
+                        }
+                        WebdavCoroutineState::Complete(Err(err)) => {
+                            WebdavCoroutineState::Complete(Err(err.into()))
+                        }
+                    };
+                }
+                State::Done => {
+                    return WebdavCoroutineState::Complete(Err(WebdavCardSyncError::Done));
+                }
+            }
+        }
+    }
+}
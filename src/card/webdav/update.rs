@@ -4,20 +4,25 @@
 //! # Example
 //!
 //! ```rust,ignore
-//! client.update_card("personal", "card-id", contents, None)?;
+//! let card = client.update_card("personal", "card-id", contents, None)?;
 //! ```
 
-use alloc::{string::String, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 
 use io_webdav::{
     coroutine::*,
     rfc4918::{WebdavAuth, send::SendError},
-    rfc6352::card::update::UpdateCard,
+    rfc6352::card::{join_path, update::UpdateCard},
 };
 use log::trace;
 use thiserror::Error;
 use url::Url;
 
+use crate::{card::CardRef, webdav::convert::card_uri};
+
 /// Errors produced by [`WebdavCardUpdate`].
 #[derive(Debug, Error)]
 pub enum WebdavCardUpdateError {
@@ -30,7 +35,12 @@ pub enum WebdavCardUpdateError {
 }
 
 /// I/O-free coroutine overwriting an existing WebDAV card.
+///
+/// On completion returns the card id, the resource href and the
+/// `ETag` response header, when the server sent one.
 pub struct WebdavCardUpdate {
+    id: String,
+    addressbook_path: String,
     inner: UpdateCard,
 }
 
@@ -58,12 +68,14 @@ impl WebdavCardUpdate {
         }
 
         Ok(Self {
+            id: card_id.to_string(),
+            addressbook_path: addressbook_path.to_string(),
             inner: UpdateCard::new(
                 base_url,
                 auth,
                 user_agent,
                 addressbook_path,
-                card_id,
+                &card_uri(card_id),
                 contents,
                 if_match,
             ),
@@ -73,12 +85,19 @@ impl WebdavCardUpdate {
 
 impl WebdavCoroutine for WebdavCardUpdate {
     type Yield = WebdavYield;
-    type Return = Result<(), WebdavCardUpdateError>;
+    type Return = Result<CardRef, WebdavCardUpdateError>;
 
     fn resume(&mut self, arg: Option<&[u8]>) -> WebdavCoroutineState<Self::Yield, Self::Return> {
         match self.inner.resume(arg) {
             WebdavCoroutineState::Yielded(y) => WebdavCoroutineState::Yielded(y),
-            WebdavCoroutineState::Complete(Ok(_)) => WebdavCoroutineState::Complete(Ok(())),
+            WebdavCoroutineState::Complete(Ok(ok)) => {
+                let card = CardRef {
+                    id: self.id.clone(),
+                    href: Some(join_path(&self.addressbook_path, &ok.uri)),
+                    etag: ok.etag,
+                };
+                WebdavCoroutineState::Complete(Ok(card))
+            }
             WebdavCoroutineState::Complete(Err(err)) => {
                 WebdavCoroutineState::Complete(Err(err.into()))
             }
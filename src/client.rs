@@ -2,27 +2,42 @@
 //!
 //! [`AddressbookClientStd`] is an enum over the single registered backend: a
 //! value is exactly one of the compiled-in per-backend clients ([`VdirClient`],
-//! [`WebdavClient`]). Unlike io-email's multi-backend `EmailClientStd` struct,
-//! an addressbook account speaks one protocol at a time, so the unified client
-//! is an enum rather than a bag of optional slots; dispatch is a plain `match`
-//! with no priority order.
+//! [`WebdavClient`], [`InMemoryClient`], [`SqliteClient`], [`VcfFileClient`],
+//! [`JmapClient`], [`GoogleClient`], [`LdapClient`], [`MsGraphClient`],
+//! [`AbookClient`], [`MuttAliasClient`], [`VdirGitClient`]).
+//! Unlike io-email's multi-backend `EmailClientStd` struct, an addressbook
+//! account speaks one protocol at a time, so the unified client is an enum
+//! rather than a bag of optional slots; dispatch is a plain `match` with no
+//! priority order.
 //!
 //! Build one via the per-backend `From` impls (e.g.
 //! `AddressbookClientStd::from(VdirClient::new(inner))`) or by naming the
-//! variant directly.
+//! variant directly. Third-party backends implementing [`AddressbookBackend`]
+//! plug in through the [`Custom`](AddressbookClientStd::Custom) variant.
 //!
 //! [`VdirClient`]: crate::vdir::client::VdirClient
 //! [`WebdavClient`]: crate::webdav::client::WebdavClient
+//! [`InMemoryClient`]: crate::memory::client::InMemoryClient
+//! [`SqliteClient`]: crate::sqlite::client::SqliteClient
+//! [`VcfFileClient`]: crate::vcf_file::client::VcfFileClient
+//! [`JmapClient`]: crate::jmap::client::JmapClient
+//! [`GoogleClient`]: crate::google::client::GoogleClient
+//! [`LdapClient`]: crate::ldap::client::LdapClient
+//! [`MsGraphClient`]: crate::msgraph::client::MsGraphClient
+//! [`AbookClient`]: crate::abook::client::AbookClient
+//! [`MuttAliasClient`]: crate::mutt_alias::client::MuttAliasClient
+//! [`VdirGitClient`]: crate::vdir::git::VdirGitClient
 
-#[cfg(feature = "webdav")]
-use alloc::boxed::Box;
-use alloc::{string::String, vec::Vec};
+use alloc::{boxed::Box, string::String, vec::Vec};
 
 use thiserror::Error;
 
 use crate::{
     addressbook::{Addressbook, AddressbookDiff},
-    card::Card,
+    backend::{AddressbookBackend, BackendError},
+    capabilities::Capabilities,
+    card::{Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef},
+    error::ErrorKind,
 };
 
 /// Errors surfaced by [`AddressbookClientStd`].
@@ -34,34 +49,220 @@ pub enum AddressbookClientStdError {
     #[cfg(feature = "vdir")]
     #[error(transparent)]
     Vdir(#[from] crate::vdir::client::VdirClientError),
+    #[cfg(feature = "vdir-git")]
+    #[error(transparent)]
+    VdirGit(#[from] crate::vdir::git::VdirGitClientError),
     #[cfg(feature = "webdav")]
     #[error(transparent)]
     Webdav(#[from] crate::webdav::client::WebdavClientError),
+    #[cfg(feature = "memory")]
+    #[error(transparent)]
+    Memory(#[from] crate::memory::client::InMemoryClientError),
+    #[cfg(feature = "sqlite")]
+    #[error(transparent)]
+    Sqlite(#[from] crate::sqlite::client::SqliteClientError),
+    #[cfg(feature = "vcf-file")]
+    #[error(transparent)]
+    VcfFile(#[from] crate::vcf_file::client::VcfFileClientError),
+    #[cfg(feature = "jmap")]
+    #[error(transparent)]
+    Jmap(#[from] crate::jmap::client::JmapClientError),
+    #[cfg(feature = "google")]
+    #[error(transparent)]
+    Google(#[from] crate::google::client::GoogleClientError),
+    #[cfg(feature = "ldap")]
+    #[error(transparent)]
+    Ldap(#[from] crate::ldap::client::LdapClientError),
+    #[cfg(feature = "msgraph")]
+    #[error(transparent)]
+    MsGraph(#[from] crate::msgraph::client::MsGraphClientError),
+    #[cfg(feature = "abook")]
+    #[error(transparent)]
+    Abook(#[from] crate::abook::client::AbookClientError),
+    #[cfg(feature = "mutt-alias")]
+    #[error(transparent)]
+    MuttAlias(#[from] crate::mutt_alias::client::MuttAliasClientError),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+impl AddressbookClientStdError {
+    /// Classifies the error independently of the backend, see
+    /// [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(err) => err.kind(),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(err) => err.kind(),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(err) => err.kind(),
+            #[cfg(feature = "memory")]
+            Self::Memory(err) => err.kind(),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(err) => err.kind(),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(err) => err.kind(),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(err) => err.kind(),
+            #[cfg(feature = "google")]
+            Self::Google(err) => err.kind(),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(err) => err.kind(),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(err) => err.kind(),
+            #[cfg(feature = "abook")]
+            Self::Abook(err) => err.kind(),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(err) => err.kind(),
+            Self::Backend(err) => err.kind,
+        }
+    }
+
+    /// Returns the current ETag of the card when the error is a failed
+    /// `if_match` precondition ([`ErrorKind::Conflict`]) and the
+    /// backend reported it, so the caller can refetch or retry against
+    /// that version.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(err) => err.current_etag(),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(err) => err.current_etag(),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(err) => err.current_etag(),
+            #[cfg(feature = "memory")]
+            Self::Memory(err) => err.current_etag(),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(err) => err.current_etag(),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(err) => err.current_etag(),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(err) => err.current_etag(),
+            #[cfg(feature = "google")]
+            Self::Google(err) => err.current_etag(),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(err) => err.current_etag(),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(err) => err.current_etag(),
+            #[cfg(feature = "abook")]
+            Self::Abook(err) => err.current_etag(),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(err) => err.current_etag(),
+            Self::Backend(err) => err.current_etag.as_deref(),
+        }
+    }
 }
 
 /// Std-blocking unified addressbook client.
 ///
-/// One variant per compiled-in backend; a value always holds exactly
-/// one. Each shared-API method dispatches to the active backend's
-/// matching method.
+/// One variant per compiled-in backend, plus [`Custom`](Self::Custom)
+/// for third-party ones; a value always holds exactly one. Each
+/// shared-API method dispatches to the active backend's matching
+/// method.
 #[derive(Debug)]
 pub enum AddressbookClientStd {
     #[cfg(feature = "vdir")]
     Vdir(crate::vdir::client::VdirClient),
+    #[cfg(feature = "vdir-git")]
+    VdirGit(crate::vdir::git::VdirGitClient),
     // NOTE: boxed because the WebDAV client (boxed stream, base URL, discovery
     // caches) dwarfs the vdir client (a filesystem root).
     #[cfg(feature = "webdav")]
     Webdav(Box<crate::webdav::client::WebdavClient>),
+    #[cfg(feature = "memory")]
+    Memory(crate::memory::client::InMemoryClient),
+    #[cfg(feature = "sqlite")]
+    Sqlite(crate::sqlite::client::SqliteClient),
+    #[cfg(feature = "vcf-file")]
+    VcfFile(crate::vcf_file::client::VcfFileClient),
+    #[cfg(feature = "jmap")]
+    Jmap(crate::jmap::client::JmapClient),
+    #[cfg(feature = "google")]
+    Google(crate::google::client::GoogleClient),
+    #[cfg(feature = "ldap")]
+    Ldap(crate::ldap::client::LdapClient),
+    #[cfg(feature = "msgraph")]
+    MsGraph(crate::msgraph::client::MsGraphClient),
+    #[cfg(feature = "abook")]
+    Abook(crate::abook::client::AbookClient),
+    #[cfg(feature = "mutt-alias")]
+    MuttAlias(crate::mutt_alias::client::MuttAliasClient),
+    /// Third-party backend, see [`AddressbookBackend`].
+    Custom(Box<dyn AddressbookBackend>),
 }
 
 impl AddressbookClientStd {
+    /// Reports what the active backend supports natively (ETag
+    /// preconditions, ctags, sync tokens, server-side search, batch
+    /// fetch) and its limits, so callers can pick a strategy up front.
+    ///
+    /// CardDAV derives them from the server (`OPTIONS` and a PROPFIND
+    /// on the home-set); vdir returns static facts.
+    pub fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(crate::vdir::client::VdirClient::capabilities(client)),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(crate::vdir::git::VdirGitClient::capabilities(client)),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.capabilities()?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(crate::memory::client::InMemoryClient::capabilities(client)),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(crate::sqlite::client::SqliteClient::capabilities(client)),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => {
+                Ok(crate::vcf_file::client::VcfFileClient::capabilities(client))
+            }
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(crate::jmap::client::JmapClient::capabilities(client)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(crate::google::client::GoogleClient::capabilities(client)),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(crate::ldap::client::LdapClient::capabilities(client)),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => {
+                Ok(crate::msgraph::client::MsGraphClient::capabilities(client))
+            }
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(crate::abook::client::AbookClient::capabilities(client)),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(
+                crate::mutt_alias::client::MuttAliasClient::capabilities(client),
+            ),
+            Self::Custom(backend) => backend.capabilities(),
+        }
+    }
+
     /// Lists every addressbook available to the active account.
     pub fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
         match self {
             #[cfg(feature = "vdir")]
             Self::Vdir(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.list_addressbooks()?),
             #[cfg(feature = "webdav")]
             Self::Webdav(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.list_addressbooks()?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.list_addressbooks()?),
+            Self::Custom(backend) => backend.list_addressbooks(),
         }
     }
 
@@ -76,8 +277,29 @@ impl AddressbookClientStd {
         match self {
             #[cfg(feature = "vdir")]
             Self::Vdir(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.create_addressbook(name, description, color)?),
             #[cfg(feature = "webdav")]
             Self::Webdav(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.create_addressbook(name, description, color)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.create_addressbook(name, description, color)?),
+            Self::Custom(backend) => backend.create_addressbook(name, description, color),
         }
     }
 
@@ -91,8 +313,29 @@ impl AddressbookClientStd {
         match self {
             #[cfg(feature = "vdir")]
             Self::Vdir(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.update_addressbook(id, patch)?),
             #[cfg(feature = "webdav")]
             Self::Webdav(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.update_addressbook(id, patch)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.update_addressbook(id, patch)?),
+            Self::Custom(backend) => backend.update_addressbook(id, patch),
         }
     }
 
@@ -102,8 +345,29 @@ impl AddressbookClientStd {
         match self {
             #[cfg(feature = "vdir")]
             Self::Vdir(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.delete_addressbook(id)?),
             #[cfg(feature = "webdav")]
             Self::Webdav(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.delete_addressbook(id)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.delete_addressbook(id)?),
+            Self::Custom(backend) => backend.delete_addressbook(id),
         }
     }
 
@@ -119,8 +383,143 @@ impl AddressbookClientStd {
         match self {
             #[cfg(feature = "vdir")]
             Self::Vdir(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
             #[cfg(feature = "webdav")]
             Self::Webdav(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.list_cards(addressbook_id, page, page_size)?),
+            Self::Custom(backend) => backend.list_cards(addressbook_id, page, page_size),
+        }
+    }
+
+    /// Lists the id and ETag of every card inside `addressbook_id`,
+    /// without contents. Pair with [`Self::get_cards`] to fetch only
+    /// the cards whose tag changed.
+    pub fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.list_card_refs(addressbook_id)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.list_card_refs(addressbook_id)?),
+            Self::Custom(backend) => backend.list_card_refs(addressbook_id),
+        }
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`.
+    ///
+    /// CardDAV evaluates the filter server-side (addressbook-query
+    /// REPORT); vdir evaluates it locally over every card.
+    pub fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.search_cards(addressbook_id, filter)?),
+            Self::Custom(backend) => backend.search_cards(addressbook_id, filter),
+        }
+    }
+
+    /// Fetches the cards of `addressbook_id` changed since
+    /// `sync_token`; pass `None` for the initial sync.
+    ///
+    /// Backends without sync tokens (vdir), or servers rejecting the
+    /// given token, return a full listing flagged by
+    /// [`CardChanges::full`].
+    pub fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.sync_cards(addressbook_id, sync_token)?),
+            Self::Custom(backend) => backend.sync_cards(addressbook_id, sync_token),
         }
     }
 
@@ -133,61 +532,287 @@ impl AddressbookClientStd {
         match self {
             #[cfg(feature = "vdir")]
             Self::Vdir(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.get_card(addressbook_id, card_id)?),
             #[cfg(feature = "webdav")]
             Self::Webdav(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.get_card(addressbook_id, card_id)?),
+            Self::Custom(backend) => backend.get_card(addressbook_id, card_id),
         }
     }
 
-    /// Appends a raw vCard to `addressbook_id`. Returns the identifier
-    /// the backend assigned to the stored card.
+    /// Fetches several cards from `addressbook_id` at once. Ids with
+    /// no matching card are reported in [`CardBatch::missing`] rather
+    /// than failing the whole batch.
+    pub fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.get_cards(addressbook_id, card_ids)?),
+            Self::Custom(backend) => backend.get_cards(addressbook_id, card_ids),
+        }
+    }
+
+    /// Appends a raw vCard to `addressbook_id` with the default
+    /// [`CardCreateOptions`]. Returns the identifier the backend
+    /// assigned to the stored card, along with its ETag so the next
+    /// conditional update needs no refetch.
     pub fn create_card(
         &mut self,
         addressbook_id: &str,
         contents: Vec<u8>,
-    ) -> Result<String, AddressbookClientStdError> {
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        self.create_card_with_options(addressbook_id, contents, CardCreateOptions::default())
+    }
+
+    /// Appends a raw vCard to `addressbook_id` with the given
+    /// `options`, e.g. to turn off UID-derived ids.
+    pub fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
         match self {
             #[cfg(feature = "vdir")]
-            Self::Vdir(client) => Ok(client.create_card(addressbook_id, contents)?),
+            Self::Vdir(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
             #[cfg(feature = "webdav")]
-            Self::Webdav(client) => Ok(client.create_card(addressbook_id, contents)?),
+            Self::Webdav(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "google")]
+            Self::Google(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => {
+                Ok(client.create_card_with_options(addressbook_id, contents, options)?)
+            }
+            Self::Custom(backend) => {
+                backend.create_card_with_options(addressbook_id, contents, options)
+            }
         }
     }
 
     /// Replaces the bytes of `card_id` inside `addressbook_id`.
     ///
     /// `if_match` is the backend-specific entity tag to gate the update
-    /// on; pass `None` to overwrite unconditionally. Backends without
-    /// ETag support (vdir) ignore it.
+    /// on (see [`Card::etag`]); pass `None` to overwrite
+    /// unconditionally. vdir compares it against a tag synthesized from
+    /// the current contents. Returns the new ETag of the card.
     pub fn update_card(
         &mut self,
         addressbook_id: &str,
         card_id: &str,
         contents: Vec<u8>,
         if_match: Option<&str>,
-    ) -> Result<(), AddressbookClientStdError> {
+    ) -> Result<CardRef, AddressbookClientStdError> {
         match self {
             #[cfg(feature = "vdir")]
             Self::Vdir(client) => {
                 Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
             }
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
             #[cfg(feature = "webdav")]
             Self::Webdav(client) => {
                 Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
             }
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
+            #[cfg(feature = "google")]
+            Self::Google(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => {
+                Ok(client.update_card(addressbook_id, card_id, contents, if_match)?)
+            }
+            Self::Custom(backend) => {
+                backend.update_card(addressbook_id, card_id, contents, if_match)
+            }
+        }
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating it when missing and overwriting it otherwise, with no
+    /// precondition. Unlike [`Self::create_card`], which is strictly
+    /// create-only, this never fails because the card exists.
+    pub fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.upsert_card(addressbook_id, card_id, contents)?),
+            Self::Custom(backend) => backend.upsert_card(addressbook_id, card_id, contents),
         }
     }
 
     /// Permanently deletes `card_id` from `addressbook_id`.
+    ///
+    /// `if_match` gates the removal the same way it gates
+    /// [`update_card`](Self::update_card).
     pub fn delete_card(
         &mut self,
         addressbook_id: &str,
         card_id: &str,
+        if_match: Option<&str>,
     ) -> Result<(), AddressbookClientStdError> {
         match self {
             #[cfg(feature = "vdir")]
-            Self::Vdir(client) => Ok(client.delete_card(addressbook_id, card_id)?),
+            Self::Vdir(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "vdir-git")]
+            Self::VdirGit(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
             #[cfg(feature = "webdav")]
-            Self::Webdav(client) => Ok(client.delete_card(addressbook_id, card_id)?),
+            Self::Webdav(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "memory")]
+            Self::Memory(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "vcf-file")]
+            Self::VcfFile(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "jmap")]
+            Self::Jmap(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "google")]
+            Self::Google(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "ldap")]
+            Self::Ldap(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "msgraph")]
+            Self::MsGraph(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "abook")]
+            Self::Abook(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            #[cfg(feature = "mutt-alias")]
+            Self::MuttAlias(client) => Ok(client.delete_card(addressbook_id, card_id, if_match)?),
+            Self::Custom(backend) => backend.delete_card(addressbook_id, card_id, if_match),
         }
     }
 }
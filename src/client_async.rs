@@ -0,0 +1,420 @@
+//! Tokio unified addressbook client.
+//!
+//! [`AddressbookClientAsync`] is the async counterpart of
+//! [`AddressbookClientStd`]: an enum over the per-backend tokio clients
+//! ([`VdirClientAsync`], [`WebdavClientAsync`]) exposing the same
+//! shared API with the same semantics. Errors are the std ones, so
+//! code handling [`AddressbookClientStdError`] works unchanged.
+//!
+//! [`AddressbookClientStd`]: crate::client::AddressbookClientStd
+//! [`VdirClientAsync`]: crate::vdir::client_async::VdirClientAsync
+//! [`WebdavClientAsync`]: crate::webdav::client_async::WebdavClientAsync
+
+#[cfg(feature = "webdav")]
+use alloc::boxed::Box;
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    capabilities::Capabilities,
+    card::{Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef},
+    client::AddressbookClientStdError,
+};
+
+#[cfg(feature = "vdir")]
+impl From<crate::vdir::client_async::VdirClientAsync> for AddressbookClientAsync {
+    fn from(client: crate::vdir::client_async::VdirClientAsync) -> Self {
+        Self::Vdir(client)
+    }
+}
+
+#[cfg(feature = "webdav")]
+impl From<crate::webdav::client_async::WebdavClientAsync> for AddressbookClientAsync {
+    fn from(client: crate::webdav::client_async::WebdavClientAsync) -> Self {
+        Self::Webdav(Box::new(client))
+    }
+}
+
+/// Tokio unified addressbook client.
+///
+/// One variant per compiled-in backend; a value always holds exactly
+/// one. Each shared-API method dispatches to the active backend's
+/// matching method.
+#[derive(Debug)]
+pub enum AddressbookClientAsync {
+    #[cfg(feature = "vdir")]
+    Vdir(crate::vdir::client_async::VdirClientAsync),
+    // NOTE: boxed because the WebDAV client (boxed stream, URLs, auth)
+    // dwarfs the vdir client (a filesystem root).
+    #[cfg(feature = "webdav")]
+    Webdav(Box<crate::webdav::client_async::WebdavClientAsync>),
+}
+
+impl AddressbookClientAsync {
+    /// Reports what the active backend supports natively (ETag
+    /// preconditions, ctags, sync tokens, server-side search, batch
+    /// fetch) and its limits, so callers can pick a strategy up front.
+    ///
+    /// CardDAV derives them from the server (`OPTIONS` and a PROPFIND
+    /// on the home-set); vdir returns static facts.
+    pub async fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.capabilities()),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.capabilities().await?),
+        }
+    }
+
+    /// Lists every addressbook available to the active account.
+    pub async fn list_addressbooks(
+        &mut self,
+    ) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.list_addressbooks().await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.list_addressbooks().await?),
+        }
+    }
+
+    /// Creates an addressbook named `name`, optionally carrying a
+    /// description and a color. Returns the backend-assigned id.
+    pub async fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.create_addressbook(name, description, color).await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.create_addressbook(name, description, color).await?),
+        }
+    }
+
+    /// Applies a partial update to the addressbook identified by `id`.
+    /// Fields left as `None` in `patch` are preserved.
+    pub async fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.update_addressbook(id, patch).await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.update_addressbook(id, patch).await?),
+        }
+    }
+
+    /// Deletes the addressbook identified by `id` and every card it
+    /// contains.
+    pub async fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.delete_addressbook(id).await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.delete_addressbook(id).await?),
+        }
+    }
+
+    /// Lists cards inside `addressbook_id`. `page` is 1-indexed; pass
+    /// `None` to default to page 1. `page_size = None` returns the full
+    /// window.
+    pub async fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.list_cards(addressbook_id, page, page_size).await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.list_cards(addressbook_id, page, page_size).await?),
+        }
+    }
+
+    /// Lists the id and ETag of every card inside `addressbook_id`,
+    /// without contents. Pair with [`Self::get_cards`] to fetch only
+    /// the cards whose tag changed.
+    pub async fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.list_card_refs(addressbook_id).await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.list_card_refs(addressbook_id).await?),
+        }
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`.
+    ///
+    /// CardDAV evaluates the filter server-side (addressbook-query
+    /// REPORT); vdir evaluates it locally over every card.
+    pub async fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.search_cards(addressbook_id, filter).await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.search_cards(addressbook_id, filter).await?),
+        }
+    }
+
+    /// Fetches the cards of `addressbook_id` changed since
+    /// `sync_token`; pass `None` for the initial sync.
+    ///
+    /// Backends without sync tokens (vdir), or servers rejecting the
+    /// given token, return a full listing flagged by
+    /// [`CardChanges::full`].
+    pub async fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.sync_cards(addressbook_id, sync_token).await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.sync_cards(addressbook_id, sync_token).await?),
+        }
+    }
+
+    /// Fetches the card `card_id` from `addressbook_id`.
+    pub async fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.get_card(addressbook_id, card_id).await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.get_card(addressbook_id, card_id).await?),
+        }
+    }
+
+    /// Fetches several cards from `addressbook_id` at once. Ids with
+    /// no matching card are reported in [`CardBatch::missing`] rather
+    /// than failing the whole batch.
+    pub async fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client.get_cards(addressbook_id, card_ids).await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client.get_cards(addressbook_id, card_ids).await?),
+        }
+    }
+
+    /// Appends a raw vCard to `addressbook_id` with the default
+    /// [`CardCreateOptions`]. Returns the identifier the backend
+    /// assigned to the stored card, along with its ETag so the next
+    /// conditional update needs no refetch.
+    pub async fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        let options = CardCreateOptions::default();
+        self.create_card_with_options(addressbook_id, contents, options)
+            .await
+    }
+
+    /// Appends a raw vCard to `addressbook_id` with the given
+    /// `options`, e.g. to turn off UID-derived ids.
+    pub async fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client
+                .create_card_with_options(addressbook_id, contents, options)
+                .await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client
+                .create_card_with_options(addressbook_id, contents, options)
+                .await?),
+        }
+    }
+
+    /// Replaces the bytes of `card_id` inside `addressbook_id`.
+    ///
+    /// `if_match` is the backend-specific entity tag to gate the update
+    /// on (see [`Card::etag`]); pass `None` to overwrite
+    /// unconditionally. vdir compares it against a tag synthesized from
+    /// the current contents. Returns the new ETag of the card.
+    pub async fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client
+                .update_card(addressbook_id, card_id, contents, if_match)
+                .await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client
+                .update_card(addressbook_id, card_id, contents, if_match)
+                .await?),
+        }
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating it when missing and overwriting it otherwise, with no
+    /// precondition. Unlike [`Self::create_card`], which is strictly
+    /// create-only, this never fails because the card exists.
+    pub async fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client
+                .upsert_card(addressbook_id, card_id, contents)
+                .await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client
+                .upsert_card(addressbook_id, card_id, contents)
+                .await?),
+        }
+    }
+
+    /// Permanently deletes `card_id` from `addressbook_id`.
+    ///
+    /// `if_match` gates the removal the same way it gates
+    /// [`update_card`](Self::update_card).
+    pub async fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        match self {
+            #[cfg(feature = "vdir")]
+            Self::Vdir(client) => Ok(client
+                .delete_card(addressbook_id, card_id, if_match)
+                .await?),
+            #[cfg(feature = "webdav")]
+            Self::Webdav(client) => Ok(client
+                .delete_card(addressbook_id, card_id, if_match)
+                .await?),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "vdir"))]
+mod tests {
+    use alloc::format;
+    use core::future::Future;
+
+    use io_vdir::{client::VdirClient as InnerVdirClient, path::VdirPath};
+
+    use super::*;
+    use crate::{
+        client::AddressbookClientStd,
+        error::ErrorKind,
+        vdir::{client::VdirClient, client_async::VdirClientAsync},
+    };
+
+    const ALICE: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alice\r\nEND:VCARD\r\n";
+    const ALICIA: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alicia\r\nEND:VCARD\r\n";
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    /// Builds an async and a std client over the same empty vdir.
+    fn clients(name: &str) -> (AddressbookClientAsync, AddressbookClientStd) {
+        let root = std::env::temp_dir().join(format!(
+            "io-addressbook-async-{name}-{}",
+            std::process::id()
+        ));
+
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        let root = VdirPath::new(root.to_string_lossy());
+        let client = VdirClientAsync::new(InnerVdirClient::new(root.clone()));
+        let std_client = VdirClient::new(InnerVdirClient::new(root));
+
+        (client.into(), std_client.into())
+    }
+
+    #[test]
+    fn match_std_client() {
+        let (mut client, mut std_client) = clients("match");
+
+        block_on(async {
+            let ab = client
+                .create_addressbook("Friends", None, None)
+                .await
+                .unwrap();
+            let card = client.create_card(&ab, ALICE.to_vec()).await.unwrap();
+
+            assert_eq!(
+                client.list_addressbooks().await.unwrap(),
+                std_client.list_addressbooks().unwrap()
+            );
+            assert_eq!(
+                client.get_card(&ab, &card.id).await.unwrap(),
+                std_client.get_card(&ab, &card.id).unwrap()
+            );
+            assert_eq!(
+                client.list_card_refs(&ab).await.unwrap(),
+                std_client.list_card_refs(&ab).unwrap()
+            );
+
+            let updated = std_client
+                .update_card(&ab, &card.id, ALICIA.to_vec(), card.etag.as_deref())
+                .unwrap();
+
+            let err = client
+                .update_card(&ab, &card.id, ALICE.to_vec(), card.etag.as_deref())
+                .await
+                .unwrap_err();
+            let std_err = std_client
+                .update_card(&ab, &card.id, ALICE.to_vec(), card.etag.as_deref())
+                .unwrap_err();
+
+            assert_eq!(err.kind(), ErrorKind::Conflict);
+            assert_eq!(err.kind(), std_err.kind());
+            assert_eq!(err.current_etag(), updated.etag.as_deref());
+            assert_eq!(err.current_etag(), std_err.current_etag());
+
+            client
+                .delete_card(&ab, &card.id, updated.etag.as_deref())
+                .await
+                .unwrap();
+
+            let err = client.get_card(&ab, &card.id).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::NotFound);
+            assert_eq!(
+                err.kind(),
+                std_client.get_card(&ab, &card.id).unwrap_err().kind()
+            );
+        });
+    }
+}
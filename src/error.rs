@@ -0,0 +1,73 @@
+//! Cross-backend error classification.
+//!
+//! Each backend client error maps to an [`ErrorKind`] through its
+//! `kind` method, so callers can react to a failure (e.g. prompt
+//! "contact changed on server" on [`ErrorKind::Conflict`]) without
+//! matching on backend-specific variants.
+
+/// Backend-agnostic category of a client error.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The addressbook or card does not exist (HTTP 404, 410).
+    NotFound,
+    /// The card changed since it was read, or already exists: failed
+    /// `If-Match` / `If-None-Match` precondition (HTTP 412), HTTP 409,
+    /// or a mismatching synthesized tag.
+    Conflict,
+    /// Missing or rejected credentials (HTTP 401).
+    Unauthorized,
+    /// Access denied (HTTP 403, filesystem permissions).
+    Forbidden,
+    /// No space left on the server or disk (HTTP 507).
+    InsufficientStorage,
+    /// The request itself is invalid: empty body, malformed id, data
+    /// rejected by the server (HTTP 400, 415, 422).
+    InvalidData,
+    /// The connection failed or the response could not be read.
+    Transport,
+    /// Any other failure.
+    Other,
+}
+
+impl ErrorKind {
+    /// Classifies an HTTP status code.
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            401 => Self::Unauthorized,
+            403 => Self::Forbidden,
+            404 | 410 => Self::NotFound,
+            409 | 412 => Self::Conflict,
+            507 => Self::InsufficientStorage,
+            400 | 415 | 422 => Self::InvalidData,
+            _ => Self::Other,
+        }
+    }
+
+    /// Classifies a filesystem or stream I/O error.
+    #[cfg(feature = "client")]
+    pub fn from_io(err: &std::io::Error) -> Self {
+        use std::io::ErrorKind as IoKind;
+
+        match err.kind() {
+            IoKind::NotFound => Self::NotFound,
+            IoKind::PermissionDenied | IoKind::ReadOnlyFilesystem => Self::Forbidden,
+            IoKind::AlreadyExists => Self::Conflict,
+            IoKind::StorageFull | IoKind::QuotaExceeded => Self::InsufficientStorage,
+            IoKind::InvalidData | IoKind::InvalidInput => Self::InvalidData,
+            IoKind::ConnectionRefused
+            | IoKind::ConnectionReset
+            | IoKind::ConnectionAborted
+            | IoKind::NotConnected
+            | IoKind::HostUnreachable
+            | IoKind::NetworkUnreachable
+            | IoKind::NetworkDown
+            | IoKind::BrokenPipe
+            | IoKind::TimedOut
+            | IoKind::UnexpectedEof => Self::Transport,
+            _ => Self::Other,
+        }
+    }
+}
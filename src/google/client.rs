@@ -0,0 +1,896 @@
+//! Std-blocking Google People API client.
+//!
+//! Wraps a connected stream to `people.googleapis.com` and pumps
+//! io-addressbook Google coroutines against it via
+//! [`GoogleClient::run`]. The OAuth 2.0 access token is obtained and
+//! refreshed by the caller, e.g. with an OAuth crate, then handed to
+//! [`GoogleClient::new`] or [`GoogleClient::set_token`].
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Debug;
+
+use log::debug;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    addressbook::{
+        Addressbook, AddressbookDiff,
+        google::{
+            create::{GoogleAddressbookCreate, GoogleAddressbookCreateError},
+            delete::{GoogleAddressbookDelete, GoogleAddressbookDeleteError},
+            list::{GoogleAddressbookList, GoogleAddressbookListError},
+            update::{GoogleAddressbookUpdate, GoogleAddressbookUpdateError},
+        },
+    },
+    backend::AddressbookBackend,
+    capabilities::Capabilities,
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
+        google::{
+            create::{GoogleCardCreate, GoogleCardCreateError},
+            delete::{GoogleCardDelete, GoogleCardDeleteError},
+            get::{GoogleCardGet, GoogleCardGetError},
+            list::{GoogleCardList, GoogleCardListError},
+            multiget::{GoogleCardMultiget, GoogleCardMultigetError},
+            sync::{GoogleCardSync, GoogleCardSyncError},
+            update::{GoogleCardUpdate, GoogleCardUpdateError},
+        },
+    },
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    google::{convert::capabilities, send::GoogleSendError},
+    http::{client::HttpStream, coroutine::*},
+};
+
+/// Default base URL of the People API.
+const BASE_URL: &str = "https://people.googleapis.com/v1/";
+
+/// Default `User-Agent` header of [`GoogleClient`].
+const USER_AGENT: &str = concat!("io-addressbook/", env!("CARGO_PKG_VERSION"));
+
+/// Errors surfaced by [`GoogleClient`] while running a coroutine.
+///
+/// One variant per shared-API Google coroutine, plus the I/O failures
+/// from the run loop.
+#[derive(Debug, Error)]
+pub enum GoogleClientError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    AddressbookCreate(#[from] GoogleAddressbookCreateError),
+    #[error(transparent)]
+    AddressbookDelete(#[from] GoogleAddressbookDeleteError),
+    #[error(transparent)]
+    AddressbookList(#[from] GoogleAddressbookListError),
+    #[error(transparent)]
+    AddressbookUpdate(#[from] GoogleAddressbookUpdateError),
+
+    #[error(transparent)]
+    CardCreate(#[from] GoogleCardCreateError),
+    #[error(transparent)]
+    CardDelete(#[from] GoogleCardDeleteError),
+    #[error(transparent)]
+    CardGet(#[from] GoogleCardGetError),
+    #[error(transparent)]
+    CardList(#[from] GoogleCardListError),
+    #[error(transparent)]
+    CardMultiget(#[from] GoogleCardMultigetError),
+    #[error(transparent)]
+    CardSync(#[from] GoogleCardSyncError),
+    #[error(transparent)]
+    CardUpdate(#[from] GoogleCardUpdateError),
+}
+
+impl GoogleClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        if let Some(err) = self.send_error() {
+            return err.kind();
+        }
+
+        match self {
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::CardUpdate(GoogleCardUpdateError::PreconditionFailed { .. })
+            | Self::CardDelete(GoogleCardDeleteError::PreconditionFailed { .. }) => {
+                ErrorKind::Conflict
+            }
+            Self::CardGet(GoogleCardGetError::CardNotFound(_))
+            | Self::CardUpdate(GoogleCardUpdateError::CardNotFound(_))
+            | Self::CardDelete(GoogleCardDeleteError::CardNotFound(_)) => ErrorKind::NotFound,
+            Self::AddressbookCreate(GoogleAddressbookCreateError::InvalidAddressbook(_))
+            | Self::AddressbookDelete(GoogleAddressbookDeleteError::InvalidAddressbook(_))
+            | Self::AddressbookUpdate(GoogleAddressbookUpdateError::InvalidAddressbook(_))
+            | Self::CardCreate(GoogleCardCreateError::EmptyCardBody)
+            | Self::CardCreate(GoogleCardCreateError::InvalidCard(_))
+            | Self::CardDelete(GoogleCardDeleteError::InvalidCardId(_))
+            | Self::CardGet(GoogleCardGetError::InvalidCardId(_))
+            | Self::CardMultiget(GoogleCardMultigetError::InvalidCardId(_))
+            | Self::CardSync(GoogleCardSyncError::InvalidSyncToken(_))
+            | Self::CardUpdate(GoogleCardUpdateError::InvalidCardId(_))
+            | Self::CardUpdate(GoogleCardUpdateError::EmptyCardBody)
+            | Self::CardUpdate(GoogleCardUpdateError::InvalidCard(_)) => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current tag of the card when the error is a failed
+    /// `if_match` precondition and it is known.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::CardUpdate(GoogleCardUpdateError::PreconditionFailed { etag, .. })
+            | Self::CardDelete(GoogleCardDeleteError::PreconditionFailed { etag, .. }) => {
+                etag.as_deref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the HTTP exchange error wrapped by a coroutine error.
+    pub(crate) fn send_error(&self) -> Option<&GoogleSendError> {
+        match self {
+            Self::AddressbookCreate(GoogleAddressbookCreateError::Send(err))
+            | Self::AddressbookDelete(GoogleAddressbookDeleteError::Send(err))
+            | Self::AddressbookList(GoogleAddressbookListError::Send(err))
+            | Self::AddressbookUpdate(GoogleAddressbookUpdateError::Send(err))
+            | Self::CardCreate(GoogleCardCreateError::Send(err))
+            | Self::CardDelete(GoogleCardDeleteError::Send(err))
+            | Self::CardGet(GoogleCardGetError::Send(err))
+            | Self::CardList(GoogleCardListError::Send(err))
+            | Self::CardMultiget(GoogleCardMultigetError::Send(err))
+            | Self::CardSync(GoogleCardSyncError::Send(err))
+            | Self::CardUpdate(GoogleCardUpdateError::Send(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Std-blocking Google People API client built on a connected stream.
+#[derive(Debug)]
+pub struct GoogleClient {
+    pub stream: Box<dyn HttpStream>,
+    /// Base URL of the API, `https://people.googleapis.com/v1/` by
+    /// default.
+    pub base_url: Url,
+    pub user_agent: String,
+    token: String,
+}
+
+impl GoogleClient {
+    /// Builds a client talking to the People API through `stream`,
+    /// authenticating with the OAuth 2.0 access `token`.
+    ///
+    /// The token needs the `https://www.googleapis.com/auth/contacts`
+    /// scope.
+    pub fn new(stream: impl HttpStream + 'static, token: impl ToString) -> Self {
+        Self {
+            stream: Box::new(stream),
+            base_url: Url::parse(BASE_URL).unwrap(),
+            user_agent: USER_AGENT.to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    /// Replaces the access token, e.g. after refreshing it.
+    pub fn set_token(&mut self, token: impl ToString) {
+        self.token = token.to_string();
+    }
+
+    /// Pumps any standard-shape Google coroutine (`Yield =
+    /// HttpYield`, `Return = Result<T, E>`) against the stream until
+    /// it terminates.
+    pub fn run<C, T, E>(&mut self, coroutine: C) -> Result<T, GoogleClientError>
+    where
+        C: HttpCoroutine<Yield = HttpYield, Return = Result<T, E>>,
+        GoogleClientError: From<E>,
+    {
+        crate::http::client::run(&mut *self.stream, coroutine)
+    }
+
+    /// Reports the capabilities of the People API.
+    pub fn capabilities(&self) -> Capabilities {
+        capabilities()
+    }
+
+    /// Lists the user contact groups plus `myContacts`, holding every
+    /// contact, sorted by name.
+    pub fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, GoogleClientError> {
+        let coroutine = GoogleAddressbookList::new(&self.base_url, &self.token, &self.user_agent);
+        self.run(coroutine)
+    }
+
+    /// Creates a contact group named `name`. Contact groups have
+    /// neither description nor color: both are ignored. Returns the id
+    /// assigned by the server.
+    pub fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, GoogleClientError> {
+        if description.is_some() || color.is_some() {
+            debug!("google contact groups have no description nor color, ignoring them");
+        }
+
+        let coroutine =
+            GoogleAddressbookCreate::new(&self.base_url, &self.token, &self.user_agent, name)?;
+        self.run(coroutine)
+    }
+
+    /// Renames the contact group identified by `id`, ignoring
+    /// description and color changes.
+    pub fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), GoogleClientError> {
+        let coroutine =
+            GoogleAddressbookUpdate::new(&self.base_url, &self.token, &self.user_agent, id, patch)?;
+        self.run(coroutine)
+    }
+
+    /// Deletes the contact group identified by `id`, along with its
+    /// contacts.
+    pub fn delete_addressbook(&mut self, id: &str) -> Result<(), GoogleClientError> {
+        let coroutine =
+            GoogleAddressbookDelete::new(&self.base_url, &self.token, &self.user_agent, id)?;
+        self.run(coroutine)
+    }
+
+    /// Lists cards inside `addressbook_id`, applying 1-indexed
+    /// pagination client-side.
+    pub fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, GoogleClientError> {
+        let coroutine = GoogleCardList::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            page,
+            page_size,
+        );
+        self.run(coroutine)
+    }
+
+    /// Lists the id and ETag of every card in `addressbook_id`, sorted
+    /// by id.
+    pub fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, GoogleClientError> {
+        let mut refs: Vec<CardRef> = self
+            .list_cards(addressbook_id, None, None)?
+            .into_iter()
+            .map(|card| CardRef {
+                id: card.id,
+                href: None,
+                etag: card.etag,
+            })
+            .collect();
+        refs.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(refs)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`.
+    ///
+    /// The People API search does not cover contact groups, so cards
+    /// are listed then matched locally.
+    pub fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, GoogleClientError> {
+        let cards = self.list_cards(addressbook_id, None, None)?;
+        Ok(cards
+            .into_iter()
+            .filter(|card| filter.matches(card))
+            .collect())
+    }
+
+    /// Fetches the cards of `addressbook_id` changed since
+    /// `sync_token` via the connections sync token.
+    ///
+    /// Pass `None` for the initial sync. When the token expired, falls
+    /// back to a tokenless sync returning the full listing (flagged by
+    /// [`CardChanges::full`]) and a fresh token.
+    pub fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, GoogleClientError> {
+        let coroutine = GoogleCardSync::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            sync_token,
+        );
+
+        match self.run(coroutine) {
+            Err(GoogleClientError::CardSync(GoogleCardSyncError::InvalidSyncToken(token))) => {
+                debug!("sync token {token} rejected, falling back to full sync");
+
+                let coroutine = GoogleCardSync::new(
+                    &self.base_url,
+                    &self.token,
+                    &self.user_agent,
+                    addressbook_id,
+                    None,
+                );
+                self.run(coroutine)
+            }
+            result => result,
+        }
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, GoogleClientError> {
+        let coroutine = GoogleCardGet::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            card_id,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id` via
+    /// `people:batchGet`. Ids the server does not return, or returns
+    /// outside of the addressbook, end up in [`CardBatch::missing`].
+    pub fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, GoogleClientError> {
+        let coroutine = GoogleCardMultiget::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            card_ids,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Creates a contact from the vCard `contents` inside
+    /// `addressbook_id`. Returns the id assigned by the server and the
+    /// ETag of the card.
+    pub fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, GoogleClientError> {
+        let coroutine = GoogleCardCreate::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            &contents,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Same as [`GoogleClient::create_card`]: the server assigns card
+    /// ids, so `options` do not apply.
+    pub fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        _options: CardCreateOptions,
+    ) -> Result<CardRef, GoogleClientError> {
+        self.create_card(addressbook_id, contents)
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id`, gating on
+    /// `if_match` when present. Returns the card id and new ETag.
+    pub fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, GoogleClientError> {
+        let coroutine = GoogleCardUpdate::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            card_id,
+            &contents,
+            if_match,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id` unconditionally,
+    /// or creates the card when missing. The server assigns the id of
+    /// a created card, which may thus differ from `card_id`.
+    pub fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, GoogleClientError> {
+        match self.update_card(addressbook_id, card_id, contents.clone(), None) {
+            Err(GoogleClientError::CardUpdate(GoogleCardUpdateError::CardNotFound(_))) => {
+                self.create_card(addressbook_id, contents)
+            }
+            result => result,
+        }
+    }
+
+    /// Permanently deletes `card_id` from `addressbook_id`, gating on
+    /// `if_match` when present.
+    pub fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), GoogleClientError> {
+        let coroutine = GoogleCardDelete::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?;
+        self.run(coroutine)
+    }
+}
+
+impl AddressbookBackend for GoogleClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(GoogleClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(GoogleClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(GoogleClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(GoogleClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(GoogleClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(GoogleClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(GoogleClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(GoogleClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(GoogleClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(GoogleClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(GoogleClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(GoogleClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(GoogleClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(GoogleClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(GoogleClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(GoogleClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String};
+
+    use super::*;
+    use crate::http::client::mock::MockStream;
+
+    fn person(id: &str, etag: &str, name: &str) -> String {
+        format!(
+            r#"{{
+                "resourceName": "people/{id}",
+                "etag": "{etag}",
+                "names": [{{ "displayName": "{name}", "givenName": "{name}" }}],
+                "memberships": [{{ "contactGroupMembership": {{
+                    "contactGroupResourceName": "contactGroups/myContacts"
+                }} }}]
+            }}"#
+        )
+    }
+
+    const FAILED_PRECONDITION: &str = r#"{ "error": {
+        "code": 400,
+        "status": "FAILED_PRECONDITION",
+        "message": "Request person.etag is different than the current person.etag."
+    } }"#;
+
+    const VCARD: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:c1\r\nFN:Alicia\r\nEND:VCARD\r\n";
+
+    fn client(stream: &MockStream) -> GoogleClient {
+        GoogleClient::new(stream.clone(), "token")
+    }
+
+    #[test]
+    fn list_cards_pages() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(
+                200,
+                &format!(
+                    r#"{{ "connections": [{}], "nextPageToken": "p2" }}"#,
+                    person("c1", "e1", "Alice")
+                ),
+            )
+            .respond(
+                200,
+                &format!(
+                    r#"{{ "connections": [{}, {{ "resourceName": "people/c3" }}],
+                        "nextSyncToken": "t1" }}"#,
+                    person("c2", "e2", "Bob")
+                ),
+            );
+
+        let cards = client.list_cards("myContacts", None, None).unwrap();
+
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].id, "c1");
+        assert_eq!(cards[0].etag.as_deref(), Some("e1"));
+        assert_eq!(cards[1].id, "c2");
+
+        let requests = stream.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("GET /v1/people/me/connections?"));
+        assert!(requests[0].contains("Authorization: Bearer token\r\n"));
+        assert!(!requests[0].contains("pageToken="));
+        assert!(requests[1].contains("pageToken=p2"));
+    }
+
+    #[test]
+    fn get_card() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(200, &person("c1", "e1", "Alice"));
+
+        let card = client.get_card("myContacts", "c1").unwrap();
+
+        assert_eq!(card.id, "c1");
+        assert_eq!(card.addressbook_id, "myContacts");
+        assert_eq!(card.etag.as_deref(), Some("e1"));
+        assert!(stream.requests()[0].starts_with("GET /v1/people/c1?personFields="));
+    }
+
+    #[test]
+    fn get_card_outside_group() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(200, &person("c1", "e1", "Alice"));
+
+        let err = client.get_card("friends", "c1").unwrap_err();
+
+        assert!(matches!(
+            err,
+            GoogleClientError::CardGet(GoogleCardGetError::CardNotFound(_))
+        ));
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn update_card() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(200, &person("c1", "e1", "Alice"))
+            .respond(200, &person("c1", "e2", "Alicia"));
+
+        let card = client
+            .update_card("myContacts", "c1", VCARD.to_vec(), Some("\"e1\""))
+            .unwrap();
+
+        assert_eq!(card.id, "c1");
+        assert_eq!(card.etag.as_deref(), Some("e2"));
+
+        let requests = stream.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("PATCH /v1/people/c1:updateContact?"));
+        assert!(requests[1].contains(r#""etag":"e1""#));
+    }
+
+    #[test]
+    fn update_card_failed_precondition() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(200, &person("c1", "e1", "Alice"))
+            .respond(400, FAILED_PRECONDITION);
+
+        let err = client
+            .update_card("myContacts", "c1", VCARD.to_vec(), Some("e1"))
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            GoogleClientError::CardUpdate(GoogleCardUpdateError::PreconditionFailed {
+                etag: None,
+                ..
+            })
+        ));
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+    }
+
+    #[test]
+    fn update_card_failed_precondition_unconditional() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(200, &person("c1", "e1", "Alice"))
+            .respond(400, FAILED_PRECONDITION);
+
+        let err = client
+            .update_card("myContacts", "c1", VCARD.to_vec(), None)
+            .unwrap_err();
+
+        // NOTE: a concurrent change between the get and the update
+        // still classifies as a conflict.
+        assert!(matches!(
+            err,
+            GoogleClientError::CardUpdate(GoogleCardUpdateError::Send(GoogleSendError::Status {
+                code: 400,
+                ..
+            }))
+        ));
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+    }
+
+    #[test]
+    fn update_card_stale_etag() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(200, &person("c1", "e1", "Alice"));
+
+        let err = client
+            .update_card("myContacts", "c1", VCARD.to_vec(), Some("e0"))
+            .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+        assert_eq!(err.current_etag(), Some("e1"));
+        // NOTE: the update is not sent.
+        assert_eq!(stream.requests().len(), 1);
+    }
+
+    #[test]
+    fn delete_card() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(200, &person("c1", "e1", "Alice"))
+            .respond(200, "{}");
+
+        client.delete_card("myContacts", "c1", Some("e1")).unwrap();
+
+        let requests = stream.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("DELETE /v1/people/c1:deleteContact HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn delete_card_not_found() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(
+            404,
+            r#"{ "error": { "code": 404, "status": "NOT_FOUND", "message": "" } }"#,
+        );
+
+        let err = client.delete_card("myContacts", "c1", None).unwrap_err();
+
+        assert!(matches!(
+            err,
+            GoogleClientError::CardDelete(GoogleCardDeleteError::CardNotFound(_))
+        ));
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn sync_cards_changes() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(
+            200,
+            &format!(
+                r#"{{ "connections": [{}, {{
+                    "resourceName": "people/c9",
+                    "metadata": {{ "deleted": true }}
+                }}], "nextSyncToken": "t2" }}"#,
+                person("c1", "e1", "Alice")
+            ),
+        );
+
+        let changes = client.sync_cards("myContacts", Some("t1")).unwrap();
+
+        assert!(!changes.full);
+        assert_eq!(changes.sync_token.as_deref(), Some("t2"));
+        assert_eq!(changes.cards.len(), 1);
+        assert_eq!(changes.cards[0].id, "c1");
+        assert_eq!(changes.deleted, ["c9"]);
+        assert!(stream.requests()[0].contains("syncToken=t1"));
+    }
+
+    fn sync_cards_expired(status: u16, body: &str) {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(status, body).respond(
+            200,
+            &format!(
+                r#"{{ "connections": [{}], "nextSyncToken": "t3" }}"#,
+                person("c1", "e1", "Alice")
+            ),
+        );
+
+        let changes = client.sync_cards("myContacts", Some("expired")).unwrap();
+
+        assert!(changes.full);
+        assert_eq!(changes.sync_token.as_deref(), Some("t3"));
+        assert_eq!(changes.cards.len(), 1);
+        assert!(changes.deleted.is_empty());
+
+        let requests = stream.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("syncToken=expired"));
+        assert!(!requests[1].contains("syncToken="));
+    }
+
+    #[test]
+    fn sync_cards_expired_token_failed_precondition() {
+        sync_cards_expired(400, FAILED_PRECONDITION);
+    }
+
+    #[test]
+    fn sync_cards_expired_token_gone() {
+        sync_cards_expired(
+            410,
+            r#"{ "error": { "code": 410, "status": "", "message": "Sync token expired" } }"#,
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Conversions between Google People resources and the shared types
+//! used by [`GoogleClient`], plus the `From` impl that wraps it into
+//! the unified client's Google variant.
+//!
+//! [`GoogleClient`]: crate::google::client::GoogleClient
+
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::Value;
+use url::Url;
+
+use crate::{
+    addressbook::Addressbook,
+    capabilities::{AddressbookField, Capabilities},
+    card::{Card, CardRef},
+    google::{
+        person::{Person, group_id},
+        send::GoogleSendError,
+    },
+};
+
+impl From<crate::google::client::GoogleClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::google::client::GoogleClient) -> Self {
+        Self::Google(client)
+    }
+}
+
+/// Id of the system contact group holding every contact.
+pub const MY_CONTACTS: &str = "myContacts";
+
+/// Contact group fields requested on every read.
+pub(crate) const GROUP_FIELDS: &str = "name,groupType,metadata";
+
+/// Largest page of connections or contact groups.
+pub(crate) const PAGE_SIZE: &str = "1000";
+
+/// Largest number of people fetched by one `people:batchGet`.
+pub(crate) const BATCH_SIZE: usize = 200;
+
+/// `ContactGroup` resource, reduced to the fields of the shared
+/// [`Addressbook`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct ContactGroup {
+    pub resource_name: String,
+    pub etag: Option<String>,
+    pub name: String,
+    pub formatted_name: String,
+    pub group_type: String,
+}
+
+impl ContactGroup {
+    /// Tells whether the group shows up as an addressbook: user groups
+    /// and the system group holding every contact.
+    pub fn is_addressbook(&self) -> bool {
+        self.group_type == "USER_CONTACT_GROUP" || group_id(&self.resource_name) == MY_CONTACTS
+    }
+}
+
+/// Deserializes a response body.
+pub(crate) fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, GoogleSendError> {
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Builds the URL of `path` (e.g. `people/c123:updateContact`) under
+/// the API `base_url`, with the `query` pairs.
+///
+/// Paths are appended rather than joined, since a relative reference
+/// like `people:batchGet` would parse as an absolute URL.
+pub(crate) fn endpoint(base_url: &Url, path: &str, query: &[(&str, &str)]) -> Url {
+    let mut url = base_url.clone();
+    let base = base_url.path().trim_end_matches('/');
+    url.set_path(&[base, path].join("/"));
+    url.set_query(None);
+
+    if !query.is_empty() {
+        let mut pairs = url.query_pairs_mut();
+
+        for (name, value) in query {
+            pairs.append_pair(name, value);
+        }
+    }
+
+    url
+}
+
+/// Maps a contact group to a shared [`Addressbook`].
+///
+/// The `ctag` is the group `etag`. Sync tokens cover every contact
+/// rather than one group, so addressbooks expose none.
+pub(crate) fn addressbook_from_group(group: ContactGroup) -> Addressbook {
+    let name = if group.formatted_name.is_empty() {
+        group.name
+    } else {
+        group.formatted_name
+    };
+
+    Addressbook {
+        id: group_id(&group.resource_name).to_string(),
+        name,
+        description: None,
+        color: None,
+        ctag: group.etag,
+        sync_token: None,
+    }
+}
+
+/// Maps a person to a shared [`Card`], its contents being the vCard
+/// rendering and its ETag the person `etag`.
+pub(crate) fn card_from_person(addressbook_id: &str, person: &Person) -> Card {
+    Card {
+        id: person.id().to_string(),
+        addressbook_id: addressbook_id.to_string(),
+        etag: person.etag.clone(),
+        contents: person.to_vcard(),
+    }
+}
+
+/// Maps a person to a [`CardRef`].
+pub(crate) fn card_ref_from_person(person: &Person) -> CardRef {
+    CardRef {
+        id: person.id().to_string(),
+        href: None,
+        etag: person.etag.clone(),
+    }
+}
+
+/// Capabilities of the Google People API.
+pub(crate) fn capabilities() -> Capabilities {
+    Capabilities {
+        etag: true,
+        ctag: true,
+        sync_token: true,
+        query: false,
+        multiget: true,
+        vcard_versions: vec!["4.0".to_string()],
+        max_resource_size: None,
+        writable_metadata: vec![AddressbookField::Name],
+    }
+}
+
+/// Compares an `If-Match`-style tag against a person `etag`,
+/// tolerating the surrounding quotes of the HTTP form.
+pub(crate) fn etag_matches(if_match: &str, etag: &str) -> bool {
+    if_match.trim_matches('"') == etag
+}
+
+/// Tells whether `id` is a valid person or contact group id: a
+/// non-empty path segment.
+pub(crate) fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && !id.contains(['/', '?', '#', ':'])
+}
+
+/// 1-indexed pagination on an in-memory list. `page_size = None`
+/// returns the full slice; `page_size = 0` or a page past the end
+/// returns an empty vector.
+pub(crate) fn paginate<T>(items: Vec<T>, page: Option<u32>, page_size: Option<u32>) -> Vec<T> {
+    let Some(size) = page_size else {
+        return items;
+    };
+
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let page = page.unwrap_or(1).max(1);
+    let skip = ((page - 1) as usize).saturating_mul(size as usize);
+
+    if skip >= items.len() {
+        return Vec::new();
+    }
+
+    items.into_iter().skip(skip).take(size as usize).collect()
+}
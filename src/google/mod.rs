@@ -0,0 +1,7 @@
+//! Google People API backend: the std client, its I/O-free HTTP
+//! exchange and its person conversions.
+
+pub mod client;
+pub mod convert;
+pub(crate) mod person;
+pub mod send;
@@ -0,0 +1,648 @@
+//! Google People `Person` resources and their conversion to and from
+//! the typed [`Contact`] view.
+//!
+//! Names, nicknames, emails, phones, addresses, organization, title,
+//! birthday, first note and URLs map to their People counterpart.
+//! Every other vCard property (including `UID`, `PHOTO`, `CATEGORIES`
+//! and extra notes) travels verbatim in `clientData`, so that a card
+//! written through this backend reads back the same. Group and
+//! parameters of typed vCard properties other than `TYPE` are dropped.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::{Address, Contact, Email, Organization, Phone, Property, StructuredName, escape};
+
+/// Person fields requested on every read.
+///
+/// Sync requests must keep the fields of the request that issued the
+/// token, hence a single set.
+pub(crate) const PERSON_FIELDS: &str = "addresses,biographies,birthdays,clientData,\
+     emailAddresses,memberships,metadata,names,nicknames,organizations,phoneNumbers,photos,urls";
+
+/// Person fields rewritten by a whole-card update. Memberships are
+/// left out so that the other groups of the person are kept.
+pub(crate) const UPDATE_FIELDS: &str = "addresses,biographies,birthdays,clientData,\
+     emailAddresses,names,nicknames,organizations,phoneNumbers,urls";
+
+/// `clientData` key of the verbatim vCard properties.
+const CLIENT_DATA_KEY: &str = "vcard";
+
+/// Resource name prefix of people.
+const PEOPLE_PREFIX: &str = "people/";
+
+/// Resource name prefix of contact groups.
+const GROUP_PREFIX: &str = "contactGroups/";
+
+/// Google People `Person` resource.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Person {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(skip_serializing)]
+    pub metadata: PersonMetadata,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<PersonName>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nicknames: Vec<PersonValue>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub email_addresses: Vec<PersonTyped>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub phone_numbers: Vec<PersonTyped>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<PersonAddress>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub organizations: Vec<PersonOrganization>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub birthdays: Vec<PersonBirthday>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub biographies: Vec<PersonValue>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<PersonTyped>,
+    #[serde(skip_serializing)]
+    pub photos: Vec<PersonPhoto>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub memberships: Vec<Membership>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub client_data: Vec<ClientData>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PersonMetadata {
+    /// Set on people deleted since the sync token of the request.
+    pub deleted: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PersonName {
+    #[serde(skip_serializing)]
+    pub display_name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub unstructured_name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub family_name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub given_name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub middle_name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub honorific_prefix: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub honorific_suffix: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PersonValue {
+    pub value: String,
+}
+
+/// Value with a free-form type, e.g. an email address or a phone
+/// number.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PersonTyped {
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PersonAddress {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub po_box: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub extended_address: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub street_address: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub city: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub region: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub postal_code: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub country: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PersonOrganization {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub department: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PersonBirthday {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<PersonDate>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub text: String,
+}
+
+/// `google.type.Date`, `0` standing for an unknown component.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PersonDate {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PersonPhoto {
+    pub url: String,
+    /// Set on the generated placeholder picture.
+    pub default: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Membership {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_group_membership: Option<GroupMembership>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GroupMembership {
+    pub contact_group_resource_name: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ClientData {
+    pub key: String,
+    pub value: String,
+}
+
+impl Person {
+    /// Maps a typed [`Contact`] to a person, without resource name nor
+    /// memberships.
+    pub fn from_contact(contact: &Contact) -> Self {
+        let mut person = Self::default();
+        let mut raw = Vec::new();
+
+        if let Some(uid) = &contact.uid {
+            raw.push(raw_prop("UID", uid.clone()));
+        }
+
+        if contact.formatted_name.is_some() || contact.name.is_some() {
+            let mut name = PersonName::default();
+
+            match &contact.name {
+                Some(n) => {
+                    name.family_name = n.family.clone();
+                    name.given_name = n.given.clone();
+                    name.middle_name = n.additional.clone();
+                    name.honorific_prefix = n.prefixes.clone();
+                    name.honorific_suffix = n.suffixes.clone();
+                }
+                None => name.unstructured_name = contact.formatted_name.clone().unwrap_or_default(),
+            }
+
+            // NOTE: Google derives the display name from the
+            // structured one, so a diverging formatted name is kept
+            // aside.
+            if let (Some(_), Some(fname)) = (&contact.name, &contact.formatted_name) {
+                if *fname != display_name(&name) {
+                    raw.push(raw_prop("FN", escape(fname)));
+                }
+            }
+
+            person.names.push(name);
+        }
+
+        for email in &contact.emails {
+            person.email_addresses.push(PersonTyped {
+                value: email.address.clone(),
+                kind: kind_from_types(&email.types),
+            });
+        }
+
+        for phone in &contact.phones {
+            person.phone_numbers.push(PersonTyped {
+                value: phone.number.clone(),
+                kind: phone_kind_from_types(&phone.types),
+            });
+        }
+
+        for adr in &contact.addresses {
+            person.addresses.push(PersonAddress {
+                po_box: adr.po_box.clone(),
+                extended_address: adr.extended.clone(),
+                street_address: adr.street.clone(),
+                city: adr.locality.clone(),
+                region: adr.region.clone(),
+                postal_code: adr.postal_code.clone(),
+                country: adr.country.clone(),
+                kind: kind_from_types(&adr.types),
+            });
+        }
+
+        if contact.org.is_some() || contact.title.is_some() {
+            let org = contact.org.clone().unwrap_or_default();
+            let mut units = org.units.into_iter();
+
+            person.organizations.push(PersonOrganization {
+                name: org.name,
+                department: units.next().unwrap_or_default(),
+                title: contact.title.clone().unwrap_or_default(),
+            });
+        }
+
+        if let Some(bday) = &contact.birthday {
+            let birthday = match google_date(bday) {
+                Some(date) => PersonBirthday {
+                    date: Some(date),
+                    ..Default::default()
+                },
+                None => PersonBirthday {
+                    text: bday.clone(),
+                    ..Default::default()
+                },
+            };
+            person.birthdays.push(birthday);
+        }
+
+        let mut notes = contact.notes.iter();
+
+        if let Some(note) = notes.next() {
+            person.biographies.push(PersonValue {
+                value: note.clone(),
+            });
+        }
+
+        for note in notes {
+            raw.push(raw_prop("NOTE", escape(note)));
+        }
+
+        if !contact.categories.is_empty() {
+            let categories: Vec<String> = contact.categories.iter().map(|c| escape(c)).collect();
+            raw.push(raw_prop("CATEGORIES", categories.join(",")));
+        }
+
+        if let Some(photo) = &contact.photo {
+            raw.push(raw_prop("PHOTO", photo.clone()));
+        }
+
+        if let Some(rev) = &contact.rev {
+            raw.push(raw_prop("REV", rev.clone()));
+        }
+
+        for prop in &contact.properties {
+            match prop.name.as_str() {
+                "NICKNAME" => {
+                    for nickname in prop.text().split(',').filter(|n| !n.is_empty()) {
+                        person.nicknames.push(PersonValue {
+                            value: nickname.to_string(),
+                        });
+                    }
+                }
+                "URL" => person.urls.push(PersonTyped {
+                    value: prop.text(),
+                    kind: kind_from_types(&types(prop)),
+                }),
+                _ => raw.push(prop.clone()),
+            }
+        }
+
+        person.client_data = raw
+            .iter()
+            .map(|prop| ClientData {
+                key: CLIENT_DATA_KEY.to_string(),
+                value: prop.to_line(),
+            })
+            .collect();
+
+        person
+    }
+
+    /// Maps the person to a typed [`Contact`].
+    pub fn to_contact(&self) -> Contact {
+        let mut contact = Contact::new();
+
+        if let Some(name) = self.names.first() {
+            let n = StructuredName {
+                family: name.family_name.clone(),
+                given: name.given_name.clone(),
+                additional: name.middle_name.clone(),
+                prefixes: name.honorific_prefix.clone(),
+                suffixes: name.honorific_suffix.clone(),
+            };
+
+            let fname = [&name.display_name, &name.unstructured_name]
+                .into_iter()
+                .find(|n| !n.is_empty())
+                .cloned()
+                .unwrap_or_else(|| display_name(name));
+
+            contact.formatted_name = Some(fname);
+
+            if n != StructuredName::default() {
+                contact.name = Some(n);
+            }
+        }
+
+        for email in &self.email_addresses {
+            contact.emails.push(Email {
+                address: email.value.clone(),
+                types: types_from_kind(email.kind.as_deref()),
+                ..Default::default()
+            });
+        }
+
+        for phone in &self.phone_numbers {
+            contact.phones.push(Phone {
+                number: phone.value.clone(),
+                types: phone_types_from_kind(phone.kind.as_deref()),
+                ..Default::default()
+            });
+        }
+
+        for adr in &self.addresses {
+            contact.addresses.push(Address {
+                po_box: adr.po_box.clone(),
+                extended: adr.extended_address.clone(),
+                street: adr.street_address.clone(),
+                locality: adr.city.clone(),
+                region: adr.region.clone(),
+                postal_code: adr.postal_code.clone(),
+                country: adr.country.clone(),
+                types: types_from_kind(adr.kind.as_deref()),
+                ..Default::default()
+            });
+        }
+
+        // NOTE: vCard keeps a single organization and title, the first
+        // ones win.
+        if let Some(org) = self.organizations.first() {
+            if !org.name.is_empty() || !org.department.is_empty() {
+                let units = Some(org.department.clone()).filter(|d| !d.is_empty());
+                contact.org = Some(Organization {
+                    name: org.name.clone(),
+                    units: units.into_iter().collect(),
+                });
+            }
+
+            contact.title = Some(org.title.clone()).filter(|t| !t.is_empty());
+        }
+
+        if let Some(bday) = self.birthdays.first() {
+            contact.birthday = match &bday.date {
+                Some(date) => vcard_date(date),
+                None => Some(bday.text.clone()).filter(|t| !t.is_empty()),
+            };
+        }
+
+        contact.notes = self.biographies.iter().map(|b| b.value.clone()).collect();
+
+        for nickname in &self.nicknames {
+            contact
+                .properties
+                .push(text_prop("NICKNAME", &nickname.value));
+        }
+
+        for url in &self.urls {
+            contact.properties.push(text_prop("URL", &url.value));
+        }
+
+        let raw = self
+            .client_data
+            .iter()
+            .filter(|data| data.key == CLIENT_DATA_KEY)
+            .filter_map(|data| Property::parse(&data.value).ok());
+
+        let mut kept_fname = None;
+
+        for prop in raw {
+            match prop.name.as_str() {
+                "FN" => kept_fname = Some(prop.text()),
+                _ => contact.properties.push(prop),
+            }
+        }
+
+        // NOTE: reparsing dispatches the verbatim properties with a
+        // typed field (`UID`, `PHOTO`, extra `NOTE`s…) to it.
+        let mut contact = Contact::parse(&contact.to_string()).unwrap_or(contact);
+
+        if kept_fname.is_some() {
+            contact.formatted_name = kept_fname;
+        }
+
+        if contact.photo.is_none() {
+            contact.photo = self
+                .photos
+                .iter()
+                .find(|photo| !photo.default && !photo.url.is_empty())
+                .map(|photo| photo.url.clone());
+        }
+
+        contact
+    }
+
+    /// Renders the person as vCard bytes.
+    pub fn to_vcard(&self) -> Vec<u8> {
+        self.to_contact().to_bytes()
+    }
+
+    /// Returns the id of the person: its resource name without the
+    /// `people/` prefix.
+    pub fn id(&self) -> &str {
+        let name = self.resource_name.as_deref().unwrap_or_default();
+        name.strip_prefix(PEOPLE_PREFIX).unwrap_or(name)
+    }
+
+    /// Tells whether the person belongs to the contact group
+    /// `group_id`.
+    pub fn in_group(&self, group_id: &str) -> bool {
+        let name = group_resource_name(group_id);
+
+        self.memberships
+            .iter()
+            .filter_map(|m| m.contact_group_membership.as_ref())
+            .any(|m| m.contact_group_resource_name == name)
+    }
+
+    /// Adds the person to the contact group `group_id`.
+    pub fn add_to_group(&mut self, group_id: &str) {
+        if self.in_group(group_id) {
+            return;
+        }
+
+        self.memberships.push(Membership {
+            contact_group_membership: Some(GroupMembership {
+                contact_group_resource_name: group_resource_name(group_id),
+            }),
+        });
+    }
+}
+
+/// Returns the resource name of the person `id`.
+pub(crate) fn person_resource_name(id: &str) -> String {
+    format!("{PEOPLE_PREFIX}{id}")
+}
+
+/// Returns the resource name of the contact group `id`.
+pub(crate) fn group_resource_name(id: &str) -> String {
+    format!("{GROUP_PREFIX}{id}")
+}
+
+/// Returns the id of a contact group: its resource name without the
+/// `contactGroups/` prefix.
+pub(crate) fn group_id(resource_name: &str) -> &str {
+    resource_name
+        .strip_prefix(GROUP_PREFIX)
+        .unwrap_or(resource_name)
+}
+
+/// Composes a display name from the structured name components.
+fn display_name(name: &PersonName) -> String {
+    let parts = [
+        &name.honorific_prefix,
+        &name.given_name,
+        &name.middle_name,
+        &name.family_name,
+        &name.honorific_suffix,
+    ];
+
+    parts
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the lower-cased `TYPE` values of a raw property.
+fn types(prop: &Property) -> Vec<String> {
+    prop.param_values("TYPE").map(str::to_lowercase).collect()
+}
+
+/// Maps vCard `TYPE` values to a People type.
+fn kind_from_types(types: &[String]) -> Option<String> {
+    types
+        .iter()
+        .find(|kind| ["home", "work", "other"].contains(&kind.as_str()))
+        .cloned()
+}
+
+/// Maps a People type to vCard `TYPE` values.
+fn types_from_kind(kind: Option<&str>) -> Vec<String> {
+    match kind {
+        Some(kind @ ("home" | "work")) => Vec::from([kind.to_string()]),
+        _ => Vec::new(),
+    }
+}
+
+/// Maps vCard `TEL` types to a People phone type.
+fn phone_kind_from_types(types: &[String]) -> Option<String> {
+    let has = |kind: &str| types.iter().any(|t| t == kind);
+
+    let kind = match () {
+        _ if has("fax") && has("home") => "homeFax",
+        _ if has("fax") && has("work") => "workFax",
+        _ if has("fax") => "otherFax",
+        _ if has("pager") && has("work") => "workPager",
+        _ if has("pager") => "pager",
+        _ if has("cell") && has("work") => "workMobile",
+        _ if has("cell") => "mobile",
+        _ if has("home") => "home",
+        _ if has("work") => "work",
+        _ => return None,
+    };
+
+    Some(kind.to_string())
+}
+
+/// Maps a People phone type to vCard `TEL` types.
+fn phone_types_from_kind(kind: Option<&str>) -> Vec<String> {
+    let types: &[&str] = match kind {
+        Some("home") => &["home"],
+        Some("work") => &["work"],
+        Some("mobile") => &["cell"],
+        Some("workMobile") => &["work", "cell"],
+        Some("homeFax") => &["home", "fax"],
+        Some("workFax") => &["work", "fax"],
+        Some("otherFax") => &["fax"],
+        Some("pager") => &["pager"],
+        Some("workPager") => &["work", "pager"],
+        _ => &[],
+    };
+
+    types.iter().map(|t| t.to_string()).collect()
+}
+
+/// Builds a vCard property holding `text`, escaped.
+fn text_prop(name: &str, text: &str) -> Property {
+    raw_prop(name, escape(text))
+}
+
+/// Builds a vCard property holding `value` as written.
+fn raw_prop(name: &str, value: String) -> Property {
+    Property {
+        name: name.to_string(),
+        value,
+        ..Default::default()
+    }
+}
+
+/// Parses a vCard date (`19850412`, `1985-04-12`, `--0412`,
+/// optionally followed by a time) into a `google.type.Date`. Partial
+/// dates other than a missing year have no People counterpart.
+fn google_date(value: &str) -> Option<PersonDate> {
+    let date = value.split('T').next().unwrap_or_default();
+
+    let (year, rest) = match date.strip_prefix("--") {
+        Some(rest) => (0, rest.replace('-', "")),
+        None => {
+            let digits = date.replace('-', "");
+            let year = digits.get(..4)?.parse().ok()?;
+            (year, digits.get(4..)?.to_string())
+        }
+    };
+
+    if rest.len() != 4 || !rest.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(PersonDate {
+        year,
+        month: rest[..2].parse().ok()?,
+        day: rest[2..].parse().ok()?,
+    })
+}
+
+/// Renders a `google.type.Date` as a vCard date.
+fn vcard_date(date: &PersonDate) -> Option<String> {
+    let date = match (date.year, date.month, date.day) {
+        (0, 0, _) => return None,
+        (y, 0, 0) => format!("{y:04}"),
+        (_, 0, _) => return None,
+        (0, m, 0) => format!("--{m:02}"),
+        (0, m, d) => format!("--{m:02}{d:02}"),
+        (y, m, 0) => format!("{y:04}-{m:02}"),
+        (y, m, d) => format!("{y:04}{m:02}{d:02}"),
+    };
+
+    Some(date)
+}
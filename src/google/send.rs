@@ -0,0 +1,165 @@
+//! I/O-free HTTP/1.1 exchange carrying Google People API requests.
+//!
+//! [`GoogleSend`] adds an OAuth 2.0 access token and JSON bodies to
+//! the shared [`HttpSend`] exchange. Error responses carry a
+//! `google.rpc.Status` body, surfaced as [`GoogleSendError::Status`].
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use log::trace;
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    error::ErrorKind,
+    http::{
+        coroutine::*,
+        send::{HttpSend, HttpSendError, json_body},
+    },
+};
+
+/// Errors produced by [`GoogleSend`].
+#[derive(Debug, Error)]
+pub enum GoogleSendError {
+    #[error("Google API error {code} {status}: {message}")]
+    Status {
+        /// HTTP status code.
+        code: u16,
+        /// Canonical `google.rpc.Code` name, e.g. `FAILED_PRECONDITION`.
+        status: String,
+        message: String,
+    },
+    #[error(transparent)]
+    Http(HttpSendError),
+    #[error("Invalid Google API response: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl GoogleSendError {
+    /// Classifies the error, see [`ErrorKind`].
+    ///
+    /// Google reports a stale `etag` as `FAILED_PRECONDITION` (HTTP
+    /// 400) rather than a 412, which classifies as a conflict.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Status { status, .. } if status == "FAILED_PRECONDITION" => ErrorKind::Conflict,
+            Self::Status { status, .. } if status == "ABORTED" => ErrorKind::Conflict,
+            Self::Status { code, .. } => ErrorKind::from_status(*code),
+            Self::Http(err) => err.kind(),
+            Self::Json(_) => ErrorKind::Transport,
+        }
+    }
+
+    /// Tells whether the server answered with the HTTP status `code`.
+    pub fn is_status(&self, code: u16) -> bool {
+        matches!(self, Self::Status { code: c, .. } if *c == code)
+    }
+
+    /// Tells whether the server refused the request because of a stale
+    /// `etag` or an expired sync token.
+    pub fn is_failed_precondition(&self) -> bool {
+        matches!(self, Self::Status { status, .. } if status == "FAILED_PRECONDITION")
+    }
+}
+
+impl From<HttpSendError> for GoogleSendError {
+    /// Decodes the `google.rpc.Status` body of a non-2xx response, or
+    /// keeps the raw body as message when it is not one.
+    fn from(err: HttpSendError) -> Self {
+        let HttpSendError::Status { status: code, body } = err else {
+            return Self::Http(err);
+        };
+
+        let status = match serde_json::from_slice::<ErrorWire>(&body) {
+            Ok(wire) => wire.error,
+            Err(_) => StatusWire {
+                message: String::from_utf8_lossy(&body).to_string(),
+                ..Default::default()
+            },
+        };
+
+        Self::Status {
+            code,
+            status: status.status,
+            message: status.message,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorWire {
+    error: StatusWire,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct StatusWire {
+    status: String,
+    message: String,
+}
+
+/// I/O-free coroutine sending one Google People API request.
+///
+/// On completion returns the JSON body of a 2xx response (`null` when
+/// empty).
+pub struct GoogleSend {
+    inner: HttpSend,
+}
+
+impl GoogleSend {
+    /// Builds the coroutine sending a `GET` on `url`.
+    pub fn get(url: &Url, token: &str, user_agent: &str) -> Self {
+        Self::new("GET", url, token, user_agent, None)
+    }
+
+    /// Builds the coroutine sending a `POST` of `body` on `url`.
+    pub fn post(url: &Url, token: &str, user_agent: &str, body: &Value) -> Self {
+        Self::new("POST", url, token, user_agent, Some(body))
+    }
+
+    /// Builds the coroutine sending a `PUT` of `body` on `url`.
+    pub fn put(url: &Url, token: &str, user_agent: &str, body: &Value) -> Self {
+        Self::new("PUT", url, token, user_agent, Some(body))
+    }
+
+    /// Builds the coroutine sending a `PATCH` of `body` on `url`.
+    pub fn patch(url: &Url, token: &str, user_agent: &str, body: &Value) -> Self {
+        Self::new("PATCH", url, token, user_agent, Some(body))
+    }
+
+    /// Builds the coroutine sending a `DELETE` on `url`.
+    pub fn delete(url: &Url, token: &str, user_agent: &str) -> Self {
+        Self::new("DELETE", url, token, user_agent, None)
+    }
+
+    fn new(method: &str, url: &Url, token: &str, user_agent: &str, body: Option<&Value>) -> Self {
+        trace!("prepare google {method} {url}");
+
+        let authorization = format!("Bearer {token}");
+        let body = body.map(|body| body.to_string().into_bytes());
+
+        Self {
+            inner: HttpSend::new(method, url, Some(&authorization), user_agent, body),
+        }
+    }
+}
+
+impl HttpCoroutine for GoogleSend {
+    type Yield = HttpYield;
+    type Return = Result<Value, GoogleSendError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => {
+                HttpCoroutineState::Complete(json_body(&body).map_err(Into::into))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Std-blocking run loop shared by the JSON-over-HTTP clients.
+
+use core::fmt::Debug;
+use std::io::{self, Read, Write};
+
+use crate::http::coroutine::*;
+
+const READ_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Connected stream an HTTP client talks to the server through, e.g.
+/// a TLS stream.
+pub trait HttpStream: Read + Write + Debug + Send {}
+
+impl<T: Read + Write + Debug + Send> HttpStream for T {}
+
+/// Pumps any standard-shape HTTP coroutine (`Yield = HttpYield`,
+/// `Return = Result<T, E>`) against `stream` until it terminates.
+pub(crate) fn run<C, T, E, F>(stream: &mut dyn HttpStream, mut coroutine: C) -> Result<T, F>
+where
+    C: HttpCoroutine<Yield = HttpYield, Return = Result<T, E>>,
+    F: From<E> + From<io::Error>,
+{
+    let mut buf = [0u8; READ_BUFFER_SIZE];
+    let mut arg: Option<&[u8]> = None;
+
+    loop {
+        match coroutine.resume(arg.take()) {
+            HttpCoroutineState::Complete(Ok(out)) => return Ok(out),
+            HttpCoroutineState::Complete(Err(err)) => return Err(err.into()),
+            HttpCoroutineState::Yielded(HttpYield::WantsRead) => {
+                let n = stream.read(&mut buf)?;
+                arg = Some(&buf[..n]);
+            }
+            HttpCoroutineState::Yielded(HttpYield::WantsWrite(bytes)) => {
+                stream.write_all(&bytes)?;
+            }
+        }
+    }
+}
+
+/// In-memory stand-in for a connected stream, replaying canned HTTP
+/// responses for the JMAP, Google and Microsoft Graph client tests.
+#[cfg(test)]
+pub(crate) mod mock {
+    use alloc::{
+        collections::VecDeque,
+        format,
+        string::{String, ToString},
+        sync::Arc,
+        vec::Vec,
+    };
+    use std::{
+        io::{self, Read, Write},
+        sync::Mutex,
+    };
+
+    /// Bytes served per read, small enough to split responses.
+    const CHUNK_SIZE: usize = 61;
+
+    #[derive(Debug, Default)]
+    struct Exchange {
+        responses: VecDeque<Vec<u8>>,
+        pending: Vec<u8>,
+        requests: Vec<String>,
+    }
+
+    /// Stream answering every request written with the next queued
+    /// response. Clones share the same exchange, so that a test keeps
+    /// a handle on the requests sent by a client owning the stream.
+    #[derive(Clone, Debug, Default)]
+    pub(crate) struct MockStream(Arc<Mutex<Exchange>>);
+
+    impl MockStream {
+        /// Queues a response with `status` and JSON `body`.
+        pub(crate) fn respond(&self, status: u16, body: &str) -> &Self {
+            let response = format!(
+                "HTTP/1.1 {status} Mock\r\n\
+                 Content-Type: application/json\r\n\
+                 Content-Length: {}\r\n\r\n{body}",
+                body.len(),
+            );
+
+            let mut exchange = self.0.lock().unwrap();
+            exchange.responses.push_back(response.into_bytes());
+            self
+        }
+
+        /// Returns the requests written so far.
+        pub(crate) fn requests(&self) -> Vec<String> {
+            self.0.lock().unwrap().requests.clone()
+        }
+    }
+
+    impl Read for MockStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut exchange = self.0.lock().unwrap();
+            let n = buf.len().min(CHUNK_SIZE).min(exchange.pending.len());
+            buf[..n].copy_from_slice(&exchange.pending[..n]);
+            exchange.pending.drain(..n);
+            Ok(n)
+        }
+    }
+
+    impl Write for MockStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut exchange = self.0.lock().unwrap();
+            let request = String::from_utf8_lossy(buf).to_string();
+            exchange.requests.push(request);
+            let response = exchange.responses.pop_front().unwrap_or_default();
+            exchange.pending.extend(response);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Coroutine protocol shared by the JSON-over-HTTP operations.
+//!
+//! Mirrors the WebDAV one: a coroutine yields [`HttpYield::WantsWrite`]
+//! with bytes to send and [`HttpYield::WantsRead`] when it needs more
+//! bytes from the stream, which the caller feeds back through
+//! [`HttpCoroutine::resume`]. An empty read signals the end of the
+//! stream.
+
+use alloc::vec::Vec;
+
+/// State returned by [`HttpCoroutine::resume`].
+#[derive(Debug)]
+pub enum HttpCoroutineState<Y, R> {
+    /// The coroutine needs I/O before it can go on.
+    Yielded(Y),
+    /// The coroutine terminated with its output.
+    Complete(R),
+}
+
+/// I/O request yielded by an HTTP coroutine.
+#[derive(Debug)]
+pub enum HttpYield {
+    /// Read bytes from the stream, then resume with them.
+    WantsRead,
+    /// Write these bytes to the stream, then resume with `None`.
+    WantsWrite(Vec<u8>),
+}
+
+/// I/O-free coroutine talking HTTP over a stream.
+pub trait HttpCoroutine {
+    type Yield;
+    type Return;
+
+    /// Resumes the coroutine with the bytes read since the last
+    /// [`HttpYield::WantsRead`], or `None` otherwise.
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return>;
+}
@@ -0,0 +1,312 @@
+//! Minimal HTTP/1.1 plumbing shared by the JSON-over-HTTP backends
+//! (JMAP, Google People, Microsoft Graph).
+//!
+//! The [`coroutine`] protocol and the [`send::HttpSend`] exchange are
+//! I/O-free: requests are built and responses parsed from raw bytes.
+//! [`client::run`] pumps them against a std stream. Each backend only
+//! decodes its own error bodies on top.
+
+pub mod client;
+pub mod coroutine;
+pub mod send;
+
+use alloc::{format, string::ToString, vec::Vec};
+use core::mem;
+
+use url::Url;
+
+/// Renders a request with an optional JSON `body`.
+pub(crate) fn request(
+    method: &str,
+    url: &Url,
+    authorization: Option<&str>,
+    user_agent: &str,
+    body: Option<Vec<u8>>,
+) -> Vec<u8> {
+    let mut target = url.path().to_string();
+
+    if let Some(query) = url.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+
+    let mut host = url.host_str().unwrap_or_default().to_string();
+
+    if let Some(port) = url.port() {
+        host.push_str(&format!(":{port}"));
+    }
+
+    let mut head = format!(
+        "{method} {target} HTTP/1.1\r\n\
+         Host: {host}\r\n\
+         User-Agent: {user_agent}\r\n\
+         Accept: application/json\r\n"
+    );
+
+    if let Some(value) = authorization {
+        head.push_str(&format!("Authorization: {value}\r\n"));
+    }
+
+    match &body {
+        Some(body) => {
+            head.push_str("Content-Type: application/json\r\n");
+            head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        // NOTE: some servers require an explicit length on bodiless
+        // writes.
+        None if method != "GET" => head.push_str("Content-Length: 0\r\n"),
+        None => (),
+    }
+
+    head.push_str("\r\n");
+
+    let mut request = head.into_bytes();
+    request.extend(body.unwrap_or_default());
+    request
+}
+
+/// Incremental HTTP/1.1 response parser.
+///
+/// Bytes are fed as they are read from the stream. The head is parsed
+/// once, then the body is tracked by offset (and chunk by chunk when
+/// chunked), so that every byte is scanned a bounded number of times
+/// however the response is split across reads. Interim (1xx)
+/// responses are skipped.
+#[derive(Debug, Default)]
+pub(crate) struct ResponseParser {
+    buf: Vec<u8>,
+    /// Start of the bytes not consumed yet.
+    pos: usize,
+    /// Where to resume searching for the next delimiter.
+    scan: usize,
+    state: ResponseState,
+    status: u16,
+    /// Chunks decoded so far.
+    chunks: Vec<u8>,
+}
+
+/// Part of the response a [`ResponseParser`] waits for.
+#[derive(Clone, Copy, Debug, Default)]
+enum ResponseState {
+    #[default]
+    Head,
+    /// Body delimited by its length, or by the end of the stream.
+    Body(Option<usize>),
+    ChunkSize,
+    ChunkData(usize),
+    Trailer,
+}
+
+impl ResponseParser {
+    /// Appends the `bytes` read from the stream, `eof` telling that
+    /// the stream ended, then parses as far as possible.
+    ///
+    /// Returns the status and body of the response once complete,
+    /// `None` while more bytes are needed.
+    pub(crate) fn feed(
+        &mut self,
+        bytes: &[u8],
+        eof: bool,
+    ) -> Result<Option<(u16, Vec<u8>)>, &'static str> {
+        self.buf.extend_from_slice(bytes);
+
+        loop {
+            match self.state {
+                ResponseState::Head => {
+                    let Some(end) = self.find(b"\r\n\r\n") else {
+                        return Ok(None);
+                    };
+
+                    let head = &self.buf[self.pos..end];
+                    let (status, state) = parse_head(head)?;
+
+                    self.status = status;
+                    self.state = state;
+                    self.consume(end + 4);
+                }
+                ResponseState::Body(length) => {
+                    let body = &self.buf[self.pos..];
+
+                    return Ok(match length {
+                        Some(length) if body.len() >= length => {
+                            Some((self.status, body[..length].to_vec()))
+                        }
+                        Some(_) => None,
+                        None if eof => Some((self.status, body.to_vec())),
+                        None => None,
+                    });
+                }
+                ResponseState::ChunkSize => {
+                    let Some(end) = self.find(b"\r\n") else {
+                        return Ok(None);
+                    };
+
+                    let line = core::str::from_utf8(&self.buf[self.pos..end])
+                        .map_err(|_| "non UTF-8 chunk size")?;
+                    let size = line.split(';').next().unwrap_or_default().trim();
+                    let size = usize::from_str_radix(size, 16).map_err(|_| "invalid chunk size")?;
+
+                    self.state = match size {
+                        // NOTE: the body ends with an empty line, after
+                        // optional trailers that are not needed here.
+                        0 => ResponseState::Trailer,
+                        size => ResponseState::ChunkData(size),
+                    };
+                    self.consume(end + 2);
+                }
+                ResponseState::ChunkData(size) => {
+                    if self.buf.len() - self.pos < size + 2 {
+                        return Ok(None);
+                    }
+
+                    let chunk = &self.buf[self.pos..self.pos + size];
+                    self.chunks.extend_from_slice(chunk);
+                    self.state = ResponseState::ChunkSize;
+                    self.consume(self.pos + size + 2);
+                }
+                ResponseState::Trailer => {
+                    let Some(end) = self.find(b"\r\n") else {
+                        return Ok(None);
+                    };
+
+                    if end == self.pos {
+                        return Ok(Some((self.status, mem::take(&mut self.chunks))));
+                    }
+
+                    self.consume(end + 2);
+                }
+            }
+        }
+    }
+
+    /// Returns the position of the next `delimiter` among the bytes
+    /// not consumed yet, remembering how far the search went.
+    fn find(&mut self, delimiter: &[u8]) -> Option<usize> {
+        let found = self.buf[self.scan..]
+            .windows(delimiter.len())
+            .position(|w| w == delimiter);
+
+        if found.is_none() {
+            // NOTE: the delimiter may straddle the next read.
+            let rewind = delimiter.len() - 1;
+            self.scan = self.buf.len().saturating_sub(rewind).max(self.pos);
+        }
+
+        found.map(|i| self.scan + i)
+    }
+
+    /// Marks the bytes up to `pos` as consumed.
+    fn consume(&mut self, pos: usize) {
+        self.pos = pos;
+        self.scan = pos;
+    }
+}
+
+/// Parses the status of a response head, along with the state to
+/// parse what follows with.
+fn parse_head(head: &[u8]) -> Result<(u16, ResponseState), &'static str> {
+    let head = core::str::from_utf8(head).map_err(|_| "non UTF-8 head")?;
+    let mut lines = head.split("\r\n");
+
+    let status = lines
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or("invalid status line")?;
+
+    if (100..200).contains(&status) {
+        return Ok((status, ResponseState::Head));
+    }
+
+    let mut content_length = None;
+    let mut chunked = false;
+
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        if name.eq_ignore_ascii_case("content-length") {
+            let length = value.trim().parse::<usize>();
+            content_length = Some(length.map_err(|_| "content length")?);
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.to_ascii_lowercase().contains("chunked");
+        }
+    }
+
+    let state = if chunked {
+        ResponseState::ChunkSize
+    } else if status == 204 || status == 304 {
+        ResponseState::Body(Some(0))
+    } else {
+        ResponseState::Body(content_length)
+    };
+
+    Ok((status, state))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec::Vec};
+
+    use super::ResponseParser;
+
+    /// Feeds `response` to a fresh parser `size` bytes at a time, then
+    /// signals the end of the stream.
+    fn parse(response: &[u8], size: usize) -> Option<(u16, Vec<u8>)> {
+        let mut parser = ResponseParser::default();
+
+        for bytes in response.chunks(size) {
+            if let Some(parsed) = parser.feed(bytes, false).unwrap() {
+                return Some(parsed);
+            }
+        }
+
+        parser.feed(&[], true).unwrap()
+    }
+
+    #[test]
+    fn parse_content_length() {
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello, leftover";
+
+        for size in [1, 2, 3, response.len()] {
+            assert_eq!(parse(response, size), Some((200, b"hello".to_vec())));
+        }
+    }
+
+    #[test]
+    fn parse_chunked_after_interim_response() {
+        let response = b"HTTP/1.1 100 Continue\r\n\r\n\
+                         HTTP/1.1 201 Created\r\nTransfer-Encoding: chunked\r\n\r\n\
+                         5;ext=1\r\nhello\r\n7\r\n, world\r\n0\r\nX-Trailer: 1\r\n\r\n";
+
+        for size in [1, 2, 3, 4, response.len()] {
+            assert_eq!(parse(response, size), Some((201, b"hello, world".to_vec())));
+        }
+    }
+
+    #[test]
+    fn parse_until_end_of_stream() {
+        let response = b"HTTP/1.1 200 OK\r\n\r\nno length";
+        assert_eq!(parse(response, 1), Some((200, b"no length".to_vec())));
+
+        let truncated = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort";
+        assert_eq!(parse(truncated, 1), None);
+    }
+
+    #[test]
+    fn parse_many_chunks() {
+        let mut response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+        let mut body = Vec::new();
+
+        for i in 0..1000 {
+            let chunk = format!("chunk {i};");
+            response.extend(format!("{:x}\r\n{chunk}\r\n", chunk.len()).into_bytes());
+            body.extend(chunk.into_bytes());
+        }
+
+        response.extend(b"0\r\n\r\n");
+
+        assert_eq!(parse(&response, 7), Some((200, body)));
+    }
+}
@@ -0,0 +1,105 @@
+//! I/O-free HTTP/1.1 exchange.
+//!
+//! [`HttpSend`] writes one request, then reads the response until its
+//! end (`Content-Length`, chunked encoding or end of stream), leaving
+//! the stream ready for the next request. Non-2xx responses surface as
+//! [`HttpSendError::Status`] along with their body, for the backends
+//! to decode.
+
+use alloc::vec::Vec;
+
+use log::trace;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    error::ErrorKind,
+    http::{self, ResponseParser, coroutine::*},
+};
+
+/// Errors produced by [`HttpSend`].
+#[derive(Debug, Error)]
+pub enum HttpSendError {
+    #[error("Unexpected HTTP status {status}")]
+    Status { status: u16, body: Vec<u8> },
+    #[error("Invalid HTTP response: {0}")]
+    InvalidResponse(&'static str),
+    #[error("Connection closed before the end of the HTTP response")]
+    UnexpectedEof,
+}
+
+impl HttpSendError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Status { status, .. } => ErrorKind::from_status(*status),
+            Self::InvalidResponse(_) | Self::UnexpectedEof => ErrorKind::Transport,
+        }
+    }
+}
+
+/// I/O-free coroutine sending one HTTP/1.1 request.
+///
+/// On completion returns the body of a 2xx response.
+pub struct HttpSend {
+    request: Option<Vec<u8>>,
+    response: ResponseParser,
+}
+
+impl HttpSend {
+    /// Builds the coroutine sending a `method` request on `url`, with
+    /// an optional `Authorization` header value and JSON `body`.
+    pub fn new(
+        method: &str,
+        url: &Url,
+        authorization: Option<&str>,
+        user_agent: &str,
+        body: Option<Vec<u8>>,
+    ) -> Self {
+        trace!("prepare http {method} {url}");
+
+        Self {
+            request: Some(http::request(method, url, authorization, user_agent, body)),
+            response: ResponseParser::default(),
+        }
+    }
+}
+
+impl HttpCoroutine for HttpSend {
+    type Yield = HttpYield;
+    type Return = Result<Vec<u8>, HttpSendError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        if let Some(request) = self.request.take() {
+            return HttpCoroutineState::Yielded(HttpYield::WantsWrite(request));
+        }
+
+        let Some(bytes) = arg else {
+            return HttpCoroutineState::Yielded(HttpYield::WantsRead);
+        };
+
+        let eof = bytes.is_empty();
+
+        match self.response.feed(bytes, eof) {
+            Ok(Some((status, body))) if (200..300).contains(&status) => {
+                HttpCoroutineState::Complete(Ok(body))
+            }
+            Ok(Some((status, body))) => {
+                HttpCoroutineState::Complete(Err(HttpSendError::Status { status, body }))
+            }
+            Ok(None) if eof => HttpCoroutineState::Complete(Err(HttpSendError::UnexpectedEof)),
+            Ok(None) => HttpCoroutineState::Yielded(HttpYield::WantsRead),
+            Err(err) => HttpCoroutineState::Complete(Err(HttpSendError::InvalidResponse(err))),
+        }
+    }
+}
+
+/// Parses a 2xx JSON body, an empty one reading as `null`.
+#[cfg(any(feature = "google", feature = "msgraph"))]
+pub(crate) fn json_body(body: &[u8]) -> Result<serde_json::Value, serde_json::Error> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        Ok(serde_json::Value::Null)
+    } else {
+        serde_json::from_slice(body)
+    }
+}
@@ -0,0 +1,199 @@
+//! JMAP API request (RFC 8620 §3).
+//!
+//! A request batches method calls, each identified by a call id; the
+//! response holds one invocation per call, either the method result
+//! or an `error` invocation. Later calls may reference the results of
+//! earlier ones (`#ids` back-references), which lets most operations
+//! complete in a single round trip.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::trace;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::{
+    error::ErrorKind,
+    http::{coroutine::*, send::HttpSendError},
+    jmap::{
+        send::{JmapAuth, JmapSend},
+        session::{CONTACTS_CAPABILITY, CORE_CAPABILITY, JmapSession},
+    },
+};
+
+/// Method call or response of a JMAP request: `(name, arguments,
+/// call id)`.
+pub type JmapInvocation = (String, Value, String);
+
+/// Builds the method call `name` with `arguments`, identified by
+/// `call_id`.
+pub fn invocation(name: &str, arguments: Value, call_id: &str) -> JmapInvocation {
+    (name.to_string(), arguments, call_id.to_string())
+}
+
+/// Error reported by the server for a method call, or for one object
+/// of a `/set` call (`SetError`).
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+#[error("JMAP error `{kind}`: {}", description.as_deref().unwrap_or("no description"))]
+pub struct JmapMethodError {
+    /// Error type, e.g. `notFound` or `stateMismatch`.
+    pub kind: String,
+    /// Human-readable description, when the server gave one.
+    pub description: Option<String>,
+}
+
+impl JmapMethodError {
+    /// Reads the `type` and `description` of an error object.
+    pub(crate) fn from_value(value: &Value) -> Self {
+        Self {
+            kind: value
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or("serverFail")
+                .to_string(),
+            description: value
+                .get("description")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        }
+    }
+
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn error_kind(&self) -> ErrorKind {
+        match self.kind.as_str() {
+            "notFound" | "accountNotFound" => ErrorKind::NotFound,
+            "stateMismatch" | "alreadyExists" | "addressBookHasContents" => ErrorKind::Conflict,
+            "forbidden" | "accountReadOnly" => ErrorKind::Forbidden,
+            "overQuota" => ErrorKind::InsufficientStorage,
+            "invalidArguments"
+            | "invalidProperties"
+            | "invalidPatch"
+            | "invalidResultReference"
+            | "cannotCalculateChanges"
+            | "tooLarge" => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// Errors produced by [`JmapApi`].
+#[derive(Debug, Error)]
+pub enum JmapApiError {
+    #[error(transparent)]
+    Send(#[from] HttpSendError),
+    #[error("Invalid JMAP response: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Method(#[from] JmapMethodError),
+    #[error("Missing JMAP response to call `{0}`")]
+    MissingResponse(String),
+}
+
+impl JmapApiError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Send(HttpSendError::Status { status, .. }) => ErrorKind::from_status(*status),
+            Self::Send(_) | Self::Json(_) | Self::MissingResponse(_) => ErrorKind::Transport,
+            Self::Method(err) => err.error_kind(),
+        }
+    }
+
+    /// Returns the method-level error type, e.g. `stateMismatch`.
+    pub fn method_kind(&self) -> Option<&str> {
+        match self {
+            Self::Method(err) => Some(&err.kind),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponseWire {
+    method_responses: Vec<JmapInvocation>,
+}
+
+/// Method responses of a JMAP request.
+#[derive(Debug)]
+pub struct JmapResponses {
+    invocations: Vec<JmapInvocation>,
+}
+
+impl JmapResponses {
+    /// Takes the arguments of the response to call `call_id`,
+    /// turning an `error` invocation into
+    /// [`JmapApiError::Method`].
+    pub fn take(&mut self, call_id: &str) -> Result<Value, JmapApiError> {
+        let position = self
+            .invocations
+            .iter()
+            .position(|(_, _, id)| id == call_id)
+            .ok_or_else(|| JmapApiError::MissingResponse(call_id.to_string()))?;
+
+        let (name, arguments, _) = self.invocations.remove(position);
+
+        if name == "error" {
+            return Err(JmapMethodError::from_value(&arguments).into());
+        }
+
+        Ok(arguments)
+    }
+}
+
+/// I/O-free coroutine sending a batch of method calls to the API
+/// endpoint of a JMAP session.
+pub struct JmapApi {
+    inner: JmapSend,
+}
+
+impl JmapApi {
+    /// Builds the coroutine sending `calls`, using the core and
+    /// contacts capabilities.
+    pub fn new(
+        session: &JmapSession,
+        auth: &JmapAuth,
+        user_agent: &str,
+        calls: Vec<JmapInvocation>,
+    ) -> Self {
+        trace!("prepare jmap api request");
+
+        let body = json!({
+            "using": [CORE_CAPABILITY, CONTACTS_CAPABILITY],
+            "methodCalls": calls,
+        });
+
+        Self {
+            inner: JmapSend::post(
+                &session.api_url,
+                auth,
+                user_agent,
+                body.to_string().into_bytes(),
+            ),
+        }
+    }
+}
+
+impl HttpCoroutine for JmapApi {
+    type Yield = HttpYield;
+    type Return = Result<JmapResponses, JmapApiError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => {
+                let result = serde_json::from_slice::<ResponseWire>(&body)
+                    .map(|wire| JmapResponses {
+                        invocations: wire.method_responses,
+                    })
+                    .map_err(Into::into);
+                HttpCoroutineState::Complete(result)
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,860 @@
+//! Std-blocking JMAP for Contacts client.
+//!
+//! Wraps a connected stream to the JMAP server and pumps io-addressbook
+//! JMAP coroutines against it via [`JmapClient::run`]. Each shared-API
+//! method first resolves the cached JMAP session (fetching it on the
+//! first call), then builds and runs the matching coroutine. The
+//! stream must stay connected across requests: the API endpoint is
+//! expected on the same host as the session resource.
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Debug;
+
+use log::debug;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    addressbook::{
+        Addressbook, AddressbookDiff,
+        jmap::{
+            create::{JmapAddressbookCreate, JmapAddressbookCreateError},
+            delete::{JmapAddressbookDelete, JmapAddressbookDeleteError},
+            list::{JmapAddressbookList, JmapAddressbookListError},
+            update::{JmapAddressbookUpdate, JmapAddressbookUpdateError},
+        },
+    },
+    backend::AddressbookBackend,
+    capabilities::Capabilities,
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
+        jmap::{
+            create::{JmapCardCreate, JmapCardCreateError},
+            delete::{JmapCardDelete, JmapCardDeleteError},
+            get::{JmapCardGet, JmapCardGetError},
+            list::{JmapCardList, JmapCardListError},
+            multiget::{JmapCardMultiget, JmapCardMultigetError},
+            query::{JmapCardQuery, JmapCardQueryError},
+            sync::{JmapCardSync, JmapCardSyncError},
+            update::{JmapCardUpdate, JmapCardUpdateError},
+        },
+    },
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    http::{client::HttpStream, coroutine::*, send::HttpSendError},
+    jmap::{
+        api::JmapApiError,
+        convert::capabilities,
+        send::JmapAuth,
+        session::{JmapSession, JmapSessionGet, JmapSessionGetError},
+    },
+};
+
+/// Default `User-Agent` header of [`JmapClient`].
+const USER_AGENT: &str = concat!("io-addressbook/", env!("CARGO_PKG_VERSION"));
+
+/// Errors surfaced by [`JmapClient`] while running a coroutine.
+///
+/// One variant per shared-API JMAP coroutine, plus the I/O failures
+/// from the run loop and the session discovery failures.
+#[derive(Debug, Error)]
+pub enum JmapClientError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Session(#[from] JmapSessionGetError),
+
+    #[error(transparent)]
+    AddressbookCreate(#[from] JmapAddressbookCreateError),
+    #[error(transparent)]
+    AddressbookDelete(#[from] JmapAddressbookDeleteError),
+    #[error(transparent)]
+    AddressbookList(#[from] JmapAddressbookListError),
+    #[error(transparent)]
+    AddressbookUpdate(#[from] JmapAddressbookUpdateError),
+
+    #[error(transparent)]
+    CardCreate(#[from] JmapCardCreateError),
+    #[error(transparent)]
+    CardDelete(#[from] JmapCardDeleteError),
+    #[error(transparent)]
+    CardGet(#[from] JmapCardGetError),
+    #[error(transparent)]
+    CardList(#[from] JmapCardListError),
+    #[error(transparent)]
+    CardMultiget(#[from] JmapCardMultigetError),
+    #[error(transparent)]
+    CardQuery(#[from] JmapCardQueryError),
+    #[error(transparent)]
+    CardSync(#[from] JmapCardSyncError),
+    #[error(transparent)]
+    CardUpdate(#[from] JmapCardUpdateError),
+}
+
+impl JmapClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        if let Some(err) = self.api_error() {
+            return err.kind();
+        }
+
+        match self {
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::Session(JmapSessionGetError::Send(HttpSendError::Status { status, .. })) => {
+                ErrorKind::from_status(*status)
+            }
+            Self::Session(_) => ErrorKind::Transport,
+            Self::CardUpdate(JmapCardUpdateError::PreconditionFailed { .. })
+            | Self::CardDelete(JmapCardDeleteError::PreconditionFailed { .. }) => {
+                ErrorKind::Conflict
+            }
+            Self::CardGet(JmapCardGetError::CardNotFound(_))
+            | Self::CardUpdate(JmapCardUpdateError::CardNotFound(_))
+            | Self::CardDelete(JmapCardDeleteError::CardNotFound(_)) => ErrorKind::NotFound,
+            Self::AddressbookCreate(JmapAddressbookCreateError::InvalidAddressbook(_))
+            | Self::AddressbookDelete(JmapAddressbookDeleteError::InvalidAddressbook(_))
+            | Self::AddressbookUpdate(JmapAddressbookUpdateError::InvalidAddressbook(_))
+            | Self::CardCreate(JmapCardCreateError::EmptyCardBody)
+            | Self::CardCreate(JmapCardCreateError::InvalidCard(_))
+            | Self::CardDelete(JmapCardDeleteError::InvalidCardId(_))
+            | Self::CardGet(JmapCardGetError::InvalidCardId(_))
+            | Self::CardMultiget(JmapCardMultigetError::InvalidCardId(_))
+            | Self::CardSync(JmapCardSyncError::InvalidSyncToken(_))
+            | Self::CardUpdate(JmapCardUpdateError::InvalidCardId(_))
+            | Self::CardUpdate(JmapCardUpdateError::EmptyCardBody)
+            | Self::CardUpdate(JmapCardUpdateError::InvalidCard(_)) => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current tag of the card when the error is a failed
+    /// `if_match` precondition and it is known.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::CardUpdate(JmapCardUpdateError::PreconditionFailed { etag, .. })
+            | Self::CardDelete(JmapCardDeleteError::PreconditionFailed { etag, .. }) => {
+                etag.as_deref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the JMAP-level error wrapped by a coroutine error.
+    pub(crate) fn api_error(&self) -> Option<&JmapApiError> {
+        match self {
+            Self::AddressbookCreate(JmapAddressbookCreateError::Api(err))
+            | Self::AddressbookDelete(JmapAddressbookDeleteError::Api(err))
+            | Self::AddressbookList(JmapAddressbookListError::Api(err))
+            | Self::AddressbookUpdate(JmapAddressbookUpdateError::Api(err))
+            | Self::CardCreate(JmapCardCreateError::Api(err))
+            | Self::CardDelete(JmapCardDeleteError::Api(err))
+            | Self::CardGet(JmapCardGetError::Api(err))
+            | Self::CardList(JmapCardListError::Api(err))
+            | Self::CardMultiget(JmapCardMultigetError::Api(err))
+            | Self::CardQuery(JmapCardQueryError::Api(err))
+            | Self::CardSync(JmapCardSyncError::Api(err))
+            | Self::CardUpdate(JmapCardUpdateError::Api(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Std-blocking JMAP for Contacts client built on a connected stream.
+#[derive(Debug)]
+pub struct JmapClient {
+    pub stream: Box<dyn HttpStream>,
+    /// URL of the JMAP session resource, usually
+    /// `https://<host>/.well-known/jmap`.
+    pub session_url: Url,
+    pub user_agent: String,
+    auth: JmapAuth,
+    session: Option<Box<JmapSession>>,
+}
+
+impl JmapClient {
+    /// Builds a client talking to the server of `session_url` through
+    /// `stream`, authenticating with `auth`.
+    pub fn new(stream: impl HttpStream + 'static, session_url: Url, auth: JmapAuth) -> Self {
+        Self {
+            stream: Box::new(stream),
+            session_url,
+            user_agent: USER_AGENT.to_string(),
+            auth,
+            session: None,
+        }
+    }
+
+    /// Returns the credentials sent along each request.
+    pub fn auth(&self) -> &JmapAuth {
+        &self.auth
+    }
+
+    /// Returns the JMAP session, fetching it on the first call.
+    pub fn session(&mut self) -> Result<&JmapSession, JmapClientError> {
+        if self.session.is_none() {
+            let coroutine = JmapSessionGet::new(&self.session_url, &self.auth, &self.user_agent);
+            let session = self.run(coroutine)?;
+            self.session = Some(Box::new(session));
+        }
+
+        Ok(self.session.as_ref().unwrap())
+    }
+
+    /// Pumps any standard-shape JMAP coroutine (`Yield = HttpYield`,
+    /// `Return = Result<T, E>`) against the stream until it
+    /// terminates.
+    pub fn run<C, T, E>(&mut self, coroutine: C) -> Result<T, JmapClientError>
+    where
+        C: HttpCoroutine<Yield = HttpYield, Return = Result<T, E>>,
+        JmapClientError: From<E>,
+    {
+        crate::http::client::run(&mut *self.stream, coroutine)
+    }
+
+    /// Reports the capabilities of the contacts account, from the
+    /// session.
+    pub fn capabilities(&mut self) -> Result<Capabilities, JmapClientError> {
+        Ok(capabilities(self.session()?))
+    }
+
+    /// Lists every addressbook of the account, sorted by name.
+    pub fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapAddressbookList::new(&session, &self.auth, &self.user_agent);
+        self.run(coroutine)
+    }
+
+    /// Creates an addressbook named `name`. JMAP addressbooks have no
+    /// color: `color` is ignored. Returns the id assigned by the
+    /// server.
+    pub fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, JmapClientError> {
+        if color.is_some() {
+            debug!("jmap addressbooks have no color, ignoring it");
+        }
+
+        let session = self.session()?.clone();
+        let coroutine =
+            JmapAddressbookCreate::new(&session, &self.auth, &self.user_agent, name, description)?;
+        self.run(coroutine)
+    }
+
+    /// Applies `patch` to the addressbook identified by `id`, ignoring
+    /// a color change.
+    pub fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine =
+            JmapAddressbookUpdate::new(&session, &self.auth, &self.user_agent, id, patch)?;
+        self.run(coroutine)
+    }
+
+    /// Deletes the addressbook identified by `id`, along with the cards
+    /// belonging to no other addressbook.
+    pub fn delete_addressbook(&mut self, id: &str) -> Result<(), JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapAddressbookDelete::new(&session, &self.auth, &self.user_agent, id)?;
+        self.run(coroutine)
+    }
+
+    /// Lists cards inside `addressbook_id`, applying 1-indexed
+    /// pagination server-side.
+    pub fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapCardList::new(
+            &session,
+            &self.auth,
+            &self.user_agent,
+            addressbook_id,
+            page,
+            page_size,
+        );
+        self.run(coroutine)
+    }
+
+    /// Lists the id and tag of every card in `addressbook_id`, sorted
+    /// by id.
+    ///
+    /// Tags are synthesized from the vCard rendering of the cards,
+    /// which are fetched in full.
+    pub fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, JmapClientError> {
+        let mut refs: Vec<CardRef> = self
+            .list_cards(addressbook_id, None, None)?
+            .into_iter()
+            .map(|card| CardRef {
+                id: card.id,
+                href: None,
+                etag: card.etag,
+            })
+            .collect();
+        refs.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(refs)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`,
+    /// narrowed down server-side via `ContactCard/query`.
+    pub fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapCardQuery::new(
+            &session,
+            &self.auth,
+            &self.user_agent,
+            addressbook_id,
+            filter,
+        );
+        self.run(coroutine)
+    }
+
+    /// Fetches the cards of `addressbook_id` changed since
+    /// `sync_token` via `ContactCard/changes`.
+    ///
+    /// Pass `None` for the initial sync. When the server cannot
+    /// compute the changes from the token, falls back to a tokenless
+    /// sync returning the full listing (flagged by
+    /// [`CardChanges::full`]) and a fresh token.
+    pub fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapCardSync::new(
+            &session,
+            &self.auth,
+            &self.user_agent,
+            addressbook_id,
+            sync_token,
+        );
+
+        match self.run(coroutine) {
+            Err(JmapClientError::CardSync(JmapCardSyncError::InvalidSyncToken(token))) => {
+                debug!("sync token {token} rejected, falling back to full sync");
+
+                let coroutine =
+                    JmapCardSync::new(&session, &self.auth, &self.user_agent, addressbook_id, None);
+                self.run(coroutine)
+            }
+            result => result,
+        }
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapCardGet::new(
+            &session,
+            &self.auth,
+            &self.user_agent,
+            addressbook_id,
+            card_id,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id` in a
+    /// single request. Ids the server does not return end up in
+    /// [`CardBatch::missing`].
+    pub fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapCardMultiget::new(
+            &session,
+            &self.auth,
+            &self.user_agent,
+            addressbook_id,
+            card_ids,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Creates a card from the vCard `contents` inside
+    /// `addressbook_id`. Returns the id assigned by the server and the
+    /// tag of the card.
+    pub fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapCardCreate::new(
+            &session,
+            &self.auth,
+            &self.user_agent,
+            addressbook_id,
+            &contents,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Same as [`JmapClient::create_card`]: the server assigns card
+    /// ids, so `options` do not apply.
+    pub fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        _options: CardCreateOptions,
+    ) -> Result<CardRef, JmapClientError> {
+        self.create_card(addressbook_id, contents)
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id`, gating on
+    /// `if_match` when present. Returns the card id and new tag.
+    pub fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapCardUpdate::new(
+            &session,
+            &self.auth,
+            &self.user_agent,
+            addressbook_id,
+            card_id,
+            &contents,
+            if_match,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id` unconditionally,
+    /// or creates the card when missing. The server assigns the id of
+    /// a created card, which may thus differ from `card_id`.
+    pub fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, JmapClientError> {
+        match self.update_card(addressbook_id, card_id, contents.clone(), None) {
+            Err(JmapClientError::CardUpdate(JmapCardUpdateError::CardNotFound(_))) => {
+                self.create_card(addressbook_id, contents)
+            }
+            result => result,
+        }
+    }
+
+    /// Permanently deletes `card_id` from `addressbook_id`, gating on
+    /// `if_match` when present.
+    pub fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), JmapClientError> {
+        let session = self.session()?.clone();
+        let coroutine = JmapCardDelete::new(
+            &session,
+            &self.auth,
+            &self.user_agent,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?;
+        self.run(coroutine)
+    }
+}
+
+impl AddressbookBackend for JmapClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(JmapClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(JmapClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(JmapClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(JmapClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(JmapClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(JmapClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(JmapClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(JmapClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(JmapClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(JmapClient::capabilities(self)?)
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(JmapClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(JmapClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(JmapClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(JmapClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(JmapClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(JmapClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String};
+
+    use super::*;
+    use crate::{card::tag::content_tag, http::client::mock::MockStream, jmap::jscontact::JsCard};
+
+    const SESSION: &str = r#"{
+        "capabilities": {
+            "urn:ietf:params:jmap:core": { "maxSizeRequest": 10000000 },
+            "urn:ietf:params:jmap:contacts": {}
+        },
+        "accounts": {
+            "a1": {
+                "accountCapabilities": {
+                    "urn:ietf:params:jmap:contacts": { "mayCreateAddressBook": false }
+                }
+            }
+        },
+        "primaryAccounts": { "urn:ietf:params:jmap:contacts": "a1" },
+        "apiUrl": "/jmap/api/"
+    }"#;
+
+    const CARD: &str = r#"{
+        "id": "c1",
+        "addressBookIds": { "ab": true },
+        "uid": "urn:uuid:c1",
+        "name": { "full": "Alice" }
+    }"#;
+
+    const VCARD: &[u8] =
+        b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:urn:uuid:c1\r\nFN:Alicia\r\nEND:VCARD\r\n";
+
+    fn client(stream: &MockStream) -> JmapClient {
+        stream.respond(200, SESSION);
+        let url = Url::parse("https://jmap.example.org/.well-known/jmap").unwrap();
+        JmapClient::new(stream.clone(), url, JmapAuth::Bearer("token".into()))
+    }
+
+    fn responses(invocations: &str) -> String {
+        format!(r#"{{ "methodResponses": [{invocations}], "sessionState": "0" }}"#)
+    }
+
+    fn card_tag() -> String {
+        let card: JsCard = serde_json::from_str(CARD).unwrap();
+        content_tag(&card.to_vcard())
+    }
+
+    #[test]
+    fn discover_session() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        let session = client.session().unwrap().clone();
+
+        assert_eq!(
+            session.api_url.as_str(),
+            "https://jmap.example.org/jmap/api/"
+        );
+        assert_eq!(session.account_id, "a1");
+        assert_eq!(session.max_size_request, Some(10000000));
+        assert!(!session.may_create_addressbook);
+
+        let requests = stream.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /.well-known/jmap HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Authorization: Bearer token\r\n"));
+
+        // NOTE: the session is cached, no request is sent again.
+        client.session().unwrap();
+        assert_eq!(stream.requests().len(), 1);
+    }
+
+    #[test]
+    fn list_addressbooks() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(
+            200,
+            &responses(
+                r#"["AddressBook/get", { "state": "b1", "list": [
+                    { "id": "work", "name": "Work" },
+                    { "id": "ab", "name": "Personal", "description": "" }
+                ] }, "0"],
+                ["ContactCard/get", { "state": "s1", "list": [] }, "1"]"#,
+            ),
+        );
+
+        let addressbooks = client.list_addressbooks().unwrap();
+
+        assert_eq!(addressbooks.len(), 2);
+        assert_eq!(addressbooks[0].id, "ab");
+        assert_eq!(addressbooks[0].description, None);
+        assert_eq!(addressbooks[0].sync_token.as_deref(), Some("s1"));
+        assert_eq!(addressbooks[1].name, "Work");
+
+        let requests = stream.requests();
+        assert!(requests[1].starts_with("POST /jmap/api/ HTTP/1.1\r\n"));
+        assert!(requests[1].contains(r#""AddressBook/get""#));
+    }
+
+    #[test]
+    fn update_card_state_mismatch() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(
+                200,
+                &responses(&format!(
+                    r#"["ContactCard/get", {{ "state": "s1", "list": [{CARD}] }}, "0"]"#
+                )),
+            )
+            .respond(
+                200,
+                &responses(r#"["error", { "type": "stateMismatch" }, "0"]"#),
+            );
+
+        let err = client
+            .update_card("ab", "c1", VCARD.to_vec(), Some(&card_tag()))
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            JmapClientError::CardUpdate(JmapCardUpdateError::PreconditionFailed { etag: None, .. })
+        ));
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+
+        let requests = stream.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].contains(r#""ifInState":"s1""#));
+    }
+
+    #[test]
+    fn update_card_stale_tag() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(
+            200,
+            &responses(&format!(
+                r#"["ContactCard/get", {{ "state": "s1", "list": [{CARD}] }}, "0"]"#
+            )),
+        );
+
+        let err = client
+            .update_card("ab", "c1", VCARD.to_vec(), Some("stale"))
+            .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+        assert_eq!(err.current_etag(), Some(card_tag().as_str()));
+        // NOTE: the write is not sent.
+        assert_eq!(stream.requests().len(), 2);
+    }
+
+    #[test]
+    fn sync_cards_changes() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(
+            200,
+            &responses(&format!(
+                r#"["ContactCard/changes", {{ "oldState": "s1", "newState": "s2",
+                    "hasMoreChanges": false, "created": ["c1"], "updated": [],
+                    "destroyed": ["c9"] }}, "0"],
+                ["ContactCard/get", {{ "state": "s2", "list": [{CARD}] }}, "1"],
+                ["ContactCard/get", {{ "state": "s2", "list": [] }}, "2"]"#
+            )),
+        );
+
+        let changes = client.sync_cards("ab", Some("s1")).unwrap();
+
+        assert!(!changes.full);
+        assert_eq!(changes.sync_token.as_deref(), Some("s2"));
+        assert_eq!(changes.cards.len(), 1);
+        assert_eq!(changes.cards[0].id, "c1");
+        assert_eq!(changes.deleted, ["c9"]);
+    }
+
+    #[test]
+    fn sync_cards_cannot_calculate_changes() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(
+                200,
+                &responses(
+                    r#"["error", { "type": "cannotCalculateChanges" }, "0"],
+                    ["error", { "type": "invalidResultReference" }, "1"],
+                    ["error", { "type": "invalidResultReference" }, "2"]"#,
+                ),
+            )
+            .respond(
+                200,
+                &responses(&format!(
+                    r#"["ContactCard/query", {{ "ids": ["c1"], "position": 0, "total": 1 }}, "0"],
+                    ["ContactCard/get", {{ "state": "s3", "list": [{CARD}] }}, "1"]"#
+                )),
+            );
+
+        let changes = client.sync_cards("ab", Some("expired")).unwrap();
+
+        assert!(changes.full);
+        assert_eq!(changes.sync_token.as_deref(), Some("s3"));
+        assert_eq!(changes.cards.len(), 1);
+        assert!(changes.deleted.is_empty());
+
+        let requests = stream.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].contains(r#""sinceState":"expired""#));
+        assert!(requests[2].contains(r#""ContactCard/query""#));
+    }
+}
@@ -0,0 +1,287 @@
+//! Conversions between JMAP for Contacts objects and the shared types
+//! used by [`JmapClient`], plus the `From` impl that wraps it into the
+//! unified client's JMAP variant.
+//!
+//! [`JmapClient`]: crate::jmap::client::JmapClient
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    addressbook::Addressbook,
+    capabilities::{AddressbookField, Capabilities},
+    card::{
+        Card, CardFilter, CardRef, FilterTest,
+        tag::{content_tag, tag_matches},
+    },
+    jmap::{
+        api::{JmapApiError, JmapMethodError},
+        jscontact::JsCard,
+        session::JmapSession,
+    },
+};
+
+impl From<crate::jmap::client::JmapClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::jmap::client::JmapClient) -> Self {
+        Self::Jmap(client)
+    }
+}
+
+/// vCard properties mapped to a `ContactCard/query` filter condition.
+const FILTER_CONDITIONS: [(&str, &str); 7] = [
+    ("FN", "name"),
+    ("N", "name"),
+    ("NICKNAME", "nickName"),
+    ("EMAIL", "email"),
+    ("TEL", "phone"),
+    ("ORG", "organization"),
+    ("NOTE", "note"),
+];
+
+/// `AddressBook` object, reduced to the fields of the shared
+/// [`Addressbook`].
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AddressBookWire {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Response of a `/get` method.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GetResponse<T> {
+    pub state: String,
+    pub list: Vec<T>,
+}
+
+/// Response of a `/set` method.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SetResponse {
+    #[serde(default)]
+    pub created: Option<BTreeMap<String, Value>>,
+    #[serde(default)]
+    pub updated: Option<BTreeMap<String, Option<Value>>>,
+    #[serde(default)]
+    pub not_created: Option<BTreeMap<String, Value>>,
+    #[serde(default)]
+    pub not_updated: Option<BTreeMap<String, Value>>,
+    #[serde(default)]
+    pub not_destroyed: Option<BTreeMap<String, Value>>,
+}
+
+impl SetResponse {
+    /// Returns the error of the creation `key`, if it failed.
+    pub fn create_error(&self, key: &str) -> Option<JmapMethodError> {
+        set_error(&self.not_created, key)
+    }
+
+    /// Returns the error of the update of `id`, if it failed.
+    pub fn update_error(&self, id: &str) -> Option<JmapMethodError> {
+        set_error(&self.not_updated, id)
+    }
+
+    /// Returns the error of the destruction of `id`, if it failed.
+    pub fn destroy_error(&self, id: &str) -> Option<JmapMethodError> {
+        set_error(&self.not_destroyed, id)
+    }
+}
+
+fn set_error(errors: &Option<BTreeMap<String, Value>>, key: &str) -> Option<JmapMethodError> {
+    errors
+        .as_ref()
+        .and_then(|errors| errors.get(key))
+        .map(JmapMethodError::from_value)
+}
+
+/// Response of a `/changes` method.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChangesResponse {
+    pub new_state: String,
+    pub has_more_changes: bool,
+    #[serde(default)]
+    pub destroyed: Vec<String>,
+}
+
+/// Deserializes the arguments of a method response.
+pub(crate) fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, JmapApiError> {
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Maps an `AddressBook` to a shared [`Addressbook`].
+///
+/// The `ctag` joins the states of the addressbooks and of the cards,
+/// which change whenever any of them does; the card state doubles as
+/// the sync token.
+pub(crate) fn addressbook_from_wire(
+    book: AddressBookWire,
+    books_state: &str,
+    cards_state: &str,
+) -> Addressbook {
+    Addressbook {
+        id: book.id,
+        name: book.name,
+        description: book.description.filter(|d| !d.is_empty()),
+        color: None,
+        ctag: Some([books_state, cards_state].join(":")),
+        sync_token: Some(cards_state.to_string()),
+    }
+}
+
+/// Maps a JSContact card to a shared [`Card`], its contents being the
+/// vCard rendering and its ETag the tag of these contents.
+pub(crate) fn card_from_wire(addressbook_id: &str, card: &JsCard) -> Card {
+    let contents = card.to_vcard();
+
+    Card {
+        id: card.id.clone().unwrap_or_default(),
+        addressbook_id: addressbook_id.to_string(),
+        etag: Some(content_tag(&contents)),
+        contents,
+    }
+}
+
+/// Maps a JSContact card to a [`CardRef`].
+pub(crate) fn card_ref_from_wire(card: &JsCard) -> CardRef {
+    CardRef {
+        id: card.id.clone().unwrap_or_default(),
+        href: None,
+        etag: Some(content_tag(&card.to_vcard())),
+    }
+}
+
+/// Builds the `#ids` back-reference to the ids at `path` in the
+/// response to call `call_id` of method `name`.
+pub(crate) fn result_of(call_id: &str, name: &str, path: &str) -> Value {
+    json!({ "resultOf": call_id, "name": name, "path": path })
+}
+
+/// Builds the `ContactCard/query` filter selecting the cards of
+/// `addressbook_id` that may match `filter`.
+///
+/// Server-side text matching is a case-insensitive "contains", which
+/// selects a superset of the matching cards for every match type and
+/// collation, so the caller still confirms each card with
+/// [`CardFilter::matches`]. Filters the conditions cannot express
+/// (property without condition, `is_not_defined`, negated or missing
+/// text match) only select the addressbook.
+pub(crate) fn filter_condition(addressbook_id: &str, filter: &CardFilter) -> Value {
+    let addressbook = json!({ "inAddressBook": addressbook_id });
+
+    let Some(condition) = filter_operator(filter) else {
+        return addressbook;
+    };
+
+    json!({ "operator": "AND", "conditions": [addressbook, condition] })
+}
+
+fn filter_operator(filter: &CardFilter) -> Option<Value> {
+    if filter.props.is_empty() {
+        return None;
+    }
+
+    let mut conditions = Vec::new();
+
+    for prop in &filter.props {
+        let name = prop.name.to_ascii_uppercase();
+
+        let (_, condition) = FILTER_CONDITIONS.iter().find(|(n, _)| *n == name)?;
+
+        if prop.is_not_defined
+            || prop.text_matches.is_empty()
+            || prop.text_matches.iter().any(|m| m.negate)
+        {
+            return None;
+        }
+
+        let matches = prop
+            .text_matches
+            .iter()
+            .map(|text_match| {
+                let mut object = Map::new();
+                object.insert(condition.to_string(), Value::from(text_match.text.as_str()));
+                Value::Object(object)
+            })
+            .collect();
+
+        conditions.push(operator(prop.test, matches));
+    }
+
+    Some(operator(filter.test, conditions))
+}
+
+fn operator(test: FilterTest, mut conditions: Vec<Value>) -> Value {
+    if conditions.len() == 1 {
+        return conditions.remove(0);
+    }
+
+    let operator = match test {
+        FilterTest::AnyOf => "OR",
+        FilterTest::AllOf => "AND",
+    };
+
+    json!({ "operator": operator, "conditions": conditions })
+}
+
+/// Capabilities of a JMAP for Contacts account.
+pub(crate) fn capabilities(session: &JmapSession) -> Capabilities {
+    Capabilities {
+        etag: true,
+        ctag: true,
+        sync_token: true,
+        query: true,
+        multiget: true,
+        vcard_versions: vec!["4.0".to_string()],
+        max_resource_size: session.max_size_request,
+        writable_metadata: vec![AddressbookField::Name, AddressbookField::Description],
+    }
+}
+
+/// Outcome of checking an `if_match` tag against a card, see
+/// [`check_precondition`].
+pub(crate) enum Precondition {
+    /// The tag matches; holds the card state to gate the write on.
+    Met(String),
+    /// The card is not in the addressbook.
+    NotFound,
+    /// The card changed; holds its current tag.
+    Failed(String),
+}
+
+/// Checks `if_match` against the tag of `card_id`, from the response
+/// of a `ContactCard/get` on it.
+pub(crate) fn check_precondition(
+    get: GetResponse<JsCard>,
+    addressbook_id: &str,
+    card_id: &str,
+    if_match: &str,
+) -> Precondition {
+    let card = get
+        .list
+        .iter()
+        .find(|card| card.id.as_deref() == Some(card_id) && card.in_addressbook(addressbook_id));
+
+    let Some(card) = card else {
+        return Precondition::NotFound;
+    };
+
+    let tag = content_tag(&card.to_vcard());
+
+    if tag_matches(if_match, &tag) {
+        Precondition::Met(get.state)
+    } else {
+        Precondition::Failed(tag)
+    }
+}
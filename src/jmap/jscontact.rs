@@ -0,0 +1,852 @@
+//! JSContact cards (RFC 9553) and their conversion to and from the
+//! typed [`Contact`] view.
+//!
+//! Properties follow the RFC 9555 mapping. vCard properties without a
+//! JSContact counterpart travel in `vCardProps`, so that a card
+//! written through this backend reads back the same. Group and
+//! parameters of typed vCard properties other than `TYPE` and `PREF`
+//! have no JSContact counterpart and are dropped.
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::card::{
+    Address, Contact, Email, Organization, Parameter, Phone, Property, StructuredName, escape,
+};
+
+/// Card properties rewritten by a whole-card update, see
+/// [`JsCard::to_patch`].
+const UPDATABLE_PROPERTIES: [&str; 15] = [
+    "kind",
+    "name",
+    "nicknames",
+    "emails",
+    "phones",
+    "addresses",
+    "organizations",
+    "titles",
+    "anniversaries",
+    "notes",
+    "keywords",
+    "media",
+    "links",
+    "updated",
+    "vCardProps",
+];
+
+/// Mapping between vCard `N` components and JSContact name component
+/// kinds, in display order.
+const NAME_COMPONENTS: [&str; 5] = ["title", "given", "given2", "surname", "credential"];
+
+/// Phone features matching a vCard `TEL` type of the same name.
+const PHONE_FEATURES: [&str; 6] = ["voice", "fax", "pager", "text", "video", "textphone"];
+
+/// JSContact card, as exchanged by the `ContactCard` methods.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsCard {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub address_book_ids: BTreeMap<String, bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<JsName>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub nicknames: BTreeMap<String, JsNickname>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub emails: BTreeMap<String, JsEmail>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub phones: BTreeMap<String, JsPhone>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub addresses: BTreeMap<String, JsAddress>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub organizations: BTreeMap<String, JsOrganization>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub titles: BTreeMap<String, JsTitle>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub anniversaries: BTreeMap<String, JsAnniversary>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<String, JsNote>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub keywords: BTreeMap<String, bool>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub media: BTreeMap<String, JsMedia>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub links: BTreeMap<String, JsLink>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    /// vCard properties without a JSContact counterpart, as jCard-like
+    /// `[name, parameters, type, value]` arrays.
+    #[serde(rename = "vCardProps", skip_serializing_if = "Vec::is_empty")]
+    pub vcard_props: Vec<Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsName {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<JsComponent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsComponent {
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsNickname {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsEmail {
+    pub address: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub contexts: BTreeMap<String, bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pref: Option<u8>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsPhone {
+    pub number: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, bool>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub contexts: BTreeMap<String, bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pref: Option<u8>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsAddress {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<JsComponent>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub contexts: BTreeMap<String, bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pref: Option<u8>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsOrganization {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub units: Vec<JsOrgUnit>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsOrgUnit {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsTitle {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsAnniversary {
+    pub kind: String,
+    pub date: JsDate,
+}
+
+/// `PartialDate` or `Timestamp` of an anniversary.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsDate {
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
+    pub date_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub month: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utc: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsNote {
+    pub note: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsMedia {
+    pub kind: String,
+    pub uri: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsLink {
+    pub uri: String,
+}
+
+impl JsCard {
+    /// Maps a typed [`Contact`] to a JSContact card, without id nor
+    /// addressbook membership.
+    pub fn from_contact(contact: &Contact) -> Self {
+        let mut card = Self {
+            uid: contact.uid.clone(),
+            ..Default::default()
+        };
+
+        if contact.formatted_name.is_some() || contact.name.is_some() {
+            let mut name = JsName {
+                full: contact.formatted_name.clone(),
+                ..Default::default()
+            };
+
+            if let Some(n) = &contact.name {
+                let values = [&n.prefixes, &n.given, &n.additional, &n.family, &n.suffixes];
+
+                for (kind, value) in NAME_COMPONENTS.iter().zip(values) {
+                    for value in value.split(',').filter(|v| !v.is_empty()) {
+                        name.components.push(JsComponent {
+                            kind: kind.to_string(),
+                            value: value.to_string(),
+                        });
+                    }
+                }
+            }
+
+            card.name = Some(name);
+        }
+
+        for (i, email) in contact.emails.iter().enumerate() {
+            let email = JsEmail {
+                address: email.address.clone(),
+                contexts: contexts_from_types(&email.types),
+                pref: email.pref,
+            };
+            card.emails.insert(format!("e{}", i + 1), email);
+        }
+
+        for (i, phone) in contact.phones.iter().enumerate() {
+            let mut features = BTreeMap::new();
+
+            for kind in &phone.types {
+                match kind.as_str() {
+                    "cell" => features.insert("mobile".to_string(), true),
+                    kind if PHONE_FEATURES.contains(&kind) => {
+                        features.insert(kind.to_string(), true)
+                    }
+                    _ => None,
+                };
+            }
+
+            let phone = JsPhone {
+                number: phone.number.clone(),
+                features,
+                contexts: contexts_from_types(&phone.types),
+                pref: phone.pref,
+            };
+            card.phones.insert(format!("p{}", i + 1), phone);
+        }
+
+        for (i, adr) in contact.addresses.iter().enumerate() {
+            let values = [
+                ("postOfficeBox", &adr.po_box),
+                ("apartment", &adr.extended),
+                ("name", &adr.street),
+                ("locality", &adr.locality),
+                ("region", &adr.region),
+                ("postcode", &adr.postal_code),
+                ("country", &adr.country),
+            ];
+
+            let components = values
+                .into_iter()
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(kind, value)| JsComponent {
+                    kind: kind.to_string(),
+                    value: value.clone(),
+                })
+                .collect();
+
+            let adr = JsAddress {
+                components,
+                contexts: contexts_from_types(&adr.types),
+                pref: adr.pref,
+            };
+            card.addresses.insert(format!("a{}", i + 1), adr);
+        }
+
+        if let Some(org) = &contact.org {
+            let org = JsOrganization {
+                name: Some(org.name.clone()).filter(|n| !n.is_empty()),
+                units: org
+                    .units
+                    .iter()
+                    .map(|name| JsOrgUnit { name: name.clone() })
+                    .collect(),
+            };
+            card.organizations.insert("o1".to_string(), org);
+        }
+
+        if let Some(title) = &contact.title {
+            let title = JsTitle {
+                name: title.clone(),
+                kind: Some("title".to_string()),
+            };
+            card.titles.insert("t1".to_string(), title);
+        }
+
+        if let Some(bday) = &contact.birthday {
+            match partial_date(bday) {
+                Some(date) => {
+                    let birth = JsAnniversary {
+                        kind: "birth".to_string(),
+                        date,
+                    };
+                    card.anniversaries.insert("k1".to_string(), birth);
+                }
+                None => card.vcard_props.push(vcard_prop("BDAY", &[], None, bday)),
+            }
+        }
+
+        for (i, note) in contact.notes.iter().enumerate() {
+            let note = JsNote { note: note.clone() };
+            card.notes.insert(format!("n{}", i + 1), note);
+        }
+
+        for category in &contact.categories {
+            card.keywords.insert(category.clone(), true);
+        }
+
+        if let Some(photo) = &contact.photo {
+            // NOTE: a vCard 3.0 inline photo only keeps its base64
+            // payload, which needs a data URI to travel as a link.
+            let uri = if photo.contains(':') {
+                photo.clone()
+            } else {
+                format!("data:image/jpeg;base64,{photo}")
+            };
+
+            let media = JsMedia {
+                kind: "photo".to_string(),
+                uri,
+            };
+            card.media.insert("m1".to_string(), media);
+        }
+
+        if let Some(rev) = &contact.rev {
+            match utc_from_vcard(rev) {
+                Some(utc) => card.updated = Some(utc),
+                None => card.vcard_props.push(vcard_prop("REV", &[], None, rev)),
+            }
+        }
+
+        for prop in &contact.properties {
+            match prop.name.as_str() {
+                "KIND" => card.kind = Some(prop.text().to_lowercase()),
+                "NICKNAME" => {
+                    let key = format!("k{}", card.nicknames.len() + 1);
+                    let name = JsNickname { name: prop.text() };
+                    card.nicknames.insert(key, name);
+                }
+                "URL" => {
+                    let key = format!("l{}", card.links.len() + 1);
+                    let link = JsLink { uri: prop.text() };
+                    card.links.insert(key, link);
+                }
+                name => {
+                    let prop = vcard_prop(name, &prop.params, prop.group.as_deref(), &prop.value);
+                    card.vcard_props.push(prop);
+                }
+            }
+        }
+
+        card
+    }
+
+    /// Maps the card to a typed [`Contact`].
+    pub fn to_contact(&self) -> Contact {
+        let mut contact = Contact::new();
+        contact.uid = self.uid.clone();
+
+        if let Some(name) = &self.name {
+            let mut n = StructuredName::default();
+
+            for component in &name.components {
+                let field = match component.kind.as_str() {
+                    "title" => &mut n.prefixes,
+                    "given" => &mut n.given,
+                    "given2" => &mut n.additional,
+                    "surname" | "surname2" => &mut n.family,
+                    "credential" | "generation" => &mut n.suffixes,
+                    _ => continue,
+                };
+
+                if !field.is_empty() {
+                    field.push(',');
+                }
+
+                field.push_str(&component.value);
+            }
+
+            // NOTE: vCard requires a formatted name, derived from the
+            // components when the card has none.
+            let full = name.full.clone().unwrap_or_else(|| {
+                let values = name.components.iter().filter(|c| c.kind != "separator");
+                values
+                    .map(|c| c.value.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            });
+
+            contact.formatted_name = Some(full);
+
+            if n != StructuredName::default() {
+                contact.name = Some(n);
+            }
+        }
+
+        for email in self.emails.values() {
+            contact.emails.push(Email {
+                address: email.address.clone(),
+                types: types_from_contexts(&email.contexts),
+                pref: email.pref,
+                ..Default::default()
+            });
+        }
+
+        for phone in self.phones.values() {
+            let mut types = types_from_contexts(&phone.contexts);
+
+            for (feature, enabled) in &phone.features {
+                match feature.as_str() {
+                    _ if !enabled => (),
+                    "mobile" => types.push("cell".to_string()),
+                    feature if PHONE_FEATURES.contains(&feature) => types.push(feature.to_string()),
+                    _ => (),
+                }
+            }
+
+            contact.phones.push(Phone {
+                number: phone.number.clone(),
+                types,
+                pref: phone.pref,
+                ..Default::default()
+            });
+        }
+
+        for adr in self.addresses.values() {
+            let mut address = Address {
+                types: types_from_contexts(&adr.contexts),
+                pref: adr.pref,
+                ..Default::default()
+            };
+
+            for component in &adr.components {
+                let field = match component.kind.as_str() {
+                    "postOfficeBox" => &mut address.po_box,
+                    "apartment" | "room" | "floor" | "building" => &mut address.extended,
+                    "number" | "name" | "block" | "direction" => &mut address.street,
+                    "locality" | "district" | "subdistrict" => &mut address.locality,
+                    "region" => &mut address.region,
+                    "postcode" => &mut address.postal_code,
+                    "country" => &mut address.country,
+                    _ => continue,
+                };
+
+                if !field.is_empty() {
+                    field.push(' ');
+                }
+
+                field.push_str(&component.value);
+            }
+
+            contact.addresses.push(address);
+        }
+
+        // NOTE: vCard keeps a single organization and title, the
+        // first ones win.
+        if let Some(org) = self.organizations.values().next() {
+            contact.org = Some(Organization {
+                name: org.name.clone().unwrap_or_default(),
+                units: org.units.iter().map(|unit| unit.name.clone()).collect(),
+            });
+        }
+
+        for title in self.titles.values() {
+            match title.kind.as_deref() {
+                Some("role") => contact.properties.push(text_prop("ROLE", &title.name)),
+                _ if contact.title.is_none() => contact.title = Some(title.name.clone()),
+                _ => (),
+            }
+        }
+
+        for anniversary in self.anniversaries.values() {
+            let Some(date) = vcard_date(&anniversary.date) else {
+                continue;
+            };
+
+            match anniversary.kind.as_str() {
+                "birth" => contact.birthday = Some(date),
+                "death" => contact.properties.push(raw_prop("DEATHDATE", date)),
+                "wedding" => contact.properties.push(raw_prop("ANNIVERSARY", date)),
+                _ => (),
+            }
+        }
+
+        contact.notes = self.notes.values().map(|n| n.note.clone()).collect();
+
+        for (keyword, enabled) in &self.keywords {
+            if *enabled {
+                contact.categories.push(keyword.clone());
+            }
+        }
+
+        contact.photo = self
+            .media
+            .values()
+            .find(|media| media.kind == "photo")
+            .map(|media| media.uri.clone());
+
+        contact.rev = self.updated.as_deref().map(vcard_from_utc);
+
+        if let Some(kind) = &self.kind {
+            contact.properties.push(raw_prop("KIND", kind.clone()));
+        }
+
+        for nickname in self.nicknames.values() {
+            contact
+                .properties
+                .push(text_prop("NICKNAME", &nickname.name));
+        }
+
+        for link in self.links.values() {
+            contact.properties.push(text_prop("URL", &link.uri));
+        }
+
+        for prop in &self.vcard_props {
+            if let Some(prop) = prop_from_vcard_prop(prop) {
+                match prop.name.as_str() {
+                    "BDAY" if contact.birthday.is_none() => contact.birthday = Some(prop.value),
+                    "REV" if contact.rev.is_none() => contact.rev = Some(prop.value),
+                    _ => contact.properties.push(prop),
+                }
+            }
+        }
+
+        contact
+    }
+
+    /// Renders the card as vCard bytes.
+    pub fn to_vcard(&self) -> Vec<u8> {
+        self.to_contact().to_bytes()
+    }
+
+    /// Builds the `/set` patch replacing every property mapped to
+    /// vCard, adding the card to `addressbook_id` while keeping its
+    /// other memberships.
+    ///
+    /// Properties the card lacks are reset to their default (`null`);
+    /// server-side properties without a vCard counterpart are left
+    /// untouched.
+    pub fn to_patch(&self, addressbook_id: &str) -> Value {
+        let mut patch = match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+
+        patch.remove("id");
+        patch.remove("addressBookIds");
+
+        // NOTE: JMAP servers may refuse a `uid` change, but setting
+        // the current value is a no-op.
+        if self.uid.is_none() {
+            patch.remove("uid");
+        }
+
+        for name in UPDATABLE_PROPERTIES {
+            patch.entry(name).or_insert(Value::Null);
+        }
+
+        patch.insert(
+            format!("addressBookIds/{addressbook_id}"),
+            Value::Bool(true),
+        );
+        Value::Object(patch)
+    }
+
+    /// Builds the `/set` creation object of the card inside
+    /// `addressbook_id`.
+    pub fn to_create(&self, addressbook_id: &str) -> Value {
+        let mut card = self.clone();
+        card.id = None;
+        card.address_book_ids = BTreeMap::from([(addressbook_id.to_string(), true)]);
+
+        let mut object = match serde_json::to_value(card) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+
+        object.insert("@type".to_string(), Value::from("Card"));
+        object.insert("version".to_string(), Value::from("1.0"));
+        Value::Object(object)
+    }
+
+    /// Overlays the properties the server reported for a created or
+    /// updated card (`created` / `updated` entries of a `/set`
+    /// response), returning the card as stored.
+    pub fn merge(&self, changes: &Value) -> Self {
+        let mut object = match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+
+        if let Some(changes) = changes.as_object() {
+            for (name, value) in changes {
+                object.insert(name.clone(), value.clone());
+            }
+        }
+
+        serde_json::from_value(Value::Object(object)).unwrap_or_else(|_| self.clone())
+    }
+
+    /// Tells whether the card belongs to `addressbook_id`.
+    pub fn in_addressbook(&self, addressbook_id: &str) -> bool {
+        self.address_book_ids.get(addressbook_id) == Some(&true)
+    }
+}
+
+/// Maps vCard `TYPE` values to JSContact contexts.
+fn contexts_from_types(types: &[String]) -> BTreeMap<String, bool> {
+    let mut contexts = BTreeMap::new();
+
+    for kind in types {
+        match kind.as_str() {
+            "home" => contexts.insert("private".to_string(), true),
+            "work" => contexts.insert("work".to_string(), true),
+            _ => None,
+        };
+    }
+
+    contexts
+}
+
+/// Maps JSContact contexts to vCard `TYPE` values.
+fn types_from_contexts(contexts: &BTreeMap<String, bool>) -> Vec<String> {
+    let mut types = Vec::new();
+
+    for (context, enabled) in contexts {
+        match context.as_str() {
+            _ if !enabled => (),
+            "private" => types.push("home".to_string()),
+            "work" => types.push("work".to_string()),
+            _ => (),
+        }
+    }
+
+    types
+}
+
+/// Builds a `vCardProps` entry from a raw vCard property, its value
+/// kept as written (`unknown` type).
+fn vcard_prop(name: &str, params: &[Parameter], group: Option<&str>, value: &str) -> Value {
+    let mut object = Map::new();
+
+    if let Some(group) = group {
+        object.insert("group".to_string(), Value::from(group));
+    }
+
+    for param in params {
+        let value = match param.values.as_slice() {
+            [value] => Value::from(value.as_str()),
+            values => Value::from(values.to_vec()),
+        };
+        object.insert(param.name.to_lowercase(), value);
+    }
+
+    Value::from(vec![
+        Value::from(name.to_lowercase()),
+        Value::Object(object),
+        Value::from("unknown"),
+        Value::from(value),
+    ])
+}
+
+/// Maps a `vCardProps` entry back to a raw vCard property.
+fn prop_from_vcard_prop(entry: &Value) -> Option<Property> {
+    let [name, params, _, value] = entry.as_array()?.as_slice() else {
+        return None;
+    };
+
+    let mut prop = Property {
+        name: name.as_str()?.to_uppercase(),
+        value: match value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        },
+        ..Default::default()
+    };
+
+    for (name, value) in params.as_object().into_iter().flatten() {
+        let values: Vec<String> = match value {
+            Value::Array(values) => values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            Value::String(value) if name == "group" => {
+                prop.group = Some(value.clone());
+                continue;
+            }
+            Value::String(value) => vec![value.clone()],
+            value => vec![value.to_string()],
+        };
+
+        prop.params.push(Parameter {
+            name: name.to_uppercase(),
+            values,
+        });
+    }
+
+    Some(prop)
+}
+
+/// Builds a vCard property holding `text`, escaped.
+fn text_prop(name: &str, text: &str) -> Property {
+    raw_prop(name, escape(text))
+}
+
+/// Builds a vCard property holding `value` as written.
+fn raw_prop(name: &str, value: String) -> Property {
+    Property {
+        name: name.to_string(),
+        value,
+        ..Default::default()
+    }
+}
+
+/// Parses a vCard date (`19850412`, `1985-04-12`, `1985-04`, `1985`,
+/// `--0412`, `---12`, optionally followed by a time) into a
+/// `PartialDate`.
+fn partial_date(value: &str) -> Option<JsDate> {
+    let date = value.split('T').next().unwrap_or_default();
+
+    let number = |digits: &str| -> Option<u32> {
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
+    };
+
+    let (year, month, day) = if let Some(day) = date.strip_prefix("---") {
+        (None, None, Some(number(day)?))
+    } else if let Some(rest) = date.strip_prefix("--") {
+        let rest = rest.replace('-', "");
+        match rest.len() {
+            2 => (None, Some(number(&rest)?), None),
+            4 => (None, Some(number(&rest[..2])?), Some(number(&rest[2..])?)),
+            _ => return None,
+        }
+    } else {
+        let digits = date.replace('-', "");
+        match digits.len() {
+            4 => (Some(number(&digits)?), None, None),
+            6 => (
+                Some(number(&digits[..4])?),
+                Some(number(&digits[4..])?),
+                None,
+            ),
+            8 => (
+                Some(number(&digits[..4])?),
+                Some(number(&digits[4..6])?),
+                Some(number(&digits[6..])?),
+            ),
+            _ => return None,
+        }
+    };
+
+    Some(JsDate {
+        date_type: Some("PartialDate".to_string()),
+        year,
+        month,
+        day,
+        utc: None,
+    })
+}
+
+/// Renders a `PartialDate` or `Timestamp` as a vCard date.
+fn vcard_date(date: &JsDate) -> Option<String> {
+    if let Some(utc) = &date.utc {
+        return partial_date(utc).and_then(|date| vcard_date(&date));
+    }
+
+    let date = match (date.year, date.month, date.day) {
+        (Some(y), Some(m), Some(d)) => format!("{y:04}{m:02}{d:02}"),
+        (Some(y), Some(m), None) => format!("{y:04}-{m:02}"),
+        (Some(y), None, None) => format!("{y:04}"),
+        (None, Some(m), Some(d)) => format!("--{m:02}{d:02}"),
+        (None, Some(m), None) => format!("--{m:02}"),
+        (None, None, Some(d)) => format!("---{d:02}"),
+        _ => return None,
+    };
+
+    Some(date)
+}
+
+/// Parses a vCard UTC timestamp (`20240101T120000Z`, extended format
+/// accepted) into a JSContact `UTCDateTime`.
+fn utc_from_vcard(value: &str) -> Option<String> {
+    let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    if digits.len() < 14 || !value.ends_with('Z') {
+        return None;
+    }
+
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}Z",
+        &digits[..4],
+        &digits[4..6],
+        &digits[6..8],
+        &digits[8..10],
+        &digits[10..12],
+        &digits[12..14],
+    ))
+}
+
+/// Renders a JSContact `UTCDateTime` as a vCard UTC timestamp.
+fn vcard_from_utc(value: &str) -> String {
+    let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    if digits.len() < 14 {
+        return value.to_string();
+    }
+
+    format!("{}T{}Z", &digits[..8], &digits[8..14])
+}
@@ -0,0 +1,9 @@
+//! JMAP for Contacts backend (RFC 9610): the I/O-free JMAP plumbing,
+//! the JSContact conversions and the std client.
+
+pub mod api;
+pub mod client;
+pub mod convert;
+pub(crate) mod jscontact;
+pub mod send;
+pub mod session;
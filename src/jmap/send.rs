@@ -0,0 +1,91 @@
+//! I/O-free HTTP/1.1 exchange carrying JMAP requests.
+//!
+//! JMAP only needs two requests: a `GET` on the session resource and
+//! `POST`s of JSON bodies on the API endpoint. [`JmapSend`] adds the
+//! JMAP credentials to the shared [`HttpSend`] exchange.
+
+use alloc::{format, string::String, vec::Vec};
+
+use log::trace;
+use url::Url;
+
+use crate::{
+    base64,
+    http::{
+        coroutine::*,
+        send::{HttpSend, HttpSendError},
+    },
+};
+
+/// Credentials sent along each JMAP request.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum JmapAuth {
+    /// No `Authorization` header.
+    #[default]
+    None,
+    /// `Authorization: Bearer <token>`, e.g. an OAuth 2.0 access
+    /// token.
+    Bearer(String),
+    /// `Authorization: Basic <base64(username:password)>`.
+    Basic { username: String, password: String },
+}
+
+impl JmapAuth {
+    /// Renders the value of the `Authorization` header, if any.
+    fn header(&self) -> Option<String> {
+        match self {
+            Self::None => None,
+            Self::Bearer(token) => Some(format!("Bearer {token}")),
+            Self::Basic { username, password } => {
+                let credentials = format!("{username}:{password}");
+                Some(format!("Basic {}", base64::encode(credentials.as_bytes())))
+            }
+        }
+    }
+}
+
+/// I/O-free coroutine sending one JMAP request over HTTP/1.1.
+///
+/// On completion returns the body of a 2xx response; any other status
+/// surfaces as [`HttpSendError::Status`].
+pub struct JmapSend {
+    inner: HttpSend,
+}
+
+impl JmapSend {
+    /// Builds the coroutine sending a `GET` on `url`.
+    pub fn get(url: &Url, auth: &JmapAuth, user_agent: &str) -> Self {
+        Self::new("GET", url, auth, user_agent, None)
+    }
+
+    /// Builds the coroutine sending a `POST` of the JSON `body` on
+    /// `url`.
+    pub fn post(url: &Url, auth: &JmapAuth, user_agent: &str, body: Vec<u8>) -> Self {
+        Self::new("POST", url, auth, user_agent, Some(body))
+    }
+
+    fn new(
+        method: &str,
+        url: &Url,
+        auth: &JmapAuth,
+        user_agent: &str,
+        body: Option<Vec<u8>>,
+    ) -> Self {
+        trace!("prepare jmap {method} {url}");
+
+        let authorization = auth.header();
+
+        Self {
+            inner: HttpSend::new(method, url, authorization.as_deref(), user_agent, body),
+        }
+    }
+}
+
+impl HttpCoroutine for JmapSend {
+    type Yield = HttpYield;
+    type Return = Result<Vec<u8>, HttpSendError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        self.inner.resume(arg)
+    }
+}
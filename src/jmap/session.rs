@@ -0,0 +1,145 @@
+//! JMAP session discovery (RFC 8620 §2).
+//!
+//! The session resource tells where to send API requests and which
+//! account holds the contacts (RFC 9610). It is fetched once, then
+//! cached by the client.
+
+use alloc::{collections::BTreeMap, string::String};
+
+use log::trace;
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    http::{coroutine::*, send::HttpSendError},
+    jmap::send::{JmapAuth, JmapSend},
+};
+
+/// Capability of the JMAP core protocol (RFC 8620).
+pub const CORE_CAPABILITY: &str = "urn:ietf:params:jmap:core";
+
+/// Capability of JMAP for Contacts (RFC 9610).
+pub const CONTACTS_CAPABILITY: &str = "urn:ietf:params:jmap:contacts";
+
+/// Errors produced by [`JmapSessionGet`].
+#[derive(Debug, Error)]
+pub enum JmapSessionGetError {
+    #[error(transparent)]
+    Send(#[from] HttpSendError),
+    #[error("Invalid JMAP session: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid JMAP API URL `{0}`")]
+    InvalidApiUrl(String),
+    #[error("No JMAP account supports contacts")]
+    MissingContactsAccount,
+}
+
+/// Subset of the JMAP session used by the contacts backend.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JmapSession {
+    /// Endpoint receiving the API requests.
+    pub api_url: Url,
+    /// Primary account for contacts.
+    pub account_id: String,
+    /// Largest request body accepted, in bytes.
+    pub max_size_request: Option<u64>,
+    /// The account may create addressbooks.
+    pub may_create_addressbook: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionWire {
+    #[serde(default)]
+    capabilities: BTreeMap<String, Value>,
+    #[serde(default)]
+    accounts: BTreeMap<String, AccountWire>,
+    #[serde(default)]
+    primary_accounts: BTreeMap<String, String>,
+    api_url: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountWire {
+    #[serde(default)]
+    account_capabilities: BTreeMap<String, Value>,
+}
+
+/// I/O-free coroutine fetching the JMAP session resource.
+///
+/// On completion returns the [`JmapSession`], the API URL being
+/// resolved against the session URL.
+pub struct JmapSessionGet {
+    session_url: Url,
+    inner: JmapSend,
+}
+
+impl JmapSessionGet {
+    /// Builds the coroutine fetching the session at `session_url`
+    /// (usually `/.well-known/jmap`).
+    pub fn new(session_url: &Url, auth: &JmapAuth, user_agent: &str) -> Self {
+        trace!("prepare jmap session get");
+
+        Self {
+            session_url: session_url.clone(),
+            inner: JmapSend::get(session_url, auth, user_agent),
+        }
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<JmapSession, JmapSessionGetError> {
+        let wire: SessionWire = serde_json::from_slice(body)?;
+
+        let api_url = self
+            .session_url
+            .join(&wire.api_url)
+            .map_err(|_| JmapSessionGetError::InvalidApiUrl(wire.api_url.clone()))?;
+
+        let account_id = wire
+            .primary_accounts
+            .get(CONTACTS_CAPABILITY)
+            .filter(|_| wire.capabilities.contains_key(CONTACTS_CAPABILITY))
+            .ok_or(JmapSessionGetError::MissingContactsAccount)?
+            .clone();
+
+        let max_size_request = wire
+            .capabilities
+            .get(CORE_CAPABILITY)
+            .and_then(|core| core.get("maxSizeRequest"))
+            .and_then(Value::as_u64);
+
+        // NOTE: the capability is optional, servers omitting it are
+        // assumed to let the account manage its addressbooks.
+        let may_create_addressbook = wire
+            .accounts
+            .get(&account_id)
+            .and_then(|account| account.account_capabilities.get(CONTACTS_CAPABILITY))
+            .and_then(|contacts| contacts.get("mayCreateAddressBook"))
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+
+        Ok(JmapSession {
+            api_url,
+            account_id,
+            max_size_request,
+            may_create_addressbook,
+        })
+    }
+}
+
+impl HttpCoroutine for JmapSessionGet {
+    type Yield = HttpYield;
+    type Return = Result<JmapSession, JmapSessionGetError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => {
+                HttpCoroutineState::Complete(self.parse(&body))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Minimal BER codec (X.690) covering the LDAPv3 messages exchanged
+//! by the backend: single-byte tags and definite lengths only, as
+//! required by RFC 4511 §5.1.
+
+use alloc::{string::String, vec::Vec};
+
+pub(crate) const BOOLEAN: u8 = 0x01;
+pub(crate) const INTEGER: u8 = 0x02;
+pub(crate) const OCTET_STRING: u8 = 0x04;
+pub(crate) const ENUMERATED: u8 = 0x0a;
+pub(crate) const SEQUENCE: u8 = 0x30;
+
+/// Encodes one element made of `tag` and `content`.
+pub(crate) fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(content.len() + 6);
+    out.push(tag);
+
+    let len = content.len();
+
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+
+    out.extend_from_slice(content);
+    out
+}
+
+/// Encodes a constructed element from its already encoded children.
+pub(crate) fn constructed(tag: u8, children: &[Vec<u8>]) -> Vec<u8> {
+    tlv(tag, &children.concat())
+}
+
+/// Encodes an integer (or enumerated, depending on `tag`) in its
+/// shortest two's complement form.
+pub(crate) fn integer(tag: u8, value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+
+    while start < 7 {
+        let redundant = (bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0);
+
+        if !redundant {
+            break;
+        }
+
+        start += 1;
+    }
+
+    tlv(tag, &bytes[start..])
+}
+
+/// Encodes a boolean.
+pub(crate) fn boolean(value: bool) -> Vec<u8> {
+    tlv(BOOLEAN, &[if value { 0xff } else { 0x00 }])
+}
+
+/// One decoded element, borrowing its content.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Tlv<'a> {
+    pub tag: u8,
+    pub content: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    /// Fails unless the element carries `tag`.
+    pub fn expect(self, tag: u8) -> Result<Self, &'static str> {
+        if self.tag == tag {
+            Ok(self)
+        } else {
+            Err("unexpected BER tag")
+        }
+    }
+
+    /// Decodes the content as an integer or enumerated value.
+    pub fn integer(&self) -> Result<i64, &'static str> {
+        if self.content.is_empty() || self.content.len() > 8 {
+            return Err("invalid BER integer");
+        }
+
+        let negative = self.content[0] & 0x80 != 0;
+        let mut value: i64 = if negative { -1 } else { 0 };
+
+        for byte in self.content {
+            value = (value << 8) | *byte as i64;
+        }
+
+        Ok(value)
+    }
+
+    /// Decodes the content as a UTF-8 string.
+    pub fn string(&self) -> Result<String, &'static str> {
+        core::str::from_utf8(self.content)
+            .map(Into::into)
+            .map_err(|_| "non UTF-8 BER string")
+    }
+
+    /// Iterates over the children of a constructed element.
+    pub fn children(&self) -> Reader<'a> {
+        Reader::new(self.content)
+    }
+}
+
+/// Decodes the element at the start of `buf`, returning it along with
+/// its encoded size, or `None` while it is incomplete.
+pub(crate) fn read(buf: &[u8]) -> Result<Option<(Tlv<'_>, usize)>, &'static str> {
+    let [tag, first, rest @ ..] = buf else {
+        return Ok(None);
+    };
+
+    if tag & 0x1f == 0x1f {
+        return Err("unsupported multi-byte BER tag");
+    }
+
+    let (len, header) = if first & 0x80 == 0 {
+        (*first as usize, 2)
+    } else {
+        let n = (first & 0x7f) as usize;
+
+        if n == 0 || n > core::mem::size_of::<usize>() {
+            return Err("unsupported BER length");
+        }
+
+        if rest.len() < n {
+            return Ok(None);
+        }
+
+        let len = rest[..n]
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+
+        (len, 2 + n)
+    };
+
+    let Some(end) = header.checked_add(len) else {
+        return Err("unsupported BER length");
+    };
+
+    if buf.len() < end {
+        return Ok(None);
+    }
+
+    let tlv = Tlv {
+        tag: *tag,
+        content: &buf[header..end],
+    };
+
+    Ok(Some((tlv, end)))
+}
+
+/// Sequential reader over the children of a constructed element.
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    /// Tells whether every child has been read.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Reads the next child, which must be complete.
+    pub fn next(&mut self) -> Result<Tlv<'a>, &'static str> {
+        match read(self.buf)? {
+            Some((tlv, size)) => {
+                self.buf = &self.buf[size..];
+                Ok(tlv)
+            }
+            None => Err("truncated BER element"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_short_length() {
+        let (tlv, size) = read(&[OCTET_STRING, 2, b'a', b'b', 0xff]).unwrap().unwrap();
+
+        assert_eq!(tlv.tag, OCTET_STRING);
+        assert_eq!(tlv.content, b"ab");
+        assert_eq!(size, 4);
+    }
+
+    #[test]
+    fn read_long_length() {
+        let encoded = tlv(OCTET_STRING, &[0x2a; 300]);
+        assert_eq!(encoded[..4], [OCTET_STRING, 0x82, 0x01, 0x2c]);
+
+        let (tlv, size) = read(&encoded).unwrap().unwrap();
+
+        assert_eq!(tlv.content.len(), 300);
+        assert_eq!(size, 304);
+    }
+
+    #[test]
+    fn read_truncated() {
+        let encoded = tlv(OCTET_STRING, &[0x2a; 300]);
+
+        assert!(read(&[]).unwrap().is_none());
+        assert!(read(&encoded[..1]).unwrap().is_none());
+        // NOTE: the long-form length itself is incomplete.
+        assert!(read(&encoded[..3]).unwrap().is_none());
+        assert!(read(&encoded[..303]).unwrap().is_none());
+        assert!(Reader::new(&encoded[..303]).next().is_err());
+    }
+
+    #[test]
+    fn read_unsupported() {
+        assert!(read(&[0x1f, 0x01, 0x00]).is_err());
+        assert!(read(&[OCTET_STRING, 0x80]).is_err());
+        assert!(read(&[OCTET_STRING, 0x89, 0, 0, 0, 0, 0, 0, 0, 0, 1]).is_err());
+        assert!(
+            read(&[
+                OCTET_STRING,
+                0x88,
+                0xff,
+                0xff,
+                0xff,
+                0xff,
+                0xff,
+                0xff,
+                0xff,
+                0xff
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn integer_round_trip() {
+        for value in [0, 1, 127, 128, 255, 256, -1, -128, -129, i64::MAX, i64::MIN] {
+            let encoded = integer(INTEGER, value);
+            let (tlv, _) = read(&encoded).unwrap().unwrap();
+            assert_eq!(tlv.integer(), Ok(value));
+        }
+
+        assert_eq!(integer(INTEGER, 128), [INTEGER, 2, 0x00, 0x80]);
+        assert_eq!(integer(INTEGER, -128), [INTEGER, 1, 0x80]);
+    }
+
+    #[test]
+    fn integer_invalid() {
+        let empty = Tlv {
+            tag: INTEGER,
+            content: &[],
+        };
+        let long = Tlv {
+            tag: INTEGER,
+            content: &[1; 9],
+        };
+
+        assert!(empty.integer().is_err());
+        assert!(long.integer().is_err());
+    }
+}
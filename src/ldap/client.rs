@@ -0,0 +1,720 @@
+//! Std-blocking LDAP directory client.
+//!
+//! Wraps a connected stream to the LDAP server (e.g. a TLS stream for
+//! `ldaps://`) and pumps io-addressbook LDAP coroutines against it via
+//! [`LdapClient::run`]. The directory is read-only: every write
+//! operation fails with [`LdapClientError::ReadOnly`].
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Debug;
+use std::io::{Read, Write};
+
+use thiserror::Error;
+
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    backend::AddressbookBackend,
+    capabilities::Capabilities,
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
+        ldap::{
+            get::{LdapCardGet, LdapCardGetError},
+            list::{LdapCardList, LdapCardListError},
+            search::{LdapCardSearch, LdapCardSearchError},
+        },
+    },
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    ldap::{
+        convert::{addressbook_from_directory, capabilities},
+        coroutine::*,
+        directory::LdapDirectory,
+        send::{LdapSend, LdapSendError},
+    },
+};
+
+const READ_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Errors surfaced by [`LdapClient`] while running a coroutine.
+///
+/// One variant per shared-API LDAP coroutine, plus the I/O failures
+/// from the run loop, the bind failures and the rejected writes.
+#[derive(Debug, Error)]
+pub enum LdapClientError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Send(#[from] LdapSendError),
+    #[error("LDAP directories are read-only")]
+    ReadOnly,
+
+    #[error(transparent)]
+    CardGet(#[from] LdapCardGetError),
+    #[error(transparent)]
+    CardList(#[from] LdapCardListError),
+    #[error(transparent)]
+    CardSearch(#[from] LdapCardSearchError),
+}
+
+impl LdapClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        if let Some(err) = self.send_error() {
+            return err.kind();
+        }
+
+        match self {
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::ReadOnly => ErrorKind::Forbidden,
+            Self::CardGet(LdapCardGetError::AddressbookNotFound(_))
+            | Self::CardGet(LdapCardGetError::CardNotFound(_))
+            | Self::CardList(LdapCardListError::AddressbookNotFound(_))
+            | Self::CardSearch(LdapCardSearchError::AddressbookNotFound(_)) => ErrorKind::NotFound,
+            Self::CardGet(LdapCardGetError::InvalidCardId(_)) => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current tag of the card when the error is a failed
+    /// `if_match` precondition, which never happens on a read-only
+    /// directory.
+    pub fn current_etag(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the LDAP exchange error wrapped by a coroutine error.
+    pub(crate) fn send_error(&self) -> Option<&LdapSendError> {
+        match self {
+            Self::Send(err)
+            | Self::CardGet(LdapCardGetError::Send(err))
+            | Self::CardList(LdapCardListError::Send(err))
+            | Self::CardSearch(LdapCardSearchError::Send(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Connected stream a [`LdapClient`] talks to the server through,
+/// e.g. a TLS stream.
+pub trait LdapStream: Read + Write + Debug + Send {}
+
+impl<T: Read + Write + Debug + Send> LdapStream for T {}
+
+/// Std-blocking LDAP directory client built on a connected stream.
+#[derive(Debug)]
+pub struct LdapClient {
+    pub stream: Box<dyn LdapStream>,
+    pub directory: LdapDirectory,
+    message_id: i32,
+}
+
+impl LdapClient {
+    /// Builds a client reading `directory` through `stream`, bound
+    /// anonymously until [`LdapClient::bind`] is called.
+    pub fn new(stream: impl LdapStream + 'static, directory: LdapDirectory) -> Self {
+        Self {
+            stream: Box::new(stream),
+            directory,
+            message_id: 0,
+        }
+    }
+
+    /// Authenticates the connection with a simple bind as `dn`.
+    pub fn bind(&mut self, dn: &str, password: &str) -> Result<(), LdapClientError> {
+        let coroutine = LdapSend::bind(self.next_message_id(), dn, password);
+        self.run(coroutine)?;
+        Ok(())
+    }
+
+    /// Returns the id of the next request message, starting at 1.
+    fn next_message_id(&mut self) -> i32 {
+        self.message_id = self.message_id.checked_add(1).unwrap_or(1);
+        self.message_id
+    }
+
+    /// Pumps any standard-shape LDAP coroutine (`Yield = LdapYield`,
+    /// `Return = Result<T, E>`) against the stream until it
+    /// terminates.
+    pub fn run<C, T, E>(&mut self, mut coroutine: C) -> Result<T, LdapClientError>
+    where
+        C: LdapCoroutine<Yield = LdapYield, Return = Result<T, E>>,
+        LdapClientError: From<E>,
+    {
+        let mut buf = [0u8; READ_BUFFER_SIZE];
+        let mut arg: Option<&[u8]> = None;
+
+        loop {
+            match coroutine.resume(arg.take()) {
+                LdapCoroutineState::Complete(Ok(out)) => return Ok(out),
+                LdapCoroutineState::Complete(Err(err)) => return Err(err.into()),
+                LdapCoroutineState::Yielded(LdapYield::WantsRead) => {
+                    let n = self.stream.read(&mut buf)?;
+                    arg = Some(&buf[..n]);
+                }
+                LdapCoroutineState::Yielded(LdapYield::WantsWrite(bytes)) => {
+                    self.stream.write_all(&bytes)?;
+                }
+            }
+        }
+    }
+
+    /// Reports the capabilities of the directory.
+    pub fn capabilities(&self) -> Capabilities {
+        capabilities()
+    }
+
+    /// Lists the single addressbook of the directory, identified by
+    /// its base DN.
+    pub fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, LdapClientError> {
+        Ok(vec![addressbook_from_directory(&self.directory)])
+    }
+
+    /// Always fails: the directory is read-only.
+    pub fn create_addressbook(
+        &mut self,
+        _name: &str,
+        _description: Option<&str>,
+        _color: Option<&str>,
+    ) -> Result<String, LdapClientError> {
+        Err(LdapClientError::ReadOnly)
+    }
+
+    /// Always fails: the directory is read-only.
+    pub fn update_addressbook(
+        &mut self,
+        _id: &str,
+        _patch: AddressbookDiff,
+    ) -> Result<(), LdapClientError> {
+        Err(LdapClientError::ReadOnly)
+    }
+
+    /// Always fails: the directory is read-only.
+    pub fn delete_addressbook(&mut self, _id: &str) -> Result<(), LdapClientError> {
+        Err(LdapClientError::ReadOnly)
+    }
+
+    /// Lists the cards of `addressbook_id`, sorted by id, applying
+    /// 1-indexed pagination client-side.
+    pub fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, LdapClientError> {
+        let id = self.next_message_id();
+        let coroutine = LdapCardList::new(&self.directory, id, addressbook_id, page, page_size)?;
+        self.run(coroutine)
+    }
+
+    /// Lists the id and tag of every card in `addressbook_id`, sorted
+    /// by id.
+    ///
+    /// Tags are hashed from the vCard rendering of the entries, which
+    /// are fetched in full.
+    pub fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, LdapClientError> {
+        let refs = self
+            .list_cards(addressbook_id, None, None)?
+            .into_iter()
+            .map(|card| CardRef {
+                id: card.id,
+                href: None,
+                etag: card.etag,
+            })
+            .collect();
+        Ok(refs)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`,
+    /// narrowed down server-side by a substring filter.
+    pub fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, LdapClientError> {
+        let id = self.next_message_id();
+        let coroutine = LdapCardSearch::new(&self.directory, id, addressbook_id, filter)?;
+        self.run(coroutine)
+    }
+
+    /// Returns the full listing of `addressbook_id`: directories have
+    /// no sync token, so `sync_token` is ignored.
+    pub fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        _sync_token: Option<&str>,
+    ) -> Result<CardChanges, LdapClientError> {
+        let cards = self.list_cards(addressbook_id, None, None)?;
+
+        Ok(CardChanges {
+            cards,
+            deleted: Vec::new(),
+            sync_token: None,
+            full: true,
+        })
+    }
+
+    /// Fetches the entry `card_id`, a DN, from `addressbook_id`.
+    pub fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, LdapClientError> {
+        let id = self.next_message_id();
+        let coroutine = LdapCardGet::new(&self.directory, id, addressbook_id, card_id)?;
+        self.run(coroutine)
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id`, one
+    /// entry at a time. Missing entries end up in
+    /// [`CardBatch::missing`].
+    pub fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, LdapClientError> {
+        let mut batch = CardBatch::default();
+
+        for card_id in card_ids {
+            match self.get_card(addressbook_id, card_id) {
+                Ok(card) => batch.cards.push(card),
+                Err(LdapClientError::CardGet(LdapCardGetError::CardNotFound(_))) => {
+                    batch.missing.push(card_id.to_string());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(batch)
+    }
+
+    /// Always fails: the directory is read-only.
+    pub fn create_card(
+        &mut self,
+        _addressbook_id: &str,
+        _contents: Vec<u8>,
+    ) -> Result<CardRef, LdapClientError> {
+        Err(LdapClientError::ReadOnly)
+    }
+
+    /// Always fails: the directory is read-only.
+    pub fn create_card_with_options(
+        &mut self,
+        _addressbook_id: &str,
+        _contents: Vec<u8>,
+        _options: CardCreateOptions,
+    ) -> Result<CardRef, LdapClientError> {
+        Err(LdapClientError::ReadOnly)
+    }
+
+    /// Always fails: the directory is read-only.
+    pub fn update_card(
+        &mut self,
+        _addressbook_id: &str,
+        _card_id: &str,
+        _contents: Vec<u8>,
+        _if_match: Option<&str>,
+    ) -> Result<CardRef, LdapClientError> {
+        Err(LdapClientError::ReadOnly)
+    }
+
+    /// Always fails: the directory is read-only.
+    pub fn upsert_card(
+        &mut self,
+        _addressbook_id: &str,
+        _card_id: &str,
+        _contents: Vec<u8>,
+    ) -> Result<CardRef, LdapClientError> {
+        Err(LdapClientError::ReadOnly)
+    }
+
+    /// Always fails: the directory is read-only.
+    pub fn delete_card(
+        &mut self,
+        _addressbook_id: &str,
+        _card_id: &str,
+        _if_match: Option<&str>,
+    ) -> Result<(), LdapClientError> {
+        Err(LdapClientError::ReadOnly)
+    }
+}
+
+impl AddressbookBackend for LdapClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(LdapClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(LdapClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(LdapClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(LdapClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(LdapClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(LdapClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(LdapClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(LdapClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(LdapClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(LdapClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(LdapClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(LdapClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(LdapClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(LdapClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(LdapClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(LdapClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::VecDeque, sync::Arc};
+    use std::{io, sync::Mutex};
+
+    use super::*;
+    use crate::ldap::{
+        ber,
+        send::tests::{entry, message, result},
+    };
+
+    /// Bytes served per read, small enough to split messages.
+    const CHUNK_SIZE: usize = 7;
+
+    #[derive(Debug, Default)]
+    struct Exchange {
+        responses: VecDeque<Vec<u8>>,
+        pending: Vec<u8>,
+        requests: Vec<Vec<u8>>,
+    }
+
+    /// Stream answering every request written with the next queued
+    /// response messages.
+    ///
+    /// LDAP counterpart of `http::client::mock::MockStream`,
+    /// which is only built along with the HTTP backends.
+    #[derive(Clone, Debug, Default)]
+    struct MockStream(Arc<Mutex<Exchange>>);
+
+    impl MockStream {
+        fn respond(&self, messages: &[Vec<u8>]) -> &Self {
+            let mut exchange = self.0.lock().unwrap();
+            exchange.responses.push_back(messages.concat());
+            self
+        }
+
+        /// Returns the message id and protocol operation tag of each
+        /// request written so far.
+        fn requests(&self) -> Vec<(i64, u8)> {
+            let exchange = self.0.lock().unwrap();
+            exchange
+                .requests
+                .iter()
+                .map(|request| {
+                    let (tlv, _) = ber::read(request).unwrap().unwrap();
+                    let mut children = tlv.children();
+                    let id = children.next().unwrap().integer().unwrap();
+                    (id, children.next().unwrap().tag)
+                })
+                .collect()
+        }
+    }
+
+    impl Read for MockStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut exchange = self.0.lock().unwrap();
+            let n = buf.len().min(CHUNK_SIZE).min(exchange.pending.len());
+            buf[..n].copy_from_slice(&exchange.pending[..n]);
+            exchange.pending.drain(..n);
+            Ok(n)
+        }
+    }
+
+    impl Write for MockStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut exchange = self.0.lock().unwrap();
+            exchange.requests.push(buf.to_vec());
+            let response = exchange.responses.pop_front().unwrap_or_default();
+            exchange.pending.extend(response);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    const BASE_DN: &str = "ou=people,dc=example,dc=org";
+
+    fn client(stream: &MockStream) -> LdapClient {
+        LdapClient::new(stream.clone(), LdapDirectory::new(BASE_DN))
+    }
+
+    #[test]
+    fn bind_and_list_cards() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(&[message(1, result(0x61, 0, ""))])
+            .respond(&[
+                message(
+                    2,
+                    entry(
+                        "cn=bob,ou=people,dc=example,dc=org",
+                        &[("cn", &["Bob"]), ("mail", &["bob@example.org"])],
+                    ),
+                ),
+                message(
+                    2,
+                    ber::constructed(
+                        0x73,
+                        &[ber::tlv(ber::OCTET_STRING, b"ldap://other.example.org/")],
+                    ),
+                ),
+                message(
+                    2,
+                    entry(
+                        "cn=alice,ou=people,dc=example,dc=org",
+                        &[("cn", &["Alice"])],
+                    ),
+                ),
+                message(2, result(0x65, 0, "")),
+            ]);
+
+        client.bind("cn=admin,dc=example,dc=org", "secret").unwrap();
+        let cards = client.list_cards(BASE_DN, None, None).unwrap();
+
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].id, "cn=alice,ou=people,dc=example,dc=org");
+        assert_eq!(cards[0].addressbook_id, BASE_DN);
+        assert!(cards[1].etag.is_some());
+        assert_eq!(stream.requests(), [(1, 0x60), (2, 0x63)]);
+    }
+
+    #[test]
+    fn bind_invalid_credentials() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(&[message(1, result(0x61, 49, "invalid credentials"))]);
+
+        let err = client
+            .bind("cn=admin,dc=example,dc=org", "wrong")
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            LdapClientError::Send(LdapSendError::Result { code: 49, .. })
+        ));
+        assert_eq!(err.kind(), ErrorKind::Unauthorized);
+    }
+
+    #[test]
+    fn get_cards_missing() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(&[
+                message(
+                    1,
+                    entry(
+                        "cn=alice,ou=people,dc=example,dc=org",
+                        &[("cn", &["Alice"])],
+                    ),
+                ),
+                message(1, result(0x65, 0, "")),
+            ])
+            .respond(&[message(2, result(0x65, 32, "no such object"))]);
+
+        let batch = client
+            .get_cards(
+                BASE_DN,
+                &[
+                    "cn=alice,ou=people,dc=example,dc=org",
+                    "cn=carol,ou=people,dc=example,dc=org",
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(batch.cards.len(), 1);
+        assert_eq!(batch.missing, ["cn=carol,ou=people,dc=example,dc=org"]);
+    }
+
+    #[test]
+    fn list_cards_notice_of_disconnection() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(&[
+            message(
+                1,
+                entry(
+                    "cn=alice,ou=people,dc=example,dc=org",
+                    &[("cn", &["Alice"])],
+                ),
+            ),
+            message(0, result(0x78, 52, "server shutting down")),
+        ]);
+
+        let err = client.list_cards(BASE_DN, None, None).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Transport);
+    }
+
+    #[test]
+    fn list_cards_connection_closed() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(&[message(
+            1,
+            entry(
+                "cn=alice,ou=people,dc=example,dc=org",
+                &[("cn", &["Alice"])],
+            ),
+        )]);
+
+        let err = client.list_cards(BASE_DN, None, None).unwrap_err();
+
+        assert!(matches!(
+            err,
+            LdapClientError::CardList(LdapCardListError::Send(LdapSendError::UnexpectedEof))
+        ));
+    }
+}
@@ -0,0 +1,279 @@
+//! Conversions between LDAP entries and the shared types used by
+//! [`LdapClient`], plus the `From` impl that wraps it into the unified
+//! client's LDAP variant.
+//!
+//! [`LdapClient`]: crate::ldap::client::LdapClient
+
+use alloc::{format, string::ToString, vec, vec::Vec};
+
+use crate::{
+    addressbook::Addressbook,
+    base64,
+    capabilities::Capabilities,
+    card::{
+        Card, CardFilter, Contact, Email, FilterTest, Organization, Phone, Property,
+        StructuredName, tag::content_tag,
+    },
+    ldap::{directory::LdapDirectory, filter::LdapFilter, send::LdapEntry},
+};
+
+impl From<crate::ldap::client::LdapClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::ldap::client::LdapClient) -> Self {
+        Self::Ldap(client)
+    }
+}
+
+/// Attributes requested on every search.
+pub(crate) const ATTRIBUTES: [&str; 15] = [
+    "cn",
+    "displayName",
+    "sn",
+    "givenName",
+    "mail",
+    "telephoneNumber",
+    "mobile",
+    "homePhone",
+    "o",
+    "ou",
+    "title",
+    "description",
+    "labeledURI",
+    "jpegPhoto",
+    "entryUUID",
+];
+
+/// vCard properties mapped to the attributes holding their values.
+const FILTER_ATTRIBUTES: [(&str, &[&str]); 7] = [
+    ("FN", &["cn", "displayName"]),
+    ("N", &["sn", "givenName"]),
+    ("EMAIL", &["mail"]),
+    ("TEL", &["telephoneNumber", "mobile", "homePhone"]),
+    ("ORG", &["o", "ou"]),
+    ("TITLE", &["title"]),
+    ("NOTE", &["description"]),
+];
+
+/// Phone attributes with the vCard `TYPE` of their numbers.
+const PHONE_ATTRIBUTES: [(&str, &str); 3] = [
+    ("telephoneNumber", "work"),
+    ("mobile", "cell"),
+    ("homePhone", "home"),
+];
+
+/// Maps the directory to a shared [`Addressbook`].
+///
+/// Directories expose no change marker: addressbooks carry neither
+/// ctag nor sync token.
+pub(crate) fn addressbook_from_directory(directory: &LdapDirectory) -> Addressbook {
+    Addressbook {
+        id: directory.base_dn.clone(),
+        name: directory.name.clone(),
+        description: None,
+        color: None,
+        ctag: None,
+        sync_token: None,
+    }
+}
+
+/// Maps an entry to a shared [`Card`], its contents being the vCard
+/// rendering and its ETag the tag of these contents.
+pub(crate) fn card_from_entry(addressbook_id: &str, entry: &LdapEntry) -> Card {
+    let contents = contact_from_entry(entry).to_bytes();
+
+    Card {
+        id: entry.dn.clone(),
+        addressbook_id: addressbook_id.to_string(),
+        etag: Some(content_tag(&contents)),
+        contents,
+    }
+}
+
+/// Renders an inetOrgPerson entry as a vCard 4.0 contact.
+///
+/// The `UID` is the `entryUUID` when the server exposes it, the DN
+/// otherwise.
+pub(crate) fn contact_from_entry(entry: &LdapEntry) -> Contact {
+    let mut contact = Contact::new();
+
+    contact.uid = entry.text("entryUUID").or_else(|| Some(entry.dn.clone()));
+
+    let family = entry.text("sn").unwrap_or_default();
+    let given = entry.text("givenName").unwrap_or_default();
+
+    contact.formatted_name = entry
+        .text("displayName")
+        .or_else(|| entry.text("cn"))
+        .or_else(|| {
+            Some(
+                [given.as_str(), family.as_str()]
+                    .join(" ")
+                    .trim()
+                    .to_string(),
+            )
+        })
+        .filter(|name| !name.is_empty());
+
+    if !family.is_empty() || !given.is_empty() {
+        contact.name = Some(StructuredName {
+            family,
+            given,
+            ..Default::default()
+        });
+    }
+
+    contact.emails = entry
+        .texts("mail")
+        .into_iter()
+        .map(|address| Email {
+            address,
+            ..Default::default()
+        })
+        .collect();
+
+    for (attr, kind) in PHONE_ATTRIBUTES {
+        contact
+            .phones
+            .extend(entry.texts(attr).into_iter().map(|number| Phone {
+                number,
+                types: vec![kind.to_string()],
+                ..Default::default()
+            }));
+    }
+
+    let org = entry.text("o");
+    let units = entry.texts("ou");
+
+    if org.is_some() || !units.is_empty() {
+        contact.org = Some(Organization {
+            name: org.unwrap_or_default(),
+            units,
+        });
+    }
+
+    contact.title = entry.text("title");
+    contact.notes = entry.texts("description");
+
+    if let Some(photo) = entry.values("jpegPhoto").find(|photo| !photo.is_empty()) {
+        contact.photo = Some(format!("data:image/jpeg;base64,{}", base64::encode(photo)));
+    }
+
+    // NOTE: labeledURI values are a URI optionally followed by a
+    // space and a label
+    for uri in entry.texts("labeledURI") {
+        let uri = uri.split_whitespace().next().unwrap_or_default();
+
+        contact.properties.push(Property {
+            group: None,
+            name: "URL".to_string(),
+            params: Vec::new(),
+            value: uri.to_string(),
+        });
+    }
+
+    contact
+}
+
+/// Builds the filter selecting the directory entries that may match
+/// `filter`.
+///
+/// Substring assertions select a superset of the matching entries for
+/// every match type and collation, so the caller still confirms each
+/// card with [`CardFilter::matches`]. Filters the assertions cannot
+/// express (unmapped property, `is_not_defined`, negated or missing
+/// text match) only select the directory entries.
+pub(crate) fn search_filter(directory: &LdapDirectory, filter: &CardFilter) -> LdapFilter {
+    let Some(condition) = filter_operator(filter) else {
+        return directory.filter.clone();
+    };
+
+    LdapFilter::And(vec![directory.filter.clone(), condition])
+}
+
+fn filter_operator(filter: &CardFilter) -> Option<LdapFilter> {
+    if filter.props.is_empty() {
+        return None;
+    }
+
+    let mut conditions = Vec::new();
+
+    for prop in &filter.props {
+        let name = prop.name.to_ascii_uppercase();
+
+        let (_, attrs) = FILTER_ATTRIBUTES.iter().find(|(n, _)| *n == name)?;
+
+        if prop.is_not_defined
+            || prop.text_matches.is_empty()
+            || prop.text_matches.iter().any(|m| m.negate)
+        {
+            return None;
+        }
+
+        let matches = prop
+            .text_matches
+            .iter()
+            .map(|text_match| {
+                let attrs = attrs
+                    .iter()
+                    .map(|attr| LdapFilter::contains(attr, &text_match.text))
+                    .collect();
+                LdapFilter::Or(attrs)
+            })
+            .collect();
+
+        conditions.push(operator(prop.test, matches));
+    }
+
+    Some(operator(filter.test, conditions))
+}
+
+fn operator(test: FilterTest, mut conditions: Vec<LdapFilter>) -> LdapFilter {
+    if conditions.len() == 1 {
+        return conditions.remove(0);
+    }
+
+    match test {
+        FilterTest::AnyOf => LdapFilter::Or(conditions),
+        FilterTest::AllOf => LdapFilter::And(conditions),
+    }
+}
+
+/// Capabilities of an LDAP directory.
+///
+/// Cards do carry an entry-derived tag, but the directory is read-only
+/// so no `if_match` precondition is ever enforced: `etag` stays off.
+pub(crate) fn capabilities() -> Capabilities {
+    Capabilities {
+        etag: false,
+        ctag: false,
+        sync_token: false,
+        query: true,
+        multiget: false,
+        vcard_versions: vec!["4.0".to_string()],
+        max_resource_size: None,
+        writable_metadata: Vec::new(),
+    }
+}
+
+/// Sorts cards by id and applies 1-indexed pagination. `page_size =
+/// None` returns every card; `page_size = 0` or a page past the end
+/// returns an empty vector.
+pub(crate) fn paginate(
+    mut cards: Vec<Card>,
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> Vec<Card> {
+    cards.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let Some(size) = page_size else {
+        return cards;
+    };
+
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let page = page.unwrap_or(1).max(1);
+    let skip = ((page - 1) as usize).saturating_mul(size as usize);
+
+    cards.into_iter().skip(skip).take(size as usize).collect()
+}
@@ -0,0 +1,37 @@
+//! Coroutine protocol shared by the LDAP operations.
+//!
+//! Mirrors the HTTP one (`http::coroutine`): a coroutine yields
+//! [`LdapYield::WantsWrite`] with bytes to send and
+//! [`LdapYield::WantsRead`] when it needs more bytes from the stream,
+//! which the caller feeds back through [`LdapCoroutine::resume`]. An
+//! empty read signals the end of the stream.
+
+use alloc::vec::Vec;
+
+/// State returned by [`LdapCoroutine::resume`].
+#[derive(Debug)]
+pub enum LdapCoroutineState<Y, R> {
+    /// The coroutine needs I/O before it can go on.
+    Yielded(Y),
+    /// The coroutine terminated with its output.
+    Complete(R),
+}
+
+/// I/O request yielded by a LDAP coroutine.
+#[derive(Debug)]
+pub enum LdapYield {
+    /// Read bytes from the stream, then resume with them.
+    WantsRead,
+    /// Write these bytes to the stream, then resume with `None`.
+    WantsWrite(Vec<u8>),
+}
+
+/// I/O-free LDAP coroutine.
+pub trait LdapCoroutine {
+    type Yield;
+    type Return;
+
+    /// Resumes the coroutine with the bytes read since the last
+    /// [`LdapYield::WantsRead`], or `None` otherwise.
+    fn resume(&mut self, arg: Option<&[u8]>) -> LdapCoroutineState<Self::Yield, Self::Return>;
+}
@@ -0,0 +1,76 @@
+//! Configuration of the directory exposed as an addressbook.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::ldap::{filter::LdapFilter, send::LdapScope};
+
+/// Entries of an LDAP directory exposed as one read-only addressbook.
+///
+/// The base DN doubles as the id of the addressbook, and the DN of
+/// each entry as the id of its card.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LdapDirectory {
+    /// DN of the entry the search starts from, e.g.
+    /// `ou=people,dc=example,dc=com`.
+    pub base_dn: String,
+    /// Filter selecting the entries, `(objectClass=inetOrgPerson)` by
+    /// default. Build it with [`LdapFilter::parse`].
+    pub filter: LdapFilter,
+    /// Scope of the search, the whole subtree by default.
+    pub scope: LdapScope,
+    /// Name of the addressbook, the base DN by default.
+    pub name: String,
+}
+
+impl LdapDirectory {
+    /// Builds the configuration of the inetOrgPerson entries under
+    /// `base_dn`.
+    pub fn new(base_dn: impl ToString) -> Self {
+        let base_dn = base_dn.to_string();
+
+        Self {
+            filter: LdapFilter::Equality("objectClass".to_string(), b"inetOrgPerson".to_vec()),
+            scope: LdapScope::Subtree,
+            name: base_dn.clone(),
+            base_dn,
+        }
+    }
+
+    /// Tells whether `id` designates the addressbook, comparing DNs
+    /// case-insensitively.
+    pub fn is_addressbook(&self, id: &str) -> bool {
+        normalize_dn(id) == normalize_dn(&self.base_dn)
+    }
+
+    /// Tells whether the entry `dn` lies under the base DN.
+    pub fn contains(&self, dn: &str) -> bool {
+        let dn = normalize_dn(dn);
+        let base = normalize_dn(&self.base_dn);
+
+        if base.is_empty() {
+            return true;
+        }
+
+        match dn.strip_suffix(base.as_str()) {
+            Some("") => self.scope != LdapScope::OneLevel,
+            Some(rdns) => match self.scope {
+                LdapScope::Base => false,
+                LdapScope::OneLevel => rdns.strip_suffix(',').is_some_and(|r| !r.contains(',')),
+                LdapScope::Subtree => rdns.ends_with(','),
+            },
+            None => false,
+        }
+    }
+}
+
+/// Lower-cases `dn` and strips the spaces around its RDNs, which is
+/// enough to compare the DNs of one directory.
+fn normalize_dn(dn: &str) -> String {
+    dn.split(',')
+        .map(|rdn| rdn.trim().to_lowercase())
+        .collect::<Vec<_>>()
+        .join(",")
+}
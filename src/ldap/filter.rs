@@ -0,0 +1,396 @@
+//! LDAP search filters: the RFC 4515 string form parsed from the
+//! directory configuration, and their RFC 4511 §4.5.1.7 encoding.
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use thiserror::Error;
+
+use crate::ldap::ber;
+
+/// Error produced when parsing an RFC 4515 filter string.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Invalid LDAP filter `{filter}`: {reason}")]
+pub struct LdapFilterError {
+    pub filter: String,
+    pub reason: &'static str,
+}
+
+/// LDAP search filter.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LdapFilter {
+    And(Vec<LdapFilter>),
+    Or(Vec<LdapFilter>),
+    Not(Box<LdapFilter>),
+    Equality(String, Vec<u8>),
+    Substrings {
+        attr: String,
+        initial: Option<Vec<u8>>,
+        any: Vec<Vec<u8>>,
+        last: Option<Vec<u8>>,
+    },
+    GreaterOrEqual(String, Vec<u8>),
+    LessOrEqual(String, Vec<u8>),
+    Present(String),
+    Approx(String, Vec<u8>),
+}
+
+impl LdapFilter {
+    /// Parses an RFC 4515 filter string, e.g.
+    /// `(&(objectClass=inetOrgPerson)(mail=*))`. The enclosing
+    /// parentheses may be omitted around a single item.
+    pub fn parse(filter: &str) -> Result<Self, LdapFilterError> {
+        let err = |reason| LdapFilterError {
+            filter: filter.to_string(),
+            reason,
+        };
+
+        let trimmed = filter.trim();
+        let mut parser = Parser {
+            input: trimmed.as_bytes(),
+            pos: 0,
+        };
+
+        let parsed = if trimmed.starts_with('(') {
+            parser.filter()
+        } else {
+            parser.item(trimmed.len())
+        };
+
+        let parsed = parsed.map_err(err)?;
+
+        if parser.pos != parser.input.len() {
+            return Err(err("trailing characters"));
+        }
+
+        Ok(parsed)
+    }
+
+    /// Builds the filter matching values containing `text`, or any
+    /// value when `text` is empty.
+    pub fn contains(attr: &str, text: &str) -> Self {
+        if text.is_empty() {
+            return Self::Present(attr.to_string());
+        }
+
+        Self::Substrings {
+            attr: attr.to_string(),
+            initial: None,
+            any: Vec::from([text.as_bytes().to_vec()]),
+            last: None,
+        }
+    }
+
+    /// Encodes the filter as the `Filter` choice of a search request.
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::And(filters) => set(0xa0, filters),
+            Self::Or(filters) => set(0xa1, filters),
+            Self::Not(filter) => ber::tlv(0xa2, &filter.encode()),
+            Self::Equality(attr, value) => assertion(0xa3, attr, value),
+            Self::Substrings {
+                attr,
+                initial,
+                any,
+                last,
+            } => {
+                let mut parts = Vec::new();
+
+                if let Some(initial) = initial {
+                    parts.push(ber::tlv(0x80, initial));
+                }
+
+                for value in any {
+                    parts.push(ber::tlv(0x81, value));
+                }
+
+                if let Some(last) = last {
+                    parts.push(ber::tlv(0x82, last));
+                }
+
+                ber::constructed(
+                    0xa4,
+                    &[
+                        ber::tlv(ber::OCTET_STRING, attr.as_bytes()),
+                        ber::constructed(ber::SEQUENCE, &parts),
+                    ],
+                )
+            }
+            Self::GreaterOrEqual(attr, value) => assertion(0xa5, attr, value),
+            Self::LessOrEqual(attr, value) => assertion(0xa6, attr, value),
+            Self::Present(attr) => ber::tlv(0x87, attr.as_bytes()),
+            Self::Approx(attr, value) => assertion(0xa8, attr, value),
+        }
+    }
+}
+
+fn set(tag: u8, filters: &[LdapFilter]) -> Vec<u8> {
+    let filters: Vec<Vec<u8>> = filters.iter().map(LdapFilter::encode).collect();
+    ber::constructed(tag, &filters)
+}
+
+fn assertion(tag: u8, attr: &str, value: &[u8]) -> Vec<u8> {
+    ber::constructed(
+        tag,
+        &[
+            ber::tlv(ber::OCTET_STRING, attr.as_bytes()),
+            ber::tlv(ber::OCTET_STRING, value),
+        ],
+    )
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// Parses a parenthesized filter.
+    fn filter(&mut self) -> Result<LdapFilter, &'static str> {
+        self.expect(b'(')?;
+
+        let filter = match self.input.get(self.pos) {
+            Some(b'&') => {
+                self.pos += 1;
+                LdapFilter::And(self.list()?)
+            }
+            Some(b'|') => {
+                self.pos += 1;
+                LdapFilter::Or(self.list()?)
+            }
+            Some(b'!') => {
+                self.pos += 1;
+                LdapFilter::Not(Box::new(self.filter()?))
+            }
+            Some(_) => {
+                let end = self.input[self.pos..]
+                    .iter()
+                    .position(|b| *b == b')')
+                    .map(|n| self.pos + n)
+                    .ok_or("missing closing parenthesis")?;
+                self.item(end)?
+            }
+            None => return Err("unexpected end of filter"),
+        };
+
+        self.expect(b')')?;
+        Ok(filter)
+    }
+
+    /// Parses the filters of an `&` or `|` set.
+    fn list(&mut self) -> Result<Vec<LdapFilter>, &'static str> {
+        let mut filters = Vec::new();
+
+        while self.input.get(self.pos) == Some(&b'(') {
+            filters.push(self.filter()?);
+        }
+
+        Ok(filters)
+    }
+
+    /// Parses an `attr op value` item ending at `end`.
+    fn item(&mut self, end: usize) -> Result<LdapFilter, &'static str> {
+        let item = &self.input[self.pos..end];
+        self.pos = end;
+
+        let eq = item.iter().position(|b| *b == b'=').ok_or("missing `=`")?;
+
+        let (attr, op) = match eq.checked_sub(1).map(|i| item[i]) {
+            Some(op @ (b'~' | b'>' | b'<')) => (&item[..eq - 1], Some(op)),
+            _ => (&item[..eq], None),
+        };
+
+        let attr = core::str::from_utf8(attr)
+            .map_err(|_| "non UTF-8 attribute")?
+            .trim();
+
+        if attr.is_empty()
+            || !attr
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.' || b == b';')
+        {
+            return Err("invalid attribute description");
+        }
+
+        let attr = attr.to_string();
+        let value = &item[eq + 1..];
+
+        let filter = match op {
+            Some(b'~') => LdapFilter::Approx(attr, unescape(value)?),
+            Some(b'>') => LdapFilter::GreaterOrEqual(attr, unescape(value)?),
+            Some(b'<') => LdapFilter::LessOrEqual(attr, unescape(value)?),
+            _ if value == b"*" => LdapFilter::Present(attr),
+            _ if value.contains(&b'*') => {
+                let mut parts = value.split(|b| *b == b'*');
+                let initial = parts.next().filter(|p| !p.is_empty());
+                let mut any: Vec<&[u8]> = parts.collect();
+                let last = any.pop().filter(|p| !p.is_empty());
+
+                LdapFilter::Substrings {
+                    attr,
+                    initial: initial.map(unescape).transpose()?,
+                    any: any
+                        .into_iter()
+                        .filter(|p| !p.is_empty())
+                        .map(unescape)
+                        .collect::<Result<_, _>>()?,
+                    last: last.map(unescape).transpose()?,
+                }
+            }
+            _ => LdapFilter::Equality(attr, unescape(value)?),
+        };
+
+        Ok(filter)
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), &'static str> {
+        if self.input.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Ok(())
+        } else if byte == b'(' {
+            Err("missing opening parenthesis")
+        } else {
+            Err("missing closing parenthesis")
+        }
+    }
+}
+
+/// Decodes the `\XX` escapes of an assertion value.
+fn unescape(value: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut out = Vec::with_capacity(value.len());
+    let mut bytes = value.iter();
+
+    while let Some(byte) = bytes.next() {
+        if *byte != b'\\' {
+            out.push(*byte);
+            continue;
+        }
+
+        let hi = bytes.next().and_then(|b| (*b as char).to_digit(16));
+        let lo = bytes.next().and_then(|b| (*b as char).to_digit(16));
+
+        match (hi, lo) {
+            (Some(hi), Some(lo)) => out.push((hi * 16 + lo) as u8),
+            _ => return Err("invalid escape sequence"),
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn parse(filter: &str) -> LdapFilter {
+        LdapFilter::parse(filter).unwrap()
+    }
+
+    #[test]
+    fn parse_items() {
+        assert_eq!(
+            parse("(mail=alice@example.org)"),
+            LdapFilter::Equality("mail".into(), b"alice@example.org".to_vec())
+        );
+        assert_eq!(parse("cn=*"), LdapFilter::Present("cn".into()));
+        assert_eq!(
+            parse("(uidNumber>=1000)"),
+            LdapFilter::GreaterOrEqual("uidNumber".into(), b"1000".to_vec())
+        );
+        assert_eq!(
+            parse("(cn~=alise)"),
+            LdapFilter::Approx("cn".into(), b"alise".to_vec())
+        );
+        assert_eq!(
+            parse("(cn=al*c*e)"),
+            LdapFilter::Substrings {
+                attr: "cn".into(),
+                initial: Some(b"al".to_vec()),
+                any: vec![b"c".to_vec()],
+                last: Some(b"e".to_vec()),
+            }
+        );
+        assert_eq!(
+            parse("(cn=*ice)"),
+            LdapFilter::Substrings {
+                attr: "cn".into(),
+                initial: None,
+                any: vec![],
+                last: Some(b"ice".to_vec()),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_sets() {
+        assert_eq!(
+            parse("(&(objectClass=inetOrgPerson)(|(mail=*)(!(cn=x))))"),
+            LdapFilter::And(vec![
+                LdapFilter::Equality("objectClass".into(), b"inetOrgPerson".to_vec()),
+                LdapFilter::Or(vec![
+                    LdapFilter::Present("mail".into()),
+                    LdapFilter::Not(Box::new(LdapFilter::Equality("cn".into(), b"x".to_vec()))),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_escapes() {
+        assert_eq!(
+            parse(r"(cn=a\2ab\28c\29\5c\00)"),
+            LdapFilter::Equality("cn".into(), b"a*b(c)\\\0".to_vec())
+        );
+        assert_eq!(
+            parse(r"(cn=\2a*)"),
+            LdapFilter::Substrings {
+                attr: "cn".into(),
+                initial: Some(b"*".to_vec()),
+                any: vec![],
+                last: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let reason = |filter| LdapFilter::parse(filter).unwrap_err().reason;
+
+        assert_eq!(reason(r"(cn=\2)"), "invalid escape sequence");
+        assert_eq!(reason(r"(cn=\zz)"), "invalid escape sequence");
+        assert_eq!(reason("(cn=alice"), "missing closing parenthesis");
+        assert_eq!(reason("(&(cn=a)"), "missing closing parenthesis");
+        assert_eq!(reason("(cn)"), "missing `=`");
+        assert_eq!(reason("(=alice)"), "invalid attribute description");
+        assert_eq!(reason("(c n=alice)"), "invalid attribute description");
+        assert_eq!(reason("(cn=a)(cn=b)"), "trailing characters");
+    }
+
+    #[test]
+    fn encode() {
+        let filter = parse("(&(cn=a)(mail=*))");
+
+        assert_eq!(
+            filter.encode(),
+            [
+                0xa0, 0x0f, 0xa3, 0x07, 0x04, 0x02, b'c', b'n', 0x04, 0x01, b'a', 0x87, 0x04, b'm',
+                b'a', b'i', b'l',
+            ]
+        );
+        assert_eq!(
+            LdapFilter::contains("cn", "al").encode(),
+            [
+                0xa4, 0x0a, 0x04, 0x02, b'c', b'n', 0x30, 0x04, 0x81, 0x02, b'a', b'l'
+            ]
+        );
+        assert_eq!(
+            LdapFilter::contains("cn", ""),
+            LdapFilter::Present("cn".into())
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! LDAP read-only directory backend (RFC 4511): the I/O-free LDAPv3
+//! plumbing, the inetOrgPerson to vCard conversion and the std client.
+
+pub(crate) mod ber;
+pub mod client;
+pub mod convert;
+pub mod coroutine;
+pub mod directory;
+pub mod filter;
+pub mod send;
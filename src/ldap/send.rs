@@ -0,0 +1,545 @@
+//! I/O-free LDAPv3 exchange (RFC 4511).
+//!
+//! [`LdapSend`] writes one request message, then reads response
+//! messages until the one terminating the operation, collecting the
+//! search entries on the way. Failed operations surface their result
+//! code as [`LdapSendError::Result`].
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use log::{debug, trace};
+use thiserror::Error;
+
+use crate::{
+    error::ErrorKind,
+    ldap::{
+        ber::{self, Tlv},
+        coroutine::*,
+        filter::LdapFilter,
+    },
+};
+
+/// `success` result code.
+const SUCCESS: i64 = 0;
+
+/// `sizeLimitExceeded` result code: the server returned as many
+/// entries as it allows, which still makes a usable listing.
+const SIZE_LIMIT_EXCEEDED: i64 = 4;
+
+/// `noSuchObject` result code.
+pub(crate) const NO_SUCH_OBJECT: i64 = 32;
+
+/// Errors produced by [`LdapSend`].
+#[derive(Debug, Error)]
+pub enum LdapSendError {
+    #[error("LDAP operation failed with result code {code}: {message}")]
+    Result {
+        /// RFC 4511 §4.1.9 result code, e.g. 49 for
+        /// `invalidCredentials`.
+        code: i64,
+        message: String,
+    },
+    #[error("Invalid LDAP response: {0}")]
+    InvalidResponse(&'static str),
+    #[error("Connection closed before the end of the LDAP response")]
+    UnexpectedEof,
+}
+
+impl LdapSendError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Result { code, .. } => match *code {
+                NO_SUCH_OBJECT => ErrorKind::NotFound,
+                48 | 49 => ErrorKind::Unauthorized,
+                50 => ErrorKind::Forbidden,
+                17 | 18 | 21 | 34 => ErrorKind::InvalidData,
+                51 | 52 => ErrorKind::Transport,
+                _ => ErrorKind::Other,
+            },
+            Self::InvalidResponse(_) | Self::UnexpectedEof => ErrorKind::Transport,
+        }
+    }
+
+    /// Tells whether the server answered with the result `code`.
+    pub fn is_result(&self, code: i64) -> bool {
+        matches!(self, Self::Result { code: c, .. } if *c == code)
+    }
+}
+
+/// Scope of a search request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LdapScope {
+    /// The base entry only.
+    Base,
+    /// The direct children of the base entry.
+    OneLevel,
+    /// The base entry and all its descendants.
+    Subtree,
+}
+
+/// Entry returned by a search.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LdapEntry {
+    pub dn: String,
+    pub attributes: Vec<LdapAttribute>,
+}
+
+/// Attribute of an [`LdapEntry`] with its raw values.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LdapAttribute {
+    /// Attribute description, e.g. `mail` or `jpegPhoto;binary`.
+    pub name: String,
+    pub values: Vec<Vec<u8>>,
+}
+
+impl LdapEntry {
+    /// Returns the raw values of the attribute `name`, matched
+    /// case-insensitively and regardless of its options.
+    pub fn values(&self, name: &str) -> impl Iterator<Item = &[u8]> {
+        self.attributes
+            .iter()
+            .filter(move |attr| {
+                let base = attr.name.split(';').next().unwrap_or_default();
+                base.eq_ignore_ascii_case(name)
+            })
+            .flat_map(|attr| attr.values.iter().map(Vec::as_slice))
+    }
+
+    /// Returns the non-empty UTF-8 values of the attribute `name`.
+    pub fn texts(&self, name: &str) -> Vec<String> {
+        self.values(name)
+            .filter_map(|value| core::str::from_utf8(value).ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Returns the first non-empty UTF-8 value of the attribute
+    /// `name`.
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.texts(name).into_iter().next()
+    }
+}
+
+/// I/O-free coroutine sending one LDAP request.
+///
+/// On completion returns the entries of a search (none for a bind).
+pub struct LdapSend {
+    message_id: i32,
+    request: Option<Vec<u8>>,
+    response: Vec<u8>,
+    position: usize,
+    entries: Vec<LdapEntry>,
+}
+
+impl LdapSend {
+    /// Builds the coroutine sending a simple bind as `dn`.
+    pub fn bind(message_id: i32, dn: &str, password: &str) -> Self {
+        trace!("prepare ldap bind as {dn}");
+
+        let op = ber::constructed(
+            0x60,
+            &[
+                ber::integer(ber::INTEGER, 3),
+                ber::tlv(ber::OCTET_STRING, dn.as_bytes()),
+                ber::tlv(0x80, password.as_bytes()),
+            ],
+        );
+
+        Self::new(message_id, op)
+    }
+
+    /// Builds the coroutine searching the entries under `base` within
+    /// `scope` matching `filter`, requesting `attributes`.
+    pub fn search(
+        message_id: i32,
+        base: &str,
+        scope: LdapScope,
+        filter: &LdapFilter,
+        attributes: &[&str],
+    ) -> Self {
+        trace!("prepare ldap search under {base}");
+
+        let scope = match scope {
+            LdapScope::Base => 0,
+            LdapScope::OneLevel => 1,
+            LdapScope::Subtree => 2,
+        };
+
+        let attributes: Vec<Vec<u8>> = attributes
+            .iter()
+            .map(|attr| ber::tlv(ber::OCTET_STRING, attr.as_bytes()))
+            .collect();
+
+        let op = ber::constructed(
+            0x63,
+            &[
+                ber::tlv(ber::OCTET_STRING, base.as_bytes()),
+                ber::integer(ber::ENUMERATED, scope),
+                // NOTE: aliases are never dereferenced
+                ber::integer(ber::ENUMERATED, 0),
+                ber::integer(ber::INTEGER, 0),
+                ber::integer(ber::INTEGER, 0),
+                ber::boolean(false),
+                filter.encode(),
+                ber::constructed(ber::SEQUENCE, &attributes),
+            ],
+        );
+
+        Self::new(message_id, op)
+    }
+
+    fn new(message_id: i32, op: Vec<u8>) -> Self {
+        let request = ber::constructed(
+            ber::SEQUENCE,
+            &[ber::integer(ber::INTEGER, message_id as i64), op],
+        );
+
+        Self {
+            message_id,
+            request: Some(request),
+            response: Vec::new(),
+            position: 0,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl LdapCoroutine for LdapSend {
+    type Yield = LdapYield;
+    type Return = Result<Vec<LdapEntry>, LdapSendError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> LdapCoroutineState<Self::Yield, Self::Return> {
+        if let Some(request) = self.request.take() {
+            return LdapCoroutineState::Yielded(LdapYield::WantsWrite(request));
+        }
+
+        match arg {
+            None => return LdapCoroutineState::Yielded(LdapYield::WantsRead),
+            Some([]) => return LdapCoroutineState::Complete(Err(LdapSendError::UnexpectedEof)),
+            Some(bytes) => self.response.extend_from_slice(bytes),
+        }
+
+        loop {
+            let (message, size) = match ber::read(&self.response[self.position..]) {
+                Ok(Some(read)) => read,
+                Ok(None) => {
+                    self.response.drain(..self.position);
+                    self.position = 0;
+                    return LdapCoroutineState::Yielded(LdapYield::WantsRead);
+                }
+                Err(err) => {
+                    let err = LdapSendError::InvalidResponse(err);
+                    return LdapCoroutineState::Complete(Err(err));
+                }
+            };
+
+            match handle(self.message_id, &mut self.entries, message) {
+                Ok(true) => {
+                    let entries = core::mem::take(&mut self.entries);
+                    return LdapCoroutineState::Complete(Ok(entries));
+                }
+                Ok(false) => self.position += size,
+                Err(err) => return LdapCoroutineState::Complete(Err(err)),
+            }
+        }
+    }
+}
+
+/// Handles one response message to `message_id`, collecting search
+/// entries into `entries`. Returns whether the operation terminated.
+fn handle(
+    message_id: i32,
+    entries: &mut Vec<LdapEntry>,
+    message: Tlv<'_>,
+) -> Result<bool, LdapSendError> {
+    let parse = || -> Result<_, &'static str> {
+        let mut children = message.expect(ber::SEQUENCE)?.children();
+        let id = children.next()?.expect(ber::INTEGER)?.integer()?;
+        Ok((id, children.next()?))
+    };
+
+    let (id, op) = parse().map_err(LdapSendError::InvalidResponse)?;
+
+    // NOTE: message 0 carries unsolicited notifications, the notice
+    // of disconnection being the only one defined
+    if id == 0 && op.tag == 0x78 {
+        let (code, message) = parse_result(op)?;
+        return Err(LdapSendError::Result { code, message });
+    }
+
+    if id != message_id as i64 {
+        debug!("skip ldap response to message {id}");
+        return Ok(false);
+    }
+
+    match op.tag {
+        0x64 => {
+            entries.push(parse_entry(op)?);
+            Ok(false)
+        }
+        0x73 => {
+            debug!("skip ldap search result reference");
+            Ok(false)
+        }
+        0x61 | 0x65 => match parse_result(op)? {
+            (SUCCESS, _) => Ok(true),
+            (SIZE_LIMIT_EXCEEDED, _) if op.tag == 0x65 => {
+                debug!("ldap size limit exceeded, keeping the returned entries");
+                Ok(true)
+            }
+            (code, message) => Err(LdapSendError::Result { code, message }),
+        },
+        _ => Err(LdapSendError::InvalidResponse(
+            "unexpected protocol operation",
+        )),
+    }
+}
+
+/// Parses the `LDAPResult` components of a response: result code and
+/// diagnostic message.
+fn parse_result(op: Tlv<'_>) -> Result<(i64, String), LdapSendError> {
+    let parse = || -> Result<_, &'static str> {
+        let mut children = op.children();
+        let code = children.next()?.expect(ber::ENUMERATED)?.integer()?;
+        let _matched_dn = children.next()?;
+        let message = children.next()?.string()?;
+        Ok((code, message))
+    };
+
+    parse().map_err(LdapSendError::InvalidResponse)
+}
+
+/// Parses a `SearchResultEntry`.
+fn parse_entry(op: Tlv<'_>) -> Result<LdapEntry, LdapSendError> {
+    let parse = || -> Result<_, &'static str> {
+        let mut children = op.children();
+        let dn = children.next()?.expect(ber::OCTET_STRING)?.string()?;
+        let mut list = children.next()?.expect(ber::SEQUENCE)?.children();
+        let mut attributes = Vec::new();
+
+        while !list.is_empty() {
+            let mut attr = list.next()?.expect(ber::SEQUENCE)?.children();
+            let name = attr.next()?.expect(ber::OCTET_STRING)?.string()?;
+            let mut vals = attr.next()?.expect(0x31)?.children();
+            let mut values = Vec::new();
+
+            while !vals.is_empty() {
+                values.push(vals.next()?.content.to_vec());
+            }
+
+            attributes.push(LdapAttribute { name, values });
+        }
+
+        Ok(LdapEntry { dn, attributes })
+    };
+
+    parse().map_err(LdapSendError::InvalidResponse)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Encodes an `LDAPMessage` carrying `op`.
+    pub(crate) fn message(id: i64, op: Vec<u8>) -> Vec<u8> {
+        ber::constructed(ber::SEQUENCE, &[ber::integer(ber::INTEGER, id), op])
+    }
+
+    /// Encodes an `LDAPResult` tagged `tag`.
+    pub(crate) fn result(tag: u8, code: i64, message: &str) -> Vec<u8> {
+        ber::constructed(
+            tag,
+            &[
+                ber::integer(ber::ENUMERATED, code),
+                ber::tlv(ber::OCTET_STRING, b""),
+                ber::tlv(ber::OCTET_STRING, message.as_bytes()),
+            ],
+        )
+    }
+
+    /// Encodes a `SearchResultEntry`.
+    pub(crate) fn entry(dn: &str, attributes: &[(&str, &[&str])]) -> Vec<u8> {
+        let attributes: Vec<Vec<u8>> = attributes
+            .iter()
+            .map(|(name, values)| {
+                let values: Vec<Vec<u8>> = values
+                    .iter()
+                    .map(|value| ber::tlv(ber::OCTET_STRING, value.as_bytes()))
+                    .collect();
+
+                ber::constructed(
+                    ber::SEQUENCE,
+                    &[
+                        ber::tlv(ber::OCTET_STRING, name.as_bytes()),
+                        ber::constructed(0x31, &values),
+                    ],
+                )
+            })
+            .collect();
+
+        ber::constructed(
+            0x64,
+            &[
+                ber::tlv(ber::OCTET_STRING, dn.as_bytes()),
+                ber::constructed(ber::SEQUENCE, &attributes),
+            ],
+        )
+    }
+
+    fn handle_one(
+        message_id: i32,
+        entries: &mut Vec<LdapEntry>,
+        bytes: &[u8],
+    ) -> Result<bool, LdapSendError> {
+        let (tlv, _) = ber::read(bytes).unwrap().unwrap();
+        handle(message_id, entries, tlv)
+    }
+
+    #[test]
+    fn handle_search_result_entry() {
+        let mut entries = Vec::new();
+        let bytes = message(
+            2,
+            entry(
+                "cn=alice,dc=example",
+                &[("cn", &["Alice"]), ("mail;x-work", &["a@example.org", " "])],
+            ),
+        );
+
+        assert!(!handle_one(2, &mut entries, &bytes).unwrap());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].dn, "cn=alice,dc=example");
+        assert_eq!(entries[0].text("CN").as_deref(), Some("Alice"));
+        assert_eq!(entries[0].texts("mail"), ["a@example.org"]);
+    }
+
+    #[test]
+    fn handle_search_result_done() {
+        let mut entries = Vec::new();
+
+        let done = message(2, result(0x65, SUCCESS, ""));
+        assert!(handle_one(2, &mut entries, &done).unwrap());
+
+        let limited = message(2, result(0x65, SIZE_LIMIT_EXCEEDED, ""));
+        assert!(handle_one(2, &mut entries, &limited).unwrap());
+
+        // NOTE: a size limit on a bind is a plain failure.
+        let bind = message(2, result(0x61, SIZE_LIMIT_EXCEEDED, ""));
+        assert!(handle_one(2, &mut entries, &bind).unwrap_err().is_result(4));
+    }
+
+    #[test]
+    fn handle_referral() {
+        let mut entries = Vec::new();
+
+        let reference = message(
+            2,
+            ber::constructed(
+                0x73,
+                &[ber::tlv(ber::OCTET_STRING, b"ldap://other.example/")],
+            ),
+        );
+        assert!(!handle_one(2, &mut entries, &reference).unwrap());
+        assert!(entries.is_empty());
+
+        // NOTE: referrals are not chased, the operation fails.
+        let referral = message(2, result(0x65, 10, "referral"));
+        let err = handle_one(2, &mut entries, &referral).unwrap_err();
+        assert!(err.is_result(10));
+        assert_eq!(err.kind(), ErrorKind::Other);
+    }
+
+    #[test]
+    fn handle_notice_of_disconnection() {
+        let mut entries = Vec::new();
+        let notice = message(0, result(0x78, 52, "server shutting down"));
+
+        let err = handle_one(2, &mut entries, &notice).unwrap_err();
+
+        assert!(matches!(
+            &err,
+            LdapSendError::Result { code: 52, message } if message == "server shutting down"
+        ));
+        assert_eq!(err.kind(), ErrorKind::Transport);
+    }
+
+    #[test]
+    fn handle_other_message() {
+        let mut entries = Vec::new();
+
+        let other = message(1, result(0x65, 32, ""));
+        assert!(!handle_one(2, &mut entries, &other).unwrap());
+
+        let unknown = message(2, result(0x67, SUCCESS, ""));
+        assert!(matches!(
+            handle_one(2, &mut entries, &unknown),
+            Err(LdapSendError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn search_over_split_reads() {
+        let filter = LdapFilter::Present("cn".into());
+        let mut send = LdapSend::search(3, "dc=example", LdapScope::Subtree, &filter, &["cn"]);
+
+        let request = match send.resume(None) {
+            LdapCoroutineState::Yielded(LdapYield::WantsWrite(bytes)) => bytes,
+            state => panic!("unexpected state {state:?}"),
+        };
+        let (tlv, _) = ber::read(&request).unwrap().unwrap();
+        let mut children = tlv.children();
+        assert_eq!(children.next().unwrap().integer(), Ok(3));
+        assert_eq!(children.next().unwrap().tag, 0x63);
+
+        assert!(matches!(
+            send.resume(None),
+            LdapCoroutineState::Yielded(LdapYield::WantsRead)
+        ));
+
+        let response = [
+            message(3, entry("cn=alice,dc=example", &[("cn", &["Alice"])])),
+            message(3, entry("cn=bob,dc=example", &[("cn", &["Bob"])])),
+            message(3, result(0x65, SUCCESS, "")),
+        ]
+        .concat();
+
+        let mut chunks = response.chunks(5);
+
+        let entries = loop {
+            match send.resume(Some(chunks.next().unwrap())) {
+                LdapCoroutineState::Yielded(LdapYield::WantsRead) => continue,
+                LdapCoroutineState::Complete(result) => break result.unwrap(),
+                state => panic!("unexpected state {state:?}"),
+            }
+        };
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].dn, "cn=bob,dc=example");
+        assert_eq!(entries[1].attributes[0].values, [b"Bob".to_vec()]);
+    }
+
+    #[test]
+    fn search_unexpected_eof() {
+        let filter = LdapFilter::Present("cn".into());
+        let mut send = LdapSend::search(3, "dc=example", LdapScope::Subtree, &filter, &["cn"]);
+
+        send.resume(None);
+        send.resume(None);
+
+        let partial = message(3, result(0x65, SUCCESS, ""));
+        assert!(matches!(
+            send.resume(Some(&partial[..4])),
+            LdapCoroutineState::Yielded(LdapYield::WantsRead)
+        ));
+        assert!(matches!(
+            send.resume(Some(&[])),
+            LdapCoroutineState::Complete(Err(LdapSendError::UnexpectedEof))
+        ));
+    }
+}
@@ -6,11 +6,43 @@ extern crate alloc;
 #[cfg(feature = "client")]
 extern crate std;
 
+#[cfg(feature = "abook")]
+pub mod abook;
 pub mod addressbook;
+#[cfg(feature = "aggregate")]
+pub mod aggregate;
+#[cfg(feature = "client")]
+pub mod backend;
+#[cfg(any(feature = "jmap", feature = "ldap"))]
+pub(crate) mod base64;
+pub mod capabilities;
 pub mod card;
 #[cfg(feature = "client")]
-#[cfg(any(feature = "vdir", feature = "webdav"))]
 pub mod client;
+#[cfg(feature = "tokio")]
+#[cfg(any(feature = "vdir", feature = "webdav"))]
+pub mod client_async;
+pub mod error;
+#[cfg(feature = "google")]
+pub mod google;
+#[cfg(any(feature = "jmap", feature = "google", feature = "msgraph"))]
+pub mod http;
+#[cfg(feature = "jmap")]
+pub mod jmap;
+#[cfg(feature = "ldap")]
+pub mod ldap;
+#[cfg(feature = "memory")]
+pub mod memory;
+#[cfg(feature = "msgraph")]
+pub mod msgraph;
+#[cfg(feature = "mutt-alias")]
+pub mod mutt_alias;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "sync")]
+pub mod sync;
+#[cfg(feature = "vcf-file")]
+pub mod vcf_file;
 #[cfg(feature = "vdir")]
 pub mod vdir;
 #[cfg(feature = "webdav")]
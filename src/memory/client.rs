@@ -0,0 +1,838 @@
+//! In-memory addressbook client.
+//!
+//! [`InMemoryClient`] keeps addressbooks and raw cards in
+//! [`BTreeMap`]s, with no I/O at all. It follows the semantics of the
+//! other backends closely enough to unit-test code written against
+//! [`AddressbookClientStd`]: tags synthesized from the card contents,
+//! `if_match` preconditions, create-only `create_card` and UID-derived
+//! ids. It can be seeded from and dumped to `.vcf` data via
+//! [`InMemoryClient::import_vcf`] and [`InMemoryClient::export_vcf`].
+//!
+//! [`AddressbookClientStd`]: crate::client::AddressbookClientStd
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use thiserror::Error;
+
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    backend::AddressbookBackend,
+    capabilities::{AddressbookField, Capabilities},
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef, join_vcf, split_vcf,
+        tag::{TagHasher, content_tag, tag_matches},
+        uid::{card_id_from_uid, find_uid, fresh_card_id, inject_uid},
+    },
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    memory::convert::paginate,
+};
+
+/// Errors surfaced by [`InMemoryClient`].
+#[derive(Debug, Error)]
+pub enum InMemoryClientError {
+    #[error("Failed to gather randomness for new card id: {0}")]
+    Random(getrandom::Error),
+    #[error("Invalid addressbook name")]
+    InvalidName,
+    #[error("Addressbook `{0}` already exists")]
+    AddressbookAlreadyExists(String),
+    #[error("Addressbook `{0}` not found")]
+    AddressbookNotFound(String),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card body is empty")]
+    EmptyBody,
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{0}` already exists")]
+    CardAlreadyExists(String),
+    #[error("Card `{card_id}` changed (current tag: {actual})")]
+    PreconditionFailed { card_id: String, actual: String },
+}
+
+impl InMemoryClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::AddressbookNotFound(_) | Self::CardNotFound(_) => ErrorKind::NotFound,
+            Self::AddressbookAlreadyExists(_)
+            | Self::CardAlreadyExists(_)
+            | Self::PreconditionFailed { .. } => ErrorKind::Conflict,
+            Self::InvalidName | Self::InvalidCardId(_) | Self::EmptyBody => ErrorKind::InvalidData,
+            Self::Random(_) => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current tag of the card when the error is a failed
+    /// `if_match` precondition.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::PreconditionFailed { actual, .. } => Some(actual),
+            _ => None,
+        }
+    }
+}
+
+/// Addressbook metadata and cards (id to raw vCard bytes).
+#[derive(Clone, Debug, Default)]
+struct Entry {
+    addressbook: Addressbook,
+    cards: BTreeMap<String, Vec<u8>>,
+}
+
+impl Entry {
+    /// Returns the addressbook with its synthesized `ctag`, hashed from
+    /// the metadata and every card: any change bumps it.
+    fn addressbook(&self) -> Addressbook {
+        let mut hasher = TagHasher::default();
+        hasher.write(self.addressbook.name.as_bytes());
+
+        for field in [&self.addressbook.description, &self.addressbook.color] {
+            hasher.write(&[field.is_some() as u8]);
+            hasher.write(field.as_deref().unwrap_or_default().as_bytes());
+        }
+
+        for (id, contents) in &self.cards {
+            hasher.write(id.as_bytes());
+            hasher.write(content_tag(contents).as_bytes());
+        }
+
+        Addressbook {
+            ctag: Some(hasher.finish()),
+            ..self.addressbook.clone()
+        }
+    }
+
+    /// Returns the card `id` as a shared [`Card`].
+    fn card(&self, id: &str) -> Option<Card> {
+        let contents = self.cards.get(id)?;
+
+        Some(Card {
+            id: id.to_string(),
+            addressbook_id: self.addressbook.id.clone(),
+            etag: Some(content_tag(contents)),
+            contents: contents.clone(),
+        })
+    }
+}
+
+/// In-memory addressbook client.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryClient {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl InMemoryClient {
+    /// Builds an empty client, with no addressbook.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the static capabilities of the in-memory backend.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            etag: true,
+            ctag: true,
+            sync_token: false,
+            query: false,
+            multiget: true,
+            vcard_versions: Vec::from(["3.0".to_string(), "4.0".to_string()]),
+            max_resource_size: None,
+            writable_metadata: AddressbookField::ALL.to_vec(),
+        }
+    }
+
+    /// Lists every addressbook, sorted by name, each with its
+    /// synthesized `ctag`.
+    pub fn list_addressbooks(&self) -> Result<Vec<Addressbook>, InMemoryClientError> {
+        let mut addressbooks: Vec<_> = self.entries.values().map(Entry::addressbook).collect();
+        addressbooks.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(addressbooks)
+    }
+
+    /// Creates an addressbook named `name`, also used as its id.
+    /// Returns the new addressbook id.
+    pub fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, InMemoryClientError> {
+        if name.is_empty() {
+            return Err(InMemoryClientError::InvalidName);
+        }
+
+        if self.entries.contains_key(name) {
+            return Err(InMemoryClientError::AddressbookAlreadyExists(
+                name.to_string(),
+            ));
+        }
+
+        let addressbook = Addressbook {
+            id: name.to_string(),
+            name: name.to_string(),
+            description: description.map(str::to_string),
+            color: color.map(str::to_string),
+            ctag: None,
+            sync_token: None,
+        };
+
+        let entry = Entry {
+            addressbook,
+            cards: BTreeMap::new(),
+        };
+
+        self.entries.insert(name.to_string(), entry);
+        Ok(name.to_string())
+    }
+
+    /// Applies `patch` to the addressbook identified by `id`.
+    pub fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), InMemoryClientError> {
+        let addressbook = &mut self.entry_mut(id)?.addressbook;
+
+        if let Some(name) = patch.name {
+            addressbook.name = name;
+        }
+
+        if let Some(description) = patch.description {
+            addressbook.description = description;
+        }
+
+        if let Some(color) = patch.color {
+            addressbook.color = color;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the addressbook identified by `id` and its cards.
+    pub fn delete_addressbook(&mut self, id: &str) -> Result<(), InMemoryClientError> {
+        match self.entries.remove(id) {
+            Some(_) => Ok(()),
+            None => Err(InMemoryClientError::AddressbookNotFound(id.to_string())),
+        }
+    }
+
+    /// Lists cards inside `addressbook_id`, sorted by id, applying
+    /// 1-indexed pagination.
+    pub fn list_cards(
+        &self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, InMemoryClientError> {
+        let entry = self.entry(addressbook_id)?;
+        let cards = entry.cards.keys().filter_map(|id| entry.card(id)).collect();
+        Ok(paginate(cards, page, page_size))
+    }
+
+    /// Lists the id and synthesized tag of every card in
+    /// `addressbook_id`, sorted by id.
+    pub fn list_card_refs(
+        &self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, InMemoryClientError> {
+        let entry = self.entry(addressbook_id)?;
+
+        let refs = entry
+            .cards
+            .iter()
+            .map(|(id, contents)| CardRef {
+                id: id.clone(),
+                href: None,
+                etag: Some(content_tag(contents)),
+            })
+            .collect();
+
+        Ok(refs)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`,
+    /// evaluated locally over every card.
+    pub fn search_cards(
+        &self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, InMemoryClientError> {
+        let mut cards = self.list_cards(addressbook_id, None, None)?;
+        cards.retain(|card| filter.matches(card));
+        Ok(cards)
+    }
+
+    /// Returns every card of `addressbook_id` as a full
+    /// [`CardChanges`] listing: there is no sync token, so
+    /// `sync_token` is ignored.
+    pub fn sync_cards(
+        &self,
+        addressbook_id: &str,
+        _sync_token: Option<&str>,
+    ) -> Result<CardChanges, InMemoryClientError> {
+        Ok(CardChanges {
+            cards: self.list_cards(addressbook_id, None, None)?,
+            deleted: Vec::new(),
+            sync_token: None,
+            full: true,
+        })
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub fn get_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, InMemoryClientError> {
+        self.entry(addressbook_id)?
+            .card(card_id)
+            .ok_or_else(|| InMemoryClientError::CardNotFound(card_id.to_string()))
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id`. Ids with
+    /// no card end up in [`CardBatch::missing`].
+    pub fn get_cards(
+        &self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, InMemoryClientError> {
+        let entry = self.entry(addressbook_id)?;
+        let mut batch = CardBatch::default();
+
+        for id in card_ids {
+            match entry.card(id) {
+                Some(card) => batch.cards.push(card),
+                None => batch.missing.push(id.to_string()),
+            }
+        }
+
+        batch.cards.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(batch)
+    }
+
+    /// Adds a new vCard to `addressbook_id` with the default
+    /// [`CardCreateOptions`]. Returns its assigned id and synthesized
+    /// tag.
+    pub fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, InMemoryClientError> {
+        self.create_card_with_options(addressbook_id, contents, CardCreateOptions::default())
+    }
+
+    /// Adds a new vCard to `addressbook_id` with the given `options`,
+    /// failing when a card with the same id exists. Returns its
+    /// assigned id and synthesized tag.
+    pub fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        mut contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, InMemoryClientError> {
+        if contents.is_empty() {
+            return Err(InMemoryClientError::EmptyBody);
+        }
+
+        let fresh_card_id = || fresh_card_id().map_err(InMemoryClientError::Random);
+
+        let id = match options.id_from_uid.then(|| find_uid(&contents)) {
+            Some(Some(Some(uid))) => card_id_from_uid(&uid),
+            Some(Some(None)) => {
+                let uuid = fresh_card_id()?;
+                contents = inject_uid(&contents, &format!("urn:uuid:{uuid}"));
+                uuid
+            }
+            Some(None) | None => fresh_card_id()?,
+        };
+
+        let entry = self.entry_mut(addressbook_id)?;
+
+        if entry.cards.contains_key(&id) {
+            return Err(InMemoryClientError::CardAlreadyExists(id));
+        }
+
+        let etag = content_tag(&contents);
+        entry.cards.insert(id.clone(), contents);
+
+        Ok(CardRef {
+            id,
+            href: None,
+            etag: Some(etag),
+        })
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id`, gating on
+    /// `if_match` when present. Without `if_match`, a missing card is
+    /// created. Returns the card id and the tag of the new contents.
+    pub fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, InMemoryClientError> {
+        if card_id.is_empty() {
+            return Err(InMemoryClientError::InvalidCardId(String::new()));
+        }
+
+        if contents.is_empty() {
+            return Err(InMemoryClientError::EmptyBody);
+        }
+
+        let entry = self.entry_mut(addressbook_id)?;
+
+        if let Some(if_match) = if_match {
+            check_precondition(entry, card_id, if_match)?;
+        }
+
+        let etag = content_tag(&contents);
+        entry.cards.insert(card_id.to_string(), contents);
+
+        Ok(CardRef {
+            id: card_id.to_string(),
+            href: None,
+            etag: Some(etag),
+        })
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating the card or overwriting it unconditionally.
+    pub fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, InMemoryClientError> {
+        self.update_card(addressbook_id, card_id, contents, None)
+    }
+
+    /// Removes `card_id` from `addressbook_id`, gating on `if_match`
+    /// when present.
+    pub fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), InMemoryClientError> {
+        let entry = self.entry_mut(addressbook_id)?;
+
+        if let Some(if_match) = if_match {
+            check_precondition(entry, card_id, if_match)?;
+        }
+
+        match entry.cards.remove(card_id) {
+            Some(_) => Ok(()),
+            None => Err(InMemoryClientError::CardNotFound(card_id.to_string())),
+        }
+    }
+
+    /// Seeds `addressbook_id` with every vCard of the multi-card
+    /// `.vcf` `data`, creating the addressbook when missing.
+    ///
+    /// Card ids derive from the vCard `UID`, or from the tag of the
+    /// contents when there is none, and existing cards with the same
+    /// id are overwritten, so importing the same data twice is
+    /// idempotent. Returns the imported cards, in data order.
+    pub fn import_vcf(
+        &mut self,
+        addressbook_id: &str,
+        data: &[u8],
+    ) -> Result<Vec<CardRef>, InMemoryClientError> {
+        if !self.entries.contains_key(addressbook_id) {
+            self.create_addressbook(addressbook_id, None, None)?;
+        }
+
+        let mut refs = Vec::new();
+
+        for contents in split_vcf(data) {
+            let id = match find_uid(&contents) {
+                Some(Some(uid)) => card_id_from_uid(&uid),
+                _ => content_tag(&contents),
+            };
+
+            refs.push(self.upsert_card(addressbook_id, &id, contents)?);
+        }
+
+        Ok(refs)
+    }
+
+    /// Dumps every card of `addressbook_id` as multi-card `.vcf` data,
+    /// sorted by card id.
+    pub fn export_vcf(&self, addressbook_id: &str) -> Result<Vec<u8>, InMemoryClientError> {
+        let entry = self.entry(addressbook_id)?;
+        Ok(join_vcf(entry.cards.values().map(Vec::as_slice)))
+    }
+
+    fn entry(&self, id: &str) -> Result<&Entry, InMemoryClientError> {
+        self.entries
+            .get(id)
+            .ok_or_else(|| InMemoryClientError::AddressbookNotFound(id.to_string()))
+    }
+
+    fn entry_mut(&mut self, id: &str) -> Result<&mut Entry, InMemoryClientError> {
+        self.entries
+            .get_mut(id)
+            .ok_or_else(|| InMemoryClientError::AddressbookNotFound(id.to_string()))
+    }
+}
+
+/// Checks `if_match` against the current tag of `card_id`. A missing
+/// card fails with [`InMemoryClientError::CardNotFound`].
+fn check_precondition(
+    entry: &Entry,
+    card_id: &str,
+    if_match: &str,
+) -> Result<(), InMemoryClientError> {
+    let actual = match entry.cards.get(card_id) {
+        Some(contents) => content_tag(contents),
+        None => return Err(InMemoryClientError::CardNotFound(card_id.to_string())),
+    };
+
+    if tag_matches(if_match, &actual) {
+        return Ok(());
+    }
+
+    Err(InMemoryClientError::PreconditionFailed {
+        card_id: card_id.to_string(),
+        actual,
+    })
+}
+
+impl AddressbookBackend for InMemoryClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(InMemoryClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(InMemoryClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(InMemoryClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(InMemoryClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(InMemoryClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(InMemoryClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(InMemoryClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(InMemoryClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(InMemoryClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(InMemoryClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(InMemoryClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(InMemoryClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(InMemoryClient::sync_cards(
+            self,
+            addressbook_id,
+            sync_token,
+        )?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(InMemoryClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(InMemoryClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(InMemoryClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &[u8] =
+        b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:urn:uuid:alice\r\nFN:Alice\r\nEND:VCARD\r\n";
+    const ALICIA: &[u8] =
+        b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:urn:uuid:alice\r\nFN:Alicia\r\nEND:VCARD\r\n";
+    const BOB: &[u8] = b"BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Bob\r\nEND:VCARD\r\n";
+
+    fn client() -> InMemoryClient {
+        let mut client = InMemoryClient::new();
+        client.create_addressbook("ab", None, None).unwrap();
+        client
+    }
+
+    fn ctag(client: &InMemoryClient) -> String {
+        client.list_addressbooks().unwrap()[0].ctag.clone().unwrap()
+    }
+
+    #[test]
+    fn update_card_if_match() {
+        let mut client = client();
+        let card = client.upsert_card("ab", "alice", ALICE.to_vec()).unwrap();
+        let etag = card.etag.unwrap();
+
+        let quoted = format!("\"{etag}\"");
+        let card = client
+            .update_card("ab", "alice", ALICIA.to_vec(), Some(&quoted))
+            .unwrap();
+
+        assert_eq!(card.etag.as_deref(), Some(content_tag(ALICIA).as_str()));
+        assert_eq!(client.get_card("ab", "alice").unwrap().contents, ALICIA);
+
+        let err = client
+            .update_card("ab", "alice", ALICE.to_vec(), Some(&etag))
+            .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+        assert_eq!(err.current_etag(), card.etag.as_deref());
+        assert_eq!(client.get_card("ab", "alice").unwrap().contents, ALICIA);
+    }
+
+    #[test]
+    fn update_card_if_match_missing() {
+        let mut client = client();
+
+        let err = client
+            .update_card("ab", "alice", ALICE.to_vec(), Some("tag"))
+            .unwrap_err();
+        assert!(matches!(err, InMemoryClientError::CardNotFound(_)));
+        assert_eq!(err.current_etag(), None);
+
+        // NOTE: without precondition, a missing card is created.
+        client
+            .update_card("ab", "alice", ALICE.to_vec(), None)
+            .unwrap();
+        assert!(client.get_card("ab", "alice").is_ok());
+    }
+
+    #[test]
+    fn delete_card_if_match() {
+        let mut client = client();
+        let card = client.upsert_card("ab", "alice", ALICE.to_vec()).unwrap();
+
+        let err = client
+            .delete_card("ab", "alice", Some("stale"))
+            .unwrap_err();
+        assert_eq!(err.current_etag(), card.etag.as_deref());
+
+        client
+            .delete_card("ab", "alice", card.etag.as_deref())
+            .unwrap();
+        assert_eq!(
+            client.get_card("ab", "alice").unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn create_card_already_exists() {
+        let mut client = client();
+        let options = CardCreateOptions { id_from_uid: true };
+
+        let card = client
+            .create_card_with_options("ab", ALICE.to_vec(), options.clone())
+            .unwrap();
+        assert_eq!(card.id, "alice");
+
+        let err = client
+            .create_card_with_options("ab", ALICIA.to_vec(), options)
+            .unwrap_err();
+
+        assert!(matches!(&err, InMemoryClientError::CardAlreadyExists(id) if id == "alice"));
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+        assert_eq!(client.get_card("ab", "alice").unwrap().contents, ALICE);
+    }
+
+    #[test]
+    fn create_card_injects_uid() {
+        let mut client = client();
+        let options = CardCreateOptions { id_from_uid: true };
+
+        let card = client
+            .create_card_with_options("ab", BOB.to_vec(), options)
+            .unwrap();
+        let contents = client.get_card("ab", &card.id).unwrap().contents;
+
+        assert_eq!(
+            find_uid(&contents),
+            Some(Some(format!("urn:uuid:{}", card.id)))
+        );
+    }
+
+    #[test]
+    fn vcf_round_trip() {
+        let mut client = client();
+        let data = [ALICE, BOB].concat();
+
+        let refs = client.import_vcf("imported", &data).unwrap();
+
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].id, "alice");
+        // NOTE: a card without UID gets the tag of its contents as id.
+        assert_eq!(refs[1].id, content_tag(BOB));
+
+        let exported = client.export_vcf("imported").unwrap();
+        assert_eq!(split_vcf(&exported).len(), 2);
+
+        let mut copy = InMemoryClient::new();
+        copy.import_vcf("imported", &exported).unwrap();
+        assert_eq!(copy.export_vcf("imported").unwrap(), exported);
+    }
+
+    #[test]
+    fn import_vcf_idempotent() {
+        let mut client = client();
+        let data = [ALICE, BOB].concat();
+
+        client.import_vcf("ab", &data).unwrap();
+        let exported = client.export_vcf("ab").unwrap();
+        let tag = ctag(&client);
+
+        client.import_vcf("ab", &data).unwrap();
+
+        assert_eq!(client.list_cards("ab", None, None).unwrap().len(), 2);
+        assert_eq!(client.export_vcf("ab").unwrap(), exported);
+        assert_eq!(ctag(&client), tag);
+    }
+
+    #[test]
+    fn ctag_bumps_on_card_writes() {
+        let mut client = client();
+        let empty = ctag(&client);
+
+        client.upsert_card("ab", "alice", ALICE.to_vec()).unwrap();
+        let created = ctag(&client);
+        assert_ne!(created, empty);
+
+        client.upsert_card("ab", "alice", ALICIA.to_vec()).unwrap();
+        let updated = ctag(&client);
+        assert_ne!(updated, created);
+
+        // NOTE: rewriting the same contents changes nothing.
+        client.upsert_card("ab", "alice", ALICIA.to_vec()).unwrap();
+        assert_eq!(ctag(&client), updated);
+
+        client.delete_card("ab", "alice", None).unwrap();
+        assert_eq!(ctag(&client), empty);
+    }
+}
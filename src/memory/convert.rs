@@ -0,0 +1,34 @@
+//! Conversions used by [`InMemoryClient`], plus the `From` impl that
+//! wraps it into the unified client's in-memory variant.
+//!
+//! [`InMemoryClient`]: crate::memory::client::InMemoryClient
+
+use alloc::vec::Vec;
+
+impl From<crate::memory::client::InMemoryClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::memory::client::InMemoryClient) -> Self {
+        Self::Memory(client)
+    }
+}
+
+/// 1-indexed pagination on an in-memory list. `page_size = None`
+/// returns the full slice; `page_size = 0` or a page past the end
+/// returns an empty vector.
+pub(crate) fn paginate<T>(items: Vec<T>, page: Option<u32>, page_size: Option<u32>) -> Vec<T> {
+    let Some(size) = page_size else {
+        return items;
+    };
+
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let page = page.unwrap_or(1).max(1);
+    let skip = ((page - 1) as usize).saturating_mul(size as usize);
+
+    if skip >= items.len() {
+        return Vec::new();
+    }
+
+    items.into_iter().skip(skip).take(size as usize).collect()
+}
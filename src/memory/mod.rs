@@ -0,0 +1,4 @@
+//! In-memory backend: the std client plus its conversions.
+
+pub mod client;
+pub mod convert;
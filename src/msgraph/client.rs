@@ -0,0 +1,822 @@
+//! Std-blocking Microsoft Graph contacts client.
+//!
+//! Wraps a connected stream to `graph.microsoft.com` and pumps
+//! io-addressbook Microsoft Graph coroutines against it via
+//! [`MsGraphClient::run`]. The OAuth 2.0 access token is obtained and
+//! refreshed by the caller, e.g. with an OAuth crate, then handed to
+//! [`MsGraphClient::new`] or [`MsGraphClient::set_token`].
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Debug;
+
+use log::debug;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    addressbook::{
+        Addressbook, AddressbookDiff,
+        msgraph::{
+            create::{MsGraphAddressbookCreate, MsGraphAddressbookCreateError},
+            delete::{MsGraphAddressbookDelete, MsGraphAddressbookDeleteError},
+            list::{MsGraphAddressbookList, MsGraphAddressbookListError},
+            update::{MsGraphAddressbookUpdate, MsGraphAddressbookUpdateError},
+        },
+    },
+    backend::AddressbookBackend,
+    capabilities::Capabilities,
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
+        msgraph::{
+            create::{MsGraphCardCreate, MsGraphCardCreateError},
+            delete::{MsGraphCardDelete, MsGraphCardDeleteError},
+            get::{MsGraphCardGet, MsGraphCardGetError},
+            list::{MsGraphCardList, MsGraphCardListError},
+            multiget::{MsGraphCardMultiget, MsGraphCardMultigetError},
+            sync::{MsGraphCardSync, MsGraphCardSyncError},
+            update::{MsGraphCardUpdate, MsGraphCardUpdateError},
+        },
+    },
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    http::{client::HttpStream, coroutine::*},
+    msgraph::{convert::capabilities, send::MsGraphSendError},
+};
+
+/// Default base URL of the Graph API.
+const BASE_URL: &str = "https://graph.microsoft.com/v1.0/";
+
+/// Default `User-Agent` header of [`MsGraphClient`].
+const USER_AGENT: &str = concat!("io-addressbook/", env!("CARGO_PKG_VERSION"));
+
+/// Errors surfaced by [`MsGraphClient`] while running a coroutine.
+///
+/// One variant per shared-API Microsoft Graph coroutine, plus the I/O
+/// failures from the run loop.
+#[derive(Debug, Error)]
+pub enum MsGraphClientError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    AddressbookCreate(#[from] MsGraphAddressbookCreateError),
+    #[error(transparent)]
+    AddressbookDelete(#[from] MsGraphAddressbookDeleteError),
+    #[error(transparent)]
+    AddressbookList(#[from] MsGraphAddressbookListError),
+    #[error(transparent)]
+    AddressbookUpdate(#[from] MsGraphAddressbookUpdateError),
+
+    #[error(transparent)]
+    CardCreate(#[from] MsGraphCardCreateError),
+    #[error(transparent)]
+    CardDelete(#[from] MsGraphCardDeleteError),
+    #[error(transparent)]
+    CardGet(#[from] MsGraphCardGetError),
+    #[error(transparent)]
+    CardList(#[from] MsGraphCardListError),
+    #[error(transparent)]
+    CardMultiget(#[from] MsGraphCardMultigetError),
+    #[error(transparent)]
+    CardSync(#[from] MsGraphCardSyncError),
+    #[error(transparent)]
+    CardUpdate(#[from] MsGraphCardUpdateError),
+}
+
+impl MsGraphClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        if let Some(err) = self.send_error() {
+            return err.kind();
+        }
+
+        match self {
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::CardUpdate(MsGraphCardUpdateError::PreconditionFailed { .. })
+            | Self::CardDelete(MsGraphCardDeleteError::PreconditionFailed { .. }) => {
+                ErrorKind::Conflict
+            }
+            Self::CardGet(MsGraphCardGetError::CardNotFound(_))
+            | Self::CardUpdate(MsGraphCardUpdateError::CardNotFound(_))
+            | Self::CardDelete(MsGraphCardDeleteError::CardNotFound(_)) => ErrorKind::NotFound,
+            Self::AddressbookCreate(MsGraphAddressbookCreateError::InvalidAddressbook(_))
+            | Self::AddressbookDelete(MsGraphAddressbookDeleteError::InvalidAddressbook(_))
+            | Self::AddressbookUpdate(MsGraphAddressbookUpdateError::InvalidAddressbook(_))
+            | Self::CardCreate(MsGraphCardCreateError::EmptyCardBody)
+            | Self::CardCreate(MsGraphCardCreateError::InvalidCard(_))
+            | Self::CardDelete(MsGraphCardDeleteError::InvalidCardId(_))
+            | Self::CardGet(MsGraphCardGetError::InvalidCardId(_))
+            | Self::CardMultiget(MsGraphCardMultigetError::InvalidCardId(_))
+            | Self::CardSync(MsGraphCardSyncError::InvalidSyncToken(_))
+            | Self::CardUpdate(MsGraphCardUpdateError::InvalidCardId(_))
+            | Self::CardUpdate(MsGraphCardUpdateError::EmptyCardBody)
+            | Self::CardUpdate(MsGraphCardUpdateError::InvalidCard(_)) => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current tag of the card when the error is a failed
+    /// `if_match` precondition and it is known.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::CardUpdate(MsGraphCardUpdateError::PreconditionFailed { etag, .. })
+            | Self::CardDelete(MsGraphCardDeleteError::PreconditionFailed { etag, .. }) => {
+                etag.as_deref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the HTTP exchange error wrapped by a coroutine error.
+    pub(crate) fn send_error(&self) -> Option<&MsGraphSendError> {
+        match self {
+            Self::AddressbookCreate(MsGraphAddressbookCreateError::Send(err))
+            | Self::AddressbookDelete(MsGraphAddressbookDeleteError::Send(err))
+            | Self::AddressbookList(MsGraphAddressbookListError::Send(err))
+            | Self::AddressbookUpdate(MsGraphAddressbookUpdateError::Send(err))
+            | Self::CardCreate(MsGraphCardCreateError::Send(err))
+            | Self::CardDelete(MsGraphCardDeleteError::Send(err))
+            | Self::CardGet(MsGraphCardGetError::Send(err))
+            | Self::CardList(MsGraphCardListError::Send(err))
+            | Self::CardMultiget(MsGraphCardMultigetError::Send(err))
+            | Self::CardSync(MsGraphCardSyncError::Send(err))
+            | Self::CardUpdate(MsGraphCardUpdateError::Send(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Std-blocking Microsoft Graph contacts client built on a connected
+/// stream.
+#[derive(Debug)]
+pub struct MsGraphClient {
+    pub stream: Box<dyn HttpStream>,
+    /// Base URL of the API, `https://graph.microsoft.com/v1.0/` by
+    /// default.
+    pub base_url: Url,
+    pub user_agent: String,
+    token: String,
+}
+
+impl MsGraphClient {
+    /// Builds a client talking to the Graph API through `stream`,
+    /// authenticating with the OAuth 2.0 access `token`.
+    ///
+    /// The token needs the `Contacts.ReadWrite` delegated permission.
+    pub fn new(stream: impl HttpStream + 'static, token: impl ToString) -> Self {
+        Self {
+            stream: Box::new(stream),
+            base_url: Url::parse(BASE_URL).unwrap(),
+            user_agent: USER_AGENT.to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    /// Replaces the access token, e.g. after refreshing it.
+    pub fn set_token(&mut self, token: impl ToString) {
+        self.token = token.to_string();
+    }
+
+    /// Pumps any standard-shape Microsoft Graph coroutine (`Yield =
+    /// HttpYield`, `Return = Result<T, E>`) against the stream until
+    /// it terminates.
+    pub fn run<C, T, E>(&mut self, coroutine: C) -> Result<T, MsGraphClientError>
+    where
+        C: HttpCoroutine<Yield = HttpYield, Return = Result<T, E>>,
+        MsGraphClientError: From<E>,
+    {
+        crate::http::client::run(&mut *self.stream, coroutine)
+    }
+
+    /// Reports the capabilities of the Graph contacts API.
+    pub fn capabilities(&self) -> Capabilities {
+        capabilities()
+    }
+
+    /// Lists the contact folders of the user plus the default
+    /// `contacts` one, sorted by name.
+    pub fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, MsGraphClientError> {
+        let coroutine = MsGraphAddressbookList::new(&self.base_url, &self.token, &self.user_agent);
+        self.run(coroutine)
+    }
+
+    /// Creates a contact folder named `name`. Contact folders have
+    /// neither description nor color: both are ignored. Returns the id
+    /// assigned by the server.
+    pub fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, MsGraphClientError> {
+        if description.is_some() || color.is_some() {
+            debug!("msgraph contact folders have no description nor color, ignoring them");
+        }
+
+        let coroutine =
+            MsGraphAddressbookCreate::new(&self.base_url, &self.token, &self.user_agent, name)?;
+        self.run(coroutine)
+    }
+
+    /// Renames the contact folder identified by `id`, ignoring
+    /// description and color changes. The default folder cannot be
+    /// renamed.
+    pub fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), MsGraphClientError> {
+        let coroutine = MsGraphAddressbookUpdate::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            id,
+            patch,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Deletes the contact folder identified by `id`, along with its
+    /// contacts. The default folder cannot be deleted.
+    pub fn delete_addressbook(&mut self, id: &str) -> Result<(), MsGraphClientError> {
+        let coroutine =
+            MsGraphAddressbookDelete::new(&self.base_url, &self.token, &self.user_agent, id)?;
+        self.run(coroutine)
+    }
+
+    /// Lists cards inside `addressbook_id`, applying 1-indexed
+    /// pagination client-side.
+    pub fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, MsGraphClientError> {
+        let coroutine = MsGraphCardList::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            page,
+            page_size,
+        );
+        self.run(coroutine)
+    }
+
+    /// Lists the id and ETag of every card in `addressbook_id`, sorted
+    /// by id.
+    pub fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, MsGraphClientError> {
+        let mut refs: Vec<CardRef> = self
+            .list_cards(addressbook_id, None, None)?
+            .into_iter()
+            .map(|card| CardRef {
+                id: card.id,
+                href: None,
+                etag: card.etag,
+            })
+            .collect();
+        refs.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(refs)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`.
+    ///
+    /// Graph has no full-text search on contacts, so cards are listed
+    /// then matched locally.
+    pub fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, MsGraphClientError> {
+        let cards = self.list_cards(addressbook_id, None, None)?;
+        Ok(cards
+            .into_iter()
+            .filter(|card| filter.matches(card))
+            .collect())
+    }
+
+    /// Fetches the cards of `addressbook_id` changed since
+    /// `sync_token` via a delta query.
+    ///
+    /// Pass `None` for the initial sync. When the token expired, falls
+    /// back to a tokenless sync returning the full listing (flagged by
+    /// [`CardChanges::full`]) and a fresh token.
+    pub fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, MsGraphClientError> {
+        let coroutine = MsGraphCardSync::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            sync_token,
+        );
+
+        match self.run(coroutine) {
+            Err(MsGraphClientError::CardSync(MsGraphCardSyncError::InvalidSyncToken(token))) => {
+                debug!("sync token {token} rejected, falling back to full sync");
+
+                let coroutine = MsGraphCardSync::new(
+                    &self.base_url,
+                    &self.token,
+                    &self.user_agent,
+                    addressbook_id,
+                    None,
+                );
+                self.run(coroutine)
+            }
+            result => result,
+        }
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, MsGraphClientError> {
+        let coroutine = MsGraphCardGet::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            card_id,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id` via
+    /// `$batch` requests. Ids the server does not find inside the
+    /// addressbook end up in [`CardBatch::missing`].
+    pub fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, MsGraphClientError> {
+        let coroutine = MsGraphCardMultiget::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            card_ids,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Creates a contact from the vCard `contents` inside
+    /// `addressbook_id`. Returns the id assigned by the server and the
+    /// ETag of the card.
+    pub fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, MsGraphClientError> {
+        let coroutine = MsGraphCardCreate::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            &contents,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Same as [`MsGraphClient::create_card`]: the server assigns card
+    /// ids, so `options` do not apply.
+    pub fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        _options: CardCreateOptions,
+    ) -> Result<CardRef, MsGraphClientError> {
+        self.create_card(addressbook_id, contents)
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id`, gating on
+    /// `if_match` when present. Returns the card id and new ETag.
+    pub fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, MsGraphClientError> {
+        let coroutine = MsGraphCardUpdate::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            card_id,
+            &contents,
+            if_match,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id` unconditionally,
+    /// or creates the card when missing. The server assigns the id of
+    /// a created card, which may thus differ from `card_id`.
+    pub fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, MsGraphClientError> {
+        match self.update_card(addressbook_id, card_id, contents.clone(), None) {
+            Err(MsGraphClientError::CardUpdate(MsGraphCardUpdateError::CardNotFound(_))) => {
+                self.create_card(addressbook_id, contents)
+            }
+            result => result,
+        }
+    }
+
+    /// Deletes `card_id` from `addressbook_id`, gating on
+    /// `if_match` when present.
+    pub fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), MsGraphClientError> {
+        let coroutine = MsGraphCardDelete::new(
+            &self.base_url,
+            &self.token,
+            &self.user_agent,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?;
+        self.run(coroutine)
+    }
+}
+
+impl AddressbookBackend for MsGraphClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(MsGraphClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(MsGraphClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(MsGraphClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(MsGraphClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(MsGraphClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(MsGraphClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(MsGraphClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(MsGraphClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(MsGraphClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(MsGraphClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(MsGraphClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(MsGraphClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(MsGraphClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(MsGraphClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(MsGraphClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(MsGraphClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String};
+
+    use super::*;
+    use crate::http::client::mock::MockStream;
+
+    const DELTA_URL: &str = "https://graph.microsoft.com/v1.0/me/contactFolders/f1/contacts/delta";
+
+    fn client(stream: &MockStream) -> MsGraphClient {
+        MsGraphClient::new(stream.clone(), "token")
+    }
+
+    fn contact(id: &str, name: &str) -> String {
+        format!(r#"{{ "id": "{id}", "@odata.etag": "W/\"{id}-1\"", "displayName": "{name}" }}"#)
+    }
+
+    /// Builds a `$batch` response from `(request id, status, body)`
+    /// items.
+    fn batch(items: &[(&str, u16, &str)]) -> String {
+        let responses: Vec<String> = items
+            .iter()
+            .map(|(id, status, body)| {
+                format!(r#"{{ "id": "{id}", "status": {status}, "body": {body} }}"#)
+            })
+            .collect();
+
+        format!(r#"{{ "responses": [{}] }}"#, responses.join(","))
+    }
+
+    const NOT_FOUND: &str = r#"{ "error": { "code": "ErrorItemNotFound", "message": "" } }"#;
+
+    #[test]
+    fn sync_cards_delta_pages() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(
+                200,
+                &format!(
+                    r#"{{ "value": [
+                        {{ "id": "c1" }},
+                        {{ "id": "c9", "@removed": {{ "reason": "deleted" }} }}
+                    ], "@odata.nextLink": "{DELTA_URL}?$skiptoken=s2" }}"#
+                ),
+            )
+            .respond(
+                200,
+                &format!(
+                    r#"{{ "value": [{{ "id": "c2" }}],
+                        "@odata.deltaLink": "{DELTA_URL}?$deltatoken=d2" }}"#
+                ),
+            )
+            .respond(
+                200,
+                &batch(&[("0", 200, &contact("c1", "Alice")), ("1", 404, NOT_FOUND)]),
+            );
+
+        let changes = client.sync_cards("f1", Some("d1")).unwrap();
+
+        assert!(!changes.full);
+        assert_eq!(changes.sync_token.as_deref(), Some("d2"));
+        assert_eq!(changes.cards.len(), 1);
+        assert_eq!(changes.cards[0].id, "c1");
+        assert_eq!(changes.cards[0].etag.as_deref(), Some("W/\"c1-1\""));
+        // NOTE: c2 vanished between the delta query and the fetch.
+        assert_eq!(changes.deleted, ["c2", "c9"]);
+
+        let requests = stream.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].starts_with("GET /v1.0/me/contactFolders/f1/contacts/delta?"));
+        assert!(requests[0].contains("deltatoken=d1"));
+        assert!(requests[0].contains("Authorization: Bearer token\r\n"));
+        assert!(requests[1].contains("$skiptoken=s2"));
+        assert!(requests[2].starts_with("POST /v1.0/$batch HTTP/1.1\r\n"));
+        assert!(requests[2].contains(r#""id":"1""#));
+    }
+
+    #[test]
+    fn sync_cards_nothing_changed() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(
+            200,
+            &format!(r#"{{ "value": [], "@odata.deltaLink": "{DELTA_URL}?$deltatoken=d2" }}"#),
+        );
+
+        let changes = client.sync_cards("f1", Some("d1")).unwrap();
+
+        assert!(changes.cards.is_empty());
+        assert!(changes.deleted.is_empty());
+        assert_eq!(changes.sync_token.as_deref(), Some("d2"));
+        // NOTE: no batch is sent without changed contacts.
+        assert_eq!(stream.requests().len(), 1);
+    }
+
+    fn sync_cards_expired(status: u16, body: &str) {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream
+            .respond(status, body)
+            .respond(
+                200,
+                &format!(
+                    r#"{{ "value": [
+                        {{ "id": "c1" }},
+                        {{ "id": "c9", "@removed": {{ "reason": "deleted" }} }}
+                    ], "@odata.deltaLink": "{DELTA_URL}?$deltatoken=d3" }}"#
+                ),
+            )
+            .respond(200, &batch(&[("0", 200, &contact("c1", "Alice"))]));
+
+        let changes = client.sync_cards("f1", Some("expired")).unwrap();
+
+        assert!(changes.full);
+        assert_eq!(changes.sync_token.as_deref(), Some("d3"));
+        assert_eq!(changes.cards.len(), 1);
+        assert!(changes.deleted.is_empty());
+
+        let requests = stream.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].contains("deltatoken=expired"));
+        assert!(!requests[1].contains("deltatoken="));
+    }
+
+    #[test]
+    fn sync_cards_expired_token_gone() {
+        sync_cards_expired(
+            410,
+            r#"{ "error": { "code": "resyncRequired", "message": "Resync required." } }"#,
+        );
+    }
+
+    #[test]
+    fn sync_cards_expired_token_sync_state() {
+        sync_cards_expired(
+            400,
+            r#"{ "error": { "code": "SyncStateNotFound", "message": "" } }"#,
+        );
+    }
+
+    #[test]
+    fn sync_cards_other_error() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(
+            401,
+            r#"{ "error": { "code": "InvalidAuthenticationToken", "message": "" } }"#,
+        );
+
+        let err = client.sync_cards("f1", Some("d1")).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Unauthorized);
+        assert_eq!(stream.requests().len(), 1);
+    }
+
+    #[test]
+    fn get_cards_missing() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(
+            200,
+            &batch(&[
+                ("2", 400, NOT_FOUND),
+                ("0", 200, &contact("c1", "Alice")),
+                ("1", 404, NOT_FOUND),
+            ]),
+        );
+
+        let batch = client.get_cards("f1", &["c1", "c2", "c3"]).unwrap();
+
+        assert_eq!(batch.cards.len(), 1);
+        assert_eq!(batch.cards[0].id, "c1");
+        assert_eq!(batch.cards[0].addressbook_id, "f1");
+        assert_eq!(batch.missing, ["c2", "c3"]);
+    }
+
+    #[test]
+    fn get_cards_failed_item() {
+        let stream = MockStream::default();
+        let mut client = client(&stream);
+
+        stream.respond(
+            200,
+            &batch(&[(
+                "0",
+                429,
+                r#"{ "error": { "code": "TooManyRequests", "message": "" } }"#,
+            )]),
+        );
+
+        let err = client.get_cards("f1", &["c1"]).unwrap_err();
+
+        assert!(matches!(
+            err,
+            MsGraphClientError::CardMultiget(MsGraphCardMultigetError::Send(
+                MsGraphSendError::Status { status: 429, .. }
+            ))
+        ));
+    }
+}
@@ -0,0 +1,462 @@
+//! Microsoft Graph `contact` resources and their conversion to and
+//! from the typed [`Contact`] view.
+//!
+//! Names, the first three emails, home, business and mobile phones,
+//! one home, business and other address, organization, title, full
+//! birthday date, first note, categories, first nickname, first URL
+//! and instant messaging addresses map to their Graph counterpart.
+//! Every other vCard property (including `UID`, `PHOTO`, fax numbers
+//! and extra notes) travels verbatim in a multi-value extended
+//! property, so that a card written through this backend reads back
+//! the same. Graph has no email type, so the `TYPE` of mapped emails
+//! is dropped, like the group and parameters of other typed vCard
+//! properties.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::card::{Address, Contact, Email, Organization, Phone, Property, StructuredName, escape};
+
+/// Id of the extended property holding the verbatim vCard properties.
+pub(crate) const RAW_PROPERTY_ID: &str =
+    "StringArray {6f3c4e52-9a1d-4c7b-8e25-0d9b7a1f4c3e} Name vcard";
+
+/// Largest number of emails a contact holds.
+const MAX_EMAILS: usize = 3;
+
+/// Microsoft Graph `contact` resource.
+///
+/// Mapped fields are always serialized, so that a `PATCH` of a whole
+/// card clears the ones the card lacks.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MsGraphContact {
+    #[serde(skip_serializing)]
+    pub id: String,
+    #[serde(rename = "@odata.etag", skip_serializing)]
+    pub etag: Option<String>,
+    /// Set on the contacts removed since the delta token of the
+    /// request, either deleted or moved out of the folder.
+    #[serde(rename = "@removed", skip_serializing)]
+    pub removed: Option<Value>,
+    pub display_name: Option<String>,
+    pub given_name: Option<String>,
+    pub surname: Option<String>,
+    pub middle_name: Option<String>,
+    /// Honorific prefix.
+    pub title: Option<String>,
+    /// Honorific suffix.
+    pub generation: Option<String>,
+    pub nick_name: Option<String>,
+    pub email_addresses: Vec<EmailAddress>,
+    pub business_phones: Vec<String>,
+    pub home_phones: Vec<String>,
+    pub mobile_phone: Option<String>,
+    pub home_address: Option<PhysicalAddress>,
+    pub business_address: Option<PhysicalAddress>,
+    pub other_address: Option<PhysicalAddress>,
+    pub company_name: Option<String>,
+    pub department: Option<String>,
+    pub job_title: Option<String>,
+    /// Date and time in UTC, e.g. `1985-04-12T11:59:00Z`.
+    pub birthday: Option<String>,
+    pub personal_notes: Option<String>,
+    pub categories: Vec<String>,
+    pub business_home_page: Option<String>,
+    pub im_addresses: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub multi_value_extended_properties: Vec<ExtendedProperty>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EmailAddress {
+    pub name: Option<String>,
+    pub address: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PhysicalAddress {
+    pub street: String,
+    pub city: String,
+    pub state: String,
+    pub postal_code: String,
+    pub country_or_region: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExtendedProperty {
+    pub id: String,
+    pub value: Vec<String>,
+}
+
+impl MsGraphContact {
+    /// Maps a typed [`Contact`] to a Graph contact, without id.
+    pub fn from_contact(contact: &Contact) -> Self {
+        let mut graph = Self::default();
+
+        // NOTE: the properties without Graph counterpart are gathered
+        // in a contact of their own, serialized as is.
+        let mut raw = Contact::new();
+        raw.uid = contact.uid.clone();
+        raw.photo = contact.photo.clone();
+        raw.rev = contact.rev.clone();
+
+        graph.display_name = contact.formatted_name.clone();
+
+        if let Some(n) = &contact.name {
+            graph.given_name = non_empty(&n.given);
+            graph.surname = non_empty(&n.family);
+            graph.middle_name = non_empty(&n.additional);
+            graph.title = non_empty(&n.prefixes);
+            graph.generation = non_empty(&n.suffixes);
+        }
+
+        for (i, email) in contact.emails.iter().enumerate() {
+            if i < MAX_EMAILS {
+                graph.email_addresses.push(EmailAddress {
+                    name: None,
+                    address: email.address.clone(),
+                });
+            } else {
+                raw.emails.push(email.clone());
+            }
+        }
+
+        for phone in &contact.phones {
+            let has = |kind: &str| phone.types.iter().any(|t| t.eq_ignore_ascii_case(kind));
+
+            if has("fax") || has("pager") {
+                raw.phones.push(phone.clone());
+            } else if has("cell") && graph.mobile_phone.is_none() {
+                graph.mobile_phone = Some(phone.number.clone());
+            } else if has("work") {
+                graph.business_phones.push(phone.number.clone());
+            } else if has("home") || phone.types.is_empty() {
+                graph.home_phones.push(phone.number.clone());
+            } else {
+                raw.phones.push(phone.clone());
+            }
+        }
+
+        for adr in &contact.addresses {
+            let has = |kind: &str| adr.types.iter().any(|t| t.eq_ignore_ascii_case(kind));
+
+            let slot = if !adr.po_box.is_empty() || !adr.extended.is_empty() {
+                None
+            } else if has("home") {
+                Some(&mut graph.home_address)
+            } else if has("work") {
+                Some(&mut graph.business_address)
+            } else {
+                Some(&mut graph.other_address)
+            };
+
+            match slot {
+                Some(slot @ None) => {
+                    *slot = Some(PhysicalAddress {
+                        street: adr.street.clone(),
+                        city: adr.locality.clone(),
+                        state: adr.region.clone(),
+                        postal_code: adr.postal_code.clone(),
+                        country_or_region: adr.country.clone(),
+                    });
+                }
+                _ => raw.addresses.push(adr.clone()),
+            }
+        }
+
+        if let Some(org) = &contact.org {
+            graph.company_name = non_empty(&org.name);
+            graph.department = org.units.first().and_then(|unit| non_empty(unit));
+        }
+
+        graph.job_title = contact.title.clone();
+
+        if let Some(bday) = &contact.birthday {
+            match graph_date(bday) {
+                Some(date) => graph.birthday = Some(date),
+                None => raw.birthday = Some(bday.clone()),
+            }
+        }
+
+        let mut notes = contact.notes.iter();
+        graph.personal_notes = notes.next().cloned();
+        raw.notes.extend(notes.cloned());
+
+        graph.categories = contact.categories.clone();
+
+        for prop in &contact.properties {
+            match prop.name.as_str() {
+                "NICKNAME" if graph.nick_name.is_none() => {
+                    let mut nicknames = prop
+                        .text()
+                        .split(',')
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>();
+                    graph.nick_name = Some(nicknames.remove(0));
+
+                    if !nicknames.is_empty() {
+                        raw.properties
+                            .push(text_prop("NICKNAME", &nicknames.join(",")));
+                    }
+                }
+                "URL" if graph.business_home_page.is_none() => {
+                    graph.business_home_page = Some(prop.text());
+                }
+                "IMPP" => graph.im_addresses.push(prop.text()),
+                _ => raw.properties.push(prop.clone()),
+            }
+        }
+
+        let lines: Vec<String> = raw
+            .to_properties()
+            .iter()
+            .filter(|prop| prop.name != "VERSION")
+            .map(Property::to_line)
+            .collect();
+
+        if !lines.is_empty() {
+            graph
+                .multi_value_extended_properties
+                .push(ExtendedProperty {
+                    id: RAW_PROPERTY_ID.to_string(),
+                    value: lines,
+                });
+        }
+
+        graph
+    }
+
+    /// Maps the Graph contact to a typed [`Contact`].
+    pub fn to_contact(&self) -> Contact {
+        let mut contact = Contact::new();
+
+        let n = StructuredName {
+            family: self.surname.clone().unwrap_or_default(),
+            given: self.given_name.clone().unwrap_or_default(),
+            additional: self.middle_name.clone().unwrap_or_default(),
+            prefixes: self.title.clone().unwrap_or_default(),
+            suffixes: self.generation.clone().unwrap_or_default(),
+        };
+
+        contact.formatted_name = self
+            .display_name
+            .clone()
+            .filter(|name| !name.is_empty())
+            .or_else(|| non_empty(&display_name(&n)));
+
+        if n != StructuredName::default() {
+            contact.name = Some(n);
+        }
+
+        for email in &self.email_addresses {
+            contact.emails.push(Email {
+                address: email.address.clone(),
+                ..Default::default()
+            });
+        }
+
+        let phones = [(&self.business_phones, "work"), (&self.home_phones, "home")];
+
+        for (numbers, kind) in phones {
+            for number in numbers.iter().filter(|n| !n.is_empty()) {
+                contact.phones.push(phone(number, kind));
+            }
+        }
+
+        if let Some(number) = self.mobile_phone.as_ref().filter(|n| !n.is_empty()) {
+            contact.phones.push(phone(number, "cell"));
+        }
+
+        let addresses = [
+            (&self.home_address, Some("home")),
+            (&self.business_address, Some("work")),
+            (&self.other_address, None),
+        ];
+
+        for (adr, kind) in addresses {
+            let Some(adr) = adr.as_ref().filter(|adr| !adr.is_empty()) else {
+                continue;
+            };
+
+            contact.addresses.push(Address {
+                street: adr.street.clone(),
+                locality: adr.city.clone(),
+                region: adr.state.clone(),
+                postal_code: adr.postal_code.clone(),
+                country: adr.country_or_region.clone(),
+                types: kind.into_iter().map(ToString::to_string).collect(),
+                ..Default::default()
+            });
+        }
+
+        let company = self.company_name.clone().unwrap_or_default();
+        let department = self.department.clone().filter(|d| !d.is_empty());
+
+        if !company.is_empty() || department.is_some() {
+            contact.org = Some(Organization {
+                name: company,
+                units: department.into_iter().collect(),
+            });
+        }
+
+        contact.title = self.job_title.clone().filter(|t| !t.is_empty());
+        contact.birthday = self.birthday.as_deref().and_then(vcard_date);
+        contact.notes = self
+            .personal_notes
+            .clone()
+            .filter(|n| !n.is_empty())
+            .into_iter()
+            .collect();
+        contact.categories = self.categories.clone();
+
+        if let Some(nickname) = self.nick_name.as_ref().filter(|n| !n.is_empty()) {
+            contact.properties.push(text_prop("NICKNAME", nickname));
+        }
+
+        if let Some(url) = self.business_home_page.as_ref().filter(|u| !u.is_empty()) {
+            contact.properties.push(text_prop("URL", url));
+        }
+
+        for im in self.im_addresses.iter().filter(|im| !im.is_empty()) {
+            contact.properties.push(text_prop("IMPP", im));
+        }
+
+        let raw = self
+            .multi_value_extended_properties
+            .iter()
+            .filter(|prop| prop.id.eq_ignore_ascii_case(RAW_PROPERTY_ID))
+            .flat_map(|prop| prop.value.iter())
+            .filter_map(|line| Property::parse(line).ok());
+
+        contact.properties.extend(raw);
+
+        // NOTE: reparsing dispatches the verbatim properties with a
+        // typed field (`UID`, `PHOTO`, extra `NOTE`s…) to it.
+        Contact::parse(&contact.to_string()).unwrap_or(contact)
+    }
+
+    /// Renders the Graph contact as vCard bytes.
+    pub fn to_vcard(&self) -> Vec<u8> {
+        self.to_contact().to_bytes()
+    }
+
+    /// Tells whether the verbatim vCard properties are set.
+    pub fn has_raw_properties(&self) -> bool {
+        self.multi_value_extended_properties
+            .iter()
+            .any(|prop| prop.id.eq_ignore_ascii_case(RAW_PROPERTY_ID) && !prop.value.is_empty())
+    }
+
+    /// Clears the verbatim vCard properties on update, when none are
+    /// set.
+    pub fn clear_raw_properties(&mut self) {
+        if self.multi_value_extended_properties.is_empty() {
+            self.multi_value_extended_properties.push(ExtendedProperty {
+                id: RAW_PROPERTY_ID.to_string(),
+                value: Vec::new(),
+            });
+        }
+    }
+}
+
+impl PhysicalAddress {
+    fn is_empty(&self) -> bool {
+        [
+            &self.street,
+            &self.city,
+            &self.state,
+            &self.postal_code,
+            &self.country_or_region,
+        ]
+        .iter()
+        .all(|part| part.is_empty())
+    }
+}
+
+/// Returns the `$expand` query value fetching the verbatim vCard
+/// properties along with the contact.
+pub(crate) fn expand_raw_property() -> String {
+    format!("multiValueExtendedProperties($filter=id eq '{RAW_PROPERTY_ID}')")
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.to_string()).filter(|v| !v.is_empty())
+}
+
+/// Composes a display name from the structured name components.
+fn display_name(name: &StructuredName) -> String {
+    let parts = [
+        &name.prefixes,
+        &name.given,
+        &name.additional,
+        &name.family,
+        &name.suffixes,
+    ];
+
+    parts
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn phone(number: &str, kind: &str) -> Phone {
+    Phone {
+        number: number.to_string(),
+        types: Vec::from([kind.to_string()]),
+        ..Default::default()
+    }
+}
+
+/// Builds a vCard property holding `text`, escaped.
+fn text_prop(name: &str, text: &str) -> Property {
+    Property {
+        name: name.to_string(),
+        value: escape(text),
+        ..Default::default()
+    }
+}
+
+/// Parses a full vCard date (`19850412` or `1985-04-12`, optionally
+/// followed by a time) into a Graph birthday. Partial dates have no
+/// Graph counterpart.
+///
+/// The time is set to noon so that the date stays the same in every
+/// time zone Outlook renders it in.
+fn graph_date(value: &str) -> Option<String> {
+    let date = value.split('T').next().unwrap_or_default().replace('-', "");
+
+    if date.len() != 8 || !date.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(format!(
+        "{}-{}-{}T11:59:00Z",
+        &date[..4],
+        &date[4..6],
+        &date[6..]
+    ))
+}
+
+/// Renders the date of a Graph birthday as a vCard date.
+fn vcard_date(value: &str) -> Option<String> {
+    let date = value.get(..10)?;
+    let digits = date.replace('-', "");
+
+    if digits.len() != 8 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(digits)
+}
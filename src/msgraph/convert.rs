@@ -0,0 +1,236 @@
+//! Conversions between Microsoft Graph resources and the shared types
+//! used by [`MsGraphClient`], plus the `From` impl that wraps it into
+//! the unified client's Microsoft Graph variant.
+//!
+//! [`MsGraphClient`]: crate::msgraph::client::MsGraphClient
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::Value;
+use url::Url;
+
+use crate::{
+    addressbook::Addressbook,
+    capabilities::{AddressbookField, Capabilities},
+    card::{Card, CardRef},
+    http::send::HttpSendError,
+    msgraph::{contact::MsGraphContact, send::MsGraphSendError},
+};
+
+impl From<crate::msgraph::client::MsGraphClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::msgraph::client::MsGraphClient) -> Self {
+        Self::MsGraph(client)
+    }
+}
+
+/// Id of the default contact folder of the user.
+pub const DEFAULT_FOLDER: &str = "contacts";
+
+/// Name of the default contact folder of the user.
+const DEFAULT_FOLDER_NAME: &str = "Contacts";
+
+/// Largest page of contacts or contact folders.
+pub(crate) const PAGE_SIZE: &str = "1000";
+
+/// Largest number of requests carried by one `$batch`.
+pub(crate) const BATCH_SIZE: usize = 20;
+
+/// `contactFolder` resource.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct ContactFolder {
+    pub id: String,
+    pub display_name: String,
+}
+
+/// Page of a Graph collection.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct CollectionPage<T> {
+    pub value: Vec<T>,
+    #[serde(rename = "@odata.nextLink")]
+    pub next_link: Option<String>,
+    #[serde(rename = "@odata.deltaLink")]
+    pub delta_link: Option<String>,
+}
+
+/// Deserializes a response body.
+pub(crate) fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, MsGraphSendError> {
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Builds the URL of `path` (e.g. `me/contactFolders`) under the API
+/// `base_url`, with the `query` pairs.
+pub(crate) fn endpoint(base_url: &Url, path: &str, query: &[(&str, &str)]) -> Url {
+    let mut url = base_url.clone();
+    let base = base_url.path().trim_end_matches('/');
+    url.set_path(&[base, path].join("/"));
+    url.set_query(None);
+
+    if !query.is_empty() {
+        let mut pairs = url.query_pairs_mut();
+
+        for (name, value) in query {
+            pairs.append_pair(name, value);
+        }
+    }
+
+    url
+}
+
+/// Parses an `@odata.nextLink`, which Graph returns as an absolute
+/// URL.
+pub(crate) fn next_link(link: &str) -> Result<Url, MsGraphSendError> {
+    Url::parse(link)
+        .map_err(|_| MsGraphSendError::Http(HttpSendError::InvalidResponse("invalid next link")))
+}
+
+/// Returns the URL of `url` relative to the API `base_url`, as the
+/// requests of a `$batch` expect it.
+pub(crate) fn batch_url(base_url: &Url, url: &Url) -> String {
+    let base = base_url.path().trim_end_matches('/');
+    let path = url.path().strip_prefix(base).unwrap_or(url.path());
+
+    match url.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    }
+}
+
+/// Returns the path of the contact folder `id`.
+pub(crate) fn folder_path(id: &str) -> String {
+    format!("me/contactFolders/{id}")
+}
+
+/// Returns the path of the contacts of the contact folder `id`.
+///
+/// The default folder is reached through `me/contacts`, since its id
+/// differs from one mailbox to another.
+pub(crate) fn contacts_path(folder_id: &str) -> String {
+    if folder_id == DEFAULT_FOLDER {
+        "me/contacts".to_string()
+    } else {
+        format!("{}/contacts", folder_path(folder_id))
+    }
+}
+
+/// Returns the path of the contact `id` of the folder `folder_id`.
+pub(crate) fn contact_path(folder_id: &str, id: &str) -> String {
+    format!("{}/{id}", contacts_path(folder_id))
+}
+
+/// Returns the addressbook standing for the default contact folder.
+pub(crate) fn default_addressbook() -> Addressbook {
+    Addressbook {
+        id: DEFAULT_FOLDER.to_string(),
+        name: DEFAULT_FOLDER_NAME.to_string(),
+        description: None,
+        color: None,
+        ctag: None,
+        sync_token: None,
+    }
+}
+
+/// Maps a contact folder to a shared [`Addressbook`].
+///
+/// Contact folders carry no change marker: addressbooks expose
+/// neither ctag nor sync token.
+pub(crate) fn addressbook_from_folder(folder: ContactFolder) -> Addressbook {
+    Addressbook {
+        id: folder.id,
+        name: folder.display_name,
+        description: None,
+        color: None,
+        ctag: None,
+        sync_token: None,
+    }
+}
+
+/// Maps a Graph contact to a shared [`Card`], its contents being the
+/// vCard rendering and its ETag the contact `@odata.etag`.
+pub(crate) fn card_from_contact(addressbook_id: &str, contact: &MsGraphContact) -> Card {
+    Card {
+        id: contact.id.clone(),
+        addressbook_id: addressbook_id.to_string(),
+        etag: contact.etag.clone(),
+        contents: contact.to_vcard(),
+    }
+}
+
+/// Maps a Graph contact to a [`CardRef`].
+pub(crate) fn card_ref_from_contact(contact: &MsGraphContact) -> CardRef {
+    CardRef {
+        id: contact.id.clone(),
+        href: None,
+        etag: contact.etag.clone(),
+    }
+}
+
+/// Capabilities of the Microsoft Graph contacts API.
+pub(crate) fn capabilities() -> Capabilities {
+    Capabilities {
+        etag: true,
+        ctag: false,
+        sync_token: true,
+        query: false,
+        multiget: true,
+        vcard_versions: vec!["4.0".to_string()],
+        max_resource_size: None,
+        writable_metadata: vec![AddressbookField::Name],
+    }
+}
+
+/// Compares an `If-Match`-style tag against a contact `@odata.etag`,
+/// tolerating the weak prefix and surrounding quotes of either.
+pub(crate) fn etag_matches(if_match: &str, etag: &str) -> bool {
+    let strip = |tag: &str| {
+        let tag = tag.trim();
+        let tag = tag.strip_prefix("W/").unwrap_or(tag);
+        tag.trim_matches('"').to_string()
+    };
+
+    strip(if_match) == strip(etag)
+}
+
+/// Tells whether `id` is a valid contact or contact folder id: a
+/// non-empty path segment.
+pub(crate) fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && !id.contains(['/', '?', '#'])
+}
+
+/// Extracts the delta token of an `@odata.deltaLink`.
+pub(crate) fn delta_token(link: &str) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+    let mut pairs = url.query_pairs();
+    pairs
+        .find(|(name, _)| name == "$deltatoken")
+        .map(|(_, token)| token.into_owned())
+}
+
+/// 1-indexed pagination on an in-memory list. `page_size = None`
+/// returns the full slice; `page_size = 0` or a page past the end
+/// returns an empty vector.
+pub(crate) fn paginate<T>(items: Vec<T>, page: Option<u32>, page_size: Option<u32>) -> Vec<T> {
+    let Some(size) = page_size else {
+        return items;
+    };
+
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let page = page.unwrap_or(1).max(1);
+    let skip = ((page - 1) as usize).saturating_mul(size as usize);
+
+    if skip >= items.len() {
+        return Vec::new();
+    }
+
+    items.into_iter().skip(skip).take(size as usize).collect()
+}
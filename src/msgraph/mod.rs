@@ -0,0 +1,7 @@
+//! Microsoft Graph contacts backend: the std client, its I/O-free
+//! HTTP exchange and its contact conversions.
+
+pub mod client;
+pub(crate) mod contact;
+pub mod convert;
+pub mod send;
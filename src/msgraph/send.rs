@@ -0,0 +1,162 @@
+//! I/O-free HTTP/1.1 exchange carrying Microsoft Graph requests.
+//!
+//! [`MsGraphSend`] adds an OAuth 2.0 access token and JSON bodies to
+//! the shared [`HttpSend`] exchange. Error responses carry an OData
+//! error body, surfaced as [`MsGraphSendError::Status`].
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use log::trace;
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    error::ErrorKind,
+    http::{
+        coroutine::*,
+        send::{HttpSend, HttpSendError, json_body},
+    },
+};
+
+/// Errors produced by [`MsGraphSend`].
+#[derive(Debug, Error)]
+pub enum MsGraphSendError {
+    #[error("Microsoft Graph error {status} {code}: {message}")]
+    Status {
+        /// HTTP status code.
+        status: u16,
+        /// Graph error code, e.g. `ErrorItemNotFound`.
+        code: String,
+        message: String,
+    },
+    #[error(transparent)]
+    Http(HttpSendError),
+    #[error("Invalid Microsoft Graph response: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl MsGraphSendError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Status { status, .. } => ErrorKind::from_status(*status),
+            Self::Http(err) => err.kind(),
+            Self::Json(_) => ErrorKind::Transport,
+        }
+    }
+
+    /// Tells whether the server answered with the HTTP status `code`.
+    pub fn is_status(&self, code: u16) -> bool {
+        matches!(self, Self::Status { status, .. } if *status == code)
+    }
+
+    /// Tells whether the server refused the delta token of the
+    /// request, either expired or unknown.
+    pub fn is_invalid_sync_state(&self) -> bool {
+        match self {
+            Self::Status { status: 410, .. } => true,
+            Self::Status { code, .. } => code.starts_with("SyncState"),
+            _ => false,
+        }
+    }
+}
+
+impl From<HttpSendError> for MsGraphSendError {
+    fn from(err: HttpSendError) -> Self {
+        match err {
+            HttpSendError::Status { status, body } => status_error(status, &body),
+            err => Self::Http(err),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorWire {
+    error: ODataError,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ODataError {
+    code: String,
+    message: String,
+}
+
+/// I/O-free coroutine sending one Microsoft Graph request.
+///
+/// On completion returns the JSON body of a 2xx response (`null` when
+/// empty).
+pub struct MsGraphSend {
+    inner: HttpSend,
+}
+
+impl MsGraphSend {
+    /// Builds the coroutine sending a `GET` on `url`.
+    pub fn get(url: &Url, token: &str, user_agent: &str) -> Self {
+        Self::new("GET", url, token, user_agent, None)
+    }
+
+    /// Builds the coroutine sending a `POST` of `body` on `url`.
+    pub fn post(url: &Url, token: &str, user_agent: &str, body: &Value) -> Self {
+        Self::new("POST", url, token, user_agent, Some(body))
+    }
+
+    /// Builds the coroutine sending a `PATCH` of `body` on `url`.
+    pub fn patch(url: &Url, token: &str, user_agent: &str, body: &Value) -> Self {
+        Self::new("PATCH", url, token, user_agent, Some(body))
+    }
+
+    /// Builds the coroutine sending a `DELETE` on `url`.
+    pub fn delete(url: &Url, token: &str, user_agent: &str) -> Self {
+        Self::new("DELETE", url, token, user_agent, None)
+    }
+
+    fn new(method: &str, url: &Url, token: &str, user_agent: &str, body: Option<&Value>) -> Self {
+        trace!("prepare msgraph {method} {url}");
+
+        let authorization = format!("Bearer {token}");
+        let body = body.map(|body| body.to_string().into_bytes());
+
+        Self {
+            inner: HttpSend::new(method, url, Some(&authorization), user_agent, body),
+        }
+    }
+}
+
+impl HttpCoroutine for MsGraphSend {
+    type Yield = HttpYield;
+    type Return = Result<Value, MsGraphSendError>;
+
+    fn resume(&mut self, arg: Option<&[u8]>) -> HttpCoroutineState<Self::Yield, Self::Return> {
+        match self.inner.resume(arg) {
+            HttpCoroutineState::Yielded(y) => HttpCoroutineState::Yielded(y),
+            HttpCoroutineState::Complete(Ok(body)) => {
+                HttpCoroutineState::Complete(json_body(&body).map_err(Into::into))
+            }
+            HttpCoroutineState::Complete(Err(err)) => HttpCoroutineState::Complete(Err(err.into())),
+        }
+    }
+}
+
+/// Builds the error of a non-2xx response from its OData error body,
+/// or from the raw body when it is not one.
+pub(crate) fn status_error(status: u16, body: &[u8]) -> MsGraphSendError {
+    let error = match serde_json::from_slice::<ErrorWire>(body) {
+        Ok(wire) => wire.error,
+        Err(_) => ODataError {
+            message: String::from_utf8_lossy(body).to_string(),
+            ..Default::default()
+        },
+    };
+
+    MsGraphSendError::Status {
+        status,
+        code: error.code,
+        message: error.message,
+    }
+}
@@ -0,0 +1,640 @@
+//! Std-blocking mutt alias file client.
+//!
+//! [`MuttAliasClient`] exposes a file of mutt `alias` commands (such as
+//! the one `$alias_file` points to) through the shared API, as one
+//! addressbook named after the file name. Aliases convert to and from
+//! vCard (see [`convert`]); writes keep every other line and every
+//! untouched alias verbatim, and replace the file atomically
+//! (temporary file then rename), so readers never see a half-written
+//! file.
+//!
+//! Card ids are the alias keys. Card and addressbook tags are
+//! synthesized from the contents; the addressbook carries no
+//! description nor color.
+//!
+//! [`convert`]: crate::mutt_alias::convert
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    backend::AddressbookBackend,
+    capabilities::Capabilities,
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef, Contact,
+        ParseContactError,
+        file::write_atomic,
+        split_vcf,
+        tag::{content_tag, tag_matches},
+    },
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    mutt_alias::convert::{
+        Line, MuttAlias, addressbook_from_file, card_from_alias, card_ref_from_alias, is_valid_key,
+        key_from_contact, paginate, parse_file, render_file,
+    },
+};
+
+/// Errors surfaced by [`MuttAliasClient`].
+#[derive(Debug, Error)]
+pub enum MuttAliasClientError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Cannot create nor delete addressbooks of a mutt alias file")]
+    SingleFile,
+    #[error("Addressbook metadata of mutt alias files cannot be changed")]
+    ReadOnlyMetadata,
+    #[error("Addressbook `{0}` not found")]
+    AddressbookNotFound(String),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card body is empty")]
+    EmptyBody,
+    #[error("Card body is not a single vCard")]
+    InvalidCard,
+    #[error("Invalid card: {0}")]
+    ParseCard(ParseContactError),
+    #[error("Card has no email address, which a mutt alias needs")]
+    MissingEmail,
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{card_id}` changed (current tag: {actual})")]
+    PreconditionFailed { card_id: String, actual: String },
+}
+
+impl MuttAliasClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::AddressbookNotFound(_) | Self::CardNotFound(_) => ErrorKind::NotFound,
+            Self::PreconditionFailed { .. } => ErrorKind::Conflict,
+            Self::SingleFile
+            | Self::ReadOnlyMetadata
+            | Self::InvalidCardId(_)
+            | Self::EmptyBody
+            | Self::InvalidCard
+            | Self::ParseCard(_)
+            | Self::MissingEmail => ErrorKind::InvalidData,
+        }
+    }
+
+    /// Returns the current tag of the card when the error is a failed
+    /// `if_match` precondition.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::PreconditionFailed { actual, .. } => Some(actual),
+            _ => None,
+        }
+    }
+}
+
+/// Std-blocking mutt alias file client.
+#[derive(Clone, Debug)]
+pub struct MuttAliasClient {
+    path: PathBuf,
+}
+
+impl MuttAliasClient {
+    /// Builds a client exposing the mutt alias file at `path` as one
+    /// addressbook, whose id is the file name. A missing file reads as
+    /// an empty addressbook and is created on first write.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the file path the client was built with.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the static capabilities of the mutt alias backend.
+    ///
+    /// Tags are synthesized from the contents; there is no sync token
+    /// nor query engine, and no addressbook metadata to write.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            etag: true,
+            ctag: true,
+            sync_token: false,
+            query: false,
+            multiget: true,
+            vcard_versions: Vec::from(["4.0".to_string()]),
+            max_resource_size: None,
+            writable_metadata: Vec::new(),
+        }
+    }
+
+    /// Lists the single addressbook of the file, with the tag of the
+    /// file as `ctag`.
+    pub fn list_addressbooks(&self) -> Result<Vec<Addressbook>, MuttAliasClientError> {
+        let data = read_or_empty(&self.path)?;
+        Ok(Vec::from([addressbook_from_file(&self.id(), &data)]))
+    }
+
+    /// Always fails with [`MuttAliasClientError::SingleFile`]: a mutt
+    /// alias file holds exactly one addressbook.
+    pub fn create_addressbook(
+        &self,
+        _name: &str,
+        _description: Option<&str>,
+        _color: Option<&str>,
+    ) -> Result<String, MuttAliasClientError> {
+        Err(MuttAliasClientError::SingleFile)
+    }
+
+    /// Checks that the addressbook `id` exists. Any metadata change
+    /// fails with [`MuttAliasClientError::ReadOnlyMetadata`]: the name
+    /// is the file name, and there is no description nor color.
+    pub fn update_addressbook(
+        &self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), MuttAliasClientError> {
+        self.check_addressbook(id)?;
+
+        if patch.name.is_some() || patch.description.is_some() || patch.color.is_some() {
+            return Err(MuttAliasClientError::ReadOnlyMetadata);
+        }
+
+        Ok(())
+    }
+
+    /// Always fails with [`MuttAliasClientError::SingleFile`]: a mutt
+    /// alias file holds exactly one addressbook.
+    pub fn delete_addressbook(&self, _id: &str) -> Result<(), MuttAliasClientError> {
+        Err(MuttAliasClientError::SingleFile)
+    }
+
+    /// Lists cards inside `addressbook_id`, in file order, applying
+    /// 1-indexed pagination.
+    ///
+    /// When a key is defined several times, mutt keeps the last
+    /// definition, and so does this client.
+    pub fn list_cards(
+        &self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, MuttAliasClientError> {
+        let lines = self.load(addressbook_id)?;
+
+        let cards = aliases(&lines)
+            .map(|alias| card_from_alias(addressbook_id, alias))
+            .collect();
+
+        Ok(paginate(cards, page, page_size))
+    }
+
+    /// Lists the id and synthesized tag of every card in
+    /// `addressbook_id`, in file order.
+    pub fn list_card_refs(
+        &self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, MuttAliasClientError> {
+        let lines = self.load(addressbook_id)?;
+        Ok(aliases(&lines).map(card_ref_from_alias).collect())
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`,
+    /// evaluated locally over every card.
+    pub fn search_cards(
+        &self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, MuttAliasClientError> {
+        let mut cards = self.list_cards(addressbook_id, None, None)?;
+        cards.retain(|card| filter.matches(card));
+        Ok(cards)
+    }
+
+    /// Returns every card of `addressbook_id` as a full
+    /// [`CardChanges`] listing: there is no sync token, so
+    /// `sync_token` is ignored.
+    pub fn sync_cards(
+        &self,
+        addressbook_id: &str,
+        _sync_token: Option<&str>,
+    ) -> Result<CardChanges, MuttAliasClientError> {
+        Ok(CardChanges {
+            cards: self.list_cards(addressbook_id, None, None)?,
+            deleted: Vec::new(),
+            sync_token: None,
+            full: true,
+        })
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub fn get_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, MuttAliasClientError> {
+        self.list_cards(addressbook_id, None, None)?
+            .into_iter()
+            .find(|card| card.id == card_id)
+            .ok_or_else(|| MuttAliasClientError::CardNotFound(card_id.to_string()))
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id`, reading
+    /// the file once. Ids with no card end up in
+    /// [`CardBatch::missing`].
+    pub fn get_cards(
+        &self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, MuttAliasClientError> {
+        let cards = self.list_cards(addressbook_id, None, None)?;
+        let mut batch = CardBatch::default();
+
+        for card_id in card_ids {
+            match cards.iter().find(|card| card.id == *card_id) {
+                Some(card) => batch.cards.push(card.clone()),
+                None => batch.missing.push(card_id.to_string()),
+            }
+        }
+
+        batch.cards.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(batch)
+    }
+
+    /// Appends a new alias to `addressbook_id`. Returns its assigned id
+    /// and synthesized tag.
+    pub fn create_card(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, MuttAliasClientError> {
+        self.create_card_with_options(addressbook_id, contents, CardCreateOptions::default())
+    }
+
+    /// Appends a new alias to `addressbook_id`. Returns its assigned id
+    /// and synthesized tag.
+    ///
+    /// The alias key, thus the card id, derives from the `NICKNAME`,
+    /// else from the first email address, else from the name, with a
+    /// `-2`, `-3`… suffix when already taken, whatever `options` say.
+    pub fn create_card_with_options(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        _options: CardCreateOptions,
+    ) -> Result<CardRef, MuttAliasClientError> {
+        let contact = parse_contents(&contents)?;
+        let mut lines = self.load(addressbook_id)?;
+
+        let base = key_from_contact(&contact);
+        let mut key = base.clone();
+        let mut n = 1;
+
+        while aliases(&lines).any(|alias| alias.key == key) {
+            n += 1;
+            key = format!("{base}-{n}");
+        }
+
+        let alias =
+            MuttAlias::from_contact(&key, &contact).ok_or(MuttAliasClientError::MissingEmail)?;
+        let card_ref = card_ref_from_alias(&alias);
+
+        lines.push(Line::Alias(alias));
+        store(&self.path, &lines)?;
+
+        Ok(card_ref)
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id` in place, gating on
+    /// `if_match` when present. Without `if_match`, a missing card is
+    /// appended. Returns the card id and the tag of the new contents,
+    /// as read back from the alias.
+    ///
+    /// The alias keeps `card_id` as key, whatever the `NICKNAME` of the
+    /// contents.
+    pub fn update_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, MuttAliasClientError> {
+        if !is_valid_key(card_id) {
+            return Err(MuttAliasClientError::InvalidCardId(card_id.to_string()));
+        }
+
+        let contact = parse_contents(&contents)?;
+        let mut lines = self.load(addressbook_id)?;
+        let position = position(&lines, card_id);
+
+        if let Some(if_match) = if_match {
+            let Some(Line::Alias(current)) = position.map(|i| &lines[i]) else {
+                return Err(MuttAliasClientError::CardNotFound(card_id.to_string()));
+            };
+
+            check_precondition(card_id, &current.to_contact().to_bytes(), if_match)?;
+        }
+
+        let alias =
+            MuttAlias::from_contact(card_id, &contact).ok_or(MuttAliasClientError::MissingEmail)?;
+        let card_ref = card_ref_from_alias(&alias);
+
+        match position {
+            Some(position) => lines[position] = Line::Alias(alias),
+            None => lines.push(Line::Alias(alias)),
+        }
+
+        store(&self.path, &lines)?;
+        Ok(card_ref)
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating the card or overwriting it unconditionally.
+    pub fn upsert_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, MuttAliasClientError> {
+        self.update_card(addressbook_id, card_id, contents, None)
+    }
+
+    /// Removes `card_id` from `addressbook_id`, gating on `if_match`
+    /// when present. Every definition of the key goes, so that no
+    /// earlier one shows up again.
+    pub fn delete_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), MuttAliasClientError> {
+        let mut lines = self.load(addressbook_id)?;
+
+        let Some(Line::Alias(current)) = position(&lines, card_id).map(|i| &lines[i]) else {
+            return Err(MuttAliasClientError::CardNotFound(card_id.to_string()));
+        };
+
+        if let Some(if_match) = if_match {
+            check_precondition(card_id, &current.to_contact().to_bytes(), if_match)?;
+        }
+
+        lines.retain(|line| !matches!(line, Line::Alias(alias) if alias.key == card_id));
+        store(&self.path, &lines)
+    }
+
+    /// Returns the id of the single addressbook, the file name.
+    fn id(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Checks that `id` is the addressbook of the file.
+    fn check_addressbook(&self, id: &str) -> Result<(), MuttAliasClientError> {
+        if id != self.id() {
+            return Err(MuttAliasClientError::AddressbookNotFound(id.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Reads and parses the file of the addressbook `id`.
+    fn load(&self, id: &str) -> Result<Vec<Line>, MuttAliasClientError> {
+        self.check_addressbook(id)?;
+        let data = read_or_empty(&self.path)?;
+        Ok(parse_file(&String::from_utf8_lossy(&data)))
+    }
+}
+
+/// Iterates over the effective aliases of `lines`, in file order: the
+/// last definition of a key wins, as in mutt.
+fn aliases(lines: &[Line]) -> impl Iterator<Item = &MuttAlias> {
+    lines.iter().enumerate().filter_map(|(i, line)| match line {
+        Line::Alias(alias) if position(lines, &alias.key) == Some(i) => Some(alias),
+        _ => None,
+    })
+}
+
+/// Returns the index of the effective definition of the alias `key`.
+fn position(lines: &[Line], key: &str) -> Option<usize> {
+    lines
+        .iter()
+        .rposition(|line| matches!(line, Line::Alias(alias) if alias.key == key))
+}
+
+/// Reads the file at `path`, a missing file reading as empty.
+fn read_or_empty(path: &Path) -> Result<Vec<u8>, MuttAliasClientError> {
+    match fs::read(path) {
+        Ok(data) => Ok(data),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Parses `contents` holding exactly one vCard.
+fn parse_contents(contents: &[u8]) -> Result<Contact, MuttAliasClientError> {
+    if contents.is_empty() {
+        return Err(MuttAliasClientError::EmptyBody);
+    }
+
+    if split_vcf(contents).len() != 1 {
+        return Err(MuttAliasClientError::InvalidCard);
+    }
+
+    Contact::from_bytes(contents).map_err(MuttAliasClientError::ParseCard)
+}
+
+/// Checks `if_match` against the tag of the current `contents` of
+/// `card_id`.
+fn check_precondition(
+    card_id: &str,
+    contents: &[u8],
+    if_match: &str,
+) -> Result<(), MuttAliasClientError> {
+    let actual = content_tag(contents);
+
+    if tag_matches(if_match, &actual) {
+        return Ok(());
+    }
+
+    Err(MuttAliasClientError::PreconditionFailed {
+        card_id: card_id.to_string(),
+        actual,
+    })
+}
+
+/// Rewrites the file at `path` with `lines`, atomically.
+fn store(path: &Path, lines: &[Line]) -> Result<(), MuttAliasClientError> {
+    Ok(write_atomic(path, render_file(lines).as_bytes())?)
+}
+
+impl AddressbookBackend for MuttAliasClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(MuttAliasClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(MuttAliasClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(MuttAliasClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(MuttAliasClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(MuttAliasClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(MuttAliasClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(MuttAliasClient::create_card(
+            self,
+            addressbook_id,
+            contents,
+        )?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(MuttAliasClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(MuttAliasClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(MuttAliasClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(MuttAliasClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(MuttAliasClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(MuttAliasClient::sync_cards(
+            self,
+            addressbook_id,
+            sync_token,
+        )?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(MuttAliasClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(MuttAliasClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(MuttAliasClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
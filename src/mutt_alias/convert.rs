@@ -0,0 +1,625 @@
+//! Conversions between mutt alias files and the shared types used by
+//! [`MuttAliasClient`], plus the `From` impl that wraps it into the
+//! unified client's mutt alias variant.
+//!
+//! An alias line reads `alias [-group <name>]… <key> <address>[,
+//! <address>…] [# comment]`. The key maps to `NICKNAME` (and is the
+//! card id), the display name of the first address to `FN`, every
+//! address to an `EMAIL`, the groups to `CATEGORIES` and the trailing
+//! comment to `NOTE`. Every other vCard property has no alias
+//! counterpart and is dropped.
+//!
+//! [`MuttAliasClient`]: crate::mutt_alias::client::MuttAliasClient
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    addressbook::Addressbook,
+    card::{Card, CardRef, Contact, Email, Property, StructuredName, escape, tag::content_tag},
+};
+
+impl From<crate::mutt_alias::client::MuttAliasClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::mutt_alias::client::MuttAliasClient) -> Self {
+        Self::MuttAlias(client)
+    }
+}
+
+/// Characters requiring a display name to be quoted (RFC 5322
+/// specials).
+const SPECIALS: &[char] = &[
+    '(', ')', '<', '>', '[', ']', ':', ';', '@', '\\', ',', '.', '"',
+];
+
+/// Line of a mutt alias file.
+#[derive(Clone, Debug)]
+pub(crate) enum Line {
+    Alias(MuttAlias),
+    /// Any other line (comment, blank line, other command), kept
+    /// verbatim.
+    Other(String),
+}
+
+/// Address of an alias, with its optional display name.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct MailAddress {
+    pub name: Option<String>,
+    pub address: String,
+}
+
+/// Parsed `alias` command.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MuttAlias {
+    pub key: String,
+    pub groups: Vec<String>,
+    pub addresses: Vec<MailAddress>,
+    pub comment: Option<String>,
+    /// Source line(s), rendered back as is while the alias is left
+    /// untouched.
+    pub raw: Option<String>,
+}
+
+/// Parses the contents of a mutt alias file into lines.
+///
+/// Lines ending with a backslash continue on the next one. Lines that
+/// are not a well-formed `alias` command are kept verbatim.
+pub(crate) fn parse_file(data: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut raw = String::new();
+
+    for line in data.lines() {
+        if !raw.is_empty() {
+            raw.push('\n');
+        }
+
+        raw.push_str(line);
+
+        if line.ends_with('\\') && !line.ends_with("\\\\") {
+            continue;
+        }
+
+        let logical = raw.replace("\\\n", " ");
+
+        let line = match MuttAlias::parse(&logical) {
+            Some(mut alias) => {
+                alias.raw = Some(core::mem::take(&mut raw));
+                Line::Alias(alias)
+            }
+            None => Line::Other(core::mem::take(&mut raw)),
+        };
+
+        lines.push(line);
+    }
+
+    if !raw.is_empty() {
+        lines.push(Line::Other(raw));
+    }
+
+    lines
+}
+
+/// Renders lines back into the contents of a mutt alias file.
+pub(crate) fn render_file(lines: &[Line]) -> String {
+    let mut out = String::new();
+
+    for line in lines {
+        match line {
+            Line::Alias(alias) => out.push_str(&alias.render()),
+            Line::Other(raw) => out.push_str(raw),
+        }
+
+        out.push('\n');
+    }
+
+    out
+}
+
+impl MuttAlias {
+    /// Parses one logical `alias` line.
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        let (line, comment) = split_comment(line);
+        let rest = line.trim_start().strip_prefix("alias")?;
+
+        if !rest.starts_with([' ', '\t']) {
+            return None;
+        }
+
+        let mut alias = Self {
+            comment: comment
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(ToString::to_string),
+            ..Default::default()
+        };
+
+        let mut rest = rest.trim_start();
+
+        loop {
+            let (token, tail) = next_token(rest);
+
+            if token == "-group" {
+                let (group, tail) = next_token(tail);
+
+                if group.is_empty() {
+                    return None;
+                }
+
+                alias.groups.push(group.to_string());
+                rest = tail;
+            } else {
+                alias.key = token.to_string();
+                rest = tail;
+                break;
+            }
+        }
+
+        if !is_valid_key(&alias.key) {
+            return None;
+        }
+
+        alias.addresses = split_addresses(rest)
+            .into_iter()
+            .map(parse_address)
+            .filter(|addr| !addr.address.is_empty())
+            .collect();
+
+        if alias.addresses.is_empty() {
+            return None;
+        }
+
+        Some(alias)
+    }
+
+    /// Renders the alias, as read when left untouched.
+    pub(crate) fn render(&self) -> String {
+        if let Some(raw) = &self.raw {
+            return raw.clone();
+        }
+
+        let mut line = String::from("alias ");
+
+        for group in &self.groups {
+            line.push_str("-group ");
+            line.push_str(group);
+            line.push(' ');
+        }
+
+        line.push_str(&self.key);
+        line.push(' ');
+
+        let addresses: Vec<String> = self.addresses.iter().map(render_address).collect();
+        line.push_str(&addresses.join(", "));
+
+        if let Some(comment) = &self.comment {
+            line.push_str(" # ");
+            line.push_str(comment);
+        }
+
+        line
+    }
+
+    /// Maps the alias to a typed [`Contact`].
+    pub(crate) fn to_contact(&self) -> Contact {
+        let mut contact = Contact::new();
+
+        let name = self
+            .addresses
+            .iter()
+            .find_map(|addr| addr.name.clone())
+            .unwrap_or_else(|| self.key.clone());
+
+        contact.formatted_name = Some(name);
+        contact.properties.push(Property {
+            name: "NICKNAME".to_string(),
+            value: escape(&self.key),
+            ..Default::default()
+        });
+
+        contact.emails = self
+            .addresses
+            .iter()
+            .map(|addr| Email {
+                address: addr.address.clone(),
+                ..Default::default()
+            })
+            .collect();
+
+        contact.notes = self.comment.clone().into_iter().collect();
+        contact.categories = self.groups.clone();
+        contact
+    }
+
+    /// Maps a typed [`Contact`] to the alias `key`.
+    ///
+    /// The display name goes to the first address. Returns `None`
+    /// when the contact has no email address, which an alias needs.
+    pub(crate) fn from_contact(key: &str, contact: &Contact) -> Option<Self> {
+        let name = contact_name(contact);
+
+        let addresses: Vec<MailAddress> = contact
+            .emails
+            .iter()
+            .map(|email| single_line(&email.address))
+            .filter(|address| !address.is_empty())
+            .enumerate()
+            .map(|(i, address)| MailAddress {
+                name: name.clone().filter(|_| i == 0),
+                address,
+            })
+            .collect();
+
+        if addresses.is_empty() {
+            return None;
+        }
+
+        let comment = single_line(&contact.notes.join(" "));
+
+        let groups = contact
+            .categories
+            .iter()
+            .map(|group| sanitize(group, false))
+            .filter(|group| !group.is_empty())
+            .collect();
+
+        Some(Self {
+            key: key.to_string(),
+            groups,
+            addresses,
+            comment: Some(comment).filter(|c| !c.is_empty()),
+            raw: None,
+        })
+    }
+}
+
+/// Tells whether `key` can be the key of an alias, and thus a card id.
+pub(crate) fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('-')
+        && !key.contains(|c: char| {
+            c.is_whitespace() || c.is_control() || matches!(c, '#' | '\\' | '"' | '\'' | ',' | '/')
+        })
+}
+
+/// Derives an alias key from a contact: its nickname, else the local
+/// part of its first email, else its name.
+pub(crate) fn key_from_contact(contact: &Contact) -> String {
+    let nickname = contact
+        .properties
+        .iter()
+        .find(|prop| prop.name == "NICKNAME")
+        .and_then(|prop| prop.text().split(',').next().map(ToString::to_string))
+        .map(|nick| sanitize(&nick, false));
+
+    if let Some(nick) = nickname.filter(|nick| is_valid_key(nick)) {
+        return nick;
+    }
+
+    let local = contact
+        .emails
+        .first()
+        .map(|email| email.address.split('@').next().unwrap_or_default())
+        .map(|local| sanitize(local, true));
+
+    if let Some(local) = local.filter(|local| is_valid_key(local)) {
+        return local;
+    }
+
+    let name = contact_name(contact).unwrap_or_default();
+    let name = sanitize(&name, true);
+
+    if is_valid_key(&name) {
+        name
+    } else {
+        "alias".to_string()
+    }
+}
+
+/// Maps the mutt alias file of `id` to a shared [`Addressbook`].
+///
+/// The id doubles as the name; the `ctag` is the tag of the whole
+/// file, so that any alias change bumps it.
+pub(crate) fn addressbook_from_file(id: &str, data: &[u8]) -> Addressbook {
+    Addressbook {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: None,
+        color: None,
+        ctag: Some(content_tag(data)),
+        sync_token: None,
+    }
+}
+
+/// Maps an alias to a shared [`Card`].
+pub(crate) fn card_from_alias(addressbook_id: &str, alias: &MuttAlias) -> Card {
+    let contents = alias.to_contact().to_bytes();
+
+    Card {
+        id: alias.key.clone(),
+        addressbook_id: addressbook_id.to_string(),
+        etag: Some(content_tag(&contents)),
+        contents,
+    }
+}
+
+/// Maps an alias to a [`CardRef`].
+pub(crate) fn card_ref_from_alias(alias: &MuttAlias) -> CardRef {
+    CardRef {
+        id: alias.key.clone(),
+        href: None,
+        etag: Some(content_tag(&alias.to_contact().to_bytes())),
+    }
+}
+
+/// 1-indexed pagination on an in-memory list. `page_size = None`
+/// returns the full slice; `page_size = 0` or a page past the end
+/// returns an empty vector.
+pub(crate) fn paginate<T>(items: Vec<T>, page: Option<u32>, page_size: Option<u32>) -> Vec<T> {
+    let Some(size) = page_size else {
+        return items;
+    };
+
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let page = page.unwrap_or(1).max(1);
+    let skip = ((page - 1) as usize).saturating_mul(size as usize);
+
+    if skip >= items.len() {
+        return Vec::new();
+    }
+
+    items.into_iter().skip(skip).take(size as usize).collect()
+}
+
+/// Splits a line at the first `#` outside quotes and angle brackets.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut angle = false;
+
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            '<' if !quoted => angle = true,
+            '>' if !quoted => angle = false,
+            '#' if !quoted && !angle => return (&line[..i], Some(&line[i + 1..])),
+            _ => (),
+        }
+    }
+
+    (line, None)
+}
+
+/// Returns the next whitespace-separated token and the rest, trimmed.
+fn next_token(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    (&s[..end], s[end..].trim_start())
+}
+
+/// Splits an address list at the commas outside quotes, angle brackets
+/// and comments.
+fn split_addresses(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut depth = 0usize;
+
+    for (i, c) in list.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            '<' | '(' if !quoted => depth += 1,
+            '>' | ')' if !quoted => depth = depth.saturating_sub(1),
+            ',' if !quoted && depth == 0 => {
+                parts.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    parts.push(list[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// Parses `Name <address>`, `address (Name)` or a bare address.
+fn parse_address(s: &str) -> MailAddress {
+    let s = s.trim();
+
+    if let (Some(lt), true) = (s.rfind('<'), s.ends_with('>')) {
+        return MailAddress {
+            name: unquote(&s[..lt]),
+            address: s[lt + 1..s.len() - 1].trim().to_string(),
+        };
+    }
+
+    if let (Some(lp), true) = (s.find('('), s.ends_with(')')) {
+        return MailAddress {
+            name: unquote(&s[lp + 1..s.len() - 1]),
+            address: s[..lp].trim().to_string(),
+        };
+    }
+
+    MailAddress {
+        name: None,
+        address: s.to_string(),
+    }
+}
+
+/// Renders an address, quoting its display name when needed.
+fn render_address(addr: &MailAddress) -> String {
+    let Some(name) = &addr.name else {
+        return addr.address.clone();
+    };
+
+    if name.contains(SPECIALS) {
+        let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("\"{name}\" <{}>", addr.address)
+    } else {
+        format!("{name} <{}>", addr.address)
+    }
+}
+
+/// Strips the quotes of a display name and unescapes it.
+fn unquote(name: &str) -> Option<String> {
+    let name = name.trim();
+    let name = match name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+        Some(inner) => inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => name.to_string(),
+    };
+
+    Some(name).filter(|n| !n.is_empty())
+}
+
+/// Returns the formatted name of a contact, composed from `N` when
+/// there is no `FN`.
+fn contact_name(contact: &Contact) -> Option<String> {
+    contact
+        .formatted_name
+        .clone()
+        .or_else(|| contact.name.as_ref().map(composed_name))
+        .map(|name| single_line(&name))
+        .filter(|name| !name.is_empty())
+}
+
+/// Composes a name from the structured name components.
+fn composed_name(name: &StructuredName) -> String {
+    [
+        &name.prefixes,
+        &name.given,
+        &name.additional,
+        &name.family,
+        &name.suffixes,
+    ]
+    .into_iter()
+    .filter(|p| !p.is_empty())
+    .map(String::as_str)
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Flattens line breaks, which alias lines cannot hold.
+fn single_line(value: &str) -> String {
+    value
+        .split(['\r', '\n'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Keeps the characters valid in a key (ASCII alphanumerics and `-`,
+/// `_`, `.`, `@`, `+`), replacing runs of others with `-`.
+fn sanitize(value: &str, lowercase: bool) -> String {
+    let mut out = String::new();
+
+    for c in value.trim().chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '@' | '+') {
+            out.push(if lowercase { c.to_ascii_lowercase() } else { c });
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+
+    out.trim_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "alias -group friends alice \"Alice P. Liddell\" <alice@example.org>, \
+        alice@work.example # met at a tea party";
+
+    #[test]
+    fn alias_to_contact() {
+        let contact = MuttAlias::parse(ALICE).unwrap().to_contact();
+
+        assert_eq!(contact.formatted_name.as_deref(), Some("Alice P. Liddell"));
+        assert_eq!(key_from_contact(&contact), "alice");
+
+        let emails: Vec<&str> = contact.emails.iter().map(|e| e.address.as_str()).collect();
+        assert_eq!(emails, ["alice@example.org", "alice@work.example"]);
+
+        assert_eq!(contact.notes, ["met at a tea party"]);
+        assert_eq!(contact.categories, ["friends"]);
+    }
+
+    #[test]
+    fn round_trip_alias_through_vcard() {
+        let contact = MuttAlias::parse(ALICE).unwrap().to_contact();
+        let contact = Contact::parse(&contact.to_string()).unwrap();
+        let alias = MuttAlias::from_contact(&key_from_contact(&contact), &contact).unwrap();
+
+        assert_eq!(alias.render(), ALICE);
+    }
+
+    #[test]
+    fn contact_to_alias() {
+        let vcard = "BEGIN:VCARD\r\n\
+            VERSION:4.0\r\n\
+            N:Hatter;Mad;;;\r\n\
+            EMAIL:hatter@example.org\r\n\
+            EMAIL:tea@example.org\r\n\
+            NOTE:Always\\nsix o'clock\r\n\
+            CATEGORIES:tea party\r\n\
+            END:VCARD\r\n";
+
+        let contact = Contact::parse(vcard).unwrap();
+        let key = key_from_contact(&contact);
+
+        assert_eq!(key, "hatter");
+        assert_eq!(
+            MuttAlias::from_contact(&key, &contact).unwrap().render(),
+            "alias -group tea-party hatter Mad Hatter <hatter@example.org>, tea@example.org \
+            # Always six o'clock"
+        );
+    }
+
+    #[test]
+    fn skip_contact_without_email() {
+        let vcard = "BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Cheshire Cat\r\nEND:VCARD\r\n";
+        let contact = Contact::parse(vcard).unwrap();
+
+        assert!(MuttAlias::from_contact("cat", &contact).is_none());
+    }
+
+    #[test]
+    fn round_trip_file() {
+        let data = "# aliases\n\
+            set reverse_alias\n\
+            alias bob Bob <bob@example.org>\n\
+            alias team alice@example.org, \\\n  bob@example.org\n\
+            alias broken\n";
+
+        let mut lines = parse_file(data);
+
+        assert!(matches!(&lines[0], Line::Other(raw) if raw == "# aliases"));
+        assert!(matches!(&lines[4], Line::Other(raw) if raw == "alias broken"));
+        assert_eq!(render_file(&lines), data);
+
+        let Line::Alias(team) = &mut lines[3] else {
+            panic!("expected an alias");
+        };
+
+        assert_eq!(team.addresses.len(), 2);
+
+        team.raw = None;
+        team.comment = Some("core".to_string());
+
+        assert_eq!(
+            render_file(&lines[3..4]),
+            "alias team alice@example.org, bob@example.org # core\n"
+        );
+    }
+}
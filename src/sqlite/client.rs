@@ -0,0 +1,903 @@
+//! SQLite addressbook client.
+//!
+//! [`SqliteClient`] stores addressbooks and raw vCard bytes in a single
+//! SQLite database, next to an index of the `FN`, `EMAIL`, `TEL` and
+//! `UID` values of every card. Listing an addressbook is one query
+//! rather than one file read per card, and [`SqliteClient::search_cards`]
+//! answers filters on indexed properties from the index. Card tags are
+//! synthesized from the contents, as for vdir; addressbook `ctag`s come
+//! from a revision counter bumped on every change.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::path::Path;
+
+use rusqlite::{Connection, ErrorCode, OptionalExtension, Transaction, params, params_from_iter};
+use thiserror::Error;
+
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    backend::AddressbookBackend,
+    capabilities::{AddressbookField, Capabilities},
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
+        tag::{content_tag, tag_matches},
+        uid::{card_id_from_uid, find_uid, fresh_card_id, inject_uid},
+    },
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    sqlite::convert::{SCHEMA, addressbook_from_row, card_from_row, filter_query, indexed_fields},
+};
+
+/// Errors surfaced by [`SqliteClient`].
+#[derive(Debug, Error)]
+pub enum SqliteClientError {
+    #[error("SQLite database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Failed to gather randomness for new card id: {0}")]
+    Random(getrandom::Error),
+    #[error("Invalid addressbook name")]
+    InvalidName,
+    #[error("Addressbook `{0}` already exists")]
+    AddressbookAlreadyExists(String),
+    #[error("Addressbook `{0}` not found")]
+    AddressbookNotFound(String),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card body is empty")]
+    EmptyBody,
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{0}` already exists")]
+    CardAlreadyExists(String),
+    #[error("Card `{card_id}` changed (current tag: {actual})")]
+    PreconditionFailed { card_id: String, actual: String },
+}
+
+impl SqliteClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::AddressbookNotFound(_) | Self::CardNotFound(_) => ErrorKind::NotFound,
+            Self::AddressbookAlreadyExists(_)
+            | Self::CardAlreadyExists(_)
+            | Self::PreconditionFailed { .. } => ErrorKind::Conflict,
+            Self::InvalidName | Self::InvalidCardId(_) | Self::EmptyBody => ErrorKind::InvalidData,
+            Self::Sqlite(err) => match err.sqlite_error_code() {
+                Some(ErrorCode::DiskFull) => ErrorKind::InsufficientStorage,
+                Some(ErrorCode::ReadOnly | ErrorCode::PermissionDenied) => ErrorKind::Forbidden,
+                Some(ErrorCode::ConstraintViolation) => ErrorKind::Conflict,
+                _ => ErrorKind::Other,
+            },
+            Self::Random(_) => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current tag of the card when the error is a failed
+    /// `if_match` precondition.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::PreconditionFailed { actual, .. } => Some(actual),
+            _ => None,
+        }
+    }
+}
+
+/// SQLite addressbook client.
+#[derive(Debug)]
+pub struct SqliteClient {
+    conn: Connection,
+}
+
+impl SqliteClient {
+    /// Opens (or creates) the database at `path` and applies the
+    /// schema.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SqliteClientError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Opens a private in-memory database, dropped with the client.
+    pub fn open_in_memory() -> Result<Self, SqliteClientError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Wraps an already opened connection, applying the schema.
+    pub fn from_connection(conn: Connection) -> Result<Self, SqliteClientError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Returns the underlying connection.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Returns the static capabilities of the SQLite backend.
+    ///
+    /// Searches on indexed properties run inside the database, hence
+    /// `query`; there is no sync token.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            etag: true,
+            ctag: true,
+            sync_token: false,
+            query: true,
+            multiget: true,
+            vcard_versions: Vec::from(["3.0".to_string(), "4.0".to_string()]),
+            max_resource_size: None,
+            writable_metadata: AddressbookField::ALL.to_vec(),
+        }
+    }
+
+    /// Lists every addressbook, sorted by name, each with its
+    /// revision as `ctag`.
+    pub fn list_addressbooks(&self) -> Result<Vec<Addressbook>, SqliteClientError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, description, color, revision FROM addressbooks ORDER BY name",
+        )?;
+
+        let addressbooks = stmt
+            .query_map([], addressbook_from_row)?
+            .collect::<Result<_, _>>()?;
+
+        Ok(addressbooks)
+    }
+
+    /// Creates an addressbook named `name`, also used as its id.
+    /// Returns the new addressbook id.
+    pub fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, SqliteClientError> {
+        if name.is_empty() {
+            return Err(SqliteClientError::InvalidName);
+        }
+
+        let tx = self.conn.transaction()?;
+
+        if addressbook_exists(&tx, name)? {
+            return Err(SqliteClientError::AddressbookAlreadyExists(
+                name.to_string(),
+            ));
+        }
+
+        tx.execute(
+            "INSERT INTO addressbooks (id, name, description, color, revision) \
+             VALUES (?1, ?1, ?2, ?3, 0)",
+            params![name, description, color],
+        )?;
+
+        bump_revision(&tx, name)?;
+        tx.commit()?;
+
+        Ok(name.to_string())
+    }
+
+    /// Applies `patch` to the addressbook identified by `id`.
+    pub fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), SqliteClientError> {
+        let tx = self.conn.transaction()?;
+
+        if !addressbook_exists(&tx, id)? {
+            return Err(SqliteClientError::AddressbookNotFound(id.to_string()));
+        }
+
+        if let Some(name) = patch.name {
+            tx.execute(
+                "UPDATE addressbooks SET name = ?2 WHERE id = ?1",
+                params![id, name],
+            )?;
+        }
+
+        if let Some(description) = patch.description {
+            tx.execute(
+                "UPDATE addressbooks SET description = ?2 WHERE id = ?1",
+                params![id, description],
+            )?;
+        }
+
+        if let Some(color) = patch.color {
+            tx.execute(
+                "UPDATE addressbooks SET color = ?2 WHERE id = ?1",
+                params![id, color],
+            )?;
+        }
+
+        bump_revision(&tx, id)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Removes the addressbook identified by `id`, its cards and their
+    /// index entries.
+    pub fn delete_addressbook(&mut self, id: &str) -> Result<(), SqliteClientError> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM addressbooks WHERE id = ?1", [id])?;
+
+        if deleted == 0 {
+            return Err(SqliteClientError::AddressbookNotFound(id.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Lists cards inside `addressbook_id`, sorted by id, applying
+    /// 1-indexed pagination in the query.
+    pub fn list_cards(
+        &self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, SqliteClientError> {
+        self.ensure_addressbook(addressbook_id)?;
+
+        // NOTE: a negative LIMIT means no limit.
+        let (limit, offset) = match page_size {
+            None => (-1, 0),
+            Some(0) => return Ok(Vec::new()),
+            Some(size) => {
+                let page = page.unwrap_or(1).max(1);
+                (size as i64, (page as i64 - 1) * size as i64)
+            }
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, etag, contents FROM cards WHERE addressbook_id = ?1 \
+             ORDER BY id LIMIT ?2 OFFSET ?3",
+        )?;
+
+        let cards = stmt
+            .query_map(params![addressbook_id, limit, offset], |row| {
+                card_from_row(addressbook_id, row)
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(cards)
+    }
+
+    /// Lists the id and synthesized tag of every card in
+    /// `addressbook_id`, sorted by id, without reading card contents.
+    pub fn list_card_refs(&self, addressbook_id: &str) -> Result<Vec<CardRef>, SqliteClientError> {
+        self.ensure_addressbook(addressbook_id)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, etag FROM cards WHERE addressbook_id = ?1 ORDER BY id")?;
+
+        let refs = stmt
+            .query_map([addressbook_id], |row| {
+                Ok(CardRef {
+                    id: row.get(0)?,
+                    href: None,
+                    etag: Some(row.get(1)?),
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(refs)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`.
+    ///
+    /// Filters on `FN`, `EMAIL`, `TEL` and `UID` text matches select
+    /// their candidates from the index; any other filter falls back to
+    /// evaluating every card locally. Either way, the result is sorted
+    /// by id.
+    pub fn search_cards(
+        &self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, SqliteClientError> {
+        let Some((query, params)) = filter_query(filter) else {
+            let mut cards = self.list_cards(addressbook_id, None, None)?;
+            cards.retain(|card| filter.matches(card));
+            return Ok(cards);
+        };
+
+        self.ensure_addressbook(addressbook_id)?;
+
+        let sql = format!(
+            "SELECT id, etag, contents FROM cards WHERE addressbook_id = ?1 \
+             AND id IN ({query}) ORDER BY id"
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let params = core::iter::once(addressbook_id.to_string()).chain(params);
+
+        let mut cards: Vec<Card> = stmt
+            .query_map(params_from_iter(params), |row| {
+                card_from_row(addressbook_id, row)
+            })?
+            .collect::<Result<_, _>>()?;
+
+        cards.retain(|card| filter.matches(card));
+        Ok(cards)
+    }
+
+    /// Returns every card of `addressbook_id` as a full
+    /// [`CardChanges`] listing: there is no sync token, so
+    /// `sync_token` is ignored.
+    pub fn sync_cards(
+        &self,
+        addressbook_id: &str,
+        _sync_token: Option<&str>,
+    ) -> Result<CardChanges, SqliteClientError> {
+        Ok(CardChanges {
+            cards: self.list_cards(addressbook_id, None, None)?,
+            deleted: Vec::new(),
+            sync_token: None,
+            full: true,
+        })
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub fn get_card(&self, addressbook_id: &str, card_id: &str) -> Result<Card, SqliteClientError> {
+        self.ensure_addressbook(addressbook_id)?;
+
+        self.conn
+            .query_row(
+                "SELECT id, etag, contents FROM cards WHERE addressbook_id = ?1 AND id = ?2",
+                [addressbook_id, card_id],
+                |row| card_from_row(addressbook_id, row),
+            )
+            .optional()?
+            .ok_or_else(|| SqliteClientError::CardNotFound(card_id.to_string()))
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id`. Ids with
+    /// no card end up in [`CardBatch::missing`].
+    pub fn get_cards(
+        &self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, SqliteClientError> {
+        self.ensure_addressbook(addressbook_id)?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, etag, contents FROM cards WHERE addressbook_id = ?1 AND id = ?2",
+        )?;
+
+        let mut batch = CardBatch::default();
+
+        for id in card_ids {
+            let card = stmt
+                .query_row([addressbook_id, id], |row| {
+                    card_from_row(addressbook_id, row)
+                })
+                .optional()?;
+
+            match card {
+                Some(card) => batch.cards.push(card),
+                None => batch.missing.push(id.to_string()),
+            }
+        }
+
+        batch.cards.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(batch)
+    }
+
+    /// Adds a new vCard to `addressbook_id` with the default
+    /// [`CardCreateOptions`]. Returns its assigned id and synthesized
+    /// tag.
+    pub fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, SqliteClientError> {
+        self.create_card_with_options(addressbook_id, contents, CardCreateOptions::default())
+    }
+
+    /// Adds a new vCard to `addressbook_id` with the given `options`,
+    /// failing when a card with the same id exists. Returns its
+    /// assigned id and synthesized tag.
+    pub fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        mut contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, SqliteClientError> {
+        if contents.is_empty() {
+            return Err(SqliteClientError::EmptyBody);
+        }
+
+        let fresh_card_id = || fresh_card_id().map_err(SqliteClientError::Random);
+
+        let id = match options.id_from_uid.then(|| find_uid(&contents)) {
+            Some(Some(Some(uid))) => card_id_from_uid(&uid),
+            Some(Some(None)) => {
+                let uuid = fresh_card_id()?;
+                contents = inject_uid(&contents, &format!("urn:uuid:{uuid}"));
+                uuid
+            }
+            Some(None) | None => fresh_card_id()?,
+        };
+
+        let tx = self.conn.transaction()?;
+
+        if !addressbook_exists(&tx, addressbook_id)? {
+            return Err(SqliteClientError::AddressbookNotFound(
+                addressbook_id.to_string(),
+            ));
+        }
+
+        if current_tag(&tx, addressbook_id, &id)?.is_some() {
+            return Err(SqliteClientError::CardAlreadyExists(id));
+        }
+
+        let etag = write_card(&tx, addressbook_id, &id, &contents)?;
+        tx.commit()?;
+
+        Ok(CardRef {
+            id,
+            href: None,
+            etag: Some(etag),
+        })
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id`, gating on
+    /// `if_match` when present. Without `if_match`, a missing card is
+    /// created. Returns the card id and the tag of the new contents.
+    pub fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, SqliteClientError> {
+        if card_id.is_empty() {
+            return Err(SqliteClientError::InvalidCardId(String::new()));
+        }
+
+        if contents.is_empty() {
+            return Err(SqliteClientError::EmptyBody);
+        }
+
+        let tx = self.conn.transaction()?;
+
+        if !addressbook_exists(&tx, addressbook_id)? {
+            return Err(SqliteClientError::AddressbookNotFound(
+                addressbook_id.to_string(),
+            ));
+        }
+
+        if let Some(if_match) = if_match {
+            check_precondition(&tx, addressbook_id, card_id, if_match)?;
+        }
+
+        let etag = write_card(&tx, addressbook_id, card_id, &contents)?;
+        tx.commit()?;
+
+        Ok(CardRef {
+            id: card_id.to_string(),
+            href: None,
+            etag: Some(etag),
+        })
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating the card or overwriting it unconditionally.
+    pub fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, SqliteClientError> {
+        self.update_card(addressbook_id, card_id, contents, None)
+    }
+
+    /// Removes `card_id` from `addressbook_id`, gating on `if_match`
+    /// when present.
+    pub fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), SqliteClientError> {
+        let tx = self.conn.transaction()?;
+
+        if !addressbook_exists(&tx, addressbook_id)? {
+            return Err(SqliteClientError::AddressbookNotFound(
+                addressbook_id.to_string(),
+            ));
+        }
+
+        if let Some(if_match) = if_match {
+            check_precondition(&tx, addressbook_id, card_id, if_match)?;
+        }
+
+        let deleted = tx.execute(
+            "DELETE FROM cards WHERE addressbook_id = ?1 AND id = ?2",
+            [addressbook_id, card_id],
+        )?;
+
+        if deleted == 0 {
+            return Err(SqliteClientError::CardNotFound(card_id.to_string()));
+        }
+
+        bump_revision(&tx, addressbook_id)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn ensure_addressbook(&self, id: &str) -> Result<(), SqliteClientError> {
+        if addressbook_exists(&self.conn, id)? {
+            Ok(())
+        } else {
+            Err(SqliteClientError::AddressbookNotFound(id.to_string()))
+        }
+    }
+}
+
+fn addressbook_exists(conn: &Connection, id: &str) -> Result<bool, SqliteClientError> {
+    let exists = conn
+        .query_row("SELECT 1 FROM addressbooks WHERE id = ?1", [id], |_| Ok(()))
+        .optional()?
+        .is_some();
+
+    Ok(exists)
+}
+
+fn current_tag(
+    conn: &Connection,
+    addressbook_id: &str,
+    card_id: &str,
+) -> Result<Option<String>, SqliteClientError> {
+    let tag = conn
+        .query_row(
+            "SELECT etag FROM cards WHERE addressbook_id = ?1 AND id = ?2",
+            [addressbook_id, card_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(tag)
+}
+
+/// Stamps the next global revision on `addressbook_id`, bumping its
+/// `ctag`.
+fn bump_revision(tx: &Transaction<'_>, addressbook_id: &str) -> Result<(), SqliteClientError> {
+    tx.execute("UPDATE meta SET revision = revision + 1", [])?;
+    tx.execute(
+        "UPDATE addressbooks SET revision = (SELECT revision FROM meta) WHERE id = ?1",
+        [addressbook_id],
+    )?;
+    Ok(())
+}
+
+/// Inserts or replaces card `card_id` along with its index entries,
+/// bumping the addressbook revision. Returns the tag of `contents`.
+fn write_card(
+    tx: &Transaction<'_>,
+    addressbook_id: &str,
+    card_id: &str,
+    contents: &[u8],
+) -> Result<String, SqliteClientError> {
+    let etag = content_tag(contents);
+
+    tx.execute(
+        "INSERT INTO cards (addressbook_id, id, etag, contents) VALUES (?1, ?2, ?3, ?4) \
+         ON CONFLICT (addressbook_id, id) \
+         DO UPDATE SET etag = excluded.etag, contents = excluded.contents",
+        params![addressbook_id, card_id, etag, contents],
+    )?;
+
+    tx.execute(
+        "DELETE FROM card_fields WHERE addressbook_id = ?1 AND card_id = ?2",
+        [addressbook_id, card_id],
+    )?;
+
+    let mut stmt = tx.prepare(
+        "INSERT INTO card_fields (addressbook_id, card_id, name, value) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+
+    for (name, value) in indexed_fields(contents) {
+        stmt.execute(params![addressbook_id, card_id, name, value])?;
+    }
+
+    bump_revision(tx, addressbook_id)?;
+    Ok(etag)
+}
+
+/// Checks `if_match` against the current tag of `card_id`. A missing
+/// card fails with [`SqliteClientError::CardNotFound`].
+fn check_precondition(
+    conn: &Connection,
+    addressbook_id: &str,
+    card_id: &str,
+    if_match: &str,
+) -> Result<(), SqliteClientError> {
+    let Some(actual) = current_tag(conn, addressbook_id, card_id)? else {
+        return Err(SqliteClientError::CardNotFound(card_id.to_string()));
+    };
+
+    if tag_matches(if_match, &actual) {
+        return Ok(());
+    }
+
+    Err(SqliteClientError::PreconditionFailed {
+        card_id: card_id.to_string(),
+        actual,
+    })
+}
+
+impl AddressbookBackend for SqliteClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(SqliteClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(SqliteClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(SqliteClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(SqliteClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(SqliteClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(SqliteClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(SqliteClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(SqliteClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(SqliteClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(SqliteClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(SqliteClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(SqliteClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(SqliteClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(SqliteClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(SqliteClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(SqliteClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::PropFilter;
+
+    const ALICE: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Alice\r\n\
+                           EMAIL:alice@example.org\r\nNOTE:climbing\r\nEND:VCARD\r\n";
+    const BOB: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Bob\r\n\
+                         EMAIL:bob@example.net\r\nNOTE:chess\r\nEND:VCARD\r\n";
+    const CAROL: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Carol\r\n\
+                           EMAIL:carol@example.org\r\nEND:VCARD\r\n";
+
+    fn client() -> SqliteClient {
+        let mut client = SqliteClient::open_in_memory().unwrap();
+        client.create_addressbook("ab", None, None).unwrap();
+        client.upsert_card("ab", "alice", ALICE.to_vec()).unwrap();
+        client.upsert_card("ab", "bob", BOB.to_vec()).unwrap();
+        client.upsert_card("ab", "carol", CAROL.to_vec()).unwrap();
+        client
+    }
+
+    fn ids(cards: Vec<Card>) -> Vec<String> {
+        cards.into_iter().map(|card| card.id).collect()
+    }
+
+    fn ctag(client: &SqliteClient) -> String {
+        client.list_addressbooks().unwrap()[0].ctag.clone().unwrap()
+    }
+
+    #[test]
+    fn search_indexed_properties_from_index() {
+        let client = client();
+        let filter = CardFilter::any([PropFilter::contains("email", "EXAMPLE.ORG")]);
+
+        assert!(filter_query(&filter).is_some());
+        assert_eq!(
+            ids(client.search_cards("ab", &filter).unwrap()),
+            ["alice", "carol"]
+        );
+
+        // NOTE: with the index emptied, an indexed search finds
+        // nothing, proving it does not scan the cards.
+        client
+            .connection()
+            .execute("DELETE FROM card_fields", [])
+            .unwrap();
+        assert!(client.search_cards("ab", &filter).unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_other_properties_locally() {
+        let client = client();
+        client
+            .connection()
+            .execute("DELETE FROM card_fields", [])
+            .unwrap();
+
+        let filter = CardFilter::any([PropFilter::contains("NOTE", "ch")]);
+        assert!(filter_query(&filter).is_none());
+        assert_eq!(ids(client.search_cards("ab", &filter).unwrap()), ["bob"]);
+
+        let filter = CardFilter::all([
+            PropFilter::contains("FN", "o"),
+            PropFilter::contains("NOTE", "c"),
+        ]);
+        assert!(filter_query(&filter).is_none());
+        assert_eq!(ids(client.search_cards("ab", &filter).unwrap()), ["bob"]);
+    }
+
+    #[test]
+    fn list_cards_paginated() {
+        let client = client();
+
+        let page = |page, size| ids(client.list_cards("ab", page, size).unwrap());
+
+        assert_eq!(page(None, None), ["alice", "bob", "carol"]);
+        assert_eq!(page(Some(1), Some(2)), ["alice", "bob"]);
+        assert_eq!(page(Some(2), Some(2)), ["carol"]);
+        assert_eq!(page(Some(3), Some(2)), Vec::<String>::new());
+        assert_eq!(page(None, Some(1)), ["alice"]);
+        assert_eq!(page(Some(1), Some(0)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn bump_ctag_on_every_change() {
+        let mut client = client();
+        let mut seen = alloc::vec![ctag(&client)];
+
+        let mut assert_bumped = |client: &SqliteClient| {
+            let ctag = ctag(client);
+            assert!(!seen.contains(&ctag), "ctag {ctag} not bumped");
+            seen.push(ctag);
+        };
+
+        client
+            .update_card("ab", "bob", CAROL.to_vec(), None)
+            .unwrap();
+        assert_bumped(&client);
+
+        client.delete_card("ab", "bob", None).unwrap();
+        assert_bumped(&client);
+
+        let patch = AddressbookDiff {
+            description: Some(Some("friends".to_string())),
+            ..Default::default()
+        };
+        client.update_addressbook("ab", patch).unwrap();
+        assert_bumped(&client);
+
+        let unchanged = ctag(&client);
+        client.get_card("ab", "alice").unwrap();
+        client.search_cards("ab", &CardFilter::default()).unwrap();
+        assert_eq!(ctag(&client), unchanged);
+    }
+}
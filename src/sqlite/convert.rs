@@ -0,0 +1,211 @@
+//! Schema, row mappings and field index used by [`SqliteClient`], plus
+//! the `From` impl that wraps it into the unified client's SQLite
+//! variant.
+//!
+//! [`SqliteClient`]: crate::sqlite::client::SqliteClient
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    addressbook::Addressbook,
+    card::{Card, CardFilter, FilterTest, MatchType, parse_properties},
+};
+
+impl From<crate::sqlite::client::SqliteClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::sqlite::client::SqliteClient) -> Self {
+        Self::Sqlite(client)
+    }
+}
+
+/// Database schema, applied idempotently when the client opens.
+///
+/// `cards` keeps the raw vCard bytes and their synthesized tag;
+/// `card_fields` indexes the lower-cased text of the
+/// [`INDEXED_PROPERTIES`] of every card. `meta.revision` is a global
+/// counter stamped on an addressbook at each change, so that its
+/// `ctag` never repeats, even across a deletion and re-creation.
+pub(crate) const SCHEMA: &str = "
+PRAGMA foreign_keys = ON;
+
+CREATE TABLE IF NOT EXISTS meta (
+    revision INTEGER NOT NULL
+);
+
+INSERT INTO meta (revision) SELECT 0 WHERE NOT EXISTS (SELECT 1 FROM meta);
+
+CREATE TABLE IF NOT EXISTS addressbooks (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    color TEXT,
+    revision INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS cards (
+    addressbook_id TEXT NOT NULL REFERENCES addressbooks (id) ON DELETE CASCADE,
+    id TEXT NOT NULL,
+    etag TEXT NOT NULL,
+    contents BLOB NOT NULL,
+    PRIMARY KEY (addressbook_id, id)
+);
+
+CREATE TABLE IF NOT EXISTS card_fields (
+    addressbook_id TEXT NOT NULL,
+    card_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    FOREIGN KEY (addressbook_id, card_id)
+        REFERENCES cards (addressbook_id, id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS card_fields_by_value
+    ON card_fields (addressbook_id, name, value);
+
+CREATE INDEX IF NOT EXISTS card_fields_by_card
+    ON card_fields (addressbook_id, card_id);
+";
+
+/// vCard properties extracted into the `card_fields` index.
+pub(crate) const INDEXED_PROPERTIES: [&str; 4] = ["FN", "EMAIL", "TEL", "UID"];
+
+/// Maps an `addressbooks` row (`id, name, description, color,
+/// revision`) to a shared [`Addressbook`], the revision becoming the
+/// `ctag`.
+pub(crate) fn addressbook_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Addressbook> {
+    Ok(Addressbook {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        color: row.get(3)?,
+        ctag: Some(row.get::<_, i64>(4)?.to_string()),
+        sync_token: None,
+    })
+}
+
+/// Maps a `cards` row (`id, etag, contents`) to a shared [`Card`].
+pub(crate) fn card_from_row(
+    addressbook_id: &str,
+    row: &rusqlite::Row<'_>,
+) -> rusqlite::Result<Card> {
+    Ok(Card {
+        id: row.get(0)?,
+        addressbook_id: addressbook_id.to_string(),
+        etag: Some(row.get(1)?),
+        contents: row.get(2)?,
+    })
+}
+
+/// Extracts the `(name, lower-cased text)` pairs of the
+/// [`INDEXED_PROPERTIES`] of raw card contents.
+///
+/// Cards that do not parse as vCard index nothing: they stay
+/// reachable through listings but never match an indexed search.
+pub(crate) fn indexed_fields(contents: &[u8]) -> Vec<(&'static str, String)> {
+    let Ok(contents) = core::str::from_utf8(contents) else {
+        return Vec::new();
+    };
+
+    let Ok(props) = parse_properties(contents) else {
+        return Vec::new();
+    };
+
+    let mut fields = Vec::new();
+
+    for prop in props {
+        let Some(name) = INDEXED_PROPERTIES.iter().find(|n| **n == prop.name) else {
+            continue;
+        };
+
+        fields.push((*name, prop.text().to_lowercase()));
+    }
+
+    fields
+}
+
+/// Builds a query selecting, from the field index, the ids of the
+/// cards of addressbook `?1` that may match `filter`, along with its
+/// parameters (numbered from `?2`).
+///
+/// The query is exact up to collation: values and texts are both
+/// lower-cased, which selects a superset of the matching cards for
+/// every [`Collation`](crate::card::Collation), so the caller still
+/// confirms each card with [`CardFilter::matches`]. Returns `None` when
+/// the index cannot answer the filter: empty filter, property outside
+/// [`INDEXED_PROPERTIES`], `is_not_defined`, negated or missing text
+/// match.
+pub(crate) fn filter_query(filter: &CardFilter) -> Option<(String, Vec<String>)> {
+    if filter.props.is_empty() {
+        return None;
+    }
+
+    let mut selects = Vec::new();
+    let mut params = Vec::new();
+
+    for prop in &filter.props {
+        let name = prop.name.to_ascii_uppercase();
+
+        if !INDEXED_PROPERTIES.contains(&name.as_str()) || prop.is_not_defined {
+            return None;
+        }
+
+        if prop.text_matches.is_empty() || prop.text_matches.iter().any(|m| m.negate) {
+            return None;
+        }
+
+        params.push(name);
+        let mut select = format!(
+            "SELECT card_id FROM card_fields WHERE addressbook_id = ?1 AND name = ?{}",
+            params.len() + 1
+        );
+
+        let mut conditions = Vec::new();
+
+        for text_match in &prop.text_matches {
+            params.push(like_pattern(&text_match.text, text_match.match_type));
+            conditions.push(format!("value LIKE ?{} ESCAPE '\\'", params.len() + 1));
+        }
+
+        // NOTE: all text matches of a property apply to the same
+        // value, hence to the same row.
+        let operator = match prop.test {
+            FilterTest::AnyOf => " OR ",
+            FilterTest::AllOf => " AND ",
+        };
+
+        select.push_str(" AND (");
+        select.push_str(&conditions.join(operator));
+        select.push(')');
+        selects.push(select);
+    }
+
+    let operator = match filter.test {
+        FilterTest::AnyOf => " UNION ",
+        FilterTest::AllOf => " INTERSECT ",
+    };
+
+    Some((selects.join(operator), params))
+}
+
+/// Turns a searched `text` into a lower-cased `LIKE` pattern, escaping
+/// `\`, `%` and `_` with `\`.
+fn like_pattern(text: &str, match_type: MatchType) -> String {
+    let mut escaped = String::new();
+
+    for c in text.to_lowercase().chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    match match_type {
+        MatchType::Equals => escaped,
+        MatchType::Contains => format!("%{escaped}%"),
+        MatchType::StartsWith => format!("{escaped}%"),
+        MatchType::EndsWith => format!("%{escaped}"),
+    }
+}
@@ -0,0 +1,4 @@
+//! SQLite backend: the std client plus its schema and conversions.
+
+pub mod client;
+pub mod convert;
@@ -0,0 +1,23 @@
+//! Two-way addressbook synchronizer.
+//!
+//! Pairs a local addressbook (typically [`VdirClient`]) with a remote
+//! one (typically [`WebdavClient`]) and propagates card creations,
+//! updates and deletions in both directions. Only the shared
+//! [`AddressbookClientStd`] operations are used, so any pair of
+//! backends works.
+//!
+//! The per-card pairing and last-seen tags live in a [`SyncState`],
+//! persisted by the caller between runs; divergent edits on both
+//! sides are resolved by a [`ConflictStrategy`].
+//!
+//! [`AddressbookClientStd`]: crate::client::AddressbookClientStd
+//! [`VdirClient`]: crate::vdir::client::VdirClient
+//! [`WebdavClient`]: crate::webdav::client::WebdavClient
+
+mod state;
+mod synchronizer;
+
+#[doc(inline)]
+pub use state::*;
+#[doc(inline)]
+pub use synchronizer::*;
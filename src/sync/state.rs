@@ -0,0 +1,146 @@
+//! Persistent synchronization state.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::{fs, io, path::Path};
+
+use thiserror::Error;
+
+const HEADER: &str = "# io-addressbook sync state v1";
+
+/// Errors surfaced while loading or saving a [`SyncState`].
+#[derive(Debug, Error)]
+pub enum SyncStateError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Invalid sync state header `{0}`")]
+    InvalidHeader(String),
+    #[error("Invalid sync state line {0}: `{1}`")]
+    InvalidLine(usize, String),
+}
+
+/// Pairing of one local card with one remote card, along with the
+/// tags both had after the last successful synchronization.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct SyncEntry {
+    /// Card id on the local side.
+    pub local_id: String,
+
+    /// Local card tag (ETag, or content hash when the backend has
+    /// none) at the last synchronization.
+    pub local_tag: String,
+
+    /// Card id on the remote side.
+    pub remote_id: String,
+
+    /// Remote card tag at the last synchronization.
+    pub remote_tag: String,
+}
+
+/// Synchronization state of one addressbook pair.
+///
+/// An empty state means "never synchronized": every card found on
+/// either side is then paired with its counterpart by `UID` or
+/// identical contents, or else treated as new.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct SyncState {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub entries: Vec<SyncEntry>,
+}
+
+impl SyncState {
+    /// Loads the state stored at `path`. A missing file yields an
+    /// empty state.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SyncStateError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Saves the state to `path`, atomically replacing any previous
+    /// one (write to a sibling temporary file, then rename).
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SyncStateError> {
+        let path = path.as_ref();
+
+        // NOTE: the suffix is appended rather than substituted to the
+        // extension, so that `state.tmp` itself or `a.state` and
+        // `a.other` never share the same temporary file.
+        let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp = path.with_file_name(tmp_name);
+
+        fs::write(&tmp, self.to_string())?;
+
+        if let Err(err) = fs::rename(&tmp, path) {
+            let _ = fs::remove_file(&tmp);
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    /// Parses the line-based representation produced by
+    /// [`ToString`]: a header line, then one tab-separated
+    /// `local_id local_tag remote_id remote_tag` line per entry.
+    pub fn parse(contents: &str) -> Result<Self, SyncStateError> {
+        let mut lines = contents.lines();
+
+        match lines.next() {
+            None => return Ok(Self::default()),
+            Some(HEADER) => (),
+            Some(header) => return Err(SyncStateError::InvalidHeader(header.to_string())),
+        }
+
+        let mut entries = Vec::new();
+
+        for (n, line) in lines.enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.split('\t');
+            let (Some(local_id), Some(local_tag), Some(remote_id), Some(remote_tag), None) = (
+                fields.next(),
+                fields.next(),
+                fields.next(),
+                fields.next(),
+                fields.next(),
+            ) else {
+                return Err(SyncStateError::InvalidLine(n + 2, line.to_string()));
+            };
+
+            entries.push(SyncEntry {
+                local_id: local_id.to_string(),
+                local_tag: local_tag.to_string(),
+                remote_id: remote_id.to_string(),
+                remote_tag: remote_tag.to_string(),
+            });
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl core::fmt::Display for SyncState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "{HEADER}")?;
+
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}",
+                entry.local_id, entry.local_tag, entry.remote_id, entry.remote_tag
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,775 @@
+//! Two-way synchronizer driving a pair of unified clients.
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::mem;
+
+use log::{debug, warn};
+use thiserror::Error;
+
+use crate::{
+    card::tag::content_tag,
+    card::uid::find_uid,
+    card::{Card, CardCreateOptions},
+    client::{AddressbookClientStd, AddressbookClientStdError},
+    sync::{SyncEntry, SyncState},
+};
+
+/// Errors surfaced by [`Synchronizer::sync`].
+#[derive(Debug, Error)]
+pub enum SyncError {
+    #[error("Local addressbook error: {0}")]
+    Local(#[source] AddressbookClientStdError),
+    #[error("Remote addressbook error: {0}")]
+    Remote(#[source] AddressbookClientStdError),
+    #[error("Card changed on both sides (local `{local_id}`, remote `{remote_id}`)")]
+    Conflict { local_id: String, remote_id: String },
+}
+
+/// How to resolve a card edited on both sides since the last
+/// synchronization.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum ConflictStrategy {
+    /// Overwrite the local card with the remote one.
+    #[default]
+    RemoteWins,
+    /// Overwrite the remote card with the local one.
+    LocalWins,
+    /// Keep both versions: each side gets a copy of the other one,
+    /// tracked as a new pair.
+    KeepBoth,
+    /// Abort the synchronization with [`SyncError::Conflict`].
+    Fail,
+}
+
+/// Counters of the changes applied by one [`Synchronizer::sync`] run.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct SyncReport {
+    pub local_created: usize,
+    pub local_updated: usize,
+    pub local_deleted: usize,
+    pub remote_created: usize,
+    pub remote_updated: usize,
+    pub remote_deleted: usize,
+    /// Cards edited on both sides, resolved by the strategy.
+    pub conflicts: usize,
+}
+
+/// Side of a synchronized pair.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Side {
+    Local,
+    Remote,
+}
+
+impl Side {
+    fn other(self) -> Self {
+        match self {
+            Self::Local => Self::Remote,
+            Self::Remote => Self::Local,
+        }
+    }
+}
+
+/// Two-way synchronizer of one local and one remote addressbook.
+#[derive(Debug)]
+pub struct Synchronizer {
+    pub local: AddressbookClientStd,
+    pub local_addressbook_id: String,
+    pub remote: AddressbookClientStd,
+    pub remote_addressbook_id: String,
+    pub strategy: ConflictStrategy,
+}
+
+impl Synchronizer {
+    /// Pairs addressbook `local_addressbook_id` of `local` with
+    /// addressbook `remote_addressbook_id` of `remote`, resolving
+    /// conflicts with [`ConflictStrategy::default`].
+    pub fn new(
+        local: impl Into<AddressbookClientStd>,
+        local_addressbook_id: impl ToString,
+        remote: impl Into<AddressbookClientStd>,
+        remote_addressbook_id: impl ToString,
+    ) -> Self {
+        Self {
+            local: local.into(),
+            local_addressbook_id: local_addressbook_id.to_string(),
+            remote: remote.into(),
+            remote_addressbook_id: remote_addressbook_id.to_string(),
+            strategy: ConflictStrategy::default(),
+        }
+    }
+
+    /// Runs one synchronization pass against `state`, then updates
+    /// `state` with the new pairings and tags.
+    ///
+    /// A change on one side only is propagated to the other; an edit
+    /// wins over a deletion. Cards not paired yet (all of them on a
+    /// first synchronization) are paired with their counterpart by
+    /// `UID`, then by identical contents, and only the remaining ones
+    /// are copied over. On error, the pairings not processed yet are
+    /// kept untouched so that the next run retries them.
+    pub fn sync(&mut self, state: &mut SyncState) -> Result<SyncReport, SyncError> {
+        let mut local = self.list(Side::Local)?;
+        let mut remote = self.list(Side::Remote)?;
+
+        let mut report = SyncReport::default();
+        let mut pending = mem::take(&mut state.entries).into_iter();
+
+        while let Some(entry) = pending.next() {
+            let l = local.remove(&entry.local_id);
+            let r = remote.remove(&entry.remote_id);
+
+            if let Err(err) = self.sync_entry(&entry, l, r, &mut state.entries, &mut report) {
+                state.entries.push(entry);
+                state.entries.extend(pending);
+                return Err(err);
+            }
+        }
+
+        self.pair_by(&mut local, &mut remote, state, &mut report, |card| {
+            find_uid(&card.contents).flatten()
+        })?;
+        self.pair_by(&mut local, &mut remote, state, &mut report, |card| {
+            Some(card.contents.clone())
+        })?;
+
+        for card in local.into_values() {
+            let (remote_id, remote_tag) = self.copy(Side::Local, &card)?;
+            report.remote_created += 1;
+
+            state.entries.push(SyncEntry {
+                local_id: card.id.clone(),
+                local_tag: card_tag(&card),
+                remote_id,
+                remote_tag,
+            });
+        }
+
+        for card in remote.into_values() {
+            let (local_id, local_tag) = self.copy(Side::Remote, &card)?;
+            report.local_created += 1;
+
+            state.entries.push(SyncEntry {
+                local_id,
+                local_tag,
+                remote_id: card.id.clone(),
+                remote_tag: card_tag(&card),
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Pairs the unpaired `local` and `remote` cards sharing the same
+    /// `key`, removing them from both maps. Paired cards with diverging
+    /// contents are resolved as conflicts, there being no previous
+    /// version to tell which side changed.
+    fn pair_by<K: Ord>(
+        &mut self,
+        local: &mut BTreeMap<String, Card>,
+        remote: &mut BTreeMap<String, Card>,
+        state: &mut SyncState,
+        report: &mut SyncReport,
+        key: impl Fn(&Card) -> Option<K>,
+    ) -> Result<(), SyncError> {
+        let mut remote_ids = BTreeMap::new();
+
+        for card in remote.values() {
+            if let Some(key) = key(card) {
+                remote_ids.entry(key).or_insert_with(|| card.id.clone());
+            }
+        }
+
+        for (id, l) in mem::take(local) {
+            let r = key(&l)
+                .and_then(|key| remote_ids.remove(&key))
+                .and_then(|remote_id| remote.remove(&remote_id));
+
+            let Some(r) = r else {
+                local.insert(id, l);
+                continue;
+            };
+
+            if l.contents == r.contents {
+                debug!("pairing card {} with remote {}", l.id, r.id);
+
+                state.entries.push(SyncEntry {
+                    local_tag: card_tag(&l),
+                    local_id: l.id,
+                    remote_tag: card_tag(&r),
+                    remote_id: r.id,
+                });
+            } else {
+                report.conflicts += 1;
+                self.resolve(l, r, &mut state.entries, report)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reconciles one known pairing given the current local and
+    /// remote cards (when still present), pushing the resulting
+    /// pairings to `next`.
+    fn sync_entry(
+        &mut self,
+        entry: &SyncEntry,
+        local: Option<Card>,
+        remote: Option<Card>,
+        next: &mut Vec<SyncEntry>,
+        report: &mut SyncReport,
+    ) -> Result<(), SyncError> {
+        match (local, remote) {
+            (None, None) => {
+                debug!("card {} deleted on both sides", entry.local_id);
+            }
+            (Some(l), None) => {
+                if card_tag(&l) == entry.local_tag {
+                    self.delete(Side::Local, &l)?;
+                    report.local_deleted += 1;
+                } else {
+                    let (remote_id, remote_tag) = self.copy(Side::Local, &l)?;
+                    report.remote_created += 1;
+
+                    next.push(SyncEntry {
+                        local_id: l.id.clone(),
+                        local_tag: card_tag(&l),
+                        remote_id,
+                        remote_tag,
+                    });
+                }
+            }
+            (None, Some(r)) => {
+                if card_tag(&r) == entry.remote_tag {
+                    self.delete(Side::Remote, &r)?;
+                    report.remote_deleted += 1;
+                } else {
+                    let (local_id, local_tag) = self.copy(Side::Remote, &r)?;
+                    report.local_created += 1;
+
+                    next.push(SyncEntry {
+                        local_id,
+                        local_tag,
+                        remote_id: r.id.clone(),
+                        remote_tag: card_tag(&r),
+                    });
+                }
+            }
+            (Some(l), Some(r)) => {
+                let local_tag = card_tag(&l);
+                let remote_tag = card_tag(&r);
+
+                let local_changed = local_tag != entry.local_tag;
+                let remote_changed = remote_tag != entry.remote_tag;
+
+                let entry = match (local_changed, remote_changed) {
+                    (false, false) => entry.clone(),
+                    (true, false) => {
+                        report.remote_updated += 1;
+                        SyncEntry {
+                            remote_tag: self.overwrite(Side::Remote, &r, l.contents)?,
+                            local_tag,
+                            ..entry.clone()
+                        }
+                    }
+                    (false, true) => {
+                        report.local_updated += 1;
+                        SyncEntry {
+                            local_tag: self.overwrite(Side::Local, &l, r.contents)?,
+                            remote_tag,
+                            ..entry.clone()
+                        }
+                    }
+                    // NOTE: both sides converged to the same bytes,
+                    // nothing to propagate.
+                    (true, true) if l.contents == r.contents => SyncEntry {
+                        local_tag,
+                        remote_tag,
+                        ..entry.clone()
+                    },
+                    (true, true) => {
+                        report.conflicts += 1;
+                        return self.resolve(l, r, next, report);
+                    }
+                };
+
+                next.push(entry);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves a card edited on both sides according to the
+    /// configured [`ConflictStrategy`].
+    fn resolve(
+        &mut self,
+        local: Card,
+        remote: Card,
+        next: &mut Vec<SyncEntry>,
+        report: &mut SyncReport,
+    ) -> Result<(), SyncError> {
+        debug!(
+            "card {} (remote {}) changed on both sides, resolving with {:?}",
+            local.id, remote.id, self.strategy
+        );
+
+        match self.strategy {
+            ConflictStrategy::RemoteWins => {
+                let local_tag = self.overwrite(Side::Local, &local, remote.contents.clone())?;
+                report.local_updated += 1;
+
+                next.push(SyncEntry {
+                    local_id: local.id,
+                    local_tag,
+                    remote_tag: card_tag(&remote),
+                    remote_id: remote.id,
+                });
+            }
+            ConflictStrategy::LocalWins => {
+                let remote_tag = self.overwrite(Side::Remote, &remote, local.contents.clone())?;
+                report.remote_updated += 1;
+
+                next.push(SyncEntry {
+                    local_tag: card_tag(&local),
+                    local_id: local.id,
+                    remote_id: remote.id,
+                    remote_tag,
+                });
+            }
+            ConflictStrategy::KeepBoth => {
+                let (remote_copy_id, remote_copy_tag) = self.copy(Side::Local, &local)?;
+
+                // NOTE: the pairing is only recorded once both copies
+                // exist, so a failing second copy rolls the first one
+                // back rather than leaving it orphaned: the next run
+                // then resolves the conflict again from scratch.
+                let (local_copy_id, local_copy_tag) = match self.copy(Side::Remote, &remote) {
+                    Ok(copy) => copy,
+                    Err(err) => {
+                        self.discard(Side::Remote, &remote_copy_id, &remote_copy_tag);
+                        return Err(err);
+                    }
+                };
+
+                report.remote_created += 1;
+                report.local_created += 1;
+
+                next.push(SyncEntry {
+                    local_tag: card_tag(&local),
+                    local_id: local.id,
+                    remote_id: remote_copy_id,
+                    remote_tag: remote_copy_tag,
+                });
+                next.push(SyncEntry {
+                    local_id: local_copy_id,
+                    local_tag: local_copy_tag,
+                    remote_tag: card_tag(&remote),
+                    remote_id: remote.id,
+                });
+            }
+            ConflictStrategy::Fail => {
+                return Err(SyncError::Conflict {
+                    local_id: local.id,
+                    remote_id: remote.id,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the client and addressbook id of `side`.
+    fn side(&mut self, side: Side) -> (&mut AddressbookClientStd, &str) {
+        match side {
+            Side::Local => (&mut self.local, &self.local_addressbook_id),
+            Side::Remote => (&mut self.remote, &self.remote_addressbook_id),
+        }
+    }
+
+    /// Lists every card of `side`, indexed by id.
+    fn list(&mut self, side: Side) -> Result<BTreeMap<String, Card>, SyncError> {
+        let (client, addressbook_id) = self.side(side);
+        let cards = client
+            .list_cards(addressbook_id, None, None)
+            .map_err(|err| side_error(side, err))?;
+
+        Ok(cards.into_iter().map(|c| (c.id.clone(), c)).collect())
+    }
+
+    /// Creates a copy of `card` (living on `from`) on the other side.
+    /// Returns the id and tag of the copy.
+    fn copy(&mut self, from: Side, card: &Card) -> Result<(String, String), SyncError> {
+        let to = from.other();
+        let (client, addressbook_id) = self.side(to);
+
+        // NOTE: copies keep random ids: a UID-derived id would collide
+        // with the other half of a pair on KeepBoth conflicts, and with
+        // any other card sharing the UID on the same side.
+        let options = CardCreateOptions { id_from_uid: false };
+
+        let copy = client
+            .create_card_with_options(addressbook_id, card.contents.clone(), options)
+            .map_err(|err| side_error(to, err))?;
+
+        let tag = match copy.etag {
+            Some(etag) => etag,
+            None => self.tag(to, &copy.id)?,
+        };
+
+        Ok((copy.id, tag))
+    }
+
+    /// Overwrites `card` (living on `side`) with `contents`, gated on
+    /// its current ETag. Returns the new tag.
+    fn overwrite(
+        &mut self,
+        side: Side,
+        card: &Card,
+        contents: Vec<u8>,
+    ) -> Result<String, SyncError> {
+        let (client, addressbook_id) = self.side(side);
+
+        let updated = client
+            .update_card(addressbook_id, &card.id, contents, card.etag.as_deref())
+            .map_err(|err| side_error(side, err))?;
+
+        match updated.etag {
+            Some(etag) => Ok(etag),
+            None => self.tag(side, &card.id),
+        }
+    }
+
+    /// Deletes `card` from `side`, gated on its current ETag.
+    fn delete(&mut self, side: Side, card: &Card) -> Result<(), SyncError> {
+        let (client, addressbook_id) = self.side(side);
+
+        client
+            .delete_card(addressbook_id, &card.id, card.etag.as_deref())
+            .map_err(|err| side_error(side, err))
+    }
+
+    /// Deletes the freshly created copy `id` from `side`, gated on
+    /// its `tag`. A failure is only logged, the copy being then left
+    /// for the next run to pick up as an unpaired card.
+    fn discard(&mut self, side: Side, id: &str, tag: &str) {
+        let (client, addressbook_id) = self.side(side);
+
+        if let Err(err) = client.delete_card(addressbook_id, id, Some(tag)) {
+            warn!("cannot roll back copy {id} on {side:?} side: {err}");
+        }
+    }
+
+    /// Fetches the current tag of card `id` on `side`, for writes that
+    /// did not report one.
+    fn tag(&mut self, side: Side, id: &str) -> Result<String, SyncError> {
+        let (client, addressbook_id) = self.side(side);
+
+        let card = client
+            .get_card(addressbook_id, id)
+            .map_err(|err| side_error(side, err))?;
+
+        Ok(card_tag(&card))
+    }
+}
+
+/// Returns the tag tracking changes of `card`: its ETag, or a hash of
+/// its contents when the backend exposes none.
+fn card_tag(card: &Card) -> String {
+    match &card.etag {
+        Some(etag) => etag.clone(),
+        None => content_tag(&card.contents),
+    }
+}
+
+fn side_error(side: Side, err: AddressbookClientStdError) -> SyncError {
+    match side {
+        Side::Local => SyncError::Local(err),
+        Side::Remote => SyncError::Remote(err),
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use alloc::{boxed::Box, string::String, vec::Vec};
+
+    use crate::{
+        addressbook::{Addressbook, AddressbookDiff},
+        backend::{AddressbookBackend, BackendError},
+        card::{Card, CardRef},
+        client::{AddressbookClientStd, AddressbookClientStdError},
+        error::ErrorKind,
+        memory::client::InMemoryClient,
+        sync::SyncState,
+    };
+
+    use super::{ConflictStrategy, SyncError, SyncReport, Synchronizer};
+
+    const ALICE: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alice\r\nEND:VCARD\r\n";
+    const ALICE_LOCAL: &[u8] =
+        b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alice L.\r\nEND:VCARD\r\n";
+    const ALICE_REMOTE: &[u8] =
+        b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alice R.\r\nEND:VCARD\r\n";
+    const BOB: &[u8] = b"BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Bob\r\nEND:VCARD\r\n";
+    const CAROL: &[u8] = b"BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Carol\r\nEND:VCARD\r\n";
+
+    /// In-memory backend refusing every card creation.
+    #[derive(Debug)]
+    struct ReadOnly(InMemoryClient);
+
+    impl AddressbookBackend for ReadOnly {
+        fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+            AddressbookBackend::list_addressbooks(&mut self.0)
+        }
+
+        fn create_addressbook(
+            &mut self,
+            name: &str,
+            description: Option<&str>,
+            color: Option<&str>,
+        ) -> Result<String, AddressbookClientStdError> {
+            AddressbookBackend::create_addressbook(&mut self.0, name, description, color)
+        }
+
+        fn update_addressbook(
+            &mut self,
+            id: &str,
+            patch: AddressbookDiff,
+        ) -> Result<(), AddressbookClientStdError> {
+            AddressbookBackend::update_addressbook(&mut self.0, id, patch)
+        }
+
+        fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+            AddressbookBackend::delete_addressbook(&mut self.0, id)
+        }
+
+        fn list_cards(
+            &mut self,
+            addressbook_id: &str,
+            page: Option<u32>,
+            page_size: Option<u32>,
+        ) -> Result<Vec<Card>, AddressbookClientStdError> {
+            AddressbookBackend::list_cards(&mut self.0, addressbook_id, page, page_size)
+        }
+
+        fn get_card(
+            &mut self,
+            addressbook_id: &str,
+            card_id: &str,
+        ) -> Result<Card, AddressbookClientStdError> {
+            AddressbookBackend::get_card(&mut self.0, addressbook_id, card_id)
+        }
+
+        fn create_card(
+            &mut self,
+            _addressbook_id: &str,
+            _contents: Vec<u8>,
+        ) -> Result<CardRef, AddressbookClientStdError> {
+            Err(BackendError::new(ErrorKind::Forbidden, "read-only addressbook").into())
+        }
+
+        fn update_card(
+            &mut self,
+            addressbook_id: &str,
+            card_id: &str,
+            contents: Vec<u8>,
+            if_match: Option<&str>,
+        ) -> Result<CardRef, AddressbookClientStdError> {
+            AddressbookBackend::update_card(
+                &mut self.0,
+                addressbook_id,
+                card_id,
+                contents,
+                if_match,
+            )
+        }
+
+        fn delete_card(
+            &mut self,
+            addressbook_id: &str,
+            card_id: &str,
+            if_match: Option<&str>,
+        ) -> Result<(), AddressbookClientStdError> {
+            AddressbookBackend::delete_card(&mut self.0, addressbook_id, card_id, if_match)
+        }
+    }
+
+    fn client(cards: &[(&str, &[u8])]) -> InMemoryClient {
+        let mut client = InMemoryClient::new();
+        client.create_addressbook("ab", None, None).unwrap();
+
+        for (id, contents) in cards {
+            client.upsert_card("ab", id, contents.to_vec()).unwrap();
+        }
+
+        client
+    }
+
+    fn contents(client: &mut AddressbookClientStd) -> Vec<Vec<u8>> {
+        let mut contents: Vec<_> = client
+            .list_cards("ab", None, None)
+            .unwrap()
+            .into_iter()
+            .map(|card| card.contents)
+            .collect();
+
+        contents.sort();
+        contents
+    }
+
+    /// Pairs `alice` on both sides, then edits it differently on each
+    /// side.
+    fn conflicting(
+        local: impl Into<AddressbookClientStd>,
+        strategy: ConflictStrategy,
+    ) -> (Synchronizer, SyncState) {
+        let remote = client(&[("alice-remote", ALICE)]);
+
+        let mut sync = Synchronizer::new(local, "ab", remote, "ab");
+        sync.strategy = strategy;
+
+        let mut state = SyncState::default();
+        sync.sync(&mut state).unwrap();
+        assert_eq!(state.entries.len(), 1);
+
+        sync.local
+            .upsert_card("ab", "alice", ALICE_LOCAL.to_vec())
+            .unwrap();
+        sync.remote
+            .upsert_card("ab", "alice-remote", ALICE_REMOTE.to_vec())
+            .unwrap();
+
+        (sync, state)
+    }
+
+    #[test]
+    fn first_sync_pairs_by_uid_then_contents() {
+        let local = client(&[("alice", ALICE), ("bob", BOB), ("carol", CAROL)]);
+        let remote = client(&[("alice-remote", ALICE), ("bob-remote", BOB)]);
+
+        let mut sync = Synchronizer::new(local, "ab", remote, "ab");
+        let mut state = SyncState::default();
+        let report = sync.sync(&mut state).unwrap();
+
+        let expected = SyncReport {
+            remote_created: 1,
+            ..SyncReport::default()
+        };
+
+        assert_eq!(report, expected);
+        assert_eq!(contents(&mut sync.local), contents(&mut sync.remote));
+
+        let mut pairs: Vec<_> = state
+            .entries
+            .iter()
+            .map(|entry| (entry.local_id.as_str(), entry.remote_id.as_str()))
+            .collect();
+        pairs.sort();
+
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs[0], ("alice", "alice-remote"));
+        assert_eq!(pairs[1], ("bob", "bob-remote"));
+        assert_eq!(pairs[2].0, "carol");
+
+        let report = sync.sync(&mut state).unwrap();
+        assert_eq!(report, SyncReport::default());
+    }
+
+    #[test]
+    fn first_sync_resolves_diverging_uid_pairs() {
+        let local = client(&[("alice", ALICE_LOCAL)]);
+        let remote = client(&[("alice-remote", ALICE_REMOTE)]);
+
+        let mut sync = Synchronizer::new(local, "ab", remote, "ab");
+        let mut state = SyncState::default();
+        let report = sync.sync(&mut state).unwrap();
+
+        assert_eq!(report.conflicts, 1);
+        assert_eq!(report.local_updated, 1);
+        assert_eq!(report.remote_created, 0);
+        assert_eq!(state.entries.len(), 1);
+        assert_eq!(contents(&mut sync.local), [ALICE_REMOTE]);
+    }
+
+    #[test]
+    fn conflict_remote_wins() {
+        let local = client(&[("alice", ALICE)]);
+        let (mut sync, mut state) = conflicting(local, ConflictStrategy::RemoteWins);
+        let report = sync.sync(&mut state).unwrap();
+
+        assert_eq!(report.conflicts, 1);
+        assert_eq!(report.local_updated, 1);
+        assert_eq!(contents(&mut sync.local), [ALICE_REMOTE]);
+        assert_eq!(contents(&mut sync.remote), [ALICE_REMOTE]);
+        assert_eq!(sync.sync(&mut state).unwrap(), SyncReport::default());
+    }
+
+    #[test]
+    fn conflict_local_wins() {
+        let local = client(&[("alice", ALICE)]);
+        let (mut sync, mut state) = conflicting(local, ConflictStrategy::LocalWins);
+        let report = sync.sync(&mut state).unwrap();
+
+        assert_eq!(report.conflicts, 1);
+        assert_eq!(report.remote_updated, 1);
+        assert_eq!(contents(&mut sync.local), [ALICE_LOCAL]);
+        assert_eq!(contents(&mut sync.remote), [ALICE_LOCAL]);
+        assert_eq!(sync.sync(&mut state).unwrap(), SyncReport::default());
+    }
+
+    #[test]
+    fn conflict_keep_both() {
+        let local = client(&[("alice", ALICE)]);
+        let (mut sync, mut state) = conflicting(local, ConflictStrategy::KeepBoth);
+        let report = sync.sync(&mut state).unwrap();
+
+        assert_eq!(report.conflicts, 1);
+        assert_eq!(report.local_created, 1);
+        assert_eq!(report.remote_created, 1);
+        assert_eq!(state.entries.len(), 2);
+        assert_eq!(contents(&mut sync.local), [ALICE_LOCAL, ALICE_REMOTE]);
+        assert_eq!(contents(&mut sync.remote), [ALICE_LOCAL, ALICE_REMOTE]);
+        assert_eq!(sync.sync(&mut state).unwrap(), SyncReport::default());
+    }
+
+    #[test]
+    fn conflict_keep_both_rolls_back_first_copy() {
+        let local = client(&[("alice", ALICE)]);
+        let local = AddressbookClientStd::Custom(Box::new(ReadOnly(local)));
+        let (mut sync, mut state) = conflicting(local, ConflictStrategy::KeepBoth);
+        let entries = state.entries.clone();
+
+        let err = sync.sync(&mut state).unwrap_err();
+
+        assert!(matches!(err, SyncError::Local(_)));
+        assert_eq!(state.entries, entries);
+        assert_eq!(contents(&mut sync.local), [ALICE_LOCAL]);
+        assert_eq!(contents(&mut sync.remote), [ALICE_REMOTE]);
+    }
+
+    #[test]
+    fn conflict_fail() {
+        let local = client(&[("alice", ALICE)]);
+        let (mut sync, mut state) = conflicting(local, ConflictStrategy::Fail);
+        let entries = state.entries.clone();
+
+        let err = sync.sync(&mut state).unwrap_err();
+
+        assert!(matches!(
+            err,
+            SyncError::Conflict { local_id, remote_id }
+                if local_id == "alice" && remote_id == "alice-remote"
+        ));
+        assert_eq!(state.entries, entries);
+        assert_eq!(contents(&mut sync.local), [ALICE_LOCAL]);
+        assert_eq!(contents(&mut sync.remote), [ALICE_REMOTE]);
+    }
+}
@@ -0,0 +1,874 @@
+//! Std-blocking `.vcf` file addressbook client.
+//!
+//! [`VcfFileClient`] exposes multi-card `.vcf` files through the shared
+//! API: either a single file, seen as one addressbook named after the
+//! file stem, or a directory holding one `.vcf` file per addressbook.
+//! Reads split the file into vCards; writes rebuild it and replace it
+//! atomically (temporary file then rename), so readers never see a
+//! half-written file.
+//!
+//! Card ids derive from the vCard `UID` (from the tag of the contents
+//! when there is none), so a plain export from a phone can be used
+//! directly. Card and addressbook tags are synthesized from the
+//! contents; addressbooks carry no description nor color.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    backend::AddressbookBackend,
+    capabilities::Capabilities,
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
+        file::{UpdateUidError, update_uid, write_atomic},
+        join_vcf, split_vcf,
+        tag::{content_tag, tag_matches},
+        uid::{card_id_from_uid, find_uid, fresh_card_id, inject_uid},
+    },
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    vcf_file::convert::{
+        EXTENSION, Entries, addressbook_from_file, card_from_entry, card_ref_from_entry,
+        cards_from_vcf, paginate,
+    },
+};
+
+/// Errors surfaced by [`VcfFileClient`].
+#[derive(Debug, Error)]
+pub enum VcfFileClientError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Failed to gather randomness for new card id: {0}")]
+    Random(getrandom::Error),
+    #[error("Invalid addressbook name `{0}`")]
+    InvalidName(String),
+    #[error("Cannot create nor delete addressbooks of a single `.vcf` file")]
+    SingleFile,
+    #[error("Addressbook metadata of `.vcf` files cannot be changed")]
+    ReadOnlyMetadata,
+    #[error("Addressbook `{0}` already exists")]
+    AddressbookAlreadyExists(String),
+    #[error("Addressbook `{0}` not found")]
+    AddressbookNotFound(String),
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Card body is empty")]
+    EmptyBody,
+    #[error("Card body is not a single vCard")]
+    InvalidCard,
+    #[error("Card `{card_id}` contents carry the UID of card `{uid_id}`")]
+    UidMismatch { card_id: String, uid_id: String },
+    #[error("Card `{0}` not found")]
+    CardNotFound(String),
+    #[error("Card `{0}` already exists")]
+    CardAlreadyExists(String),
+    #[error("Card `{card_id}` changed (current tag: {actual})")]
+    PreconditionFailed { card_id: String, actual: String },
+}
+
+impl VcfFileClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::AddressbookNotFound(_) | Self::CardNotFound(_) => ErrorKind::NotFound,
+            Self::AddressbookAlreadyExists(_)
+            | Self::CardAlreadyExists(_)
+            | Self::PreconditionFailed { .. } => ErrorKind::Conflict,
+            Self::InvalidName(_)
+            | Self::SingleFile
+            | Self::ReadOnlyMetadata
+            | Self::InvalidCardId(_)
+            | Self::EmptyBody
+            | Self::InvalidCard
+            | Self::UidMismatch { .. } => ErrorKind::InvalidData,
+            Self::Random(_) => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current tag of the card when the error is a failed
+    /// `if_match` precondition.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::PreconditionFailed { actual, .. } => Some(actual),
+            _ => None,
+        }
+    }
+}
+
+impl From<UpdateUidError> for VcfFileClientError {
+    fn from(err: UpdateUidError) -> Self {
+        match err {
+            UpdateUidError::Mismatch { card_id, uid_id } => Self::UidMismatch { card_id, uid_id },
+            UpdateUidError::InvalidCardId(card_id) => Self::InvalidCardId(card_id),
+        }
+    }
+}
+
+/// Where the addressbooks live.
+#[derive(Clone, Debug)]
+enum Layout {
+    /// One `.vcf` file, one addressbook.
+    File(PathBuf),
+    /// One addressbook per `.vcf` file of the directory.
+    Directory(PathBuf),
+}
+
+/// Std-blocking `.vcf` file addressbook client.
+#[derive(Clone, Debug)]
+pub struct VcfFileClient {
+    layout: Layout,
+}
+
+impl VcfFileClient {
+    /// Builds a client exposing the single `.vcf` file at `path` as one
+    /// addressbook, whose id is the file stem. A missing file reads as
+    /// an empty addressbook and is created on first write.
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            layout: Layout::File(path.into()),
+        }
+    }
+
+    /// Builds a client exposing every `.vcf` file of the directory at
+    /// `path` as an addressbook, whose id is the file stem.
+    pub fn directory(path: impl Into<PathBuf>) -> Self {
+        Self {
+            layout: Layout::Directory(path.into()),
+        }
+    }
+
+    /// Returns the file or directory path the client was built with.
+    pub fn path(&self) -> &Path {
+        match &self.layout {
+            Layout::File(path) | Layout::Directory(path) => path,
+        }
+    }
+
+    /// Returns the static capabilities of the `.vcf` file backend.
+    ///
+    /// Tags are synthesized from the contents; there is no sync token
+    /// nor query engine, and no addressbook metadata to write.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            etag: true,
+            ctag: true,
+            sync_token: false,
+            query: false,
+            multiget: true,
+            vcard_versions: Vec::from(["3.0".to_string(), "4.0".to_string()]),
+            max_resource_size: None,
+            writable_metadata: Vec::new(),
+        }
+    }
+
+    /// Lists every addressbook, sorted by id, each with the tag of its
+    /// file as `ctag`.
+    pub fn list_addressbooks(&self) -> Result<Vec<Addressbook>, VcfFileClientError> {
+        let dir = match &self.layout {
+            Layout::File(path) => {
+                let data = read_or_empty(path)?;
+                return Ok(Vec::from([addressbook_from_file(&file_stem(path), &data)]));
+            }
+            Layout::Directory(dir) => dir,
+        };
+
+        let mut addressbooks = Vec::new();
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+
+            let is_vcf = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(EXTENSION));
+
+            if !is_vcf || !path.is_file() {
+                continue;
+            }
+
+            let data = fs::read(&path)?;
+            addressbooks.push(addressbook_from_file(&file_stem(&path), &data));
+        }
+
+        addressbooks.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(addressbooks)
+    }
+
+    /// Creates an empty `<name>.vcf` file in the directory. Returns the
+    /// new addressbook id, `name` itself.
+    ///
+    /// `.vcf` files have nowhere to store `description` and `color`,
+    /// which are dropped. Fails with [`VcfFileClientError::SingleFile`]
+    /// on a single-file client.
+    pub fn create_addressbook(
+        &self,
+        name: &str,
+        _description: Option<&str>,
+        _color: Option<&str>,
+    ) -> Result<String, VcfFileClientError> {
+        let Layout::Directory(dir) = &self.layout else {
+            return Err(VcfFileClientError::SingleFile);
+        };
+
+        let path = addressbook_file(dir, name)?;
+
+        let created = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path);
+
+        match created {
+            Ok(_) => Ok(name.to_string()),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Err(
+                VcfFileClientError::AddressbookAlreadyExists(name.to_string()),
+            ),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Checks that the addressbook `id` exists. Any metadata change
+    /// fails with [`VcfFileClientError::ReadOnlyMetadata`]: the name is
+    /// the file stem, and there is no description nor color.
+    pub fn update_addressbook(
+        &self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), VcfFileClientError> {
+        self.addressbook_path(id)?;
+
+        if patch.name.is_some() || patch.description.is_some() || patch.color.is_some() {
+            return Err(VcfFileClientError::ReadOnlyMetadata);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the `.vcf` file of the addressbook `id`. Fails with
+    /// [`VcfFileClientError::SingleFile`] on a single-file client.
+    pub fn delete_addressbook(&self, id: &str) -> Result<(), VcfFileClientError> {
+        let Layout::Directory(dir) = &self.layout else {
+            return Err(VcfFileClientError::SingleFile);
+        };
+
+        match fs::remove_file(addressbook_file(dir, id)?) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Err(VcfFileClientError::AddressbookNotFound(id.to_string()))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Lists cards inside `addressbook_id`, in file order, applying
+    /// 1-indexed pagination.
+    pub fn list_cards(
+        &self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, VcfFileClientError> {
+        let (_, entries) = self.load(addressbook_id)?;
+
+        let cards = entries
+            .iter()
+            .map(|(id, contents)| card_from_entry(addressbook_id, id, contents))
+            .collect();
+
+        Ok(paginate(cards, page, page_size))
+    }
+
+    /// Lists the id and synthesized tag of every card in
+    /// `addressbook_id`, in file order.
+    pub fn list_card_refs(&self, addressbook_id: &str) -> Result<Vec<CardRef>, VcfFileClientError> {
+        let (_, entries) = self.load(addressbook_id)?;
+
+        let refs = entries
+            .iter()
+            .map(|(id, contents)| card_ref_from_entry(id, contents))
+            .collect();
+
+        Ok(refs)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`,
+    /// evaluated locally over every card.
+    pub fn search_cards(
+        &self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, VcfFileClientError> {
+        let mut cards = self.list_cards(addressbook_id, None, None)?;
+        cards.retain(|card| filter.matches(card));
+        Ok(cards)
+    }
+
+    /// Returns every card of `addressbook_id` as a full
+    /// [`CardChanges`] listing: there is no sync token, so
+    /// `sync_token` is ignored.
+    pub fn sync_cards(
+        &self,
+        addressbook_id: &str,
+        _sync_token: Option<&str>,
+    ) -> Result<CardChanges, VcfFileClientError> {
+        Ok(CardChanges {
+            cards: self.list_cards(addressbook_id, None, None)?,
+            deleted: Vec::new(),
+            sync_token: None,
+            full: true,
+        })
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub fn get_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, VcfFileClientError> {
+        let (_, entries) = self.load(addressbook_id)?;
+
+        entries
+            .iter()
+            .find(|(id, _)| id == card_id)
+            .map(|(id, contents)| card_from_entry(addressbook_id, id, contents))
+            .ok_or_else(|| VcfFileClientError::CardNotFound(card_id.to_string()))
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id`, reading
+    /// the file once. Ids with no card end up in
+    /// [`CardBatch::missing`].
+    pub fn get_cards(
+        &self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, VcfFileClientError> {
+        let (_, entries) = self.load(addressbook_id)?;
+        let mut batch = CardBatch::default();
+
+        for card_id in card_ids {
+            match entries.iter().find(|(id, _)| id == card_id) {
+                Some((id, contents)) => {
+                    batch
+                        .cards
+                        .push(card_from_entry(addressbook_id, id, contents));
+                }
+                None => batch.missing.push(card_id.to_string()),
+            }
+        }
+
+        batch.cards.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(batch)
+    }
+
+    /// Appends a new vCard to `addressbook_id`. Returns its assigned id
+    /// and synthesized tag.
+    pub fn create_card(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, VcfFileClientError> {
+        self.create_card_with_options(addressbook_id, contents, CardCreateOptions::default())
+    }
+
+    /// Appends a new vCard to `addressbook_id`, failing when a card
+    /// with the same id exists. Returns its assigned id and
+    /// synthesized tag.
+    ///
+    /// The id always derives from the `UID`, the only identity a
+    /// `.vcf` file keeps: a fresh `UID` is injected when the card has
+    /// none, whatever `options` say.
+    pub fn create_card_with_options(
+        &self,
+        addressbook_id: &str,
+        mut contents: Vec<u8>,
+        _options: CardCreateOptions,
+    ) -> Result<CardRef, VcfFileClientError> {
+        let id = match check_contents(&contents)? {
+            Some(uid) => card_id_from_uid(&uid),
+            None => {
+                let uuid = fresh_card_id().map_err(VcfFileClientError::Random)?;
+                contents = inject_uid(&contents, &format!("urn:uuid:{uuid}"));
+                uuid
+            }
+        };
+
+        let (path, mut entries) = self.load(addressbook_id)?;
+
+        if entries.iter().any(|(existing, _)| *existing == id) {
+            return Err(VcfFileClientError::CardAlreadyExists(id));
+        }
+
+        let etag = content_tag(&contents);
+        entries.push((id.clone(), contents));
+        store(&path, &entries)?;
+
+        Ok(CardRef {
+            id,
+            href: None,
+            etag: Some(etag),
+        })
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id` in place, gating on
+    /// `if_match` when present. Without `if_match`, a missing card is
+    /// appended. Returns the card id and the tag of the new contents.
+    ///
+    /// Contents without `UID` get the current `UID` of the card, or
+    /// `card_id` itself, so the card keeps its id; contents whose `UID`
+    /// maps to another id, and is not the current `UID` of the card
+    /// either (a duplicate `UID` disambiguated on read), fail with
+    /// [`VcfFileClientError::UidMismatch`].
+    pub fn update_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        mut contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, VcfFileClientError> {
+        if card_id.is_empty() {
+            return Err(VcfFileClientError::InvalidCardId(String::new()));
+        }
+
+        let uid = check_contents(&contents)?;
+        let (path, mut entries) = self.load(addressbook_id)?;
+        let position = entries.iter().position(|(id, _)| id == card_id);
+
+        let current_uid = position.and_then(|i| find_uid(&entries[i].1).flatten());
+
+        if let Some(uid) = update_uid(card_id, uid.as_deref(), current_uid.as_deref())? {
+            contents = inject_uid(&contents, &uid);
+        }
+
+        if let Some(if_match) = if_match {
+            let Some(position) = position else {
+                return Err(VcfFileClientError::CardNotFound(card_id.to_string()));
+            };
+
+            check_precondition(card_id, &entries[position].1, if_match)?;
+        }
+
+        let etag = content_tag(&contents);
+
+        match position {
+            Some(position) => entries[position].1 = contents,
+            None => entries.push((card_id.to_string(), contents)),
+        }
+
+        store(&path, &entries)?;
+
+        Ok(CardRef {
+            id: card_id.to_string(),
+            href: None,
+            etag: Some(etag),
+        })
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating the card or overwriting it unconditionally.
+    pub fn upsert_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, VcfFileClientError> {
+        self.update_card(addressbook_id, card_id, contents, None)
+    }
+
+    /// Removes `card_id` from `addressbook_id`, gating on `if_match`
+    /// when present.
+    pub fn delete_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), VcfFileClientError> {
+        let (path, mut entries) = self.load(addressbook_id)?;
+
+        let Some(position) = entries.iter().position(|(id, _)| id == card_id) else {
+            return Err(VcfFileClientError::CardNotFound(card_id.to_string()));
+        };
+
+        if let Some(if_match) = if_match {
+            check_precondition(card_id, &entries[position].1, if_match)?;
+        }
+
+        entries.remove(position);
+        store(&path, &entries)
+    }
+
+    /// Resolves the file of the addressbook `id`, checking it exists
+    /// (a missing single file reads as empty).
+    fn addressbook_path(&self, id: &str) -> Result<PathBuf, VcfFileClientError> {
+        match &self.layout {
+            Layout::File(path) if file_stem(path) == id => Ok(path.clone()),
+            Layout::File(_) => Err(VcfFileClientError::AddressbookNotFound(id.to_string())),
+            Layout::Directory(dir) => {
+                let path = addressbook_file(dir, id)?;
+
+                if !path.is_file() {
+                    return Err(VcfFileClientError::AddressbookNotFound(id.to_string()));
+                }
+
+                Ok(path)
+            }
+        }
+    }
+
+    /// Reads the cards of the addressbook `id`, along with its file.
+    fn load(&self, id: &str) -> Result<(PathBuf, Entries), VcfFileClientError> {
+        let path = self.addressbook_path(id)?;
+        let entries = cards_from_vcf(&read_or_empty(&path)?);
+        Ok((path, entries))
+    }
+}
+
+/// Returns the file stem of `path`, lossily decoded.
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Builds the path of the `.vcf` file of the addressbook `id` under
+/// `dir`, rejecting ids that would escape it or be hidden.
+fn addressbook_file(dir: &Path, id: &str) -> Result<PathBuf, VcfFileClientError> {
+    if id.is_empty() || id.starts_with('.') || id.contains(['/', '\\']) {
+        return Err(VcfFileClientError::InvalidName(id.to_string()));
+    }
+
+    Ok(dir.join(format!("{id}.{EXTENSION}")))
+}
+
+/// Reads the file at `path`, a missing file reading as empty.
+fn read_or_empty(path: &Path) -> Result<Vec<u8>, VcfFileClientError> {
+    match fs::read(path) {
+        Ok(data) => Ok(data),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Checks that `contents` hold exactly one vCard, returning its `UID`
+/// when it has one.
+fn check_contents(contents: &[u8]) -> Result<Option<String>, VcfFileClientError> {
+    if contents.is_empty() {
+        return Err(VcfFileClientError::EmptyBody);
+    }
+
+    if split_vcf(contents).len() != 1 {
+        return Err(VcfFileClientError::InvalidCard);
+    }
+
+    find_uid(contents).ok_or(VcfFileClientError::InvalidCard)
+}
+
+/// Checks `if_match` against the tag of the current `contents` of
+/// `card_id`.
+fn check_precondition(
+    card_id: &str,
+    contents: &[u8],
+    if_match: &str,
+) -> Result<(), VcfFileClientError> {
+    let actual = content_tag(contents);
+
+    if tag_matches(if_match, &actual) {
+        return Ok(());
+    }
+
+    Err(VcfFileClientError::PreconditionFailed {
+        card_id: card_id.to_string(),
+        actual,
+    })
+}
+
+/// Rewrites the file at `path` with `entries`, atomically.
+fn store(path: &Path, entries: &[(String, Vec<u8>)]) -> Result<(), VcfFileClientError> {
+    let data = join_vcf(entries.iter().map(|(_, contents)| contents.as_slice()));
+    Ok(write_atomic(path, &data)?)
+}
+
+impl AddressbookBackend for VcfFileClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(VcfFileClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(VcfFileClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(VcfFileClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(VcfFileClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(VcfFileClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(VcfFileClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VcfFileClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VcfFileClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(VcfFileClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(VcfFileClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(VcfFileClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(VcfFileClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(VcfFileClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(VcfFileClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VcfFileClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VcfFileClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alice\r\nEND:VCARD\r\n";
+    const ALICIA: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alicia\r\nEND:VCARD\r\n";
+    const ALICE_BIS: &[u8] =
+        b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alice bis\r\nEND:VCARD\r\n";
+    const ALICE_2: &[u8] =
+        b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice-2\r\nFN:Alice 2\r\nEND:VCARD\r\n";
+    const BOB: &[u8] = b"BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Bob\r\nEND:VCARD\r\n";
+
+    /// Creates an empty directory unique to the test `name`.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("io-addressbook-vcf-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn ids(client: &VcfFileClient) -> Vec<String> {
+        let cards = client.list_cards("ab", None, None).unwrap();
+        cards.into_iter().map(|card| card.id).collect()
+    }
+
+    #[test]
+    fn rewrite_file_atomically() {
+        let dir = temp_dir("atomic");
+        let client = VcfFileClient::directory(&dir);
+        client.create_addressbook("ab", None, None).unwrap();
+
+        let bob = client.create_card("ab", BOB.to_vec()).unwrap();
+        client.create_card("ab", ALICE.to_vec()).unwrap();
+        client
+            .update_card("ab", "alice", ALICIA.to_vec(), None)
+            .unwrap();
+
+        let data = fs::read(dir.join("ab.vcf")).unwrap();
+        let cards = split_vcf(&data);
+
+        assert_eq!(cards.len(), 2);
+        assert_eq!(content_tag(&cards[0]), bob.etag.unwrap());
+        assert_eq!(cards[1], ALICIA);
+
+        // NOTE: the temporary file is renamed over the addressbook.
+        let names: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["ab.vcf"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keep_file_on_failed_rewrite() {
+        let dir = temp_dir("failed");
+        let path = dir.join("ab.vcf");
+        let client = VcfFileClient::file(&path);
+        client.create_card("ab", ALICE.to_vec()).unwrap();
+
+        // NOTE: a directory in place of the temporary file makes the
+        // rewrite fail before the rename.
+        fs::create_dir(dir.join(".ab.vcf.tmp")).unwrap();
+
+        let err = client.update_card("ab", "alice", ALICIA.to_vec(), None);
+
+        assert!(matches!(err, Err(VcfFileClientError::Io(_))));
+        assert_eq!(fs::read(&path).unwrap(), ALICE);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn disambiguate_colliding_ids() {
+        let dir = temp_dir("colliding");
+        let path = dir.join("ab.vcf");
+        let data = join_vcf([ALICE, ALICE_BIS, ALICE_2, BOB, BOB]);
+        fs::write(&path, &data).unwrap();
+
+        let client = VcfFileClient::file(&path);
+        let bob = content_tag(BOB);
+
+        assert_eq!(
+            ids(&client),
+            [
+                "alice".to_string(),
+                "alice-3".to_string(),
+                "alice-2".to_string(),
+                bob.clone(),
+                format!("{bob}-2"),
+            ]
+        );
+
+        assert_eq!(
+            client.get_card("ab", "alice-3").unwrap().contents,
+            ALICE_BIS
+        );
+        assert_eq!(client.get_card("ab", "alice-2").unwrap().contents, ALICE_2);
+
+        // NOTE: the duplicate keeps its disambiguated id when updated
+        // without UID, and reads back at the same position.
+        let without_uid = b"BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Alice ter\r\nEND:VCARD\r\n";
+        client
+            .update_card("ab", "alice-3", without_uid.to_vec(), None)
+            .unwrap();
+
+        let card = client.get_card("ab", "alice-3").unwrap();
+        assert_eq!(find_uid(&card.contents), Some(Some("alice".to_string())));
+
+        let err = client.create_card("ab", ALICE.to_vec()).unwrap_err();
+        assert!(matches!(err, VcfFileClientError::CardAlreadyExists(id) if id == "alice"));
+
+        client.delete_card("ab", &format!("{bob}-2"), None).unwrap();
+        assert_eq!(ids(&client).len(), 4);
+        assert_eq!(split_vcf(&fs::read(&path).unwrap()).len(), 4);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+//! Conversions between multi-card `.vcf` data and the shared types used
+//! by [`VcfFileClient`], plus the `From` impl that wraps it into the
+//! unified client's `.vcf` file variant.
+//!
+//! [`VcfFileClient`]: crate::vcf_file::client::VcfFileClient
+
+use alloc::{
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    addressbook::Addressbook,
+    card::{
+        Card, CardRef, split_vcf,
+        tag::content_tag,
+        uid::{card_id_from_uid, find_uid},
+    },
+};
+
+impl From<crate::vcf_file::client::VcfFileClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::vcf_file::client::VcfFileClient) -> Self {
+        Self::VcfFile(client)
+    }
+}
+
+/// File extension of the addressbooks of a directory.
+pub(crate) const EXTENSION: &str = "vcf";
+
+/// Cards of a `.vcf` file, as `(card id, contents)` pairs in file
+/// order.
+pub(crate) type Entries = Vec<(String, Vec<u8>)>;
+
+/// Maps the `.vcf` file of `id` to a shared [`Addressbook`].
+///
+/// The id doubles as the name; the `ctag` is the tag of the whole file,
+/// so that any card change bumps it.
+pub(crate) fn addressbook_from_file(id: &str, data: &[u8]) -> Addressbook {
+    Addressbook {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: None,
+        color: None,
+        ctag: Some(content_tag(data)),
+        sync_token: None,
+    }
+}
+
+/// Splits multi-card `.vcf` data into `(card id, contents)` pairs, in
+/// file order.
+///
+/// Ids derive from the vCard `UID`, or from the tag of the contents
+/// when there is none, so the ids of a plain export stay stable across
+/// reads. Cards colliding on an id (shared `UID`, identical contents
+/// without `UID`) keep it for the first occurrence; the next ones get a
+/// `-2`, `-3`… suffix, skipping ids already taken.
+pub(crate) fn cards_from_vcf(data: &[u8]) -> Entries {
+    let cards: Entries = split_vcf(data)
+        .into_iter()
+        .map(|contents| (card_id(&contents), contents))
+        .collect();
+
+    let mut taken: BTreeSet<String> = cards.iter().map(|(id, _)| id.clone()).collect();
+    let mut seen = BTreeSet::new();
+
+    cards
+        .into_iter()
+        .map(|(id, contents)| {
+            if seen.insert(id.clone()) {
+                return (id, contents);
+            }
+
+            let unique = (2..)
+                .map(|n| format!("{id}-{n}"))
+                .find(|candidate| !taken.contains(candidate))
+                .unwrap_or_default();
+
+            taken.insert(unique.clone());
+            (unique, contents)
+        })
+        .collect()
+}
+
+/// Returns the id of the raw vCard `contents`, see [`cards_from_vcf`].
+fn card_id(contents: &[u8]) -> String {
+    match find_uid(contents) {
+        Some(Some(uid)) => card_id_from_uid(&uid),
+        _ => content_tag(contents),
+    }
+}
+
+/// Maps a `(card id, contents)` pair to a shared [`Card`].
+pub(crate) fn card_from_entry(addressbook_id: &str, id: &str, contents: &[u8]) -> Card {
+    Card {
+        id: id.to_string(),
+        addressbook_id: addressbook_id.to_string(),
+        etag: Some(content_tag(contents)),
+        contents: contents.to_vec(),
+    }
+}
+
+/// Maps a `(card id, contents)` pair to a [`CardRef`].
+pub(crate) fn card_ref_from_entry(id: &str, contents: &[u8]) -> CardRef {
+    CardRef {
+        id: id.to_string(),
+        href: None,
+        etag: Some(content_tag(contents)),
+    }
+}
+
+/// 1-indexed pagination on an in-memory list. `page_size = None`
+/// returns the full slice; `page_size = 0` or a page past the end
+/// returns an empty vector.
+pub(crate) fn paginate<T>(items: Vec<T>, page: Option<u32>, page_size: Option<u32>) -> Vec<T> {
+    let Some(size) = page_size else {
+        return items;
+    };
+
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let page = page.unwrap_or(1).max(1);
+    let skip = ((page - 1) as usize).saturating_mul(size as usize);
+
+    if skip >= items.len() {
+        return Vec::new();
+    }
+
+    items.into_iter().skip(skip).take(size as usize).collect()
+}
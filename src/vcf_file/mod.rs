@@ -0,0 +1,4 @@
+//! `.vcf` file backend: the std client plus its conversions.
+
+pub mod client;
+pub mod convert;
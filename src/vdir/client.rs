@@ -7,18 +7,13 @@
 //! reachable through [`VdirClient::inner`].
 
 use alloc::{
-    collections::{BTreeMap, BTreeSet},
+    collections::BTreeSet,
     string::{String, ToString},
-    vec,
     vec::Vec,
 };
 use std::{fs, io};
 
-use getrandom::fill;
-use io_vdir::{
-    client::VdirClient as InnerVdirClient, collection::Collection, coroutine::*, path::VdirPath,
-};
-use log::trace;
+use io_vdir::{client::VdirClient as InnerVdirClient, coroutine::*, path::VdirPath};
 use thiserror::Error;
 
 use crate::{
@@ -31,19 +26,159 @@ use crate::{
             update::{VdirAddressbookUpdate, VdirAddressbookUpdateError},
         },
     },
+    backend::AddressbookBackend,
+    capabilities::Capabilities,
     card::{
-        Card,
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
         vdir::{
             create::{VdirCardCreate, VdirCardCreateError},
             delete::{VdirCardDelete, VdirCardDeleteError},
             get::{VdirCardGet, VdirCardGetError},
             list::{VdirCardList, VdirCardListError},
+            multiget::{VdirCardMultiget, VdirCardMultigetError},
+            query::{VdirCardQuery, VdirCardQueryError},
+            refs::{VdirCardRefList, VdirCardRefListError},
             update::{VdirCardUpdate, VdirCardUpdateError},
         },
     },
-    vdir::convert::{paginate, resolve_addressbook_path},
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    vdir::convert::{
+        addressbook_path, files_tag, paginate, patch_collection, resolve_addressbook_path,
+        static_capabilities,
+    },
 };
 
+/// Pumps a standard-shape Vdir coroutine until it terminates,
+/// returning from the enclosing function.
+///
+/// Shared by [`VdirClient::run`] and its async counterpart: requests
+/// are served through the `fs` module and the `dir_entries` function
+/// in scope at the call site (`std::fs` or `tokio::fs`), and the
+/// trailing `await` turns every filesystem call into an awaited one.
+macro_rules! run_vdir {
+    ($coroutine:expr $(, $await:ident)?) => {{
+        let mut coroutine = $coroutine;
+        let mut arg: Option<VdirReply> = None;
+
+        loop {
+            let y = match coroutine.resume(arg.take()) {
+                VdirCoroutineState::Complete(Ok(out)) => return Ok(out),
+                VdirCoroutineState::Complete(Err(err)) => return Err(err.into()),
+                VdirCoroutineState::Yielded(y) => y,
+            };
+
+            let reply = match y {
+                VdirYield::WantsRandom { len } => {
+                    let mut bytes = ::alloc::vec![0u8; len];
+                    ::getrandom::fill(&mut bytes).map_err(VdirClientError::Random)?;
+                    VdirReply::Random(bytes)
+                }
+                VdirYield::WantsFileExists(paths) => {
+                    let mut out = ::alloc::collections::BTreeMap::new();
+                    for path in paths {
+                        let exists = fs::metadata(path.as_str())
+                            $(.$await)?
+                            .map(|m| m.is_file())
+                            .unwrap_or(false);
+                        ::log::trace!("file_exists {path}: {exists}");
+                        out.insert(path, exists);
+                    }
+                    VdirReply::FileExists(out)
+                }
+                VdirYield::WantsDirExists(paths) => {
+                    let mut out = ::alloc::collections::BTreeMap::new();
+                    for path in paths {
+                        let exists = fs::metadata(path.as_str())
+                            $(.$await)?
+                            .map(|m| m.is_dir())
+                            .unwrap_or(false);
+                        ::log::trace!("dir_exists {path}: {exists}");
+                        out.insert(path, exists);
+                    }
+                    VdirReply::DirExists(out)
+                }
+                VdirYield::WantsDirRead(paths) => {
+                    let mut entries = ::alloc::collections::BTreeMap::new();
+                    for path in paths {
+                        ::log::trace!("read_dir {path}");
+                        let names = dir_entries(&path)$(.$await)??;
+                        entries.insert(path, names);
+                    }
+                    VdirReply::DirRead(entries)
+                }
+                VdirYield::WantsFileRead(paths) => {
+                    let mut contents = ::alloc::collections::BTreeMap::new();
+                    for path in paths {
+                        ::log::trace!("read_file {path}");
+                        // NOTE: a file vanishing after its existence
+                        // check is left out of the reply, which the
+                        // coroutine reports as missing.
+                        match fs::read(path.as_str())$(.$await)? {
+                            Ok(bytes) => {
+                                contents.insert(path, bytes);
+                            }
+                            Err(err) if err.kind() == ::std::io::ErrorKind::NotFound => {}
+                            Err(err) => return Err(err.into()),
+                        }
+                    }
+                    VdirReply::FileRead(contents)
+                }
+                VdirYield::WantsFileCreate(files) => {
+                    for (path, bytes) in files {
+                        ::log::trace!("write {path} ({} bytes)", bytes.len());
+                        if let Some(parent) = ::std::path::Path::new(path.as_str()).parent() {
+                            fs::create_dir_all(parent)$(.$await)??;
+                        }
+                        fs::write(path.as_str(), &bytes)$(.$await)??;
+                    }
+                    VdirReply::FileCreate
+                }
+                VdirYield::WantsDirCreate(paths) => {
+                    for path in paths {
+                        ::log::trace!("create_dir_all {path}");
+                        fs::create_dir_all(path.as_str())$(.$await)??;
+                    }
+                    VdirReply::DirCreate
+                }
+                VdirYield::WantsDirRemove(paths) => {
+                    for path in paths {
+                        ::log::trace!("remove_dir_all {path}");
+                        fs::remove_dir_all(path.as_str())$(.$await)??;
+                    }
+                    VdirReply::DirRemove
+                }
+                VdirYield::WantsFileRemove(paths) => {
+                    for path in paths {
+                        ::log::trace!("remove_file {path}");
+                        fs::remove_file(path.as_str())$(.$await)??;
+                    }
+                    VdirReply::FileRemove
+                }
+                VdirYield::WantsRename(pairs) => {
+                    for (from, to) in pairs {
+                        ::log::trace!("rename {from} -> {to}");
+                        fs::rename(from.as_str(), to.as_str())$(.$await)??;
+                    }
+                    VdirReply::Rename
+                }
+                VdirYield::WantsCopy(pairs) => {
+                    for (from, to) in pairs {
+                        ::log::trace!("copy {from} -> {to}");
+                        fs::copy(from.as_str(), to.as_str())$(.$await)??;
+                    }
+                    VdirReply::Copy
+                }
+            };
+
+            arg = Some(reply);
+        }
+    }};
+}
+
+#[cfg(feature = "tokio")]
+pub(crate) use run_vdir;
+
 /// Errors surfaced by [`VdirClient`] while running a coroutine.
 ///
 /// One variant per shared-API Vdir coroutine, plus filesystem and
@@ -79,9 +214,54 @@ pub enum VdirClientError {
     #[error(transparent)]
     CardList(#[from] VdirCardListError),
     #[error(transparent)]
+    CardMultiget(#[from] VdirCardMultigetError),
+    #[error(transparent)]
+    CardQuery(#[from] VdirCardQueryError),
+    #[error(transparent)]
+    CardRefList(#[from] VdirCardRefListError),
+    #[error(transparent)]
     CardUpdate(#[from] VdirCardUpdateError),
 }
 
+impl VdirClientError {
+    /// Classifies the error, see [`ErrorKind`]. Failures reported by
+    /// io-vdir itself are not detailed enough to classify, and fall
+    /// back to [`ErrorKind::Other`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::AddressbookNotFound(_)
+            | Self::CardGet(VdirCardGetError::NotFound(_))
+            | Self::CardUpdate(VdirCardUpdateError::NotFound(_))
+            | Self::CardDelete(VdirCardDeleteError::NotFound(_)) => ErrorKind::NotFound,
+            Self::CardCreate(VdirCardCreateError::AlreadyExists(_))
+            | Self::CardDelete(VdirCardDeleteError::PreconditionFailed { .. })
+            | Self::CardUpdate(VdirCardUpdateError::PreconditionFailed { .. }) => {
+                ErrorKind::Conflict
+            }
+            Self::InvalidAddressbook(_)
+            | Self::AddressbookCreate(VdirAddressbookCreateError::InvalidName)
+            | Self::CardCreate(VdirCardCreateError::EmptyBody)
+            | Self::CardGet(VdirCardGetError::InvalidCardId(_))
+            | Self::CardMultiget(VdirCardMultigetError::InvalidCardId(_))
+            | Self::CardUpdate(VdirCardUpdateError::EmptyBody) => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current synthesized tag of the card when the error
+    /// is a failed `if_match` precondition.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::CardDelete(VdirCardDeleteError::PreconditionFailed { actual, .. })
+            | Self::CardUpdate(VdirCardUpdateError::PreconditionFailed { actual, .. }) => {
+                Some(actual)
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Std-blocking Vdir addressbook client built on a filesystem root.
 #[derive(Debug)]
 pub struct VdirClient {
@@ -97,125 +277,34 @@ impl VdirClient {
     /// Pumps any standard-shape Vdir coroutine (`Yield = VdirYield`,
     /// `Return = Result<T, E>`) against the local filesystem until it
     /// terminates.
-    pub fn run<C, T, E>(&self, mut coroutine: C) -> Result<T, VdirClientError>
+    pub fn run<C, T, E>(&self, coroutine: C) -> Result<T, VdirClientError>
     where
         C: VdirCoroutine<Yield = VdirYield, Return = Result<T, E>>,
         VdirClientError: From<E>,
     {
-        let mut arg: Option<VdirReply> = None;
+        run_vdir!(coroutine)
+    }
 
-        loop {
-            match coroutine.resume(arg.take()) {
-                VdirCoroutineState::Complete(Ok(out)) => return Ok(out),
-                VdirCoroutineState::Complete(Err(err)) => return Err(err.into()),
-                VdirCoroutineState::Yielded(VdirYield::WantsRandom { len }) => {
-                    let mut bytes = vec![0u8; len];
-                    fill(&mut bytes).map_err(VdirClientError::Random)?;
-                    arg = Some(VdirReply::Random(bytes));
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsFileExists(paths)) => {
-                    let mut out = BTreeMap::new();
-                    for path in paths {
-                        let exists = fs::metadata(path.as_str())
-                            .map(|m| m.is_file())
-                            .unwrap_or(false);
-                        trace!("file_exists {path}: {exists}");
-                        out.insert(path, exists);
-                    }
-                    arg = Some(VdirReply::FileExists(out));
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsDirExists(paths)) => {
-                    let mut out = BTreeMap::new();
-                    for path in paths {
-                        let exists = fs::metadata(path.as_str())
-                            .map(|m| m.is_dir())
-                            .unwrap_or(false);
-                        trace!("dir_exists {path}: {exists}");
-                        out.insert(path, exists);
-                    }
-                    arg = Some(VdirReply::DirExists(out));
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsDirRead(paths)) => {
-                    let mut entries = BTreeMap::new();
-                    for path in paths {
-                        trace!("read_dir {path}");
-                        let mut names = BTreeSet::new();
-                        match fs::read_dir(path.as_str()) {
-                            Ok(iter) => {
-                                for entry in iter {
-                                    let entry = entry?;
-                                    names.insert(normalize_path(entry.path()));
-                                }
-                            }
-                            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
-                            Err(err) => return Err(err.into()),
-                        }
-                        entries.insert(path, names);
-                    }
-                    arg = Some(VdirReply::DirRead(entries));
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsFileRead(paths)) => {
-                    let mut contents = BTreeMap::new();
-                    for path in paths {
-                        trace!("read_file {path}");
-                        let bytes = fs::read(path.as_str())?;
-                        contents.insert(path, bytes);
-                    }
-                    arg = Some(VdirReply::FileRead(contents));
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsFileCreate(files)) => {
-                    for (path, bytes) in files {
-                        trace!("write {path} ({} bytes)", bytes.len());
-                        if let Some(parent) = std::path::Path::new(path.as_str()).parent() {
-                            fs::create_dir_all(parent)?;
-                        }
-                        fs::write(path.as_str(), &bytes)?;
-                    }
-                    arg = Some(VdirReply::FileCreate);
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsDirCreate(paths)) => {
-                    for path in paths {
-                        trace!("create_dir_all {path}");
-                        fs::create_dir_all(path.as_str())?;
-                    }
-                    arg = Some(VdirReply::DirCreate);
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsDirRemove(paths)) => {
-                    for path in paths {
-                        trace!("remove_dir_all {path}");
-                        fs::remove_dir_all(path.as_str())?;
-                    }
-                    arg = Some(VdirReply::DirRemove);
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsFileRemove(paths)) => {
-                    for path in paths {
-                        trace!("remove_file {path}");
-                        fs::remove_file(path.as_str())?;
-                    }
-                    arg = Some(VdirReply::FileRemove);
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsRename(pairs)) => {
-                    for (from, to) in pairs {
-                        trace!("rename {from} -> {to}");
-                        fs::rename(from.as_str(), to.as_str())?;
-                    }
-                    arg = Some(VdirReply::Rename);
-                }
-                VdirCoroutineState::Yielded(VdirYield::WantsCopy(pairs)) => {
-                    for (from, to) in pairs {
-                        trace!("copy {from} -> {to}");
-                        fs::copy(from.as_str(), to.as_str())?;
-                    }
-                    arg = Some(VdirReply::Copy);
-                }
-            }
-        }
+    /// Returns the static capabilities of the vdir backend.
+    pub fn capabilities(&self) -> Capabilities {
+        static_capabilities()
     }
 
     /// Lists every addressbook under the configured root, sorted by
     /// name.
+    ///
+    /// Each addressbook gets a synthesized `ctag` (see
+    /// [`collection_tag`]) so callers can skip unchanged ones without
+    /// listing their cards.
     pub fn list_addressbooks(&self) -> Result<Vec<Addressbook>, VdirClientError> {
-        self.run(VdirAddressbookList::new(self.inner.root().clone()))
+        let mut addressbooks = self.run(VdirAddressbookList::new(self.inner.root().clone()))?;
+
+        for addressbook in &mut addressbooks {
+            let path = addressbook_path(self.inner.root(), &addressbook.id);
+            addressbook.ctag = Some(collection_tag(&path)?);
+        }
+
+        Ok(addressbooks)
     }
 
     /// Creates an addressbook named `name` under the root. Returns the
@@ -249,23 +338,9 @@ impl VdirClient {
             .find(|c| c.id() == id)
             .ok_or_else(|| VdirClientError::AddressbookNotFound(id.to_string()))?;
 
-        let next = Collection {
-            path,
-            display_name: match patch.name {
-                Some(name) => Some(name),
-                None => current.display_name,
-            },
-            description: match patch.description {
-                Some(desc) => desc,
-                None => current.description,
-            },
-            color: match patch.color {
-                Some(color) => color,
-                None => current.color,
-            },
-        };
-
-        self.run(VdirAddressbookUpdate::new(next))
+        self.run(VdirAddressbookUpdate::new(patch_collection(
+            path, current, patch,
+        )))
     }
 
     /// Recursively removes the addressbook identified by `id`.
@@ -287,46 +362,325 @@ impl VdirClient {
         Ok(paginate(cards, page, page_size))
     }
 
+    /// Lists the id and synthesized tag of every card in
+    /// `addressbook_id`, sorted by id.
+    pub fn list_card_refs(&self, addressbook_id: &str) -> Result<Vec<CardRef>, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardRefList::new(path))
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`,
+    /// evaluated locally over every card.
+    pub fn search_cards(
+        &self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardQuery::new(path, addressbook_id, filter))
+    }
+
+    /// Returns every card of `addressbook_id` as a full
+    /// [`CardChanges`] listing: vdir has no sync token, so
+    /// `sync_token` is ignored and the result is never a delta.
+    pub fn sync_cards(
+        &self,
+        addressbook_id: &str,
+        _sync_token: Option<&str>,
+    ) -> Result<CardChanges, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        let cards = self.run(VdirCardList::new(path, addressbook_id))?;
+
+        Ok(CardChanges {
+            cards,
+            deleted: Vec::new(),
+            sync_token: None,
+            full: true,
+        })
+    }
+
     /// Fetches `card_id` from `addressbook_id`.
     pub fn get_card(&self, addressbook_id: &str, card_id: &str) -> Result<Card, VdirClientError> {
         let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
         self.run(VdirCardGet::new(path, addressbook_id, card_id))
     }
 
-    /// Appends a new vCard to `addressbook_id`. Returns its assigned
-    /// id.
+    /// Fetches every card of `card_ids` from `addressbook_id` at
+    /// once. Ids with no card end up in [`CardBatch::missing`].
+    pub fn get_cards(
+        &self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardMultiget::new(path, addressbook_id, card_ids)?)
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the default
+    /// [`CardCreateOptions`]. Returns its assigned id and synthesized
+    /// tag.
     pub fn create_card(
         &self,
         addressbook_id: &str,
         contents: Vec<u8>,
-    ) -> Result<String, VdirClientError> {
+    ) -> Result<CardRef, VdirClientError> {
+        self.create_card_with_options(addressbook_id, contents, CardCreateOptions::default())
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the given
+    /// `options`. Returns its assigned id and synthesized tag.
+    pub fn create_card_with_options(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, VdirClientError> {
         let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
-        self.run(VdirCardCreate::new(path, contents)?)
+        self.run(VdirCardCreate::new(path, contents, options)?)
     }
 
-    /// Overwrites `card_id` inside `addressbook_id`. `if_match` is
-    /// ignored: vdir has no entity-tag concept.
+    /// Overwrites `card_id` inside `addressbook_id`, gating on
+    /// `if_match` (a tag synthesized from the card contents, see
+    /// [`Card::etag`]) when present. Returns the card id and the tag of
+    /// the new contents.
     pub fn update_card(
         &self,
         addressbook_id: &str,
         card_id: &str,
         contents: Vec<u8>,
-        _if_match: Option<&str>,
-    ) -> Result<(), VdirClientError> {
+        if_match: Option<&str>,
+    ) -> Result<CardRef, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardUpdate::new(path, card_id, contents, if_match)?)
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating the card or overwriting it unconditionally. Returns
+    /// the card id and synthesized tag.
+    pub fn upsert_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, VdirClientError> {
         let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
-        self.run(VdirCardUpdate::new(path, card_id, contents)?)
+        self.run(VdirCardUpdate::new(path, card_id, contents, None)?)
     }
 
-    /// Permanently deletes `card_id` from `addressbook_id`.
-    pub fn delete_card(&self, addressbook_id: &str, card_id: &str) -> Result<(), VdirClientError> {
+    /// Permanently deletes `card_id` from `addressbook_id`, gating on
+    /// `if_match` when present.
+    pub fn delete_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), VdirClientError> {
         let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
-        self.run(VdirCardDelete::new(path, card_id))
+        self.run(VdirCardDelete::new(path, card_id, if_match))
     }
 }
 
+impl AddressbookBackend for VdirClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(VdirClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(VdirClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(VdirClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(VdirClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(VdirClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(VdirClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VdirClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VdirClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(VdirClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(VdirClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(VdirClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(VdirClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(VdirClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(VdirClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VdirClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VdirClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
+
+/// Synthesizes a collection tag for the addressbook directory at
+/// `path` from the metadata of its files, see [`files_tag`].
+fn collection_tag(path: &VdirPath) -> Result<String, VdirClientError> {
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(path.as_str())? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+
+        if !meta.is_file() {
+            continue;
+        }
+
+        let mtime = meta
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        files.push((entry.file_name().into_encoded_bytes(), meta.len(), mtime));
+    }
+
+    Ok(files_tag(files))
+}
+
+/// Lists the entries of the directory at `path`, none when it does
+/// not exist.
+fn dir_entries(path: &VdirPath) -> io::Result<BTreeSet<VdirPath>> {
+    let mut names = BTreeSet::new();
+
+    match fs::read_dir(path.as_str()) {
+        Ok(iter) => {
+            for entry in iter {
+                names.insert(normalize_path(entry?.path()));
+            }
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    Ok(names)
+}
+
 /// Normalizes a host [`std::path::PathBuf`] into a `/`-separated
 /// [`VdirPath`].
-fn normalize_path(path: std::path::PathBuf) -> VdirPath {
+pub(crate) fn normalize_path(path: std::path::PathBuf) -> VdirPath {
     let s = path.to_string_lossy().into_owned();
     #[cfg(windows)]
     let s = s.replace('\\', "/");
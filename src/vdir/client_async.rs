@@ -0,0 +1,328 @@
+//! Tokio Vdir addressbook client.
+//!
+//! Async counterpart of [`VdirClient`]: drives the same io-addressbook
+//! Vdir coroutines, but serves their filesystem requests through
+//! [`tokio::fs`] instead of blocking [`std::fs`] calls. Methods mirror
+//! the std client one to one, with the same semantics and the same
+//! [`VdirClientError`].
+//!
+//! [`VdirClient`]: crate::vdir::client::VdirClient
+
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::io;
+
+use io_vdir::{
+    client::VdirClient as InnerVdirClient,
+    collection::list::{VdirCollectionList, VdirCollectionListError, VdirCollectionListOptions},
+    coroutine::*,
+    path::VdirPath,
+};
+use tokio::fs;
+
+use crate::{
+    addressbook::{
+        Addressbook, AddressbookDiff,
+        vdir::{
+            create::VdirAddressbookCreate, delete::VdirAddressbookDelete,
+            list::VdirAddressbookList, update::VdirAddressbookUpdate,
+        },
+    },
+    capabilities::Capabilities,
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
+        vdir::{
+            create::VdirCardCreate, delete::VdirCardDelete, get::VdirCardGet, list::VdirCardList,
+            multiget::VdirCardMultiget, query::VdirCardQuery, refs::VdirCardRefList,
+            update::VdirCardUpdate,
+        },
+    },
+    vdir::{
+        client::{VdirClientError, normalize_path, run_vdir},
+        convert::{
+            addressbook_path, files_tag, paginate, patch_collection, resolve_addressbook_path,
+            static_capabilities,
+        },
+    },
+};
+
+// NOTE: the std client reads the current collection metadata through
+// the blocking inner client; the async one runs the upstream list
+// coroutine instead, whose failure is an addressbook list failure.
+impl From<VdirCollectionListError> for VdirClientError {
+    fn from(err: VdirCollectionListError) -> Self {
+        Self::AddressbookList(err.into())
+    }
+}
+
+/// Tokio Vdir addressbook client built on a filesystem root.
+#[derive(Debug)]
+pub struct VdirClientAsync {
+    pub inner: InnerVdirClient,
+}
+
+impl VdirClientAsync {
+    /// Wraps an already-built inner client. Only its root is used.
+    pub fn new(inner: InnerVdirClient) -> Self {
+        Self { inner }
+    }
+
+    /// Pumps any standard-shape Vdir coroutine (`Yield = VdirYield`,
+    /// `Return = Result<T, E>`) against the local filesystem through
+    /// [`tokio::fs`] until it terminates.
+    pub async fn run<C, T, E>(&self, coroutine: C) -> Result<T, VdirClientError>
+    where
+        C: VdirCoroutine<Yield = VdirYield, Return = Result<T, E>>,
+        VdirClientError: From<E>,
+    {
+        run_vdir!(coroutine, await)
+    }
+
+    /// Returns the static capabilities of the vdir backend.
+    pub fn capabilities(&self) -> Capabilities {
+        static_capabilities()
+    }
+
+    /// Lists every addressbook under the configured root, sorted by
+    /// name, each with its synthesized `ctag`.
+    pub async fn list_addressbooks(&self) -> Result<Vec<Addressbook>, VdirClientError> {
+        let root = self.inner.root().clone();
+        let mut addressbooks = self.run(VdirAddressbookList::new(root)).await?;
+
+        for addressbook in &mut addressbooks {
+            let path = addressbook_path(self.inner.root(), &addressbook.id);
+            addressbook.ctag = Some(collection_tag(&path).await?);
+        }
+
+        Ok(addressbooks)
+    }
+
+    /// Creates an addressbook named `name` under the root. Returns the
+    /// new addressbook id.
+    pub async fn create_addressbook(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, VdirClientError> {
+        let root = self.inner.root().clone();
+        let coroutine = VdirAddressbookCreate::new(root, name, description, color)?;
+        self.run(coroutine).await
+    }
+
+    /// Applies `patch` to the addressbook identified by `id`, merging
+    /// it against the current collection metadata.
+    pub async fn update_addressbook(
+        &self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, id)?;
+
+        let root = self.inner.root().clone();
+        let options = VdirCollectionListOptions::default();
+        let collections = self.run(VdirCollectionList::new(root, options)).await?;
+        let current = collections
+            .into_iter()
+            .find(|c| c.id() == id)
+            .ok_or_else(|| VdirClientError::AddressbookNotFound(id.to_string()))?;
+
+        let coroutine = VdirAddressbookUpdate::new(patch_collection(path, current, patch));
+        self.run(coroutine).await
+    }
+
+    /// Recursively removes the addressbook identified by `id`.
+    pub async fn delete_addressbook(&self, id: &str) -> Result<(), VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, id)?;
+        self.run(VdirAddressbookDelete::new(path)).await
+    }
+
+    /// Lists cards inside `addressbook_id`, applying 1-indexed
+    /// pagination.
+    pub async fn list_cards(
+        &self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        let cards = self.run(VdirCardList::new(path, addressbook_id)).await?;
+        Ok(paginate(cards, page, page_size))
+    }
+
+    /// Lists the id and synthesized tag of every card in
+    /// `addressbook_id`, sorted by id.
+    pub async fn list_card_refs(
+        &self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardRefList::new(path)).await
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`,
+    /// evaluated locally over every card.
+    pub async fn search_cards(
+        &self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardQuery::new(path, addressbook_id, filter))
+            .await
+    }
+
+    /// Returns every card of `addressbook_id` as a full
+    /// [`CardChanges`] listing, see
+    /// [`VdirClient::sync_cards`](crate::vdir::client::VdirClient::sync_cards).
+    pub async fn sync_cards(
+        &self,
+        addressbook_id: &str,
+        _sync_token: Option<&str>,
+    ) -> Result<CardChanges, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        let cards = self.run(VdirCardList::new(path, addressbook_id)).await?;
+
+        Ok(CardChanges {
+            cards,
+            deleted: Vec::new(),
+            sync_token: None,
+            full: true,
+        })
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub async fn get_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardGet::new(path, addressbook_id, card_id))
+            .await
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id` at
+    /// once. Ids with no card end up in [`CardBatch::missing`].
+    pub async fn get_cards(
+        &self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardMultiget::new(path, addressbook_id, card_ids)?)
+            .await
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the default
+    /// [`CardCreateOptions`]. Returns its assigned id and synthesized
+    /// tag.
+    pub async fn create_card(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, VdirClientError> {
+        let options = CardCreateOptions::default();
+        self.create_card_with_options(addressbook_id, contents, options)
+            .await
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the given
+    /// `options`. Returns its assigned id and synthesized tag.
+    pub async fn create_card_with_options(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardCreate::new(path, contents, options)?)
+            .await
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id`, gating on
+    /// `if_match` when present. Returns the card id and the tag of the
+    /// new contents.
+    pub async fn update_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardUpdate::new(path, card_id, contents, if_match)?)
+            .await
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating the card or overwriting it unconditionally.
+    pub async fn upsert_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardUpdate::new(path, card_id, contents, None)?)
+            .await
+    }
+
+    /// Permanently deletes `card_id` from `addressbook_id`, gating on
+    /// `if_match` when present.
+    pub async fn delete_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), VdirClientError> {
+        let path = resolve_addressbook_path(&self.inner, addressbook_id)?;
+        self.run(VdirCardDelete::new(path, card_id, if_match)).await
+    }
+}
+
+/// Synthesizes a collection tag for the addressbook directory at
+/// `path` from the metadata of its files, see [`files_tag`].
+async fn collection_tag(path: &VdirPath) -> Result<String, VdirClientError> {
+    let mut files = Vec::new();
+    let mut entries = fs::read_dir(path.as_str()).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let meta = entry.metadata().await?;
+
+        if !meta.is_file() {
+            continue;
+        }
+
+        let mtime = meta
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        files.push((entry.file_name().into_encoded_bytes(), meta.len(), mtime));
+    }
+
+    Ok(files_tag(files))
+}
+
+/// Lists the entries of the directory at `path`, none when it does
+/// not exist.
+async fn dir_entries(path: &VdirPath) -> io::Result<BTreeSet<VdirPath>> {
+    let mut names = BTreeSet::new();
+
+    match fs::read_dir(path.as_str()).await {
+        Ok(mut iter) => {
+            while let Some(entry) = iter.next_entry().await? {
+                names.insert(normalize_path(entry.path()));
+            }
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    Ok(names)
+}
@@ -15,7 +15,14 @@ use io_vdir::{client::VdirClient, collection::Collection, item::Item, path::Vdir
 
 #[cfg(feature = "client")]
 use crate::vdir::client::VdirClientError;
-use crate::{addressbook::Addressbook, card::Card};
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    capabilities::{AddressbookField, Capabilities},
+    card::{
+        Card,
+        tag::{TagHasher, content_tag},
+    },
+};
 
 #[cfg(feature = "client")]
 impl From<crate::vdir::client::VdirClient> for crate::client::AddressbookClientStd {
@@ -24,11 +31,39 @@ impl From<crate::vdir::client::VdirClient> for crate::client::AddressbookClientS
     }
 }
 
+#[cfg(feature = "vdir-git")]
+impl From<crate::vdir::git::VdirGitClient> for crate::client::AddressbookClientStd {
+    fn from(client: crate::vdir::git::VdirGitClient) -> Self {
+        Self::VdirGit(client)
+    }
+}
+
+/// Static [`Capabilities`] of the vdir backend.
+///
+/// Card and collection tags are synthesized locally (see
+/// [`content_tag`] and [`files_tag`]) and `if_match` is checked before
+/// writing; vdir has no sync token and no query engine, so sync and
+/// search go through full listings. Files store any vCard version and
+/// have no size limit. Every metadata field maps to a collection file.
+pub(crate) fn static_capabilities() -> Capabilities {
+    Capabilities {
+        etag: true,
+        ctag: true,
+        sync_token: false,
+        query: false,
+        multiget: true,
+        vcard_versions: Vec::from(["3.0".to_string(), "4.0".to_string()]),
+        max_resource_size: None,
+        writable_metadata: AddressbookField::ALL.to_vec(),
+    }
+}
+
 /// Maps a vdir [`Collection`] to a shared [`Addressbook`].
 ///
 /// Uses the collection's final path segment as the id; the
-/// `display_name` falls back to the id when absent. `ctag` stays
-/// `None` since vdir has no collection-state token.
+/// `display_name` falls back to the id when absent. vdir has no
+/// collection-state token: `sync_token` stays `None`, and `ctag` is
+/// left for the std client to synthesize from the directory entries.
 pub(crate) fn addressbook_from_collection(collection: Collection) -> Addressbook {
     let id = collection.id().to_string();
     let name = collection
@@ -42,6 +77,31 @@ pub(crate) fn addressbook_from_collection(collection: Collection) -> Addressbook
         description: collection.description,
         color: collection.color,
         ctag: None,
+        sync_token: None,
+    }
+}
+
+/// Applies `patch` on top of the `current` metadata of the collection
+/// at `path`, returning the collection to write.
+pub(crate) fn patch_collection(
+    path: VdirPath,
+    current: Collection,
+    patch: AddressbookDiff,
+) -> Collection {
+    Collection {
+        path,
+        display_name: match patch.name {
+            Some(name) => Some(name),
+            None => current.display_name,
+        },
+        description: match patch.description {
+            Some(desc) => desc,
+            None => current.description,
+        },
+        color: match patch.color {
+            Some(color) => color,
+            None => current.color,
+        },
     }
 }
 
@@ -55,19 +115,39 @@ pub(crate) fn addressbook_path(root: &VdirPath, addressbook_id: &str) -> VdirPat
 /// Maps a vdir [`Item`] to a shared [`Card`].
 ///
 /// The card id is the item's file stem; non-vCard items are filtered
-/// out by the caller via [`io_vdir::item::ItemKind`]. ETag is `None`
-/// because vdir has no entity tag concept.
+/// out by the caller via [`io_vdir::item::ItemKind`]. vdir has no
+/// entity tag concept, so the ETag is synthesized from the contents
+/// via [`content_tag`].
 pub(crate) fn card_from_item(addressbook_id: &str, item: Item) -> Option<Card> {
     let id = item.id()?.to_string();
 
     Some(Card {
         id,
         addressbook_id: addressbook_id.to_string(),
-        etag: None,
+        etag: Some(content_tag(&item.contents)),
         contents: item.contents,
     })
 }
 
+/// Synthesizes a collection tag from the `(file name, size, mtime
+/// nanos)` of every file of an addressbook directory.
+///
+/// Any card creation, update, deletion or metadata change bumps the
+/// tag, without reading card contents. The order of `files` does not
+/// matter.
+pub(crate) fn files_tag(mut files: Vec<(Vec<u8>, u64, u128)>) -> String {
+    files.sort();
+
+    let mut hasher = TagHasher::default();
+    for (name, len, mtime) in files {
+        hasher.write(&name);
+        hasher.write(&len.to_le_bytes());
+        hasher.write(&mtime.to_le_bytes());
+    }
+
+    hasher.finish()
+}
+
 /// 1-indexed pagination on an in-memory list. `page_size = None`
 /// returns the full slice; `page_size = 0` or a page past the end
 /// returns an empty vector.
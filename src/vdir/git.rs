@@ -0,0 +1,820 @@
+//! Std-blocking git-versioned Vdir addressbook client.
+//!
+//! [`VdirGitClient`] wraps a [`VdirClient`] and, after every write it
+//! drives through [`VdirClient::run`] (card and addressbook creation,
+//! update and deletion), commits the affected files to a git
+//! repository at the vdir root. The repository is created on first
+//! write, with a commit importing the existing addressbooks. Reads go
+//! straight to the wrapped client.
+//!
+//! Commits are best-effort: the write already happened on disk, so a
+//! failing commit is logged rather than returned, the path being kept
+//! aside and committed again with the next write. See
+//! [`VdirGitClient::uncommitted`].
+//!
+//! The history of a card is then a plain `git log` of its file, see
+//! [`VdirGitClient::card_history`], and any revision can be written
+//! back as the current card, see [`VdirGitClient::restore_card`]. git
+//! runs as an external `git` process, found in the `PATH`.
+
+use alloc::{
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::{io, path::Path, process::Command, sync::Mutex};
+
+use log::{debug, trace, warn};
+use thiserror::Error;
+
+use crate::{
+    addressbook::{Addressbook, AddressbookDiff},
+    backend::AddressbookBackend,
+    capabilities::Capabilities,
+    card::{Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef},
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    vdir::client::{VdirClient, VdirClientError},
+};
+
+/// Name of the git directory, hidden from the addressbook listing.
+const GIT_DIR: &str = ".git";
+
+/// Errors surfaced by [`VdirGitClient`].
+#[derive(Debug, Error)]
+pub enum VdirGitClientError {
+    #[error(transparent)]
+    Vdir(#[from] VdirClientError),
+    #[error("Failed to run git: {0}")]
+    Io(#[from] io::Error),
+    #[error("git {command} failed: {stderr}")]
+    Git { command: String, stderr: String },
+    #[error("Invalid card id `{0}`")]
+    InvalidCardId(String),
+    #[error("Invalid revision `{0}`")]
+    InvalidRevision(String),
+    #[error("Card `{card_id}` not found at revision `{revision}`")]
+    RevisionNotFound { card_id: String, revision: String },
+}
+
+impl VdirGitClientError {
+    /// Classifies the error, see [`ErrorKind`]. Failures of the wrapped
+    /// vdir client keep their own classification.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Vdir(err) => err.kind(),
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::RevisionNotFound { .. } => ErrorKind::NotFound,
+            Self::InvalidCardId(_) | Self::InvalidRevision(_) => ErrorKind::InvalidData,
+            Self::Git { .. } => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current synthesized tag of the card when the error
+    /// is a failed `if_match` precondition.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::Vdir(err) => err.current_etag(),
+            _ => None,
+        }
+    }
+}
+
+/// Revision of a card file, as listed by
+/// [`VdirGitClient::card_history`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct CardRevision {
+    /// Full hash of the commit, to pass to
+    /// [`VdirGitClient::restore_card`].
+    pub revision: String,
+    /// Commit timestamp, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// Author name of the commit.
+    pub author: String,
+    /// Subject line of the commit.
+    pub message: String,
+    /// Whether the commit deleted the card; such a revision has no
+    /// contents to restore.
+    pub deleted: bool,
+}
+
+/// Std-blocking Vdir addressbook client committing every write to git.
+#[derive(Debug)]
+pub struct VdirGitClient {
+    pub inner: VdirClient,
+    author: Option<(String, String)>,
+    uncommitted: Mutex<BTreeSet<String>>,
+}
+
+impl VdirGitClient {
+    /// Wraps an already-built vdir client. The git repository lives at
+    /// its root; it is created on first write when missing.
+    pub fn new(inner: VdirClient) -> Self {
+        Self {
+            inner,
+            author: None,
+            uncommitted: Mutex::default(),
+        }
+    }
+
+    /// Commits as `name <email>` instead of the identity from the git
+    /// configuration, which may be missing on hosts running the client
+    /// unattended.
+    pub fn with_author(mut self, name: impl ToString, email: impl ToString) -> Self {
+        self.author = Some((name.to_string(), email.to_string()));
+        self
+    }
+
+    /// Returns the static capabilities of the vdir backend.
+    pub fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    /// Lists every addressbook under the root, sorted by name, leaving
+    /// out the git directory.
+    pub fn list_addressbooks(&self) -> Result<Vec<Addressbook>, VdirGitClientError> {
+        let mut addressbooks = self.inner.list_addressbooks()?;
+        addressbooks.retain(|addressbook| addressbook.id != GIT_DIR);
+        Ok(addressbooks)
+    }
+
+    /// Creates an addressbook named `name` under the root, then commits
+    /// it. Returns the new addressbook id.
+    pub fn create_addressbook(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, VdirGitClientError> {
+        self.ensure_repository()?;
+        let id = self.inner.create_addressbook(name, description, color)?;
+        self.commit(&id, &format!("Create addressbook {id}"));
+        Ok(id)
+    }
+
+    /// Applies `patch` to the addressbook identified by `id`, then
+    /// commits its metadata files.
+    pub fn update_addressbook(
+        &self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), VdirGitClientError> {
+        self.ensure_repository()?;
+        self.inner.update_addressbook(id, patch)?;
+        self.commit(id, &format!("Update addressbook {id}"));
+        Ok(())
+    }
+
+    /// Recursively removes the addressbook identified by `id`, then
+    /// commits the removal. Its cards remain in the history.
+    pub fn delete_addressbook(&self, id: &str) -> Result<(), VdirGitClientError> {
+        self.ensure_repository()?;
+        self.inner.delete_addressbook(id)?;
+        self.commit(id, &format!("Delete addressbook {id}"));
+        Ok(())
+    }
+
+    /// Lists cards inside `addressbook_id`, applying 1-indexed
+    /// pagination.
+    pub fn list_cards(
+        &self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, VdirGitClientError> {
+        Ok(self.inner.list_cards(addressbook_id, page, page_size)?)
+    }
+
+    /// Lists the id and synthesized tag of every card in
+    /// `addressbook_id`, sorted by id.
+    pub fn list_card_refs(&self, addressbook_id: &str) -> Result<Vec<CardRef>, VdirGitClientError> {
+        Ok(self.inner.list_card_refs(addressbook_id)?)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`,
+    /// evaluated locally over every card.
+    pub fn search_cards(
+        &self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, VdirGitClientError> {
+        Ok(self.inner.search_cards(addressbook_id, filter)?)
+    }
+
+    /// Returns every card of `addressbook_id` as a full
+    /// [`CardChanges`] listing, see [`VdirClient::sync_cards`].
+    pub fn sync_cards(
+        &self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, VdirGitClientError> {
+        Ok(self.inner.sync_cards(addressbook_id, sync_token)?)
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub fn get_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, VdirGitClientError> {
+        Ok(self.inner.get_card(addressbook_id, card_id)?)
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id` at
+    /// once. Ids with no card end up in [`CardBatch::missing`].
+    pub fn get_cards(
+        &self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, VdirGitClientError> {
+        Ok(self.inner.get_cards(addressbook_id, card_ids)?)
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the default
+    /// [`CardCreateOptions`], then commits it. Returns its assigned id
+    /// and synthesized tag.
+    pub fn create_card(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, VdirGitClientError> {
+        self.create_card_with_options(addressbook_id, contents, CardCreateOptions::default())
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the given
+    /// `options`, then commits it. Returns its assigned id and
+    /// synthesized tag.
+    pub fn create_card_with_options(
+        &self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, VdirGitClientError> {
+        self.ensure_repository()?;
+
+        let card = self
+            .inner
+            .create_card_with_options(addressbook_id, contents, options)?;
+
+        self.commit(
+            &card_path(addressbook_id, &card.id)?,
+            &format!("Create card {} in {addressbook_id}", card.id),
+        );
+
+        Ok(card)
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id`, gating on
+    /// `if_match` when present, then commits it. Returns the card id
+    /// and the tag of the new contents.
+    pub fn update_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, VdirGitClientError> {
+        let path = card_path(addressbook_id, card_id)?;
+        self.ensure_repository()?;
+
+        let card = self
+            .inner
+            .update_card(addressbook_id, card_id, contents, if_match)?;
+
+        self.commit(&path, &format!("Update card {card_id} in {addressbook_id}"));
+
+        Ok(card)
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id`,
+    /// creating the card or overwriting it unconditionally, then
+    /// commits it.
+    pub fn upsert_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, VdirGitClientError> {
+        self.update_card(addressbook_id, card_id, contents, None)
+    }
+
+    /// Permanently deletes `card_id` from `addressbook_id`, gating on
+    /// `if_match` when present, then commits the removal. The card
+    /// remains in the history, see [`Self::restore_card`].
+    pub fn delete_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), VdirGitClientError> {
+        let path = card_path(addressbook_id, card_id)?;
+        self.ensure_repository()?;
+        self.inner.delete_card(addressbook_id, card_id, if_match)?;
+
+        self.commit(
+            &path,
+            &format!("Delete card {card_id} from {addressbook_id}"),
+        );
+
+        Ok(())
+    }
+
+    /// Lists the revisions of `card_id` inside `addressbook_id`, most
+    /// recent first, including the one deleting it if any. A card
+    /// never committed, or a vdir without repository, has no history.
+    pub fn card_history(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Vec<CardRevision>, VdirGitClientError> {
+        let path = card_path(addressbook_id, card_id)?;
+
+        if !self.has_repository() || !self.has_commits()? {
+            return Ok(Vec::new());
+        }
+
+        let log = self.git(&[
+            "log",
+            "--no-renames",
+            "--name-status",
+            "--format=%x1e%H%x1f%ct%x1f%an%x1f%s",
+            "--",
+            &path,
+        ])?;
+
+        Ok(parse_log(&String::from_utf8_lossy(&log)))
+    }
+
+    /// Writes the contents `card_id` had at `revision` back as the
+    /// current card of `addressbook_id`, then commits it. A card
+    /// deleted since then is created again. Returns the card id and the
+    /// tag of the restored contents.
+    pub fn restore_card(
+        &self,
+        addressbook_id: &str,
+        card_id: &str,
+        revision: &str,
+    ) -> Result<CardRef, VdirGitClientError> {
+        let path = card_path(addressbook_id, card_id)?;
+
+        if revision.is_empty()
+            || revision.starts_with('-')
+            || revision.contains(|c: char| c.is_whitespace() || c == ':')
+        {
+            return Err(VdirGitClientError::InvalidRevision(revision.to_string()));
+        }
+
+        let not_found = || VdirGitClientError::RevisionNotFound {
+            card_id: card_id.to_string(),
+            revision: revision.to_string(),
+        };
+
+        if !self.has_repository() {
+            return Err(not_found());
+        }
+
+        let contents =
+            self.git(&["show", &format!("{revision}:{path}")])
+                .map_err(|err| match err {
+                    VdirGitClientError::Git { .. } => not_found(),
+                    err => err,
+                })?;
+
+        let card = self
+            .inner
+            .update_card(addressbook_id, card_id, contents, None)?;
+
+        let short = revision.get(..12).unwrap_or(revision);
+        self.commit(
+            &path,
+            &format!("Restore card {card_id} in {addressbook_id} to {short}"),
+        );
+
+        Ok(card)
+    }
+
+    /// Returns the vdir root, the working tree of the repository.
+    fn root(&self) -> &Path {
+        Path::new(self.inner.inner.root().as_str())
+    }
+
+    /// Tells whether the root holds a git repository.
+    fn has_repository(&self) -> bool {
+        self.root().join(GIT_DIR).exists()
+    }
+
+    /// Tells whether the repository has at least one commit.
+    fn has_commits(&self) -> Result<bool, VdirGitClientError> {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(self.root())
+            .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+            .output()?
+            .status;
+
+        Ok(status.success())
+    }
+
+    /// Creates the repository when missing, committing the current
+    /// contents of the root so that cards written before keep a
+    /// history.
+    fn ensure_repository(&self) -> Result<(), VdirGitClientError> {
+        if self.has_repository() {
+            return Ok(());
+        }
+
+        debug!("init git repository at {}", self.root().display());
+        self.git(&["init", "--quiet"])?;
+        self.git(&["add", "--all"])?;
+
+        if self.has_staged_changes(None)? {
+            self.git(&["commit", "--quiet", "--message", "Import vdir"])?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the paths (relative to the root) written but left
+    /// uncommitted after a git failure, sorted. They are committed
+    /// again along with the next write.
+    pub fn uncommitted(&self) -> Vec<String> {
+        let uncommitted = self
+            .uncommitted
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        uncommitted.iter().cloned().collect()
+    }
+
+    /// Commits the change under `path` with `message`, after retrying
+    /// the paths left uncommitted by previous writes. Failures are
+    /// only logged, `path` then joining the uncommitted ones.
+    fn commit(&self, path: &str, message: &str) {
+        let mut uncommitted = self
+            .uncommitted
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        uncommitted.remove(path);
+
+        uncommitted.retain(|pending| {
+            let message = format!("Commit pending change to {pending}");
+            match self.try_commit(pending, &message) {
+                Ok(()) => false,
+                Err(err) => {
+                    warn!("cannot commit pending change to {pending}, retrying later: {err}");
+                    true
+                }
+            }
+        });
+
+        if let Err(err) = self.try_commit(path, message) {
+            warn!("cannot commit change to {path}, retrying later: {err}");
+            uncommitted.insert(path.to_string());
+        }
+    }
+
+    /// Stages every change under `path` (relative to the root) and
+    /// commits it with `message`, leaving any other change aside.
+    /// Nothing is committed when `path` did not change.
+    fn try_commit(&self, path: &str, message: &str) -> Result<(), VdirGitClientError> {
+        // NOTE: git refuses to stage a path neither on disk nor in the
+        // index, such as a card removed before ever being committed.
+        let tracked = Command::new("git")
+            .arg("-C")
+            .arg(self.root())
+            .args(["ls-files", "--error-unmatch", "--", path])
+            .output()?
+            .status
+            .success();
+
+        if !tracked && !self.root().join(path).exists() {
+            trace!("nothing to commit for {path}");
+            return Ok(());
+        }
+
+        self.git(&["add", "--all", "--", path])?;
+
+        if !self.has_staged_changes(Some(path))? {
+            trace!("nothing to commit for {path}");
+            return Ok(());
+        }
+
+        self.git(&["commit", "--quiet", "--message", message, "--", path])?;
+        Ok(())
+    }
+
+    /// Tells whether changes are staged, under `path` when given.
+    fn has_staged_changes(&self, path: Option<&str>) -> Result<bool, VdirGitClientError> {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(self.root())
+            .args(["diff", "--cached", "--quiet", "--"])
+            .args(path)
+            .status()?;
+
+        Ok(!status.success())
+    }
+
+    /// Runs `git` with `args` inside the root, returning its standard
+    /// output.
+    fn git(&self, args: &[&str]) -> Result<Vec<u8>, VdirGitClientError> {
+        trace!("git {}", args.join(" "));
+
+        let mut command = Command::new("git");
+        command.arg("-C").arg(self.root());
+
+        if let Some((name, email)) = &self.author {
+            command
+                .arg("-c")
+                .arg(format!("user.name={name}"))
+                .arg("-c")
+                .arg(format!("user.email={email}"));
+        }
+
+        let output = command.args(args).output()?;
+
+        if !output.status.success() {
+            return Err(VdirGitClientError::Git {
+                command: args.first().copied().unwrap_or_default().to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+/// Returns the path of the file of `card_id` inside `addressbook_id`,
+/// relative to the root.
+fn card_path(addressbook_id: &str, card_id: &str) -> Result<String, VdirGitClientError> {
+    let valid = |id: &str| !id.is_empty() && !id.starts_with('.') && !id.contains(['/', '\\']);
+
+    if !valid(addressbook_id) {
+        let err = VdirClientError::InvalidAddressbook(addressbook_id.to_string());
+        return Err(err.into());
+    }
+
+    if !valid(card_id) {
+        return Err(VdirGitClientError::InvalidCardId(card_id.to_string()));
+    }
+
+    Ok(format!("{addressbook_id}/{card_id}.vcf"))
+}
+
+/// Parses the output of `git log --name-status` with one
+/// `\x1e`-prefixed, `\x1f`-separated header per commit.
+fn parse_log(log: &str) -> Vec<CardRevision> {
+    log.split('\x1e')
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut header = lines.next()?.split('\x1f');
+
+            let revision = header.next()?.to_string();
+            let timestamp = header.next()?.parse().ok()?;
+            let author = header.next()?.to_string();
+            let message = header.next().unwrap_or_default().to_string();
+            let deleted = lines.any(|line| line.starts_with("D\t"));
+
+            Some(CardRevision {
+                revision,
+                timestamp,
+                author,
+                message,
+                deleted,
+            })
+        })
+        .collect()
+}
+
+impl AddressbookBackend for VdirGitClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(VdirGitClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(VdirGitClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(VdirGitClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(VdirGitClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(VdirGitClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(VdirGitClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VdirGitClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VdirGitClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(VdirGitClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(VdirGitClient::capabilities(self))
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(VdirGitClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(VdirGitClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(VdirGitClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(VdirGitClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VdirGitClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(VdirGitClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use io_vdir::{client::VdirClient as InnerVdirClient, path::VdirPath};
+
+    use super::*;
+
+    const ALICE: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alice\r\nEND:VCARD\r\n";
+    const ALICIA: &[u8] = b"BEGIN:VCARD\r\nVERSION:4.0\r\nUID:alice\r\nFN:Alicia\r\nEND:VCARD\r\n";
+
+    fn client(name: &str) -> VdirGitClient {
+        let root =
+            std::env::temp_dir().join(format!("io-addressbook-git-{name}-{}", std::process::id()));
+
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        let inner = InnerVdirClient::new(VdirPath::new(root.to_string_lossy()));
+        VdirGitClient::new(VdirClient::new(inner)).with_author("Test", "test@localhost")
+    }
+
+    #[test]
+    fn restore_deleted_card() {
+        let client = client("restore");
+        let ab = client.create_addressbook("ab", None, None).unwrap();
+        let id = client.create_card(&ab, ALICE.to_vec()).unwrap().id;
+
+        client.update_card(&ab, &id, ALICIA.to_vec(), None).unwrap();
+        client.delete_card(&ab, &id, None).unwrap();
+
+        let history = client.card_history(&ab, &id).unwrap();
+        let summary: Vec<(&str, bool)> = history
+            .iter()
+            .map(|rev| (rev.message.as_str(), rev.deleted))
+            .collect();
+
+        assert_eq!(
+            summary,
+            [
+                (format!("Delete card {id} from {ab}").as_str(), true),
+                (format!("Update card {id} in {ab}").as_str(), false),
+                (format!("Create card {id} in {ab}").as_str(), false),
+            ]
+        );
+        assert!(history.iter().all(|rev| rev.author == "Test"));
+
+        let err = client
+            .restore_card(&ab, &id, &history[0].revision)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let card = client.restore_card(&ab, &id, &history[1].revision).unwrap();
+        assert_eq!(card.id, id);
+        assert_eq!(client.get_card(&ab, &id).unwrap().contents, ALICIA);
+
+        let history = client.card_history(&ab, &id).unwrap();
+        assert_eq!(history.len(), 4);
+        assert!(!history[0].deleted);
+        let restored = format!("Restore card {id} in {ab} to ");
+        assert!(history[0].message.starts_with(&restored));
+        assert!(client.uncommitted().is_empty());
+    }
+
+    #[test]
+    fn no_history_without_repository() {
+        let client = client("empty");
+
+        assert!(client.card_history("ab", "alice").unwrap().is_empty());
+
+        let err = client.restore_card("ab", "alice", "HEAD").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let err = client.restore_card("ab", "alice", "--all").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
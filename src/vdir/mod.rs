@@ -1,5 +1,10 @@
-//! Vdir backend: the std client plus its wire conversions.
+//! Vdir backend: the std and tokio clients, the git-versioned std
+//! client, plus its wire conversions.
 
 #[cfg(feature = "client")]
 pub mod client;
+#[cfg(feature = "tokio")]
+pub mod client_async;
 pub mod convert;
+#[cfg(feature = "vdir-git")]
+pub mod git;
@@ -14,7 +14,12 @@ use alloc::{
 };
 use std::io::{Read, Write};
 
-use io_webdav::{client::WebdavClientStd, coroutine::*};
+use io_webdav::{
+    client::WebdavClientStd,
+    coroutine::*,
+    rfc4918::{follow_redirects::FollowRedirectsError, send::SendError},
+};
+use log::{debug, warn};
 use thiserror::Error;
 use url::Url;
 
@@ -24,24 +29,69 @@ use crate::{
         webdav::{
             create::{WebdavAddressbookCreate, WebdavAddressbookCreateError},
             delete::{WebdavAddressbookDelete, WebdavAddressbookDeleteError},
+            discover::WebdavAddressbookDiscoverError,
             list::{WebdavAddressbookList, WebdavAddressbookListError},
             update::{WebdavAddressbookUpdate, WebdavAddressbookUpdateError},
         },
     },
+    backend::AddressbookBackend,
+    capabilities::{
+        Capabilities,
+        webdav::discover::{WebdavCapabilitiesDiscover, WebdavCapabilitiesDiscoverError},
+    },
     card::{
-        Card,
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
         webdav::{
             create::{WebdavCardCreate, WebdavCardCreateError},
             delete::{WebdavCardDelete, WebdavCardDeleteError},
+            etag::{WebdavCardEtag, WebdavCardEtagError},
             get::{WebdavCardGet, WebdavCardGetError},
             list::{WebdavCardList, WebdavCardListError},
+            multiget::{WebdavCardMultiget, WebdavCardMultigetError},
+            query::{WebdavCardQuery, WebdavCardQueryError},
+            refs::{WebdavCardRefList, WebdavCardRefListError},
+            sync::{WebdavCardSync, WebdavCardSyncError},
             update::{WebdavCardUpdate, WebdavCardUpdateError},
         },
     },
-    webdav::convert::paginate,
+    client::AddressbookClientStdError,
+    error::ErrorKind,
+    webdav::convert::{paginate, send_error_kind, send_error_status},
 };
 
-const READ_BUFFER_SIZE: usize = 16 * 1024;
+pub(crate) const READ_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Pumps a standard-shape WebDAV coroutine against `$stream` until it
+/// terminates, returning from the enclosing function.
+///
+/// Shared by [`WebdavClient::run`] and its async counterpart: the
+/// trailing `await` turns every stream call into an awaited one, so
+/// both clients serve the coroutine yields the same way.
+macro_rules! run_webdav {
+    ($stream:expr, $coroutine:expr $(, $await:ident)?) => {{
+        let mut coroutine = $coroutine;
+        let mut buf = [0u8; $crate::webdav::client::READ_BUFFER_SIZE];
+        let mut arg: Option<&[u8]> = None;
+
+        loop {
+            match coroutine.resume(arg.take()) {
+                WebdavCoroutineState::Complete(Ok(out)) => return Ok(out),
+                WebdavCoroutineState::Complete(Err(err)) => return Err(err.into()),
+                WebdavCoroutineState::Yielded(WebdavYield::WantsRead) => {
+                    let n = $stream.read(&mut buf)$(.$await)??;
+                    arg = Some(&buf[..n]);
+                }
+                WebdavCoroutineState::Yielded(WebdavYield::WantsWrite(bytes)) => {
+                    $stream.write_all(&bytes)$(.$await)??;
+                    $stream.flush()$(.$await)??;
+                }
+            }
+        }
+    }};
+}
+
+#[cfg(feature = "tokio")]
+pub(crate) use run_webdav;
 
 /// Errors surfaced by [`WebdavClient`] while running a coroutine.
 ///
@@ -60,20 +110,104 @@ pub enum WebdavClientError {
     #[error(transparent)]
     AddressbookDelete(#[from] WebdavAddressbookDeleteError),
     #[error(transparent)]
+    AddressbookDiscover(#[from] WebdavAddressbookDiscoverError),
+    #[error(transparent)]
     AddressbookList(#[from] WebdavAddressbookListError),
     #[error(transparent)]
     AddressbookUpdate(#[from] WebdavAddressbookUpdateError),
 
+    #[error(transparent)]
+    Capabilities(#[from] WebdavCapabilitiesDiscoverError),
+
     #[error(transparent)]
     CardCreate(#[from] WebdavCardCreateError),
     #[error(transparent)]
     CardDelete(#[from] WebdavCardDeleteError),
     #[error(transparent)]
+    CardEtag(#[from] WebdavCardEtagError),
+    #[error(transparent)]
     CardGet(#[from] WebdavCardGetError),
     #[error(transparent)]
     CardList(#[from] WebdavCardListError),
     #[error(transparent)]
+    CardMultiget(#[from] WebdavCardMultigetError),
+    #[error(transparent)]
+    CardQuery(#[from] WebdavCardQueryError),
+    #[error(transparent)]
+    CardRefList(#[from] WebdavCardRefListError),
+    #[error(transparent)]
+    CardSync(#[from] WebdavCardSyncError),
+    #[error(transparent)]
     CardUpdate(#[from] WebdavCardUpdateError),
+
+    #[error("Card `{card_id}` changed on the server (current ETag: {etag:?})")]
+    PreconditionFailed {
+        card_id: String,
+        etag: Option<String>,
+    },
+}
+
+impl WebdavClientError {
+    /// Classifies the error, see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        if let Some(err) = self.send_error() {
+            return send_error_kind(err);
+        }
+
+        match self {
+            Self::Io(err) => ErrorKind::from_io(err),
+            Self::Discovery(_) => ErrorKind::Transport,
+            Self::AddressbookDiscover(WebdavAddressbookDiscoverError::Send(
+                FollowRedirectsError::HttpStatus(status, _),
+            )) => ErrorKind::from_status(*status),
+            Self::AddressbookDiscover(_) => ErrorKind::Transport,
+            Self::PreconditionFailed { .. }
+            | Self::CardCreate(WebdavCardCreateError::AlreadyExists(_)) => ErrorKind::Conflict,
+            Self::AddressbookCreate(WebdavAddressbookCreateError::InvalidAddressbook(_))
+            | Self::AddressbookDelete(WebdavAddressbookDeleteError::InvalidAddressbook(_))
+            | Self::AddressbookUpdate(WebdavAddressbookUpdateError::InvalidAddressbook(_))
+            | Self::CardCreate(WebdavCardCreateError::EmptyCardBody)
+            | Self::CardDelete(WebdavCardDeleteError::InvalidCardId(_))
+            | Self::CardEtag(WebdavCardEtagError::InvalidCardId(_))
+            | Self::CardGet(WebdavCardGetError::InvalidCardId(_))
+            | Self::CardMultiget(WebdavCardMultigetError::InvalidCardId(_))
+            | Self::CardSync(WebdavCardSyncError::InvalidSyncToken(_))
+            | Self::CardUpdate(WebdavCardUpdateError::InvalidCardId(_))
+            | Self::CardUpdate(WebdavCardUpdateError::EmptyCardBody) => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the current ETag of the card when the error is a failed
+    /// `If-Match` precondition and the server reported it.
+    pub fn current_etag(&self) -> Option<&str> {
+        match self {
+            Self::PreconditionFailed { etag, .. } => etag.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the HTTP-level error wrapped by a coroutine error.
+    pub(crate) fn send_error(&self) -> Option<&SendError> {
+        match self {
+            Self::AddressbookCreate(WebdavAddressbookCreateError::Send(err))
+            | Self::AddressbookDelete(WebdavAddressbookDeleteError::Send(err))
+            | Self::AddressbookList(WebdavAddressbookListError::Send(err))
+            | Self::AddressbookUpdate(WebdavAddressbookUpdateError::Send(err))
+            | Self::Capabilities(WebdavCapabilitiesDiscoverError::Send(err))
+            | Self::CardCreate(WebdavCardCreateError::Send(err))
+            | Self::CardDelete(WebdavCardDeleteError::Send(err))
+            | Self::CardEtag(WebdavCardEtagError::Send(err))
+            | Self::CardGet(WebdavCardGetError::Send(err))
+            | Self::CardList(WebdavCardListError::Send(err))
+            | Self::CardMultiget(WebdavCardMultigetError::Send(err))
+            | Self::CardQuery(WebdavCardQueryError::Send(err))
+            | Self::CardRefList(WebdavCardRefListError::Send(err))
+            | Self::CardSync(WebdavCardSyncError::Send(err))
+            | Self::CardUpdate(WebdavCardUpdateError::Send(err)) => Some(err),
+            _ => None,
+        }
+    }
 }
 
 /// Std-blocking WebDAV addressbook client built on a connected stream.
@@ -91,27 +225,27 @@ impl WebdavClient {
     /// Pumps any standard-shape WebDAV coroutine (`Yield =
     /// WebdavYield`, `Return = Result<T, E>`) against the inner stream
     /// until it terminates.
-    pub fn run<C, T, E>(&mut self, mut coroutine: C) -> Result<T, WebdavClientError>
+    pub fn run<C, T, E>(&mut self, coroutine: C) -> Result<T, WebdavClientError>
     where
         C: WebdavCoroutine<Yield = WebdavYield, Return = Result<T, E>>,
         WebdavClientError: From<E>,
     {
-        let mut buf = [0u8; READ_BUFFER_SIZE];
-        let mut arg: Option<&[u8]> = None;
+        run_webdav!(self.inner.stream, coroutine)
+    }
 
-        loop {
-            match coroutine.resume(arg.take()) {
-                WebdavCoroutineState::Complete(Ok(out)) => return Ok(out),
-                WebdavCoroutineState::Complete(Err(err)) => return Err(err.into()),
-                WebdavCoroutineState::Yielded(WebdavYield::WantsRead) => {
-                    let n = self.inner.stream.read(&mut buf)?;
-                    arg = Some(&buf[..n]);
-                }
-                WebdavCoroutineState::Yielded(WebdavYield::WantsWrite(bytes)) => {
-                    self.inner.stream.write_all(&bytes)?;
-                }
-            }
-        }
+    /// Discovers the capabilities of the server, from an `OPTIONS` on
+    /// the home-set and the properties of its addressbooks.
+    pub fn capabilities(&mut self) -> Result<Capabilities, WebdavClientError> {
+        let home = self.inner.addressbook_home_set()?;
+        let path = home.path().to_string();
+
+        let coroutine = WebdavCapabilitiesDiscover::new(
+            &self.inner.base_url,
+            self.inner.auth(),
+            &self.inner.user_agent,
+            &path,
+        );
+        self.run(coroutine)
     }
 
     /// Lists every addressbook under the discovered home-set, sorted by
@@ -209,6 +343,86 @@ impl WebdavClient {
         Ok(paginate(cards, page, page_size))
     }
 
+    /// Lists the id, href and ETag of every card in `addressbook_id`,
+    /// sorted by id, without fetching contents.
+    pub fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, WebdavClientError> {
+        let home = self.inner.addressbook_home_set()?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardRefList::new(
+            &self.inner.base_url,
+            self.inner.auth(),
+            &self.inner.user_agent,
+            &path,
+        );
+        self.run(coroutine)
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`
+    /// server-side, via an RFC 6352 addressbook-query REPORT.
+    pub fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, WebdavClientError> {
+        let home = self.inner.addressbook_home_set()?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardQuery::new(
+            &self.inner.base_url,
+            self.inner.auth(),
+            &self.inner.user_agent,
+            &path,
+            addressbook_id,
+            filter,
+        );
+        self.run(coroutine)
+    }
+
+    /// Fetches the cards of `addressbook_id` changed since
+    /// `sync_token` via an RFC 6578 sync-collection REPORT.
+    ///
+    /// Pass `None` for the initial sync. When the server rejects the
+    /// token, falls back to a tokenless sync returning the full
+    /// listing (flagged by [`CardChanges::full`]) and a fresh token.
+    pub fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, WebdavClientError> {
+        let home = self.inner.addressbook_home_set()?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardSync::new(
+            &self.inner.base_url,
+            self.inner.auth(),
+            &self.inner.user_agent,
+            &path,
+            addressbook_id,
+            sync_token,
+        );
+
+        match self.run(coroutine) {
+            Err(WebdavClientError::CardSync(WebdavCardSyncError::InvalidSyncToken(token))) => {
+                debug!("sync token {token} rejected, falling back to full sync");
+
+                let coroutine = WebdavCardSync::new(
+                    &self.inner.base_url,
+                    self.inner.auth(),
+                    &self.inner.user_agent,
+                    &path,
+                    addressbook_id,
+                    None,
+                );
+                self.run(coroutine)
+            }
+            result => result,
+        }
+    }
+
     /// Fetches `card_id` from `addressbook_id`.
     pub fn get_card(
         &mut self,
@@ -229,12 +443,46 @@ impl WebdavClient {
         self.run(coroutine)
     }
 
-    /// Appends a new vCard to `addressbook_id`. Returns its assigned id.
+    /// Fetches every card of `card_ids` from `addressbook_id` in a
+    /// single addressbook-multiget REPORT. Ids the server does not
+    /// return end up in [`CardBatch::missing`].
+    pub fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, WebdavClientError> {
+        let home = self.inner.addressbook_home_set()?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardMultiget::new(
+            &self.inner.base_url,
+            self.inner.auth(),
+            &self.inner.user_agent,
+            &path,
+            addressbook_id,
+            card_ids,
+        )?;
+        self.run(coroutine)
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the default
+    /// [`CardCreateOptions`]. Returns its assigned id, href and ETag.
     pub fn create_card(
         &mut self,
         addressbook_id: &str,
         contents: Vec<u8>,
-    ) -> Result<String, WebdavClientError> {
+    ) -> Result<CardRef, WebdavClientError> {
+        self.create_card_with_options(addressbook_id, contents, CardCreateOptions::default())
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the given
+    /// `options`. Returns its assigned id, href and ETag.
+    pub fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, WebdavClientError> {
         let home = self.inner.addressbook_home_set()?;
         let path = collection_path(&home, addressbook_id);
 
@@ -244,19 +492,22 @@ impl WebdavClient {
             &self.inner.user_agent,
             &path,
             contents,
+            options,
         )?;
-        self.run(coroutine)
+        let card = self.run(coroutine)?;
+        Ok(self.ensure_etag(&path, card))
     }
 
     /// Overwrites `card_id` inside `addressbook_id`, gating on
-    /// `if_match` when present.
+    /// `if_match` when present. Returns the card id, href and new
+    /// ETag.
     pub fn update_card(
         &mut self,
         addressbook_id: &str,
         card_id: &str,
         contents: Vec<u8>,
         if_match: Option<&str>,
-    ) -> Result<(), WebdavClientError> {
+    ) -> Result<CardRef, WebdavClientError> {
         let home = self.inner.addressbook_home_set()?;
         let path = collection_path(&home, addressbook_id);
 
@@ -269,14 +520,45 @@ impl WebdavClient {
             contents,
             if_match,
         )?;
-        self.run(coroutine)
+
+        match self.run(coroutine) {
+            Ok(card) => Ok(self.ensure_etag(&path, card)),
+            Err(err) => Err(self.precondition_failed(&path, card_id, err)),
+        }
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id` with
+    /// a `PUT` free of preconditions, creating the card or overwriting
+    /// it. Returns the card id, href and ETag.
+    pub fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, WebdavClientError> {
+        let home = self.inner.addressbook_home_set()?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardUpdate::new(
+            &self.inner.base_url,
+            self.inner.auth(),
+            &self.inner.user_agent,
+            &path,
+            card_id,
+            contents,
+            None,
+        )?;
+        let card = self.run(coroutine)?;
+        Ok(self.ensure_etag(&path, card))
     }
 
-    /// Permanently deletes `card_id` from `addressbook_id`.
+    /// Permanently deletes `card_id` from `addressbook_id`, gating on
+    /// `if_match` when present.
     pub fn delete_card(
         &mut self,
         addressbook_id: &str,
         card_id: &str,
+        if_match: Option<&str>,
     ) -> Result<(), WebdavClientError> {
         let home = self.inner.addressbook_home_set()?;
         let path = collection_path(&home, addressbook_id);
@@ -287,15 +569,247 @@ impl WebdavClient {
             &self.inner.user_agent,
             &path,
             card_id,
+            if_match,
         )?;
+
         self.run(coroutine)
+            .map_err(|err| self.precondition_failed(&path, card_id, err))
+    }
+
+    /// Turns a 412 Precondition Failed into
+    /// [`WebdavClientError::PreconditionFailed`], carrying the current
+    /// ETag of the card fetched with a Depth: 0 PROPFIND. Other errors
+    /// pass through.
+    fn precondition_failed(
+        &mut self,
+        path: &str,
+        card_id: &str,
+        err: WebdavClientError,
+    ) -> WebdavClientError {
+        if err.send_error().and_then(send_error_status) != Some(412) {
+            return err;
+        }
+
+        let coroutine = WebdavCardEtag::new(
+            &self.inner.base_url,
+            self.inner.auth(),
+            &self.inner.user_agent,
+            path,
+            card_id,
+        );
+
+        // NOTE: the ETag is a best-effort hint: failing to fetch it
+        // (e.g. the card was deleted meanwhile) must not hide the
+        // precondition failure itself.
+        let etag = match coroutine.map(|coroutine| self.run(coroutine)) {
+            Ok(Ok(card)) => card.etag,
+            _ => None,
+        };
+
+        WebdavClientError::PreconditionFailed {
+            card_id: card_id.to_string(),
+            etag,
+        }
+    }
+
+    /// Completes a write result lacking an ETag (the server omitted
+    /// the response header) with a Depth: 0 PROPFIND on the card.
+    ///
+    /// The write already succeeded at this point, so a failing
+    /// PROPFIND is only logged and the card returned without ETag.
+    fn ensure_etag(&mut self, path: &str, card: CardRef) -> CardRef {
+        if card.etag.is_some() {
+            return card;
+        }
+
+        debug!("no etag returned for card {}, fetching it", card.id);
+
+        let coroutine = WebdavCardEtag::new(
+            &self.inner.base_url,
+            self.inner.auth(),
+            &self.inner.user_agent,
+            path,
+            &card.id,
+        );
+
+        let fetched = match coroutine {
+            Ok(coroutine) => self.run(coroutine),
+            Err(err) => Err(err.into()),
+        };
+
+        match fetched {
+            Ok(fetched) => CardRef {
+                href: card.href.or(fetched.href),
+                etag: fetched.etag,
+                ..card
+            },
+            Err(err) => {
+                warn!("cannot fetch etag of card {}, skipping it: {err}", card.id);
+                card
+            }
+        }
+    }
+}
+
+impl AddressbookBackend for WebdavClient {
+    fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, AddressbookClientStdError> {
+        Ok(WebdavClient::list_addressbooks(self)?)
+    }
+
+    fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, AddressbookClientStdError> {
+        Ok(WebdavClient::create_addressbook(
+            self,
+            name,
+            description,
+            color,
+        )?)
+    }
+
+    fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(WebdavClient::update_addressbook(self, id, patch)?)
+    }
+
+    fn delete_addressbook(&mut self, id: &str) -> Result<(), AddressbookClientStdError> {
+        Ok(WebdavClient::delete_addressbook(self, id)?)
+    }
+
+    fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(WebdavClient::list_cards(
+            self,
+            addressbook_id,
+            page,
+            page_size,
+        )?)
+    }
+
+    fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, AddressbookClientStdError> {
+        Ok(WebdavClient::get_card(self, addressbook_id, card_id)?)
+    }
+
+    fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(WebdavClient::create_card(self, addressbook_id, contents)?)
+    }
+
+    fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(WebdavClient::update_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+            if_match,
+        )?)
+    }
+
+    fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), AddressbookClientStdError> {
+        Ok(WebdavClient::delete_card(
+            self,
+            addressbook_id,
+            card_id,
+            if_match,
+        )?)
+    }
+
+    fn capabilities(&mut self) -> Result<Capabilities, AddressbookClientStdError> {
+        Ok(WebdavClient::capabilities(self)?)
+    }
+
+    fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, AddressbookClientStdError> {
+        Ok(WebdavClient::list_card_refs(self, addressbook_id)?)
+    }
+
+    fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, AddressbookClientStdError> {
+        Ok(WebdavClient::search_cards(self, addressbook_id, filter)?)
+    }
+
+    fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, AddressbookClientStdError> {
+        Ok(WebdavClient::sync_cards(self, addressbook_id, sync_token)?)
+    }
+
+    fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, AddressbookClientStdError> {
+        Ok(WebdavClient::get_cards(self, addressbook_id, card_ids)?)
+    }
+
+    fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(WebdavClient::create_card_with_options(
+            self,
+            addressbook_id,
+            contents,
+            options,
+        )?)
+    }
+
+    fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, AddressbookClientStdError> {
+        Ok(WebdavClient::upsert_card(
+            self,
+            addressbook_id,
+            card_id,
+            contents,
+        )?)
     }
 }
 
 /// Builds the collection path of `addressbook_id` under the home-set
 /// URL, mirroring io_webdav's own `addressbook_path` (trim the
 /// home-set trailing slash and the id's surrounding slashes).
-fn collection_path(home: &Url, addressbook_id: &str) -> String {
+pub(crate) fn collection_path(home: &Url, addressbook_id: &str) -> String {
     let base = home.path().trim_end_matches('/');
     let id = addressbook_id.trim_matches('/');
     format!("{base}/{id}")
@@ -0,0 +1,508 @@
+//! Tokio WebDAV addressbook client.
+//!
+//! Async counterpart of [`WebdavClient`]: drives the same io-addressbook
+//! WebDAV coroutines, but over any connected [`AsyncStream`] (typically
+//! a `tokio_rustls` TLS stream on top of a [`tokio::net::TcpStream`]).
+//! Methods mirror the std client one to one, with the same semantics
+//! and the same [`WebdavClientError`].
+//!
+//! The CardDAV home-set is discovered on first use by the same
+//! [`WebdavAddressbookDiscover`] coroutine as the std client, unless
+//! the caller already knows it (see
+//! [`WebdavClientAsync::with_addressbook_home_set`]).
+//!
+//! [`WebdavClient`]: crate::webdav::client::WebdavClient
+//! [`tokio::net::TcpStream`]: https://docs.rs/tokio/latest/tokio/net/struct.TcpStream.html
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Debug;
+
+use io_webdav::{coroutine::*, rfc4918::WebdavAuth};
+use log::{debug, warn};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use url::Url;
+
+use crate::{
+    addressbook::{
+        Addressbook, AddressbookDiff,
+        webdav::{
+            create::WebdavAddressbookCreate, delete::WebdavAddressbookDelete,
+            discover::WebdavAddressbookDiscover, list::WebdavAddressbookList,
+            update::WebdavAddressbookUpdate,
+        },
+    },
+    capabilities::{Capabilities, webdav::discover::WebdavCapabilitiesDiscover},
+    card::{
+        Card, CardBatch, CardChanges, CardCreateOptions, CardFilter, CardRef,
+        webdav::{
+            create::WebdavCardCreate,
+            delete::WebdavCardDelete,
+            etag::WebdavCardEtag,
+            get::WebdavCardGet,
+            list::WebdavCardList,
+            multiget::WebdavCardMultiget,
+            query::WebdavCardQuery,
+            refs::WebdavCardRefList,
+            sync::{WebdavCardSync, WebdavCardSyncError},
+            update::WebdavCardUpdate,
+        },
+    },
+    webdav::{
+        client::{WebdavClientError, collection_path, run_webdav},
+        convert::{paginate, send_error_status},
+    },
+};
+
+/// Connected byte stream the async client talks HTTP over.
+pub trait AsyncStream: AsyncRead + AsyncWrite + Debug + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Debug + Send + Unpin> AsyncStream for T {}
+
+/// Tokio WebDAV addressbook client built on a connected stream.
+#[derive(Debug)]
+pub struct WebdavClientAsync {
+    pub stream: Box<dyn AsyncStream>,
+    pub base_url: Url,
+    pub auth: WebdavAuth,
+    pub user_agent: String,
+    home: Option<Url>,
+}
+
+impl WebdavClientAsync {
+    /// Wraps a connected `stream` to the server at `base_url`. The
+    /// CardDAV home-set is discovered on first use.
+    pub fn new(stream: impl AsyncStream + 'static, base_url: Url, auth: WebdavAuth) -> Self {
+        Self {
+            stream: Box::new(stream),
+            base_url,
+            auth,
+            user_agent: concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")).into(),
+            home: None,
+        }
+    }
+
+    /// Serves the CardDAV home-set `home`, skipping discovery.
+    pub fn with_addressbook_home_set(mut self, home: Url) -> Self {
+        self.home = Some(home);
+        self
+    }
+
+    /// Pumps any standard-shape WebDAV coroutine (`Yield =
+    /// WebdavYield`, `Return = Result<T, E>`) against the stream until
+    /// it terminates.
+    pub async fn run<C, T, E>(&mut self, coroutine: C) -> Result<T, WebdavClientError>
+    where
+        C: WebdavCoroutine<Yield = WebdavYield, Return = Result<T, E>>,
+        WebdavClientError: From<E>,
+    {
+        run_webdav!(self.stream, coroutine, await)
+    }
+
+    /// Returns the CardDAV addressbook home-set, discovering it on the
+    /// first call (see [`WebdavAddressbookDiscover`]).
+    pub async fn addressbook_home_set(&mut self) -> Result<Url, WebdavClientError> {
+        if let Some(home) = &self.home {
+            return Ok(home.clone());
+        }
+
+        let coroutine =
+            WebdavAddressbookDiscover::new(&self.base_url, &self.auth, &self.user_agent);
+        let home = self.run(coroutine).await?;
+        debug!("discovered addressbook home-set {home}");
+
+        self.home = Some(home.clone());
+        Ok(home)
+    }
+
+    /// Discovers the capabilities of the server, from an `OPTIONS` on
+    /// the home-set and the properties of its addressbooks.
+    pub async fn capabilities(&mut self) -> Result<Capabilities, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = home.path().to_string();
+
+        let coroutine =
+            WebdavCapabilitiesDiscover::new(&self.base_url, &self.auth, &self.user_agent, &path);
+        self.run(coroutine).await
+    }
+
+    /// Lists every addressbook under the home-set, sorted by name.
+    pub async fn list_addressbooks(&mut self) -> Result<Vec<Addressbook>, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = home.path().to_string();
+
+        let coroutine =
+            WebdavAddressbookList::new(&self.base_url, &self.auth, &self.user_agent, &path);
+        self.run(coroutine).await
+    }
+
+    /// Creates an addressbook named `name` under the home-set. Returns
+    /// the new addressbook id.
+    pub async fn create_addressbook(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<String, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = home.path().to_string();
+
+        let coroutine = WebdavAddressbookCreate::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            name,
+            description,
+            color,
+        )?;
+        self.run(coroutine).await
+    }
+
+    /// Applies `patch` to the addressbook identified by `id`.
+    pub async fn update_addressbook(
+        &mut self,
+        id: &str,
+        patch: AddressbookDiff,
+    ) -> Result<(), WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = home.path().to_string();
+
+        let coroutine = WebdavAddressbookUpdate::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            id,
+            patch,
+        )?;
+        self.run(coroutine).await
+    }
+
+    /// Deletes the addressbook identified by `id`.
+    pub async fn delete_addressbook(&mut self, id: &str) -> Result<(), WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = home.path().to_string();
+
+        let coroutine =
+            WebdavAddressbookDelete::new(&self.base_url, &self.auth, &self.user_agent, &path, id)?;
+        self.run(coroutine).await
+    }
+
+    /// Lists cards inside `addressbook_id`, applying 1-indexed
+    /// pagination.
+    pub async fn list_cards(
+        &mut self,
+        addressbook_id: &str,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<Card>, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardList::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            addressbook_id,
+        );
+        let cards = self.run(coroutine).await?;
+        Ok(paginate(cards, page, page_size))
+    }
+
+    /// Lists the id, href and ETag of every card in `addressbook_id`,
+    /// sorted by id, without fetching contents.
+    pub async fn list_card_refs(
+        &mut self,
+        addressbook_id: &str,
+    ) -> Result<Vec<CardRef>, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardRefList::new(&self.base_url, &self.auth, &self.user_agent, &path);
+        self.run(coroutine).await
+    }
+
+    /// Searches the cards of `addressbook_id` matching `filter`
+    /// server-side, via an RFC 6352 addressbook-query REPORT.
+    pub async fn search_cards(
+        &mut self,
+        addressbook_id: &str,
+        filter: &CardFilter,
+    ) -> Result<Vec<Card>, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardQuery::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            addressbook_id,
+            filter,
+        );
+        self.run(coroutine).await
+    }
+
+    /// Fetches the cards of `addressbook_id` changed since
+    /// `sync_token`, falling back to a full listing when the server
+    /// rejects the token, see
+    /// [`WebdavClient::sync_cards`](crate::webdav::client::WebdavClient::sync_cards).
+    pub async fn sync_cards(
+        &mut self,
+        addressbook_id: &str,
+        sync_token: Option<&str>,
+    ) -> Result<CardChanges, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardSync::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            addressbook_id,
+            sync_token,
+        );
+
+        match self.run(coroutine).await {
+            Err(WebdavClientError::CardSync(WebdavCardSyncError::InvalidSyncToken(token))) => {
+                debug!("sync token {token} rejected, falling back to full sync");
+
+                let coroutine = WebdavCardSync::new(
+                    &self.base_url,
+                    &self.auth,
+                    &self.user_agent,
+                    &path,
+                    addressbook_id,
+                    None,
+                );
+                self.run(coroutine).await
+            }
+            result => result,
+        }
+    }
+
+    /// Fetches `card_id` from `addressbook_id`.
+    pub async fn get_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+    ) -> Result<Card, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardGet::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            addressbook_id,
+            card_id,
+        )?;
+        self.run(coroutine).await
+    }
+
+    /// Fetches every card of `card_ids` from `addressbook_id` in a
+    /// single addressbook-multiget REPORT. Ids the server does not
+    /// return end up in [`CardBatch::missing`].
+    pub async fn get_cards(
+        &mut self,
+        addressbook_id: &str,
+        card_ids: &[&str],
+    ) -> Result<CardBatch, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardMultiget::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            addressbook_id,
+            card_ids,
+        )?;
+        self.run(coroutine).await
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the default
+    /// [`CardCreateOptions`]. Returns its assigned id, href and ETag.
+    pub async fn create_card(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, WebdavClientError> {
+        let options = CardCreateOptions::default();
+        self.create_card_with_options(addressbook_id, contents, options)
+            .await
+    }
+
+    /// Appends a new vCard to `addressbook_id` with the given
+    /// `options`. Returns its assigned id, href and ETag.
+    pub async fn create_card_with_options(
+        &mut self,
+        addressbook_id: &str,
+        contents: Vec<u8>,
+        options: CardCreateOptions,
+    ) -> Result<CardRef, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardCreate::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            contents,
+            options,
+        )?;
+        let card = self.run(coroutine).await?;
+        Ok(self.ensure_etag(&path, card).await)
+    }
+
+    /// Overwrites `card_id` inside `addressbook_id`, gating on
+    /// `if_match` when present. Returns the card id, href and new
+    /// ETag.
+    pub async fn update_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+        if_match: Option<&str>,
+    ) -> Result<CardRef, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardUpdate::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            card_id,
+            contents,
+            if_match,
+        )?;
+
+        match self.run(coroutine).await {
+            Ok(card) => Ok(self.ensure_etag(&path, card).await),
+            Err(err) => Err(self.precondition_failed(&path, card_id, err).await),
+        }
+    }
+
+    /// Writes `contents` as card `card_id` inside `addressbook_id` with
+    /// a `PUT` free of preconditions, creating the card or overwriting
+    /// it. Returns the card id, href and ETag.
+    pub async fn upsert_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        contents: Vec<u8>,
+    ) -> Result<CardRef, WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardUpdate::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            card_id,
+            contents,
+            None,
+        )?;
+        let card = self.run(coroutine).await?;
+        Ok(self.ensure_etag(&path, card).await)
+    }
+
+    /// Permanently deletes `card_id` from `addressbook_id`, gating on
+    /// `if_match` when present.
+    pub async fn delete_card(
+        &mut self,
+        addressbook_id: &str,
+        card_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<(), WebdavClientError> {
+        let home = self.addressbook_home_set().await?;
+        let path = collection_path(&home, addressbook_id);
+
+        let coroutine = WebdavCardDelete::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            &path,
+            card_id,
+            if_match,
+        )?;
+
+        match self.run(coroutine).await {
+            Ok(()) => Ok(()),
+            Err(err) => Err(self.precondition_failed(&path, card_id, err).await),
+        }
+    }
+
+    /// Turns a 412 Precondition Failed into
+    /// [`WebdavClientError::PreconditionFailed`], carrying the
+    /// best-effort current ETag of the card. Other errors pass through.
+    async fn precondition_failed(
+        &mut self,
+        path: &str,
+        card_id: &str,
+        err: WebdavClientError,
+    ) -> WebdavClientError {
+        if err.send_error().and_then(send_error_status) != Some(412) {
+            return err;
+        }
+
+        let etag = match WebdavCardEtag::new(
+            &self.base_url,
+            &self.auth,
+            &self.user_agent,
+            path,
+            card_id,
+        ) {
+            Ok(coroutine) => self.run(coroutine).await.ok().and_then(|card| card.etag),
+            Err(_) => None,
+        };
+
+        WebdavClientError::PreconditionFailed {
+            card_id: card_id.to_string(),
+            etag,
+        }
+    }
+
+    /// Completes a write result lacking an ETag with a Depth: 0
+    /// PROPFIND on the card.
+    ///
+    /// The write already succeeded at this point, so a failing
+    /// PROPFIND is only logged and the card returned without ETag.
+    async fn ensure_etag(&mut self, path: &str, card: CardRef) -> CardRef {
+        if card.etag.is_some() {
+            return card;
+        }
+
+        debug!("no etag returned for card {}, fetching it", card.id);
+
+        let fetched =
+            match WebdavCardEtag::new(&self.base_url, &self.auth, &self.user_agent, path, &card.id)
+            {
+                Ok(coroutine) => self.run(coroutine).await,
+                Err(err) => Err(err.into()),
+            };
+
+        match fetched {
+            Ok(fetched) => CardRef {
+                href: card.href.or(fetched.href),
+                etag: fetched.etag,
+                ..card
+            },
+            Err(err) => {
+                warn!("cannot fetch etag of card {}, skipping it: {err}", card.id);
+                card
+            }
+        }
+    }
+}
@@ -8,13 +8,27 @@
 
 use alloc::{
     boxed::Box,
+    format,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
+use core::fmt::Write;
 
-use io_webdav::rfc6352::{addressbook::Addressbook as WireAddressbook, card::CardEntry};
+use io_webdav::{
+    rfc4918::{GETETAG, ResponseEntry, send::SendError},
+    rfc6352::{
+        addressbook::{ADDRESS_DATA, Addressbook as WireAddressbook},
+        card::{CardEntry, CardRef as WireCardRef, join_path},
+    },
+};
 
-use crate::{addressbook::Addressbook, card::Card};
+use crate::{
+    addressbook::Addressbook,
+    capabilities::{AddressbookField, Capabilities},
+    card::{Card, CardFilter, CardRef, Collation, FilterTest, MatchType},
+    error::ErrorKind,
+};
 
 #[cfg(feature = "client")]
 impl From<crate::webdav::client::WebdavClient> for crate::client::AddressbookClientStd {
@@ -25,8 +39,10 @@ impl From<crate::webdav::client::WebdavClient> for crate::client::AddressbookCli
 
 /// Maps a WebDAV [`WireAddressbook`] to a shared [`Addressbook`].
 ///
-/// The wire `display_name` falls back to the id when absent; `ctag`
-/// stays `None` until WebDAV CTag discovery lands.
+/// The wire `display_name` falls back to the id when absent. `ctag`
+/// comes from `CS:getctag`, falling back to `DAV:sync-token` for
+/// servers that dropped the CalendarServer extension: both change
+/// whenever the collection content does.
 pub(crate) fn addressbook_from_wire(wire: WireAddressbook) -> Addressbook {
     let id = wire.id;
     let name = wire.display_name.clone().unwrap_or_else(|| id.clone());
@@ -36,7 +52,8 @@ pub(crate) fn addressbook_from_wire(wire: WireAddressbook) -> Addressbook {
         name,
         description: wire.description,
         color: wire.color,
-        ctag: None,
+        ctag: wire.ctag.or_else(|| wire.sync_token.clone()),
+        sync_token: wire.sync_token,
     }
 }
 
@@ -50,6 +67,193 @@ pub(crate) fn card_from_entry(addressbook_id: &str, entry: CardEntry) -> Card {
     }
 }
 
+/// Maps a multistatus [`ResponseEntry`] carrying `DAV:getetag` and
+/// `CR:address-data` to a shared [`Card`], the same way io-webdav maps
+/// its own listings: the id is the resource name without `.vcf`, and
+/// entries without address data (such as the collection itself) are
+/// skipped.
+pub(crate) fn card_from_response(addressbook_id: &str, entry: &ResponseEntry) -> Option<Card> {
+    if entry.href.ends_with('/') {
+        return None;
+    }
+
+    let id = card_id_from_href(&entry.href);
+    if id.is_empty() {
+        return None;
+    }
+
+    Some(Card {
+        id: id.to_string(),
+        addressbook_id: addressbook_id.to_string(),
+        etag: entry
+            .text(GETETAG)
+            .map(|etag| etag.trim_matches('"').to_string()),
+        contents: entry.text(ADDRESS_DATA)?.as_bytes().to_vec(),
+    })
+}
+
+/// Maps a WebDAV [`WireCardRef`] listed in the collection at
+/// `addressbook_path` to a shared [`CardRef`].
+pub(crate) fn card_ref_from_wire(addressbook_path: &str, wire: WireCardRef) -> CardRef {
+    CardRef {
+        href: Some(join_path(addressbook_path, &wire.uri)),
+        id: wire.id,
+        etag: wire.etag,
+    }
+}
+
+/// Returns the resource name of card `card_id`, the `.vcf` suffixed
+/// name io-webdav gives the cards it creates.
+pub(crate) fn card_uri(card_id: &str) -> String {
+    format!("{card_id}.vcf")
+}
+
+/// Returns the card id of a member `href`: its last path segment,
+/// without `.vcf`.
+pub(crate) fn card_id_from_href(href: &str) -> &str {
+    let uri = href.rsplit('/').next().unwrap_or_default();
+    uri.strip_suffix(".vcf").unwrap_or(uri)
+}
+
+/// Derives [`Capabilities`] from the `DAV` and `Allow` headers of the
+/// `OPTIONS` response on the home-set and from its addressbooks.
+///
+/// Per-addressbook facts are intersected: a capability is reported
+/// only when every addressbook has it. The rest is the RFC 6352
+/// baseline every CardDAV server must meet: addressbook-query and
+/// addressbook-multiget REPORTs (§8) and vCard 3.0 (§6.2.2). Metadata
+/// is writable unless `Allow` omits `PROPPATCH`. Like in
+/// [`addressbook_from_wire`], a `DAV:sync-token` stands in for a
+/// missing `CS:getctag`.
+pub(crate) fn capabilities_from_wire(
+    dav: &str,
+    allow: &str,
+    addressbooks: &[WireAddressbook],
+) -> Capabilities {
+    let has_class = |class: &str| dav.split(',').any(|c| c.trim() == class);
+    let allows = |method: &str| {
+        allow.trim().is_empty()
+            || allow
+                .split(',')
+                .any(|m| m.trim().eq_ignore_ascii_case(method))
+    };
+
+    Capabilities {
+        etag: has_class("1") || has_class("addressbook"),
+        ctag: !addressbooks.is_empty()
+            && addressbooks
+                .iter()
+                .all(|a| a.ctag.is_some() || a.sync_token.is_some()),
+        sync_token: !addressbooks.is_empty() && addressbooks.iter().all(|a| a.sync_token.is_some()),
+        query: true,
+        multiget: true,
+        vcard_versions: vec!["3.0".to_string()],
+        max_resource_size: None,
+        writable_metadata: if allows("PROPPATCH") {
+            AddressbookField::ALL.to_vec()
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+/// Extracts the HTTP status code carried by a [`SendError`], when the
+/// failure is an unexpected server response rather than a transport
+/// or parsing error.
+pub(crate) fn send_error_status(err: &SendError) -> Option<u16> {
+    match err {
+        SendError::HttpStatus(status, _) => Some(*status),
+        _ => None,
+    }
+}
+
+/// Classifies a [`SendError`]: by HTTP status for unexpected server
+/// responses, as a transport failure otherwise.
+pub(crate) fn send_error_kind(err: &SendError) -> ErrorKind {
+    match send_error_status(err) {
+        Some(status) => ErrorKind::from_status(status),
+        None => ErrorKind::Transport,
+    }
+}
+
+/// Renders `filter` as an RFC 6352 §10.5 `filter` element, using the
+/// `C` prefix for the `urn:ietf:params:xml:ns:carddav` namespace.
+pub(crate) fn filter_to_xml(filter: &CardFilter) -> String {
+    let mut xml = String::new();
+
+    let _ = write!(xml, r#"<C:filter test="{}">"#, test_to_xml(filter.test));
+
+    for prop in &filter.props {
+        let _ = write!(
+            xml,
+            r#"<C:prop-filter name="{}" test="{}">"#,
+            escape_xml(&prop.name),
+            test_to_xml(prop.test)
+        );
+
+        // NOTE: RFC 6352 §10.5.1 makes `is-not-defined` exclusive
+        // with `text-match`: a property cannot both be missing and
+        // match some text, so the latter are dropped.
+        if prop.is_not_defined {
+            xml.push_str("<C:is-not-defined/></C:prop-filter>");
+            continue;
+        }
+
+        for m in &prop.text_matches {
+            let collation = match m.collation {
+                Collation::UnicodeCasemap => "i;unicode-casemap",
+                Collation::AsciiCasemap => "i;ascii-casemap",
+                Collation::Octet => "i;octet",
+            };
+
+            let match_type = match m.match_type {
+                MatchType::Equals => "equals",
+                MatchType::Contains => "contains",
+                MatchType::StartsWith => "starts-with",
+                MatchType::EndsWith => "ends-with",
+            };
+
+            let negate = if m.negate { "yes" } else { "no" };
+
+            let _ = write!(
+                xml,
+                r#"<C:text-match collation="{collation}" match-type="{match_type}" negate-condition="{negate}">{}</C:text-match>"#,
+                escape_xml(&m.text)
+            );
+        }
+
+        xml.push_str("</C:prop-filter>");
+    }
+
+    xml.push_str("</C:filter>");
+    xml
+}
+
+fn test_to_xml(test: FilterTest) -> &'static str {
+    match test {
+        FilterTest::AnyOf => "anyof",
+        FilterTest::AllOf => "allof",
+    }
+}
+
+/// Escapes the XML special characters of text and attribute values.
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
 /// 1-indexed pagination on an in-memory list. `page_size = None`
 /// returns the full slice; `page_size = 0` or a page past the end
 /// returns an empty vector.
@@ -91,6 +295,8 @@ pub(crate) fn wire_from_name(
         display_name,
         description: description.map(str::to_string),
         color: color.map(str::to_string),
+        ctag: None,
+        sync_token: None,
     }
 }
 
@@ -102,32 +308,3 @@ pub(crate) fn validate_id(id: &str) -> Option<String> {
     }
     Some(id.to_string())
 }
-
-/// Generates a fresh CardDAV card id from the system entropy source.
-///
-/// CardDAV requires the caller to supply the resource name; the vCard
-/// UID parsing path is gated behind the optional parser feature
-/// upstream, so on the bare client API we always synthesize the id.
-pub(crate) fn fresh_card_id() -> Result<String, getrandom::Error> {
-    let mut bytes = [0u8; 16];
-    getrandom::fill(&mut bytes)?;
-
-    // NOTE: RFC 4122 4.4 stamps version 4 and variant 10xx.
-    bytes[6] = (bytes[6] & 0x0f) | 0x40;
-    bytes[8] = (bytes[8] & 0x3f) | 0x80;
-
-    const HEX: &[u8; 16] = b"0123456789abcdef";
-    let mut out = [0u8; 36];
-    let mut cursor = 0;
-    for (i, byte) in bytes.iter().enumerate() {
-        if matches!(i, 4 | 6 | 8 | 10) {
-            out[cursor] = b'-';
-            cursor += 1;
-        }
-        out[cursor] = HEX[(byte >> 4) as usize];
-        out[cursor + 1] = HEX[(byte & 0x0f) as usize];
-        cursor += 2;
-    }
-
-    Ok(String::from_utf8(out.to_vec()).expect("ASCII hex is always valid UTF-8"))
-}
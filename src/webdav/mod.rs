@@ -1,5 +1,7 @@
-//! CardDAV backend: the std client plus its wire conversions.
+//! CardDAV backend: the std and tokio clients plus its wire conversions.
 
 #[cfg(feature = "client")]
 pub mod client;
+#[cfg(feature = "tokio")]
+pub mod client_async;
 pub mod convert;